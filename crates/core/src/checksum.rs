@@ -0,0 +1,47 @@
+//! Content hashes used to verify parquet uploads end to end
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+/// Header the daemon sends a file's SHA-256 in when uploading, so the oracle can reject a
+/// truncated or corrupted upload
+pub const CONTENT_SHA256_HEADER: &str = "x-content-sha256";
+
+/// Lowercase hex SHA-256 of `bytes`
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Lowercase hex SHA-256 of the file at `path`, read in chunks rather than all at once
+pub fn sha256_file(path: impl AsRef<Path>) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARQUET_BYTES_SHA256: &str =
+        "e67960879830f85c2ee10e69e9893e8d7b54cd435829e92a5c2f37ca131018de";
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(sha256_hex(b"parquet bytes"), PARQUET_BYTES_SHA256);
+    }
+
+    #[test]
+    fn test_sha256_file_matches_bytes() {
+        let path =
+            std::env::temp_dir().join(format!("core_checksum_{}.parquet", std::process::id()));
+        std::fs::write(&path, b"parquet bytes").unwrap();
+
+        assert_eq!(sha256_file(&path).unwrap(), PARQUET_BYTES_SHA256);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
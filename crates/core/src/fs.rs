@@ -1,7 +1,9 @@
 //! Filesystem utilities
 
-use std::fs;
-use std::path::Path;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use log::{error, info};
 
@@ -49,6 +51,128 @@ pub fn is_directory(path: &str) -> bool {
     Path::new(path).is_dir()
 }
 
+/// Check that a directory exists, or can be created, and that files can be written into it
+///
+/// Writes and removes a probe file instead of reading permission bits, so read-only mounts,
+/// ACLs and running as root are all accounted for.
+pub fn check_writable_dir(path: &str) -> io::Result<()> {
+    let dir = Path::new(path);
+    if dir.exists() && !dir.is_dir() {
+        return Err(io::Error::other("exists but is not a directory"));
+    }
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".write_check_{}", std::process::id()));
+    File::create(&probe)?;
+    fs::remove_file(&probe)
+}
+
+/// How long `FileLock::lock` waits between attempts when falling back to a lock marker
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Exclusive advisory lock held on a file
+///
+/// Uses the OS advisory lock (`flock` on unix, `LockFileEx` on windows), which is released
+/// when this is dropped or by the OS if the process dies while holding it. Platforms
+/// without advisory locks fall back to a `<file>.lck` marker created exclusively next to
+/// the file; that marker is removed on drop but is left behind if the process crashes.
+///
+/// Only processes that also take the lock are kept out, the file itself can still be read
+/// and written by anyone.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+    marker: Option<PathBuf>,
+}
+
+impl FileLock {
+    /// Try to take the lock without blocking, creating the file if needed
+    ///
+    /// Returns `None` when another handle, in this or any other process, holds the lock.
+    pub fn try_lock(path: impl AsRef<Path>) -> io::Result<Option<Self>> {
+        let path = path.as_ref();
+        let file = open_lock_file(path)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self::held(file, path, None))),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) if e.kind() == io::ErrorKind::Unsupported => {
+                Self::try_lock_marker(file, path)
+            }
+            Err(TryLockError::Error(e)) => Err(e),
+        }
+    }
+
+    /// Take the lock, blocking until whoever holds it releases it
+    pub fn lock(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = open_lock_file(path)?;
+        match file.lock() {
+            Ok(()) => Ok(Self::held(file, path, None)),
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => loop {
+                if let Some(lock) = Self::try_lock_marker(file.try_clone()?, path)? {
+                    return Ok(lock);
+                }
+                std::thread::sleep(LOCK_RETRY_INTERVAL);
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The locked file, for recording state alongside the lock
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    fn held(file: File, path: &Path, marker: Option<PathBuf>) -> Self {
+        Self {
+            file,
+            path: path.to_path_buf(),
+            marker,
+        }
+    }
+
+    /// Fallback for platforms without advisory locks, whoever creates the marker holds the lock
+    fn try_lock_marker(file: File, path: &Path) -> io::Result<Option<Self>> {
+        let mut marker = path.as_os_str().to_owned();
+        marker.push(".lck");
+        let marker = PathBuf::from(marker);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&marker)
+        {
+            Ok(_) => Ok(Some(Self::held(file, path, Some(marker)))),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let released = match &self.marker {
+            Some(marker) => fs::remove_file(marker),
+            None => self.file.unlock(),
+        };
+        if let Err(e) = released {
+            error!("Failed to unlock {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+fn open_lock_file(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +190,100 @@ mod tests {
     fn test_is_directory() {
         assert!(is_directory("."));
     }
+
+    #[test]
+    fn test_check_writable_dir() {
+        let dir = lock_dir("writable_dir");
+        let missing = dir.join("nested/data");
+        check_writable_dir(missing.to_str().unwrap()).unwrap();
+        assert!(missing.is_dir());
+        // The probe is cleaned up
+        assert_eq!(fs::read_dir(&missing).unwrap().count(), 0);
+
+        let file = dir.join("data.txt");
+        fs::write(&file, "not a dir").unwrap();
+        let err = check_writable_dir(file.to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("not a directory"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn lock_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("core_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_lock_acquires_exclusively() {
+        let dir = lock_dir("file_lock_exclusive");
+        let path = dir.join("writer.lock");
+
+        let lock = FileLock::try_lock(&path).unwrap().unwrap();
+        assert_eq!(lock.path(), path);
+        assert!(path.exists());
+
+        drop(lock);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_lock_contention_fails_second_lock() {
+        let dir = lock_dir("file_lock_contention");
+        let path = dir.join("writer.lock");
+
+        let _lock = FileLock::try_lock(&path).unwrap().unwrap();
+        // A second handle contends the same way another process would
+        assert!(FileLock::try_lock(&path).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_lock_released_on_drop() {
+        let dir = lock_dir("file_lock_release");
+        let path = dir.join("writer.lock");
+
+        let lock = FileLock::try_lock(&path).unwrap().unwrap();
+        let waiter = {
+            let path = path.clone();
+            std::thread::spawn(move || FileLock::lock(&path).map(|_| ()))
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!waiter.is_finished());
+
+        drop(lock);
+        waiter.join().unwrap().unwrap();
+        assert!(FileLock::try_lock(&path).unwrap().is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_lock_marker_fallback() {
+        let dir = lock_dir("file_lock_marker");
+        let path = dir.join("writer.lock");
+        let marker = dir.join("writer.lock.lck");
+
+        let lock = FileLock::try_lock_marker(open_lock_file(&path).unwrap(), &path)
+            .unwrap()
+            .unwrap();
+        assert!(marker.exists());
+        assert!(
+            FileLock::try_lock_marker(open_lock_file(&path).unwrap(), &path)
+                .unwrap()
+                .is_none()
+        );
+
+        drop(lock);
+        assert!(!marker.exists());
+        assert!(
+            FileLock::try_lock_marker(open_lock_file(&path).unwrap(), &path)
+                .unwrap()
+                .is_some()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
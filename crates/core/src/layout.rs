@@ -0,0 +1,125 @@
+//! Parquet file layout under a data directory
+//!
+//! The daemon writes and the oracle discovers files with the same layout:
+//! - `date-sub` (default): `data_dir/<YYYY-MM-DD>/<type>_<timestamp>.parquet`
+//! - `hive`: `data_dir/type=<type>/date=<YYYY-MM-DD>/<type>_<timestamp>.parquet`
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+/// Header the daemon sends its layout in when uploading, so the oracle can reject a mismatch
+pub const DATA_LAYOUT_HEADER: &str = "x-data-layout";
+
+/// How parquet files are arranged under a data directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DataLayout {
+    /// One folder per UTC date holding every file type
+    #[default]
+    DateSub,
+    /// Hive style partitions, `type=<type>/date=<YYYY-MM-DD>`
+    Hive,
+}
+
+impl DataLayout {
+    /// Folder holding `file_type` files generated on `date`
+    pub fn folder(&self, data_dir: &str, file_type: &str, date: impl fmt::Display) -> String {
+        match self {
+            DataLayout::DateSub => format!("{}/{}", data_dir, date),
+            DataLayout::Hive => format!("{}/type={}/date={}", data_dir, file_type, date),
+        }
+    }
+
+    /// Path of `file_name`, the file type is the part of the name before the first `_`
+    pub fn file_path(&self, data_dir: &str, file_name: &str, date: impl fmt::Display) -> String {
+        let file_type = file_name.split('_').next().unwrap_or_default();
+        format!("{}/{}", self.folder(data_dir, file_type, date), file_name)
+    }
+
+    /// Date part of a date folder name, None when the folder isn't one for this layout
+    pub fn date_folder<'a>(&self, folder_name: &'a str) -> Option<&'a str> {
+        match self {
+            DataLayout::DateSub => Some(folder_name),
+            DataLayout::Hive => folder_name.strip_prefix("date="),
+        }
+    }
+
+    /// File type of a type folder name, None when the layout doesn't partition by type
+    pub fn type_folder<'a>(&self, folder_name: &'a str) -> Option<&'a str> {
+        match self {
+            DataLayout::DateSub => None,
+            DataLayout::Hive => folder_name.strip_prefix("type="),
+        }
+    }
+}
+
+impl FromStr for DataLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "date-sub" => Ok(DataLayout::DateSub),
+            "hive" => Ok(DataLayout::Hive),
+            _ => Err(anyhow!(
+                "unknown layout '{}', expected date-sub or hive",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DataLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataLayout::DateSub => write!(f, "date-sub"),
+            DataLayout::Hive => write!(f, "hive"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_sub_paths() {
+        let layout = DataLayout::DateSub;
+        assert_eq!(
+            layout.file_path(
+                "./data",
+                "forecasts_2024-01-15T04:36:33Z.parquet",
+                "2024-01-15"
+            ),
+            "./data/2024-01-15/forecasts_2024-01-15T04:36:33Z.parquet"
+        );
+        assert_eq!(layout.date_folder("2024-01-15"), Some("2024-01-15"));
+        assert_eq!(layout.type_folder("2024-01-15"), None);
+    }
+
+    #[test]
+    fn test_hive_paths() {
+        let layout = DataLayout::Hive;
+        assert_eq!(
+            layout.file_path(
+                "./data",
+                "observations_2024-01-15T04:36:33Z.parquet",
+                "2024-01-15"
+            ),
+            "./data/type=observations/date=2024-01-15/observations_2024-01-15T04:36:33Z.parquet"
+        );
+        assert_eq!(layout.date_folder("date=2024-01-15"), Some("2024-01-15"));
+        assert_eq!(layout.date_folder("2024-01-15"), None);
+        assert_eq!(layout.type_folder("type=forecasts"), Some("forecasts"));
+    }
+
+    #[test]
+    fn test_layout_round_trips_through_strings() {
+        for layout in [DataLayout::DateSub, DataLayout::Hive] {
+            assert_eq!(layout.to_string().parse::<DataLayout>().unwrap(), layout);
+        }
+        assert!("flat".parse::<DataLayout>().is_err());
+    }
+}
@@ -3,15 +3,24 @@
 //! Shared utilities for the oracle and daemon services:
 //! - Configuration loading (XDG-compliant)
 //! - File system utilities
+//! - Upload checksums
+//! - Parquet data directory layout
 //! - Common types
 
+mod checksum;
 mod config;
 pub mod fs;
+mod layout;
 
+pub use checksum::{sha256_file, sha256_hex, CONTENT_SHA256_HEADER};
 pub use config::{
-    find_config_file, get_xdg_cache_dir, get_xdg_data_dir, load_config, ConfigSource,
+    find_config_file, get_xdg_cache_dir, get_xdg_data_dir, load_config, ConfigProblems,
+    ConfigSource, EffectiveConfig, EffectiveValue, ValueSource,
 };
-pub use fs::{create_dir_all, ensure_dir_exists, is_directory, path_exists};
+pub use fs::{
+    check_writable_dir, create_dir_all, ensure_dir_exists, is_directory, path_exists, FileLock,
+};
+pub use layout::{DataLayout, DATA_LAYOUT_HEADER};
 
 /// Application name used for XDG paths
 pub const APP_NAME: &str = "noaa-oracle";
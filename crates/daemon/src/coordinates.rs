@@ -1,12 +1,17 @@
 use std::{
     collections::{HashMap, HashSet},
-    fmt,
+    fmt, fs,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use crate::{Point, XmlFetcher};
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use serde::{Deserialize, Serialize};
+use slog::{debug, info, warn, Logger};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct WeatherStation {
@@ -104,6 +109,33 @@ impl CityWeather {
         self.city_data
             .retain(|_, v| !(v.latitude == point.latitude && v.longitude == point.longitude));
     }
+    /// Keep a single station per rounded (2 decimal) coordinate. Forecasts are matched back to
+    /// stations by rounded coordinate, so collisions would mix data between stations.
+    /// The station with the lowest station_id wins, the dropped stations are returned.
+    pub fn dedup_coordinates(&mut self) -> Vec<WeatherStation> {
+        let mut winners: HashMap<String, String> = HashMap::new();
+        for (key, station) in &self.city_data {
+            let coordinate = format!("{},{}", station.get_latitude(), station.get_longitude());
+            match winners.get(&coordinate) {
+                Some(current) if self.city_data[current].station_id <= station.station_id => {}
+                _ => {
+                    winners.insert(coordinate, key.clone());
+                }
+            }
+        }
+
+        let keep: HashSet<String> = winners.into_values().collect();
+        let mut dropped: Vec<WeatherStation> = self
+            .city_data
+            .iter()
+            .filter(|(key, _)| !keep.contains(*key))
+            .map(|(_, station)| station.clone())
+            .collect();
+        self.city_data.retain(|key, _| keep.contains(key));
+        dropped.sort_by(|a, b| a.station_id.cmp(&b.station_id));
+        dropped
+    }
+
     pub fn get_station_ids(&self) -> HashSet<String> {
         let mut station_ids: HashSet<String> = HashSet::new();
         self.city_data.iter().for_each(|(_city_name, city_data)| {
@@ -150,7 +182,10 @@ static STATE_ABBERVIATIONS: &[&str] = &[
     "WV", "WI", "WY",
 ];
 
-pub async fn get_coordinates(fetcher: Arc<XmlFetcher>) -> Result<CityWeather, Error> {
+pub async fn get_coordinates(
+    fetcher: Arc<XmlFetcher>,
+    logger: &Logger,
+) -> Result<CityWeather, Error> {
     let mut city_data: HashMap<String, WeatherStation> = HashMap::new();
     // Broken @ NOAA: https://forecast.weather.gov/xml/current_obs/index.xml
 
@@ -179,7 +214,135 @@ pub async fn get_coordinates(fetcher: Arc<XmlFetcher>) -> Result<CityWeather, Er
         }
     }
 
-    Ok(CityWeather { city_data })
+    let mut city_weather = CityWeather { city_data };
+    let dropped = city_weather.dedup_coordinates();
+    for station in &dropped {
+        warn!(
+            logger,
+            "dropping station {} at {},{}, another station shares its rounded coordinates",
+            station.station_id,
+            station.get_latitude(),
+            station.get_longitude()
+        );
+    }
+    if !dropped.is_empty() {
+        info!(
+            logger,
+            "dropped {} stations with duplicate coordinates",
+            dropped.len()
+        );
+    }
+
+    Ok(city_weather)
+}
+
+/// File under the data dir the last fetched station list is kept in
+pub const COORDINATE_CACHE_FILE: &str = "coordinates.json";
+
+/// Default hours a fetched station list is reused before it is fetched again
+pub const DEFAULT_COORDINATE_CACHE_TTL_HOURS: u64 = 24;
+
+#[derive(Serialize, Deserialize)]
+struct CachedCoordinates {
+    /// Seconds since the unix epoch
+    fetched_at: u64,
+    coordinates: CityWeather,
+}
+
+/// The last fetched station list, kept on disk so most cycles skip the download and a failed
+/// download can fall back to it
+pub struct CoordinateCache {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+impl CoordinateCache {
+    pub fn new(path: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            path: path.into(),
+            ttl,
+        }
+    }
+
+    /// The cached coordinates and when they were fetched, `None` when missing or unreadable
+    fn read(&self) -> Option<(SystemTime, CityWeather)> {
+        let raw = fs::read(&self.path).ok()?;
+        let cached: CachedCoordinates = serde_json::from_slice(&raw).ok()?;
+        Some((
+            UNIX_EPOCH + Duration::from_secs(cached.fetched_at),
+            cached.coordinates,
+        ))
+    }
+
+    /// Written to a temp file and renamed so a crash mid write never leaves a torn cache
+    fn write(&self, coordinates: &CityWeather, fetched_at: SystemTime) -> Result<(), Error> {
+        if let Some(folder) = self.path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+        let cached = CachedCoordinates {
+            fetched_at: fetched_at.duration_since(UNIX_EPOCH)?.as_secs(),
+            coordinates: coordinates.clone(),
+        };
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_vec(&cached)?)?;
+        fs::rename(&temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Cached coordinates while they're younger than the cache TTL, otherwise `fetch`es and caches
+/// them. A failed fetch falls back to the cached copy however old it is, so one bad download
+/// doesn't cost a whole cycle. `refresh` skips the cache unless the fetch fails.
+pub async fn cached_coordinates<F, Fut>(
+    cache: &CoordinateCache,
+    refresh: bool,
+    now: SystemTime,
+    fetch: F,
+    logger: &Logger,
+) -> Result<CityWeather, Error>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<CityWeather, Error>>,
+{
+    let cached = cache.read();
+    if let Some((fetched_at, coordinates)) = &cached {
+        let age = now.duration_since(*fetched_at).unwrap_or_default();
+        if !refresh && age < cache.ttl {
+            debug!(
+                logger,
+                "using coordinates cached {} seconds ago",
+                age.as_secs()
+            );
+            return Ok(coordinates.clone());
+        }
+    }
+
+    match fetch().await {
+        Ok(coordinates) => {
+            // The fetch already succeeded, a cache we can't write only costs the next cycle a download
+            if let Err(e) = cache.write(&coordinates, now) {
+                warn!(
+                    logger,
+                    "failed to cache coordinates at {}: {}",
+                    cache.path.display(),
+                    e
+                );
+            }
+            Ok(coordinates)
+        }
+        Err(e) => match cached {
+            Some((fetched_at, coordinates)) => {
+                warn!(
+                    logger,
+                    "failed to fetch coordinates, using copy cached {} seconds ago: {}",
+                    now.duration_since(fetched_at).unwrap_or_default().as_secs(),
+                    e
+                );
+                Ok(coordinates)
+            }
+            None => Err(e),
+        },
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -282,3 +445,277 @@ pub struct Request {
     #[serde(rename = "type")]
     request_type: String,
 }
+
+/// A row of the offline stations reference file
+#[derive(Debug, Clone, Deserialize)]
+pub struct StationMetadata {
+    pub station_id: String,
+    #[serde(default)]
+    pub station_name: String,
+    #[serde(default)]
+    pub state: String,
+    #[serde(default)]
+    pub iata_id: String,
+    #[serde(default)]
+    pub elevation_m: Option<f64>,
+}
+
+/// Offline station metadata keyed by station_id, used to backfill what the station list omits
+#[derive(Debug, Default)]
+pub struct StationsTable {
+    stations: HashMap<String, StationMetadata>,
+}
+
+impl StationsTable {
+    /// Reads a CSV with a `station_id,station_name,state,iata_id,elevation_m` header
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|e| anyhow!("error opening stations file {}: {}", path.display(), e))?;
+        Self::from_reader(reader)
+            .map_err(|e| anyhow!("error reading stations file {}: {}", path.display(), e))
+    }
+
+    fn from_reader<R: io::Read>(mut reader: csv::Reader<R>) -> Result<Self, csv::Error> {
+        let stations = reader
+            .deserialize::<StationMetadata>()
+            .map(|row| row.map(|station| (station.station_id.clone(), station)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { stations })
+    }
+
+    pub fn len(&self) -> usize {
+        self.stations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stations.is_empty()
+    }
+
+    /// Fills empty metadata fields from the table, returns how many stations gained anything.
+    /// Fields the station list already has are never overwritten.
+    pub fn enrich(&self, city_weather: &mut CityWeather) -> usize {
+        let mut enriched = 0;
+        for station in city_weather.city_data.values_mut() {
+            let Some(fallback) = self.stations.get(&station.station_id) else {
+                continue;
+            };
+            let mut filled = false;
+            for (field, value) in [
+                (&mut station.station_name, &fallback.station_name),
+                (&mut station.state, &fallback.state),
+                (&mut station.iata_id, &fallback.iata_id),
+            ] {
+                if field.is_empty() && !value.is_empty() {
+                    field.clone_from(value);
+                    filled = true;
+                }
+            }
+            if station.elevation_m.is_none() && fallback.elevation_m.is_some() {
+                station.elevation_m = fallback.elevation_m;
+                filled = true;
+            }
+            if filled {
+                enriched += 1;
+            }
+        }
+        enriched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    fn station(station_id: &str, latitude: &str, longitude: &str) -> WeatherStation {
+        WeatherStation {
+            station_id: station_id.to_string(),
+            station_name: format!("{} station", station_id),
+            state: String::from("NY"),
+            iata_id: String::new(),
+            elevation_m: None,
+            latitude: latitude.to_string(),
+            longitude: longitude.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_dedup_coordinates_keeps_lowest_station_id() {
+        let mut city_weather = CityWeather {
+            city_data: HashMap::from([
+                (String::from("KJFK"), station("KJFK", "40.639", "-73.779")),
+                (String::from("KJRB"), station("KJRB", "40.641", "-73.781")),
+                (String::from("KLGA"), station("KLGA", "40.779", "-73.880")),
+            ]),
+        };
+
+        let dropped = city_weather.dedup_coordinates();
+
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].station_id, "KJRB");
+        assert_eq!(city_weather.city_data.len(), 2);
+        assert!(city_weather.city_data.contains_key("KJFK"));
+        assert!(city_weather.city_data.contains_key("KLGA"));
+        assert_eq!(city_weather.get_coordinates().len(), 2);
+    }
+
+    fn cache(name: &str) -> CoordinateCache {
+        let dir = std::env::temp_dir().join(format!(
+            "daemon_coordinates_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        CoordinateCache::new(dir.join(COORDINATE_CACHE_FILE), HOUR * 24)
+    }
+
+    fn city_weather(station_ids: &[&str]) -> CityWeather {
+        CityWeather {
+            city_data: station_ids
+                .iter()
+                .enumerate()
+                .map(|(i, id)| {
+                    let latitude = format!("40.{}", i);
+                    (id.to_string(), station(id, &latitude, "-73.78"))
+                })
+                .collect(),
+        }
+    }
+
+    async fn failed_fetch() -> Result<CityWeather, Error> {
+        Err(anyhow!("NOAA is down"))
+    }
+
+    #[tokio::test]
+    async fn test_cached_coordinates_skip_fetch_until_ttl() {
+        let logger = Logger::root(slog::Discard, o!());
+        let cache = cache("hit");
+        let now = SystemTime::now();
+
+        let fetched = cached_coordinates(
+            &cache,
+            false,
+            now,
+            || async { Ok(city_weather(&["KJFK"])) },
+            &logger,
+        )
+        .await
+        .unwrap();
+        assert!(fetched.city_data.contains_key("KJFK"));
+
+        let cached = cached_coordinates(
+            &cache,
+            false,
+            now + HOUR * 23,
+            || async { panic!("fresh cache should not be fetched") },
+            &logger,
+        )
+        .await
+        .unwrap();
+        assert_eq!(cached.get_station_ids(), fetched.get_station_ids());
+
+        // the flag skips a fresh cache
+        let refreshed = cached_coordinates(
+            &cache,
+            true,
+            now + HOUR,
+            || async { Ok(city_weather(&["KLGA"])) },
+            &logger,
+        )
+        .await
+        .unwrap();
+        assert!(refreshed.city_data.contains_key("KLGA"));
+        fs::remove_dir_all(cache.path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cached_coordinates_refetch_after_ttl() {
+        let logger = Logger::root(slog::Discard, o!());
+        let cache = cache("expiry");
+        let now = SystemTime::now();
+        cache.write(&city_weather(&["KJFK"]), now).unwrap();
+
+        let later = now + HOUR * 25;
+        let coordinates = cached_coordinates(
+            &cache,
+            false,
+            later,
+            || async { Ok(city_weather(&["KLGA", "KJRB"])) },
+            &logger,
+        )
+        .await
+        .unwrap();
+        assert_eq!(coordinates.city_data.len(), 2);
+
+        let (fetched_at, cached) = cache.read().unwrap();
+        assert_eq!(
+            fetched_at.duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            later.duration_since(UNIX_EPOCH).unwrap().as_secs()
+        );
+        assert_eq!(cached.get_station_ids(), coordinates.get_station_ids());
+        fs::remove_dir_all(cache.path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cached_coordinates_fall_back_when_fetch_fails() {
+        let logger = Logger::root(slog::Discard, o!());
+        let cache = cache("fallback");
+        let now = SystemTime::now();
+
+        let result = cached_coordinates(&cache, false, now, failed_fetch, &logger).await;
+        assert!(result.is_err());
+
+        cache.write(&city_weather(&["KJFK"]), now).unwrap();
+        let stale = cached_coordinates(&cache, false, now + HOUR * 72, failed_fetch, &logger)
+            .await
+            .unwrap();
+        assert!(stale.city_data.contains_key("KJFK"));
+        let refreshed = cached_coordinates(&cache, true, now, failed_fetch, &logger)
+            .await
+            .unwrap();
+        assert!(refreshed.city_data.contains_key("KJFK"));
+        fs::remove_dir_all(cache.path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_stations_table_backfills_missing_metadata() {
+        let csv = "station_id,station_name,state,iata_id,elevation_m\n\
+                   KJFK,John F Kennedy Intl,NY,JFK,4.0\n\
+                   KLGA,LaGuardia,NY,LGA,\n";
+        let table = StationsTable::from_reader(csv::Reader::from_reader(csv.as_bytes())).unwrap();
+        assert_eq!(table.len(), 2);
+
+        // The station feed had coordinates for KJFK but none of its metadata
+        let mut bare = station("KJFK", "40.639", "-73.779");
+        bare.station_name = String::new();
+        bare.state = String::new();
+        let mut city_weather = CityWeather {
+            city_data: HashMap::from([
+                (String::from("KJFK"), bare),
+                (String::from("KLGA"), station("KLGA", "40.779", "-73.880")),
+                (String::from("KBOS"), station("KBOS", "42.361", "-71.010")),
+            ]),
+        };
+
+        assert_eq!(table.enrich(&mut city_weather), 2);
+        let kjfk = &city_weather.city_data["KJFK"];
+        assert_eq!(kjfk.station_name, "John F Kennedy Intl");
+        assert_eq!(kjfk.state, "NY");
+        assert_eq!(kjfk.iata_id, "JFK");
+        assert_eq!(kjfk.elevation_m, Some(4.0));
+        // Metadata from the feed is kept, only the empty iata_id is filled
+        let klga = &city_weather.city_data["KLGA"];
+        assert_eq!(klga.station_name, "KLGA station");
+        assert_eq!(klga.iata_id, "LGA");
+        assert_eq!(klga.elevation_m, None);
+        assert_eq!(city_weather.city_data["KBOS"].iata_id, "");
+
+        // Nothing left to fill on a second pass
+        assert_eq!(table.enrich(&mut city_weather), 0);
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, Error};
+use noaa_oracle_core::FileLock;
+use time::OffsetDateTime;
+
+/// Folder under the data dir holding one lock file per fetch cycle
+pub const CYCLE_LOCK_DIR: &str = "locks";
+
+const CYCLE_COMPLETE: &[u8] = b"complete\n";
+
+/// Outcome of trying to claim a fetch cycle
+#[derive(Debug)]
+pub enum CycleClaim {
+    /// This instance owns the cycle until the lock is dropped
+    Acquired(CycleLock),
+    /// Another instance is processing the cycle right now
+    Held(PathBuf),
+    /// Another instance already finished the cycle
+    Completed(PathBuf),
+}
+
+/// Advisory lock on a single fetch cycle, shared by every daemon writing to the same data dir
+///
+/// Cycles are keyed by the start of the fetch interval they fall in, so with the default
+/// hourly interval each date/hour is processed once. A crashed cycle releases its lock
+/// without being marked complete, leaving it for another instance to retry.
+#[derive(Debug)]
+pub struct CycleLock {
+    lock: FileLock,
+}
+
+impl CycleLock {
+    pub fn try_acquire(
+        data_dir: &Path,
+        interval: Duration,
+        now: OffsetDateTime,
+    ) -> Result<CycleClaim, Error> {
+        let folder = data_dir.join(CYCLE_LOCK_DIR);
+        std::fs::create_dir_all(&folder)
+            .map_err(|e| anyhow!("error creating lock folder {}: {}", folder.display(), e))?;
+        let path = folder.join(cycle_lock_file(interval, now)?);
+
+        let Some(lock) = FileLock::try_lock(&path)
+            .map_err(|e| anyhow!("error locking {}: {}", path.display(), e))?
+        else {
+            return Ok(CycleClaim::Held(path));
+        };
+        let mut state = Vec::new();
+        lock.file().read_to_end(&mut state)?;
+        if state == CYCLE_COMPLETE {
+            return Ok(CycleClaim::Completed(path));
+        }
+        Ok(CycleClaim::Acquired(Self { lock }))
+    }
+
+    pub fn path(&self) -> &Path {
+        self.lock.path()
+    }
+
+    /// Records the cycle as done so later claims skip it, then releases the lock
+    pub fn complete(self) -> Result<(), Error> {
+        let mut file = self.lock.file();
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(CYCLE_COMPLETE)?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Deletes cycle lock files under `data_dir` last modified more than `retention` before `now`,
+/// one is left behind per fetch cycle and nothing else clears them. Returns how many were removed.
+pub fn prune_cycle_locks(
+    data_dir: &Path,
+    retention: Duration,
+    now: SystemTime,
+) -> Result<usize, Error> {
+    let cutoff = now
+        .checked_sub(retention)
+        .ok_or_else(|| anyhow!("retention reaches before the unix epoch"))?;
+    let folder = data_dir.join(CYCLE_LOCK_DIR);
+    if !folder.exists() {
+        return Ok(0);
+    }
+    let mut pruned = 0;
+    for entry in fs::read_dir(&folder)? {
+        let path = entry?.path();
+        let is_cycle_lock = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("cycle-") && name.ends_with(".lock"));
+        if !is_cycle_lock || fs::metadata(&path)?.modified()? >= cutoff {
+            continue;
+        }
+        fs::remove_file(&path)?;
+        pruned += 1;
+    }
+    Ok(pruned)
+}
+
+/// `cycle-<date>T<hour><minute>.lock` for the start of the interval holding `now`
+fn cycle_lock_file(interval: Duration, now: OffsetDateTime) -> Result<String, Error> {
+    let interval = i64::try_from(interval.as_secs())?;
+    if interval == 0 {
+        return Err(anyhow!("fetch interval must be at least one second"));
+    }
+    let timestamp = now.unix_timestamp();
+    let start = OffsetDateTime::from_unix_timestamp(timestamp - timestamp.rem_euclid(interval))?;
+    Ok(format!(
+        "cycle-{}T{:02}{:02}.lock",
+        start.date(),
+        start.hour(),
+        start.minute()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn temp_data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("daemon_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_cycle_lock_file_is_keyed_by_interval_start() {
+        assert_eq!(
+            cycle_lock_file(HOUR, datetime!(2024-03-05 14:59:59 UTC)).unwrap(),
+            "cycle-2024-03-05T1400.lock"
+        );
+        assert_eq!(
+            cycle_lock_file(Duration::from_secs(900), datetime!(2024-03-05 14:31:00 UTC)).unwrap(),
+            "cycle-2024-03-05T1430.lock"
+        );
+        assert!(cycle_lock_file(Duration::ZERO, datetime!(2024-03-05 14:31:00 UTC)).is_err());
+    }
+
+    #[test]
+    fn test_contending_instances_only_one_processes_cycle() {
+        let data_dir = temp_data_dir("cycle_lock_contention");
+        let now = datetime!(2024-03-05 14:10:00 UTC);
+
+        // Each claim opens its own handle, the same as separate daemon processes would
+        let CycleClaim::Acquired(winner) = CycleLock::try_acquire(&data_dir, HOUR, now).unwrap()
+        else {
+            panic!("first instance should own the cycle");
+        };
+        let later_in_hour = now + time::Duration::minutes(20);
+        assert!(matches!(
+            CycleLock::try_acquire(&data_dir, HOUR, later_in_hour).unwrap(),
+            CycleClaim::Held(path) if path == winner.path()
+        ));
+
+        winner.complete().unwrap();
+        assert!(matches!(
+            CycleLock::try_acquire(&data_dir, HOUR, later_in_hour).unwrap(),
+            CycleClaim::Completed(_)
+        ));
+        assert!(matches!(
+            CycleLock::try_acquire(&data_dir, HOUR, now + time::Duration::hours(1)).unwrap(),
+            CycleClaim::Acquired(_)
+        ));
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_only_removes_cycle_locks_past_retention() {
+        let data_dir = temp_data_dir("cycle_lock_prune");
+        let now = SystemTime::now();
+
+        let CycleClaim::Acquired(old) =
+            CycleLock::try_acquire(&data_dir, HOUR, datetime!(2024-03-05 14:10:00 UTC)).unwrap()
+        else {
+            panic!("old cycle should be free");
+        };
+        let old_path = old.path().to_path_buf();
+        old.complete().unwrap();
+        let CycleClaim::Acquired(current) =
+            CycleLock::try_acquire(&data_dir, HOUR, OffsetDateTime::now_utc()).unwrap()
+        else {
+            panic!("current cycle should be free");
+        };
+        let other = data_dir.join(CYCLE_LOCK_DIR).join("other.lock");
+        fs::write(&other, b"").unwrap();
+        for path in [&old_path, &other] {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(now - DAY * 10)
+                .unwrap();
+        }
+
+        assert_eq!(prune_cycle_locks(&data_dir, DAY * 7, now).unwrap(), 1);
+        assert!(!old_path.exists());
+        assert!(current.path().exists());
+        assert!(other.exists());
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_abandoned_cycle_can_be_retried() {
+        let data_dir = temp_data_dir("cycle_lock_abandoned");
+        let now = datetime!(2024-03-05 14:10:00 UTC);
+
+        // Dropping without completing is what a failed or crashed cycle leaves behind
+        let claim = CycleLock::try_acquire(&data_dir, HOUR, now).unwrap();
+        assert!(matches!(claim, CycleClaim::Acquired(_)));
+        drop(claim);
+
+        assert!(matches!(
+            CycleLock::try_acquire(&data_dir, HOUR, now).unwrap(),
+            CycleClaim::Acquired(_)
+        ));
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
@@ -3,30 +3,37 @@ use crate::Type::{
     ProbabilityOfPrecipitationWithin12Hours, Snow, SnowRatio, Sustained, Wind,
 };
 use crate::{
-    split_cityweather, CityWeather, DataReading, Dwml, Location, Units, WeatherStation, XmlFetcher,
+    split_cityweather, CityWeather, DataReading, Dwml, ForecastBatch, ForecastSource, Location,
+    RowGroupWriter, Units, WeatherStation, XmlFetcher,
 };
 use anyhow::{anyhow, Error};
+use async_trait::async_trait;
 use core::time::Duration as StdDuration;
 use parquet::basic::LogicalType;
 use parquet::file::properties::WriterProperties;
 use parquet::file::writer::SerializedFileWriter;
-use parquet::record::RecordWriter;
 use parquet::{
     basic::{Repetition, Type as PhysicalType},
     schema::types::Type,
 };
 use parquet_derive::ParquetRecordWriter;
+use reqwest::Url;
 use serde_xml_rs::from_str;
-use slog::{error, info, Logger};
+use slog::{error, info, warn, Logger};
 use std::fs::File;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::{collections::HashMap, ops::Add};
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    ops::Add,
+};
 use time::{
     format_description::well_known::Rfc3339, macros::format_description, Duration, OffsetDateTime,
     UtcOffset,
 };
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, Semaphore};
 use tokio::task::JoinSet;
 use tokio::time::sleep;
 /*
@@ -460,7 +467,7 @@ impl TryFrom<Dwml> for HashMap<String, Vec<WeatherForecast>> {
         }
 
         // Sort by start time to ensure consistent ordering
-        all_time_ranges.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+        all_time_ranges.sort_by_key(|a| a.start_time);
 
         let generated_at = get_generated_at(&raw_data);
 
@@ -913,10 +920,75 @@ fn get_interval_exact(current_data: &WeatherForecast, time_ranges: &[TimeRange])
     None
 }
 
+/// Forecasts from the graphical.weather.gov NDFD DWML service
+pub struct NdfdForecastSource {
+    base_url: Url,
+    fetcher: Arc<XmlFetcher>,
+    logger: Logger,
+}
+
+impl NdfdForecastSource {
+    pub fn new(base_url: Url, fetcher: Arc<XmlFetcher>, logger: Logger) -> Self {
+        NdfdForecastSource {
+            base_url,
+            fetcher,
+            logger,
+        }
+    }
+}
+
+#[async_trait]
+impl ForecastSource for NdfdForecastSource {
+    fn name(&self) -> &'static str {
+        "ndfd"
+    }
+
+    async fn fetch_forecasts(&self, batch: &CityWeather) -> Result<ForecastBatch, Error> {
+        let url = get_url(&self.base_url, batch);
+        info!(self.logger, "url: {}", url);
+        let xml = self.fetcher.fetch_xml(&url).await?;
+
+        // Check if the response is an error from the NOAA API
+        // Error responses start with "<error>" instead of "<dwml>"
+        if is_error_response(&xml) {
+            return Ok(ForecastBatch::Rejected);
+        }
+
+        let grouped_xml = group_parameter_elements(&xml);
+        let converted_xml: Dwml = match from_str(&grouped_xml) {
+            Ok(xml) => xml,
+            Err(err) => {
+                error!(
+                    self.logger,
+                    "error converting xml: {} \n raw string: {}", err, xml
+                );
+                Dwml::default()
+            }
+        };
+        if converted_xml == Dwml::default() {
+            info!(
+                self.logger,
+                "no current forecast xml found, skipping converting"
+            );
+            return Ok(ForecastBatch::Forecasts(HashMap::new()));
+        }
+        let weather_with_stations = add_station_ids(batch, converted_xml, &self.logger);
+        let current_forecast_data = match weather_with_stations.try_into() {
+            Ok(weather) => weather,
+            Err(err) => {
+                error!(self.logger, "error converting to Forecast: {}", err);
+
+                HashMap::new()
+            }
+        };
+        Ok(ForecastBatch::Forecasts(current_forecast_data))
+    }
+}
+
 pub struct ForecastRetry {
     pub tx: mpsc::Sender<Result<HashMap<String, Vec<WeatherForecast>>, Error>>,
     pub max_retries: usize,
-    pub fetcher: Arc<XmlFetcher>,
+    pub source: Arc<dyn ForecastSource>,
     pub logger: Logger,
 }
 
@@ -924,112 +996,126 @@ impl ForecastRetry {
     pub fn new(
         tx: mpsc::Sender<Result<HashMap<String, Vec<WeatherForecast>>, Error>>,
         max_retries: usize,
-        fetcher: Arc<XmlFetcher>,
+        source: Arc<dyn ForecastSource>,
         logger: Logger,
     ) -> Self {
         ForecastRetry {
             tx,
             max_retries,
-            fetcher,
+            source,
             logger,
         }
     }
 
-    pub async fn fetch_forecast_with_retry(
-        &self,
-        url: String,
-        city_weather: &CityWeather,
-    ) -> Result<(), Error> {
-        info!(self.logger, "url: {}", url);
-        loop {
-            match self.fetcher.fetch_xml(&url).await {
-                Ok(xml) => {
-                    // Check if the response is an error from the NOAA API
-                    // Error responses start with "<error>" instead of "<dwml>"
-                    if xml.trim_start().starts_with("<error>") {
-                        info!(
+    pub async fn fetch_forecast_with_retry(&self, city_weather: &CityWeather) -> Result<(), Error> {
+        // Batches that the source rejects are halved and pushed back onto the queue until
+        // they either succeed or are down to a single station
+        let mut pending = vec![city_weather.clone()];
+        while let Some(batch) = pending.pop() {
+            let response = loop {
+                match self.source.fetch_forecasts(&batch).await {
+                    Ok(response) => break response,
+                    Err(err) => {
+                        // Log the error and retry after a delay
+                        error!(
                             self.logger,
-                            "NOAA API returned error response for batch, skipping"
+                            "Error fetching {} forecasts: {}",
+                            self.source.name(),
+                            err
                         );
-                        if let Err(err) = self.tx.send(Ok(HashMap::new())).await {
-                            error!(self.logger, "Error sending result through channel: {}", err);
-                            return Ok(());
-                        }
-                        return Ok(());
+                        sleep(StdDuration::from_secs(5)).await;
                     }
+                }
+            };
 
-                    let grouped_xml = group_parameter_elements(&xml);
-                    let converted_xml: Dwml = match from_str(&grouped_xml) {
-                        Ok(xml) => xml,
-                        Err(err) => {
-                            error!(
-                                self.logger,
-                                "error converting xml: {} \n raw string: {}", err, xml
-                            );
-                            Dwml::default()
-                        }
-                    };
-                    if converted_xml == Dwml::default() {
+            let current_forecast_data = match response {
+                ForecastBatch::Forecasts(data) => data,
+                ForecastBatch::Rejected => {
+                    let batch_size = batch.city_data.len();
+                    if let Some(halves) = halve_batch(batch) {
                         info!(
                             self.logger,
-                            "no current forecast xml found, skipping converting"
+                            "{} source rejected batch of {} stations, retrying in halves",
+                            self.source.name(),
+                            batch_size
                         );
-                        if let Err(err) = self.tx.send(Ok(HashMap::new())).await {
-                            error!(self.logger, "Error sending result through channel: {}", err);
-                            return Ok(());
-                        }
-                        return Ok(());
-                    }
-                    let weather_with_stations = add_station_ids(city_weather, converted_xml);
-                    let current_forecast_data: HashMap<String, Vec<WeatherForecast>> =
-                        match weather_with_stations.try_into() {
-                            Ok(weather) => weather,
-                            Err(err) => {
-                                error!(self.logger, "error converting to Forecast: {}", err);
-
-                                HashMap::new()
-                            }
-                        };
-                    if current_forecast_data.is_empty() {
-                        info!(self.logger, "no current forecast data found");
-                        return Ok(());
-                    }
-                    // Send the result through the channel
-                    if let Err(err) = self.tx.send(Ok(current_forecast_data)).await {
-                        error!(self.logger, "Error sending result through channel: {}", err);
+                        pending.extend(halves);
+                        continue;
                     }
-
-                    return Ok(());
-                }
-                Err(err) => {
-                    // Log the error and retry after a delay
-                    error!(self.logger, "Error fetching XML: {}", err);
-                    sleep(StdDuration::from_secs(5)).await;
+                    info!(
+                        self.logger,
+                        "{} source rejected batch, skipping",
+                        self.source.name()
+                    );
+                    HashMap::new()
                 }
+            };
+            if current_forecast_data.is_empty() {
+                info!(self.logger, "no current forecast data found");
+            }
+            // Send the result through the channel
+            if let Err(err) = self.tx.send(Ok(current_forecast_data)).await {
+                error!(self.logger, "Error sending result through channel: {}", err);
+                return Ok(());
             }
         }
+
+        Ok(())
     }
 }
 
+/// NOAA error responses start with "<error>" instead of "<dwml>"
+fn is_error_response(xml: &str) -> bool {
+    xml.trim_start().starts_with("<error>")
+}
+
+/// Split a batch into two smaller batches, returns None once the batch can't be split further
+fn halve_batch(batch: CityWeather) -> Option<Vec<CityWeather>> {
+    let batch_size = batch.city_data.len();
+    if batch_size <= 1 {
+        return None;
+    }
+    Some(split_cityweather(batch, batch_size.div_ceil(2)))
+}
+
 pub struct ForecastService {
-    pub fetcher: Arc<XmlFetcher>,
+    pub source: Arc<dyn ForecastSource>,
     pub logger: Logger,
+    pub batch_size: usize,
+    /// Most batches fetched at once, on top of the rate limiter spacing out requests
+    pub max_concurrency: usize,
+    /// Rows buffered before they are written out as one row group
+    pub row_group_size: usize,
 }
 
 impl ForecastService {
-    pub fn new(logger: Logger, fetcher: Arc<XmlFetcher>) -> Self {
-        ForecastService { logger, fetcher }
+    pub fn new(
+        logger: Logger,
+        source: Arc<dyn ForecastSource>,
+        batch_size: usize,
+        max_concurrency: usize,
+        row_group_size: usize,
+    ) -> Self {
+        ForecastService {
+            logger,
+            source,
+            batch_size,
+            max_concurrency: max_concurrency.max(1),
+            row_group_size,
+        }
     }
 
     /// Fetches forecasts and writes them directly to a parquet file in batches.
-    /// Returns the path to the written parquet file.
+    /// Returns how many stations were written to the file.
     /// This approach streams data to disk as it arrives, avoiding memory accumulation.
+    /// Batches are buffered into row groups of about `row_group_size` rows, a batch that
+    /// fails is skipped and the file is still closed with every row group written before it.
     pub async fn get_forecasts_to_file(
         &self,
         city_weather: &CityWeather,
         output_path: &str,
-    ) -> Result<String, Error> {
-        let split_maps = split_cityweather(city_weather.clone(), 50);
+    ) -> Result<usize, Error> {
+        let split_maps = split_cityweather(city_weather.clone(), self.batch_size);
         let total_requests = split_maps.len();
         let (tx, mut rx) =
             mpsc::channel::<Result<HashMap<String, Vec<WeatherForecast>>, Error>>(total_requests);
@@ -1037,30 +1123,42 @@ impl ForecastService {
         let max_retries = 3;
         let request_counter = Arc::new(AtomicUsize::new(total_requests));
         let mut set = JoinSet::new();
+        // Every batch gets a task up front, but only this many talk to the source at a time
+        let fetch_permits = Arc::new(Semaphore::new(self.max_concurrency));
 
         // Spawn fetch tasks
         for city_weather in split_maps {
-            let url = get_url(&city_weather);
+            let batch_size = city_weather.city_data.len();
             let counter_clone = Arc::clone(&request_counter);
             let forecast_retry = ForecastRetry::new(
                 tx.clone(),
                 max_retries,
-                self.fetcher.clone(),
+                self.source.clone(),
                 self.logger.clone(),
             );
             let logger_cpy = self.logger.clone();
+            let fetch_permits = Arc::clone(&fetch_permits);
 
             set.spawn(async move {
+                let Ok(_permit) = fetch_permits.acquire_owned().await else {
+                    return;
+                };
                 match forecast_retry
-                    .fetch_forecast_with_retry(url.clone(), &city_weather)
+                    .fetch_forecast_with_retry(&city_weather)
                     .await
                 {
                     Ok(_) => {
-                        info!(&logger_cpy, "completed getting forecast data for: {}", url);
+                        info!(
+                            &logger_cpy,
+                            "completed getting forecast data for batch of {} stations", batch_size
+                        );
                         counter_clone.fetch_sub(1, Ordering::Relaxed);
                     }
                     Err(_) => {
-                        error!(&logger_cpy, "error getting forecast data for: {}", url);
+                        error!(
+                            &logger_cpy,
+                            "error getting forecast data for batch of {} stations", batch_size
+                        );
                         counter_clone.fetch_sub(1, Ordering::Relaxed);
                     }
                 }
@@ -1074,92 +1172,88 @@ impl ForecastService {
         let file = File::create(output_path)
             .map_err(|e| anyhow!("failed to create parquet file: {}", e))?;
         let props = WriterProperties::builder().build();
-        let writer = Arc::new(Mutex::new(
+        let mut writer = RowGroupWriter::new(
             SerializedFileWriter::new(file, Arc::new(create_forecast_schema()), Arc::new(props))
                 .map_err(|e| anyhow!("failed to create parquet writer: {}", e))?,
-        ));
+            self.row_group_size,
+        );
+
+        // Batches are written here as they arrive rather than on a spawned task, so a task
+        // dying can't take the writer and everything already written with it
+        let mut stations_written = 0;
+        // Stations in rows still waiting on the buffer to fill up
+        let mut stations_buffered = HashSet::new();
+        while let Some(result) = rx.recv().await {
+            match result {
+                Ok(data) => {
+                    if data.is_empty() {
+                        continue;
+                    }
 
-        let writer_clone = Arc::clone(&writer);
-        let city_weather_clone = city_weather.clone();
-        let logger_clone = self.logger.clone();
-        let request_counter_clone = Arc::clone(&request_counter);
-
-        // Spawn receiver task that writes batches as they arrive
-        set.spawn(async move {
-            while let Some(result) = rx.recv().await {
-                match result {
-                    Ok(data) => {
-                        if data.is_empty() {
+                    info!(
+                        &self.logger,
+                        "writing forecast batch with {} stations",
+                        data.len()
+                    );
+
+                    let batch_forecasts = match panic::catch_unwind(AssertUnwindSafe(|| {
+                        forecast_rows(&data, city_weather)
+                    })) {
+                        Ok(batch_forecasts) => batch_forecasts,
+                        Err(_) => {
+                            error!(
+                                &self.logger,
+                                "failed to convert forecast batch of {} stations, skipping",
+                                data.len()
+                            );
                             continue;
                         }
+                    };
 
-                        info!(
-                            &logger_clone,
-                            "writing forecast batch with {} stations",
-                            data.len()
+                    if !batch_forecasts.is_empty() {
+                        stations_buffered.extend(
+                            batch_forecasts
+                                .iter()
+                                .map(|forecast| forecast.station_id.clone()),
                         );
-
-                        // Convert batch to Forecast structs
-                        let mut batch_forecasts = Vec::new();
-                        for all_forecasts in data.values() {
-                            for weather_forecast in all_forecasts {
-                                if let Ok(mut forecast) =
-                                    Forecast::try_from(weather_forecast.clone())
-                                {
-                                    if let Some(city) =
-                                        city_weather_clone.city_data.get(&forecast.station_id)
-                                    {
-                                        forecast.station_name = city.station_name.clone();
-                                        forecast.state = city.state.clone();
-                                        forecast.iata_id = city.iata_id.clone();
-                                        forecast.elevation_m = city.elevation_m;
-                                        batch_forecasts.push(forecast);
-                                    }
-                                }
+                        match writer.push(batch_forecasts) {
+                            Ok(0) => {}
+                            Ok(_) => stations_written += mem::take(&mut stations_buffered).len(),
+                            Err(e) => {
+                                // A half written row group leaves the writer unusable, stop
+                                // here and keep what made it to disk
+                                error!(
+                                    &self.logger,
+                                    "failed to write forecast batch, keeping {} stations already written: {}",
+                                    stations_written,
+                                    e
+                                );
+                                stations_buffered.clear();
+                                set.abort_all();
+                                break;
                             }
                         }
-
-                        // Write batch as a row group
-                        if !batch_forecasts.is_empty() {
-                            let mut writer_guard = writer_clone.lock().await;
-                            match writer_guard.next_row_group() {
-                                Ok(mut row_group) => {
-                                    if let Err(e) = batch_forecasts
-                                        .as_slice()
-                                        .write_to_row_group(&mut row_group)
-                                    {
-                                        error!(&logger_clone, "failed to write row group: {}", e);
-                                    }
-                                    if let Err(e) = row_group.close() {
-                                        error!(&logger_clone, "failed to close row group: {}", e);
-                                    }
-                                }
-                                Err(e) => {
-                                    error!(&logger_clone, "failed to create row group: {}", e);
-                                }
-                            };
-                        }
-                    }
-                    Err(err) => {
-                        error!(&logger_clone, "Error fetching forecast data: {}", err);
                     }
                 }
-
-                let batches_left = request_counter_clone.load(Ordering::Relaxed);
-                if batches_left > 0 {
-                    let progress = ((total_requests as f64 - batches_left as f64)
-                        / total_requests as f64)
-                        * 100_f64;
-                    info!(
-                        &logger_clone,
-                        "waiting for next batch of weather data, batches left: {} progress: {:.2}%",
-                        batches_left,
-                        progress
-                    );
+                Err(err) => {
+                    error!(&self.logger, "Error fetching forecast data: {}", err);
                 }
             }
-            info!(&logger_clone, "all requests have completed, moving on");
-        });
+
+            let batches_left = request_counter.load(Ordering::Relaxed);
+            if batches_left > 0 {
+                let progress = ((total_requests as f64 - batches_left as f64)
+                    / total_requests as f64)
+                    * 100_f64;
+                info!(
+                    &self.logger,
+                    "waiting for next batch of weather data, batches left: {} progress: {:.2}%",
+                    batches_left,
+                    progress
+                );
+            }
+        }
+        info!(&self.logger, "all requests have completed, moving on");
 
         // Wait for all tasks to complete
         while let Some(inner_res) = set.join_next().await {
@@ -1169,21 +1263,42 @@ impl ForecastService {
             }
         }
 
-        // Close the parquet writer
+        // Write what's left in the buffer and close the parquet writer
         info!(self.logger, "closing parquet writer");
-        let writer_guard = Arc::try_unwrap(writer)
-            .map_err(|_| anyhow!("failed to unwrap writer Arc"))?
-            .into_inner();
-        writer_guard
-            .close()
-            .map_err(|e| anyhow!("failed to close parquet writer: {}", e))?;
-
-        info!(self.logger, "done writing forecasts to {}", output_path);
-        Ok(output_path.to_string())
+        writer.close()?;
+        stations_written += stations_buffered.len();
+
+        info!(
+            self.logger,
+            "done writing forecasts for {} stations to {}", stations_written, output_path
+        );
+        Ok(stations_written)
+    }
+}
+
+/// Rows for one fetched batch, with station details filled in from the coordinates
+fn forecast_rows(
+    data: &HashMap<String, Vec<WeatherForecast>>,
+    city_weather: &CityWeather,
+) -> Vec<Forecast> {
+    let mut batch_forecasts = Vec::new();
+    for all_forecasts in data.values() {
+        for weather_forecast in all_forecasts {
+            if let Ok(mut forecast) = Forecast::try_from(weather_forecast.clone()) {
+                if let Some(city) = city_weather.city_data.get(&forecast.station_id) {
+                    forecast.station_name = city.station_name.clone();
+                    forecast.state = city.state.clone();
+                    forecast.iata_id = city.iata_id.clone();
+                    forecast.elevation_m = city.elevation_m;
+                    batch_forecasts.push(forecast);
+                }
+            }
+        }
     }
+    batch_forecasts
 }
 
-fn add_station_ids(city_weather: &CityWeather, mut converted_xml: Dwml) -> Dwml {
+fn add_station_ids(city_weather: &CityWeather, mut converted_xml: Dwml, logger: &Logger) -> Dwml {
     converted_xml.data.location = converted_xml
         .data
         .location
@@ -1194,10 +1309,18 @@ fn add_station_ids(city_weather: &CityWeather, mut converted_xml: Dwml) -> Dwml
 
             let station_id = city_weather
                 .city_data
-                .clone()
                 .values()
                 .find(|val| compare_coordinates(val, &latitude, &longitude))
                 .map(|val| val.station_id.clone());
+            if station_id.is_none() {
+                warn!(
+                    logger,
+                    "no station found for forecast location {} at {},{}",
+                    location.location_key,
+                    latitude,
+                    longitude
+                );
+            }
 
             Location {
                 location_key: location.location_key.clone(),
@@ -1209,15 +1332,31 @@ fn add_station_ids(city_weather: &CityWeather, mut converted_xml: Dwml) -> Dwml
     converted_xml
 }
 
-// forecast xml files always provide these to 2 decimal places, make sure to match on that percision
+// forecast xml files provide these to 2 decimal places, but trailing zeros may be dropped
+// (e.g. "40.7" vs "40.70"), so compare numerically at that precision instead of as strings.
+// The station side uses the same rounding as the request, otherwise x.xx5 coordinates drift apart
 fn compare_coordinates(weather_station: &WeatherStation, latitude: &str, longitude: &str) -> bool {
-    let station_lat = weather_station.get_latitude();
-    let station_long = weather_station.get_longitude();
+    let (Some(station_lat), Some(station_long), Some(latitude), Some(longitude)) = (
+        coordinate_hundredths(&weather_station.get_latitude()),
+        coordinate_hundredths(&weather_station.get_longitude()),
+        coordinate_hundredths(latitude),
+        coordinate_hundredths(longitude),
+    ) else {
+        return false;
+    };
 
     station_lat == latitude && station_long == longitude
 }
 
-fn get_url(city_weather: &CityWeather) -> String {
+fn coordinate_hundredths(coordinate: &str) -> Option<i64> {
+    coordinate
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|value| (value * 100.0).round() as i64)
+}
+
+fn get_url(base_url: &Url, city_weather: &CityWeather) -> String {
     // Get the current time
     let mut current_time = OffsetDateTime::now_utc();
 
@@ -1252,7 +1391,7 @@ fn get_url(city_weather: &CityWeather) -> String {
     let one_week_from_now = current_time.add(one_week_duration);
 
     let one_week = one_week_from_now.format(&format_description).unwrap();
-    format!("https://graphical.weather.gov/xml/sample_products/browser_interface/ndfdXMLclient.php?listLatLon={}&product=time-series&begin={}&end={}&Unit=e&maxt=maxt&mint=mint&wspd=wspd&wdir=wdir&pop12=pop12&qpf=qpf&snow=snow&snowratio=snowratio&iceaccum=iceaccum&maxrh=maxrh&minrh=minrh", city_weather.get_coordinates_url(),now,one_week)
+    format!("{}?listLatLon={}&product=time-series&begin={}&end={}&Unit=e&maxt=maxt&mint=mint&wspd=wspd&wdir=wdir&pop12=pop12&qpf=qpf&snow=snow&snowratio=snowratio&iceaccum=iceaccum&maxrh=maxrh&minrh=minrh", base_url, city_weather.get_coordinates_url(),now,one_week)
 }
 
 /// Reorder child elements within `<parameters>` blocks so that elements with
@@ -1355,3 +1494,251 @@ fn group_parameter_elements(xml: &str) -> String {
     result.push_str(remaining);
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_base_url, DEFAULT_NOAA_BASE_URL};
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::Field,
+    };
+
+    fn city_weather(station_count: usize) -> CityWeather {
+        let city_data = (0..station_count)
+            .map(|i| {
+                let station_id = format!("K{:03}", i);
+                let station = WeatherStation {
+                    station_id: station_id.clone(),
+                    station_name: format!("Station {}", i),
+                    state: String::from("NY"),
+                    iata_id: String::new(),
+                    elevation_m: None,
+                    latitude: format!("{:.2}", 40.0 + i as f64 * 0.1),
+                    longitude: String::from("-73.00"),
+                };
+                (station_id, station)
+            })
+            .collect();
+        CityWeather { city_data }
+    }
+
+    #[test]
+    fn test_custom_batch_size_splits_stations() {
+        let batches = split_cityweather(city_weather(25), 10);
+        let mut sizes: Vec<usize> = batches.iter().map(|b| b.city_data.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![5, 10, 10]);
+
+        let batches = split_cityweather(city_weather(25), 50);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].city_data.len(), 25);
+    }
+
+    #[test]
+    fn test_compare_coordinates_ignores_trailing_zeros() {
+        let station = WeatherStation {
+            station_id: String::from("KJFK"),
+            station_name: String::from("New York/JF Kennedy Intl"),
+            state: String::from("NY"),
+            iata_id: String::from("JFK"),
+            elevation_m: None,
+            latitude: String::from("40.70"),
+            longitude: String::from("-73.8"),
+        };
+
+        assert!(compare_coordinates(&station, "40.7", "-73.80"));
+        assert!(compare_coordinates(&station, "40.70", "-73.8"));
+        assert!(!compare_coordinates(&station, "40.71", "-73.80"));
+        assert!(!compare_coordinates(&station, "", "-73.80"));
+    }
+
+    #[test]
+    fn test_compare_coordinates_rounds_station_precision() {
+        let station = WeatherStation {
+            station_id: String::from("KLGA"),
+            station_name: String::from("New York/La Guardia"),
+            state: String::from("NY"),
+            iata_id: String::from("LGA"),
+            elevation_m: None,
+            latitude: String::from("40.779"),
+            longitude: String::from("-73.880"),
+        };
+
+        assert!(compare_coordinates(&station, "40.78", "-73.88"));
+    }
+
+    #[test]
+    fn test_compare_coordinates_matches_the_requested_rounding() {
+        let station = WeatherStation {
+            station_id: String::from("KHOU"),
+            station_name: String::from("Houston/William P. Hobby"),
+            state: String::from("TX"),
+            iata_id: String::from("HOU"),
+            elevation_m: None,
+            latitude: String::from("30.0050"),
+            longitude: String::from("-95.2850"),
+        };
+
+        // The point comes back as it was requested
+        assert_eq!(station.get_latitude(), "30.00");
+        assert_eq!(station.get_longitude(), "-95.28");
+        assert!(compare_coordinates(
+            &station,
+            &station.get_latitude(),
+            &station.get_longitude()
+        ));
+        assert!(!compare_coordinates(&station, "30.01", "-95.29"));
+    }
+
+    #[test]
+    fn test_error_response_detection() {
+        assert!(is_error_response("  <error><h2>ERROR</h2></error>"));
+        assert!(!is_error_response("<?xml version=\"1.0\"?><dwml></dwml>"));
+    }
+
+    #[test]
+    fn test_halve_batch_on_error() {
+        let halves = halve_batch(city_weather(7)).expect("batch should be split");
+        let mut sizes: Vec<usize> = halves.iter().map(|b| b.city_data.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![3, 4]);
+
+        // Every station survives the split
+        let mut station_ids: Vec<String> = halves
+            .iter()
+            .flat_map(|b| b.city_data.keys().cloned())
+            .collect();
+        station_ids.sort();
+        let mut expected: Vec<String> = city_weather(7).city_data.into_keys().collect();
+        expected.sort();
+        assert_eq!(station_ids, expected);
+    }
+
+    #[test]
+    fn test_halve_batch_stops_at_single_station() {
+        let mut batch = city_weather(2);
+        let mut splits = 0;
+        while let Some(mut halves) = halve_batch(batch) {
+            splits += 1;
+            batch = halves.pop().unwrap();
+        }
+        assert_eq!(splits, 1);
+    }
+
+    /// Answers like NOAA, rejecting batches over `max_batch` stations and returning one forecast
+    /// per station for the rest
+    struct SplittingForecastSource {
+        max_batch: usize,
+        /// Size of every batch requested
+        requests: std::sync::Mutex<Vec<usize>>,
+    }
+
+    #[async_trait]
+    impl ForecastSource for SplittingForecastSource {
+        fn name(&self) -> &'static str {
+            "splitting"
+        }
+
+        async fn fetch_forecasts(&self, batch: &CityWeather) -> Result<ForecastBatch, Error> {
+            self.requests.lock().unwrap().push(batch.city_data.len());
+            if batch.city_data.len() > self.max_batch {
+                return Ok(ForecastBatch::Rejected);
+            }
+            let generated_at = time::macros::datetime!(2024-08-12 00:00 UTC);
+            let forecasts = batch
+                .city_data
+                .values()
+                .map(|station| {
+                    let forecast = WeatherForecast {
+                        station_id: station.station_id.clone(),
+                        station_name: station.station_name.clone(),
+                        latitude: station.latitude.clone(),
+                        longitude: station.longitude.clone(),
+                        generated_at,
+                        begin_time: generated_at,
+                        end_time: generated_at + Duration::days(1),
+                        max_temp: Some(80),
+                        min_temp: Some(60),
+                        temperature_unit_code: String::from("Fahrenheit"),
+                        wind_speed: None,
+                        wind_speed_unit_code: String::from("knots"),
+                        wind_direction: None,
+                        wind_direction_unit_code: String::from("degrees true"),
+                        relative_humidity_max: None,
+                        relative_humidity_min: None,
+                        relative_humidity_unit_code: String::from("percent"),
+                        liquid_precipitation_amt: None,
+                        liquid_precipitation_unit_code: String::from("inches"),
+                        snow_amt: None,
+                        snow_amt_unit_code: String::from("inches"),
+                        snow_ratio: None,
+                        snow_ratio_unit_code: String::from("percent"),
+                        ice_amt: None,
+                        ice_amt_unit_code: String::from("inches"),
+                        twelve_hour_probability_of_precipitation: None,
+                        twelve_hour_probability_of_precipitation_unit_code: String::from("percent"),
+                    };
+                    (station.station_id.clone(), vec![forecast])
+                })
+                .collect();
+            Ok(ForecastBatch::Forecasts(forecasts))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_batch_writes_each_station_once_from_its_halves() {
+        let source = Arc::new(SplittingForecastSource {
+            max_batch: 2,
+            requests: Default::default(),
+        });
+        let dir = std::env::temp_dir().join(format!("daemon_split_batch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("forecasts.parquet").to_string_lossy().to_string();
+
+        let logger = Logger::root(slog::Discard, slog::o!());
+        let stations_written = ForecastService::new(logger, source.clone(), 4, 1, 100)
+            .get_forecasts_to_file(&city_weather(4), &path)
+            .await
+            .unwrap();
+
+        // the combined request was rejected and both halves went through
+        let mut requests = source.requests.lock().unwrap().clone();
+        requests.sort();
+        assert_eq!(requests, vec![2, 2, 4]);
+        assert_eq!(stations_written, 4);
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let mut station_ids: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .filter_map(|row| {
+                row.unwrap().get_column_iter().find_map(|(name, field)| {
+                    match (name.as_str(), field) {
+                        ("station_id", Field::Str(value)) => Some(value.clone()),
+                        _ => None,
+                    }
+                })
+            })
+            .collect();
+        station_ids.sort();
+        assert_eq!(station_ids, vec!["K000", "K001", "K002", "K003"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_custom_base_url_prefixes_forecast_query() {
+        let base_url = parse_base_url("http://127.0.0.1:8080/ndfd/client.php").unwrap();
+        let url = get_url(&base_url, &city_weather(1));
+
+        assert!(url.starts_with("http://127.0.0.1:8080/ndfd/client.php?listLatLon=40.00,-73.00&product=time-series&begin="));
+        assert!(url.ends_with("&minrh=minrh"));
+
+        let default_url = get_url(
+            &parse_base_url(DEFAULT_NOAA_BASE_URL).unwrap(),
+            &city_weather(1),
+        );
+        assert!(default_url.starts_with("https://graphical.weather.gov/xml/sample_products/browser_interface/ndfdXMLclient.php?listLatLon="));
+    }
+}
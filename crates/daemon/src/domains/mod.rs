@@ -1,5 +1,7 @@
 pub mod forecasts;
 pub mod observations;
+pub mod sources;
 
 pub use forecasts::*;
 pub use observations::*;
+pub use sources::*;
@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Error};
+use async_trait::async_trait;
 use parquet::file::properties::WriterProperties;
 use parquet::file::writer::SerializedFileWriter;
-use parquet::record::RecordWriter;
 use parquet::{
     basic::{LogicalType, Repetition, Type as PhysicalType},
     schema::types::Type,
@@ -12,7 +12,9 @@ use std::fs::File;
 use std::sync::Arc;
 use time::{format_description::well_known::Rfc3339, macros::format_description, OffsetDateTime};
 
-use crate::{CityWeather, Metar, ObservationData, Units, XmlFetcher};
+use crate::{
+    CityWeather, Metar, ObservationData, ObservationSource, RowGroupWriter, Units, XmlFetcher,
+};
 
 #[derive(Clone)]
 pub struct CurrentWeather {
@@ -31,6 +33,8 @@ pub struct CurrentWeather {
     pub precip_in: Option<f64>,
     pub precip_unit_code: String,
     pub wx_string: String,
+    /// Peak gust in the same unit as `wind_speed`, only reported while the wind is gusting
+    pub wind_gust: Option<i64>,
 }
 
 impl TryFrom<Metar> for CurrentWeather {
@@ -89,6 +93,12 @@ impl TryFrom<Metar> for CurrentWeather {
                 .unwrap_or(None),
             precip_unit_code: Units::Inches.to_string(),
             wx_string: val.wx_string.unwrap_or_default(),
+            wind_gust: val
+                .wind_gust_kt
+                .unwrap_or(String::from(""))
+                .parse::<i64>()
+                .map(Some)
+                .unwrap_or(None),
         })
     }
 }
@@ -115,6 +125,7 @@ pub struct Observation {
     pub precip_in: Option<f64>,
     pub precip_unit_code: String,
     pub wx_string: String,
+    pub wind_gust: Option<i64>,
 }
 
 impl TryFrom<CurrentWeather> for Observation {
@@ -146,6 +157,7 @@ impl TryFrom<CurrentWeather> for Observation {
             precip_in: val.precip_in,
             precip_unit_code: val.precip_unit_code,
             wx_string: val.wx_string,
+            wind_gust: val.wind_gust,
         };
         Ok(parquet)
     }
@@ -264,6 +276,11 @@ pub fn create_observation_schema() -> Type {
         .build()
         .unwrap();
 
+    let wind_gust = Type::primitive_type_builder("wind_gust", PhysicalType::INT64)
+        .with_repetition(Repetition::OPTIONAL)
+        .build()
+        .unwrap();
+
     let schema = Type::group_type_builder("observation")
         .with_fields(vec![
             Arc::new(station_id),
@@ -286,6 +303,7 @@ pub fn create_observation_schema() -> Type {
             Arc::new(precip_in),
             Arc::new(precip_unit_code),
             Arc::new(wx_string),
+            Arc::new(wind_gust),
         ])
         .build()
         .unwrap();
@@ -293,13 +311,62 @@ pub fn create_observation_schema() -> Type {
     schema
 }
 
+/// Observations from the aviationweather.gov METAR cache, one download covers every station
+pub struct MetarObservationSource {
+    fetcher: Arc<XmlFetcher>,
+    logger: Logger,
+}
+
+impl MetarObservationSource {
+    pub fn new(fetcher: Arc<XmlFetcher>, logger: Logger) -> Self {
+        MetarObservationSource { fetcher, logger }
+    }
+}
+
+#[async_trait]
+impl ObservationSource for MetarObservationSource {
+    fn name(&self) -> &'static str {
+        "metar"
+    }
+
+    async fn fetch_observations(
+        &self,
+        _city_weather: &CityWeather,
+    ) -> Result<Vec<CurrentWeather>, Error> {
+        let url = "https://aviationweather.gov/data/cache/metars.cache.xml.gz";
+        info!(self.logger, "fetching observations from {}", url);
+        let raw_observation = self.fetcher.fetch_xml_gzip(url).await?;
+        let converted_xml: ObservationData = serde_xml_rs::from_str(&raw_observation)?;
+
+        let mut observations = vec![];
+        for value in converted_xml.data.metar.into_iter() {
+            if value.temp_c.is_none()
+                || value.longitude.is_none()
+                || value.latitude.is_none()
+                || value.observation_time.is_none()
+            {
+                // skip reading if missing key values
+                continue;
+            }
+            observations.push(value.try_into()?);
+        }
+        Ok(observations)
+    }
+}
+
 pub struct ObservationService {
     pub logger: Logger,
-    pub fetcher: Arc<XmlFetcher>,
+    pub source: Arc<dyn ObservationSource>,
+    /// Rows buffered before they are written out as one row group
+    pub row_group_size: usize,
 }
 impl ObservationService {
-    pub fn new(logger: Logger, fetcher: Arc<XmlFetcher>) -> Self {
-        ObservationService { logger, fetcher }
+    pub fn new(logger: Logger, source: Arc<dyn ObservationSource>, row_group_size: usize) -> Self {
+        ObservationService {
+            logger,
+            source,
+            row_group_size,
+        }
     }
 
     /// Fetches observations and writes them directly to a parquet file.
@@ -309,31 +376,25 @@ impl ObservationService {
         city_weather: &CityWeather,
         output_path: &str,
     ) -> Result<String, Error> {
-        let url = "https://aviationweather.gov/data/cache/metars.cache.xml.gz";
-        info!(self.logger, "fetching observations from {}", url);
-        let raw_observation = self.fetcher.fetch_xml_gzip(url).await?;
-        let converted_xml: ObservationData = serde_xml_rs::from_str(&raw_observation)?;
+        info!(
+            self.logger,
+            "fetching observations from {} source",
+            self.source.name()
+        );
+        let readings = self.source.fetch_observations(city_weather).await?;
 
         // Create parquet writer
         let file = File::create(output_path)
             .map_err(|e| anyhow!("failed to create parquet file: {}", e))?;
         let props = WriterProperties::builder().build();
-        let mut writer =
+        let mut writer = RowGroupWriter::new(
             SerializedFileWriter::new(file, Arc::new(create_observation_schema()), Arc::new(props))
-                .map_err(|e| anyhow!("failed to create parquet writer: {}", e))?;
+                .map_err(|e| anyhow!("failed to create parquet writer: {}", e))?,
+            self.row_group_size,
+        );
 
         let mut observations = vec![];
-        for value in converted_xml.data.metar.iter() {
-            if value.temp_c.is_none()
-                || value.longitude.is_none()
-                || value.latitude.is_none()
-                || value.observation_time.is_none()
-            {
-                // skip reading if missing key values
-                continue;
-            }
-            let current: CurrentWeather = value.clone().try_into()?;
-
+        for current in readings {
             let mut observation: Observation = current.try_into()?;
             if let Some(city) = city_weather.city_data.get(&observation.station_id) {
                 // only add observation if we have a station_name with it
@@ -345,26 +406,14 @@ impl ObservationService {
             }
         }
 
-        // Write all observations as a single row group
         info!(
             self.logger,
             "writing {} observations to {}",
             observations.len(),
             output_path
         );
-        let mut row_group = writer
-            .next_row_group()
-            .map_err(|e| anyhow!("failed to create row group: {}", e))?;
-        observations
-            .as_slice()
-            .write_to_row_group(&mut row_group)
-            .map_err(|e| anyhow!("failed to write observations: {}", e))?;
-        row_group
-            .close()
-            .map_err(|e| anyhow!("failed to close row group: {}", e))?;
-        writer
-            .close()
-            .map_err(|e| anyhow!("failed to close parquet writer: {}", e))?;
+        writer.push(observations)?;
+        writer.close()?;
 
         info!(self.logger, "done writing observations to {}", output_path);
         Ok(output_path.to_string())
@@ -66,6 +66,9 @@ pub struct Metar {
     #[serde(rename = "wind_speed_kt")]
     pub wind_speed_kt: Option<String>,
 
+    #[serde(rename = "wind_gust_kt")]
+    pub wind_gust_kt: Option<String>,
+
     #[serde(rename = "elevation_m")]
     pub elevation_m: Option<String>,
 
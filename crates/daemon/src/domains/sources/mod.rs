@@ -0,0 +1,5 @@
+pub mod source;
+pub mod weather_gov;
+
+pub use source::*;
+pub use weather_gov::*;
@@ -0,0 +1,374 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use clap::ValueEnum;
+use reqwest::Url;
+use serde::Deserialize;
+use slog::Logger;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use crate::{
+    CityWeather, CurrentWeather, MetarObservationSource, NdfdForecastSource, WeatherForecast,
+    WeatherGovObservationSource, XmlFetcher,
+};
+
+/// Result of asking a forecast provider for one batch of stations
+pub enum ForecastBatch {
+    /// Parsed forecasts keyed by station id
+    Forecasts(HashMap<String, Vec<WeatherForecast>>),
+    /// The provider refused the batch, smaller batches may still succeed
+    Rejected,
+}
+
+/// Fetches and parses forecasts from a weather provider
+#[async_trait]
+pub trait ForecastSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Transport errors are retried by the caller, parse failures should come back as an empty batch
+    async fn fetch_forecasts(&self, batch: &CityWeather) -> Result<ForecastBatch, Error>;
+}
+
+/// Fetches and parses current observations from a weather provider
+#[async_trait]
+pub trait ObservationSource: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Readings for the requested stations, the provider may also return readings for
+    /// stations outside `city_weather`, those are dropped before writing
+    async fn fetch_observations(
+        &self,
+        city_weather: &CityWeather,
+    ) -> Result<Vec<CurrentWeather>, Error>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ForecastProvider {
+    /// graphical.weather.gov DWML (National Digital Forecast Database)
+    #[default]
+    Ndfd,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ObservationProvider {
+    /// aviationweather.gov METAR cache
+    #[default]
+    Metar,
+    /// api.weather.gov latest station observations (GeoJSON)
+    WeatherGov,
+}
+
+impl fmt::Display for ForecastProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForecastProvider::Ndfd => write!(f, "ndfd"),
+        }
+    }
+}
+
+impl fmt::Display for ObservationProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObservationProvider::Metar => write!(f, "metar"),
+            ObservationProvider::WeatherGov => write!(f, "weather-gov"),
+        }
+    }
+}
+
+pub fn forecast_source(
+    provider: ForecastProvider,
+    ndfd_base_url: Url,
+    fetcher: Arc<XmlFetcher>,
+    logger: Logger,
+) -> Arc<dyn ForecastSource> {
+    match provider {
+        ForecastProvider::Ndfd => Arc::new(NdfdForecastSource::new(ndfd_base_url, fetcher, logger)),
+    }
+}
+
+pub fn observation_source(
+    provider: ObservationProvider,
+    fetcher: Arc<XmlFetcher>,
+    logger: Logger,
+) -> Arc<dyn ObservationSource> {
+    match provider {
+        ObservationProvider::Metar => Arc::new(MetarObservationSource::new(fetcher, logger)),
+        ObservationProvider::WeatherGov => {
+            Arc::new(WeatherGovObservationSource::new(fetcher, logger))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ForecastService, ObservationService, Units, WeatherStation};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+    use slog::o;
+    use std::fs::{self, File};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use time::{macros::datetime, Duration};
+
+    struct StubForecastSource;
+
+    #[async_trait]
+    impl ForecastSource for StubForecastSource {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        async fn fetch_forecasts(&self, batch: &CityWeather) -> Result<ForecastBatch, Error> {
+            let begin_time = datetime!(2025-06-01 12:00 UTC);
+            let forecasts = batch
+                .city_data
+                .values()
+                .map(|station| {
+                    let forecast = WeatherForecast {
+                        station_id: station.station_id.clone(),
+                        station_name: String::new(),
+                        latitude: station.latitude.clone(),
+                        longitude: station.longitude.clone(),
+                        generated_at: begin_time,
+                        begin_time,
+                        end_time: begin_time + Duration::days(1),
+                        max_temp: Some(80),
+                        min_temp: Some(60),
+                        temperature_unit_code: Units::Fahrenheit.to_string(),
+                        wind_speed: Some(10),
+                        wind_speed_unit_code: Units::Knots.to_string(),
+                        wind_direction: None,
+                        wind_direction_unit_code: Units::DegreesTrue.to_string(),
+                        relative_humidity_max: None,
+                        relative_humidity_min: None,
+                        relative_humidity_unit_code: Units::Percent.to_string(),
+                        liquid_precipitation_amt: None,
+                        liquid_precipitation_unit_code: Units::Inches.to_string(),
+                        snow_amt: None,
+                        snow_amt_unit_code: Units::Inches.to_string(),
+                        snow_ratio: None,
+                        snow_ratio_unit_code: Units::Percent.to_string(),
+                        ice_amt: None,
+                        ice_amt_unit_code: Units::Inches.to_string(),
+                        twelve_hour_probability_of_precipitation: None,
+                        twelve_hour_probability_of_precipitation_unit_code: Units::Percent
+                            .to_string(),
+                    };
+                    (station.station_id.clone(), vec![forecast])
+                })
+                .collect();
+            Ok(ForecastBatch::Forecasts(forecasts))
+        }
+    }
+
+    /// Fails every batch holding `failing_station`, the rest come from the stub
+    struct FailingForecastSource {
+        failing_station: &'static str,
+    }
+
+    #[async_trait]
+    impl ForecastSource for FailingForecastSource {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        async fn fetch_forecasts(&self, batch: &CityWeather) -> Result<ForecastBatch, Error> {
+            if batch.city_data.contains_key(self.failing_station) {
+                panic!("batch with {} failed", self.failing_station);
+            }
+            StubForecastSource.fetch_forecasts(batch).await
+        }
+    }
+
+    /// Records the most batches it was asked for at once
+    #[derive(Default)]
+    struct CountingForecastSource {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ForecastSource for CountingForecastSource {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        async fn fetch_forecasts(&self, batch: &CityWeather) -> Result<ForecastBatch, Error> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            StubForecastSource.fetch_forecasts(batch).await
+        }
+    }
+
+    struct StubObservationSource;
+
+    #[async_trait]
+    impl ObservationSource for StubObservationSource {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        async fn fetch_observations(
+            &self,
+            _city_weather: &CityWeather,
+        ) -> Result<Vec<CurrentWeather>, Error> {
+            let reading = |station_id: &str| CurrentWeather {
+                station_id: station_id.to_string(),
+                latitude: 40.0,
+                longitude: -73.0,
+                generated_at: datetime!(2025-06-01 12:00 UTC),
+                temperature_value: Some(21.5),
+                temperature_unit_code: Units::Celcius.to_string(),
+                wind_direction: Some(180),
+                wind_direction_unit_code: Units::DegreesTrue.to_string(),
+                wind_speed: Some(7),
+                wind_speed_unit_code: Units::Knots.to_string(),
+                dewpoint_value: None,
+                dewpoint_unit_code: Units::Celcius.to_string(),
+                precip_in: None,
+                precip_unit_code: Units::Inches.to_string(),
+                wx_string: String::new(),
+                wind_gust: None,
+            };
+            // KXYZ isn't in the station list and should be dropped
+            Ok(vec![reading("KJFK"), reading("KXYZ")])
+        }
+    }
+
+    fn city_weather() -> CityWeather {
+        let city_data = ["KJFK", "KLGA"]
+            .iter()
+            .map(|id| {
+                let station = WeatherStation {
+                    station_id: id.to_string(),
+                    station_name: format!("{} Airport", id),
+                    state: String::from("NY"),
+                    iata_id: id[1..].to_string(),
+                    elevation_m: None,
+                    latitude: String::from("40.64"),
+                    longitude: String::from("-73.78"),
+                };
+                (id.to_string(), station)
+            })
+            .collect();
+        CityWeather { city_data }
+    }
+
+    fn read_station_names(path: &str) -> Vec<String> {
+        let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+        let mut names: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().get_string(1).unwrap().clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_writes_parquet_from_non_dwml_sources() {
+        let logger = Logger::root(slog::Discard, o!());
+        let dir = std::env::temp_dir().join(format!("daemon_sources_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let forecast_path = dir.join("forecasts.parquet").to_string_lossy().to_string();
+        let observation_path = dir
+            .join("observations.parquet")
+            .to_string_lossy()
+            .to_string();
+        let stations = city_weather();
+
+        let forecast_service =
+            ForecastService::new(logger.clone(), Arc::new(StubForecastSource), 1, 1, 100);
+        forecast_service
+            .get_forecasts_to_file(&stations, &forecast_path)
+            .await
+            .unwrap();
+        let observation_service =
+            ObservationService::new(logger, Arc::new(StubObservationSource), 100);
+        observation_service
+            .get_observations_to_file(&stations, &observation_path)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            read_station_names(&forecast_path),
+            vec!["KJFK Airport", "KLGA Airport"]
+        );
+        assert_eq!(read_station_names(&observation_path), vec!["KJFK Airport"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_providers_parse_from_config_names() {
+        assert_eq!(
+            ObservationProvider::from_str("weather-gov", true).unwrap(),
+            ObservationProvider::WeatherGov
+        );
+        assert_eq!(ObservationProvider::default().to_string(), "metar");
+        assert_eq!(
+            ForecastProvider::from_str("ndfd", true).unwrap(),
+            ForecastProvider::Ndfd
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_forecast_batch_keeps_the_others() {
+        let logger = Logger::root(slog::Discard, o!());
+        let dir = std::env::temp_dir().join(format!("daemon_failed_batch_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let forecast_path = dir.join("forecasts.parquet").to_string_lossy().to_string();
+
+        let source = FailingForecastSource {
+            failing_station: "KLGA",
+        };
+        let forecast_service = ForecastService::new(logger, Arc::new(source), 1, 1, 100);
+        let stations_written = forecast_service
+            .get_forecasts_to_file(&city_weather(), &forecast_path)
+            .await
+            .unwrap();
+
+        assert_eq!(stations_written, 1);
+        assert_eq!(read_station_names(&forecast_path), vec!["KJFK Airport"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_forecast_batches_respect_max_concurrency() {
+        let logger = Logger::root(slog::Discard, o!());
+        let dir = std::env::temp_dir().join(format!("daemon_concurrency_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let forecast_path = dir.join("forecasts.parquet").to_string_lossy().to_string();
+        let city_data = (0..12)
+            .map(|i| {
+                let station_id = format!("K{:03}", i);
+                let station = WeatherStation {
+                    station_id: station_id.clone(),
+                    station_name: format!("{} Airport", station_id),
+                    state: String::from("NY"),
+                    iata_id: String::new(),
+                    elevation_m: None,
+                    latitude: format!("{:.2}", 40.0 + i as f64 * 0.1),
+                    longitude: String::from("-73.78"),
+                };
+                (station_id, station)
+            })
+            .collect();
+        let source = Arc::new(CountingForecastSource::default());
+
+        let forecast_service = ForecastService::new(logger, source.clone(), 1, 3, 100);
+        let stations_written = forecast_service
+            .get_forecasts_to_file(&CityWeather { city_data }, &forecast_path)
+            .await
+            .unwrap();
+
+        assert_eq!(stations_written, 12);
+        assert_eq!(source.max_in_flight.load(Ordering::SeqCst), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,293 @@
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use serde::Deserialize;
+use slog::{info, warn, Logger};
+use std::sync::Arc;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{CityWeather, CurrentWeather, ObservationSource, Units, XmlFetcher};
+
+const KNOTS_PER_KM_H: f64 = 0.539_957;
+const INCHES_PER_MM: f64 = 0.039_370_1;
+
+/// Latest observations from api.weather.gov. The API has no bulk endpoint so this makes one
+/// request per station, which makes it better suited as a fallback than the primary source.
+pub struct WeatherGovObservationSource {
+    fetcher: Arc<XmlFetcher>,
+    logger: Logger,
+}
+
+impl WeatherGovObservationSource {
+    pub fn new(fetcher: Arc<XmlFetcher>, logger: Logger) -> Self {
+        WeatherGovObservationSource { fetcher, logger }
+    }
+}
+
+#[async_trait]
+impl ObservationSource for WeatherGovObservationSource {
+    fn name(&self) -> &'static str {
+        "weather-gov"
+    }
+
+    async fn fetch_observations(
+        &self,
+        city_weather: &CityWeather,
+    ) -> Result<Vec<CurrentWeather>, Error> {
+        info!(
+            self.logger,
+            "fetching observations for {} stations from api.weather.gov",
+            city_weather.city_data.len()
+        );
+        let mut observations = vec![];
+        for station_id in city_weather.city_data.keys() {
+            let url = format!(
+                "https://api.weather.gov/stations/{}/observations/latest",
+                station_id
+            );
+            let body = match self.fetcher.fetch_xml(&url).await {
+                Ok(body) => body,
+                Err(err) => {
+                    warn!(self.logger, "skipping station {}: {}", station_id, err);
+                    continue;
+                }
+            };
+            match parse_latest_observation(station_id, &body) {
+                Ok(Some(current)) => observations.push(current),
+                Ok(None) => {}
+                Err(err) => warn!(self.logger, "skipping station {}: {}", station_id, err),
+            }
+        }
+        Ok(observations)
+    }
+}
+
+#[derive(Deserialize)]
+struct ObservationFeature {
+    geometry: Option<Point>,
+    properties: ObservationProperties,
+}
+
+#[derive(Deserialize)]
+struct Point {
+    /// GeoJSON order, longitude then latitude
+    coordinates: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ObservationProperties {
+    timestamp: Option<String>,
+    #[serde(default)]
+    present_weather: Vec<PresentWeather>,
+    temperature: Option<QuantitativeValue>,
+    dewpoint: Option<QuantitativeValue>,
+    wind_direction: Option<QuantitativeValue>,
+    wind_speed: Option<QuantitativeValue>,
+    wind_gust: Option<QuantitativeValue>,
+    precipitation_last_hour: Option<QuantitativeValue>,
+}
+
+/// One weather phenomenon, `raw_string` holds its METAR code e.g. `-SN`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PresentWeather {
+    raw_string: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuantitativeValue {
+    value: Option<f64>,
+    unit_code: Option<String>,
+}
+
+impl QuantitativeValue {
+    /// The value converted by `scale` when reported in `unit`, None for any other unit
+    fn in_unit(&self, unit: &str, scale: f64) -> Option<f64> {
+        match self.unit_code.as_deref() {
+            Some(code) if code.ends_with(unit) => self.value.map(|value| value * scale),
+            _ => None,
+        }
+    }
+}
+
+fn value_in(value: &Option<QuantitativeValue>, unit: &str, scale: f64) -> Option<f64> {
+    value.as_ref().and_then(|value| value.in_unit(unit, scale))
+}
+
+/// Parse a `/stations/{id}/observations/latest` feature, returns None when the reading is
+/// missing the same key values the METAR source skips on
+fn parse_latest_observation(station_id: &str, body: &str) -> Result<Option<CurrentWeather>, Error> {
+    let feature: ObservationFeature = serde_json::from_str(body)
+        .map_err(|e| anyhow!("error parsing observation geojson: {}", e))?;
+    let properties = feature.properties;
+
+    let temperature_value = value_in(&properties.temperature, ":degC", 1.0);
+    let (Some(point), Some(timestamp), Some(_)) =
+        (feature.geometry, properties.timestamp, temperature_value)
+    else {
+        return Ok(None);
+    };
+    let [longitude, latitude] = point.coordinates[..] else {
+        return Err(anyhow!("unexpected coordinates: {:?}", point.coordinates));
+    };
+    let generated_at = OffsetDateTime::parse(&timestamp, &Rfc3339)
+        .map_err(|e| anyhow!("error parsing timestamp {}: {}", timestamp, e))?;
+
+    Ok(Some(CurrentWeather {
+        station_id: station_id.to_string(),
+        latitude,
+        longitude,
+        generated_at,
+        temperature_value,
+        temperature_unit_code: Units::Celcius.to_string(),
+        wind_direction: value_in(&properties.wind_direction, ":degree_(angle)", 1.0)
+            .map(|degrees| degrees.round() as i64),
+        wind_direction_unit_code: Units::DegreesTrue.to_string(),
+        wind_speed: value_in(&properties.wind_speed, ":km_h-1", KNOTS_PER_KM_H)
+            .map(|knots| knots.round() as i64),
+        wind_speed_unit_code: Units::Knots.to_string(),
+        dewpoint_value: value_in(&properties.dewpoint, ":degC", 1.0),
+        dewpoint_unit_code: Units::Celcius.to_string(),
+        precip_in: value_in(&properties.precipitation_last_hour, ":mm", INCHES_PER_MM),
+        precip_unit_code: Units::Inches.to_string(),
+        // Same space separated METAR codes the aviationweather source writes, the oracle
+        // splits precipitation into rain/snow/ice with them
+        wx_string: properties
+            .present_weather
+            .iter()
+            .filter_map(|weather| weather.raw_string.as_deref())
+            .collect::<Vec<_>>()
+            .join(" "),
+        wind_gust: value_in(&properties.wind_gust, ":km_h-1", KNOTS_PER_KM_H)
+            .map(|knots| knots.round() as i64),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ObservationService, WeatherStation};
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::Field,
+    };
+    use slog::o;
+    use std::{collections::HashMap, fs};
+    use time::macros::datetime;
+
+    const LATEST_OBSERVATION: &str = r#"{
+        "type": "Feature",
+        "geometry": { "type": "Point", "coordinates": [-73.76, 40.64] },
+        "properties": {
+            "station": "https://api.weather.gov/stations/KJFK",
+            "timestamp": "2025-06-01T11:51:00+00:00",
+            "textDescription": "Light Rain and Mist",
+            "presentWeather": [
+                { "intensity": "light", "weather": "rain", "rawString": "-RA" },
+                { "intensity": null, "weather": "fog_mist", "rawString": "BR" }
+            ],
+            "temperature": { "unitCode": "wmoUnit:degC", "value": 21.1 },
+            "dewpoint": { "unitCode": "wmoUnit:degC", "value": 15.6 },
+            "windDirection": { "unitCode": "wmoUnit:degree_(angle)", "value": 180 },
+            "windSpeed": { "unitCode": "wmoUnit:km_h-1", "value": 18.52 },
+            "windGust": { "unitCode": "wmoUnit:km_h-1", "value": 37.04 },
+            "precipitationLastHour": { "unitCode": "wmoUnit:mm", "value": 2.54 }
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_latest_observation_converts_units() {
+        let current = parse_latest_observation("KJFK", LATEST_OBSERVATION)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(current.station_id, "KJFK");
+        assert_eq!(current.latitude, 40.64);
+        assert_eq!(current.longitude, -73.76);
+        assert_eq!(current.generated_at, datetime!(2025-06-01 11:51 UTC));
+        assert_eq!(current.temperature_value, Some(21.1));
+        assert_eq!(current.wind_direction, Some(180));
+        assert_eq!(current.wind_speed, Some(10));
+        assert_eq!(current.wind_gust, Some(20));
+        assert!((current.precip_in.unwrap() - 0.1).abs() < 1e-6);
+        assert_eq!(current.wx_string, "-RA BR");
+    }
+
+    #[test]
+    fn test_parse_latest_observation_skips_missing_temperature() {
+        let body = LATEST_OBSERVATION.replace("\"value\": 21.1", "\"value\": null");
+        assert!(parse_latest_observation("KJFK", &body).unwrap().is_none());
+    }
+
+    /// Replays one parsed weather.gov reading
+    struct ParsedObservationSource(CurrentWeather);
+
+    #[async_trait]
+    impl ObservationSource for ParsedObservationSource {
+        fn name(&self) -> &'static str {
+            "parsed"
+        }
+
+        async fn fetch_observations(
+            &self,
+            _city_weather: &CityWeather,
+        ) -> Result<Vec<CurrentWeather>, Error> {
+            Ok(vec![self.0.clone()])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_present_weather_codes_reach_the_observation_file() {
+        // textDescription says snow in words the oracle's code matching can't read
+        let body = LATEST_OBSERVATION
+            .replace("Light Rain and Mist", "Snow and Mist")
+            .replace(
+                r#"{ "intensity": "light", "weather": "rain", "rawString": "-RA" }"#,
+                r#"{ "intensity": null, "weather": "snow", "rawString": "SN" }"#,
+            );
+        let current = parse_latest_observation("KJFK", &body).unwrap().unwrap();
+        let station = WeatherStation {
+            station_id: String::from("KJFK"),
+            station_name: String::from("KJFK Airport"),
+            state: String::from("NY"),
+            iata_id: String::from("JFK"),
+            elevation_m: None,
+            latitude: String::from("40.64"),
+            longitude: String::from("-73.76"),
+        };
+        let city_weather = CityWeather {
+            city_data: HashMap::from([(String::from("KJFK"), station)]),
+        };
+        let dir = std::env::temp_dir().join(format!("daemon_weather_gov_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir
+            .join("observations.parquet")
+            .to_string_lossy()
+            .to_string();
+
+        let logger = Logger::root(slog::Discard, o!());
+        ObservationService::new(logger, Arc::new(ParsedObservationSource(current)), 100)
+            .get_observations_to_file(&city_weather, &path)
+            .await
+            .unwrap();
+
+        let reader = SerializedFileReader::new(fs::File::open(&path).unwrap()).unwrap();
+        let wx_strings: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .filter_map(|row| {
+                row.unwrap().get_column_iter().find_map(|(name, field)| {
+                    match (name.as_str(), field) {
+                        ("wx_string", Field::Str(value)) => Some(value.clone()),
+                        _ => None,
+                    }
+                })
+            })
+            .collect();
+        // `SN` as its own token is what the oracle's default snow codes match on
+        assert_eq!(wx_strings, vec!["SN BR"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,458 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Error};
+use parquet::{
+    arrow::{
+        arrow_reader::{ParquetRecordBatchReaderBuilder, RowSelection, RowSelector},
+        arrow_writer::{compute_leaves, get_column_writers},
+    },
+    column::writer::ColumnCloseResult,
+    file::{
+        properties::WriterProperties,
+        reader::{FileReader, SerializedFileReader},
+        writer::SerializedFileWriter,
+    },
+    record::RowAccessor,
+    schema::types::Type,
+};
+use time::OffsetDateTime;
+
+use crate::uploaded_marker;
+
+/// Start of the hour holding `generated_at`, every run in that hour shares its file name
+pub fn hour_start(generated_at: OffsetDateTime) -> OffsetDateTime {
+    generated_at
+        .replace_minute(0)
+        .and_then(|at| at.replace_second(0))
+        .and_then(|at| at.replace_nanosecond(0))
+        .unwrap_or(generated_at)
+}
+
+/// Where a run writes before it is merged into the hour's file, `<file>.parquet.partial`
+/// so neither the oracle nor pruning mistake it for a finished file
+pub fn run_file_path(hour_path: &str) -> String {
+    format!("{}.partial", hour_path)
+}
+
+/// How a run's file ended up in the hour's file
+#[derive(Debug, PartialEq)]
+pub enum HourlyWrite {
+    /// First run of the hour, the run's file became the hour's file
+    Created,
+    /// The run's row groups were added after the earlier runs' row groups. Earlier row
+    /// groups holding only stations the run fetched again were dropped, those holding some
+    /// were rewritten without their rows.
+    Appended {
+        kept: usize,
+        replaced: usize,
+        trimmed: usize,
+    },
+    /// The earlier file couldn't be read or has another schema, the run's file replaced it
+    Replaced(String),
+}
+
+/// Merges the file a run wrote at `run_path` into the hour's file at `hour_path`
+///
+/// Parquet can't be appended to in place, so the merged file is written next to the hour's
+/// file and renamed over it. Row groups are copied without decoding, an earlier row group is
+/// kept when none of its stations were fetched again and dropped when all of them were. One
+/// only partly re-fetched is decoded and rewritten without the re-fetched stations' rows, so a
+/// re-run never leaves two rows for a station nor loses one it didn't fetch again. Only a
+/// different schema or an unreadable earlier file makes the run's file replace the hour's
+/// file instead. Either way readers only ever see a complete file, and an earlier upload
+/// marker is cleared since S3 no longer has what the hour's file holds.
+pub fn merge_hourly_file(run_path: &Path, hour_path: &Path) -> Result<HourlyWrite, Error> {
+    if !hour_path.exists() {
+        replace(run_path, hour_path)?;
+        return Ok(HourlyWrite::Created);
+    }
+    let _ = fs::remove_file(uploaded_marker(hour_path));
+
+    let merged_path = merged_file_path(hour_path);
+    match append_row_groups(hour_path, run_path, &merged_path) {
+        Ok(write) => {
+            replace(&merged_path, hour_path)?;
+            let _ = fs::remove_file(run_path);
+            Ok(write)
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&merged_path);
+            replace(run_path, hour_path)?;
+            Ok(HourlyWrite::Replaced(err.to_string()))
+        }
+    }
+}
+
+fn merged_file_path(hour_path: &Path) -> PathBuf {
+    let mut path = hour_path.as_os_str().to_owned();
+    path.push(".merge");
+    PathBuf::from(path)
+}
+
+fn replace(from: &Path, to: &Path) -> Result<(), Error> {
+    fs::rename(from, to)
+        .map_err(|e| anyhow!("error moving {} to {}: {}", from.display(), to.display(), e))
+}
+
+/// What happens to an earlier row group when a run is merged in
+enum EarlierRowGroup {
+    Keep,
+    Drop,
+    /// Rewrite with only these rows, the ones of stations the run didn't fetch again
+    Trim(RowSelection),
+}
+
+/// Writes the earlier row groups that survive plus every row group of the run to
+/// `merged_path`
+fn append_row_groups(
+    hour_path: &Path,
+    run_path: &Path,
+    merged_path: &Path,
+) -> Result<HourlyWrite, Error> {
+    let existing_file = File::open(hour_path)?;
+    let existing = SerializedFileReader::new(existing_file.try_clone()?)
+        .map_err(|e| anyhow!("earlier file is unreadable: {}", e))?;
+    let run_file = File::open(run_path)?;
+    let run = SerializedFileReader::new(run_file.try_clone()?)
+        .map_err(|e| anyhow!("run file is unreadable: {}", e))?;
+
+    let schema = run.metadata().file_metadata().schema_descr_ptr();
+    if existing
+        .metadata()
+        .file_metadata()
+        .schema_descr()
+        .root_schema()
+        != schema.root_schema()
+    {
+        return Err(anyhow!("earlier file has a different schema"));
+    }
+
+    let mut run_stations = HashSet::new();
+    for index in 0..run.num_row_groups() {
+        run_stations.extend(row_group_stations(&run, index)?);
+    }
+    let mut earlier = Vec::new();
+    for index in 0..existing.num_row_groups() {
+        let stations = row_group_stations(&existing, index)?;
+        let refetched: Vec<bool> = stations
+            .iter()
+            .map(|station| run_stations.contains(station))
+            .collect();
+        earlier.push(if refetched.iter().all(|refetched| !refetched) {
+            EarlierRowGroup::Keep
+        } else if refetched.iter().all(|refetched| *refetched) {
+            EarlierRowGroup::Drop
+        } else {
+            EarlierRowGroup::Trim(remaining_rows(&refetched))
+        });
+    }
+
+    let props = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(
+        File::create(merged_path)?,
+        schema.root_schema_ptr(),
+        props.clone(),
+    )?;
+    let (mut kept, mut replaced, mut trimmed) = (0, 0, 0);
+    for (index, row_group) in earlier.into_iter().enumerate() {
+        match row_group {
+            EarlierRowGroup::Keep => {
+                copy_row_groups(&mut writer, &existing_file, &existing, [index].into_iter())?;
+                kept += 1;
+            }
+            EarlierRowGroup::Drop => replaced += 1,
+            EarlierRowGroup::Trim(rows) => {
+                rewrite_row_group(&mut writer, &props, &existing_file, index, rows)?;
+                trimmed += 1;
+            }
+        }
+    }
+    copy_row_groups(&mut writer, &run_file, &run, 0..run.num_row_groups())?;
+    writer.close()?;
+    Ok(HourlyWrite::Appended {
+        kept,
+        replaced,
+        trimmed,
+    })
+}
+
+/// Selects the rows not `refetched`, in runs the reader can skip through
+fn remaining_rows(refetched: &[bool]) -> RowSelection {
+    let mut selectors: Vec<RowSelector> = Vec::new();
+    for &skip in refetched {
+        match selectors.last_mut() {
+            Some(last) if last.skip == skip => last.row_count += 1,
+            _ => selectors.push(if skip {
+                RowSelector::skip(1)
+            } else {
+                RowSelector::select(1)
+            }),
+        }
+    }
+    selectors.into()
+}
+
+/// Decodes the `rows` of row group `index` and writes them out as a new row group
+fn rewrite_row_group(
+    writer: &mut SerializedFileWriter<File>,
+    props: &Arc<WriterProperties>,
+    source: &File,
+    index: usize,
+    rows: RowSelection,
+) -> Result<(), Error> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(source.try_clone()?)?;
+    let parquet_schema = builder.metadata().file_metadata().schema_descr_ptr();
+    let arrow_schema = builder.schema().clone();
+    let reader = builder
+        .with_row_groups(vec![index])
+        .with_row_selection(rows)
+        .build()?;
+
+    let mut columns = get_column_writers(&parquet_schema, props, &arrow_schema)?;
+    for batch in reader {
+        let batch = batch?;
+        let mut column_writers = columns.iter_mut();
+        for (field, array) in arrow_schema.fields().iter().zip(batch.columns()) {
+            for leaf in compute_leaves(field, array)? {
+                column_writers
+                    .next()
+                    .ok_or_else(|| anyhow!("row group {} has more columns than its schema", index))?
+                    .write(&leaf)?;
+            }
+        }
+    }
+    let mut row_group = writer.next_row_group()?;
+    for column in columns {
+        column.close()?.append_to_row_group(&mut row_group)?;
+    }
+    row_group.close()?;
+    Ok(())
+}
+
+/// Station of each row of row group `index`, in row order
+fn row_group_stations(
+    reader: &SerializedFileReader<File>,
+    index: usize,
+) -> Result<Vec<String>, Error> {
+    let schema = reader.metadata().file_metadata().schema_descr();
+    let station_field = schema
+        .root_schema()
+        .get_fields()
+        .iter()
+        .find(|field| field.name() == "station_id")
+        .cloned()
+        .ok_or_else(|| anyhow!("file has no station_id column"))?;
+    let projection = Type::group_type_builder(schema.name())
+        .with_fields(vec![station_field])
+        .build()?;
+
+    let mut stations = Vec::new();
+    for row in reader
+        .get_row_group(index)?
+        .get_row_iter(Some(projection))?
+    {
+        stations.push(row?.get_string(0)?.clone());
+    }
+    Ok(stations)
+}
+
+fn copy_row_groups(
+    writer: &mut SerializedFileWriter<File>,
+    source: &File,
+    reader: &SerializedFileReader<File>,
+    row_groups: impl Iterator<Item = usize>,
+) -> Result<(), Error> {
+    for index in row_groups {
+        let metadata = reader.metadata().row_group(index);
+        let mut row_group = writer.next_row_group()?;
+        for column in metadata.columns() {
+            row_group.append_column(
+                source,
+                ColumnCloseResult {
+                    bytes_written: column.compressed_size() as u64,
+                    rows_written: metadata.num_rows() as u64,
+                    metadata: column.clone(),
+                    bloom_filter: None,
+                    column_index: None,
+                    offset_index: None,
+                },
+            )?;
+        }
+        row_group.close()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::record::RecordWriter;
+    use parquet_derive::ParquetRecordWriter;
+    use time::macros::datetime;
+
+    #[derive(ParquetRecordWriter)]
+    struct Reading {
+        station_id: String,
+        temperature_value: f64,
+    }
+
+    fn temp_data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("daemon_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Writes one row group per batch, the way buffered forecast rows are flushed
+    fn write_run(path: &Path, batches: &[&[(&str, f64)]]) {
+        let schema = [Reading {
+            station_id: String::new(),
+            temperature_value: 0.0,
+        }]
+        .as_slice()
+        .schema()
+        .unwrap();
+        let props = Arc::new(WriterProperties::builder().build());
+        let mut writer =
+            SerializedFileWriter::new(File::create(path).unwrap(), schema, props).unwrap();
+        for batch in batches {
+            let readings: Vec<Reading> = batch
+                .iter()
+                .map(|(station_id, temperature_value)| Reading {
+                    station_id: station_id.to_string(),
+                    temperature_value: *temperature_value,
+                })
+                .collect();
+            let mut row_group = writer.next_row_group().unwrap();
+            readings
+                .as_slice()
+                .write_to_row_group(&mut row_group)
+                .unwrap();
+            row_group.close().unwrap();
+        }
+        writer.close().unwrap();
+    }
+
+    fn read_rows(path: &Path) -> Vec<(String, f64)> {
+        let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+        let mut rows: Vec<(String, f64)> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                let row = row.unwrap();
+                (
+                    row.get_string(0).unwrap().clone(),
+                    row.get_double(1).unwrap(),
+                )
+            })
+            .collect();
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        rows
+    }
+
+    #[test]
+    fn test_hour_start_truncates_to_the_hour() {
+        assert_eq!(
+            hour_start(datetime!(2024-03-05 14:59:59.5 UTC)),
+            datetime!(2024-03-05 14:00:00 UTC)
+        );
+    }
+
+    #[test]
+    fn test_rerun_within_hour_leaves_no_duplicate_stations() {
+        let dir = temp_data_dir("hourly_rerun");
+        let hour_path = dir.join("forecasts_2024-03-05T14:00:00Z.parquet");
+        let run_path = PathBuf::from(run_file_path(hour_path.to_str().unwrap()));
+
+        write_run(
+            &run_path,
+            &[&[("KJFK", 1.0), ("KLGA", 2.0)], &[("KSEA", 3.0)]],
+        );
+        assert_eq!(
+            merge_hourly_file(&run_path, &hour_path).unwrap(),
+            HourlyWrite::Created
+        );
+        assert!(!run_path.exists());
+
+        // The re-run only got KJFK and KLGA back, KSEA's earlier rows are still wanted
+        write_run(&run_path, &[&[("KJFK", 10.0), ("KLGA", 20.0)]]);
+        assert_eq!(
+            merge_hourly_file(&run_path, &hour_path).unwrap(),
+            HourlyWrite::Appended {
+                kept: 1,
+                replaced: 1,
+                trimmed: 0
+            }
+        );
+        assert!(!run_path.exists());
+        assert_eq!(
+            read_rows(&hour_path),
+            vec![
+                (String::from("KJFK"), 10.0),
+                (String::from("KLGA"), 20.0),
+                (String::from("KSEA"), 3.0),
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_partly_refetched_row_group_keeps_the_other_stations() {
+        let dir = temp_data_dir("hourly_trim");
+        let hour_path = dir.join("observations_2024-03-05T14:00:00Z.parquet");
+        let run_path = PathBuf::from(run_file_path(hour_path.to_str().unwrap()));
+
+        write_run(
+            &hour_path,
+            &[&[("KJFK", 1.0), ("KLGA", 2.0), ("KBOS", 3.0), ("KSEA", 4.0)]],
+        );
+        write_run(&run_path, &[&[("KJFK", 10.0), ("KBOS", 30.0)]]);
+        assert_eq!(
+            merge_hourly_file(&run_path, &hour_path).unwrap(),
+            HourlyWrite::Appended {
+                kept: 0,
+                replaced: 0,
+                trimmed: 1
+            }
+        );
+        assert_eq!(
+            read_rows(&hour_path),
+            vec![
+                (String::from("KBOS"), 30.0),
+                (String::from("KJFK"), 10.0),
+                (String::from("KLGA"), 2.0),
+                (String::from("KSEA"), 4.0),
+            ]
+        );
+        assert!(!run_path.exists());
+        assert!(!merged_file_path(&hour_path).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_earlier_file_is_replaced() {
+        let dir = temp_data_dir("hourly_replace");
+        let hour_path = dir.join("observations_2024-03-05T14:00:00Z.parquet");
+        let run_path = PathBuf::from(run_file_path(hour_path.to_str().unwrap()));
+
+        // A crash can leave the hour's file truncated, the run's file replaces it
+        fs::write(&hour_path, b"PAR1").unwrap();
+        write_run(&run_path, &[&[("KLGA", 20.0)]]);
+        assert!(matches!(
+            merge_hourly_file(&run_path, &hour_path).unwrap(),
+            HourlyWrite::Replaced(_)
+        ));
+        assert_eq!(read_rows(&hour_path), vec![(String::from("KLGA"), 20.0)]);
+        assert!(!run_path.exists());
+        assert!(!merged_file_path(&hour_path).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
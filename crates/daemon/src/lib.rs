@@ -1,13 +1,21 @@
 mod coordinates;
+mod cycle_lock;
 mod domains;
+mod hourly_file;
 mod parquet_handler;
+mod row_groups;
 
 mod s3_storage;
 mod utils;
+mod validate;
 
 pub use coordinates::*;
+pub use cycle_lock::*;
 pub use domains::*;
+pub use hourly_file::*;
 pub use parquet_handler::*;
+pub use row_groups::*;
 
 pub use s3_storage::*;
 pub use utils::*;
+pub use validate::*;
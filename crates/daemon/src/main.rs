@@ -1,23 +1,87 @@
 use daemon::{
-    create_folder, get_config_info, get_coordinates, send_parquet_files, setup_logger,
-    subfolder_exists, upload_to_s3, Cli, ForecastService, ObservationService, RateLimiter,
-    S3Storage, XmlFetcher,
+    cached_coordinates, create_folder, fetch_weather_files, forecast_source, get_config_info,
+    get_coordinates, hour_start, merge_hourly_file, observation_source, parquet_file_path,
+    prune_cycle_locks, prune_parquet, run_cycles, run_file_path, send_parquet_files, setup_logger,
+    shutdown_signal, subfolder_exists, upload_to_s3, validate_config, Cli, CoordinateCache,
+    CycleClaim, CycleLock, ForecastService, HourlyWrite, ObservationService, PartialFiles,
+    RateLimiter, S3Storage, StationsTable, XmlFetcher, COORDINATE_CACHE_FILE,
 };
-use slog::{debug, error, info, Logger};
-use std::{sync::Arc, time::Duration};
-use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use slog::{debug, error, info, warn, Logger};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+use time::OffsetDateTime;
 use tokio::sync::Mutex;
-use tokio::time::interval;
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
     let cli = get_config_info();
     let logger = setup_logger(&cli);
 
+    if cli.validate_config {
+        validate_config(&cli, &logger).await?;
+        info!(logger, "Config is valid");
+        return Ok(());
+    }
+
     info!(logger, "NOAA Daemon starting...");
     info!(logger, "  Oracle URL: {}", cli.base_url());
     info!(logger, "  Data dir: {}", cli.data_dir());
+    info!(logger, "  Data layout: {}", cli.layout()?);
     info!(logger, "  Fetch interval: {} seconds", cli.sleep_interval());
+    if let Some(days) = cli.retention_days.filter(|days| *days > 0) {
+        info!(logger, "  Retention: {} days", days);
+        if cli.prune_require_s3() && cli.s3_bucket.is_none() {
+            warn!(
+                logger,
+                "  Pruning requires S3 confirmation but S3 is disabled, nothing will be pruned"
+            );
+        }
+    }
+    info!(
+        logger,
+        "  Forecast batch size: {} stations",
+        cli.forecast_batch_size()
+    );
+    info!(
+        logger,
+        "  Forecast concurrency: {} batches",
+        cli.forecast_concurrency()
+    );
+    info!(
+        logger,
+        "  Parquet row group size: {} rows",
+        cli.parquet_row_group_size()
+    );
+    info!(logger, "  Forecast source: {}", cli.forecast_source());
+    info!(logger, "  NOAA base URL: {}", cli.noaa_base_url()?);
+    info!(logger, "  Observation source: {}", cli.observation_source());
+    info!(
+        logger,
+        "  Coordinate cache TTL: {} seconds",
+        cli.coordinate_cache_ttl().as_secs()
+    );
+
+    // A stations file that can't be read is a config mistake, fail at startup rather than
+    // quietly writing parquet with the gaps it was meant to fill
+    let stations = match cli.stations_file {
+        Some(ref path) => {
+            let stations = StationsTable::load(path)?;
+            info!(
+                logger,
+                "  Stations file: {} ({} stations)",
+                path,
+                stations.len()
+            );
+            Some(stations)
+        }
+        None => None,
+    };
 
     if let Some(ref bucket) = cli.s3_bucket {
         info!(logger, "  S3 bucket: {}", bucket);
@@ -28,91 +92,239 @@ async fn main() -> Result<(), anyhow::Error> {
         info!(logger, "  S3 disabled, using local storage only");
     }
 
+    // Misconfigurations stop startup here, all reported at once, instead of partway into a cycle
+    validate_config(&cli, &logger).await?;
+
     let rate_limiter = Arc::new(Mutex::new(RateLimiter::new(
         cli.token_capacity(),
         cli.refill_rate(),
     )));
+    // One client for every cycle and fetch task, so requests reuse its keep-alive connections
+    let http_pool = cli.http_pool();
+    info!(
+        logger,
+        "  HTTP pool: {} idle connections per host, {} second idle timeout",
+        http_pool.max_idle_per_host,
+        http_pool.idle_timeout.as_secs()
+    );
+    let proxy = cli.proxy();
+    let logged_proxy = proxy.redacted();
+    info!(
+        logger,
+        "  Proxy: http {:?}, https {:?}, no_proxy {:?}",
+        logged_proxy.http,
+        logged_proxy.https,
+        logged_proxy.no_proxy
+    );
+    let fetcher = Arc::new(XmlFetcher::new(
+        logger.clone(),
+        cli.user_agent(),
+        rate_limiter,
+        http_pool,
+        &proxy,
+    )?);
 
     let s3_storage = if let Some(ref bucket) = cli.s3_bucket {
         Some(
-            S3Storage::new(bucket.clone(), cli.s3_endpoint.clone(), logger.clone())
-                .await
-                .expect("Failed to initialize S3 storage"),
+            S3Storage::new(
+                bucket.clone(),
+                cli.s3_endpoint.clone(),
+                &proxy,
+                logger.clone(),
+            )
+            .await?,
         )
     } else {
         None
     };
 
-    process_weather_data_hourly(cli, logger, Arc::clone(&rate_limiter), s3_storage).await;
-
-    Ok(())
+    if cli.once {
+        info!(logger, "  Running a single cycle");
+    }
+    process_weather_data(cli, logger, fetcher, s3_storage, stations).await
 }
 
-async fn process_weather_data_hourly(
+async fn process_weather_data(
     cli: Cli,
     logger: Logger,
-    rate_limit: Arc<Mutex<RateLimiter>>,
+    fetcher: Arc<XmlFetcher>,
     s3_storage: Option<S3Storage>,
-) {
+    stations: Option<StationsTable>,
+) -> Result<(), anyhow::Error> {
     let sleep_between_checks = cli.sleep_interval();
-    info!(
-        logger,
-        "Wait time between data pulls: {} seconds", sleep_between_checks
+    if !cli.once {
+        info!(
+            logger,
+            "Wait time between data pulls: {} seconds", sleep_between_checks
+        );
+    }
+
+    let coordinate_cache = CoordinateCache::new(
+        Path::new(&cli.data_dir()).join(COORDINATE_CACHE_FILE),
+        cli.coordinate_cache_ttl(),
     );
+    // Only the first cycle skips the cache when asked to refresh
+    let refresh_coordinates = AtomicBool::new(cli.refresh_coordinates);
 
-    let mut check_channel_interval = interval(Duration::from_secs(sleep_between_checks));
-    loop {
-        tokio::select! {
-            _ = check_channel_interval.tick() => {
-                match process_data(cli.clone(), logger.clone(), rate_limit.clone(), s3_storage.as_ref()).await {
-                    Ok(_) => info!(logger, "Finished processing data, waiting {} seconds for next run", sleep_between_checks),
-                    Err(err) => error!(&logger, "Error processing data: {}", err)
-                }
-            }
-        }
-    }
+    run_cycles(
+        cli.once,
+        Duration::from_secs(sleep_between_checks),
+        Duration::from_secs(cli.shutdown_timeout()),
+        shutdown_signal(),
+        &logger,
+        || {
+            process_data(
+                cli.clone(),
+                logger.clone(),
+                fetcher.clone(),
+                s3_storage.as_ref(),
+                stations.as_ref(),
+                &coordinate_cache,
+                refresh_coordinates.swap(false, Ordering::Relaxed),
+            )
+        },
+    )
+    .await
 }
 
 async fn process_data(
     cli: Cli,
     logger: Logger,
-    rate_limiter: Arc<Mutex<RateLimiter>>,
+    fetcher: Arc<XmlFetcher>,
     s3_storage: Option<&S3Storage>,
+    stations: Option<&StationsTable>,
+    coordinate_cache: &CoordinateCache,
+    refresh_coordinates: bool,
 ) -> Result<(), anyhow::Error> {
     let logger_cpy = &logger.clone();
-    let fetcher = Arc::new(XmlFetcher::new(
-        logger.clone(),
-        cli.user_agent(),
-        rate_limiter,
-    ));
-
-    let city_weather_coordinates = get_coordinates(fetcher.clone()).await?;
-    debug!(logger_cpy, "coordinates: {}", city_weather_coordinates);
-
-    let current_utc_time: String = OffsetDateTime::now_utc().format(&Rfc3339)?;
+    let generated_at = OffsetDateTime::now_utc();
     let root_path = cli.data_dir();
     create_folder(&root_path, logger_cpy);
 
-    let current_date = OffsetDateTime::now_utc().date();
-    let subfolder = format!("{}/{}", root_path, current_date);
-    if !subfolder_exists(&subfolder) {
-        create_folder(&subfolder, logger_cpy)
+    // Replicas sharing a data dir take turns, whoever claims the cycle first processes it
+    let cycle_lock = match CycleLock::try_acquire(
+        Path::new(&root_path),
+        Duration::from_secs(cli.sleep_interval()),
+        generated_at,
+    )? {
+        CycleClaim::Acquired(lock) => lock,
+        CycleClaim::Held(path) => {
+            info!(
+                logger_cpy,
+                "cycle locked by another instance ({}), skipping",
+                path.display()
+            );
+            return Ok(());
+        }
+        CycleClaim::Completed(path) => {
+            info!(
+                logger_cpy,
+                "cycle already processed by another instance ({}), skipping",
+                path.display()
+            );
+            return Ok(());
+        }
+    };
+    debug!(
+        logger_cpy,
+        "claimed cycle lock: {}",
+        cycle_lock.path().display()
+    );
+
+    let mut city_weather_coordinates = cached_coordinates(
+        coordinate_cache,
+        refresh_coordinates,
+        SystemTime::now(),
+        || get_coordinates(fetcher.clone(), logger_cpy),
+        logger_cpy,
+    )
+    .await?;
+    if let Some(stations) = stations {
+        let enriched = stations.enrich(&mut city_weather_coordinates);
+        info!(
+            logger_cpy,
+            "enriched {} stations from the stations file", enriched
+        );
     }
+    debug!(logger_cpy, "coordinates: {}", city_weather_coordinates);
 
-    // Write forecasts directly to parquet file (streaming, low memory)
-    let forecast_parquet = format!("{}/forecasts_{}.parquet", subfolder, current_utc_time);
-    let forecast_service = ForecastService::new(logger.clone(), fetcher.clone());
-    forecast_service
-        .get_forecasts_to_file(&city_weather_coordinates, &forecast_parquet)
-        .await?;
-    debug!(logger_cpy, "forecasts written to: {}", forecast_parquet);
+    let layout = cli.layout()?;
+    let hourly_files = cli.hourly_files();
 
-    // Write observations directly to parquet file
-    let observation_parquet = format!("{}/observations_{}.parquet", subfolder, current_utc_time);
-    let observation_service = ObservationService::new(logger, fetcher);
-    observation_service
-        .get_observations_to_file(&city_weather_coordinates, &observation_parquet)
+    // With hourly files a re-run in the same hour merges into that hour's files
+    let file_time = if hourly_files {
+        hour_start(generated_at)
+    } else {
+        generated_at
+    };
+    let forecast_parquet = parquet_file_path(&root_path, layout, "forecasts", file_time)?;
+    let observation_parquet = parquet_file_path(&root_path, layout, "observations", file_time)?;
+    for file_path in [&forecast_parquet, &observation_parquet] {
+        if let Some(folder) = Path::new(file_path)
+            .parent()
+            .and_then(|parent| parent.to_str())
+        {
+            if !subfolder_exists(folder) {
+                create_folder(folder, logger_cpy)
+            }
+        }
+    }
+
+    let forecast_service = ForecastService::new(
+        logger.clone(),
+        forecast_source(
+            cli.forecast_source(),
+            cli.noaa_base_url()?,
+            fetcher.clone(),
+            logger.clone(),
+        ),
+        cli.forecast_batch_size(),
+        cli.forecast_concurrency(),
+        cli.parquet_row_group_size(),
+    );
+    let observation_service = ObservationService::new(
+        logger.clone(),
+        observation_source(cli.observation_source(), fetcher, logger),
+        cli.parquet_row_group_size(),
+    );
+    let (forecast_output, observation_output) = if hourly_files {
+        (
+            run_file_path(&forecast_parquet),
+            run_file_path(&observation_parquet),
+        )
+    } else {
+        (forecast_parquet.clone(), observation_parquet.clone())
+    };
+    let partial_files =
+        PartialFiles::new(vec![forecast_output.clone(), observation_output.clone()]);
+    let forecast_stations = partial_files
+        .write(fetch_weather_files(
+            &forecast_service,
+            &observation_service,
+            &city_weather_coordinates,
+            &forecast_output,
+            &observation_output,
+        ))
         .await?;
+    if hourly_files {
+        for (output, parquet) in [
+            (&forecast_output, &forecast_parquet),
+            (&observation_output, &observation_parquet),
+        ] {
+            match merge_hourly_file(Path::new(output), Path::new(parquet))? {
+                HourlyWrite::Replaced(reason) => warn!(
+                    logger_cpy,
+                    "replaced {} instead of appending: {}", parquet, reason
+                ),
+                write => debug!(logger_cpy, "merged run into {}: {:?}", parquet, write),
+            }
+        }
+    }
+    partial_files.keep();
+    debug!(
+        logger_cpy,
+        "forecasts for {} stations written to: {}", forecast_stations, forecast_parquet
+    );
     debug!(
         logger_cpy,
         "observations written to: {}", observation_parquet
@@ -129,7 +341,7 @@ async fn process_data(
 
     // Also upload to S3 for archival if configured
     if let Some(s3) = s3_storage {
-        let date_folder = current_date.to_string();
+        let date_folder = generated_at.date().to_string();
         upload_to_s3(
             s3,
             logger_cpy,
@@ -140,5 +352,22 @@ async fn process_data(
         .await?;
     }
 
-    Ok(())
+    // Pruning problems shouldn't fail the cycle that already fetched and uploaded
+    if let Some(retention) = cli.retention() {
+        if let Err(e) = prune_parquet(
+            Path::new(&root_path),
+            retention,
+            SystemTime::now(),
+            cli.prune_require_s3(),
+            logger_cpy,
+        ) {
+            error!(logger_cpy, "error pruning parquet files: {}", e);
+        }
+        match prune_cycle_locks(Path::new(&root_path), retention, SystemTime::now()) {
+            Ok(pruned) => debug!(logger_cpy, "pruned {} cycle lock files", pruned),
+            Err(e) => error!(logger_cpy, "error pruning cycle lock files: {}", e),
+        }
+    }
+
+    cycle_lock.complete()
 }
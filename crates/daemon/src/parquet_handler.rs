@@ -1,8 +1,13 @@
-use std::path::Path;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
 use anyhow::{anyhow, Error};
+use noaa_oracle_core::{sha256_file, DataLayout, CONTENT_SHA256_HEADER, DATA_LAYOUT_HEADER};
 use reqwest::{multipart, Body, Client};
-use slog::{error, info, Logger};
+use slog::{debug, error, info, warn, Logger};
 use tokio::fs::File as TokioFile;
 use tokio_util::codec::{BytesCodec, FramedRead};
 
@@ -27,14 +32,118 @@ pub async fn upload_to_s3(
 
     s3.upload_parquet(Path::new(observation_path), date_folder, obs_filename)
         .await?;
+    mark_uploaded(Path::new(observation_path))?;
 
     s3.upload_parquet(Path::new(forecast_path), date_folder, forecast_filename)
         .await?;
+    mark_uploaded(Path::new(forecast_path))?;
 
     info!(logger, "Uploaded parquet files to S3");
     Ok(())
 }
 
+/// Sidecar written next to a parquet file once S3 has it, `<file>.parquet.uploaded`
+pub fn uploaded_marker(parquet_path: &Path) -> PathBuf {
+    let mut marker = parquet_path.as_os_str().to_owned();
+    marker.push(".uploaded");
+    PathBuf::from(marker)
+}
+
+pub fn mark_uploaded(parquet_path: &Path) -> Result<(), Error> {
+    let marker = uploaded_marker(parquet_path);
+    fs::write(&marker, b"")
+        .map_err(|e| anyhow!("error writing upload marker {}: {}", marker.display(), e))
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct PruneReport {
+    pub pruned: Vec<PathBuf>,
+    pub reclaimed_bytes: u64,
+    /// Old enough to prune but kept because S3 hasn't confirmed them
+    pub unconfirmed: usize,
+}
+
+/// Deletes parquet files under `data_dir` last modified more than `retention` before `now`,
+/// anything inside the retention window is always kept. With `require_uploaded` a file is
+/// only deleted once its upload marker exists. Walks every folder so it works for either
+/// data layout, folders left empty are removed too.
+pub fn prune_parquet(
+    data_dir: &Path,
+    retention: Duration,
+    now: SystemTime,
+    require_uploaded: bool,
+    logger: &Logger,
+) -> Result<PruneReport, Error> {
+    let cutoff = now
+        .checked_sub(retention)
+        .ok_or_else(|| anyhow!("retention reaches before the unix epoch"))?;
+    let mut report = PruneReport::default();
+    prune_folder(data_dir, cutoff, require_uploaded, logger, &mut report)?;
+    info!(
+        logger,
+        "pruned {} parquet files, reclaimed {} bytes",
+        report.pruned.len(),
+        report.reclaimed_bytes
+    );
+    if report.unconfirmed > 0 {
+        info!(
+            logger,
+            "kept {} expired parquet files not yet confirmed in S3", report.unconfirmed
+        );
+    }
+    Ok(report)
+}
+
+fn prune_folder(
+    folder: &Path,
+    cutoff: SystemTime,
+    require_uploaded: bool,
+    logger: &Logger,
+    report: &mut PruneReport,
+) -> Result<(), Error> {
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            prune_folder(&path, cutoff, require_uploaded, logger, report)?;
+            // Only succeeds once the folder is empty
+            if fs::remove_dir(&path).is_ok() {
+                debug!(logger, "removed empty folder: {}", path.display());
+            }
+            continue;
+        }
+        if path
+            .extension()
+            .is_none_or(|extension| extension != "parquet")
+        {
+            continue;
+        }
+        let metadata = fs::metadata(&path)?;
+        if metadata.modified()? >= cutoff {
+            continue;
+        }
+        let marker = uploaded_marker(&path);
+        if require_uploaded && !marker.exists() {
+            report.unconfirmed += 1;
+            continue;
+        }
+        fs::remove_file(&path)?;
+        if let Err(e) = fs::remove_file(&marker) {
+            if e.kind() != io::ErrorKind::NotFound {
+                error!(logger, "error removing {}: {}", marker.display(), e);
+            }
+        }
+        info!(
+            logger,
+            "pruned {} ({} bytes)",
+            path.display(),
+            metadata.len()
+        );
+        report.reclaimed_bytes += metadata.len();
+        report.pruned.push(path);
+    }
+    Ok(())
+}
+
 pub async fn send_parquet_files(
     cli: &Cli,
     logger: &Logger,
@@ -57,40 +166,79 @@ pub async fn send_parquet_files(
     let observation_full_path = get_full_path(observation_relative_file_path.clone());
     let forecast_full_path = get_full_path(forecast_relative_file_path_file.clone());
 
+    let layout = cli.layout()?;
     let url_observ = format!("{}/file/{}", base_url, observation_filename);
     let url_forcast = format!("{}/file/{}", base_url, forecast_filename);
 
-    match send_file_to_endpoint(
+    if let Err(e) = send_file_with_retries(
         logger,
         &observation_full_path,
         observation_filename,
         &url_observ,
+        layout,
     )
     .await
     {
-        Ok(_) => {}
-        Err(e) => {
-            error!(logger, "failed to upload observations: {}", e)
-        }
+        error!(logger, "failed to upload observations: {}", e)
     }
-    match send_file_to_endpoint(logger, &forecast_full_path, forecast_filename, &url_forcast).await
+    if let Err(e) = send_file_with_retries(
+        logger,
+        &forecast_full_path,
+        forecast_filename,
+        &url_forcast,
+        layout,
+    )
+    .await
     {
-        Ok(_) => {}
-        Err(e) => {
-            error!(logger, "failed to upload forecasts: {}", e)
-        }
+        error!(logger, "failed to upload forecasts: {}", e)
     }
     Ok(())
 }
 
+/// Attempts per file before giving up, a rejected checksum usually means a cut off transfer
+const UPLOAD_ATTEMPTS: usize = 3;
+
+async fn send_file_with_retries(
+    logger: &Logger,
+    file_path: &str,
+    file_name: &str,
+    endpoint_url: &str,
+    layout: DataLayout,
+) -> Result<(), Error> {
+    let mut attempt = 1;
+    loop {
+        match send_file_to_endpoint(logger, file_path, file_name, endpoint_url, layout).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < UPLOAD_ATTEMPTS => {
+                warn!(
+                    logger,
+                    "upload attempt {}/{} of {} failed, retrying: {}",
+                    attempt,
+                    UPLOAD_ATTEMPTS,
+                    file_name,
+                    e
+                );
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 async fn send_file_to_endpoint(
     logger: &Logger,
     file_path: &str,
     file_name: &str,
     endpoint_url: &str,
+    layout: DataLayout,
 ) -> Result<(), anyhow::Error> {
     let client = Client::new();
 
+    let checksum_path = file_path.to_owned();
+    let checksum = tokio::task::spawn_blocking(move || sha256_file(checksum_path))
+        .await?
+        .map_err(|e| anyhow!("error hashing file to upload: {}", e))?;
+
     let file = TokioFile::open(file_path)
         .await
         .map_err(|e| anyhow!("error opening file to upload: {}", e))?;
@@ -107,20 +255,99 @@ async fn send_file_to_endpoint(
     info!(logger, "sending file to endpoint: {}", endpoint_url);
     let response = client
         .post(endpoint_url)
+        // Lets the oracle reject uploads when the two services disagree on the layout
+        .header(DATA_LAYOUT_HEADER, layout.to_string())
+        // Lets the oracle reject a truncated or corrupted upload instead of storing it
+        .header(CONTENT_SHA256_HEADER, checksum)
         .multipart(form)
         .send()
         .await
         .map_err(|e| anyhow!("error sending file to api: {}", e))?;
 
-    if response.status().is_success() {
-        info!(logger, "file successfully uploaded.");
-    } else {
-        error!(
-            logger,
-            "failed to upload the file. status code: {:?}",
-            response.status()
-        );
+    let status = response.status();
+    if !status.is_success() {
+        let reason = response.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "failed to upload the file. status code: {:?} {}",
+            status,
+            reason
+        ));
     }
+    info!(logger, "file successfully uploaded.");
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn data_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("daemon_prune_{}_{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("2024-08-12")).unwrap();
+        dir
+    }
+
+    fn write_parquet(dir: &Path, name: &str, contents: &[u8]) -> PathBuf {
+        let path = dir.join("2024-08-12").join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_prune_only_removes_files_past_retention() {
+        let logger = Logger::root(slog::Discard, o!());
+        let dir = data_dir("cutoff");
+        let old = write_parquet(&dir, "forecasts_old.parquet", b"0123456789");
+        let now = SystemTime::now();
+        let recent = write_parquet(&dir, "observations_recent.parquet", b"01234");
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(now - DAY * 10)
+            .unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&recent)
+            .unwrap()
+            .set_modified(now - DAY * 6)
+            .unwrap();
+
+        let report = prune_parquet(&dir, DAY * 7, now, false, &logger).unwrap();
+
+        assert_eq!(report.pruned, vec![old.clone()]);
+        assert_eq!(report.reclaimed_bytes, 10);
+        assert!(!old.exists());
+        assert!(recent.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_requires_upload_marker_when_asked() {
+        let logger = Logger::root(slog::Discard, o!());
+        let dir = data_dir("uploaded");
+        let uploaded = write_parquet(&dir, "forecasts_uploaded.parquet", b"012");
+        let pending = write_parquet(&dir, "observations_pending.parquet", b"0123");
+        mark_uploaded(&uploaded).unwrap();
+        let later = SystemTime::now() + DAY * 30;
+
+        let report = prune_parquet(&dir, DAY * 7, later, true, &logger).unwrap();
+
+        assert_eq!(report.pruned, vec![uploaded.clone()]);
+        assert_eq!(report.unconfirmed, 1);
+        assert!(!uploaded.exists());
+        assert!(!uploaded_marker(&uploaded).exists());
+        assert!(pending.exists());
+
+        // Without the guard the unconfirmed file goes too, along with its emptied folder
+        let report = prune_parquet(&dir, DAY * 7, later, false, &logger).unwrap();
+        assert_eq!(report.pruned, vec![pending]);
+        assert!(!dir.join("2024-08-12").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
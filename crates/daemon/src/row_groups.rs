@@ -0,0 +1,145 @@
+use std::fs::File;
+
+use anyhow::{anyhow, Error};
+use parquet::{file::writer::SerializedFileWriter, record::RecordWriter};
+
+/// Default rows buffered before they are written out as one row group
+pub const DEFAULT_PARQUET_ROW_GROUP_SIZE: usize = 100_000;
+
+/// Buffers rows and writes them out as a row group once `row_group_size` rows are waiting,
+/// so small fetch batches don't leave the file with many tiny row groups the oracle has to
+/// scan one by one. A pushed batch is never split across row groups, which keeps a station's
+/// rows together for the hourly file merge, so at most `row_group_size` plus one batch of
+/// rows is held in memory.
+pub struct RowGroupWriter<T> {
+    writer: SerializedFileWriter<File>,
+    rows: Vec<T>,
+    row_group_size: usize,
+}
+
+impl<T> RowGroupWriter<T>
+where
+    for<'a> &'a [T]: RecordWriter<T>,
+{
+    pub fn new(writer: SerializedFileWriter<File>, row_group_size: usize) -> Self {
+        Self {
+            writer,
+            rows: Vec::new(),
+            row_group_size: row_group_size.max(1),
+        }
+    }
+
+    /// Adds `rows` to the buffer, returning how many rows were written to the file, 0 when
+    /// they are still waiting on more. After an error the writer is unusable and the buffered
+    /// rows are dropped, `close` still finishes the file with the row groups already written.
+    pub fn push(&mut self, rows: Vec<T>) -> Result<usize, Error> {
+        self.rows.extend(rows);
+        if self.rows.len() < self.row_group_size {
+            return Ok(0);
+        }
+        self.flush()
+    }
+
+    /// Writes whatever is still buffered and closes the file
+    pub fn close(mut self) -> Result<(), Error> {
+        self.flush()?;
+        self.writer
+            .close()
+            .map_err(|e| anyhow!("failed to close parquet writer: {}", e))?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<usize, Error> {
+        if self.rows.is_empty() {
+            return Ok(0);
+        }
+        let rows = std::mem::take(&mut self.rows);
+        let mut row_group = self
+            .writer
+            .next_row_group()
+            .map_err(|e| anyhow!("failed to create row group: {}", e))?;
+        rows.as_slice()
+            .write_to_row_group(&mut row_group)
+            .map_err(|e| anyhow!("failed to write row group: {}", e))?;
+        row_group
+            .close()
+            .map_err(|e| anyhow!("failed to close row group: {}", e))?;
+        Ok(rows.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use parquet::file::{
+        properties::WriterProperties,
+        reader::{FileReader, SerializedFileReader},
+    };
+    use parquet_derive::ParquetRecordWriter;
+
+    #[derive(ParquetRecordWriter)]
+    struct Reading {
+        station_id: String,
+        temperature_value: f64,
+    }
+
+    fn readings(count: usize) -> Vec<Reading> {
+        (0..count)
+            .map(|index| Reading {
+                station_id: format!("K{:03}", index),
+                temperature_value: index as f64,
+            })
+            .collect()
+    }
+
+    /// Writes `total` rows pushed `batch` at a time, returning the file's row group sizes
+    fn write_file(name: &str, total: usize, batch: usize, row_group_size: usize) -> Vec<i64> {
+        let path = std::env::temp_dir().join(format!(
+            "daemon_row_groups_{}_{}.parquet",
+            name,
+            std::process::id()
+        ));
+        let schema = readings(1).as_slice().schema().unwrap();
+        let props = Arc::new(WriterProperties::builder().build());
+        let file_writer =
+            SerializedFileWriter::new(File::create(&path).unwrap(), schema, props).unwrap();
+        let mut writer = RowGroupWriter::new(file_writer, row_group_size);
+        let mut rows = readings(total);
+        while !rows.is_empty() {
+            let rest = rows.split_off(batch.min(rows.len()));
+            writer.push(rows).unwrap();
+            rows = rest;
+        }
+        writer.close().unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let sizes = reader
+            .metadata()
+            .row_groups()
+            .iter()
+            .map(|row_group| row_group.num_rows())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        sizes
+    }
+
+    #[test]
+    fn test_small_batches_are_buffered_into_full_row_groups() {
+        let sizes = write_file("small_batches", 250, 10, 100);
+        assert_eq!(sizes, vec![100, 100, 50]);
+    }
+
+    #[test]
+    fn test_input_under_the_row_group_size_is_one_row_group() {
+        let sizes = write_file("one_group", 40, 10, 100);
+        assert_eq!(sizes, vec![40]);
+    }
+
+    #[test]
+    fn test_batches_are_not_split_across_row_groups() {
+        let sizes = write_file("uneven_batches", 100, 30, 50);
+        assert_eq!(sizes, vec![60, 40]);
+    }
+}
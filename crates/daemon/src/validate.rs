@@ -0,0 +1,146 @@
+use noaa_oracle_core::{check_writable_dir, ConfigProblems};
+use slog::Logger;
+
+use crate::{Cli, S3Storage, StationsTable};
+
+/// Checks the settings that otherwise only fail once a cycle is running: the data dir has to
+/// be writable, the stations file has to load and, with S3 enabled, the credentials have to
+/// reach the bucket. Every problem found is returned, not just the first
+pub async fn validate_config(cli: &Cli, logger: &Logger) -> Result<(), ConfigProblems> {
+    let mut problems = ConfigProblems::default();
+
+    let data_dir = cli.data_dir();
+    problems.check(
+        "data_dir",
+        check_writable_dir(&data_dir).map_err(|e| format!("{} is not writable: {}", data_dir, e)),
+    );
+    problems.check("layout", cli.layout().map(|_| ()));
+    problems.check("noaa_base_url", cli.noaa_base_url().map(|_| ()));
+    if let Some(ref path) = cli.stations_file {
+        problems.check("stations_file", StationsTable::load(path).map(|_| ()));
+    }
+    if let Some(ref bucket) = cli.s3_bucket {
+        let storage = S3Storage::new(
+            bucket.clone(),
+            cli.s3_endpoint.clone(),
+            &cli.proxy(),
+            logger.clone(),
+        )
+        .await;
+        let access = match storage {
+            Ok(storage) => storage.check_access().await,
+            Err(e) => Err(e),
+        };
+        problems.check("s3_bucket", access);
+    }
+
+    problems.into_result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use slog::o;
+    use std::{fs, path::PathBuf};
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("daemon_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn logger() -> Logger {
+        Logger::root(slog::Discard, o!())
+    }
+
+    fn cli(data_dir: &str) -> Cli {
+        Cli {
+            data_dir: Some(data_dir.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_valid_config_passes() {
+        let dir = test_dir("validate_ok");
+        let data_dir = dir.join("data");
+
+        validate_config(&cli(data_dir.to_str().unwrap()), &logger())
+            .await
+            .unwrap();
+        assert!(data_dir.is_dir());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unwritable_data_dir_is_reported() {
+        let dir = test_dir("validate_data_dir");
+        let data_dir = dir.join("data");
+        fs::write(&data_dir, "not a dir").unwrap();
+
+        let err = validate_config(&cli(data_dir.to_str().unwrap()), &logger())
+            .await
+            .unwrap_err();
+        assert_eq!(err.problems.len(), 1);
+        assert!(err.problems[0].starts_with("data_dir: "));
+        assert!(err.problems[0].contains("is not writable: exists but is not a directory"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_stations_file_is_reported() {
+        let dir = test_dir("validate_stations");
+        let mut cli = cli(dir.join("data").to_str().unwrap());
+        cli.stations_file = Some(dir.join("stations.csv").to_str().unwrap().to_string());
+
+        let err = validate_config(&cli, &logger()).await.unwrap_err();
+        assert_eq!(err.problems.len(), 1);
+        assert!(err.problems[0].starts_with("stations_file: error opening stations file"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_s3_bucket_is_reported() {
+        let dir = test_dir("validate_s3");
+        let mut cli = cli(dir.join("data").to_str().unwrap());
+        cli.s3_bucket = Some(String::from("noaa-weather"));
+        // Nothing listens on port 1
+        cli.s3_endpoint = Some(String::from("http://127.0.0.1:1"));
+
+        let err = validate_config(&cli, &logger()).await.unwrap_err();
+        assert_eq!(err.problems.len(), 1);
+        assert!(err.problems[0].starts_with("s3_bucket: "));
+        assert!(err.problems[0].contains("noaa-weather"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_every_problem_is_reported_together() {
+        let dir = test_dir("validate_all");
+        let data_dir = dir.join("data");
+        fs::write(&data_dir, "not a dir").unwrap();
+        let mut cli = cli(data_dir.to_str().unwrap());
+        cli.layout = Some(String::from("flat"));
+        cli.noaa_base_url = Some(String::from("not a url"));
+        cli.stations_file = Some(dir.join("stations.csv").to_str().unwrap().to_string());
+
+        let err = validate_config(&cli, &logger()).await.unwrap_err();
+        let settings: Vec<&str> = err
+            .problems
+            .iter()
+            .map(|problem| problem.split(':').next().unwrap())
+            .collect();
+        assert_eq!(
+            settings,
+            vec!["data_dir", "layout", "noaa_base_url", "stations_file"]
+        );
+        assert!(err.to_string().starts_with("invalid config, 4 problem(s):"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,2 @@
+-- Unit the weather temperatures were stored in, NOAA reports in fahrenheit by default
+ALTER TABLE weather ADD COLUMN temp_unit_code TEXT NOT NULL DEFAULT 'fahrenheit';
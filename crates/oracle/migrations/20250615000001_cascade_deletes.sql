@@ -0,0 +1,57 @@
+-- Rebuild the child tables so deleting an event removes its entries, choices and weather links.
+-- SQLite can't alter constraints in place and the migrator runs this inside a transaction, so
+-- foreign keys stay on: checks are deferred to commit and events_entries is rebuilt while the
+-- old expected_observations (no cascade) still points at it, so no choices get removed.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE events_entries_old AS SELECT * FROM events_entries;
+DROP TABLE events_entries;
+CREATE TABLE events_entries (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    score INTEGER NOT NULL DEFAULT 0,
+    base_score INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    updated_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
+INSERT INTO events_entries (id, event_id, score, base_score, created_at, updated_at)
+    SELECT id, event_id, score, base_score, created_at, updated_at FROM events_entries_old;
+DROP TABLE events_entries_old;
+CREATE INDEX idx_events_entries_event_id ON events_entries(event_id);
+
+CREATE TABLE expected_observations_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entry_id TEXT NOT NULL REFERENCES events_entries(id) ON DELETE CASCADE,
+    station TEXT NOT NULL,
+    temp_low TEXT CHECK(temp_low IN ('over', 'par', 'under')),
+    temp_high TEXT CHECK(temp_high IN ('over', 'par', 'under')),
+    wind_speed TEXT CHECK(wind_speed IN ('over', 'par', 'under')),
+    wind_direction TEXT CHECK(wind_direction IN ('over', 'par', 'under')),
+    rain_amt TEXT CHECK(rain_amt IN ('over', 'par', 'under')),
+    snow_amt TEXT CHECK(snow_amt IN ('over', 'par', 'under')),
+    humidity TEXT CHECK(humidity IN ('over', 'par', 'under'))
+);
+INSERT INTO expected_observations_new (
+    id, entry_id, station, temp_low, temp_high, wind_speed,
+    wind_direction, rain_amt, snow_amt, humidity
+)
+    SELECT id, entry_id, station, temp_low, temp_high, wind_speed,
+           wind_direction, rain_amt, snow_amt, humidity
+    FROM expected_observations;
+DROP TABLE expected_observations;
+ALTER TABLE expected_observations_new RENAME TO expected_observations;
+CREATE INDEX idx_expected_observations_entry_id ON expected_observations(entry_id);
+
+CREATE TABLE events_weather_new (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    weather_id TEXT NOT NULL REFERENCES weather(id) ON DELETE CASCADE,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    updated_at INTEGER NOT NULL DEFAULT (unixepoch())
+);
+INSERT INTO events_weather_new (id, event_id, weather_id, created_at, updated_at)
+    SELECT id, event_id, weather_id, created_at, updated_at FROM events_weather;
+DROP TABLE events_weather;
+ALTER TABLE events_weather_new RENAME TO events_weather;
+CREATE INDEX idx_events_weather_event_id ON events_weather(event_id);
+CREATE INDEX idx_events_weather_weather_id ON events_weather(weather_id);
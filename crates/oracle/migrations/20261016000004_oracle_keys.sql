@@ -0,0 +1,10 @@
+-- Every signing key the oracle has used, an event is signed by the key active when it was created.
+-- active_until is NULL for the current key, ranges are half-open [active_from, active_until).
+CREATE TABLE IF NOT EXISTS oracle_keys (
+    pubkey BLOB NOT NULL PRIMARY KEY,
+    active_from INTEGER NOT NULL,
+    active_until INTEGER
+);
+
+-- The key already stored created every existing event
+INSERT INTO oracle_keys (pubkey, active_from) SELECT pubkey, 0 FROM oracle_metadata;
@@ -0,0 +1,2 @@
+-- Unix timestamp after which entries are rejected, NULL uses the start observation date
+ALTER TABLE events ADD COLUMN entry_cutoff INTEGER;
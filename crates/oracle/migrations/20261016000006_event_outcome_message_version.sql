@@ -0,0 +1,2 @@
+-- Layout of the outcome messages the event's locking points commit to, existing events used the unprefixed layout
+ALTER TABLE events ADD COLUMN outcome_message_version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,84 @@
+-- Postgres event store schema, mirrors the SQLite migrations with cascading deletes built in.
+-- Timestamps stay unix seconds and the serialized nonce/announcement/attestation stay opaque bytes.
+CREATE TABLE IF NOT EXISTS oracle_metadata (
+    pubkey BYTEA NOT NULL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT)
+);
+
+CREATE TABLE IF NOT EXISTS events (
+    id TEXT PRIMARY KEY,
+    total_allowed_entries BIGINT NOT NULL,
+    number_of_places_win BIGINT NOT NULL,
+    number_of_values_per_entry BIGINT NOT NULL,
+    signing_date BIGINT NOT NULL,
+    start_observation_date BIGINT NOT NULL,
+    end_observation_date BIGINT NOT NULL,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT),
+    nonce BYTEA NOT NULL,
+    event_announcement BYTEA NOT NULL,
+    locations JSONB NOT NULL,
+    coordinator_pubkey TEXT,
+    attestation_signature BYTEA,
+    scoring_fields JSONB
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_signing_date ON events(signing_date);
+CREATE INDEX IF NOT EXISTS idx_events_attestation ON events(attestation_signature);
+
+CREATE TABLE IF NOT EXISTS events_entries (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    score BIGINT NOT NULL DEFAULT 0,
+    base_score BIGINT NOT NULL DEFAULT 0,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT)
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_entries_event_id ON events_entries(event_id);
+
+CREATE TABLE IF NOT EXISTS expected_observations (
+    id BIGSERIAL PRIMARY KEY,
+    entry_id TEXT NOT NULL REFERENCES events_entries(id) ON DELETE CASCADE,
+    station TEXT NOT NULL,
+    temp_low TEXT CHECK(temp_low IN ('over', 'par', 'under')),
+    temp_high TEXT CHECK(temp_high IN ('over', 'par', 'under')),
+    wind_speed TEXT CHECK(wind_speed IN ('over', 'par', 'under')),
+    wind_direction TEXT CHECK(wind_direction IN ('over', 'par', 'under')),
+    rain_amt TEXT CHECK(rain_amt IN ('over', 'par', 'under')),
+    snow_amt TEXT CHECK(snow_amt IN ('over', 'par', 'under')),
+    humidity TEXT CHECK(humidity IN ('over', 'par', 'under'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_expected_observations_entry_id ON expected_observations(entry_id);
+
+CREATE TABLE IF NOT EXISTS weather (
+    id TEXT PRIMARY KEY,
+    station_id TEXT NOT NULL,
+    observed_date BIGINT,
+    observed_temp_low BIGINT,
+    observed_temp_high BIGINT,
+    observed_wind_speed BIGINT,
+    forecasted_date BIGINT NOT NULL,
+    forecasted_temp_low BIGINT NOT NULL,
+    forecasted_temp_high BIGINT NOT NULL,
+    forecasted_wind_speed BIGINT,
+    temp_unit_code TEXT NOT NULL DEFAULT 'fahrenheit',
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT)
+);
+
+CREATE INDEX IF NOT EXISTS idx_weather_station_id ON weather(station_id);
+
+CREATE TABLE IF NOT EXISTS events_weather (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL REFERENCES events(id) ON DELETE CASCADE,
+    weather_id TEXT NOT NULL REFERENCES weather(id) ON DELETE CASCADE,
+    created_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT),
+    updated_at BIGINT NOT NULL DEFAULT (EXTRACT(EPOCH FROM now())::BIGINT)
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_weather_event_id ON events_weather(event_id);
+CREATE INDEX IF NOT EXISTS idx_events_weather_weather_id ON events_weather(weather_id);
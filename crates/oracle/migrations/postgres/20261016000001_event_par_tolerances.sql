@@ -0,0 +1,2 @@
+-- Per scoring field par band overrides, NULL means every field uses its default band
+ALTER TABLE events ADD COLUMN par_tolerances JSONB;
@@ -0,0 +1,4 @@
+-- How entries are ranked, existing events keep the over/par/under scoring
+ALTER TABLE events ADD COLUMN scoring_mode TEXT NOT NULL DEFAULT 'ternary';
+-- Numeric predictions used by proximity scored events, keyed by scoring field
+ALTER TABLE expected_observations ADD COLUMN predictions JSONB;
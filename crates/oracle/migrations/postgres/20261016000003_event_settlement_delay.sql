@@ -0,0 +1,2 @@
+-- Hours to wait after the end observation date before signing, NULL uses the oracle's configured delay
+ALTER TABLE events ADD COLUMN settlement_delay_hours BIGINT;
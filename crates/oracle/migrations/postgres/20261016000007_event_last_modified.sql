@@ -0,0 +1,12 @@
+-- Unix milliseconds of the last write that changed an event (creation, attestation), so mirrors
+-- can sync incrementally. Existing events count as modified when they were last updated
+ALTER TABLE events ADD COLUMN last_modified BIGINT NOT NULL DEFAULT 0;
+UPDATE events SET last_modified = updated_at * 1000;
+CREATE INDEX idx_events_last_modified ON events(last_modified);
+
+-- Ids of deleted and purged events, kept so mirrors learn about the removal
+CREATE TABLE deleted_events (
+    id TEXT PRIMARY KEY,
+    deleted_at BIGINT NOT NULL
+);
+CREATE INDEX idx_deleted_events_deleted_at ON deleted_events(deleted_at);
@@ -25,10 +25,20 @@ impl IntoResponse for AppError {
         let (status, error_message) = match self.borrow() {
             AppError::Request(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             AppError::WeatherData(e) => match e {
-                weather_data::Error::Query(_) | &weather_data::Error::FileAccess(_) => (
+                weather_data::Error::Query(_)
+                | weather_data::Error::FileAccess(_)
+                | weather_data::Error::Export(_) => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     String::from("internal error"),
                 ),
+                weather_data::Error::RemoteUnavailable(_) => (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    String::from("weather files temporarily unavailable, retry later"),
+                ),
+                weather_data::Error::RemoteMissing(_) => (
+                    StatusCode::NOT_FOUND,
+                    String::from("weather file not found"),
+                ),
                 _ => (StatusCode::BAD_REQUEST, self.to_string()),
             },
             AppError::FileAccess(_) => (StatusCode::BAD_REQUEST, self.to_string()),
@@ -0,0 +1,96 @@
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::sync::{Mutex, PoisonError};
+use time::{Duration, OffsetDateTime};
+use uuid::{Builder, Uuid};
+
+/// Where the oracle reads the current time from, swapped for a `FixedClock` in tests so event
+/// status, entry cutoffs and signing don't depend on when the test runs
+pub trait Clock: Send + Sync {
+    fn now(&self) -> OffsetDateTime;
+}
+
+/// The system's UTC time, what the oracle uses unless told otherwise
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+/// A clock that only moves when `set` or `advance` is called
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<OffsetDateTime>,
+}
+
+impl FixedClock {
+    pub fn new(now: OffsetDateTime) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: OffsetDateTime) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> OffsetDateTime {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Randomness behind random nonces and generated event ids, swapped for a `SeededRng` in tests
+/// to make them reproducible
+pub trait RngSource: Send + Sync {
+    fn fill_bytes(&self, dest: &mut [u8]);
+}
+
+/// The thread-local CSPRNG, what the oracle uses unless told otherwise
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadRng;
+
+impl RngSource for ThreadRng {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        rand::thread_rng().fill_bytes(dest);
+    }
+}
+
+/// Bytes from a fixed seed, the same seed always gives the same sequence. Not for production,
+/// nonces drawn from it are predictable
+#[derive(Debug)]
+pub struct SeededRng {
+    rng: Mutex<StdRng>,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+}
+
+impl RngSource for SeededRng {
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        self.rng
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .fill_bytes(dest);
+    }
+}
+
+/// A UUIDv7 stamped with `clock`'s time and filled from `rng`
+pub fn new_uuid_v7(clock: &dyn Clock, rng: &dyn RngSource) -> Uuid {
+    let millis = (clock.now().unix_timestamp_nanos() / 1_000_000).max(0) as u64;
+    let mut random = [0u8; 10];
+    rng.fill_bytes(&mut random);
+    Builder::from_unix_timestamp_millis(millis, &random).into_uuid()
+}
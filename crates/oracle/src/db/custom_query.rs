@@ -0,0 +1,279 @@
+use crate::weather_data::Error;
+use duckdb::{
+    types::{TimeUnit, Value},
+    Connection,
+};
+use log::debug;
+use serde_json::{Map, Number, Value as JsonValue};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use time::{format_description::well_known::Rfc3339, Date, OffsetDateTime, Time};
+use tokio::sync::mpsc;
+
+/// Default cap on the rows a custom query returns
+pub const DEFAULT_CUSTOM_QUERY_MAX_ROWS: usize = 10_000;
+/// Default cap on how long a custom query runs, loading its files included
+pub const DEFAULT_CUSTOM_QUERY_TIMEOUT_SECS: u64 = 30;
+
+/// Rows buffered ahead of a slow client before the query waits on it
+const ROW_BUFFER: usize = 256;
+
+/// Julian day of 1970-01-01, DuckDB dates count days from it
+const UNIX_EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+/// Leading keywords of the statements a custom query may be
+const READ_KEYWORDS: [&str; 4] = ["SELECT", "WITH", "FROM", "VALUES"];
+
+/// Caps on the SQL run by `POST /query`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomQueryLimits {
+    pub max_rows: usize,
+    pub timeout: Duration,
+}
+
+impl Default for CustomQueryLimits {
+    fn default() -> Self {
+        Self {
+            max_rows: DEFAULT_CUSTOM_QUERY_MAX_ROWS,
+            timeout: Duration::from_secs(DEFAULT_CUSTOM_QUERY_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl CustomQueryLimits {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.max_rows == 0 {
+            return Err(anyhow::anyhow!("custom query max rows must be at least 1"));
+        }
+        if self.timeout.is_zero() {
+            return Err(anyhow::anyhow!("custom query timeout must be at least 1s"));
+        }
+        Ok(())
+    }
+}
+
+/// A custom query's rows as JSON objects, sent as they're read. An `Err` after rows means the
+/// query was cut short by the limits or failed part way
+pub type CustomQueryRows = mpsc::Receiver<Result<Map<String, JsonValue>, Error>>;
+
+/// The statement trimmed of comments and a trailing `;`, rejected unless it's a single query.
+/// It's also run as a subquery, so anything that slips past this fails to parse
+pub fn check_custom_query(sql: &str) -> Result<&str, Error> {
+    let mut rest = sql.trim();
+    loop {
+        if let Some(comment) = rest.strip_prefix("--") {
+            rest = comment
+                .split_once('\n')
+                .map_or("", |(_, after)| after)
+                .trim();
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment
+                .split_once("*/")
+                .map_or("", |(_, after)| after)
+                .trim();
+        } else {
+            break;
+        }
+    }
+    let statement = rest.trim_end_matches(|c: char| c == ';' || c.is_whitespace());
+    if statement.is_empty() {
+        return Err(Error::CustomQuery(String::from("query is empty")));
+    }
+    let keyword = statement
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+    if !READ_KEYWORDS.contains(&keyword.as_str()) {
+        return Err(Error::CustomQuery(format!(
+            "only SELECT queries are allowed, got '{}'",
+            keyword
+        )));
+    }
+    if statement.contains(';') {
+        return Err(Error::CustomQuery(String::from(
+            "only a single statement is allowed",
+        )));
+    }
+    Ok(statement)
+}
+
+/// Loads the files into the `observations` and `forecasts` tables, locks the connection
+/// down, then runs `sql` on a blocking thread, sending rows as they're read. Errors before
+/// the first row come back as the first message.
+pub fn run_custom_query(
+    conn: Connection,
+    sql: String,
+    observation_paths: Vec<String>,
+    forecast_paths: Vec<String>,
+    limits: CustomQueryLimits,
+) -> CustomQueryRows {
+    let (tx, rx) = mpsc::channel(ROW_BUFFER);
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let interrupt = conn.interrupt_handle();
+    let timer = {
+        let timed_out = timed_out.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(limits.timeout).await;
+            timed_out.store(true, Ordering::SeqCst);
+            interrupt.interrupt();
+        })
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let result = stream_rows(
+            &conn,
+            &sql,
+            &observation_paths,
+            &forecast_paths,
+            limits.max_rows,
+            &tx,
+        );
+        timer.abort();
+        if let Err(err) = result {
+            let err = if timed_out.load(Ordering::SeqCst) {
+                Error::CustomQuery(format!(
+                    "query ran longer than {}s",
+                    limits.timeout.as_secs()
+                ))
+            } else {
+                err
+            };
+            // Nothing to do when the client already went away
+            let _ = tx.blocking_send(Err(err));
+        }
+    });
+    rx
+}
+
+fn stream_rows(
+    conn: &Connection,
+    sql: &str,
+    observation_paths: &[String],
+    forecast_paths: &[String],
+    max_rows: usize,
+    tx: &mpsc::Sender<Result<Map<String, JsonValue>, Error>>,
+) -> Result<(), Error> {
+    for (table, paths) in [
+        ("observations", observation_paths),
+        ("forecasts", forecast_paths),
+    ] {
+        if paths.is_empty() {
+            continue;
+        }
+        conn.execute_batch(&format!(
+            "CREATE TABLE {} AS SELECT * FROM read_parquet(['{}'], union_by_name = true);",
+            table,
+            paths.join("', '")
+        ))?;
+    }
+    // Everything the query needs is in memory now, so it gets no way to reach the host
+    conn.execute_batch("SET enable_external_access = false; SET lock_configuration = true;")?;
+
+    let user_error = |e: duckdb::Error| Error::CustomQuery(e.to_string());
+    // One extra row tells a result at the limit apart from one over it
+    let wrapped = format!(
+        "SELECT * FROM ({}\n) AS custom_query LIMIT {}",
+        sql,
+        max_rows + 1
+    );
+    debug!("custom query: {}", wrapped);
+    let mut stmt = conn.prepare(&wrapped).map_err(user_error)?;
+    let mut rows = stmt.query([]).map_err(user_error)?;
+    let columns = rows
+        .as_ref()
+        .map(|stmt| stmt.column_names())
+        .unwrap_or_default();
+
+    let mut sent = 0;
+    while let Some(row) = rows.next().map_err(user_error)? {
+        if sent == max_rows {
+            return Err(Error::CustomQuery(format!(
+                "query returned more than {} rows, add a LIMIT or narrow start and end",
+                max_rows
+            )));
+        }
+        let mut object = Map::with_capacity(columns.len());
+        for (index, column) in columns.iter().enumerate() {
+            let value: Value = row.get(index).map_err(user_error)?;
+            object.insert(column.clone(), to_json(value));
+        }
+        if tx.blocking_send(Ok(object)).is_err() {
+            return Ok(());
+        }
+        sent += 1;
+    }
+    Ok(())
+}
+
+/// JSON for a DuckDB value, times as RFC3339 text and anything without a JSON
+/// counterpart as its text form
+fn to_json(value: Value) -> JsonValue {
+    match value {
+        Value::Null => JsonValue::Null,
+        Value::Boolean(value) => JsonValue::Bool(value),
+        Value::TinyInt(value) => JsonValue::from(value),
+        Value::SmallInt(value) => JsonValue::from(value),
+        Value::Int(value) => JsonValue::from(value),
+        Value::BigInt(value) => JsonValue::from(value),
+        Value::HugeInt(value) => i64::try_from(value)
+            .map(JsonValue::from)
+            .unwrap_or_else(|_| JsonValue::String(value.to_string())),
+        Value::UTinyInt(value) => JsonValue::from(value),
+        Value::USmallInt(value) => JsonValue::from(value),
+        Value::UInt(value) => JsonValue::from(value),
+        Value::UBigInt(value) => JsonValue::from(value),
+        Value::Float(value) => float(value as f64),
+        Value::Double(value) => float(value),
+        Value::Decimal(value) => value.to_string().parse().map_or(JsonValue::Null, float),
+        Value::Timestamp(unit, value) => {
+            OffsetDateTime::from_unix_timestamp_nanos(unit.to_micros(value) as i128 * 1_000)
+                .ok()
+                .and_then(|at| at.format(&Rfc3339).ok())
+                .map_or(JsonValue::Null, JsonValue::String)
+        }
+        Value::Date32(days) => Date::from_julian_day(UNIX_EPOCH_JULIAN_DAY + days)
+            .map_or(JsonValue::Null, |date| JsonValue::String(date.to_string())),
+        Value::Time64(unit, value) => time_of_day(unit, value),
+        Value::Interval {
+            months,
+            days,
+            nanos,
+        } => JsonValue::String(format!("{} months {} days {} ns", months, days, nanos)),
+        Value::Text(value) | Value::Enum(value) => JsonValue::String(value),
+        Value::Blob(value) => JsonValue::String(hex::encode(value)),
+        Value::List(values) | Value::Array(values) => {
+            JsonValue::Array(values.into_iter().map(to_json).collect())
+        }
+        Value::Struct(fields) => JsonValue::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), to_json(value.clone())))
+                .collect(),
+        ),
+        Value::Map(entries) => JsonValue::Array(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    JsonValue::Array(vec![to_json(key.clone()), to_json(value.clone())])
+                })
+                .collect(),
+        ),
+        Value::Union(value) => to_json(*value),
+    }
+}
+
+/// NaN and infinities have no JSON form and come back as null
+fn float(value: f64) -> JsonValue {
+    Number::from_f64(value).map_or(JsonValue::Null, JsonValue::Number)
+}
+
+fn time_of_day(unit: TimeUnit, value: i64) -> JsonValue {
+    let at = Time::MIDNIGHT + time::Duration::microseconds(unit.to_micros(value));
+    JsonValue::String(at.to_string())
+}
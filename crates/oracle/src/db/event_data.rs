@@ -686,9 +686,33 @@ impl EventData {
             let where_clause = format!("events.id IN {}", event_ids_val);
             event_select = event_select.clone().where_(where_clause);
         }
+        let date_bounds = [
+            ("events.end_observation_date >=", filter.observation_after),
+            (
+                "events.start_observation_date <=",
+                filter.observation_before,
+            ),
+            ("events.signing_date >=", filter.signing_after),
+            ("events.signing_date <=", filter.signing_before),
+        ];
+        for (predicate, bound) in date_bounds {
+            if let Some(bound) = bound {
+                let bound = OffsetDateTime::format(bound, &Rfc3339)
+                    .map_err(|e| duckdb::Error::ToSqlConversionFailure(Box::new(e)))?;
+                event_select = event_select
+                    .clone()
+                    .where_(format!("{} '{}'::TIMESTAMPTZ", predicate, bound));
+            }
+        }
+        event_select = event_select
+            .clone()
+            .order_by(filter.sort.order_by("events", filter.direction));
         if let Some(limit) = filter.limit {
             event_select = event_select.clone().limit(limit);
         }
+        if let Some(offset) = filter.offset {
+            event_select = event_select.clone().offset(offset);
+        }
 
         let conn = self.new_readonly_connection_retry().await?;
         let query_str = self.prepare_query(event_select.to_string());
@@ -0,0 +1,221 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use dlctix::musig2::secp256k1::XOnlyPublicKey;
+use std::{
+    io::{BufRead, Write},
+    sync::Arc,
+};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{
+    ActiveEvent, ChangedEvents, CreateEventData, Database, DatabaseSettings, Event,
+    EventAggregates, EventFilter, EventIncludes, EventSummary, ImportReport, MaintenanceReport,
+    MigrationStatus, OracleKey, SignEvent, Weather, WeatherEntry,
+};
+use crate::TemperatureUnit;
+
+/// Persistence for events, entries and the weather attached to them
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    async fn migration_status(&self) -> Result<MigrationStatus>;
+
+    /// Check database connectivity and integrity.
+    async fn health_check(&self) -> Result<()>;
+
+    /// Flush pending writes before shutdown, a no-op for stores without a local write-ahead log
+    async fn checkpoint(&self);
+
+    async fn maintenance(&self, vacuum: bool) -> Result<MaintenanceReport>;
+
+    async fn add_oracle_metadata(&self, pubkey: XOnlyPublicKey) -> Result<()>;
+
+    async fn get_stored_public_key(&self) -> Result<XOnlyPublicKey>;
+
+    async fn get_oracle_name(&self) -> Result<String>;
+
+    /// Every key the oracle has signed with, oldest first
+    async fn get_oracle_keys(&self) -> Result<Vec<OracleKey>>;
+
+    /// Retires the active key at `at` and makes `pubkey` the active key from then on
+    async fn rotate_oracle_key(&self, pubkey: XOnlyPublicKey, at: OffsetDateTime) -> Result<()>;
+
+    /// The key that was active when the event was created
+    async fn get_event_oracle_key(&self, event_id: &Uuid) -> Result<OracleKey>;
+
+    /// Stores a new event, `now` is recorded as its creation and last modified time
+    async fn add_event(&self, event: CreateEventData, now: OffsetDateTime) -> Result<Event>;
+
+    async fn add_event_entries(&self, entries: Vec<WeatherEntry>) -> Result<()>;
+
+    async fn add_entry(&self, entry: WeatherEntry) -> Result<()> {
+        self.add_event_entries(vec![entry]).await
+    }
+
+    /// Inserts a complete event in one transaction, returns false if the event already exists.
+    /// Keeps the event's own creation and last modified times, falling back to `now` for
+    /// exports that don't carry them
+    async fn import_event(&self, event: Event, now: OffsetDateTime) -> Result<bool>;
+
+    /// Deletes the event, its entries, choices and weather links cascade through the foreign keys,
+    /// the tombstone is stamped with `now`
+    async fn delete_event(&self, id: &Uuid, now: OffsetDateTime) -> Result<bool>;
+
+    /// Deletes every event whose signing date is before `cutoff`, signed or not, along with its
+    /// dependents, returning how many events were removed
+    async fn purge_events_before(&self, cutoff: OffsetDateTime, now: OffsetDateTime)
+        -> Result<u64>;
+
+    async fn list_event_ids(&self) -> Result<Vec<Uuid>>;
+
+    /// Events created or signed at or after `since`, or whose observation window opened or
+    /// closed between `since` and `until` (changing their status), plus events deleted at or
+    /// after `since`
+    async fn changed_events(
+        &self,
+        since: OffsetDateTime,
+        until: OffsetDateTime,
+    ) -> Result<ChangedEvents>;
+
+    async fn get_event(&self, id: &Uuid, now: OffsetDateTime) -> Result<Event> {
+        self.get_event_with(id, &EventIncludes::all(), now).await
+    }
+
+    /// Loads the event metadata plus only the sections requested in `includes`, with its
+    /// status as of `now`
+    async fn get_event_with(
+        &self,
+        id: &Uuid,
+        includes: &EventIncludes,
+        now: OffsetDateTime,
+    ) -> Result<Event>;
+
+    async fn event_exists(&self, id: &Uuid) -> Result<bool>;
+
+    /// Whether an event with this id was deleted or purged, its tombstone in `deleted_events`
+    /// outlives the event
+    async fn event_was_deleted(&self, id: &Uuid) -> Result<bool>;
+
+    async fn get_event_weather_entries(&self, event_id: &Uuid) -> Result<Vec<WeatherEntry>>;
+
+    async fn get_active_events(&self, now: OffsetDateTime) -> Result<Vec<ActiveEvent>>;
+
+    async fn get_events_to_sign(
+        &self,
+        event_ids: Vec<Uuid>,
+        now: OffsetDateTime,
+    ) -> Result<Vec<SignEvent>>;
+
+    async fn update_event_attestation(&self, event: &SignEvent, now: OffsetDateTime) -> Result<()>;
+
+    async fn update_entry_scores(&self, entry_scores: Vec<(Uuid, i64, i64)>) -> Result<()>;
+
+    async fn get_event_coordinator_pubkey(&self, event_id: Uuid) -> Result<String>;
+
+    async fn filtered_list_events(
+        &self,
+        filter: EventFilter,
+        now: OffsetDateTime,
+    ) -> Result<Vec<EventSummary>>;
+
+    /// Counts events by status as of `now`, and the signed ones with a signing date at or after
+    /// `signed_since`, leaving `window_days` for the caller to fill in
+    async fn event_aggregates(
+        &self,
+        now: OffsetDateTime,
+        signed_since: OffsetDateTime,
+    ) -> Result<EventAggregates>;
+
+    async fn update_weather_station_data(
+        &self,
+        event_id: Uuid,
+        weather: Vec<Weather>,
+        temp_unit: &TemperatureUnit,
+    ) -> Result<()>;
+
+    async fn get_event_weather(&self, event_id: Uuid) -> Result<Vec<Weather>> {
+        self.get_event_weather_in_unit(event_id, &TemperatureUnit::default())
+            .await
+    }
+
+    /// Event weather with temperatures converted from the unit each reading was stored in into `temp_unit`
+    async fn get_event_weather_in_unit(
+        &self,
+        event_id: Uuid,
+        temp_unit: &TemperatureUnit,
+    ) -> Result<Vec<Weather>>;
+
+    async fn get_weather_entry(&self, event_id: &Uuid, entry_id: &Uuid) -> Result<WeatherEntry>;
+
+    /// Writes every event, with its entries, choices and weather, as one JSON document per line
+    async fn export_events(
+        &self,
+        out: &mut (dyn Write + Send),
+        now: OffsetDateTime,
+    ) -> Result<usize> {
+        let ids = self.list_event_ids().await?;
+
+        for id in &ids {
+            let event = self.get_event(id, now).await?;
+            serde_json::to_writer(&mut *out, &event)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+
+        Ok(ids.len())
+    }
+
+    /// Loads events written by `export_events`, events whose id already exists are left untouched
+    async fn import_events(
+        &self,
+        input: &mut (dyn BufRead + Send),
+        now: OffsetDateTime,
+    ) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut line = String::new();
+        let mut line_number = 0;
+        loop {
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                break;
+            }
+            line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: Event = serde_json::from_str(&line)
+                .with_context(|| format!("invalid event on line {}", line_number))?;
+            if self.import_event(event, now).await? {
+                report.imported += 1;
+            } else {
+                report.skipped += 1;
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Opens the event store named by `url`, `sqlite:<dir>` (the default) or `postgres://...`
+pub async fn connect_event_store(
+    url: &str,
+    settings: DatabaseSettings,
+) -> Result<Arc<dyn EventStore>> {
+    if let Some(path) = url.strip_prefix("sqlite:") {
+        let db =
+            Database::new_with_settings(path.strip_prefix("//").unwrap_or(path), settings).await?;
+        return Ok(Arc::new(db));
+    }
+    if url.starts_with("postgres:") || url.starts_with("postgresql:") {
+        #[cfg(feature = "postgres")]
+        {
+            let db = super::PostgresDatabase::new(url, settings).await?;
+            return Ok(Arc::new(db));
+        }
+        #[cfg(not(feature = "postgres"))]
+        bail!("postgres event store requested but the oracle was built without the `postgres` feature");
+    }
+    bail!(
+        "unsupported event store url '{}', expected a sqlite: or postgres: scheme",
+        url
+    )
+}
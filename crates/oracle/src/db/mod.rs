@@ -1,28 +1,74 @@
+use crate::TemperatureUnit;
 use anyhow::anyhow;
-use dlctix::secp::{MaybeScalar, Point, Scalar};
+use base64::{engine::general_purpose, Engine};
+use dlctix::musig2::secp256k1::XOnlyPublicKey;
+use dlctix::secp::{MaybePoint, MaybeScalar, Point, Scalar};
 use dlctix::{attestation_locking_point, EventLockingConditions};
 use duckdb::types::{OrderedMap, ToSqlOutput, Type, Value};
 use duckdb::{ffi, ErrorCode, Row, ToSql};
 use log::{debug, info};
 use nostr_sdk::{PublicKey as NostrPublicKey, ToBech32};
 use serde::{Deserialize, Serialize};
-use time::format_description::well_known::Rfc3339;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use time::format_description::{well_known::Rfc3339, BorrowedFormatItem};
 use time::macros::format_description;
 use time::{Date, Duration, OffsetDateTime, UtcOffset};
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+pub mod custom_query;
 pub mod event_data;
 pub mod event_db_migrations;
+pub mod event_store;
 pub mod outcome_generator;
+#[cfg(feature = "postgres")]
+pub mod postgres;
 pub mod sqlite;
+pub mod station_aliases;
 pub mod weather_data;
 
+pub use custom_query::{
+    CustomQueryLimits, CustomQueryRows, DEFAULT_CUSTOM_QUERY_MAX_ROWS,
+    DEFAULT_CUSTOM_QUERY_TIMEOUT_SECS,
+};
 pub use event_data::*;
 pub use event_db_migrations::*;
+pub use event_store::{connect_event_store, EventStore};
 pub use outcome_generator::*;
-pub use sqlite::{Database, DatabaseWriter};
-pub use weather_data::{DailyObservation, Forecast, Observation, Station, WeatherData};
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresDatabase;
+pub use sqlite::{
+    Database, DatabaseSettings, DatabaseWriter, DEFAULT_DB_BUSY_TIMEOUT_MS,
+    DEFAULT_DB_CACHE_SIZE_KIB, DEFAULT_DB_MAX_CONNECTIONS,
+};
+pub use station_aliases::StationAliases;
+pub use weather_data::{
+    classify_remote_error, DailyComparison, DailyObservation, EmptyData, Forecast,
+    HttpRetrySettings, HumidityFormula, MagnusCoefficients, MaterializeReport, Observation,
+    ObservationCoverage, PrecipCodes, QuerySettings, QueryStats, RemoteReadFailure, SanityBounds,
+    Station, WeatherData, DAILY_FOLDER,
+};
+
+/// DuckDB renders a TIMESTAMPTZ cast to text in the session's time zone,
+/// e.g. `2024-08-11 00:27:39.013046-04` or `2024-08-11 10:27:39+05:30`
+const SQL_TIME_FORMAT: &[BorrowedFormatItem<'_>] = format_description!(
+    "[year]-[month]-[day] [hour]:[minute]:[second][optional [.[subsecond]]][offset_hour][optional [:[offset_minute]]]"
+);
+
+/// Parses a timestamp in DuckDB's text format and normalizes it to UTC, whatever offset it was
+/// rendered with
+pub fn parse_sql_timestamp(value: &str) -> Result<OffsetDateTime, time::error::Parse> {
+    OffsetDateTime::parse(value, SQL_TIME_FORMAT).map(|val| val.to_offset(UtcOffset::UTC))
+}
+
+/// Reads a text timestamp column as UTC, see `parse_sql_timestamp`
+fn utc_column(row: &Row, index: usize) -> Result<OffsetDateTime, duckdb::Error> {
+    let value = row.get::<usize, String>(index)?;
+    parse_sql_timestamp(&value)
+        .map_err(|e| duckdb::Error::FromSqlConversionFailure(index, Type::Any, Box::new(e)))
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateEvent {
@@ -39,8 +85,10 @@ pub struct CreateEvent {
     pub end_observation_date: OffsetDateTime,
     /// NOAA observation stations used in this event
     pub locations: Vec<String>,
-    /// The number of values that can be selected per entry in the event (default to number_of_locations * 3, (temp_low, temp_high, wind_speed))
-    pub number_of_values_per_entry: usize,
+    /// The number of values that can be selected per entry in the event, at most one per location and scoring field.
+    /// Defaults to that maximum, number_of_locations * number of scoring_fields, when left out.
+    #[serde(default)]
+    pub number_of_values_per_entry: Option<usize>,
     /// Total number of allowed entries into the event
     pub total_allowed_entries: usize,
     /// Total number of ranks can win (max 5 ranks)
@@ -49,6 +97,72 @@ pub struct CreateEvent {
     /// Available options: temp_high, temp_low, wind_speed, wind_direction, rain_amt, snow_amt, humidity
     #[serde(default = "ScoringField::defaults")]
     pub scoring_fields: Vec<ScoringField>,
+    /// How far an observation may land from the forecast and still count as par, keyed by scoring field
+    /// (e.g. {"rain_amt": 0.25}). Fields left out use their default band, temperatures and wind speed are exact.
+    #[serde(default)]
+    pub par_tolerances: ParTolerances,
+    /// How entries are ranked, "ternary" (default) scores over/par/under picks, "proximity" ranks entries
+    /// by how close their numeric predictions land to the observed values
+    #[serde(default)]
+    pub scoring_mode: ScoringMode,
+    /// Hours to wait after the end observation date for late observations before the event is signed,
+    /// overrides the oracle's configured settlement delay
+    #[serde(default)]
+    pub settlement_delay_hours: Option<u32>,
+    /// Time after which no more entries are accepted, defaults to the start observation date and can't be after it
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub entry_cutoff: Option<OffsetDateTime>,
+}
+
+/// What changes when an event is cloned, its locations, scoring and settings are copied from the
+/// source event. Entries and the attestation are never copied.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CloneEvent {
+    /// UUIDv7 for the new event, one is generated when left out
+    #[serde(default)]
+    pub id: Option<Uuid>,
+    /// Time when the new event's weather observations start, needs to be in the future
+    #[serde(with = "time::serde::rfc3339")]
+    pub start_observation_date: OffsetDateTime,
+    /// Time when the new event's weather observations end, defaults to keeping the source's
+    /// observation window length
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub end_observation_date: Option<OffsetDateTime>,
+    /// Time at which the new event will be signed, defaults to keeping the source's gap between
+    /// the end observation date and signing
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub signing_date: Option<OffsetDateTime>,
+}
+
+impl CloneEvent {
+    /// The `CreateEvent` for `source` moved to the new dates, an entry cutoff is kept the same
+    /// distance ahead of the start observation date
+    pub fn create_event(&self, source: &Event, id: Uuid) -> CreateEvent {
+        let start_observation_date = self.start_observation_date;
+        let end_observation_date = self.end_observation_date.unwrap_or(
+            start_observation_date + (source.end_observation_date - source.start_observation_date),
+        );
+        let signing_date = self
+            .signing_date
+            .unwrap_or(end_observation_date + (source.signing_date - source.end_observation_date));
+        CreateEvent {
+            id: self.id.unwrap_or(id),
+            signing_date,
+            start_observation_date,
+            end_observation_date,
+            locations: source.locations.clone(),
+            number_of_values_per_entry: Some(source.number_of_values_per_entry as usize),
+            total_allowed_entries: source.total_allowed_entries as usize,
+            number_of_places_win: source.number_of_places_win,
+            scoring_fields: source.scoring_fields.clone(),
+            par_tolerances: source.par_tolerances.clone(),
+            scoring_mode: source.scoring_mode,
+            settlement_delay_hours: source.settlement_delay_hours,
+            entry_cutoff: source.entry_cutoff.map(|entry_cutoff| {
+                start_observation_date - (source.start_observation_date - entry_cutoff)
+            }),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +194,17 @@ pub struct CreateEventData {
     pub coordinator_pubkey: String,
     /// Which weather fields to use for scoring
     pub scoring_fields: Vec<ScoringField>,
+    /// Par band overrides for scoring fields
+    pub par_tolerances: ParTolerances,
+    /// How entries are ranked
+    pub scoring_mode: ScoringMode,
+    /// Settlement delay override in hours
+    pub settlement_delay_hours: Option<u32>,
+    /// Entry cutoff override, the start observation date applies when unset
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub entry_cutoff: Option<OffsetDateTime>,
+    /// Layout of the outcome messages the locking points commit to
+    pub outcome_message_version: OutcomeMessageVersion,
 }
 
 impl CreateEventData {
@@ -87,6 +212,8 @@ impl CreateEventData {
         oracle_pubkey: Point,
         coordinator_pubkey: NostrPublicKey,
         event: CreateEvent,
+        nonce: Scalar,
+        outcome_message_version: OutcomeMessageVersion,
     ) -> Result<Self, anyhow::Error> {
         if event.id.get_version_num() != 7 {
             return Err(anyhow!(
@@ -101,6 +228,15 @@ impl CreateEventData {
                 event.end_observation_date.format(&Rfc3339).unwrap()
             ));
         }
+        if let Some(entry_cutoff) = event.entry_cutoff {
+            if entry_cutoff > event.start_observation_date {
+                return Err(anyhow::anyhow!(
+                    "Entry cutoff {} can't be after start observation date {}",
+                    entry_cutoff.format(&Rfc3339).unwrap(),
+                    event.start_observation_date.format(&Rfc3339).unwrap()
+                ));
+            }
+        }
         if event.end_observation_date > event.signing_date {
             return Err(anyhow::anyhow!(
                 "Signing date {} needs to be after end observation date {}",
@@ -119,16 +255,38 @@ impl CreateEventData {
                 "At least one scoring field must be selected"
             ));
         }
+        let max_values_per_entry = event.locations.len() * event.scoring_fields.len();
+        let number_of_values_per_entry = match event.number_of_values_per_entry {
+            None => max_values_per_entry,
+            Some(values) if values == 0 || values > max_values_per_entry => {
+                return Err(anyhow::anyhow!(
+                    "Number of values per entry must be between 1 and {} ({} locations * {} scoring fields), requested {}",
+                    max_values_per_entry,
+                    event.locations.len(),
+                    event.scoring_fields.len(),
+                    values
+                ));
+            }
+            Some(values) => values,
+        };
+        for (field, tolerance) in &event.par_tolerances {
+            if !tolerance.is_finite() || *tolerance < 0.0 {
+                return Err(anyhow::anyhow!(
+                    "Par tolerance for {} must be a non-negative number, got {}",
+                    field,
+                    tolerance
+                ));
+            }
+        }
         let possible_user_outcomes: Vec<Vec<usize>> = generate_ranking_permutations(
             event.total_allowed_entries,
             event.number_of_places_win as usize,
         );
         info!("user outcomes: {:?}", possible_user_outcomes);
 
-        let outcome_messages: Vec<Vec<u8>> = generate_outcome_messages(possible_user_outcomes);
+        let outcome_messages: Vec<Vec<u8>> =
+            generate_outcome_messages(possible_user_outcomes, outcome_message_version);
 
-        let mut rng = rand::thread_rng();
-        let nonce = Scalar::random(&mut rng);
         let nonce_point = nonce.base_point_mul();
 
         // Manually set expiry to 1 day after the signature should have been provided so users can get their funds back
@@ -160,11 +318,16 @@ impl CreateEventData {
             nonce,
             total_allowed_entries: event.total_allowed_entries as i64,
             number_of_places_win: event.number_of_places_win,
-            number_of_values_per_entry: event.number_of_values_per_entry as i64,
+            number_of_values_per_entry: number_of_values_per_entry as i64,
             locations: event.locations.clone(),
             event_announcement,
             coordinator_pubkey,
             scoring_fields: event.scoring_fields,
+            par_tolerances: event.par_tolerances,
+            scoring_mode: event.scoring_mode,
+            settlement_delay_hours: event.settlement_delay_hours,
+            entry_cutoff: event.entry_cutoff,
+            outcome_message_version,
         })
     }
 }
@@ -189,6 +352,13 @@ impl From<CreateEventData> for Event {
             attestation: None,
             coordinator_pubkey: value.coordinator_pubkey,
             scoring_fields: value.scoring_fields,
+            par_tolerances: value.par_tolerances,
+            scoring_mode: value.scoring_mode,
+            settlement_delay_hours: value.settlement_delay_hours,
+            entry_cutoff: value.entry_cutoff,
+            outcome_message_version: value.outcome_message_version,
+            created_at: None,
+            last_modified: None,
         }
     }
 }
@@ -198,6 +368,28 @@ pub struct EventFilter {
     // TODO: add more options, proper pagination and search
     pub limit: Option<usize>,
     pub event_ids: Option<Vec<Uuid>>,
+    /// Only events still observing at or after this time, their end_observation_date is not earlier
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub observation_after: Option<OffsetDateTime>,
+    /// Only events observing at or before this time, their start_observation_date is not later
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub observation_before: Option<OffsetDateTime>,
+    /// Only events signing at or after this time
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub signing_after: Option<OffsetDateTime>,
+    /// Only events signing at or before this time
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub signing_before: Option<OffsetDateTime>,
+    /// Field to order the listing by, newest created first when unset
+    #[serde(default)]
+    #[param(inline)]
+    pub sort: EventSort,
+    #[serde(default)]
+    #[param(inline)]
+    pub direction: SortDirection,
+    /// Events to skip before `limit` applies, the listing pages by offset rather than an id
+    /// keyset since rows sorted by a date or entry count have no stable cursor
+    pub offset: Option<usize>,
 }
 
 impl Default for EventFilter {
@@ -205,10 +397,245 @@ impl Default for EventFilter {
         Self {
             limit: Some(100_usize),
             event_ids: None,
+            observation_after: None,
+            observation_before: None,
+            signing_after: None,
+            signing_before: None,
+            sort: EventSort::default(),
+            direction: SortDirection::default(),
+            offset: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSort {
+    /// When the event was created on the oracle
+    #[default]
+    Created,
+    /// Start of the event's observation window
+    ObservationDate,
+    SigningDate,
+    TotalEntries,
+}
+
+impl EventSort {
+    /// ORDER BY clause for this sort, `events` is the alias the events table is selected under.
+    /// Ties fall back to the event id so pages stay deterministic.
+    pub fn order_by(&self, events: &str, direction: SortDirection) -> String {
+        let column = match self {
+            EventSort::Created => format!("{}.created_at", events),
+            EventSort::ObservationDate => format!("{}.start_observation_date", events),
+            EventSort::SigningDate => format!("{}.signing_date", events),
+            EventSort::TotalEntries => String::from("total_entries"),
+        };
+        format!("{} {}, {}.id {}", column, direction, events, direction)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl std::fmt::Display for SortDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortDirection::Asc => write!(f, "ASC"),
+            SortDirection::Desc => write!(f, "DESC"),
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// Latest migration successfully applied to the event database
+    pub current_version: Option<i64>,
+    /// Latest migration bundled with this build of the oracle
+    pub latest_version: Option<i64>,
+    /// True when every bundled migration has been applied
+    pub up_to_date: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct MaintenanceReport {
+    /// Database pages before maintenance ran
+    pub page_count_before: i64,
+    /// Database pages after maintenance ran, only shrinks when vacuumed
+    pub page_count_after: i64,
+    /// Pages on the freelist after maintenance ran
+    pub freelist_count_after: i64,
+    pub vacuumed: bool,
+}
+
+/// Event counts computed in the database, statuses are derived the same way as `get_status`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct EventAggregates {
+    pub total_events: i64,
+    pub live: i64,
+    pub running: i64,
+    pub completed: i64,
+    pub signed: i64,
+    /// Entries across every event
+    pub total_entries: i64,
+    /// Signed events whose signing date falls inside the last `window_days`
+    pub signed_recently: i64,
+    pub window_days: u32,
+}
+
+/// Events touched in a sync window, see `EventStore::changed_events`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangedEvents {
+    /// Created, signed or moved to another status
+    pub changed: Vec<Uuid>,
+    /// Deleted and not recreated since
+    pub deleted: Vec<Uuid>,
+}
+
+/// Everything a mirror needs to catch up from `since` to `until`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct EventChanges {
+    /// Events created, signed or moved to another status in the window
+    pub events: Vec<EventSummary>,
+    /// Ids of events deleted or purged in the window
+    pub deleted: Vec<Uuid>,
+    /// End of the window, pass it as `since` on the next poll
+    #[serde(with = "time::serde::rfc3339")]
+    pub until: OffsetDateTime,
+}
+
+/// Unix milliseconds, the resolution `last_modified` and `deleted_at` are stored at
+pub(crate) fn unix_millis(at: OffsetDateTime) -> i64 {
+    (at.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+/// Reads back a time stored by `unix_millis`
+pub(crate) fn from_unix_millis(millis: i64) -> Result<OffsetDateTime, time::error::ComponentRange> {
+    OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Events written to the database
+    pub imported: usize,
+    /// Events skipped because their id already exists
+    pub skipped: usize,
+}
+
+/// Optional sections loaded alongside an event's metadata, parsed from a comma separated list
+/// of `entries`, `weather` and `entry_choices` (which implies `entries`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventIncludes {
+    pub entries: bool,
+    pub entry_choices: bool,
+    pub weather: bool,
+}
+
+impl EventIncludes {
+    pub fn all() -> Self {
+        Self {
+            entries: true,
+            entry_choices: true,
+            weather: true,
+        }
+    }
+}
+
+impl FromStr for EventIncludes {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut includes = EventIncludes::default();
+        for section in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            match section {
+                "entries" => includes.entries = true,
+                "entry_choices" => {
+                    includes.entries = true;
+                    includes.entry_choices = true;
+                }
+                "weather" => includes.weather = true,
+                other => return Err(anyhow!("unknown event include: {}", other)),
+            }
+        }
+        Ok(includes)
+    }
+}
+
+/// Plain text encoding for the scalar and point fields of event responses
+///
+/// Scalars (`nonce`, `attestation`) are their 32 byte big-endian value, points
+/// (`event_announcement.locking_points`) are 33 byte compressed SEC1 encodings with the point at
+/// infinity as 33 zero bytes. Responses default to the dlctix serialization, which is lowercase hex.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ByteEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+impl ByteEncoding {
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            ByteEncoding::Hex => hex::encode(bytes),
+            ByteEncoding::Base64 => general_purpose::STANDARD.encode(bytes),
+        }
+    }
+
+    pub fn decode(&self, encoded: &str) -> Result<Vec<u8>, anyhow::Error> {
+        match self {
+            ByteEncoding::Hex => Ok(hex::decode(encoded)?),
+            ByteEncoding::Base64 => Ok(general_purpose::STANDARD.decode(encoded)?),
+        }
+    }
+
+    /// Re-encodes the scalar and point fields of a serialized `Event`, `EventSummary` or
+    /// `BatchEvent`, which hold them as hex
+    pub fn reencode_event(&self, event: &mut serde_json::Value) -> Result<(), anyhow::Error> {
+        if let Some(inner) = event.get_mut("event") {
+            return self.reencode_event(inner);
+        }
+        for field in ["nonce", "attestation"] {
+            if let Some(value) = event.get_mut(field) {
+                self.reencode(value)?;
+            }
+        }
+        if let Some(serde_json::Value::Array(points)) = event
+            .get_mut("event_announcement")
+            .and_then(|announcement| announcement.get_mut("locking_points"))
+        {
+            for point in points {
+                self.reencode(point)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn reencode(&self, value: &mut serde_json::Value) -> Result<(), anyhow::Error> {
+        if let serde_json::Value::String(encoded) = value {
+            *encoded = self.encode(&ByteEncoding::Hex.decode(encoded)?);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GetEventsBatch {
+    /// IDs of the events to fetch, capped by the oracle's configured max batch size
+    pub ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchEvent {
+    Found { event: Box<Event> },
+    NotFound { id: Uuid },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
 pub struct SignEvent {
     pub id: Uuid,
@@ -226,14 +653,18 @@ pub struct SignEvent {
     pub number_of_values_per_entry: i64,
     #[schema(value_type = String)]
     pub attestation: Option<MaybeScalar>,
+    #[serde(default)]
+    #[schema(value_type = u8)]
+    pub outcome_message_version: OutcomeMessageVersion,
 }
 
 impl SignEvent {
-    pub fn update_status(&mut self) {
+    pub fn update_status(&mut self, now: OffsetDateTime) {
         self.status = get_status(
             self.attestation,
             self.start_observation_date,
             self.end_observation_date,
+            now,
         );
     }
 }
@@ -242,31 +673,16 @@ impl TryFrom<&Row<'_>> for SignEvent {
     type Error = duckdb::Error;
 
     fn try_from(row: &Row) -> Result<Self, Self::Error> {
-        //raw date format 2024-08-11 00:27:39.013046-04
-        let sql_time_format = format_description!(
-            "[year]-[month]-[day] [hour]:[minute]:[second][optional [.[subsecond]]][offset_hour]"
-        );
         let mut sign_events = SignEvent {
             id: row
                 .get::<usize, String>(0)
                 .map(|val| Uuid::parse_str(&val))?
                 .map_err(|e| duckdb::Error::FromSqlConversionFailure(0, Type::Any, Box::new(e)))?,
-            signing_date: row
-                .get::<usize, String>(1)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(1, Type::Any, Box::new(e)))?,
-            start_observation_date: row
-                .get::<usize, String>(2)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(2, Type::Any, Box::new(e)))?,
-            end_observation_date: row
-                .get::<usize, String>(3)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(3, Type::Any, Box::new(e)))?,
+            signing_date: utc_column(row, 1)?,
+            start_observation_date: utc_column(row, 2)?,
+            end_observation_date: utc_column(row, 3)?,
             status: EventStatus::default(),
+            outcome_message_version: OutcomeMessageVersion::V0,
             number_of_places_win: row.get::<usize, i64>(4)?,
             number_of_values_per_entry: row.get::<usize, i64>(5)?,
             attestation: row.get::<usize, Option<Value>>(6).map(|opt| {
@@ -296,12 +712,12 @@ impl TryFrom<&Row<'_>> for SignEvent {
                 })?
                 .map_err(|e| duckdb::Error::FromSqlConversionFailure(8, Type::Any, Box::new(e)))?,
         };
-        sign_events.update_status();
+        sign_events.update_status(OffsetDateTime::now_utc());
         Ok(sign_events)
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
 pub struct ActiveEvent {
     pub id: Uuid,
     pub locations: Vec<String>,
@@ -319,16 +735,63 @@ pub struct ActiveEvent {
     pub attestation: Option<MaybeScalar>,
     /// Which weather fields are used for scoring in this event
     pub scoring_fields: Vec<ScoringField>,
+    /// Par band overrides for this event's scoring fields
+    #[serde(default)]
+    pub par_tolerances: ParTolerances,
+    /// How entries are ranked in this event
+    #[serde(default)]
+    pub scoring_mode: ScoringMode,
+    /// Settlement delay override in hours, the oracle's default applies when unset
+    #[serde(default)]
+    pub settlement_delay_hours: Option<u32>,
+}
+
+/// Entries would take the event past `total_allowed_entries`, its outcomes were only generated for that many
+#[derive(thiserror::Error, Debug)]
+#[error(
+    "event {event_id} allows {allowed} entries, it has {existing} and {new} more were submitted"
+)]
+pub struct EntryLimitExceeded {
+    pub event_id: Uuid,
+    pub allowed: i64,
+    pub existing: i64,
+    pub new: i64,
+}
+
+/// Number of entries being added to each event
+fn entries_per_event(entries: &[WeatherEntry]) -> HashMap<Uuid, i64> {
+    let mut counts = HashMap::new();
+    for entry in entries {
+        *counts.entry(entry.event_id).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// The event's own settlement delay when set, otherwise the oracle's default
+pub fn settlement_delay(hours: Option<u32>, default_settlement_delay: Duration) -> Duration {
+    hours
+        .map(|hours| Duration::hours(hours.into()))
+        .unwrap_or(default_settlement_delay)
 }
 
 impl ActiveEvent {
-    pub fn update_status(&mut self) {
+    pub fn update_status(&mut self, now: OffsetDateTime) {
         self.status = get_status(
             self.attestation,
             self.start_observation_date,
             self.end_observation_date,
+            now,
         );
     }
+
+    /// Completed events stay unsigned until late observations have had the settlement delay to arrive
+    pub fn ready_to_sign(&self, default_settlement_delay: Duration, now: OffsetDateTime) -> bool {
+        self.status == EventStatus::Completed
+            && self.attestation.is_none()
+            && now
+                >= self.end_observation_date
+                    + settlement_delay(self.settlement_delay_hours, default_settlement_delay)
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
@@ -387,30 +850,14 @@ impl TryFrom<&Row<'_>> for ActiveEvent {
     type Error = duckdb::Error;
 
     fn try_from(row: &Row) -> Result<Self, Self::Error> {
-        //raw date format 2024-08-11 00:27:39.013046-04
-        let sql_time_format = format_description!(
-            "[year]-[month]-[day] [hour]:[minute]:[second][optional [.[subsecond]]][offset_hour]"
-        );
         let mut active_events = ActiveEvent {
             id: row
                 .get::<usize, String>(0)
                 .map(|val| Uuid::parse_str(&val))?
                 .map_err(|e| duckdb::Error::FromSqlConversionFailure(0, Type::Any, Box::new(e)))?,
-            signing_date: row
-                .get::<usize, String>(1)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(1, Type::Any, Box::new(e)))?,
-            start_observation_date: row
-                .get::<usize, String>(2)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(2, Type::Any, Box::new(e)))?,
-            end_observation_date: row
-                .get::<usize, String>(3)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(3, Type::Any, Box::new(e)))?,
+            signing_date: utc_column(row, 1)?,
+            start_observation_date: utc_column(row, 2)?,
+            end_observation_date: utc_column(row, 3)?,
             locations: row
                 .get::<usize, Value>(4)
                 .map(|locations| {
@@ -460,8 +907,11 @@ impl TryFrom<&Row<'_>> for ActiveEvent {
                     }
                 })
                 .unwrap_or_else(|_| ScoringField::defaults()),
+            par_tolerances: ParTolerances::new(),
+            scoring_mode: ScoringMode::default(),
+            settlement_delay_hours: None,
         };
-        active_events.update_status();
+        active_events.update_status(OffsetDateTime::now_utc());
         Ok(active_events)
     }
 }
@@ -501,26 +951,28 @@ pub struct EventSummary {
 }
 
 impl EventSummary {
-    pub fn update_status(&mut self) {
+    pub fn update_status(&mut self, now: OffsetDateTime) {
         self.status = get_status(
             self.attestation,
             self.start_observation_date,
             self.end_observation_date,
+            now,
         )
     }
 }
 
+/// Where an event stands at `now`: live until its observation window starts, running through
+/// it, completed after it and signed once attested
 pub fn get_status(
     attestation: Option<MaybeScalar>,
     start_observation_date: OffsetDateTime,
     end_observation_date: OffsetDateTime,
+    now: OffsetDateTime,
 ) -> EventStatus {
     if attestation.is_some() {
         return EventStatus::Signed;
     }
 
-    let now = OffsetDateTime::now_utc();
-
     if now < start_observation_date {
         return EventStatus::Live;
     }
@@ -536,30 +988,14 @@ impl TryFrom<&Row<'_>> for EventSummary {
     type Error = duckdb::Error;
 
     fn try_from(row: &Row) -> Result<Self, Self::Error> {
-        //raw date format 2024-08-11 00:27:39.013046-04
-        let sql_time_format = format_description!(
-            "[year]-[month]-[day] [hour]:[minute]:[second][optional [.[subsecond]]][offset_hour]"
-        );
         let mut event_summary = EventSummary {
             id: row
                 .get::<usize, String>(0)
                 .map(|val| Uuid::parse_str(&val))?
                 .map_err(|e| duckdb::Error::FromSqlConversionFailure(0, Type::Any, Box::new(e)))?,
-            signing_date: row
-                .get::<usize, String>(1)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(1, Type::Any, Box::new(e)))?,
-            start_observation_date: row
-                .get::<usize, String>(2)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(2, Type::Any, Box::new(e)))?,
-            end_observation_date: row
-                .get::<usize, String>(3)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(3, Type::Any, Box::new(e)))?,
+            signing_date: utc_column(row, 1)?,
+            start_observation_date: utc_column(row, 2)?,
+            end_observation_date: utc_column(row, 3)?,
             status: EventStatus::default(),
             locations: row
                 .get::<usize, Value>(4)
@@ -599,12 +1035,12 @@ impl TryFrom<&Row<'_>> for EventSummary {
                 .map_err(|e| duckdb::Error::FromSqlConversionFailure(10, Type::Any, Box::new(e)))?,
             weather: vec![],
         };
-        event_summary.update_status();
+        event_summary.update_status(OffsetDateTime::now_utc());
         Ok(event_summary)
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
 pub struct Event {
     pub id: Uuid,
     #[serde(with = "time::serde::rfc3339")]
@@ -645,26 +1081,266 @@ pub struct Event {
     pub coordinator_pubkey: String,
     /// Which weather fields are used for scoring in this event
     pub scoring_fields: Vec<ScoringField>,
+    /// Par band overrides for this event's scoring fields, fields left out use their default band
+    #[serde(default)]
+    pub par_tolerances: ParTolerances,
+    /// How entries are ranked in this event
+    #[serde(default)]
+    pub scoring_mode: ScoringMode,
+    /// Hours after the end observation date to wait for late observations before signing, the oracle's
+    /// configured delay applies when unset
+    #[serde(default)]
+    pub settlement_delay_hours: Option<u32>,
+    /// Time after which no more entries are accepted, the start observation date applies when unset
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub entry_cutoff: Option<OffsetDateTime>,
+    /// Layout of the outcome messages the event's locking points commit to, 0 for events created
+    /// before the layout was versioned
+    #[serde(default)]
+    #[schema(value_type = u8)]
+    pub outcome_message_version: OutcomeMessageVersion,
+    /// When the event was created, unset in exports made before it was included
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_at: Option<OffsetDateTime>,
+    /// When the event, its entries or its attestation last changed, unset in exports made before
+    /// it was included
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub last_modified: Option<OffsetDateTime>,
 }
 
 impl Event {
-    pub fn update_status(&mut self) {
+    pub fn update_status(&mut self, now: OffsetDateTime) {
         self.status = get_status(
             self.attestation,
             self.start_observation_date,
             self.end_observation_date,
+            now,
         );
     }
+
+    /// The event's own entry cutoff when set, otherwise the start of the observation window
+    pub fn entries_close_at(&self) -> OffsetDateTime {
+        self.entry_cutoff.unwrap_or(self.start_observation_date)
+    }
+}
+
+/// A signing key the oracle has used and when it was the active key
+#[derive(Debug, Clone, PartialEq)]
+pub struct OracleKey {
+    pub pubkey: XOnlyPublicKey,
+    pub active_from: OffsetDateTime,
+    /// `None` for the key the oracle currently signs with
+    pub active_until: Option<OffsetDateTime>,
+}
+
+impl OracleKey {
+    fn from_row(
+        pubkey: &[u8],
+        active_from: i64,
+        active_until: Option<i64>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            pubkey: XOnlyPublicKey::from_slice(pubkey)
+                .map_err(|e| anyhow!("Invalid pubkey: {}", e))?,
+            active_from: OffsetDateTime::from_unix_timestamp(active_from)?,
+            active_until: active_until
+                .map(OffsetDateTime::from_unix_timestamp)
+                .transpose()?,
+        })
+    }
+}
+
+/// Version of the announcement and attestation artifacts, bumped whenever their shape changes
+pub const ARTIFACT_VERSION: u8 = 1;
+
+/// What a coordinator needs from the oracle to build the event's dlctix contract
+///
+/// Raw bytes layout: `version (1) || event_id (16) || oracle_pubkey (33) || nonce_point (33) ||
+/// expiry (4, big endian, 0 when unset) || locking point count (4, big endian) || locking_points (33 each)`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct EventAnnouncement {
+    /// Artifact shape version, currently 1
+    pub version: u8,
+    pub event_id: Uuid,
+    /// Hex encoded compressed public key the oracle signs with
+    pub oracle_pubkey: String,
+    /// Hex encoded compressed point of the nonce the oracle committed to
+    pub nonce_point: String,
+    /// Hex encoded compressed locking points in outcome order, the point at infinity is 33 zero bytes
+    pub locking_points: Vec<String>,
+    /// Unix timestamp after which the oracle is considered to have gone AWOL
+    pub expiry: Option<u32>,
+    /// Layout of the outcome messages the locking points commit to, not part of the raw bytes.
+    /// Announcements served before it was recorded are version 0.
+    #[serde(default)]
+    #[schema(value_type = u8)]
+    pub outcome_message_version: OutcomeMessageVersion,
+}
+
+impl EventAnnouncement {
+    pub fn new(oracle_pubkey: Point, event: &Event) -> Self {
+        Self {
+            version: ARTIFACT_VERSION,
+            event_id: event.id,
+            oracle_pubkey: hex::encode(oracle_pubkey.serialize()),
+            nonce_point: hex::encode(event.nonce.base_point_mul().serialize()),
+            locking_points: event
+                .event_announcement
+                .locking_points
+                .iter()
+                .map(|point| hex::encode(point.serialize()))
+                .collect(),
+            expiry: event.event_announcement.expiry,
+            outcome_message_version: event.outcome_message_version,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, hex::FromHexError> {
+        let mut bytes = vec![self.version];
+        bytes.extend_from_slice(self.event_id.as_bytes());
+        bytes.extend(hex::decode(&self.oracle_pubkey)?);
+        bytes.extend(hex::decode(&self.nonce_point)?);
+        bytes.extend(self.expiry.unwrap_or_default().to_be_bytes());
+        bytes.extend((self.locking_points.len() as u32).to_be_bytes());
+        for point in &self.locking_points {
+            bytes.extend(hex::decode(point)?);
+        }
+        Ok(bytes)
+    }
+}
+
+/// The oracle's signature over the event's final outcome
+///
+/// Raw bytes layout: the 32 byte attestation scalar
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct EventAttestation {
+    /// Artifact shape version, currently 1
+    pub version: u8,
+    pub event_id: Uuid,
+    /// Hex encoded attestation scalar, the discrete log of the winning outcome's locking point
+    pub attestation: String,
+    /// Index into the announcement's locking points of the outcome the attestation unlocks
+    pub outcome_index: Option<usize>,
+}
+
+impl EventAttestation {
+    /// `None` until the oracle has signed the event
+    pub fn new(event: &Event) -> Option<Self> {
+        let attestation = event.attestation?;
+        let locking_point = attestation.base_point_mul();
+        Some(Self {
+            version: ARTIFACT_VERSION,
+            event_id: event.id,
+            attestation: hex::encode(attestation.serialize()),
+            outcome_index: event
+                .event_announcement
+                .locking_points
+                .iter()
+                .position(|point| *point == locking_point),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, hex::FromHexError> {
+        hex::decode(&self.attestation)
+    }
+
+    /// Checks the attestation is the oracle's signature over `winners`: its point must be the
+    /// locking point derived from the announcement's key and nonce for that outcome, and one of
+    /// the announcement's locking points. The outcome message is built with the announcement's
+    /// outcome message version.
+    pub fn verify(
+        &self,
+        announcement: &EventAnnouncement,
+        winners: &[usize],
+    ) -> Result<AttestationVerification, anyhow::Error> {
+        let oracle_pubkey = Point::from_slice(&hex::decode(&announcement.oracle_pubkey)?)
+            .map_err(|e| anyhow!("invalid oracle pubkey: {}", e))?;
+        let nonce_point = Point::from_slice(&hex::decode(&announcement.nonce_point)?)
+            .map_err(|e| anyhow!("invalid nonce point: {}", e))?;
+        let attestation = MaybeScalar::from_slice(&hex::decode(&self.attestation)?)
+            .map_err(|e| anyhow!("invalid attestation: {}", e))?;
+        let locking_points = announcement
+            .locking_points
+            .iter()
+            .map(|point| {
+                MaybePoint::from_slice(&hex::decode(point)?)
+                    .map_err(|e| anyhow!("invalid locking point: {}", e))
+            })
+            .collect::<Result<Vec<MaybePoint>, anyhow::Error>>()?;
+
+        let attested_point = attestation.base_point_mul();
+        let outcome_index = locking_points
+            .iter()
+            .position(|point| *point == attested_point);
+        let outcome_message = announcement.outcome_message_version.message(winners);
+        let claimed_point = attestation_locking_point(oracle_pubkey, nonce_point, &outcome_message);
+
+        let valid = self.event_id == announcement.event_id
+            && outcome_index.is_some()
+            && self
+                .outcome_index
+                .is_none_or(|index| Some(index) == outcome_index)
+            && attested_point == claimed_point;
+        Ok(AttestationVerification {
+            valid,
+            outcome_index,
+        })
+    }
+}
+
+/// What to check with `POST /verify`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum VerifyAttestation {
+    /// An event the oracle has signed, checked under the key that announced it
+    Event { event_id: Uuid },
+    /// Artifacts from the event's `/announcement` and `/attestation` endpoints
+    Artifacts {
+        announcement: EventAnnouncement,
+        attestation: EventAttestation,
+        /// The claimed outcome, entry indices in ranking order or every entry index for a refund
+        winners: Vec<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct AttestationVerification {
+    /// Whether the attestation unlocks the claimed outcome under the oracle's pubkey
+    pub valid: bool,
+    /// Index into the announcement's locking points of the outcome the attestation unlocks
+    pub outcome_index: Option<usize>,
+}
+
+/// A signed event re-scored from its persisted weather and entries, purely diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct EventAudit {
+    pub event_id: Uuid,
+    /// Entries from first to last place by their recomputed score
+    pub entries: Vec<EntryAudit>,
+    /// Winners from the recomputed scores, as indices into the entries sorted by id
+    pub winners: Vec<usize>,
+    /// Whether the stored attestation signs the recomputed winners
+    pub attestation_matches: bool,
+    /// Set when any stored score differs from its recomputed one or the attestation signs
+    /// other winners
+    pub discrepancy: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct EntryAudit {
+    pub entry_id: Uuid,
+    pub stored_score: Option<i64>,
+    pub stored_base_score: Option<i64>,
+    pub recomputed_score: i64,
+    pub recomputed_base_score: i64,
+    /// Whether the stored scores equal the recomputed ones
+    pub matches: bool,
 }
 
 impl TryFrom<&Row<'_>> for Event {
     type Error = duckdb::Error;
 
     fn try_from(row: &Row) -> Result<Self, Self::Error> {
-        //raw date format 2024-08-11 00:27:39.013046-04
-        let sql_time_format = format_description!(
-            "[year]-[month]-[day] [hour]:[minute]:[second][optional [.[subsecond]]][offset_hour]"
-        );
         let mut oracle_event_data = Event {
             id: row
                 .get::<usize, String>(0)
@@ -673,27 +1349,9 @@ impl TryFrom<&Row<'_>> for Event {
                     Uuid::parse_str(&val)
                 })?
                 .map_err(|e| duckdb::Error::FromSqlConversionFailure(0, Type::Any, Box::new(e)))?,
-            signing_date: row
-                .get::<usize, String>(1)
-                .map(|val| {
-                    debug!("{}", val);
-                    OffsetDateTime::parse(&val, &sql_time_format)
-                })?
-                .map(|val| {
-                    debug!("{}", val);
-                    val.to_offset(UtcOffset::UTC)
-                })
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(1, Type::Any, Box::new(e)))?,
-            start_observation_date: row
-                .get::<usize, String>(2)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(2, Type::Any, Box::new(e)))?,
-            end_observation_date: row
-                .get::<usize, String>(3)
-                .map(|val| OffsetDateTime::parse(&val, &sql_time_format))?
-                .map(|val| val.to_offset(UtcOffset::UTC))
-                .map_err(|e| duckdb::Error::FromSqlConversionFailure(3, Type::Any, Box::new(e)))?,
+            signing_date: utc_column(row, 1)?,
+            start_observation_date: utc_column(row, 2)?,
+            end_observation_date: utc_column(row, 3)?,
             event_announcement: row
                 .get::<usize, Value>(4)
                 .map(|raw| {
@@ -775,13 +1433,20 @@ impl TryFrom<&Row<'_>> for Event {
                     }
                 })
                 .unwrap_or_else(|_| ScoringField::defaults()),
+            par_tolerances: ParTolerances::new(),
+            scoring_mode: ScoringMode::default(),
+            settlement_delay_hours: None,
+            entry_cutoff: None,
+            outcome_message_version: OutcomeMessageVersion::V0,
+            created_at: None,
+            last_modified: None,
             status: EventStatus::default(),
             //These nested values have to be made by more quries
             entry_ids: vec![],
             entries: vec![],
             weather: vec![],
         };
-        oracle_event_data.update_status();
+        oracle_event_data.update_status(OffsetDateTime::now_utc());
         Ok(oracle_event_data)
     }
 }
@@ -793,6 +1458,21 @@ pub struct Weather {
     pub forecasted: Forecasted,
 }
 
+impl Weather {
+    /// Converts the stored integer temperatures from `from_unit` into `to_unit`, rounding to the nearest degree
+    pub fn convert_temperature(&mut self, from_unit: &str, to_unit: &TemperatureUnit) {
+        let convert = |value: i64| {
+            weather_data::convert_temperature(value as f64, from_unit, to_unit).round() as i64
+        };
+        if let Some(observed) = self.observed.as_mut() {
+            observed.temp_low = convert(observed.temp_low);
+            observed.temp_high = convert(observed.temp_high);
+        }
+        self.forecasted.temp_low = convert(self.forecasted.temp_low);
+        self.forecasted.temp_high = convert(self.forecasted.temp_high);
+    }
+}
+
 impl TryFrom<&Row<'_>> for Weather {
     type Error = duckdb::Error;
 
@@ -1355,7 +2035,7 @@ impl From<AddEventEntry> for WeatherEntry {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
 pub struct WeatherEntry {
     pub id: Uuid,
     pub event_id: Uuid,
@@ -1528,7 +2208,7 @@ impl TryFrom<&Row<'_>> for WeatherChoicesWithEntry {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
 pub struct WeatherChoices {
     // NOAA weather stations we're using
     pub stations: String,
@@ -1539,6 +2219,29 @@ pub struct WeatherChoices {
     pub rain_amt: Option<ValueOptions>,
     pub snow_amt: Option<ValueOptions>,
     pub humidity: Option<ValueOptions>,
+    /// Numeric predictions keyed by scoring field (e.g. {"temp_high": 72}), only used by proximity scored events
+    #[serde(default, skip_serializing_if = "WeatherPredictions::is_empty")]
+    pub predictions: WeatherPredictions,
+}
+
+impl WeatherChoices {
+    /// The option picked for a scoring field, if this choice covers it
+    pub fn choice_for(&self, field: &ScoringField) -> Option<&ValueOptions> {
+        match field {
+            ScoringField::TempHigh => self.temp_high.as_ref(),
+            ScoringField::TempLow => self.temp_low.as_ref(),
+            ScoringField::WindSpeed => self.wind_speed.as_ref(),
+            ScoringField::WindDirection => self.wind_direction.as_ref(),
+            ScoringField::RainAmt => self.rain_amt.as_ref(),
+            ScoringField::SnowAmt => self.snow_amt.as_ref(),
+            ScoringField::Humidity => self.humidity.as_ref(),
+        }
+    }
+
+    /// The numeric prediction for a scoring field, if this choice has one
+    pub fn prediction_for(&self, field: &ScoringField) -> Option<f64> {
+        self.predictions.get(field).copied()
+    }
 }
 
 impl From<WeatherChoicesWithEntry> for WeatherChoices {
@@ -1552,6 +2255,7 @@ impl From<WeatherChoicesWithEntry> for WeatherChoices {
             rain_amt: value.rain_amt,
             snow_amt: value.snow_amt,
             humidity: value.humidity,
+            predictions: WeatherPredictions::new(),
         }
     }
 }
@@ -1592,6 +2296,7 @@ impl TryFrom<&Row<'_>> for WeatherChoices {
                 .get::<usize, Option<String>>(7)
                 .map(|raw| raw.and_then(|inner| ValueOptions::try_from(inner).ok()))
                 .map_err(|e| duckdb::Error::FromSqlConversionFailure(7, Type::Any, Box::new(e)))?,
+            predictions: WeatherPredictions::new(),
         })
     }
 }
@@ -1648,6 +2353,7 @@ impl TryInto<WeatherChoices> for &OrderedMap<String, Value> {
             rain_amt,
             snow_amt,
             humidity,
+            predictions: WeatherPredictions::new(),
         })
     }
 }
@@ -1749,7 +2455,95 @@ impl TryFrom<String> for ScoringField {
     }
 }
 
+/// Per scoring field overrides of the par band, see `ScoringField::default_par_tolerance`
+pub type ParTolerances = HashMap<ScoringField, f64>;
+
+/// Numeric predictions for an entry's station, keyed by scoring field
+pub type WeatherPredictions = HashMap<ScoringField, f64>;
+
+/// How entries in an event are ranked
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringMode {
+    /// Points for each over/par/under pick that matches where the observation landed
+    #[default]
+    Ternary,
+    /// Entries closest to the observed values win, measured as the sum of absolute differences
+    /// between their numeric predictions and the observations
+    Proximity,
+}
+
+impl std::fmt::Display for ScoringMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ternary => write!(f, "ternary"),
+            Self::Proximity => write!(f, "proximity"),
+        }
+    }
+}
+
+impl TryFrom<&str> for ScoringMode {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "ternary" => Ok(ScoringMode::Ternary),
+            "proximity" => Ok(ScoringMode::Proximity),
+            val => Err(anyhow!("invalid scoring mode: {}", val)),
+        }
+    }
+}
+
 impl ScoringField {
+    pub const ALL: [ScoringField; 7] = [
+        ScoringField::TempHigh,
+        ScoringField::TempLow,
+        ScoringField::WindSpeed,
+        ScoringField::WindDirection,
+        ScoringField::RainAmt,
+        ScoringField::SnowAmt,
+        ScoringField::Humidity,
+    ];
+
+    /// How far, in the field's own unit, an observation can land from the forecast and still be par
+    pub fn default_par_tolerance(&self) -> f64 {
+        match self {
+            Self::TempHigh | Self::TempLow | Self::WindSpeed => 0.0,
+            // degrees either side of the forecast heading
+            Self::WindDirection => 22.0,
+            // inches
+            Self::RainAmt => 0.1,
+            Self::SnowAmt => 0.5,
+            // percentage points
+            Self::Humidity => 5.0,
+        }
+    }
+
+    /// Values a proximity prediction for this field may take, wide enough for any real reading
+    /// while keeping the distances scored from them far from overflowing the total score
+    pub fn prediction_range(&self) -> RangeInclusive<f64> {
+        match self {
+            // fahrenheit, the default sanity bounds
+            Self::TempHigh | Self::TempLow => -200.0..=200.0,
+            // knots
+            Self::WindSpeed => 0.0..=300.0,
+            // compass degrees
+            Self::WindDirection => 0.0..=360.0,
+            // inches
+            Self::RainAmt | Self::SnowAmt => 0.0..=100.0,
+            // percent
+            Self::Humidity => 0.0..=100.0,
+        }
+    }
+
+    /// The event's override for this field's par band, or the default band when it has none
+    pub fn par_tolerance(&self, overrides: &ParTolerances) -> f64 {
+        overrides
+            .get(self)
+            .copied()
+            .unwrap_or_else(|| self.default_par_tolerance())
+    }
+
     /// Returns the default scoring fields (original behavior)
     pub fn defaults() -> Vec<ScoringField> {
         vec![
@@ -1768,6 +2562,20 @@ pub enum ValueOptions {
     Under,
 }
 
+impl ValueOptions {
+    /// Where an observation landed relative to its forecast, anything within `tolerance` either
+    /// side counts as par. A tiny epsilon keeps bands like 0.1 inclusive despite float rounding.
+    pub fn classify(forecast: f64, observed: f64, tolerance: f64) -> Self {
+        if (observed - forecast).abs() <= tolerance + 1e-9 {
+            ValueOptions::Par
+        } else if observed > forecast {
+            ValueOptions::Over
+        } else {
+            ValueOptions::Under
+        }
+    }
+}
+
 impl std::fmt::Display for ValueOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
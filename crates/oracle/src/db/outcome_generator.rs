@@ -1,4 +1,7 @@
+use anyhow::anyhow;
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 /// We are assuming the scoring mechanism does not allow for ties and every user has a unique score
 /// One additional outcome is the "refund all" outcome.
@@ -13,22 +16,85 @@ pub fn generate_ranking_permutations(num_players: usize, rankings: usize) -> Vec
     permutations
 }
 
-pub fn generate_outcome_messages(possible_user_outcomes: Vec<Vec<usize>>) -> Vec<Vec<u8>> {
+/// Byte layout of the outcome messages an event's locking points commit to and the oracle signs.
+/// Recorded on each event so an event is always signed and verified with the layout it was
+/// announced with, even after the oracle moves to a newer one.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum OutcomeMessageVersion {
+    /// The winning entry indices as big-endian `usize` bytes with no prefix, what events created
+    /// before the layout was versioned committed to
+    #[default]
+    V0 = 0,
+    /// A leading version byte followed by the `V0` bytes
+    V1 = 1,
+}
+
+impl OutcomeMessageVersion {
+    /// The layout new events are announced with
+    pub const CURRENT: Self = OutcomeMessageVersion::V1;
+
+    pub fn message(&self, winners: &[usize]) -> Vec<u8> {
+        let mut message = match self {
+            OutcomeMessageVersion::V0 => vec![],
+            OutcomeMessageVersion::V1 => vec![*self as u8],
+        };
+        message.extend(winners.iter().flat_map(|idx| idx.to_be_bytes()));
+        message
+    }
+}
+
+impl From<OutcomeMessageVersion> for u8 {
+    fn from(value: OutcomeMessageVersion) -> Self {
+        value as u8
+    }
+}
+
+impl TryFrom<u8> for OutcomeMessageVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OutcomeMessageVersion::V0),
+            1 => Ok(OutcomeMessageVersion::V1),
+            other => Err(anyhow!("unknown outcome message version {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for OutcomeMessageVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", *self as u8)
+    }
+}
+
+impl FromStr for OutcomeMessageVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let version: u8 = value
+            .trim()
+            .trim_start_matches(['v', 'V'])
+            .parse()
+            .map_err(|_| anyhow!("invalid outcome message version '{}'", value))?;
+        OutcomeMessageVersion::try_from(version)
+    }
+}
+
+pub fn generate_outcome_messages(
+    possible_user_outcomes: Vec<Vec<usize>>,
+    version: OutcomeMessageVersion,
+) -> Vec<Vec<u8>> {
     possible_user_outcomes
-        .into_iter()
-        .map(|inner_vec| {
-            inner_vec
-                .into_iter()
-                .flat_map(|num| num.to_be_bytes())
-                .collect::<Vec<u8>>()
-        })
+        .iter()
+        .map(|winners| version.message(winners))
         .collect()
 }
 
 #[cfg(test)]
 mod test {
 
-    use super::generate_ranking_permutations;
+    use super::{generate_outcome_messages, generate_ranking_permutations, OutcomeMessageVersion};
 
     #[test]
     fn can_generate_list_of_winners_n5() {
@@ -58,6 +124,28 @@ mod test {
         assert_eq!(permutations.len(), 970_201);
     }
 
+    #[test]
+    fn outcome_messages_carry_their_version() {
+        let outcomes = vec![vec![2, 0], vec![0, 1, 2]];
+        let legacy = generate_outcome_messages(outcomes.clone(), OutcomeMessageVersion::V0);
+        let versioned = generate_outcome_messages(outcomes, OutcomeMessageVersion::V1);
+
+        assert_eq!(
+            legacy[0],
+            [2usize.to_be_bytes(), 0usize.to_be_bytes()].concat()
+        );
+        for (legacy, versioned) in legacy.iter().zip(&versioned) {
+            assert_eq!(versioned[0], 1);
+            assert_eq!(&versioned[1..], legacy.as_slice());
+        }
+        assert_eq!(OutcomeMessageVersion::CURRENT, OutcomeMessageVersion::V1);
+        assert_eq!(
+            "v1".parse::<OutcomeMessageVersion>().unwrap(),
+            OutcomeMessageVersion::V1
+        );
+        assert!(OutcomeMessageVersion::try_from(2).is_err());
+    }
+
     #[test]
     #[ignore = "Slow test, skip for ci"]
     fn can_generate_list_of_winners_n200() {
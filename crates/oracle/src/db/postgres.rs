@@ -0,0 +1,1108 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use dlctix::secp::{MaybeScalar, Scalar};
+use dlctix::{musig2::secp256k1::XOnlyPublicKey, EventLockingConditions};
+use log::info;
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgPool, PgPoolOptions, PgRow},
+    types::Json,
+    Postgres, Row, Transaction,
+};
+use std::{collections::HashMap, time::Duration};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{
+    entries_per_event, from_unix_millis, unix_millis, ActiveEvent, ChangedEvents, CreateEventData,
+    DatabaseSettings, EntryLimitExceeded, Event, EventAggregates, EventFilter, EventIncludes,
+    EventStore, EventSummary, Forecasted, MaintenanceReport, MigrationStatus, Observed, OracleKey,
+    OutcomeMessageVersion, ScoringField, ScoringMode, SignEvent, ValueOptions, Weather,
+    WeatherChoices, WeatherEntry,
+};
+use crate::TemperatureUnit;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// Event store backed by Postgres, for deployments that already run and back up a Postgres server
+#[derive(Clone)]
+pub struct PostgresDatabase {
+    pool: PgPool,
+}
+
+impl PostgresDatabase {
+    /// Connects to `url` and runs the Postgres migrations, only `max_connections` applies from
+    /// the settings as the remaining knobs are SQLite pragmas
+    pub async fn new(url: &str, settings: DatabaseSettings) -> Result<Self> {
+        settings.validate()?;
+        let pool = PgPoolOptions::new()
+            .max_connections(settings.max_connections)
+            .acquire_timeout(Duration::from_secs(30))
+            .connect(url)
+            .await
+            .context("Failed to create Postgres connection pool")?;
+
+        MIGRATOR
+            .run(&pool)
+            .await
+            .context("Failed to run database migrations")?;
+
+        info!(
+            "Postgres event store initialized, max_connections={}",
+            settings.max_connections
+        );
+        Ok(Self { pool })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    async fn get_basic_event(&self, id: &Uuid, now: OffsetDateTime) -> Result<Event> {
+        let row = sqlx::query(
+            "SELECT id, signing_date, start_observation_date, end_observation_date,
+                    event_announcement, locations, total_allowed_entries,
+                    number_of_places_win, number_of_values_per_entry,
+                    attestation_signature, nonce, coordinator_pubkey, scoring_fields,
+                    par_tolerances, scoring_mode, settlement_delay_hours, entry_cutoff,
+                    outcome_message_version, created_at, last_modified
+             FROM events WHERE id = $1",
+        )
+        .bind(id.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+
+        let id: String = row.get("id");
+        let signing_date = OffsetDateTime::from_unix_timestamp(row.get("signing_date"))?;
+        let start_observation_date =
+            OffsetDateTime::from_unix_timestamp(row.get("start_observation_date"))?;
+        let end_observation_date =
+            OffsetDateTime::from_unix_timestamp(row.get("end_observation_date"))?;
+        let Json(locations): Json<Vec<String>> = row.get("locations");
+        let nonce_bytes: Vec<u8> = row.get("nonce");
+        let nonce: Scalar = serde_json::from_slice(&nonce_bytes)?;
+        let announcement_bytes: Vec<u8> = row.get("event_announcement");
+        let event_announcement: EventLockingConditions =
+            serde_json::from_slice(&announcement_bytes)?;
+        let attestation = decode_attestation(&row);
+        let coordinator_pubkey: Option<String> = row.get("coordinator_pubkey");
+
+        let status = super::get_status(
+            attestation,
+            start_observation_date,
+            end_observation_date,
+            now,
+        );
+
+        Ok(Event {
+            id: Uuid::parse_str(&id)?,
+            signing_date,
+            start_observation_date,
+            end_observation_date,
+            locations,
+            number_of_values_per_entry: row.get("number_of_values_per_entry"),
+            status,
+            total_allowed_entries: row.get("total_allowed_entries"),
+            entry_ids: vec![],
+            number_of_places_win: row.get("number_of_places_win"),
+            entries: vec![],
+            weather: vec![],
+            nonce,
+            event_announcement,
+            attestation,
+            coordinator_pubkey: coordinator_pubkey.unwrap_or_default(),
+            scoring_fields: decode_scoring_fields(&row),
+            par_tolerances: decode_field_values(&row, "par_tolerances"),
+            scoring_mode: ScoringMode::try_from(row.get::<&str, _>("scoring_mode"))?,
+            settlement_delay_hours: decode_settlement_delay(&row),
+            entry_cutoff: decode_entry_cutoff(&row)?,
+            outcome_message_version: decode_outcome_message_version(&row)?,
+            created_at: Some(OffsetDateTime::from_unix_timestamp(row.get("created_at"))?),
+            last_modified: Some(from_unix_millis(row.get("last_modified"))?),
+        })
+    }
+
+    async fn get_event_entries(
+        &self,
+        event_id: &Uuid,
+        with_choices: bool,
+    ) -> Result<Vec<WeatherEntry>> {
+        let rows = sqlx::query(
+            "SELECT id, score, base_score
+             FROM events_entries WHERE event_id = $1",
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let entry_id = Uuid::parse_str(row.get("id"))?;
+            let choices = if with_choices {
+                self.get_entry_choices(&entry_id).await?
+            } else {
+                vec![]
+            };
+
+            entries.push(WeatherEntry {
+                id: entry_id,
+                event_id: *event_id,
+                score: row.get::<Option<i64>, _>("score").filter(|&s| s != 0),
+                base_score: row.get::<Option<i64>, _>("base_score").filter(|&s| s != 0),
+                expected_observations: choices,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    async fn get_entry_choices(&self, entry_id: &Uuid) -> Result<Vec<WeatherChoices>> {
+        let rows = sqlx::query(
+            "SELECT station, temp_low, temp_high, wind_speed,
+                    wind_direction, rain_amt, snow_amt, humidity, predictions
+             FROM expected_observations WHERE entry_id = $1
+             ORDER BY id",
+        )
+        .bind(entry_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let choice = |row: &PgRow, column: &str| {
+            row.get::<Option<String>, _>(column)
+                .and_then(|s| ValueOptions::try_from(s).ok())
+        };
+        Ok(rows
+            .iter()
+            .map(|row| WeatherChoices {
+                stations: row.get("station"),
+                temp_low: choice(row, "temp_low"),
+                temp_high: choice(row, "temp_high"),
+                wind_speed: choice(row, "wind_speed"),
+                wind_direction: choice(row, "wind_direction"),
+                rain_amt: choice(row, "rain_amt"),
+                snow_amt: choice(row, "snow_amt"),
+                humidity: choice(row, "humidity"),
+                predictions: decode_field_values(row, "predictions"),
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl EventStore for PostgresDatabase {
+    async fn migration_status(&self) -> Result<MigrationStatus> {
+        let current_version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+                .fetch_one(&self.pool)
+                .await
+                .context("Failed to read applied migrations")?;
+        let latest_version = MIGRATOR.iter().map(|migration| migration.version).max();
+        Ok(MigrationStatus {
+            current_version,
+            latest_version,
+            up_to_date: current_version == latest_version,
+        })
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .context("Database connectivity check failed")?;
+        Ok(())
+    }
+
+    /// Postgres manages its own WAL, nothing to flush on shutdown
+    async fn checkpoint(&self) {}
+
+    /// Runs `ANALYZE` and optionally `VACUUM`, page counts are the database size in blocks
+    async fn maintenance(&self, vacuum: bool) -> Result<MaintenanceReport> {
+        let page_count_query = "SELECT pg_database_size(current_database())
+                                / current_setting('block_size')::BIGINT";
+        let page_count_before: i64 = sqlx::query_scalar(page_count_query)
+            .fetch_one(&self.pool)
+            .await?;
+
+        sqlx::query("ANALYZE")
+            .execute(&self.pool)
+            .await
+            .context("Failed to analyze database")?;
+        if vacuum {
+            sqlx::query("VACUUM")
+                .execute(&self.pool)
+                .await
+                .context("Failed to vacuum database")?;
+        }
+
+        let page_count_after: i64 = sqlx::query_scalar(page_count_query)
+            .fetch_one(&self.pool)
+            .await?;
+        info!(
+            "Database maintenance complete: pages {} -> {}, vacuumed: {}",
+            page_count_before, page_count_after, vacuum
+        );
+        Ok(MaintenanceReport {
+            page_count_before,
+            page_count_after,
+            // Postgres reuses dead tuples in place rather than keeping a freelist
+            freelist_count_after: 0,
+            vacuumed: vacuum,
+        })
+    }
+
+    async fn add_oracle_metadata(&self, pubkey: XOnlyPublicKey) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO oracle_metadata (pubkey, name) VALUES ($1, $2)
+             ON CONFLICT(pubkey) DO NOTHING",
+        )
+        .bind(pubkey.serialize().to_vec())
+        .bind("4casttruth")
+        .execute(&mut *tx)
+        .await?;
+        // the first key covers every event until the first rotation
+        sqlx::query(
+            "INSERT INTO oracle_keys (pubkey, active_from) VALUES ($1, 0)
+             ON CONFLICT(pubkey) DO NOTHING",
+        )
+        .bind(pubkey.serialize().to_vec())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_stored_public_key(&self) -> Result<XOnlyPublicKey> {
+        let row: (Vec<u8>,) = sqlx::query_as("SELECT pubkey FROM oracle_metadata LIMIT 1")
+            .fetch_one(&self.pool)
+            .await?;
+
+        XOnlyPublicKey::from_slice(&row.0).map_err(|e| anyhow::anyhow!("Invalid pubkey: {}", e))
+    }
+
+    async fn get_oracle_name(&self) -> Result<String> {
+        let row: (String,) = sqlx::query_as("SELECT name FROM oracle_metadata LIMIT 1")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.0)
+    }
+
+    async fn get_oracle_keys(&self) -> Result<Vec<OracleKey>> {
+        let rows: Vec<(Vec<u8>, i64, Option<i64>)> = sqlx::query_as(
+            "SELECT pubkey, active_from, active_until FROM oracle_keys ORDER BY active_from",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|(pubkey, from, until)| OracleKey::from_row(pubkey, *from, *until))
+            .collect()
+    }
+
+    async fn rotate_oracle_key(&self, pubkey: XOnlyPublicKey, at: OffsetDateTime) -> Result<()> {
+        let pubkey_bytes = pubkey.serialize().to_vec();
+        let at = at.unix_timestamp();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE oracle_keys SET active_until = $1 WHERE active_until IS NULL")
+            .bind(at)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO oracle_keys (pubkey, active_from) VALUES ($1, $2)")
+            .bind(&pubkey_bytes)
+            .bind(at)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE oracle_metadata SET pubkey = $1, updated_at = $2")
+            .bind(&pubkey_bytes)
+            .bind(at)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_event_oracle_key(&self, event_id: &Uuid) -> Result<OracleKey> {
+        let row: (Vec<u8>, i64, Option<i64>) = sqlx::query_as(
+            "SELECT k.pubkey, k.active_from, k.active_until
+             FROM events e
+             JOIN oracle_keys k ON k.active_from <= e.created_at
+                AND (k.active_until IS NULL OR e.created_at < k.active_until)
+             WHERE e.id = $1",
+        )
+        .bind(event_id.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+        OracleKey::from_row(&row.0, row.1, row.2)
+    }
+
+    async fn add_event(&self, event: CreateEventData, now: OffsetDateTime) -> Result<Event> {
+        sqlx::query(
+            "INSERT INTO events (
+                id, total_allowed_entries, number_of_places_win,
+                number_of_values_per_entry, nonce, signing_date,
+                start_observation_date, end_observation_date,
+                locations, event_announcement, coordinator_pubkey,
+                scoring_fields, par_tolerances, scoring_mode, settlement_delay_hours,
+                entry_cutoff, outcome_message_version, created_at, last_modified
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                      $19)",
+        )
+        .bind(event.id.to_string())
+        .bind(event.total_allowed_entries)
+        .bind(event.number_of_places_win)
+        .bind(event.number_of_values_per_entry)
+        .bind(serde_json::to_vec(&event.nonce)?)
+        .bind(event.signing_date.unix_timestamp())
+        .bind(event.start_observation_date.unix_timestamp())
+        .bind(event.end_observation_date.unix_timestamp())
+        .bind(Json(&event.locations))
+        .bind(serde_json::to_vec(&event.event_announcement)?)
+        .bind(&event.coordinator_pubkey)
+        .bind(Json(&event.scoring_fields))
+        .bind(encode_field_values(&event.par_tolerances))
+        .bind(event.scoring_mode.to_string())
+        .bind(event.settlement_delay_hours.map(i64::from))
+        .bind(event.entry_cutoff.map(OffsetDateTime::unix_timestamp))
+        .bind(i16::from(u8::from(event.outcome_message_version)))
+        .bind(now.unix_timestamp())
+        .bind(unix_millis(now))
+        .execute(&self.pool)
+        .await?;
+
+        let mut created: Event = event.into();
+        // Truncated to the resolution they are stored at, so a later read returns the same event
+        created.created_at = Some(OffsetDateTime::from_unix_timestamp(now.unix_timestamp())?);
+        created.last_modified = Some(from_unix_millis(unix_millis(now))?);
+        Ok(created)
+    }
+
+    async fn add_event_entries(&self, entries: Vec<WeatherEntry>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        check_entry_limit(&mut tx, &entries).await?;
+        for entry in &entries {
+            sqlx::query("INSERT INTO events_entries (id, event_id) VALUES ($1, $2)")
+                .bind(entry.id.to_string())
+                .bind(entry.event_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+            insert_entry_choices(&mut tx, entry).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn import_event(&self, event: Event, now: OffsetDateTime) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO events (
+                id, total_allowed_entries, number_of_places_win,
+                number_of_values_per_entry, nonce, signing_date,
+                start_observation_date, end_observation_date,
+                locations, event_announcement, coordinator_pubkey,
+                attestation_signature, scoring_fields, par_tolerances, scoring_mode,
+                settlement_delay_hours, entry_cutoff, outcome_message_version, created_at,
+                last_modified
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                      $19, $20)
+            ON CONFLICT(id) DO NOTHING",
+        )
+        .bind(event.id.to_string())
+        .bind(event.total_allowed_entries)
+        .bind(event.number_of_places_win)
+        .bind(event.number_of_values_per_entry)
+        .bind(serde_json::to_vec(&event.nonce)?)
+        .bind(event.signing_date.unix_timestamp())
+        .bind(event.start_observation_date.unix_timestamp())
+        .bind(event.end_observation_date.unix_timestamp())
+        .bind(Json(&event.locations))
+        .bind(serde_json::to_vec(&event.event_announcement)?)
+        .bind(&event.coordinator_pubkey)
+        .bind(
+            event
+                .attestation
+                .as_ref()
+                .map(serde_json::to_vec)
+                .transpose()?,
+        )
+        .bind(Json(&event.scoring_fields))
+        .bind(encode_field_values(&event.par_tolerances))
+        .bind(event.scoring_mode.to_string())
+        .bind(event.settlement_delay_hours.map(i64::from))
+        .bind(event.entry_cutoff.map(OffsetDateTime::unix_timestamp))
+        .bind(i16::from(u8::from(event.outcome_message_version)))
+        .bind(event.created_at.unwrap_or(now).unix_timestamp())
+        .bind(unix_millis(event.last_modified.unwrap_or(now)))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        for entry in &event.entries {
+            sqlx::query(
+                "INSERT INTO events_entries (id, event_id, score, base_score)
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(entry.id.to_string())
+            .bind(event.id.to_string())
+            .bind(entry.score.unwrap_or_default())
+            .bind(entry.base_score.unwrap_or_default())
+            .execute(&mut *tx)
+            .await?;
+            insert_entry_choices(&mut tx, entry).await?;
+        }
+
+        let weather_ids =
+            insert_weather(&mut tx, &event.weather, &TemperatureUnit::default()).await?;
+        link_weather(&mut tx, event.id, &weather_ids).await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn delete_event(&self, id: &Uuid, now: OffsetDateTime) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO deleted_events (id, deleted_at)
+             SELECT id, $1 FROM events WHERE id = $2
+             ON CONFLICT(id) DO UPDATE SET deleted_at = EXCLUDED.deleted_at",
+        )
+        .bind(unix_millis(now))
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await?;
+        let deleted = sqlx::query("DELETE FROM events WHERE id = $1")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        delete_orphaned_weather(&mut tx).await?;
+        tx.commit().await?;
+        Ok(deleted > 0)
+    }
+
+    async fn purge_events_before(
+        &self,
+        cutoff: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO deleted_events (id, deleted_at)
+             SELECT id, $1 FROM events WHERE signing_date < $2
+             ON CONFLICT(id) DO UPDATE SET deleted_at = EXCLUDED.deleted_at",
+        )
+        .bind(unix_millis(now))
+        .bind(cutoff.unix_timestamp())
+        .execute(&mut *tx)
+        .await?;
+        let deleted = sqlx::query("DELETE FROM events WHERE signing_date < $1")
+            .bind(cutoff.unix_timestamp())
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        delete_orphaned_weather(&mut tx).await?;
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    async fn list_event_ids(&self) -> Result<Vec<Uuid>> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM events ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+        ids.iter()
+            .map(|id| Uuid::parse_str(id).map_err(Into::into))
+            .collect()
+    }
+
+    async fn changed_events(
+        &self,
+        since: OffsetDateTime,
+        until: OffsetDateTime,
+    ) -> Result<ChangedEvents> {
+        // Observation dates are unix seconds, scaled up to compare against the window
+        let changed: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM events
+             WHERE last_modified >= $1
+                OR start_observation_date * 1000 BETWEEN $1 AND $2
+                OR end_observation_date * 1000 BETWEEN $1 AND $2
+             ORDER BY id",
+        )
+        .bind(unix_millis(since))
+        .bind(unix_millis(until))
+        .fetch_all(&self.pool)
+        .await?;
+        let deleted: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM deleted_events d
+             WHERE deleted_at >= $1 AND NOT EXISTS (SELECT 1 FROM events e WHERE e.id = d.id)
+             ORDER BY id",
+        )
+        .bind(unix_millis(since))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let parse = |ids: Vec<String>| -> Result<Vec<Uuid>> {
+            ids.iter()
+                .map(|id| Uuid::parse_str(id).map_err(Into::into))
+                .collect()
+        };
+        Ok(ChangedEvents {
+            changed: parse(changed)?,
+            deleted: parse(deleted)?,
+        })
+    }
+
+    async fn get_event_with(
+        &self,
+        id: &Uuid,
+        includes: &EventIncludes,
+        now: OffsetDateTime,
+    ) -> Result<Event> {
+        let mut event = self.get_basic_event(id, now).await?;
+        if includes.entries {
+            event.entries = self.get_event_entries(id, includes.entry_choices).await?;
+            event.entry_ids = event.entries.iter().map(|e| e.id).collect();
+        }
+        if includes.weather {
+            event.weather = self.get_event_weather(*id).await?;
+        }
+        Ok(event)
+    }
+
+    async fn event_exists(&self, id: &Uuid) -> Result<bool> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM events WHERE id = $1)")
+            .bind(id.to_string())
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(exists)
+    }
+
+    async fn event_was_deleted(&self, id: &Uuid) -> Result<bool> {
+        let deleted: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM deleted_events WHERE id = $1)")
+                .bind(id.to_string())
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(deleted)
+    }
+
+    async fn get_event_weather_entries(&self, event_id: &Uuid) -> Result<Vec<WeatherEntry>> {
+        self.get_event_entries(event_id, true).await
+    }
+
+    async fn get_active_events(&self, now: OffsetDateTime) -> Result<Vec<ActiveEvent>> {
+        let rows = sqlx::query(
+            "SELECT e.id, e.signing_date, e.start_observation_date, e.end_observation_date,
+                    e.locations, e.total_allowed_entries, e.number_of_places_win,
+                    e.number_of_values_per_entry, e.attestation_signature,
+                    e.scoring_fields, e.par_tolerances, e.scoring_mode,
+                    e.settlement_delay_hours, COUNT(ee.id) as total_entries
+             FROM events e
+             LEFT JOIN events_entries ee ON ee.event_id = e.id
+             WHERE e.attestation_signature IS NULL
+             GROUP BY e.id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+            let signing_date = OffsetDateTime::from_unix_timestamp(row.get("signing_date"))?;
+            let start_observation_date =
+                OffsetDateTime::from_unix_timestamp(row.get("start_observation_date"))?;
+            let end_observation_date =
+                OffsetDateTime::from_unix_timestamp(row.get("end_observation_date"))?;
+            let Json(locations): Json<Vec<String>> = row.get("locations");
+            let attestation = decode_attestation(&row);
+
+            let status = super::get_status(
+                attestation,
+                start_observation_date,
+                end_observation_date,
+                now,
+            );
+
+            events.push(ActiveEvent {
+                id: Uuid::parse_str(&id)?,
+                locations,
+                signing_date,
+                start_observation_date,
+                end_observation_date,
+                status,
+                total_allowed_entries: row.get("total_allowed_entries"),
+                total_entries: row.get("total_entries"),
+                number_of_values_per_entry: row.get("number_of_values_per_entry"),
+                number_of_places_win: row.get("number_of_places_win"),
+                attestation,
+                scoring_fields: decode_scoring_fields(&row),
+                par_tolerances: decode_field_values(&row, "par_tolerances"),
+                scoring_mode: ScoringMode::try_from(row.get::<&str, _>("scoring_mode"))?,
+                settlement_delay_hours: decode_settlement_delay(&row),
+            });
+        }
+
+        Ok(events)
+    }
+
+    async fn get_events_to_sign(
+        &self,
+        event_ids: Vec<Uuid>,
+        now: OffsetDateTime,
+    ) -> Result<Vec<SignEvent>> {
+        if event_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let ids: Vec<String> = event_ids.iter().map(|id| id.to_string()).collect();
+        let rows = sqlx::query(
+            "SELECT id, signing_date, start_observation_date, end_observation_date,
+                    number_of_places_win, number_of_values_per_entry,
+                    attestation_signature, nonce, event_announcement, outcome_message_version
+             FROM events
+             WHERE attestation_signature IS NULL AND id = ANY($1)",
+        )
+        .bind(&ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+            let signing_date = OffsetDateTime::from_unix_timestamp(row.get("signing_date"))?;
+            let start_observation_date =
+                OffsetDateTime::from_unix_timestamp(row.get("start_observation_date"))?;
+            let end_observation_date =
+                OffsetDateTime::from_unix_timestamp(row.get("end_observation_date"))?;
+            let nonce_bytes: Vec<u8> = row.get("nonce");
+            let nonce: Scalar = serde_json::from_slice(&nonce_bytes)?;
+            let announcement_bytes: Vec<u8> = row.get("event_announcement");
+            let event_announcement: EventLockingConditions =
+                serde_json::from_slice(&announcement_bytes)?;
+            let attestation = decode_attestation(&row);
+
+            let status = super::get_status(
+                attestation,
+                start_observation_date,
+                end_observation_date,
+                now,
+            );
+
+            events.push(SignEvent {
+                id: Uuid::parse_str(&id)?,
+                signing_date,
+                start_observation_date,
+                end_observation_date,
+                status,
+                nonce,
+                event_announcement,
+                number_of_places_win: row.get("number_of_places_win"),
+                number_of_values_per_entry: row.get("number_of_values_per_entry"),
+                attestation,
+                outcome_message_version: decode_outcome_message_version(&row)?,
+            });
+        }
+
+        Ok(events)
+    }
+
+    async fn update_event_attestation(&self, event: &SignEvent, now: OffsetDateTime) -> Result<()> {
+        let Some(attestation) = event.attestation else {
+            return Err(anyhow::anyhow!("No attestation to update"));
+        };
+
+        sqlx::query(
+            "UPDATE events SET attestation_signature = $1, last_modified = $2 WHERE id = $3",
+        )
+        .bind(serde_json::to_vec(&attestation)?)
+        .bind(unix_millis(now))
+        .bind(event.id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn update_entry_scores(&self, entry_scores: Vec<(Uuid, i64, i64)>) -> Result<()> {
+        if entry_scores.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for (entry_id, score, base_score) in entry_scores {
+            sqlx::query("UPDATE events_entries SET score = $1, base_score = $2 WHERE id = $3")
+                .bind(score)
+                .bind(base_score)
+                .bind(entry_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_event_coordinator_pubkey(&self, event_id: Uuid) -> Result<String> {
+        let row: (Option<String>,) =
+            sqlx::query_as("SELECT coordinator_pubkey FROM events WHERE id = $1")
+                .bind(event_id.to_string())
+                .fetch_one(&self.pool)
+                .await?;
+
+        row.0
+            .ok_or_else(|| anyhow::anyhow!("Coordinator pubkey not found"))
+    }
+
+    async fn filtered_list_events(
+        &self,
+        filter: EventFilter,
+        now: OffsetDateTime,
+    ) -> Result<Vec<EventSummary>> {
+        let ids: Option<Vec<String>> = filter
+            .event_ids
+            .map(|ids| ids.iter().map(|id| id.to_string()).collect());
+        let query = format!(
+            "SELECT e.id, e.signing_date, e.start_observation_date, e.end_observation_date,
+                    e.locations, e.total_allowed_entries, e.number_of_places_win,
+                    e.number_of_values_per_entry, e.attestation_signature, e.nonce,
+                    COUNT(ee.id) as total_entries
+             FROM events e
+             LEFT JOIN events_entries ee ON ee.event_id = e.id
+             WHERE ($1::TEXT[] IS NULL OR e.id = ANY($1))
+               AND ($3::BIGINT IS NULL OR e.end_observation_date >= $3)
+               AND ($4::BIGINT IS NULL OR e.start_observation_date <= $4)
+               AND ($5::BIGINT IS NULL OR e.signing_date >= $5)
+               AND ($6::BIGINT IS NULL OR e.signing_date <= $6)
+             GROUP BY e.id
+             ORDER BY {}
+             LIMIT $2 OFFSET $7",
+            filter.sort.order_by("e", filter.direction)
+        );
+        let rows = sqlx::query(&query)
+            .bind(ids)
+            .bind(filter.limit.map(|limit| limit as i64))
+            .bind(filter.observation_after.map(|date| date.unix_timestamp()))
+            .bind(filter.observation_before.map(|date| date.unix_timestamp()))
+            .bind(filter.signing_after.map(|date| date.unix_timestamp()))
+            .bind(filter.signing_before.map(|date| date.unix_timestamp()))
+            .bind(filter.offset.map(|offset| offset as i64))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let id = Uuid::parse_str(row.get("id"))?;
+            let signing_date = OffsetDateTime::from_unix_timestamp(row.get("signing_date"))?;
+            let start_observation_date =
+                OffsetDateTime::from_unix_timestamp(row.get("start_observation_date"))?;
+            let end_observation_date =
+                OffsetDateTime::from_unix_timestamp(row.get("end_observation_date"))?;
+            let Json(locations): Json<Vec<String>> = row.get("locations");
+            let nonce_bytes: Vec<u8> = row.get("nonce");
+            let nonce: Scalar = serde_json::from_slice(&nonce_bytes)?;
+            let attestation = decode_attestation(&row);
+
+            let status = super::get_status(
+                attestation,
+                start_observation_date,
+                end_observation_date,
+                now,
+            );
+
+            events.push(EventSummary {
+                id,
+                signing_date,
+                start_observation_date,
+                end_observation_date,
+                locations,
+                number_of_values_per_entry: row.get("number_of_values_per_entry"),
+                status,
+                total_allowed_entries: row.get("total_allowed_entries"),
+                total_entries: row.get("total_entries"),
+                number_of_places_win: row.get("number_of_places_win"),
+                weather: self.get_event_weather(id).await?,
+                attestation,
+                nonce,
+            });
+        }
+
+        Ok(events)
+    }
+
+    async fn event_aggregates(
+        &self,
+        now: OffsetDateTime,
+        signed_since: OffsetDateTime,
+    ) -> Result<EventAggregates> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS total_events,
+                    COUNT(*) FILTER (WHERE attestation_signature IS NULL
+                                     AND $1 < start_observation_date) AS live,
+                    COUNT(*) FILTER (WHERE attestation_signature IS NULL
+                                     AND start_observation_date <= $1
+                                     AND $1 < end_observation_date) AS running,
+                    COUNT(*) FILTER (WHERE attestation_signature IS NULL
+                                     AND end_observation_date <= $1) AS completed,
+                    COUNT(attestation_signature) AS signed,
+                    COUNT(*) FILTER (WHERE attestation_signature IS NOT NULL
+                                     AND signing_date >= $2) AS signed_recently,
+                    (SELECT COUNT(*) FROM events_entries) AS total_entries
+             FROM events",
+        )
+        .bind(now.unix_timestamp())
+        .bind(signed_since.unix_timestamp())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(EventAggregates {
+            total_events: row.get("total_events"),
+            live: row.get("live"),
+            running: row.get("running"),
+            completed: row.get("completed"),
+            signed: row.get("signed"),
+            total_entries: row.get("total_entries"),
+            signed_recently: row.get("signed_recently"),
+            window_days: 0,
+        })
+    }
+
+    async fn update_weather_station_data(
+        &self,
+        event_id: Uuid,
+        weather: Vec<Weather>,
+        temp_unit: &TemperatureUnit,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let weather_ids = insert_weather(&mut tx, &weather, temp_unit).await?;
+        link_weather(&mut tx, event_id, &weather_ids).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_event_weather_in_unit(
+        &self,
+        event_id: Uuid,
+        temp_unit: &TemperatureUnit,
+    ) -> Result<Vec<Weather>> {
+        let rows = sqlx::query(
+            "SELECT w.station_id, w.observed_date, w.observed_temp_low, w.observed_temp_high,
+                    w.observed_wind_speed, w.forecasted_date, w.forecasted_temp_low,
+                    w.forecasted_temp_high, w.forecasted_wind_speed, w.temp_unit_code
+             FROM weather w
+             JOIN events_weather ew ON ew.weather_id = w.id
+             WHERE ew.event_id = $1
+             ORDER BY ew.id",
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut weather = Vec::new();
+        for row in rows {
+            let observed = match row.get::<Option<i64>, _>("observed_date") {
+                Some(date) => Some(Observed {
+                    date: OffsetDateTime::from_unix_timestamp(date)?,
+                    temp_low: row.get("observed_temp_low"),
+                    temp_high: row.get("observed_temp_high"),
+                    wind_speed: row.get("observed_wind_speed"),
+                }),
+                None => None,
+            };
+
+            let forecasted = Forecasted {
+                date: OffsetDateTime::from_unix_timestamp(row.get("forecasted_date"))?,
+                temp_low: row.get("forecasted_temp_low"),
+                temp_high: row.get("forecasted_temp_high"),
+                wind_speed: row.get("forecasted_wind_speed"),
+            };
+
+            let mut station_weather = Weather {
+                station_id: row.get("station_id"),
+                observed,
+                forecasted,
+            };
+            let stored_unit: String = row.get("temp_unit_code");
+            station_weather.convert_temperature(&stored_unit, temp_unit);
+            weather.push(station_weather);
+        }
+
+        Ok(weather)
+    }
+
+    async fn get_weather_entry(&self, event_id: &Uuid, entry_id: &Uuid) -> Result<WeatherEntry> {
+        let row = sqlx::query(
+            "SELECT score, base_score
+             FROM events_entries
+             WHERE id = $1 AND event_id = $2",
+        )
+        .bind(entry_id.to_string())
+        .bind(event_id.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(WeatherEntry {
+            id: *entry_id,
+            event_id: *event_id,
+            score: row.get::<Option<i64>, _>("score").filter(|&s| s != 0),
+            base_score: row.get::<Option<i64>, _>("base_score").filter(|&s| s != 0),
+            expected_observations: self.get_entry_choices(entry_id).await?,
+        })
+    }
+}
+
+fn decode_attestation(row: &PgRow) -> Option<MaybeScalar> {
+    row.get::<Option<Vec<u8>>, _>("attestation_signature")
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+/// Per field values (par tolerances, numeric predictions) store NULL when empty so they read back the
+/// same as rows from before their column existed
+fn encode_field_values(
+    values: &HashMap<ScoringField, f64>,
+) -> Option<Json<&HashMap<ScoringField, f64>>> {
+    (!values.is_empty()).then_some(Json(values))
+}
+
+fn decode_field_values(row: &PgRow, column: &str) -> HashMap<ScoringField, f64> {
+    row.get::<Option<Json<HashMap<ScoringField, f64>>>, _>(column)
+        .map(|Json(values)| values)
+        .unwrap_or_default()
+}
+
+fn decode_scoring_fields(row: &PgRow) -> Vec<ScoringField> {
+    row.get::<Option<Json<Vec<ScoringField>>>, _>("scoring_fields")
+        .map(|Json(fields)| fields)
+        .unwrap_or_else(ScoringField::defaults)
+}
+
+/// Locks each event row so concurrent submissions to the same event count entries one at a time
+async fn check_entry_limit(
+    tx: &mut Transaction<'_, Postgres>,
+    entries: &[WeatherEntry],
+) -> Result<()> {
+    for (event_id, new) in entries_per_event(entries) {
+        let (allowed,): (i64,) =
+            sqlx::query_as("SELECT total_allowed_entries FROM events WHERE id = $1 FOR UPDATE")
+                .bind(event_id.to_string())
+                .fetch_one(&mut **tx)
+                .await?;
+        let (existing,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM events_entries WHERE event_id = $1")
+                .bind(event_id.to_string())
+                .fetch_one(&mut **tx)
+                .await?;
+        if existing + new > allowed {
+            return Err(EntryLimitExceeded {
+                event_id,
+                allowed,
+                existing,
+                new,
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+async fn insert_entry_choices(
+    tx: &mut Transaction<'_, Postgres>,
+    entry: &WeatherEntry,
+) -> Result<()> {
+    for choice in &entry.expected_observations {
+        sqlx::query(
+            "INSERT INTO expected_observations
+             (entry_id, station, temp_low, temp_high, wind_speed,
+              wind_direction, rain_amt, snow_amt, humidity, predictions)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(entry.id.to_string())
+        .bind(&choice.stations)
+        .bind(choice.temp_low.as_ref().map(|v| v.to_string()))
+        .bind(choice.temp_high.as_ref().map(|v| v.to_string()))
+        .bind(choice.wind_speed.as_ref().map(|v| v.to_string()))
+        .bind(choice.wind_direction.as_ref().map(|v| v.to_string()))
+        .bind(choice.rain_amt.as_ref().map(|v| v.to_string()))
+        .bind(choice.snow_amt.as_ref().map(|v| v.to_string()))
+        .bind(choice.humidity.as_ref().map(|v| v.to_string()))
+        .bind(encode_field_values(&choice.predictions))
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn insert_weather(
+    tx: &mut Transaction<'_, Postgres>,
+    weather: &[Weather],
+    temp_unit: &TemperatureUnit,
+) -> Result<Vec<Uuid>> {
+    let mut weather_ids = Vec::new();
+    for w in weather {
+        let weather_id = Uuid::now_v7();
+        sqlx::query(
+            "INSERT INTO weather (
+                id, station_id, observed_date, observed_temp_low,
+                observed_temp_high, observed_wind_speed,
+                forecasted_date, forecasted_temp_low,
+                forecasted_temp_high, forecasted_wind_speed, temp_unit_code
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+        )
+        .bind(weather_id.to_string())
+        .bind(&w.station_id)
+        .bind(w.observed.as_ref().map(|o| o.date.unix_timestamp()))
+        .bind(w.observed.as_ref().map(|o| o.temp_low))
+        .bind(w.observed.as_ref().map(|o| o.temp_high))
+        .bind(w.observed.as_ref().map(|o| o.wind_speed))
+        .bind(w.forecasted.date.unix_timestamp())
+        .bind(w.forecasted.temp_low)
+        .bind(w.forecasted.temp_high)
+        .bind(w.forecasted.wind_speed)
+        .bind(temp_unit.to_string())
+        .execute(&mut **tx)
+        .await?;
+        weather_ids.push(weather_id);
+    }
+    Ok(weather_ids)
+}
+
+async fn link_weather(
+    tx: &mut Transaction<'_, Postgres>,
+    event_id: Uuid,
+    weather_ids: &[Uuid],
+) -> Result<()> {
+    for weather_id in weather_ids {
+        sqlx::query("INSERT INTO events_weather (id, event_id, weather_id) VALUES ($1, $2, $3)")
+            .bind(Uuid::now_v7().to_string())
+            .bind(event_id.to_string())
+            .bind(weather_id.to_string())
+            .execute(&mut **tx)
+            .await?;
+    }
+    Ok(())
+}
+
+async fn delete_orphaned_weather(tx: &mut Transaction<'_, Postgres>) -> Result<()> {
+    sqlx::query(
+        "DELETE FROM weather w
+         WHERE NOT EXISTS (SELECT 1 FROM events_weather ew WHERE ew.weather_id = w.id)",
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+fn decode_settlement_delay(row: &PgRow) -> Option<u32> {
+    row.get::<Option<i64>, _>("settlement_delay_hours")
+        .and_then(|hours| u32::try_from(hours).ok())
+}
+
+fn decode_outcome_message_version(row: &PgRow) -> Result<OutcomeMessageVersion> {
+    let version: i16 = row.get("outcome_message_version");
+    OutcomeMessageVersion::try_from(u8::try_from(version)?)
+}
+
+fn decode_entry_cutoff(row: &PgRow) -> Result<Option<OffsetDateTime>> {
+    Ok(row
+        .get::<Option<i64>, _>("entry_cutoff")
+        .map(OffsetDateTime::from_unix_timestamp)
+        .transpose()?)
+}
@@ -1,12 +1,14 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
 use dlctix::secp::{MaybeScalar, Scalar};
 use dlctix::{musig2::secp256k1::XOnlyPublicKey, EventLockingConditions};
 use log::info;
 use sqlx::{
+    migrate::Migrator,
     sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions},
-    Row,
+    Row, Sqlite, Transaction,
 };
-use std::{future::Future, path::Path, str::FromStr, time::Duration};
+use std::{collections::HashMap, future::Future, path::Path, str::FromStr, time::Duration};
 use time::OffsetDateTime;
 use tokio::{
     fs::create_dir_all,
@@ -15,9 +17,62 @@ use tokio::{
 use uuid::Uuid;
 
 use super::{
-    ActiveEvent, CreateEventData, Event, EventFilter, EventSummary, Forecasted, Observed,
-    ScoringField, SignEvent, ValueOptions, Weather, WeatherChoices, WeatherEntry,
+    entries_per_event, from_unix_millis, unix_millis, ActiveEvent, ChangedEvents, CreateEventData,
+    EntryLimitExceeded, Event, EventAggregates, EventFilter, EventIncludes, EventStore,
+    EventSummary, Forecasted, MaintenanceReport, MigrationStatus, Observed, OracleKey,
+    OutcomeMessageVersion, ScoringField, ScoringMode, SignEvent, ValueOptions, Weather,
+    WeatherChoices, WeatherEntry,
 };
+use crate::TemperatureUnit;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+pub const DEFAULT_DB_MAX_CONNECTIONS: u32 = 5;
+pub const DEFAULT_DB_BUSY_TIMEOUT_MS: u32 = 5000;
+pub const DEFAULT_DB_CACHE_SIZE_KIB: u32 = 64000;
+
+/// Connection pool size and pragmas used when opening the event database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatabaseSettings {
+    pub max_connections: u32,
+    pub busy_timeout_ms: u32,
+    /// Page cache per connection in KiB, passed to sqlite as a negative `cache_size`
+    pub cache_size_kib: u32,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: DEFAULT_DB_MAX_CONNECTIONS,
+            busy_timeout_ms: DEFAULT_DB_BUSY_TIMEOUT_MS,
+            cache_size_kib: DEFAULT_DB_CACHE_SIZE_KIB,
+        }
+    }
+}
+
+impl DatabaseSettings {
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=100).contains(&self.max_connections) {
+            return Err(anyhow!(
+                "db max connections must be between 1 and 100, got {}",
+                self.max_connections
+            ));
+        }
+        if self.busy_timeout_ms > 600_000 {
+            return Err(anyhow!(
+                "db busy timeout must be at most 600000ms, got {}",
+                self.busy_timeout_ms
+            ));
+        }
+        if !(1024..=4_194_304).contains(&self.cache_size_kib) {
+            return Err(anyhow!(
+                "db cache size must be between 1024 and 4194304 KiB, got {}",
+                self.cache_size_kib
+            ));
+        }
+        Ok(())
+    }
+}
 
 type WriteOperation = std::pin::Pin<Box<dyn Future<Output = ()> + Send>>;
 
@@ -73,6 +128,8 @@ impl DatabaseWriter {
 
 pub struct Database {
     pool: SqlitePool,
+    /// Read-only connections used by the query paths so reads don't contend with the writer
+    read_pool: SqlitePool,
     writer: DatabaseWriter,
 }
 
@@ -80,6 +137,7 @@ impl Clone for Database {
     fn clone(&self) -> Self {
         Self {
             pool: self.pool.clone(),
+            read_pool: self.read_pool.clone(),
             writer: DatabaseWriter::new(),
         }
     }
@@ -87,6 +145,11 @@ impl Clone for Database {
 
 impl Database {
     pub async fn new(path: &str) -> Result<Self> {
+        Self::new_with_settings(path, DatabaseSettings::default()).await
+    }
+
+    pub async fn new_with_settings(path: &str, settings: DatabaseSettings) -> Result<Self> {
+        settings.validate()?;
         let db_path = format!("{}/events.sqlite", path);
 
         if let Some(parent) = Path::new(&db_path).parent() {
@@ -95,36 +158,58 @@ impl Database {
                 .with_context(|| format!("Failed to create database directory: {parent:?}"))?;
         }
 
-        let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", db_path))?
+        let base_options = SqliteConnectOptions::from_str(&format!("sqlite:{}", db_path))?;
+        let options = base_options
+            .clone()
             .create_if_missing(true)
             .pragma("journal_mode", "WAL")
             .pragma("synchronous", "NORMAL")
-            .pragma("busy_timeout", "5000")
-            .pragma("cache_size", "-64000")
+            .pragma("busy_timeout", settings.busy_timeout_ms.to_string())
+            .pragma("cache_size", format!("-{}", settings.cache_size_kib))
             .pragma("foreign_keys", "ON")
             .pragma("temp_store", "MEMORY");
 
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
+            .max_connections(settings.max_connections)
             .acquire_timeout(Duration::from_secs(30))
             .connect_with(options)
             .await
             .context("Failed to create database connection pool")?;
 
+        Self::run_migrations(&pool).await?;
+
+        let read_options = base_options
+            .read_only(true)
+            .pragma("query_only", "ON")
+            .pragma("busy_timeout", settings.busy_timeout_ms.to_string())
+            .pragma("cache_size", format!("-{}", settings.cache_size_kib))
+            .pragma("temp_store", "MEMORY");
+
+        let read_pool = SqlitePoolOptions::new()
+            .max_connections(settings.max_connections)
+            .acquire_timeout(Duration::from_secs(30))
+            .connect_with(read_options)
+            .await
+            .context("Failed to create read-only database connection pool")?;
+
         let db = Self {
             pool,
+            read_pool,
             writer: DatabaseWriter::new(),
         };
 
-        db.run_migrations().await?;
         info!("SQLite database initialized at: {}", db_path);
+        info!(
+            "SQLite settings: max_connections={} busy_timeout={}ms cache_size={}KiB",
+            settings.max_connections, settings.busy_timeout_ms, settings.cache_size_kib
+        );
 
         Ok(db)
     }
 
-    async fn run_migrations(&self) -> Result<()> {
-        sqlx::migrate!("./migrations")
-            .run(&self.pool)
+    async fn run_migrations(pool: &SqlitePool) -> Result<()> {
+        MIGRATOR
+            .run(pool)
             .await
             .context("Failed to run database migrations")?;
         Ok(())
@@ -134,179 +219,28 @@ impl Database {
         &self.pool
     }
 
-    /// Check database connectivity and integrity.
-    pub async fn health_check(&self) -> Result<()> {
-        // Basic connectivity
-        sqlx::query("SELECT 1")
-            .fetch_one(&self.pool)
-            .await
-            .context("Database connectivity check failed")?;
-
-        // Page structure integrity
-        let result: String = sqlx::query_scalar("PRAGMA quick_check;")
-            .fetch_one(&self.pool)
-            .await
-            .context("Database integrity check failed")?;
-        if result != "ok" {
-            return Err(anyhow::anyhow!(
-                "Database integrity check failed: {}",
-                result
-            ));
-        }
-
-        Ok(())
-    }
-
-    /// Checkpoint WAL to main database file before shutdown.
-    /// This ensures all pending writes are flushed so Litestream
-    /// can replicate a complete database to S3.
-    pub async fn checkpoint(&self) {
-        match sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
-            .execute(&self.pool)
-            .await
-        {
-            Ok(_) => info!("WAL checkpoint completed successfully"),
-            Err(e) => log::error!("WAL checkpoint failed: {}", e),
-        }
-    }
-
-    pub async fn add_oracle_metadata(&self, pubkey: XOnlyPublicKey) -> Result<()> {
-        let pool = self.pool.clone();
-        let pubkey_bytes = pubkey.serialize().to_vec();
-        let name = "4casttruth".to_string();
-
-        self.writer
-            .execute(pool, move |pool| async move {
-                sqlx::query(
-                    "INSERT INTO oracle_metadata (pubkey, name) VALUES (?, ?)
-                     ON CONFLICT(pubkey) DO NOTHING",
-                )
-                .bind(&pubkey_bytes)
-                .bind(&name)
-                .execute(&pool)
-                .await?;
-                Ok(())
-            })
-            .await
-    }
-
-    pub async fn get_stored_public_key(&self) -> Result<XOnlyPublicKey> {
-        let row: (Vec<u8>,) = sqlx::query_as("SELECT pubkey FROM oracle_metadata LIMIT 1")
-            .fetch_one(&self.pool)
-            .await?;
-
-        XOnlyPublicKey::from_slice(&row.0).map_err(|e| anyhow::anyhow!("Invalid pubkey: {}", e))
-    }
-
-    pub async fn add_event(&self, event: CreateEventData) -> Result<Event> {
-        let pool = self.pool.clone();
-        let event_clone = event.clone();
-
-        self.writer
-            .execute(pool, move |pool| async move {
-                let locations_json = serde_json::to_string(&event.locations)?;
-                let nonce_bytes = serde_json::to_vec(&event.nonce)?;
-                let announcement_bytes = serde_json::to_vec(&event.event_announcement)?;
-                let scoring_fields_json = serde_json::to_string(&event.scoring_fields)?;
-
-                sqlx::query(
-                    "INSERT INTO events (
-                        id, total_allowed_entries, number_of_places_win,
-                        number_of_values_per_entry, nonce, signing_date,
-                        start_observation_date, end_observation_date,
-                        locations, event_announcement, coordinator_pubkey,
-                        scoring_fields
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                )
-                .bind(event.id.to_string())
-                .bind(event.total_allowed_entries)
-                .bind(event.number_of_places_win)
-                .bind(event.number_of_values_per_entry)
-                .bind(&nonce_bytes)
-                .bind(event.signing_date.unix_timestamp())
-                .bind(event.start_observation_date.unix_timestamp())
-                .bind(event.end_observation_date.unix_timestamp())
-                .bind(&locations_json)
-                .bind(&announcement_bytes)
-                .bind(&event.coordinator_pubkey)
-                .bind(&scoring_fields_json)
-                .execute(&pool)
-                .await?;
-
-                Ok(event_clone.into())
-            })
-            .await
-    }
-
-    pub async fn add_event_entries(&self, entries: Vec<WeatherEntry>) -> Result<()> {
-        let pool = self.pool.clone();
-
-        self.writer
-            .execute(pool, move |pool| async move {
-                let mut tx = pool.begin().await?;
-
-                for entry in entries {
-                    sqlx::query("INSERT INTO events_entries (id, event_id) VALUES (?, ?)")
-                        .bind(entry.id.to_string())
-                        .bind(entry.event_id.to_string())
-                        .execute(&mut *tx)
-                        .await?;
-
-                    for choice in &entry.expected_observations {
-                        sqlx::query(
-                            "INSERT INTO expected_observations
-                             (entry_id, station, temp_low, temp_high, wind_speed,
-                              wind_direction, rain_amt, snow_amt, humidity)
-                             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                        )
-                        .bind(entry.id.to_string())
-                        .bind(&choice.stations)
-                        .bind(choice.temp_low.as_ref().map(|v| v.to_string()))
-                        .bind(choice.temp_high.as_ref().map(|v| v.to_string()))
-                        .bind(choice.wind_speed.as_ref().map(|v| v.to_string()))
-                        .bind(choice.wind_direction.as_ref().map(|v| v.to_string()))
-                        .bind(choice.rain_amt.as_ref().map(|v| v.to_string()))
-                        .bind(choice.snow_amt.as_ref().map(|v| v.to_string()))
-                        .bind(choice.humidity.as_ref().map(|v| v.to_string()))
-                        .execute(&mut *tx)
-                        .await?;
-                    }
-                }
-
-                tx.commit().await?;
-                Ok(())
-            })
-            .await
-    }
-
-    pub async fn add_entry(&self, entry: WeatherEntry) -> Result<()> {
-        self.add_event_entries(vec![entry]).await
+    pub fn read_pool(&self) -> &SqlitePool {
+        &self.read_pool
     }
 
-    pub async fn get_event(&self, id: &Uuid) -> Result<Event> {
-        let mut event = self.get_basic_event(id).await?;
-        event.entries = self.get_event_weather_entries(id).await?;
-        event.entry_ids = event.entries.iter().map(|e| e.id).collect();
-        event.weather = self.get_event_weather(*id).await?;
-        Ok(event)
-    }
-
-    async fn get_basic_event(&self, id: &Uuid) -> Result<Event> {
+    async fn get_basic_event(&self, id: &Uuid, now: OffsetDateTime) -> Result<Event> {
         let row = sqlx::query(
             "SELECT id, signing_date, start_observation_date, end_observation_date,
                     event_announcement, locations, total_allowed_entries,
                     number_of_places_win, number_of_values_per_entry,
-                    attestation_signature, nonce, coordinator_pubkey, scoring_fields
+                    attestation_signature, nonce, coordinator_pubkey, scoring_fields,
+                    par_tolerances, scoring_mode, settlement_delay_hours, entry_cutoff,
+                    outcome_message_version, created_at, last_modified
              FROM events WHERE id = ?",
         )
         .bind(id.to_string())
-        .fetch_one(&self.pool)
+        .fetch_one(&self.read_pool)
         .await?;
 
-        self.row_to_event(&row)
+        self.row_to_event(&row, now)
     }
 
-    fn row_to_event(&self, row: &sqlx::sqlite::SqliteRow) -> Result<Event> {
+    fn row_to_event(&self, row: &sqlx::sqlite::SqliteRow, now: OffsetDateTime) -> Result<Event> {
         let id: String = row.get("id");
         let signing_ts: i64 = row.get("signing_date");
         let start_ts: i64 = row.get("start_observation_date");
@@ -317,6 +251,7 @@ impl Database {
         let attestation_bytes: Option<Vec<u8>> = row.get("attestation_signature");
         let coordinator_pubkey: Option<String> = row.get("coordinator_pubkey");
         let scoring_fields_json: Option<String> = row.get("scoring_fields");
+        let par_tolerances_json: Option<String> = row.get("par_tolerances");
 
         let signing_date = OffsetDateTime::from_unix_timestamp(signing_ts)?;
         let start_observation_date = OffsetDateTime::from_unix_timestamp(start_ts)?;
@@ -332,8 +267,15 @@ impl Database {
         let scoring_fields: Vec<ScoringField> = scoring_fields_json
             .and_then(|json| serde_json::from_str(&json).ok())
             .unwrap_or_else(ScoringField::defaults);
+        let par_tolerances = decode_field_values(par_tolerances_json, "par_tolerances")?;
+        let scoring_mode = ScoringMode::try_from(row.get::<&str, _>("scoring_mode"))?;
 
-        let status = super::get_status(attestation, start_observation_date, end_observation_date);
+        let status = super::get_status(
+            attestation,
+            start_observation_date,
+            end_observation_date,
+            now,
+        );
 
         Ok(Event {
             id: Uuid::parse_str(&id)?,
@@ -353,16 +295,27 @@ impl Database {
             attestation,
             coordinator_pubkey: coordinator_pubkey.unwrap_or_default(),
             scoring_fields,
+            par_tolerances,
+            scoring_mode,
+            settlement_delay_hours: decode_settlement_delay(row),
+            entry_cutoff: decode_entry_cutoff(row)?,
+            outcome_message_version: decode_outcome_message_version(row)?,
+            created_at: Some(OffsetDateTime::from_unix_timestamp(row.get("created_at"))?),
+            last_modified: Some(from_unix_millis(row.get("last_modified"))?),
         })
     }
 
-    pub async fn get_event_weather_entries(&self, event_id: &Uuid) -> Result<Vec<WeatherEntry>> {
+    async fn get_event_entries(
+        &self,
+        event_id: &Uuid,
+        with_choices: bool,
+    ) -> Result<Vec<WeatherEntry>> {
         let rows = sqlx::query(
             "SELECT id, event_id, score, base_score
              FROM events_entries WHERE event_id = ?",
         )
         .bind(event_id.to_string())
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool)
         .await?;
 
         let mut entries = Vec::new();
@@ -370,7 +323,11 @@ impl Database {
             let entry_id: String = row.get("id");
             let entry_uuid = Uuid::parse_str(&entry_id)?;
 
-            let choices = self.get_entry_choices(&entry_uuid).await?;
+            let choices = if with_choices {
+                self.get_entry_choices(&entry_uuid).await?
+            } else {
+                vec![]
+            };
 
             entries.push(WeatherEntry {
                 id: entry_uuid,
@@ -387,11 +344,11 @@ impl Database {
     async fn get_entry_choices(&self, entry_id: &Uuid) -> Result<Vec<WeatherChoices>> {
         let rows = sqlx::query(
             "SELECT station, temp_low, temp_high, wind_speed,
-                    wind_direction, rain_amt, snow_amt, humidity
+                    wind_direction, rain_amt, snow_amt, humidity, predictions
              FROM expected_observations WHERE entry_id = ?",
         )
         .bind(entry_id.to_string())
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool)
         .await?;
 
         let mut choices = Vec::new();
@@ -419,90 +376,73 @@ impl Database {
                 humidity: row
                     .get::<Option<String>, _>("humidity")
                     .and_then(|s| ValueOptions::try_from(s).ok()),
+                predictions: decode_field_values(row.get("predictions"), "predictions")?,
             });
         }
 
         Ok(choices)
     }
 
-    pub async fn get_active_events(&self) -> Result<Vec<ActiveEvent>> {
-        let rows = sqlx::query(
+    async fn get_filtered_event_summaries(
+        &self,
+        filter: EventFilter,
+        now: OffsetDateTime,
+    ) -> Result<Vec<EventSummary>> {
+        let mut query = String::from(
             "SELECT e.id, e.signing_date, e.start_observation_date, e.end_observation_date,
                     e.locations, e.total_allowed_entries, e.number_of_places_win,
-                    e.number_of_values_per_entry, e.attestation_signature,
-                    e.scoring_fields, COUNT(ee.id) as total_entries
+                    e.number_of_values_per_entry, e.attestation_signature, e.nonce,
+                    COUNT(ee.id) as total_entries
              FROM events e
-             LEFT JOIN events_entries ee ON ee.event_id = e.id
-             WHERE e.attestation_signature IS NULL
-             GROUP BY e.id",
-        )
-        .fetch_all(&self.pool)
-        .await?;
+             LEFT JOIN events_entries ee ON ee.event_id = e.id",
+        );
 
-        let mut events = Vec::new();
-        for row in rows {
-            let id: String = row.get("id");
-            let signing_ts: i64 = row.get("signing_date");
-            let start_ts: i64 = row.get("start_observation_date");
-            let end_ts: i64 = row.get("end_observation_date");
-            let locations_json: String = row.get("locations");
-            let attestation_bytes: Option<Vec<u8>> = row.get("attestation_signature");
-            let scoring_fields_json: Option<String> = row.get("scoring_fields");
+        let mut conditions = Vec::new();
+        let mut bindings: Vec<String> = Vec::new();
 
-            let signing_date = OffsetDateTime::from_unix_timestamp(signing_ts)?;
-            let start_observation_date = OffsetDateTime::from_unix_timestamp(start_ts)?;
-            let end_observation_date = OffsetDateTime::from_unix_timestamp(end_ts)?;
-            let locations: Vec<String> = serde_json::from_str(&locations_json)?;
-            let attestation: Option<MaybeScalar> = attestation_bytes
-                .as_ref()
-                .and_then(|b| serde_json::from_slice(b).ok());
-            let scoring_fields: Vec<ScoringField> = scoring_fields_json
-                .and_then(|json| serde_json::from_str(&json).ok())
-                .unwrap_or_else(ScoringField::defaults);
+        if let Some(ref ids) = filter.event_ids {
+            let placeholders: String = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+            conditions.push(format!("e.id IN ({})", placeholders));
+            bindings.extend(ids.iter().map(|id| id.to_string()));
+        }
 
-            let status =
-                super::get_status(attestation, start_observation_date, end_observation_date);
+        // Dates are stored as unix seconds, so the bounds are inlined as integers
+        let date_bounds = [
+            ("e.end_observation_date >=", filter.observation_after),
+            ("e.start_observation_date <=", filter.observation_before),
+            ("e.signing_date >=", filter.signing_after),
+            ("e.signing_date <=", filter.signing_before),
+        ];
+        for (predicate, bound) in date_bounds {
+            if let Some(bound) = bound {
+                conditions.push(format!("{} {}", predicate, bound.unix_timestamp()));
+            }
+        }
 
-            events.push(ActiveEvent {
-                id: Uuid::parse_str(&id)?,
-                locations,
-                signing_date,
-                start_observation_date,
-                end_observation_date,
-                status,
-                total_allowed_entries: row.get("total_allowed_entries"),
-                total_entries: row.get("total_entries"),
-                number_of_values_per_entry: row.get("number_of_values_per_entry"),
-                number_of_places_win: row.get("number_of_places_win"),
-                attestation,
-                scoring_fields,
-            });
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
         }
 
-        Ok(events)
-    }
+        query.push_str(" GROUP BY e.id ORDER BY ");
+        query.push_str(&filter.sort.order_by("e", filter.direction));
 
-    pub async fn get_events_to_sign(&self, event_ids: Vec<Uuid>) -> Result<Vec<SignEvent>> {
-        if event_ids.is_empty() {
-            return Ok(vec![]);
+        if let Some(limit) = filter.limit {
+            query.push_str(&format!(" LIMIT {}", limit));
+        } else if filter.offset.is_some() {
+            // SQLite only accepts OFFSET after a LIMIT, -1 leaves it unbounded
+            query.push_str(" LIMIT -1");
+        }
+        if let Some(offset) = filter.offset {
+            query.push_str(&format!(" OFFSET {}", offset));
         }
-
-        let placeholders: String = event_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-        let query = format!(
-            "SELECT id, signing_date, start_observation_date, end_observation_date,
-                    number_of_places_win, number_of_values_per_entry,
-                    attestation_signature, nonce, event_announcement
-             FROM events
-             WHERE attestation_signature IS NULL AND id IN ({})",
-            placeholders
-        );
 
         let mut q = sqlx::query(&query);
-        for id in &event_ids {
-            q = q.bind(id.to_string());
+        for binding in &bindings {
+            q = q.bind(binding);
         }
 
-        let rows = q.fetch_all(&self.pool).await?;
+        let rows = q.fetch_all(&self.read_pool).await?;
         let mut events = Vec::new();
 
         for row in rows {
@@ -510,143 +450,748 @@ impl Database {
             let signing_ts: i64 = row.get("signing_date");
             let start_ts: i64 = row.get("start_observation_date");
             let end_ts: i64 = row.get("end_observation_date");
+            let locations_json: String = row.get("locations");
             let nonce_bytes: Vec<u8> = row.get("nonce");
-            let announcement_bytes: Vec<u8> = row.get("event_announcement");
             let attestation_bytes: Option<Vec<u8>> = row.get("attestation_signature");
 
             let signing_date = OffsetDateTime::from_unix_timestamp(signing_ts)?;
             let start_observation_date = OffsetDateTime::from_unix_timestamp(start_ts)?;
             let end_observation_date = OffsetDateTime::from_unix_timestamp(end_ts)?;
 
+            let locations: Vec<String> = serde_json::from_str(&locations_json)?;
             let nonce: Scalar = serde_json::from_slice(&nonce_bytes)?;
-            let event_announcement: EventLockingConditions =
-                serde_json::from_slice(&announcement_bytes)?;
             let attestation: Option<MaybeScalar> = attestation_bytes
                 .as_ref()
                 .and_then(|b| serde_json::from_slice(b).ok());
 
-            let status =
-                super::get_status(attestation, start_observation_date, end_observation_date);
+            let status = super::get_status(
+                attestation,
+                start_observation_date,
+                end_observation_date,
+                now,
+            );
 
-            events.push(SignEvent {
+            events.push(EventSummary {
                 id: Uuid::parse_str(&id)?,
                 signing_date,
                 start_observation_date,
                 end_observation_date,
+                locations,
+                number_of_values_per_entry: row.get("number_of_values_per_entry"),
                 status,
-                nonce,
-                event_announcement,
+                total_allowed_entries: row.get("total_allowed_entries"),
+                total_entries: row.get("total_entries"),
                 number_of_places_win: row.get("number_of_places_win"),
-                number_of_values_per_entry: row.get("number_of_values_per_entry"),
+                weather: vec![],
                 attestation,
+                nonce,
             });
         }
 
         Ok(events)
     }
 
-    pub async fn update_event_attestation(&self, event: &SignEvent) -> Result<()> {
-        let Some(attestation) = event.attestation else {
-            return Err(anyhow::anyhow!("No attestation to update"));
-        };
+    pub async fn add_weather_readings(
+        &self,
+        weather: Vec<Weather>,
+        temp_unit: &TemperatureUnit,
+    ) -> Result<Vec<Uuid>> {
+        let pool = self.pool.clone();
+        let temp_unit_code = temp_unit.to_string();
+
+        self.writer
+            .execute(pool, move |pool| async move {
+                let mut tx = pool.begin().await?;
+                let mut weather_ids = Vec::new();
+
+                for w in weather {
+                    let weather_id = Uuid::now_v7();
+                    weather_ids.push(weather_id);
+
+                    let (obs_date, obs_low, obs_high, obs_wind) = match &w.observed {
+                        Some(obs) => (
+                            Some(obs.date.unix_timestamp()),
+                            Some(obs.temp_low),
+                            Some(obs.temp_high),
+                            Some(obs.wind_speed),
+                        ),
+                        None => (None, None, None, None),
+                    };
+
+                    sqlx::query(
+                        "INSERT INTO weather (
+                            id, station_id, observed_date, observed_temp_low,
+                            observed_temp_high, observed_wind_speed,
+                            forecasted_date, forecasted_temp_low,
+                            forecasted_temp_high, forecasted_wind_speed, temp_unit_code
+                        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    )
+                    .bind(weather_id.to_string())
+                    .bind(&w.station_id)
+                    .bind(obs_date)
+                    .bind(obs_low)
+                    .bind(obs_high)
+                    .bind(obs_wind)
+                    .bind(w.forecasted.date.unix_timestamp())
+                    .bind(w.forecasted.temp_low)
+                    .bind(w.forecasted.temp_high)
+                    .bind(w.forecasted.wind_speed)
+                    .bind(&temp_unit_code)
+                    .execute(&mut *tx)
+                    .await?;
+                }
 
+                tx.commit().await?;
+                Ok(weather_ids)
+            })
+            .await
+    }
+
+    pub async fn batch_add_weather_to_event(
+        &self,
+        event_id: Uuid,
+        weather_ids: Vec<Uuid>,
+    ) -> Result<()> {
         let pool = self.pool.clone();
-        let event_id = event.id.to_string();
-        let attestation_bytes = serde_json::to_vec(&attestation)?;
 
         self.writer
             .execute(pool, move |pool| async move {
-                sqlx::query("UPDATE events SET attestation_signature = ? WHERE id = ?")
-                    .bind(&attestation_bytes)
-                    .bind(&event_id)
-                    .execute(&pool)
+                let mut tx = pool.begin().await?;
+
+                for weather_id in weather_ids {
+                    let junction_id = Uuid::now_v7();
+                    sqlx::query(
+                        "INSERT INTO events_weather (id, event_id, weather_id) VALUES (?, ?, ?)",
+                    )
+                    .bind(junction_id.to_string())
+                    .bind(event_id.to_string())
+                    .bind(weather_id.to_string())
+                    .execute(&mut *tx)
                     .await?;
+                }
+
+                tx.commit().await?;
                 Ok(())
             })
             .await
     }
+}
 
-    pub async fn update_entry_scores(&self, entry_scores: Vec<(Uuid, i64, i64)>) -> Result<()> {
-        if entry_scores.is_empty() {
-            return Ok(());
+#[async_trait]
+impl EventStore for Database {
+    async fn migration_status(&self) -> Result<MigrationStatus> {
+        let current_version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success = 1")
+                .fetch_one(&self.read_pool)
+                .await
+                .context("Failed to read applied migrations")?;
+        let latest_version = MIGRATOR.iter().map(|migration| migration.version).max();
+        Ok(MigrationStatus {
+            current_version,
+            latest_version,
+            up_to_date: current_version == latest_version,
+        })
+    }
+
+    /// Check database connectivity and integrity.
+    async fn health_check(&self) -> Result<()> {
+        // Basic connectivity
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .context("Database connectivity check failed")?;
+
+        // Page structure integrity
+        let result: String = sqlx::query_scalar("PRAGMA quick_check;")
+            .fetch_one(&self.pool)
+            .await
+            .context("Database integrity check failed")?;
+        if result != "ok" {
+            return Err(anyhow::anyhow!(
+                "Database integrity check failed: {}",
+                result
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checkpoint WAL to main database file before shutdown.
+    /// This ensures all pending writes are flushed so Litestream
+    /// can replicate a complete database to S3.
+    async fn checkpoint(&self) {
+        match sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+            .execute(&self.pool)
+            .await
+        {
+            Ok(_) => info!("WAL checkpoint completed successfully"),
+            Err(e) => log::error!("WAL checkpoint failed: {}", e),
         }
+    }
+
+    /// Runs `PRAGMA optimize` and optionally `VACUUM` through the writer so it never overlaps a write,
+    /// checkpointing the WAL first so the page counts reflect the main database file.
+    async fn maintenance(&self, vacuum: bool) -> Result<MaintenanceReport> {
+        let pool = self.pool.clone();
+
+        self.writer
+            .execute(pool, move |pool| async move {
+                sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+                    .execute(&pool)
+                    .await
+                    .context("Failed to checkpoint WAL before maintenance")?;
+                let page_count_before: i64 = sqlx::query_scalar("PRAGMA page_count;")
+                    .fetch_one(&pool)
+                    .await?;
+
+                sqlx::query("PRAGMA optimize;")
+                    .execute(&pool)
+                    .await
+                    .context("Failed to optimize database")?;
+                if vacuum {
+                    sqlx::query("VACUUM;")
+                        .execute(&pool)
+                        .await
+                        .context("Failed to vacuum database")?;
+                    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+                        .execute(&pool)
+                        .await
+                        .context("Failed to checkpoint WAL after vacuum")?;
+                }
+
+                let page_count_after: i64 = sqlx::query_scalar("PRAGMA page_count;")
+                    .fetch_one(&pool)
+                    .await?;
+                let freelist_count_after: i64 = sqlx::query_scalar("PRAGMA freelist_count;")
+                    .fetch_one(&pool)
+                    .await?;
+                info!(
+                    "Database maintenance complete: pages {} -> {}, vacuumed: {}",
+                    page_count_before, page_count_after, vacuum
+                );
+                Ok(MaintenanceReport {
+                    page_count_before,
+                    page_count_after,
+                    freelist_count_after,
+                    vacuumed: vacuum,
+                })
+            })
+            .await
+    }
 
+    async fn add_oracle_metadata(&self, pubkey: XOnlyPublicKey) -> Result<()> {
         let pool = self.pool.clone();
+        let pubkey_bytes = pubkey.serialize().to_vec();
+        let name = "4casttruth".to_string();
 
         self.writer
             .execute(pool, move |pool| async move {
                 let mut tx = pool.begin().await?;
+                sqlx::query(
+                    "INSERT INTO oracle_metadata (pubkey, name) VALUES (?, ?)
+                     ON CONFLICT(pubkey) DO NOTHING",
+                )
+                .bind(&pubkey_bytes)
+                .bind(&name)
+                .execute(&mut *tx)
+                .await?;
+                // the first key covers every event until the first rotation
+                sqlx::query(
+                    "INSERT INTO oracle_keys (pubkey, active_from) VALUES (?, 0)
+                     ON CONFLICT(pubkey) DO NOTHING",
+                )
+                .bind(&pubkey_bytes)
+                .execute(&mut *tx)
+                .await?;
+                tx.commit().await?;
+                Ok(())
+            })
+            .await
+    }
 
-                for (entry_id, score, base_score) in entry_scores {
-                    sqlx::query("UPDATE events_entries SET score = ?, base_score = ? WHERE id = ?")
-                        .bind(score)
-                        .bind(base_score)
-                        .bind(entry_id.to_string())
+    async fn get_stored_public_key(&self) -> Result<XOnlyPublicKey> {
+        let row: (Vec<u8>,) = sqlx::query_as("SELECT pubkey FROM oracle_metadata LIMIT 1")
+            .fetch_one(&self.read_pool)
+            .await?;
+
+        XOnlyPublicKey::from_slice(&row.0).map_err(|e| anyhow::anyhow!("Invalid pubkey: {}", e))
+    }
+
+    async fn get_oracle_name(&self) -> Result<String> {
+        let row: (String,) = sqlx::query_as("SELECT name FROM oracle_metadata LIMIT 1")
+            .fetch_one(&self.read_pool)
+            .await?;
+        Ok(row.0)
+    }
+
+    async fn get_oracle_keys(&self) -> Result<Vec<OracleKey>> {
+        let rows: Vec<(Vec<u8>, i64, Option<i64>)> = sqlx::query_as(
+            "SELECT pubkey, active_from, active_until FROM oracle_keys ORDER BY active_from",
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
+        rows.iter()
+            .map(|(pubkey, from, until)| OracleKey::from_row(pubkey, *from, *until))
+            .collect()
+    }
+
+    async fn rotate_oracle_key(&self, pubkey: XOnlyPublicKey, at: OffsetDateTime) -> Result<()> {
+        let pool = self.pool.clone();
+        let pubkey_bytes = pubkey.serialize().to_vec();
+        let at = at.unix_timestamp();
+
+        self.writer
+            .execute(pool, move |pool| async move {
+                let mut tx = pool.begin().await?;
+                sqlx::query("UPDATE oracle_keys SET active_until = ? WHERE active_until IS NULL")
+                    .bind(at)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("INSERT INTO oracle_keys (pubkey, active_from) VALUES (?, ?)")
+                    .bind(&pubkey_bytes)
+                    .bind(at)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("UPDATE oracle_metadata SET pubkey = ?, updated_at = ?")
+                    .bind(&pubkey_bytes)
+                    .bind(at)
+                    .execute(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+                Ok(())
+            })
+            .await
+    }
+
+    async fn get_event_oracle_key(&self, event_id: &Uuid) -> Result<OracleKey> {
+        let row: (Vec<u8>, i64, Option<i64>) = sqlx::query_as(
+            "SELECT k.pubkey, k.active_from, k.active_until
+             FROM events e
+             JOIN oracle_keys k ON k.active_from <= e.created_at
+                AND (k.active_until IS NULL OR e.created_at < k.active_until)
+             WHERE e.id = ?",
+        )
+        .bind(event_id.to_string())
+        .fetch_one(&self.read_pool)
+        .await?;
+        OracleKey::from_row(&row.0, row.1, row.2)
+    }
+
+    async fn add_event(&self, event: CreateEventData, now: OffsetDateTime) -> Result<Event> {
+        let pool = self.pool.clone();
+        let mut created: Event = event.clone().into();
+        // Truncated to the resolution they are stored at, so a later read returns the same event
+        created.created_at = Some(OffsetDateTime::from_unix_timestamp(now.unix_timestamp())?);
+        created.last_modified = Some(from_unix_millis(unix_millis(now))?);
+
+        self.writer
+            .execute(pool, move |pool| async move {
+                let locations_json = serde_json::to_string(&event.locations)?;
+                let nonce_bytes = serde_json::to_vec(&event.nonce)?;
+                let announcement_bytes = serde_json::to_vec(&event.event_announcement)?;
+                let scoring_fields_json = serde_json::to_string(&event.scoring_fields)?;
+                let par_tolerances_json = encode_field_values(&event.par_tolerances)?;
+
+                sqlx::query(
+                    "INSERT INTO events (
+                        id, total_allowed_entries, number_of_places_win,
+                        number_of_values_per_entry, nonce, signing_date,
+                        start_observation_date, end_observation_date,
+                        locations, event_announcement, coordinator_pubkey,
+                        scoring_fields, par_tolerances, scoring_mode, settlement_delay_hours,
+                        entry_cutoff, outcome_message_version, created_at, last_modified
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(event.id.to_string())
+                .bind(event.total_allowed_entries)
+                .bind(event.number_of_places_win)
+                .bind(event.number_of_values_per_entry)
+                .bind(&nonce_bytes)
+                .bind(event.signing_date.unix_timestamp())
+                .bind(event.start_observation_date.unix_timestamp())
+                .bind(event.end_observation_date.unix_timestamp())
+                .bind(&locations_json)
+                .bind(&announcement_bytes)
+                .bind(&event.coordinator_pubkey)
+                .bind(&scoring_fields_json)
+                .bind(&par_tolerances_json)
+                .bind(event.scoring_mode.to_string())
+                .bind(event.settlement_delay_hours)
+                .bind(event.entry_cutoff.map(OffsetDateTime::unix_timestamp))
+                .bind(u8::from(event.outcome_message_version))
+                .bind(now.unix_timestamp())
+                .bind(unix_millis(now))
+                .execute(&pool)
+                .await?;
+
+                Ok(created)
+            })
+            .await
+    }
+
+    async fn add_event_entries(&self, entries: Vec<WeatherEntry>) -> Result<()> {
+        let pool = self.pool.clone();
+
+        self.writer
+            .execute(pool, move |pool| async move {
+                let mut tx = pool.begin().await?;
+                // Writes are serialized, so no other entries can land between the count and the inserts
+                check_entry_limit(&mut tx, &entries).await?;
+
+                for entry in entries {
+                    sqlx::query("INSERT INTO events_entries (id, event_id) VALUES (?, ?)")
+                        .bind(entry.id.to_string())
+                        .bind(entry.event_id.to_string())
                         .execute(&mut *tx)
                         .await?;
+
+                    insert_entry_choices(&mut tx, &entry).await?;
                 }
 
-                tx.commit().await?;
-                Ok(())
-            })
-            .await
+                tx.commit().await?;
+                Ok(())
+            })
+            .await
+    }
+
+    async fn import_event(&self, event: Event, now: OffsetDateTime) -> Result<bool> {
+        let pool = self.pool.clone();
+        let temp_unit_code = TemperatureUnit::default().to_string();
+
+        self.writer
+            .execute(pool, move |pool| async move {
+                let mut tx = pool.begin().await?;
+
+                let inserted = sqlx::query(
+                    "INSERT OR IGNORE INTO events (
+                        id, total_allowed_entries, number_of_places_win,
+                        number_of_values_per_entry, nonce, signing_date,
+                        start_observation_date, end_observation_date,
+                        locations, event_announcement, coordinator_pubkey,
+                        attestation_signature, scoring_fields, par_tolerances, scoring_mode,
+                        settlement_delay_hours, entry_cutoff, outcome_message_version,
+                        created_at, last_modified
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(event.id.to_string())
+                .bind(event.total_allowed_entries)
+                .bind(event.number_of_places_win)
+                .bind(event.number_of_values_per_entry)
+                .bind(serde_json::to_vec(&event.nonce)?)
+                .bind(event.signing_date.unix_timestamp())
+                .bind(event.start_observation_date.unix_timestamp())
+                .bind(event.end_observation_date.unix_timestamp())
+                .bind(serde_json::to_string(&event.locations)?)
+                .bind(serde_json::to_vec(&event.event_announcement)?)
+                .bind(&event.coordinator_pubkey)
+                .bind(
+                    event
+                        .attestation
+                        .as_ref()
+                        .map(serde_json::to_vec)
+                        .transpose()?,
+                )
+                .bind(serde_json::to_string(&event.scoring_fields)?)
+                .bind(encode_field_values(&event.par_tolerances)?)
+                .bind(event.scoring_mode.to_string())
+                .bind(event.settlement_delay_hours)
+                .bind(event.entry_cutoff.map(OffsetDateTime::unix_timestamp))
+                .bind(u8::from(event.outcome_message_version))
+                .bind(event.created_at.unwrap_or(now).unix_timestamp())
+                .bind(unix_millis(event.last_modified.unwrap_or(now)))
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+                if inserted == 0 {
+                    return Ok(false);
+                }
+
+                for entry in &event.entries {
+                    sqlx::query(
+                        "INSERT INTO events_entries (id, event_id, score, base_score)
+                         VALUES (?, ?, ?, ?)",
+                    )
+                    .bind(entry.id.to_string())
+                    .bind(event.id.to_string())
+                    .bind(entry.score.unwrap_or_default())
+                    .bind(entry.base_score.unwrap_or_default())
+                    .execute(&mut *tx)
+                    .await?;
+                    insert_entry_choices(&mut tx, entry).await?;
+                }
+
+                for w in &event.weather {
+                    let weather_id = Uuid::now_v7();
+                    sqlx::query(
+                        "INSERT INTO weather (
+                            id, station_id, observed_date, observed_temp_low,
+                            observed_temp_high, observed_wind_speed,
+                            forecasted_date, forecasted_temp_low,
+                            forecasted_temp_high, forecasted_wind_speed, temp_unit_code
+                        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    )
+                    .bind(weather_id.to_string())
+                    .bind(&w.station_id)
+                    .bind(w.observed.as_ref().map(|o| o.date.unix_timestamp()))
+                    .bind(w.observed.as_ref().map(|o| o.temp_low))
+                    .bind(w.observed.as_ref().map(|o| o.temp_high))
+                    .bind(w.observed.as_ref().map(|o| o.wind_speed))
+                    .bind(w.forecasted.date.unix_timestamp())
+                    .bind(w.forecasted.temp_low)
+                    .bind(w.forecasted.temp_high)
+                    .bind(w.forecasted.wind_speed)
+                    .bind(&temp_unit_code)
+                    .execute(&mut *tx)
+                    .await?;
+
+                    sqlx::query(
+                        "INSERT INTO events_weather (id, event_id, weather_id) VALUES (?, ?, ?)",
+                    )
+                    .bind(Uuid::now_v7().to_string())
+                    .bind(event.id.to_string())
+                    .bind(weather_id.to_string())
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                tx.commit().await?;
+                Ok(true)
+            })
+            .await
+    }
+
+    async fn delete_event(&self, id: &Uuid, now: OffsetDateTime) -> Result<bool> {
+        let pool = self.pool.clone();
+        let id = id.to_string();
+
+        self.writer
+            .execute(pool, move |pool| async move {
+                let mut tx = pool.begin().await?;
+                sqlx::query(
+                    "INSERT OR REPLACE INTO deleted_events (id, deleted_at)
+                     SELECT id, ? FROM events WHERE id = ?",
+                )
+                .bind(unix_millis(now))
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+                let deleted = sqlx::query("DELETE FROM events WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+                delete_orphaned_weather(&mut tx).await?;
+                tx.commit().await?;
+                Ok(deleted > 0)
+            })
+            .await
+    }
+
+    async fn purge_events_before(
+        &self,
+        cutoff: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<u64> {
+        let pool = self.pool.clone();
+
+        self.writer
+            .execute(pool, move |pool| async move {
+                let mut tx = pool.begin().await?;
+                sqlx::query(
+                    "INSERT OR REPLACE INTO deleted_events (id, deleted_at)
+                     SELECT id, ? FROM events WHERE signing_date < ?",
+                )
+                .bind(unix_millis(now))
+                .bind(cutoff.unix_timestamp())
+                .execute(&mut *tx)
+                .await?;
+                let deleted = sqlx::query("DELETE FROM events WHERE signing_date < ?")
+                    .bind(cutoff.unix_timestamp())
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+                delete_orphaned_weather(&mut tx).await?;
+                tx.commit().await?;
+                Ok(deleted)
+            })
+            .await
+    }
+
+    async fn list_event_ids(&self) -> Result<Vec<Uuid>> {
+        let ids: Vec<String> = sqlx::query_scalar("SELECT id FROM events ORDER BY id")
+            .fetch_all(&self.read_pool)
+            .await?;
+        ids.iter()
+            .map(|id| Uuid::parse_str(id).map_err(Into::into))
+            .collect()
+    }
+
+    async fn changed_events(
+        &self,
+        since: OffsetDateTime,
+        until: OffsetDateTime,
+    ) -> Result<ChangedEvents> {
+        // Observation dates are unix seconds, scaled up to compare against the window
+        let changed: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM events
+             WHERE last_modified >= ?1
+                OR start_observation_date * 1000 BETWEEN ?1 AND ?2
+                OR end_observation_date * 1000 BETWEEN ?1 AND ?2
+             ORDER BY id",
+        )
+        .bind(unix_millis(since))
+        .bind(unix_millis(until))
+        .fetch_all(&self.read_pool)
+        .await?;
+        let deleted: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM deleted_events
+             WHERE deleted_at >= ? AND id NOT IN (SELECT id FROM events)
+             ORDER BY id",
+        )
+        .bind(unix_millis(since))
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        let parse = |ids: Vec<String>| -> Result<Vec<Uuid>> {
+            ids.iter()
+                .map(|id| Uuid::parse_str(id).map_err(Into::into))
+                .collect()
+        };
+        Ok(ChangedEvents {
+            changed: parse(changed)?,
+            deleted: parse(deleted)?,
+        })
     }
 
-    pub async fn get_event_coordinator_pubkey(&self, event_id: Uuid) -> Result<String> {
-        let row: (Option<String>,) =
-            sqlx::query_as("SELECT coordinator_pubkey FROM events WHERE id = ?")
-                .bind(event_id.to_string())
-                .fetch_one(&self.pool)
-                .await?;
+    async fn get_event_with(
+        &self,
+        id: &Uuid,
+        includes: &EventIncludes,
+        now: OffsetDateTime,
+    ) -> Result<Event> {
+        let mut event = self.get_basic_event(id, now).await?;
+        if includes.entries {
+            event.entries = self.get_event_entries(id, includes.entry_choices).await?;
+            event.entry_ids = event.entries.iter().map(|e| e.id).collect();
+        }
+        if includes.weather {
+            event.weather = self.get_event_weather(*id).await?;
+        }
+        Ok(event)
+    }
 
-        row.0
-            .ok_or_else(|| anyhow::anyhow!("Coordinator pubkey not found"))
+    async fn event_exists(&self, id: &Uuid) -> Result<bool> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM events WHERE id = ?)")
+            .bind(id.to_string())
+            .fetch_one(&self.read_pool)
+            .await?;
+        Ok(exists)
     }
 
-    pub async fn filtered_list_events(&self, filter: EventFilter) -> Result<Vec<EventSummary>> {
-        let mut events = self.get_filtered_event_summaries(filter).await?;
-        for event in events.iter_mut() {
-            event.weather = self.get_event_weather(event.id).await?;
-        }
-        Ok(events)
+    async fn event_was_deleted(&self, id: &Uuid) -> Result<bool> {
+        let deleted: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM deleted_events WHERE id = ?)")
+                .bind(id.to_string())
+                .fetch_one(&self.read_pool)
+                .await?;
+        Ok(deleted)
     }
 
-    async fn get_filtered_event_summaries(&self, filter: EventFilter) -> Result<Vec<EventSummary>> {
-        let mut query = String::from(
+    async fn get_event_weather_entries(&self, event_id: &Uuid) -> Result<Vec<WeatherEntry>> {
+        self.get_event_entries(event_id, true).await
+    }
+
+    async fn get_active_events(&self, now: OffsetDateTime) -> Result<Vec<ActiveEvent>> {
+        let rows = sqlx::query(
             "SELECT e.id, e.signing_date, e.start_observation_date, e.end_observation_date,
                     e.locations, e.total_allowed_entries, e.number_of_places_win,
-                    e.number_of_values_per_entry, e.attestation_signature, e.nonce,
-                    COUNT(ee.id) as total_entries
+                    e.number_of_values_per_entry, e.attestation_signature,
+                    e.scoring_fields, e.par_tolerances, e.scoring_mode,
+                    e.settlement_delay_hours, COUNT(ee.id) as total_entries
              FROM events e
-             LEFT JOIN events_entries ee ON ee.event_id = e.id",
-        );
+             LEFT JOIN events_entries ee ON ee.event_id = e.id
+             WHERE e.attestation_signature IS NULL
+             GROUP BY e.id",
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
 
-        let mut conditions = Vec::new();
-        let mut bindings: Vec<String> = Vec::new();
+        let mut events = Vec::new();
+        for row in rows {
+            let id: String = row.get("id");
+            let signing_ts: i64 = row.get("signing_date");
+            let start_ts: i64 = row.get("start_observation_date");
+            let end_ts: i64 = row.get("end_observation_date");
+            let locations_json: String = row.get("locations");
+            let attestation_bytes: Option<Vec<u8>> = row.get("attestation_signature");
+            let scoring_fields_json: Option<String> = row.get("scoring_fields");
+            let par_tolerances_json: Option<String> = row.get("par_tolerances");
 
-        if let Some(ref ids) = filter.event_ids {
-            let placeholders: String = ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-            conditions.push(format!("e.id IN ({})", placeholders));
-            bindings.extend(ids.iter().map(|id| id.to_string()));
-        }
+            let signing_date = OffsetDateTime::from_unix_timestamp(signing_ts)?;
+            let start_observation_date = OffsetDateTime::from_unix_timestamp(start_ts)?;
+            let end_observation_date = OffsetDateTime::from_unix_timestamp(end_ts)?;
+            let locations: Vec<String> = serde_json::from_str(&locations_json)?;
+            let attestation: Option<MaybeScalar> = attestation_bytes
+                .as_ref()
+                .and_then(|b| serde_json::from_slice(b).ok());
+            let scoring_fields: Vec<ScoringField> = scoring_fields_json
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_else(ScoringField::defaults);
 
-        if !conditions.is_empty() {
-            query.push_str(" WHERE ");
-            query.push_str(&conditions.join(" AND "));
+            let status = super::get_status(
+                attestation,
+                start_observation_date,
+                end_observation_date,
+                now,
+            );
+
+            events.push(ActiveEvent {
+                id: Uuid::parse_str(&id)?,
+                locations,
+                signing_date,
+                start_observation_date,
+                end_observation_date,
+                status,
+                total_allowed_entries: row.get("total_allowed_entries"),
+                total_entries: row.get("total_entries"),
+                number_of_values_per_entry: row.get("number_of_values_per_entry"),
+                number_of_places_win: row.get("number_of_places_win"),
+                attestation,
+                scoring_fields,
+                par_tolerances: decode_field_values(par_tolerances_json, "par_tolerances")?,
+                scoring_mode: ScoringMode::try_from(row.get::<&str, _>("scoring_mode"))?,
+                settlement_delay_hours: decode_settlement_delay(&row),
+            });
         }
 
-        query.push_str(" GROUP BY e.id");
+        Ok(events)
+    }
 
-        if let Some(limit) = filter.limit {
-            query.push_str(&format!(" LIMIT {}", limit));
+    async fn get_events_to_sign(
+        &self,
+        event_ids: Vec<Uuid>,
+        now: OffsetDateTime,
+    ) -> Result<Vec<SignEvent>> {
+        if event_ids.is_empty() {
+            return Ok(vec![]);
         }
 
+        let placeholders: String = event_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let query = format!(
+            "SELECT id, signing_date, start_observation_date, end_observation_date,
+                    number_of_places_win, number_of_values_per_entry,
+                    attestation_signature, nonce, event_announcement, outcome_message_version
+             FROM events
+             WHERE attestation_signature IS NULL AND id IN ({})",
+            placeholders
+        );
+
         let mut q = sqlx::query(&query);
-        for binding in &bindings {
-            q = q.bind(binding);
+        for id in &event_ids {
+            q = q.bind(id.to_string());
         }
 
-        let rows = q.fetch_all(&self.pool).await?;
+        let rows = q.fetch_all(&self.read_pool).await?;
         let mut events = Vec::new();
 
         for row in rows {
@@ -654,114 +1199,88 @@ impl Database {
             let signing_ts: i64 = row.get("signing_date");
             let start_ts: i64 = row.get("start_observation_date");
             let end_ts: i64 = row.get("end_observation_date");
-            let locations_json: String = row.get("locations");
             let nonce_bytes: Vec<u8> = row.get("nonce");
+            let announcement_bytes: Vec<u8> = row.get("event_announcement");
             let attestation_bytes: Option<Vec<u8>> = row.get("attestation_signature");
 
             let signing_date = OffsetDateTime::from_unix_timestamp(signing_ts)?;
             let start_observation_date = OffsetDateTime::from_unix_timestamp(start_ts)?;
             let end_observation_date = OffsetDateTime::from_unix_timestamp(end_ts)?;
 
-            let locations: Vec<String> = serde_json::from_str(&locations_json)?;
             let nonce: Scalar = serde_json::from_slice(&nonce_bytes)?;
+            let event_announcement: EventLockingConditions =
+                serde_json::from_slice(&announcement_bytes)?;
             let attestation: Option<MaybeScalar> = attestation_bytes
                 .as_ref()
                 .and_then(|b| serde_json::from_slice(b).ok());
 
-            let status =
-                super::get_status(attestation, start_observation_date, end_observation_date);
+            let status = super::get_status(
+                attestation,
+                start_observation_date,
+                end_observation_date,
+                now,
+            );
 
-            events.push(EventSummary {
+            events.push(SignEvent {
                 id: Uuid::parse_str(&id)?,
                 signing_date,
                 start_observation_date,
                 end_observation_date,
-                locations,
-                number_of_values_per_entry: row.get("number_of_values_per_entry"),
                 status,
-                total_allowed_entries: row.get("total_allowed_entries"),
-                total_entries: row.get("total_entries"),
+                nonce,
+                event_announcement,
                 number_of_places_win: row.get("number_of_places_win"),
-                weather: vec![],
+                number_of_values_per_entry: row.get("number_of_values_per_entry"),
                 attestation,
-                nonce,
+                outcome_message_version: decode_outcome_message_version(&row)?,
             });
         }
 
         Ok(events)
     }
 
-    pub async fn add_weather_readings(&self, weather: Vec<Weather>) -> Result<Vec<Uuid>> {
+    async fn update_event_attestation(&self, event: &SignEvent, now: OffsetDateTime) -> Result<()> {
+        let Some(attestation) = event.attestation else {
+            return Err(anyhow::anyhow!("No attestation to update"));
+        };
+
         let pool = self.pool.clone();
+        let event_id = event.id.to_string();
+        let attestation_bytes = serde_json::to_vec(&attestation)?;
 
         self.writer
             .execute(pool, move |pool| async move {
-                let mut tx = pool.begin().await?;
-                let mut weather_ids = Vec::new();
-
-                for w in weather {
-                    let weather_id = Uuid::now_v7();
-                    weather_ids.push(weather_id);
-
-                    let (obs_date, obs_low, obs_high, obs_wind) = match &w.observed {
-                        Some(obs) => (
-                            Some(obs.date.unix_timestamp()),
-                            Some(obs.temp_low),
-                            Some(obs.temp_high),
-                            Some(obs.wind_speed),
-                        ),
-                        None => (None, None, None, None),
-                    };
-
-                    sqlx::query(
-                        "INSERT INTO weather (
-                            id, station_id, observed_date, observed_temp_low,
-                            observed_temp_high, observed_wind_speed,
-                            forecasted_date, forecasted_temp_low,
-                            forecasted_temp_high, forecasted_wind_speed
-                        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                    )
-                    .bind(weather_id.to_string())
-                    .bind(&w.station_id)
-                    .bind(obs_date)
-                    .bind(obs_low)
-                    .bind(obs_high)
-                    .bind(obs_wind)
-                    .bind(w.forecasted.date.unix_timestamp())
-                    .bind(w.forecasted.temp_low)
-                    .bind(w.forecasted.temp_high)
-                    .bind(w.forecasted.wind_speed)
-                    .execute(&mut *tx)
-                    .await?;
-                }
-
-                tx.commit().await?;
-                Ok(weather_ids)
+                sqlx::query(
+                    "UPDATE events SET attestation_signature = ?, last_modified = ? WHERE id = ?",
+                )
+                .bind(&attestation_bytes)
+                .bind(unix_millis(now))
+                .bind(&event_id)
+                .execute(&pool)
+                .await?;
+                Ok(())
             })
             .await
     }
 
-    pub async fn batch_add_weather_to_event(
-        &self,
-        event_id: Uuid,
-        weather_ids: Vec<Uuid>,
-    ) -> Result<()> {
+    async fn update_entry_scores(&self, entry_scores: Vec<(Uuid, i64, i64)>) -> Result<()> {
+        if entry_scores.is_empty() {
+            return Ok(());
+        }
+
         let pool = self.pool.clone();
 
         self.writer
             .execute(pool, move |pool| async move {
                 let mut tx = pool.begin().await?;
 
-                for weather_id in weather_ids {
-                    let junction_id = Uuid::now_v7();
-                    sqlx::query(
-                        "INSERT INTO events_weather (id, event_id, weather_id) VALUES (?, ?, ?)",
-                    )
-                    .bind(junction_id.to_string())
-                    .bind(event_id.to_string())
-                    .bind(weather_id.to_string())
-                    .execute(&mut *tx)
-                    .await?;
+                for (entry_id, score, base_score) in entry_scores {
+                    sqlx::query("UPDATE events_entries SET score = ?, base_score = ? WHERE id = ?")
+                        .bind(score)
+                        .bind(base_score)
+                        .bind(entry_id.to_string())
+                        .execute(&mut *tx)
+                        .await?;
                 }
 
                 tx.commit().await?;
@@ -770,26 +1289,91 @@ impl Database {
             .await
     }
 
-    pub async fn update_weather_station_data(
+    async fn get_event_coordinator_pubkey(&self, event_id: Uuid) -> Result<String> {
+        let row: (Option<String>,) =
+            sqlx::query_as("SELECT coordinator_pubkey FROM events WHERE id = ?")
+                .bind(event_id.to_string())
+                .fetch_one(&self.read_pool)
+                .await?;
+
+        row.0
+            .ok_or_else(|| anyhow::anyhow!("Coordinator pubkey not found"))
+    }
+
+    async fn filtered_list_events(
+        &self,
+        filter: EventFilter,
+        now: OffsetDateTime,
+    ) -> Result<Vec<EventSummary>> {
+        let mut events = self.get_filtered_event_summaries(filter, now).await?;
+        for event in events.iter_mut() {
+            event.weather = self.get_event_weather(event.id).await?;
+        }
+        Ok(events)
+    }
+
+    async fn event_aggregates(
+        &self,
+        now: OffsetDateTime,
+        signed_since: OffsetDateTime,
+    ) -> Result<EventAggregates> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS total_events,
+                    COUNT(CASE WHEN attestation_signature IS NULL
+                               AND ?1 < start_observation_date THEN 1 END) AS live,
+                    COUNT(CASE WHEN attestation_signature IS NULL
+                               AND start_observation_date <= ?1
+                               AND ?1 < end_observation_date THEN 1 END) AS running,
+                    COUNT(CASE WHEN attestation_signature IS NULL
+                               AND end_observation_date <= ?1 THEN 1 END) AS completed,
+                    COUNT(attestation_signature) AS signed,
+                    COUNT(CASE WHEN attestation_signature IS NOT NULL
+                               AND signing_date >= ?2 THEN 1 END) AS signed_recently,
+                    (SELECT COUNT(*) FROM events_entries) AS total_entries
+             FROM events",
+        )
+        .bind(now.unix_timestamp())
+        .bind(signed_since.unix_timestamp())
+        .fetch_one(&self.read_pool)
+        .await?;
+
+        Ok(EventAggregates {
+            total_events: row.get("total_events"),
+            live: row.get("live"),
+            running: row.get("running"),
+            completed: row.get("completed"),
+            signed: row.get("signed"),
+            total_entries: row.get("total_entries"),
+            signed_recently: row.get("signed_recently"),
+            window_days: 0,
+        })
+    }
+
+    async fn update_weather_station_data(
         &self,
         event_id: Uuid,
         weather: Vec<Weather>,
+        temp_unit: &TemperatureUnit,
     ) -> Result<()> {
-        let weather_ids = self.add_weather_readings(weather).await?;
+        let weather_ids = self.add_weather_readings(weather, temp_unit).await?;
         self.batch_add_weather_to_event(event_id, weather_ids).await
     }
 
-    pub async fn get_event_weather(&self, event_id: Uuid) -> Result<Vec<Weather>> {
+    async fn get_event_weather_in_unit(
+        &self,
+        event_id: Uuid,
+        temp_unit: &TemperatureUnit,
+    ) -> Result<Vec<Weather>> {
         let rows = sqlx::query(
             "SELECT w.station_id, w.observed_date, w.observed_temp_low, w.observed_temp_high,
                     w.observed_wind_speed, w.forecasted_date, w.forecasted_temp_low,
-                    w.forecasted_temp_high, w.forecasted_wind_speed
+                    w.forecasted_temp_high, w.forecasted_wind_speed, w.temp_unit_code
              FROM weather w
              JOIN events_weather ew ON ew.weather_id = w.id
              WHERE ew.event_id = ?",
         )
         .bind(event_id.to_string())
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool)
         .await?;
 
         let mut weather = Vec::new();
@@ -812,21 +1396,20 @@ impl Database {
                 wind_speed: row.get("forecasted_wind_speed"),
             };
 
-            weather.push(Weather {
+            let mut station_weather = Weather {
                 station_id: row.get("station_id"),
                 observed,
                 forecasted,
-            });
+            };
+            let stored_unit: String = row.get("temp_unit_code");
+            station_weather.convert_temperature(&stored_unit, temp_unit);
+            weather.push(station_weather);
         }
 
         Ok(weather)
     }
 
-    pub async fn get_weather_entry(
-        &self,
-        event_id: &Uuid,
-        entry_id: &Uuid,
-    ) -> Result<WeatherEntry> {
+    async fn get_weather_entry(&self, event_id: &Uuid, entry_id: &Uuid) -> Result<WeatherEntry> {
         let row = sqlx::query(
             "SELECT id, event_id, score, base_score
              FROM events_entries
@@ -834,7 +1417,7 @@ impl Database {
         )
         .bind(entry_id.to_string())
         .bind(event_id.to_string())
-        .fetch_one(&self.pool)
+        .fetch_one(&self.read_pool)
         .await?;
 
         let choices = self.get_entry_choices(entry_id).await?;
@@ -848,3 +1431,97 @@ impl Database {
         })
     }
 }
+
+async fn check_entry_limit(
+    tx: &mut Transaction<'_, Sqlite>,
+    entries: &[WeatherEntry],
+) -> Result<()> {
+    for (event_id, new) in entries_per_event(entries) {
+        let (allowed, existing): (i64, i64) = sqlx::query_as(
+            "SELECT e.total_allowed_entries,
+                    (SELECT COUNT(*) FROM events_entries ee WHERE ee.event_id = e.id)
+             FROM events e WHERE e.id = ?",
+        )
+        .bind(event_id.to_string())
+        .fetch_one(&mut **tx)
+        .await?;
+        if existing + new > allowed {
+            return Err(EntryLimitExceeded {
+                event_id,
+                allowed,
+                existing,
+                new,
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+async fn insert_entry_choices(
+    tx: &mut Transaction<'_, Sqlite>,
+    entry: &WeatherEntry,
+) -> Result<()> {
+    for choice in &entry.expected_observations {
+        sqlx::query(
+            "INSERT INTO expected_observations
+             (entry_id, station, temp_low, temp_high, wind_speed,
+              wind_direction, rain_amt, snow_amt, humidity, predictions)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry.id.to_string())
+        .bind(&choice.stations)
+        .bind(choice.temp_low.as_ref().map(|v| v.to_string()))
+        .bind(choice.temp_high.as_ref().map(|v| v.to_string()))
+        .bind(choice.wind_speed.as_ref().map(|v| v.to_string()))
+        .bind(choice.wind_direction.as_ref().map(|v| v.to_string()))
+        .bind(choice.rain_amt.as_ref().map(|v| v.to_string()))
+        .bind(choice.snow_amt.as_ref().map(|v| v.to_string()))
+        .bind(choice.humidity.as_ref().map(|v| v.to_string()))
+        .bind(encode_field_values(&choice.predictions)?)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Weather readings are shared through `events_weather`, so they are removed once no event links to them
+async fn delete_orphaned_weather(tx: &mut Transaction<'_, Sqlite>) -> Result<()> {
+    sqlx::query("DELETE FROM weather WHERE id NOT IN (SELECT weather_id FROM events_weather)")
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// Per field values (par tolerances, numeric predictions) store NULL when empty so they read back the
+/// same as rows from before their column existed
+fn encode_field_values(values: &HashMap<ScoringField, f64>) -> Result<Option<String>> {
+    if values.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_string(values)?))
+}
+
+fn decode_field_values(json: Option<String>, column: &str) -> Result<HashMap<ScoringField, f64>> {
+    let Some(json) = json else {
+        return Ok(HashMap::new());
+    };
+    serde_json::from_str(&json).with_context(|| format!("invalid {} stored", column))
+}
+
+fn decode_settlement_delay(row: &sqlx::sqlite::SqliteRow) -> Option<u32> {
+    row.get::<Option<i64>, _>("settlement_delay_hours")
+        .and_then(|hours| u32::try_from(hours).ok())
+}
+
+fn decode_outcome_message_version(row: &sqlx::sqlite::SqliteRow) -> Result<OutcomeMessageVersion> {
+    let version: i64 = row.get("outcome_message_version");
+    OutcomeMessageVersion::try_from(u8::try_from(version)?)
+}
+
+fn decode_entry_cutoff(row: &sqlx::sqlite::SqliteRow) -> Result<Option<OffsetDateTime>> {
+    Ok(row
+        .get::<Option<i64>, _>("entry_cutoff")
+        .map(OffsetDateTime::from_unix_timestamp)
+        .transpose()?)
+}
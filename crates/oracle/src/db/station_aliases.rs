@@ -0,0 +1,87 @@
+use crate::Station;
+use anyhow::anyhow;
+use std::collections::HashMap;
+
+/// Resolves the ways users name a station to the NOAA `station_id` the weather data is keyed on:
+/// the station id itself, its IATA code (`JFK`) or the ICAO code NOAA gives contiguous US
+/// airports (`K` + IATA, `KJFK`). Matching ignores case and station ids win over aliases.
+#[derive(Clone, Debug, Default)]
+pub struct StationAliases {
+    /// Uppercased station id to the station id as stored
+    station_ids: HashMap<String, String>,
+    /// Uppercased alias to every station id carrying it
+    aliases: HashMap<String, Vec<String>>,
+}
+
+impl StationAliases {
+    pub fn new(stations: &[Station]) -> Self {
+        let station_ids: HashMap<String, String> = stations
+            .iter()
+            .map(|station| {
+                (
+                    station.station_id.to_uppercase(),
+                    station.station_id.clone(),
+                )
+            })
+            .collect();
+
+        let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+        for station in stations {
+            let iata = station.iata_id.trim().to_uppercase();
+            if iata.is_empty() {
+                continue;
+            }
+            let icao = (iata.len() == 3).then(|| format!("K{}", iata));
+            for alias in std::iter::once(iata).chain(icao) {
+                if station_ids.contains_key(&alias) {
+                    continue;
+                }
+                let matches = aliases.entry(alias).or_default();
+                if !matches.contains(&station.station_id) {
+                    matches.push(station.station_id.clone());
+                }
+            }
+        }
+
+        StationAliases {
+            station_ids,
+            aliases,
+        }
+    }
+
+    /// The station id `id` names, if it names exactly one station
+    pub fn resolve(&self, id: &str) -> Result<String, anyhow::Error> {
+        let key = id.trim().to_uppercase();
+        if let Some(station_id) = self.station_ids.get(&key) {
+            return Ok(station_id.clone());
+        }
+        match self.aliases.get(&key).map(Vec::as_slice) {
+            Some([station_id]) => Ok(station_id.clone()),
+            Some(station_ids) => Err(anyhow!(
+                "station alias {} matches more than one station: {}",
+                id,
+                station_ids.join(", ")
+            )),
+            None => Err(anyhow!("unknown station ids: {}", id)),
+        }
+    }
+
+    /// Resolves every id in order, reporting all of the ones that don't resolve at once
+    pub fn resolve_all(&self, ids: &[String]) -> Result<Vec<String>, anyhow::Error> {
+        let mut resolved = Vec::with_capacity(ids.len());
+        let mut unknown = vec![];
+        for id in ids {
+            match self.resolve(id) {
+                Ok(station_id) => resolved.push(station_id),
+                Err(_) if !self.aliases.contains_key(&id.trim().to_uppercase()) => {
+                    unknown.push(id.as_str())
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(anyhow!("unknown station ids: {}", unknown.join(", ")));
+        }
+        Ok(resolved)
+    }
+}
@@ -1,33 +1,485 @@
 use crate::{
-    file_access, FileAccess, FileData, FileParams, ForecastRequest, ObservationRequest,
-    TemperatureUnit,
+    custom_query::{check_custom_query, run_custom_query},
+    file_access, CompareRequest, CoverageRequest, CustomQueryLimits, CustomQueryRequest,
+    CustomQueryRows, DayTimeZone, FileAccess, FileData, FileParams, FileType, ForecastAggregation,
+    ForecastField, ForecastFields, ForecastRequest, Granularity, ObservationRequest,
+    ObservationTempAggregation, ObservationWindAggregation, PrecipUnit, TemperatureUnit,
+    WindSpeedUnit,
 };
 use async_trait::async_trait;
+use axum::body::Body;
 use duckdb::{
     arrow::array::{Array, Float64Array, Int64Array, RecordBatch, StringArray},
-    params_from_iter, Connection,
+    params, params_from_iter, Connection,
 };
+use log::{debug, warn};
 use regex::Regex;
 use scooby::postgres::Select;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime, Time};
+use std::{fmt, path::Path, str::FromStr, sync::Arc, time::Instant};
+use time::{
+    format_description::well_known::Rfc3339, Date, Duration, OffsetDateTime, Time, UtcOffset,
+};
+use tokio_util::io::ReaderStream;
 use utoipa::ToSchema;
+use uuid::Uuid;
 
 pub struct WeatherAccess {
     file_access: Arc<dyn FileData>,
+    daily_dir: String,
+    extension_dir: Option<String>,
+    query_settings: QuerySettings,
+    http_retries: Option<HttpRetrySettings>,
+    humidity_formula: HumidityFormula,
+    sanity_bounds: SanityBounds,
+    precip_codes: PrecipCodes,
+}
+
+/// Folder under the weather data dir holding materialized daily aggregates, the raw file
+/// scans skip it as it isn't a date folder
+pub const DAILY_FOLDER: &str = "daily";
+
+/// Days written by `WeatherAccess::materialize_daily`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MaterializeReport {
+    pub forecast_days: usize,
+    pub observation_days: usize,
+}
+
+/// Fallback memory limit when total system memory can't be read
+pub const FALLBACK_QUERY_MEMORY_LIMIT_MIB: u64 = 1024;
+
+/// DuckDB resource caps applied to every weather query connection. Lower values keep large
+/// multi-file scans from starving other services on the box, at the cost of slower queries
+/// and spilling to disk sooner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuerySettings {
+    pub threads: u32,
+    pub memory_limit_mib: u64,
+}
+
+impl Default for QuerySettings {
+    /// Half the cores and a quarter of system memory, DuckDB's own defaults are all cores and 80%
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism().map_or(2, |cores| cores.get() as u32);
+        Self {
+            threads: (cores / 2).max(1),
+            memory_limit_mib: total_memory_mib().map_or(FALLBACK_QUERY_MEMORY_LIMIT_MIB, |total| {
+                (total / 4).max(256)
+            }),
+        }
+    }
+}
+
+impl QuerySettings {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.threads == 0 {
+            return Err(anyhow::anyhow!("duckdb threads must be at least 1"));
+        }
+        if self.memory_limit_mib < 64 {
+            return Err(anyhow::anyhow!(
+                "duckdb memory limit must be at least 64 MiB, got {}",
+                self.memory_limit_mib
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, conn: &Connection) -> Result<(), duckdb::Error> {
+        conn.execute_batch(&format!(
+            "SET threads = {}; SET memory_limit = '{}MiB';",
+            self.threads, self.memory_limit_mib
+        ))
+    }
+}
+
+/// DuckDB httpfs retry knobs for weather files read over HTTP or S3. A GET that fails
+/// mid-scan is retried `retries` times, waiting `retry_wait_ms` and multiplying the wait by
+/// `backoff` after each attempt, before the whole query fails.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HttpRetrySettings {
+    pub retries: u32,
+    pub timeout_secs: u64,
+    pub retry_wait_ms: u64,
+    pub backoff: f64,
+}
+
+impl Default for HttpRetrySettings {
+    /// DuckDB's own httpfs defaults
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            timeout_secs: 30,
+            retry_wait_ms: 100,
+            backoff: 4.0,
+        }
+    }
+}
+
+impl HttpRetrySettings {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.timeout_secs == 0 {
+            return Err(anyhow::anyhow!(
+                "duckdb http timeout must be at least 1 second"
+            ));
+        }
+        if !self.backoff.is_finite() || self.backoff < 1.0 {
+            return Err(anyhow::anyhow!(
+                "duckdb http retry backoff must be at least 1.0, got {}",
+                self.backoff
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn statements(&self) -> String {
+        format!(
+            "SET http_retries = {}; SET http_timeout = {}; SET http_retry_wait_ms = {}; SET http_retry_backoff = {};",
+            self.retries, self.timeout_secs, self.retry_wait_ms, self.backoff
+        )
+    }
+
+    /// Loads httpfs, installing it when missing, then applies the retry settings
+    pub fn apply(&self, conn: &Connection) -> Result<(), duckdb::Error> {
+        let (installed, loaded): (bool, bool) = conn.query_row(
+            "SELECT installed, loaded FROM duckdb_extensions() WHERE extension_name = 'httpfs'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if let Some(setup) = httpfs_setup(installed, loaded) {
+            conn.execute_batch(setup)?;
+        }
+        conn.execute_batch(&self.statements())
+    }
+}
+
+/// Why a read of a remote weather file failed, decides whether a retry can help
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteReadFailure {
+    /// The file isn't there, retrying won't change that
+    Missing,
+    /// Timeouts, dropped connections, throttling and 5xx responses that outlived the httpfs
+    /// retries, the same query may succeed later
+    Transient,
+}
+
+/// Classifies a DuckDB error message from an HTTP or S3 read, None for errors that didn't
+/// come from a remote read or that a retry won't fix (e.g. access denied)
+pub fn classify_remote_error(message: &str) -> Option<RemoteReadFailure> {
+    let lower = message.to_lowercase();
+    let remote = [
+        "s3://",
+        "http://",
+        "https://",
+        "http error",
+        "http get",
+        "http head",
+    ]
+    .iter()
+    .any(|marker| lower.contains(marker));
+    if !remote {
+        return None;
+    }
+    // Standalone codes only, file names carry long fractional seconds
+    let status = Regex::new(r"\b(404|429|50[0-4])\b").unwrap();
+    let status = status.find(&lower).map(|code| code.as_str());
+    let missing = ["not found", "no files found", "nosuchkey"]
+        .iter()
+        .any(|marker| lower.contains(marker));
+    if status == Some("404") || (status.is_none() && missing) {
+        return Some(RemoteReadFailure::Missing);
+    }
+    let transient = [
+        "too many requests",
+        "slowdown",
+        "service unavailable",
+        "timeout",
+        "timed out",
+        "connection error",
+        "could not establish connection",
+        "connection reset",
+    ];
+    (status.is_some() || transient.iter().any(|marker| lower.contains(marker)))
+        .then_some(RemoteReadFailure::Transient)
+}
+
+/// Readings outside these bounds are treated as bad data and left out of the daily values.
+/// Temperatures are configured in one unit and converted to each row's own unit, so the
+/// same bounds work for Fahrenheit forecasts and Celsius observations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanityBounds {
+    pub temperature_unit: TemperatureUnit,
+    pub temperature_min: f64,
+    pub temperature_max: f64,
+    /// Upper wind speed in the stored unit (knots for NDFD and METAR), the lower bound is 0
+    pub wind_speed_max: i64,
+}
+
+impl Default for SanityBounds {
+    fn default() -> Self {
+        Self::defaults_for(TemperatureUnit::Fahrenheit)
+    }
+}
+
+impl SanityBounds {
+    /// -200°F to 200°F, or roughly the same range in Celsius
+    pub fn defaults_for(temperature_unit: TemperatureUnit) -> Self {
+        let (temperature_min, temperature_max) = match temperature_unit {
+            TemperatureUnit::Fahrenheit => (-200.0, 200.0),
+            TemperatureUnit::Celsius => (-130.0, 95.0),
+        };
+        Self {
+            temperature_unit,
+            temperature_min,
+            temperature_max,
+            wind_speed_max: 500,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.temperature_min >= self.temperature_max {
+            return Err(anyhow::anyhow!(
+                "sanity temperature min {} must be below max {}",
+                self.temperature_min,
+                self.temperature_max
+            ));
+        }
+        if self.wind_speed_max <= 0 {
+            return Err(anyhow::anyhow!(
+                "sanity wind speed max must be positive, got {}",
+                self.wind_speed_max
+            ));
+        }
+        Ok(())
+    }
+
+    /// SQL predicate keeping `column` within bounds, converted to the row's `temperature_unit_code`.
+    /// Rows without a unit code are compared in `default_unit`.
+    pub fn temperature_filter(&self, column: &str, default_unit: &TemperatureUnit) -> String {
+        let bound = |value: f64, unit: &TemperatureUnit| {
+            convert_temperature(value, &self.temperature_unit.to_string(), unit)
+        };
+        format!(
+            "CASE WHEN COALESCE(lower(temperature_unit_code), '{default}') IN ('celsius', 'celcius') \
+             THEN {column} BETWEEN {c_min} AND {c_max} \
+             ELSE {column} BETWEEN {f_min} AND {f_max} END",
+            default = default_unit,
+            c_min = bound(self.temperature_min, &TemperatureUnit::Celsius),
+            c_max = bound(self.temperature_max, &TemperatureUnit::Celsius),
+            f_min = bound(self.temperature_min, &TemperatureUnit::Fahrenheit),
+            f_max = bound(self.temperature_max, &TemperatureUnit::Fahrenheit),
+        )
+    }
+
+    pub fn wind_speed_filter(&self, column: &str) -> String {
+        format!(
+            "{column} >= 0 AND {column} <= {max}",
+            max = self.wind_speed_max
+        )
+    }
+
+    pub fn wind_speed_in_bounds(&self, wind_speed: i64) -> bool {
+        (0..=self.wind_speed_max).contains(&wind_speed)
+    }
+}
+
+/// METAR weather codes in an observation's `wx_string` that mark its precipitation as snow
+/// or ice, everything else is rain. Codes are matched as whole space separated tokens and
+/// snow is checked before ice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecipCodes {
+    pub snow: Vec<String>,
+    pub ice: Vec<String>,
+}
+
+impl Default for PrecipCodes {
+    fn default() -> Self {
+        let codes = |codes: &[&str]| codes.iter().map(|code| code.to_string()).collect();
+        Self {
+            snow: codes(&["SN", "BLSN", "DRSN"]),
+            ice: codes(&["FZRA", "FZDZ", "PL", "GR", "GS", "IC"]),
+        }
+    }
+}
+
+impl PrecipCodes {
+    /// Codes end up inside a SQL regex, so only plain uppercase codes are accepted
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        for (kind, codes) in [("snow", &self.snow), ("ice", &self.ice)] {
+            if codes.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "{} precipitation codes can't be empty",
+                    kind
+                ));
+            }
+            if let Some(code) = codes.iter().find(|code| {
+                !(2..=8).contains(&code.len()) || !code.chars().all(|c| c.is_ascii_uppercase())
+            }) {
+                return Err(anyhow::anyhow!(
+                    "invalid {} precipitation code '{}', expected 2-8 uppercase letters",
+                    kind,
+                    code
+                ));
+            }
+        }
+        if let Some(code) = self.snow.iter().find(|code| self.ice.contains(code)) {
+            return Err(anyhow::anyhow!(
+                "precipitation code '{}' can't be both snow and ice",
+                code
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn snow_pattern(&self) -> String {
+        token_pattern(&self.snow)
+    }
+
+    pub fn ice_pattern(&self) -> String {
+        token_pattern(&self.ice)
+    }
+}
+
+fn token_pattern(codes: &[String]) -> String {
+    format!(r"(^|\s)({})(\s|$)", codes.join("|"))
+}
+
+/// Saturation vapour pressure coefficients for the Magnus formula,
+/// `es(T) = 6.112 * exp(a * T / (b + T))` with T in Celsius
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MagnusCoefficients {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl MagnusCoefficients {
+    /// Over liquid water (Alduchov & Eskridge 1996)
+    pub const WATER: Self = Self {
+        a: 17.625,
+        b: 243.04,
+    };
+    /// Over ice (Sonntag 1990)
+    pub const ICE: Self = Self {
+        a: 22.46,
+        b: 272.62,
+    };
+
+    /// Relative humidity in percent, unrounded
+    pub fn relative_humidity(&self, temperature: f64, dewpoint: f64) -> f64 {
+        100.0 * (self.a * dewpoint / (self.b + dewpoint)).exp()
+            / (self.a * temperature / (self.b + temperature)).exp()
+    }
+
+    fn sql(&self, temperature: &str, dewpoint: &str) -> String {
+        format!(
+            "100.0 * EXP(({a} * {dewpoint}) / ({b} + {dewpoint})) / EXP(({a} * {temperature}) / ({b} + {temperature}))",
+            a = self.a,
+            b = self.b,
+        )
+    }
+}
+
+/// Which Magnus coefficients derive observed humidity from temperature and dewpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HumidityFormula {
+    /// Water coefficients at every temperature, overestimates RH below freezing
+    #[default]
+    Water,
+    /// Ice coefficients below 0°C, water coefficients otherwise
+    IceBelowFreezing,
+}
+
+impl HumidityFormula {
+    pub fn coefficients(&self, temperature: f64) -> MagnusCoefficients {
+        match self {
+            HumidityFormula::IceBelowFreezing if temperature < 0.0 => MagnusCoefficients::ICE,
+            _ => MagnusCoefficients::WATER,
+        }
+    }
+
+    pub fn relative_humidity(&self, temperature: f64, dewpoint: f64) -> f64 {
+        self.coefficients(temperature)
+            .relative_humidity(temperature, dewpoint)
+    }
+
+    /// SQL expression matching `relative_humidity`, unrounded
+    fn sql(&self, temperature: &str, dewpoint: &str) -> String {
+        match self {
+            HumidityFormula::Water => MagnusCoefficients::WATER.sql(temperature, dewpoint),
+            HumidityFormula::IceBelowFreezing => format!(
+                "CASE WHEN {} < 0 THEN {} ELSE {} END",
+                temperature,
+                MagnusCoefficients::ICE.sql(temperature, dewpoint),
+                MagnusCoefficients::WATER.sql(temperature, dewpoint)
+            ),
+        }
+    }
+}
+
+impl FromStr for HumidityFormula {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "water" => Ok(HumidityFormula::Water),
+            "ice-below-freezing" => Ok(HumidityFormula::IceBelowFreezing),
+            other => Err(anyhow::anyhow!(
+                "unknown humidity formula '{}', expected water or ice-below-freezing",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for HumidityFormula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HumidityFormula::Water => write!(f, "water"),
+            HumidityFormula::IceBelowFreezing => write!(f, "ice-below-freezing"),
+        }
+    }
+}
+
+/// MemTotal from /proc/meminfo, None off Linux
+fn total_memory_mib() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib / 1024)
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Failed to query duckdb: {0}")]
-    Query(#[from] duckdb::Error),
+    Query(duckdb::Error),
+    #[error("Weather file temporarily unreachable, retry later: {0}")]
+    RemoteUnavailable(duckdb::Error),
+    #[error("Weather file not found: {0}")]
+    RemoteMissing(duckdb::Error),
     #[error("Failed to format time string: {0}")]
     TimeFormat(#[from] time::error::Format),
     #[error("Failed to parse time string: {0}")]
     TimeParse(#[from] time::error::Parse),
     #[error("Failed to access files: {0}")]
     FileAccess(#[from] file_access::Error),
+    #[error("Failed to materialize daily data: {0}")]
+    Materialize(String),
+    #[error("Invalid custom query: {0}")]
+    CustomQuery(String),
+    #[error("Failed to export forecasts: {0}")]
+    Export(String),
+}
+
+impl From<duckdb::Error> for Error {
+    /// Failed remote reads are split out so callers can tell a retryable S3 hiccup from a
+    /// file that doesn't exist
+    fn from(err: duckdb::Error) -> Self {
+        match classify_remote_error(&err.to_string()) {
+            Some(RemoteReadFailure::Missing) => Error::RemoteMissing(err),
+            Some(RemoteReadFailure::Transient) => Error::RemoteUnavailable(err),
+            None => Error::Query(err),
+        }
+    }
 }
 
 #[async_trait]
@@ -49,6 +501,340 @@ pub trait WeatherData: Sync + Send {
         station_ids: Vec<String>,
     ) -> Result<Vec<DailyObservation>, Error>;
     async fn stations(&self) -> Result<Vec<Station>, Error>;
+    /// Ids of the stations in the `file_type` files generated on `date` (UTC), sorted
+    async fn stations_with_data(
+        &self,
+        date: Date,
+        file_type: FileType,
+    ) -> Result<Vec<String>, Error>;
+
+    /// Runs a read-only query over the observation and forecast files generated in the
+    /// request's range, loaded as the `observations` and `forecasts` tables
+    async fn custom_query(
+        &self,
+        req: &CustomQueryRequest,
+        limits: CustomQueryLimits,
+    ) -> Result<CustomQueryRows, Error>;
+
+    /// Daily forecasts as a parquet file along with what the query touched. DuckDB encodes
+    /// the aggregation's rows itself, they never pass through JSON.
+    async fn forecasts_parquet(
+        &self,
+        req: &ForecastRequest,
+        station_ids: Vec<String>,
+    ) -> Result<(Body, QueryStats), Error>;
+
+    /// Per station over the request's range, how many observations arrived and how many of
+    /// the range's hourly slots they cover, least covered first. Stations in the files read
+    /// without an observation in the range are listed with no coverage.
+    async fn observation_coverage(
+        &self,
+        req: &CoverageRequest,
+    ) -> Result<Vec<ObservationCoverage>, Error>;
+
+    /// `forecasts_data` along with what the query touched
+    async fn forecasts_data_with_stats(
+        &self,
+        req: &ForecastRequest,
+        station_ids: Vec<String>,
+    ) -> Result<(Vec<Forecast>, QueryStats), Error> {
+        let started = Instant::now();
+        let forecasts = self.forecasts_data(req, station_ids).await?;
+        let stats = QueryStats::new(None, forecasts.len(), started);
+        Ok((forecasts, stats))
+    }
+
+    /// `observation_data` along with what the query touched
+    async fn observation_data_with_stats(
+        &self,
+        req: &ObservationRequest,
+        station_ids: Vec<String>,
+    ) -> Result<(Vec<Observation>, QueryStats), Error> {
+        let started = Instant::now();
+        let observations = self.observation_data(req, station_ids).await?;
+        let stats = QueryStats::new(None, observations.len(), started);
+        Ok((observations, stats))
+    }
+
+    /// `daily_observations` along with what the query touched
+    async fn daily_observations_with_stats(
+        &self,
+        req: &ObservationRequest,
+        station_ids: Vec<String>,
+    ) -> Result<(Vec<DailyObservation>, QueryStats), Error> {
+        let started = Instant::now();
+        let observations = self.daily_observations(req, station_ids).await?;
+        let stats = QueryStats::new(None, observations.len(), started);
+        Ok((observations, stats))
+    }
+
+    /// Daily forecasts next to the daily observations for the same station and date,
+    /// dates missing either side are left out
+    async fn forecast_comparison(
+        &self,
+        req: &CompareRequest,
+        station_ids: Vec<String>,
+    ) -> Result<Vec<DailyComparison>, Error> {
+        let forecasts = self
+            .forecasts_data(&ForecastRequest::from(req), station_ids.clone())
+            .await?;
+        let observations = self
+            .daily_observations(&ObservationRequest::from(req), station_ids)
+            .await?;
+        Ok(compare_daily(&forecasts, &observations)?)
+    }
+}
+
+/// Size and timing of a weather query
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    /// Parquet files scanned, None when the source doesn't report it
+    pub files: Option<usize>,
+    pub rows: usize,
+    pub elapsed_ms: u64,
+    /// Newest `generated_at` among the rows behind the results, how fresh the data is
+    pub generated_at: Option<OffsetDateTime>,
+}
+
+/// Daily aggregate of a forecast column, `extreme` (MIN/MAX) for min/max mode otherwise the
+/// requested percentile rounded back to the column's integer type
+fn daily_aggregate(agg: &ForecastAggregation, extreme: &str, column: &str, valid: &str) -> String {
+    match agg.quantile() {
+        None => format!(
+            "{}({}) FILTER (WHERE {} IS NOT NULL AND {})",
+            extreme, column, column, valid
+        ),
+        Some(quantile) => format!(
+            "ROUND(quantile_cont({}, {}) FILTER (WHERE {} IS NOT NULL AND {}))::BIGINT",
+            column, quantile, column, valid
+        ),
+    }
+}
+
+/// Final projection of the daily forecast query. Fields that weren't requested are typed
+/// NULLs so rows keep their column layout without DuckDB computing them, and the
+/// precipitation CTEs are only joined when a precipitation field is wanted.
+pub fn daily_forecast_select(
+    fields: &ForecastFields,
+    start_time_expr: &str,
+    end_time_expr: &str,
+) -> String {
+    use ForecastField::*;
+    let column = |wanted: bool, expr: &str, null_type: &str, name: &str| {
+        if wanted {
+            format!("{} AS {}", expr, name)
+        } else {
+            format!("NULL::{} AS {}", null_type, name)
+        }
+    };
+    let has = |field| fields.includes(field);
+    // Temperatures are converted using the unit code, dominant precip is picked from the amounts
+    let temperatures = has(TempLow) || has(TempHigh) || has(TempUnitCode);
+    let precip = has(DominantPrecip);
+
+    let columns = [
+        String::from("df.station_id"),
+        String::from("df.date"),
+        column(has(StartTime), start_time_expr, "VARCHAR", "start_time"),
+        column(has(EndTime), end_time_expr, "VARCHAR", "end_time"),
+        column(has(TempLow), "MIN(df.temp_low)", "BIGINT", "temp_low"),
+        column(has(TempHigh), "MAX(df.temp_high)", "BIGINT", "temp_high"),
+        column(has(WindSpeed), "MAX(df.wind_speed)", "BIGINT", "wind_speed"),
+        column(
+            has(WindDirection),
+            "MAX(df.wind_direction)",
+            "BIGINT",
+            "wind_direction",
+        ),
+        column(has(HumidityMax), "MAX(df.humidity_max)", "BIGINT", "humidity_max"),
+        column(has(HumidityMin), "MIN(df.humidity_min)", "BIGINT", "humidity_min"),
+        column(
+            temperatures,
+            "MAX(df.temperature_unit_code)",
+            "VARCHAR",
+            "temperature_unit_code",
+        ),
+        column(
+            has(PrecipChance),
+            "MAX(df.precip_chance)",
+            "DOUBLE",
+            "precip_chance",
+        ),
+        // Rain is QPF minus the snow's liquid equivalent (snow / snow_ratio) and ice, or all
+        // of QPF minus ice without a snow ratio, never negative
+        column(
+            has(RainAmt) || precip,
+            "GREATEST(0, COALESCE(
+                    dp.total_qpf - (dp.snow_amt / NULLIF(dp.avg_snow_ratio, 0)) - COALESCE(dp.ice_amt, 0),
+                    dp.total_qpf - COALESCE(dp.ice_amt, 0)
+                ))",
+            "DOUBLE",
+            "rain_amt",
+        ),
+        column(has(SnowAmt) || precip, "dp.snow_amt", "DOUBLE", "snow_amt"),
+        column(has(IceAmt) || precip, "dp.ice_amt", "DOUBLE", "ice_amt"),
+        String::from("MAX(df.generated_at) AS generated_at"),
+        // Last so the columns before it keep the layout materialized days were written with
+        column(
+            has(WindSpeed),
+            "MAX(df.wind_speed_unit_code)",
+            "VARCHAR",
+            "wind_speed_unit_code",
+        ),
+    ];
+    let joins_precip = has(RainAmt) || has(SnowAmt) || has(IceAmt) || precip;
+    let (join, group_by) = if joins_precip {
+        (
+            "LEFT JOIN daily_precip dp ON df.station_id = dp.station_id AND df.date = dp.date",
+            "df.station_id, df.date, dp.total_qpf, dp.snow_amt, dp.avg_snow_ratio, dp.ice_amt",
+        )
+    } else {
+        ("", "df.station_id, df.date")
+    };
+    format!(
+        "SELECT {} FROM daily_forecasts df {} GROUP BY {}",
+        columns.join(", "),
+        join,
+        group_by
+    )
+}
+
+/// Latest of the queries' per-row `generated_at` column, None when it's missing or empty
+fn newest_generated_at(records: &[RecordBatch]) -> Option<OffsetDateTime> {
+    records
+        .iter()
+        .filter_map(|record| {
+            let index = record.schema().index_of("generated_at").ok()?;
+            record
+                .column(index)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .cloned()
+        })
+        .flat_map(|generated_at| {
+            generated_at
+                .iter()
+                .flatten()
+                .filter_map(|value| OffsetDateTime::parse(value, &Rfc3339).ok())
+                .collect::<Vec<_>>()
+        })
+        .max()
+}
+
+fn log_query_stats(query: &str, stats: &QueryStats) {
+    debug!(
+        "{} scanned {} parquet files, returned {} rows in {}ms",
+        query,
+        stats.files.unwrap_or_default(),
+        stats.rows,
+        stats.elapsed_ms
+    );
+}
+
+impl QueryStats {
+    pub fn new(files: Option<usize>, rows: usize, started: Instant) -> Self {
+        QueryStats {
+            files,
+            rows,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            generated_at: None,
+        }
+    }
+
+    pub fn with_generated_at(mut self, generated_at: Option<OffsetDateTime>) -> Self {
+        self.generated_at = generated_at;
+        self
+    }
+
+    /// Why the query returned nothing, None when it has rows or the source doesn't report
+    /// the files it scanned
+    pub fn empty_data(&self) -> Option<EmptyData> {
+        match (self.rows, self.files?) {
+            (0, 0) => Some(EmptyData::NoFiles),
+            (0, _) => Some(EmptyData::NoRows),
+            _ => None,
+        }
+    }
+}
+
+/// Reason a weather query came back empty, a missing or misconfigured data dir shows up as
+/// `NoFiles` rather than looking like a gap in the data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmptyData {
+    /// No parquet files exist for the requested range
+    NoFiles,
+    /// Files were scanned but no rows matched the filters
+    NoRows,
+}
+
+impl EmptyData {
+    /// Value of the `x-data-source` header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmptyData::NoFiles => "none",
+            EmptyData::NoRows => "empty",
+        }
+    }
+}
+
+/// The optional `wind_speed_unit_code` column, missing from materialized days written before
+/// it was selected
+fn wind_speed_unit_codes(record_batch: &RecordBatch) -> Option<&StringArray> {
+    let index = record_batch
+        .schema()
+        .index_of("wind_speed_unit_code")
+        .ok()?;
+    record_batch
+        .column(index)
+        .as_any()
+        .downcast_ref::<StringArray>()
+}
+
+/// The optional `bucket` column, only selected when a granularity was requested
+fn bucket_starts(record_batch: &RecordBatch) -> Option<&StringArray> {
+    let index = record_batch.schema().index_of("bucket").ok()?;
+    record_batch
+        .column(index)
+        .as_any()
+        .downcast_ref::<StringArray>()
+}
+
+/// A row's bucket start, None without a `bucket` column
+fn bucket_start(buckets: Option<&StringArray>, row_index: usize) -> Option<String> {
+    buckets
+        .filter(|buckets| !buckets.is_null(row_index))
+        .map(|buckets| buckets.value(row_index).to_owned())
+}
+
+/// Unit a row's wind speed is stored in. Rows without a code are knots, what NDFD and METAR
+/// both report, and None for a code that isn't a wind speed unit so it's left unconverted.
+fn stored_wind_unit(codes: Option<&StringArray>, row_index: usize) -> Option<WindSpeedUnit> {
+    match codes {
+        Some(codes) if !codes.is_null(row_index) => codes.value(row_index).parse().ok(),
+        _ => Some(WindSpeedUnit::Knots),
+    }
+}
+
+/// Converts rain/snow/ice amounts from the unit in `unit_code` to `target_unit` and updates the
+/// code. Amounts are stored in inches, so an empty code is inches and an unknown one is left
+/// alone.
+fn convert_precip_amounts(
+    amounts: [&mut Option<f64>; 3],
+    unit_code: &mut String,
+    target_unit: PrecipUnit,
+) {
+    let current_unit = if unit_code.is_empty() {
+        PrecipUnit::Inches
+    } else {
+        match unit_code.parse::<PrecipUnit>() {
+            Ok(unit) => unit,
+            Err(_) => return,
+        }
+    };
+    for amount in amounts {
+        *amount = amount.map(|value| target_unit.convert(value, current_unit));
+    }
+    *unit_code = target_unit.to_string();
 }
 
 pub fn convert_temperature(value: f64, from_unit: &str, to_unit: &TemperatureUnit) -> f64 {
@@ -59,15 +845,93 @@ pub fn convert_temperature(value: f64, from_unit: &str, to_unit: &TemperatureUni
     }
 }
 
+/// Statements needed to make the parquet extension usable, only reaching out to the
+/// extension repository when it isn't installed yet
+pub fn parquet_setup(installed: bool, loaded: bool) -> Option<&'static str> {
+    match (installed, loaded) {
+        (_, true) => None,
+        (true, false) => Some("LOAD parquet;"),
+        (false, false) => Some("INSTALL parquet; LOAD parquet;"),
+    }
+}
+
+/// Same as `parquet_setup` for the httpfs extension, only needed when retries are configured
+pub fn httpfs_setup(installed: bool, loaded: bool) -> Option<&'static str> {
+    match (installed, loaded) {
+        (_, true) => None,
+        (true, false) => Some("LOAD httpfs;"),
+        (false, false) => Some("INSTALL httpfs; LOAD httpfs;"),
+    }
+}
+
 impl WeatherAccess {
     pub fn new(file_access: Arc<FileAccess>) -> Result<Self, duckdb::Error> {
-        Ok(Self { file_access })
+        Ok(Self {
+            daily_dir: format!("{}/{}", file_access.data_dir(), DAILY_FOLDER),
+            file_access,
+            extension_dir: None,
+            query_settings: QuerySettings::default(),
+            http_retries: None,
+            humidity_formula: HumidityFormula::default(),
+            sanity_bounds: SanityBounds::default(),
+            precip_codes: PrecipCodes::default(),
+        })
+    }
+
+    /// Load extensions from `extension_dir` instead of DuckDB's default, lets air-gapped hosts
+    /// ship the parquet extension alongside the oracle
+    pub fn with_extension_dir(mut self, extension_dir: Option<String>) -> Self {
+        self.extension_dir = extension_dir;
+        self
+    }
+
+    pub fn with_query_settings(mut self, query_settings: QuerySettings) -> Self {
+        self.query_settings = query_settings;
+        self
+    }
+
+    /// Retry flaky HTTP/S3 reads inside DuckDB, None leaves httpfs unloaded for local files
+    pub fn with_http_retries(mut self, http_retries: Option<HttpRetrySettings>) -> Self {
+        self.http_retries = http_retries;
+        self
+    }
+
+    pub fn with_humidity_formula(mut self, humidity_formula: HumidityFormula) -> Self {
+        self.humidity_formula = humidity_formula;
+        self
+    }
+
+    pub fn with_sanity_bounds(mut self, sanity_bounds: SanityBounds) -> Self {
+        self.sanity_bounds = sanity_bounds;
+        self
+    }
+
+    pub fn with_precip_codes(mut self, precip_codes: PrecipCodes) -> Self {
+        self.precip_codes = precip_codes;
+        self
     }
 
     /// Creates new in-memory connection, making it so we always start with a fresh slate and no possible locking issues
     pub fn open_connection(&self) -> Result<Connection, duckdb::Error> {
         let conn = Connection::open_in_memory()?;
-        conn.execute_batch("INSTALL parquet; LOAD parquet;")?;
+        self.query_settings.apply(&conn)?;
+        if let Some(extension_dir) = &self.extension_dir {
+            conn.execute_batch(&format!(
+                "SET extension_directory = '{}';",
+                extension_dir.replace('\'', "''")
+            ))?;
+        }
+        let (installed, loaded): (bool, bool) = conn.query_row(
+            "SELECT installed, loaded FROM duckdb_extensions() WHERE extension_name = 'parquet'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        if let Some(setup) = parquet_setup(installed, loaded) {
+            conn.execute_batch(setup)?;
+        }
+        if let Some(http_retries) = &self.http_retries {
+            http_retries.apply(&conn)?;
+        }
         Ok(conn)
     }
 
@@ -85,25 +949,67 @@ impl WeatherAccess {
         Ok(stmt.query_arrow(sql_params)?.collect())
     }
 }
-#[async_trait]
-impl WeatherData for WeatherAccess {
-    async fn forecasts_data(
+impl WeatherAccess {
+    async fn forecasts_data_query(
         &self,
         req: &ForecastRequest,
         station_ids: Vec<String>,
-    ) -> Result<Vec<Forecast>, Error> {
-        // If start is provided, look back one day to ensure we capture relevant files
-        // If start is None, keep it None to find all available data
-        let mut file_params: FileParams = req.into();
-        if let Some(start_date) = req.start {
+    ) -> Result<(Vec<Forecast>, usize, Option<OffsetDateTime>), Error> {
+        let (query_sql, files) = self.forecasts_sql(req, &station_ids).await?;
+        if files == 0 {
+            return Ok((vec![], 0, None));
+        }
+        let (forecasts, generated_at) = self.run_forecasts_query(&query_sql, req)?;
+        Ok((forecasts, files, generated_at))
+    }
+
+    /// The forecast query for `req` and how many files it reads, the materialized days when
+    /// they cover the request. With no files the query still has its columns, just no rows.
+    async fn forecasts_sql(
+        &self,
+        req: &ForecastRequest,
+        station_ids: &[String],
+    ) -> Result<(String, usize), Error> {
+        if let Some(daily_paths) = self.materialized_forecast_paths(req) {
+            let query_sql = materialized_forecasts_sql(req, &daily_paths, station_ids)?;
+            return Ok((bucketed_forecasts_sql(req, query_sql), daily_paths.len()));
+        }
+
+        // A replay reaches back from `as_of` when the forecast period starts after it
+        let lookback_from = match (req.start, req.as_of) {
+            (Some(start), Some(as_of)) => Some(start.min(as_of)),
+            (start, _) => start,
+        };
+        let file_paths = self.raw_file_paths(req.into(), lookback_from).await?;
+        let query_sql = self.daily_forecasts_sql(req, &file_paths, station_ids, true)?;
+        Ok((bucketed_forecasts_sql(req, query_sql), file_paths.len()))
+    }
+
+    /// Raw parquet files for a query, looking back one day from `start` so files generated
+    /// the day before still cover the first requested day. With no start every file is used.
+    async fn raw_file_paths(
+        &self,
+        mut file_params: FileParams,
+        start: Option<OffsetDateTime>,
+    ) -> Result<Vec<String>, Error> {
+        if let Some(start_date) = start {
             file_params.start = Some(start_date.saturating_sub(Duration::days(1)));
         }
         let parquet_files = self.file_access.grab_file_names(file_params).await?;
-        let file_paths = self.file_access.build_file_paths(parquet_files);
-        if file_paths.is_empty() {
-            return Ok(vec![]);
-        }
+        Ok(self.file_access.build_file_paths(parquet_files))
+    }
 
+    /// Daily forecast aggregation over the raw files. With `clip_times` off each day's
+    /// `start_time`/`end_time` are left as the forecast windows have them, materialized days
+    /// are stored that way and clipped to the request at read time. A finer `granularity`
+    /// groups by its buckets instead of days, `date` then holds the bucket's start.
+    fn daily_forecasts_sql(
+        &self,
+        req: &ForecastRequest,
+        file_paths: &[String],
+        station_ids: &[String],
+        clip_times: bool,
+    ) -> Result<String, Error> {
         // Build station filter clause
         let station_filter = if !station_ids.is_empty() {
             let quoted: Vec<String> = station_ids.iter().map(|s| format!("'{}'", s)).collect();
@@ -127,7 +1033,8 @@ impl WeatherData for WeatherAccess {
             ));
         }
 
-        let now = OffsetDateTime::now_utc();
+        // A replay picks its default generated window as if it were running at `as_of`
+        let now = req.as_of.unwrap_or_else(OffsetDateTime::now_utc);
         let (generated_start, generated_end) = match (req.generated_start, req.generated_end) {
             (Some(gs), Some(ge)) => (Some(gs), Some(ge)),
             (Some(gs), None) => (Some(gs), None),
@@ -138,10 +1045,16 @@ impl WeatherData for WeatherAccess {
                     if start <= threshold {
                         // Use start of the previous day to ensure we capture all relevant forecast files
                         // The DISTINCT ON ... ORDER BY generated_at DESC in SQL ensures we use the latest forecast
-                        let prev_day_start = start
+                        let mut prev_day_start = start
                             .date()
                             .previous_day()
                             .map(|d| d.with_time(Time::MIDNIGHT).assume_utc());
+                        // A replay right before the day starts still reaches the issuance
+                        // that was current then, which can be older than the previous day
+                        if req.as_of.is_some() {
+                            prev_day_start = prev_day_start
+                                .map(|at| at.min(now.saturating_sub(Duration::days(1))));
+                        }
                         (prev_day_start, Some(now))
                     } else {
                         (Some(now.saturating_sub(Duration::days(1))), Some(now))
@@ -164,6 +1077,13 @@ impl WeatherData for WeatherAccess {
                 generated_end.format(&Rfc3339)?
             ));
         }
+        // Applied before the dedup below, so the latest forecast left is the one known then
+        if let Some(as_of) = req.as_of {
+            time_filters.push(format!(
+                "generated_at::TIMESTAMPTZ <= '{}'::TIMESTAMPTZ",
+                as_of.format(&Rfc3339)?
+            ));
+        }
 
         let time_filter = if time_filters.is_empty() {
             String::new()
@@ -174,7 +1094,7 @@ impl WeatherData for WeatherAccess {
         };
 
         // Build start/end time expressions for final select
-        let start_time_expr = if let Some(start) = &req.start {
+        let start_time_expr = if let Some(start) = req.start.filter(|_| clip_times) {
             format!(
                 "GREATEST('{}', MIN(df.start_time))",
                 start.format(&Rfc3339)?
@@ -182,18 +1102,41 @@ impl WeatherData for WeatherAccess {
         } else {
             "MIN(df.start_time)".to_string()
         };
-        let end_time_expr = if let Some(end) = &req.end {
+        let end_time_expr = if let Some(end) = req.end.filter(|_| clip_times) {
             format!("LEAST('{}', MAX(df.end_time))", end.format(&Rfc3339)?)
         } else {
             "MAX(df.end_time)".to_string()
         };
 
+        // Without files only the typed empty row set below is read
+        let raw_files = if file_paths.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "UNION ALL BY NAME SELECT * FROM read_parquet(['{}'], union_by_name = true)",
+                file_paths.join("', '")
+            )
+        };
+
+        // Windows are grouped by the UTC start of the bucket they begin in
+        let granularity = req.granularity.unwrap_or_default();
+        let bucket = format!(
+            "{}::TEXT",
+            granularity.start_sql("begin_time", &DayTimeZone::Utc)
+        );
+        // A bucket shorter than a day rarely chains windows, it takes its shortest window
+        // rather than the longest so summing the buckets doesn't count the overlap twice
+        let unchained_order = match granularity {
+            Granularity::Daily => "-duration_secs",
+            Granularity::Hourly | Granularity::Raw => "duration_secs",
+        };
+
         // Use raw SQL with UNION ALL BY NAME to handle schema differences
         // Old files may not have all columns - we define NULL defaults for backwards compatibility
         // For precipitation, we first deduplicate by taking the latest forecast for each unique time window,
         // then sum across time windows to get daily totals
         // Rain is calculated as: QPF - (snow_amt / snow_ratio), or just QPF if no snow_ratio
-        let query_sql = format!(
+        Ok(format!(
             r#"
             WITH parquet_data AS (
                 SELECT * FROM (
@@ -204,10 +1147,9 @@ impl WeatherData for WeatherAccess {
                            NULL::VARCHAR AS temperature_unit_code, NULL::DOUBLE AS twelve_hour_probability_of_precipitation,
                            NULL::DOUBLE AS liquid_precipitation_amt, NULL::DOUBLE AS snow_amt,
                            NULL::DOUBLE AS snow_ratio, NULL::DOUBLE AS ice_amt,
-                           NULL::VARCHAR AS generated_at
+                           NULL::VARCHAR AS wind_speed_unit_code, NULL::VARCHAR AS generated_at
                     WHERE false
-                    UNION ALL BY NAME
-                    SELECT * FROM read_parquet(['{}'], union_by_name = true)
+                    {}
                 )
             ),
             -- Deduplicate: for each station + time window (normalized to UTC), take the most recent forecast
@@ -228,6 +1170,7 @@ impl WeatherData for WeatherAccess {
                     snow_amt,
                     snow_ratio,
                     ice_amt,
+                    wind_speed_unit_code,
                     generated_at
                 FROM parquet_data
                 {} {}
@@ -239,7 +1182,7 @@ impl WeatherData for WeatherAccess {
             precip_rows AS (
                 SELECT
                     station_id,
-                    DATE_TRUNC('day', begin_time::TIMESTAMPTZ AT TIME ZONE 'UTC')::TEXT AS date,
+                    {bucket} AS date,
                     begin_time::TIMESTAMPTZ AS begin_ts,
                     end_time::TIMESTAMPTZ AS end_ts,
                     EXTRACT(EPOCH FROM (end_time::TIMESTAMPTZ - begin_time::TIMESTAMPTZ)) AS duration_secs,
@@ -262,12 +1205,12 @@ impl WeatherData for WeatherAccess {
                     FROM precip_rows WHERE liquid_precipitation_amt IS NOT NULL
                 ) sub
                 GROUP BY station_id, date, duration_secs
-                HAVING COUNT(*) > 1
             ),
             best_qpf_duration AS (
-                SELECT DISTINCT ON (station_id, date) station_id, date, duration_secs
+                SELECT DISTINCT ON (station_id, date) station_id, date, duration_secs, chain_count > 0 AS chained
                 FROM qpf_duration
-                ORDER BY station_id, date, chain_count::FLOAT / row_count DESC, duration_secs ASC
+                ORDER BY station_id, date, chain_count > 0 DESC, chain_count::FLOAT / row_count DESC,
+                    CASE WHEN chain_count > 0 THEN duration_secs ELSE {unchained_order} END ASC
             ),
             -- Snow: detect native interval for snow amount
             snow_duration AS (
@@ -279,12 +1222,12 @@ impl WeatherData for WeatherAccess {
                     FROM precip_rows WHERE snow_amt IS NOT NULL
                 ) sub
                 GROUP BY station_id, date, duration_secs
-                HAVING COUNT(*) > 1
             ),
             best_snow_duration AS (
-                SELECT DISTINCT ON (station_id, date) station_id, date, duration_secs
+                SELECT DISTINCT ON (station_id, date) station_id, date, duration_secs, chain_count > 0 AS chained
                 FROM snow_duration
-                ORDER BY station_id, date, chain_count::FLOAT / row_count DESC, duration_secs ASC
+                ORDER BY station_id, date, chain_count > 0 DESC, chain_count::FLOAT / row_count DESC,
+                    CASE WHEN chain_count > 0 THEN duration_secs ELSE {unchained_order} END ASC
             ),
             -- Ice: detect native interval for ice amount
             ice_duration AS (
@@ -296,25 +1239,30 @@ impl WeatherData for WeatherAccess {
                     FROM precip_rows WHERE ice_amt IS NOT NULL
                 ) sub
                 GROUP BY station_id, date, duration_secs
-                HAVING COUNT(*) > 1
             ),
             best_ice_duration AS (
-                SELECT DISTINCT ON (station_id, date) station_id, date, duration_secs
+                SELECT DISTINCT ON (station_id, date) station_id, date, duration_secs, chain_count > 0 AS chained
                 FROM ice_duration
-                ORDER BY station_id, date, chain_count::FLOAT / row_count DESC, duration_secs ASC
+                ORDER BY station_id, date, chain_count > 0 DESC, chain_count::FLOAT / row_count DESC,
+                    CASE WHEN chain_count > 0 THEN duration_secs ELSE {unchained_order} END ASC
             ),
-            -- Sum each field using its own native duration.
-            -- Fallback: when best_*_duration has no match (single-row days filtered by HAVING > 1),
-            -- use the shortest available duration for that field.
+            -- Sum each field using its own native duration, picked per day by best_*_duration:
+            --   1. Durations whose windows chain end-to-start win, best chained share first, then
+            --      the shortest duration.
+            --   2. With no chain at all (single windows, or windows that overlap), summing every row
+            --      would double count, so only the longest window is used, the earliest on ties.
+            --      Buckets shorter than a day use their shortest window instead.
             daily_qpf AS (
                 SELECT pr.station_id, pr.date,
                     SUM(pr.liquid_precipitation_amt) FILTER (WHERE pr.liquid_precipitation_amt IS NOT NULL AND pr.liquid_precipitation_amt >= 0) AS total_qpf
                 FROM precip_rows pr
-                LEFT JOIN best_qpf_duration bqd ON pr.station_id = bqd.station_id AND pr.date = bqd.date
+                JOIN best_qpf_duration bqd ON pr.station_id = bqd.station_id AND pr.date = bqd.date
+                    AND pr.duration_secs = bqd.duration_secs
                 WHERE pr.liquid_precipitation_amt IS NOT NULL
-                  AND pr.duration_secs = COALESCE(bqd.duration_secs, (
-                      SELECT MIN(p2.duration_secs) FROM precip_rows p2
-                      WHERE p2.station_id = pr.station_id AND p2.date = pr.date AND p2.liquid_precipitation_amt IS NOT NULL
+                  AND (bqd.chained OR pr.begin_ts = (
+                      SELECT MIN(p2.begin_ts) FROM precip_rows p2
+                      WHERE p2.station_id = pr.station_id AND p2.date = pr.date
+                        AND p2.duration_secs = pr.duration_secs AND p2.liquid_precipitation_amt IS NOT NULL
                   ))
                 GROUP BY pr.station_id, pr.date
             ),
@@ -323,11 +1271,13 @@ impl WeatherData for WeatherAccess {
                     SUM(pr.snow_amt) FILTER (WHERE pr.snow_amt IS NOT NULL AND pr.snow_amt >= 0) AS snow_amt,
                     AVG(pr.snow_ratio) FILTER (WHERE pr.snow_ratio IS NOT NULL AND pr.snow_ratio > 0) AS avg_snow_ratio
                 FROM precip_rows pr
-                LEFT JOIN best_snow_duration bsd ON pr.station_id = bsd.station_id AND pr.date = bsd.date
+                JOIN best_snow_duration bsd ON pr.station_id = bsd.station_id AND pr.date = bsd.date
+                    AND pr.duration_secs = bsd.duration_secs
                 WHERE pr.snow_amt IS NOT NULL
-                  AND pr.duration_secs = COALESCE(bsd.duration_secs, (
-                      SELECT MIN(p2.duration_secs) FROM precip_rows p2
-                      WHERE p2.station_id = pr.station_id AND p2.date = pr.date AND p2.snow_amt IS NOT NULL
+                  AND (bsd.chained OR pr.begin_ts = (
+                      SELECT MIN(p2.begin_ts) FROM precip_rows p2
+                      WHERE p2.station_id = pr.station_id AND p2.date = pr.date
+                        AND p2.duration_secs = pr.duration_secs AND p2.snow_amt IS NOT NULL
                   ))
                 GROUP BY pr.station_id, pr.date
             ),
@@ -335,11 +1285,13 @@ impl WeatherData for WeatherAccess {
                 SELECT pr.station_id, pr.date,
                     SUM(pr.ice_amt) FILTER (WHERE pr.ice_amt IS NOT NULL AND pr.ice_amt >= 0) AS ice_amt
                 FROM precip_rows pr
-                LEFT JOIN best_ice_duration bid ON pr.station_id = bid.station_id AND pr.date = bid.date
+                JOIN best_ice_duration bid ON pr.station_id = bid.station_id AND pr.date = bid.date
+                    AND pr.duration_secs = bid.duration_secs
                 WHERE pr.ice_amt IS NOT NULL
-                  AND pr.duration_secs = COALESCE(bid.duration_secs, (
-                      SELECT MIN(p2.duration_secs) FROM precip_rows p2
-                      WHERE p2.station_id = pr.station_id AND p2.date = pr.date AND p2.ice_amt IS NOT NULL
+                  AND (bid.chained OR pr.begin_ts = (
+                      SELECT MIN(p2.begin_ts) FROM precip_rows p2
+                      WHERE p2.station_id = pr.station_id AND p2.date = pr.date
+                        AND p2.duration_secs = pr.duration_secs AND p2.ice_amt IS NOT NULL
                   ))
                 GROUP BY pr.station_id, pr.date
             ),
@@ -359,75 +1311,86 @@ impl WeatherData for WeatherAccess {
             daily_forecasts AS (
                 SELECT
                     station_id,
-                    DATE_TRUNC('day', begin_time::TIMESTAMPTZ AT TIME ZONE 'UTC')::TEXT AS date,
+                    {bucket} AS date,
                     MIN(begin_time) AS start_time,
                     MAX(end_time) AS end_time,
-                    MIN(min_temp) FILTER (WHERE min_temp IS NOT NULL AND min_temp >= -200 AND min_temp <= 200) AS temp_low,
-                    MAX(max_temp) FILTER (WHERE max_temp IS NOT NULL AND max_temp >= -200 AND max_temp <= 200) AS temp_high,
-                    MAX(wind_speed) FILTER (WHERE wind_speed IS NOT NULL AND wind_speed >= 0 AND wind_speed <= 500) AS wind_speed,
+                    {} AS temp_low,
+                    {} AS temp_high,
+                    {} AS wind_speed,
                     -- For wind direction, use mode (most common) or just take max as approximation
                     MAX(wind_direction) FILTER (WHERE wind_direction IS NOT NULL AND wind_direction >= 0 AND wind_direction <= 360) AS wind_direction,
                     MAX(relative_humidity_max) FILTER (WHERE relative_humidity_max IS NOT NULL AND relative_humidity_max >= 0 AND relative_humidity_max <= 100) AS humidity_max,
                     MIN(relative_humidity_min) FILTER (WHERE relative_humidity_min IS NOT NULL AND relative_humidity_min >= 0 AND relative_humidity_min <= 100) AS humidity_min,
                     MAX(temperature_unit_code) AS temperature_unit_code,
-                    MAX(twelve_hour_probability_of_precipitation) FILTER (WHERE twelve_hour_probability_of_precipitation IS NOT NULL) AS precip_chance
+                    MAX(twelve_hour_probability_of_precipitation) FILTER (WHERE twelve_hour_probability_of_precipitation IS NOT NULL) AS precip_chance,
+                    MAX(wind_speed_unit_code) AS wind_speed_unit_code,
+                    MAX(generated_at) AS generated_at
                 FROM deduped_forecasts
-                GROUP BY station_id, DATE_TRUNC('day', begin_time::TIMESTAMPTZ AT TIME ZONE 'UTC')::TEXT
+                GROUP BY station_id, {bucket}
             )
-            SELECT
-                df.station_id,
-                df.date,
-                {} AS start_time,
-                {} AS end_time,
-                MIN(df.temp_low) AS temp_low,
-                MAX(df.temp_high) AS temp_high,
-                MAX(df.wind_speed) AS wind_speed,
-                MAX(df.wind_direction) AS wind_direction,
-                MAX(df.humidity_max) AS humidity_max,
-                MIN(df.humidity_min) AS humidity_min,
-                MAX(df.temperature_unit_code) AS temperature_unit_code,
-                MAX(df.precip_chance) AS precip_chance,
-                -- Calculate rain: QPF - (snow / snow_ratio) - ice
-                -- If no snow_ratio, treat all QPF as rain (minus ice)
-                -- Never return negative values
-                GREATEST(0, COALESCE(
-                    dp.total_qpf - (dp.snow_amt / NULLIF(dp.avg_snow_ratio, 0)) - COALESCE(dp.ice_amt, 0),
-                    dp.total_qpf - COALESCE(dp.ice_amt, 0)
-                )) AS rain_amt,
-                dp.snow_amt AS snow_amt,
-                dp.ice_amt AS ice_amt
-            FROM daily_forecasts df
-            LEFT JOIN daily_precip dp ON df.station_id = dp.station_id AND df.date = dp.date
-            GROUP BY df.station_id, df.date, dp.total_qpf, dp.snow_amt, dp.avg_snow_ratio, dp.ice_amt
+            {}
             "#,
-            file_paths.join("', '"),
+            raw_files,
             station_filter,
             time_filter,
-            start_time_expr,
-            end_time_expr,
-        );
+            daily_aggregate(
+                &req.agg,
+                "MIN",
+                "min_temp",
+                &self
+                    .sanity_bounds
+                    .temperature_filter("min_temp", &TemperatureUnit::Fahrenheit)
+            ),
+            daily_aggregate(
+                &req.agg,
+                "MAX",
+                "max_temp",
+                &self
+                    .sanity_bounds
+                    .temperature_filter("max_temp", &TemperatureUnit::Fahrenheit)
+            ),
+            daily_aggregate(
+                &req.agg,
+                "MAX",
+                "wind_speed",
+                &self.sanity_bounds.wind_speed_filter("wind_speed")
+            ),
+            daily_forecast_select(&req.fields, &start_time_expr, &end_time_expr),
+        ))
+    }
 
-        // Execute raw SQL directly
+    fn run_forecasts_query(
+        &self,
+        query_sql: &str,
+        req: &ForecastRequest,
+    ) -> Result<(Vec<Forecast>, Option<OffsetDateTime>), Error> {
         let conn = self.open_connection()?;
-        let mut stmt = conn.prepare(&query_sql)?;
+        let mut stmt = conn.prepare(query_sql)?;
         let records: Vec<RecordBatch> = stmt.query_arrow([])?.collect();
 
         let forecasts: Forecasts = records
             .iter()
-            .map(|record| Forecasts::from_with_temp_unit(record, &req.temperature_unit))
+            .map(|record| {
+                Forecasts::from_with_units(
+                    record,
+                    &req.temperature_unit,
+                    req.wind_unit,
+                    req.precip_unit,
+                    &self.sanity_bounds,
+                )
+            })
             .fold(Forecasts::new(), |mut acc, forecast| {
                 acc.merge(forecast);
                 acc
             });
-
-        Ok(forecasts.values)
+        Ok((forecasts.values, newest_generated_at(&records)))
     }
 
-    async fn observation_data(
+    async fn observation_data_query(
         &self,
         req: &ObservationRequest,
         station_ids: Vec<String>,
-    ) -> Result<Vec<Observation>, Error> {
+    ) -> Result<(Vec<Observation>, usize, Option<OffsetDateTime>), Error> {
         // If start is provided, look back one day to ensure we capture relevant files
         // If start is None, keep it None to find all available data
         let mut file_params: FileParams = req.into();
@@ -438,11 +1401,11 @@ impl WeatherData for WeatherAccess {
         let file_paths = self.file_access.build_file_paths(parquet_files);
 
         if file_paths.is_empty() {
-            return Ok(vec![]);
+            return Ok((vec![], 0, None));
         }
 
         if file_paths.is_empty() {
-            return Ok(vec![]);
+            return Ok((vec![], 0, None));
         }
 
         // Build station filter clause
@@ -488,10 +1451,25 @@ impl WeatherData for WeatherAccess {
             "MAX(generated_at)".to_string()
         };
 
+        let (temp_low, temp_high) = req.temp_agg.sql("temperature_value");
+        // With a granularity each station gets a row per bucket instead of one for the window
+        let (bucket_column, bucket_group) = match req.granularity {
+            Some(granularity) => (
+                format!(
+                    ", {} AS bucket",
+                    Granularity::bucket_sql(&granularity.start_sql("generated_at", &req.tz))
+                ),
+                ", bucket ORDER BY station_id, bucket",
+            ),
+            None => (String::new(), ""),
+        };
+
         // Use raw SQL with UNION ALL BY NAME to handle schema differences
-        // Old parquet files may not have wind_direction, dewpoint_value, precip_in, or wx_string
+        // Old parquet files may not have wind_direction, dewpoint_value, precip_in, wx_string
+        // or wind_gust
         // Humidity is derived from temperature and dewpoint using the Magnus formula
-        // Precipitation is split into rain/snow/ice using wx_string (METAR weather codes):
+        // Precipitation is split into rain/snow/ice using wx_string (METAR weather codes),
+        // configurable through PrecipCodes:
         //   Snow: SN, BLSN, DRSN  |  Ice: FZRA, FZDZ, PL, GR, GS, IC  |  Rain: everything else
         // For old files without wx_string, temperature heuristic is used (<=2°C = snow)
         // precip_in is liquid equivalent; snow inches = precip_in * snow_ratio (default 10)
@@ -501,10 +1479,10 @@ impl WeatherData for WeatherAccess {
                 SELECT * FROM (
                     SELECT NULL::VARCHAR AS station_id, NULL::VARCHAR AS generated_at,
                            NULL::DOUBLE AS temperature_value, NULL::BIGINT AS wind_speed,
-                           NULL::BIGINT AS wind_direction,
+                           NULL::BIGINT AS wind_gust, NULL::BIGINT AS wind_direction,
                            NULL::DOUBLE AS dewpoint_value, NULL::DOUBLE AS precip_in,
                            NULL::VARCHAR AS temperature_unit_code,
-                           NULL::VARCHAR AS wx_string
+                           NULL::VARCHAR AS wind_speed_unit_code, NULL::VARCHAR AS wx_string
                     WHERE false
                     UNION ALL BY NAME
                     SELECT * FROM read_parquet(['{}'], union_by_name = true)
@@ -518,8 +1496,8 @@ impl WeatherData for WeatherAccess {
                         -- wx_string available: use METAR weather codes
                         WHEN wx_string IS NOT NULL AND wx_string != '' THEN
                             CASE
-                                WHEN regexp_matches(wx_string, '(^|\s)(SN|BLSN|DRSN)(\s|$)') THEN 'snow'
-                                WHEN regexp_matches(wx_string, '(^|\s)(FZRA|FZDZ|PL|GR|GS|IC)(\s|$)') THEN 'ice'
+                                WHEN regexp_matches(wx_string, '{snow_pattern}') THEN 'snow'
+                                WHEN regexp_matches(wx_string, '{ice_pattern}') THEN 'ice'
                                 ELSE 'rain'
                             END
                         -- No wx_string: fall back to temperature heuristic
@@ -532,16 +1510,15 @@ impl WeatherData for WeatherAccess {
                 station_id,
                 {} AS start_time,
                 {} AS end_time,
-                MIN(temperature_value) AS temp_low,
-                MAX(temperature_value) AS temp_high,
-                MAX(wind_speed) FILTER (WHERE wind_speed IS NOT NULL AND wind_speed >= 0 AND wind_speed <= 500) AS wind_speed,
+                {temp_low} FILTER (WHERE temperature_value IS NOT NULL AND {temperature_valid}) AS temp_low,
+                {temp_high} FILTER (WHERE temperature_value IS NOT NULL AND {temperature_valid}) AS temp_high,
+                MAX({wind}) FILTER (WHERE {wind} IS NOT NULL AND {wind_speed_valid}) AS wind_speed,
                 MAX(temperature_unit_code) AS temperature_unit_code,
                 MAX(wind_direction) FILTER (WHERE wind_direction IS NOT NULL AND wind_direction >= 0 AND wind_direction <= 360) AS wind_direction,
                 -- Derive humidity from temperature and dewpoint using Magnus formula
                 CASE
                     WHEN AVG(dewpoint_value) IS NOT NULL AND AVG(temperature_value) IS NOT NULL
-                    THEN ROUND(100.0 * EXP((17.625 * AVG(dewpoint_value)) / (243.04 + AVG(dewpoint_value)))
-                         / EXP((17.625 * AVG(temperature_value)) / (243.04 + AVG(temperature_value))))::BIGINT
+                    THEN ROUND({humidity})::BIGINT
                     ELSE NULL
                 END AS humidity,
                 -- Rain: sum precip_in where type is rain (already liquid inches)
@@ -549,15 +1526,28 @@ impl WeatherData for WeatherAccess {
                 -- Snow: precip_in * 10 (default snow ratio) to convert liquid equivalent to snow inches
                 SUM(precip_in * 10.0) FILTER (WHERE precip_in IS NOT NULL AND precip_in >= 0 AND precip_type = 'snow') AS snow_amt,
                 -- Ice: liquid equivalent inches (roughly 1:1)
-                SUM(precip_in) FILTER (WHERE precip_in IS NOT NULL AND precip_in >= 0 AND precip_type = 'ice') AS ice_amt
+                SUM(precip_in) FILTER (WHERE precip_in IS NOT NULL AND precip_in >= 0 AND precip_type = 'ice') AS ice_amt,
+                MAX(generated_at) AS generated_at,
+                MAX(wind_speed_unit_code) AS wind_speed_unit_code
+                {bucket_column}
             FROM classified
-            GROUP BY station_id
+            GROUP BY station_id{bucket_group}
             "#,
             file_paths.join("', '"),
             station_filter,
             time_filter,
             start_time_expr,
             end_time_expr,
+            humidity = self
+                .humidity_formula
+                .sql("AVG(temperature_value)", "AVG(dewpoint_value)"),
+            temperature_valid = self
+                .sanity_bounds
+                .temperature_filter("temperature_value", &TemperatureUnit::Celsius),
+            wind = req.wind_agg.sql(),
+            wind_speed_valid = self.sanity_bounds.wind_speed_filter(req.wind_agg.sql()),
+            snow_pattern = self.precip_codes.snow_pattern(),
+            ice_pattern = self.precip_codes.ice_pattern(),
         );
 
         let conn = self.open_connection()?;
@@ -565,30 +1555,53 @@ impl WeatherData for WeatherAccess {
         let records: Vec<RecordBatch> = stmt.query_arrow([])?.collect();
         let observations: Observations = records
             .iter()
-            .map(|record| Observations::from_with_temp_unit(record, &req.temperature_unit))
+            .map(|record| {
+                Observations::from_with_units(
+                    record,
+                    &req.temperature_unit,
+                    req.wind_unit,
+                    req.precip_unit,
+                )
+            })
             .fold(Observations::new(), |mut acc, obs| {
                 acc.merge(obs);
                 acc
             });
-        Ok(observations.values)
+        Ok((
+            observations.values,
+            file_paths.len(),
+            newest_generated_at(&records),
+        ))
     }
 
-    async fn daily_observations(
+    async fn daily_observations_query(
         &self,
         req: &ObservationRequest,
         station_ids: Vec<String>,
-    ) -> Result<Vec<DailyObservation>, Error> {
-        let mut file_params: FileParams = req.into();
-        if let Some(start_date) = req.start {
-            file_params.start = Some(start_date.saturating_sub(Duration::days(1)));
+    ) -> Result<(Vec<DailyObservation>, usize, Option<OffsetDateTime>), Error> {
+        if let Some(daily_paths) = self.materialized_observation_paths(req) {
+            let query_sql = materialized_sql(&daily_paths, &station_ids, "*");
+            let (observations, generated_at) =
+                self.run_daily_observations_query(&query_sql, req)?;
+            return Ok((observations, daily_paths.len(), generated_at));
         }
-        let parquet_files = self.file_access.grab_file_names(file_params).await?;
-        let file_paths = self.file_access.build_file_paths(parquet_files);
 
+        let file_paths = self.raw_file_paths(req.into(), req.start).await?;
         if file_paths.is_empty() {
-            return Ok(vec![]);
+            return Ok((vec![], 0, None));
         }
+        let query_sql = self.daily_observations_sql(req, &file_paths, &station_ids)?;
+        let (observations, generated_at) = self.run_daily_observations_query(&query_sql, req)?;
+        Ok((observations, file_paths.len(), generated_at))
+    }
 
+    /// Daily observation aggregation over the raw files, days are bucketed in `req.tz`
+    fn daily_observations_sql(
+        &self,
+        req: &ObservationRequest,
+        file_paths: &[String],
+        station_ids: &[String],
+    ) -> Result<String, Error> {
         // Build station filter clause
         let station_filter = if !station_ids.is_empty() {
             let quoted: Vec<String> = station_ids.iter().map(|s| format!("'{}'", s)).collect();
@@ -622,16 +1635,16 @@ impl WeatherData for WeatherAccess {
 
         // Use raw SQL with UNION ALL BY NAME to handle schema differences
         // Same precipitation classification as observation_data()
-        let query_sql = format!(
+        Ok(format!(
             r#"
             WITH parquet_data AS (
                 SELECT * FROM (
                     SELECT NULL::VARCHAR AS station_id, NULL::VARCHAR AS generated_at,
                            NULL::DOUBLE AS temperature_value, NULL::BIGINT AS wind_speed,
-                           NULL::BIGINT AS wind_direction,
+                           NULL::BIGINT AS wind_gust, NULL::BIGINT AS wind_direction,
                            NULL::DOUBLE AS dewpoint_value, NULL::DOUBLE AS precip_in,
                            NULL::VARCHAR AS temperature_unit_code,
-                           NULL::VARCHAR AS wx_string
+                           NULL::VARCHAR AS wind_speed_unit_code, NULL::VARCHAR AS wx_string
                     WHERE false
                     UNION ALL BY NAME
                     SELECT * FROM read_parquet(['{}'], union_by_name = true)
@@ -643,8 +1656,8 @@ impl WeatherData for WeatherAccess {
                     CASE
                         WHEN wx_string IS NOT NULL AND wx_string != '' THEN
                             CASE
-                                WHEN regexp_matches(wx_string, '(^|\s)(SN|BLSN|DRSN)(\s|$)') THEN 'snow'
-                                WHEN regexp_matches(wx_string, '(^|\s)(FZRA|FZDZ|PL|GR|GS|IC)(\s|$)') THEN 'ice'
+                                WHEN regexp_matches(wx_string, '{snow_pattern}') THEN 'snow'
+                                WHEN regexp_matches(wx_string, '{ice_pattern}') THEN 'ice'
                                 ELSE 'rain'
                             END
                         WHEN temperature_value IS NOT NULL AND temperature_value <= 2.0 THEN 'snow'
@@ -654,40 +1667,359 @@ impl WeatherData for WeatherAccess {
             )
             SELECT
                 station_id,
-                DATE_TRUNC('day', generated_at::TIMESTAMP)::TEXT AS date,
-                MIN(temperature_value) FILTER (WHERE temperature_value IS NOT NULL) AS temp_low,
-                MAX(temperature_value) FILTER (WHERE temperature_value IS NOT NULL) AS temp_high,
-                MAX(wind_speed) FILTER (WHERE wind_speed IS NOT NULL AND wind_speed >= 0 AND wind_speed <= 500) AS wind_speed,
+                {day} AS date,
+                MIN(temperature_value) FILTER (WHERE temperature_value IS NOT NULL AND {temperature_valid}) AS temp_low,
+                MAX(temperature_value) FILTER (WHERE temperature_value IS NOT NULL AND {temperature_valid}) AS temp_high,
+                MAX({wind}) FILTER (WHERE {wind} IS NOT NULL AND {wind_speed_valid}) AS wind_speed,
                 MAX(temperature_unit_code) AS temperature_unit_code,
                 MAX(wind_direction) FILTER (WHERE wind_direction IS NOT NULL AND wind_direction >= 0 AND wind_direction <= 360) AS wind_direction,
                 CASE
                     WHEN AVG(dewpoint_value) IS NOT NULL AND AVG(temperature_value) IS NOT NULL
-                    THEN ROUND(100.0 * EXP((17.625 * AVG(dewpoint_value)) / (243.04 + AVG(dewpoint_value)))
-                         / EXP((17.625 * AVG(temperature_value)) / (243.04 + AVG(temperature_value))))::BIGINT
+                    THEN ROUND({humidity})::BIGINT
                     ELSE NULL
                 END AS humidity,
                 SUM(precip_in) FILTER (WHERE precip_in IS NOT NULL AND precip_in >= 0 AND precip_type = 'rain') AS rain_amt,
                 SUM(precip_in * 10.0) FILTER (WHERE precip_in IS NOT NULL AND precip_in >= 0 AND precip_type = 'snow') AS snow_amt,
-                SUM(precip_in) FILTER (WHERE precip_in IS NOT NULL AND precip_in >= 0 AND precip_type = 'ice') AS ice_amt
+                SUM(precip_in) FILTER (WHERE precip_in IS NOT NULL AND precip_in >= 0 AND precip_type = 'ice') AS ice_amt,
+                MAX(generated_at) AS generated_at,
+                MAX(wind_speed_unit_code) AS wind_speed_unit_code
             FROM classified
-            GROUP BY station_id, DATE_TRUNC('day', generated_at::TIMESTAMP)::TEXT
+            GROUP BY station_id, {day}
             "#,
             file_paths.join("', '"),
             station_filter,
             time_filter,
-        );
+            day = req.tz.day_sql("generated_at"),
+            humidity = self
+                .humidity_formula
+                .sql("AVG(temperature_value)", "AVG(dewpoint_value)"),
+            temperature_valid = self
+                .sanity_bounds
+                .temperature_filter("temperature_value", &TemperatureUnit::Celsius),
+            wind = req.wind_agg.sql(),
+            wind_speed_valid = self.sanity_bounds.wind_speed_filter(req.wind_agg.sql()),
+            snow_pattern = self.precip_codes.snow_pattern(),
+            ice_pattern = self.precip_codes.ice_pattern(),
+        ))
+    }
 
+    fn run_daily_observations_query(
+        &self,
+        query_sql: &str,
+        req: &ObservationRequest,
+    ) -> Result<(Vec<DailyObservation>, Option<OffsetDateTime>), Error> {
         let conn = self.open_connection()?;
-        let mut stmt = conn.prepare(&query_sql)?;
+        let mut stmt = conn.prepare(query_sql)?;
         let records: Vec<RecordBatch> = stmt.query_arrow([])?.collect();
         let observations: DailyObservations = records
             .iter()
-            .map(|record| DailyObservations::from_with_temp_unit(record, &req.temperature_unit))
+            .map(|record| {
+                DailyObservations::from_with_units(
+                    record,
+                    &req.temperature_unit,
+                    req.wind_unit,
+                    req.precip_unit,
+                )
+            })
             .fold(DailyObservations::new(), |mut acc, obs| {
                 acc.merge(obs);
                 acc
             });
-        Ok(observations.values)
+        Ok((observations.values, newest_generated_at(&records)))
+    }
+}
+
+impl WeatherAccess {
+    /// Runs the daily aggregations once for each UTC day from `start` to `end` inclusive and
+    /// writes them to `daily/daily_<type>_<date>.parquet`. Each day holds what a one-day
+    /// query returns for it, with start/end times unclipped. Days without raw files of a type
+    /// are skipped and keep being computed on the fly.
+    pub async fn materialize_daily(
+        &self,
+        start: Date,
+        end: Date,
+    ) -> Result<MaterializeReport, Error> {
+        let today = OffsetDateTime::now_utc().date();
+        if end < start || end >= today {
+            return Err(Error::Materialize(format!(
+                "days {} to {} must be in order and finished before today",
+                start, end
+            )));
+        }
+        std::fs::create_dir_all(&self.daily_dir)
+            .map_err(|e| Error::Materialize(format!("creating {}: {}", self.daily_dir, e)))?;
+
+        let mut report = MaterializeReport::default();
+        let mut day = start;
+        while day <= end {
+            let day_start = day.midnight().assume_utc();
+            let day_end = day_start + Duration::days(1);
+
+            let forecasts = ForecastRequest {
+                start: Some(day_start),
+                end: Some(day_end),
+                generated_start: None,
+                generated_end: None,
+                as_of: None,
+                station_ids: String::new(),
+                temperature_unit: TemperatureUnit::default(),
+                wind_unit: None,
+                precip_unit: PrecipUnit::default(),
+                agg: ForecastAggregation::MinMax,
+                fields: ForecastFields::default(),
+                granularity: None,
+            };
+            let file_paths = self
+                .raw_file_paths((&forecasts).into(), forecasts.start)
+                .await?;
+            if !file_paths.is_empty() {
+                let query_sql = self.daily_forecasts_sql(&forecasts, &file_paths, &[], false)?;
+                self.write_materialized("forecasts", day, &query_sql)?;
+                report.forecast_days += 1;
+            }
+
+            let observations = ObservationRequest {
+                start: Some(day_start),
+                end: Some(day_end),
+                station_ids: String::new(),
+                temperature_unit: TemperatureUnit::default(),
+                tz: DayTimeZone::Utc,
+                temp_agg: ObservationTempAggregation::default(),
+                wind_agg: ObservationWindAggregation::default(),
+                wind_unit: None,
+                precip_unit: PrecipUnit::default(),
+                granularity: None,
+            };
+            let file_paths = self
+                .raw_file_paths((&observations).into(), observations.start)
+                .await?;
+            if !file_paths.is_empty() {
+                let query_sql = self.daily_observations_sql(&observations, &file_paths, &[])?;
+                self.write_materialized("observations", day, &query_sql)?;
+                report.observation_days += 1;
+            }
+
+            let Some(next_day) = day.next_day() else {
+                break;
+            };
+            day = next_day;
+        }
+        Ok(report)
+    }
+
+    fn materialized_path(&self, file_type: &str, day: Date) -> String {
+        format!("{}/daily_{}_{}.parquet", self.daily_dir, file_type, day)
+    }
+
+    /// Keeps only `day`'s rows, a one-day query also returns the neighbouring days that
+    /// forecast windows or end-of-range readings spill into. Written to a temp file first so
+    /// queries never read a half-written day.
+    fn write_materialized(&self, file_type: &str, day: Date, query_sql: &str) -> Result<(), Error> {
+        let path = self.materialized_path(file_type, day);
+        let tmp_path = format!("{}.tmp", path);
+        let conn = self.open_connection()?;
+        conn.execute_batch(&format!(
+            "COPY (SELECT * FROM ({}) WHERE date::TIMESTAMP::DATE = DATE '{}') TO '{}' (FORMAT PARQUET);",
+            query_sql, day, tmp_path
+        ))?;
+        std::fs::rename(&tmp_path, &path)
+            .map_err(|e| Error::Materialize(format!("writing {}: {}", path, e)))
+    }
+
+    /// Materialized files for every requested day, None when the request isn't whole UTC
+    /// days or any of them hasn't been materialized
+    fn materialized_paths(
+        &self,
+        file_type: &str,
+        start: Option<OffsetDateTime>,
+        end: Option<OffsetDateTime>,
+    ) -> Option<Vec<String>> {
+        let (start, end) = (
+            start?.to_offset(UtcOffset::UTC),
+            end?.to_offset(UtcOffset::UTC),
+        );
+        if start.time() != Time::MIDNIGHT || end.time() != Time::MIDNIGHT || end <= start {
+            return None;
+        }
+        let mut paths = vec![];
+        let mut day = start.date();
+        while day < end.date() {
+            let path = self.materialized_path(file_type, day);
+            if !Path::new(&path).exists() {
+                return None;
+            }
+            paths.push(path);
+            day = day.next_day()?;
+        }
+        Some(paths)
+    }
+
+    /// Materialized days hold the default min/max aggregation over the latest forecasts
+    fn materialized_forecast_paths(&self, req: &ForecastRequest) -> Option<Vec<String>> {
+        if req.agg != ForecastAggregation::MinMax
+            || req.generated_start.is_some()
+            || req.generated_end.is_some()
+            || req.as_of.is_some()
+            || req.granularity.unwrap_or_default() != Granularity::Daily
+        {
+            return None;
+        }
+        self.materialized_paths("forecasts", req.start, req.end)
+    }
+
+    /// Materialized days are bucketed in UTC and hold the sustained wind
+    fn materialized_observation_paths(&self, req: &ObservationRequest) -> Option<Vec<String>> {
+        if req.tz != DayTimeZone::Utc || req.wind_agg != ObservationWindAggregation::Sustained {
+            return None;
+        }
+        self.materialized_paths("observations", req.start, req.end)
+    }
+}
+
+/// Reads materialized days back in the column order the on-the-fly queries return
+fn materialized_sql(daily_paths: &[String], station_ids: &[String], select: &str) -> String {
+    let station_filter = if !station_ids.is_empty() {
+        let quoted: Vec<String> = station_ids.iter().map(|s| format!("'{}'", s)).collect();
+        format!("WHERE station_id IN ({})", quoted.join(", "))
+    } else {
+        String::new()
+    };
+    format!(
+        "SELECT {} FROM read_parquet(['{}']) {}",
+        select,
+        daily_paths.join("', '"),
+        station_filter
+    )
+}
+
+/// Converts a forecast query's `temp_low`/`temp_high` to `unit` the way
+/// `Forecast::convert_temperature` does, for results that don't pass through `Forecast`
+fn forecast_temperatures_sql(query_sql: &str, unit: &TemperatureUnit) -> String {
+    let from_units = match unit {
+        TemperatureUnit::Fahrenheit => "'celsius', 'celcius'",
+        TemperatureUnit::Celsius => "'fahrenheit'",
+    };
+    let column = |name: &str| {
+        let converted = match unit {
+            TemperatureUnit::Fahrenheit => format!("ROUND({} * 9.0 / 5.0 + 32)", name),
+            TemperatureUnit::Celsius => format!("ROUND(({} - 32) * 5.0 / 9.0)", name),
+        };
+        format!(
+            "CASE WHEN lower(temperature_unit_code) IN ({}) THEN {}::BIGINT ELSE {} END AS {}",
+            from_units, converted, name, name
+        )
+    };
+    format!(
+        "SELECT * REPLACE ({}, {}, CASE WHEN lower(temperature_unit_code) IN ({}) THEN '{}' \
+         ELSE temperature_unit_code END AS temperature_unit_code) FROM ({})",
+        column("temp_low"),
+        column("temp_high"),
+        from_units,
+        unit,
+        query_sql
+    )
+}
+
+/// Moves a forecast query's bucket start from `date` to an RFC3339 `bucket` column when a
+/// granularity was requested, leaving `date` as the UTC day the bucket falls in
+fn bucketed_forecasts_sql(req: &ForecastRequest, query_sql: String) -> String {
+    if req.granularity.is_none() {
+        return query_sql;
+    }
+    format!(
+        "SELECT * REPLACE (DATE_TRUNC('day', \"date\"::TIMESTAMP)::TEXT AS \"date\"), {} AS bucket \
+         FROM ({}) ORDER BY station_id, bucket",
+        Granularity::bucket_sql("\"date\"::TIMESTAMP"),
+        query_sql
+    )
+}
+
+/// Materialized forecast days with their start/end times clipped to the request, the same
+/// as the on-the-fly query does
+fn materialized_forecasts_sql(
+    req: &ForecastRequest,
+    daily_paths: &[String],
+    station_ids: &[String],
+) -> Result<String, Error> {
+    let select = match (req.start, req.end) {
+        (Some(start), Some(end)) => format!(
+            "* REPLACE (GREATEST('{}', start_time) AS start_time, LEAST('{}', end_time) AS end_time)",
+            start.format(&Rfc3339)?,
+            end.format(&Rfc3339)?
+        ),
+        _ => String::from("*"),
+    };
+    Ok(materialized_sql(daily_paths, station_ids, &select))
+}
+
+#[async_trait]
+impl WeatherData for WeatherAccess {
+    async fn forecasts_data(
+        &self,
+        req: &ForecastRequest,
+        station_ids: Vec<String>,
+    ) -> Result<Vec<Forecast>, Error> {
+        Ok(self.forecasts_data_with_stats(req, station_ids).await?.0)
+    }
+
+    async fn observation_data(
+        &self,
+        req: &ObservationRequest,
+        station_ids: Vec<String>,
+    ) -> Result<Vec<Observation>, Error> {
+        Ok(self.observation_data_with_stats(req, station_ids).await?.0)
+    }
+
+    async fn daily_observations(
+        &self,
+        req: &ObservationRequest,
+        station_ids: Vec<String>,
+    ) -> Result<Vec<DailyObservation>, Error> {
+        Ok(self
+            .daily_observations_with_stats(req, station_ids)
+            .await?
+            .0)
+    }
+
+    async fn forecasts_data_with_stats(
+        &self,
+        req: &ForecastRequest,
+        station_ids: Vec<String>,
+    ) -> Result<(Vec<Forecast>, QueryStats), Error> {
+        let started = Instant::now();
+        let (forecasts, files, generated_at) = self.forecasts_data_query(req, station_ids).await?;
+        let stats =
+            QueryStats::new(Some(files), forecasts.len(), started).with_generated_at(generated_at);
+        log_query_stats("forecasts_data", &stats);
+        Ok((forecasts, stats))
+    }
+
+    async fn observation_data_with_stats(
+        &self,
+        req: &ObservationRequest,
+        station_ids: Vec<String>,
+    ) -> Result<(Vec<Observation>, QueryStats), Error> {
+        let started = Instant::now();
+        let (observations, files, generated_at) =
+            self.observation_data_query(req, station_ids).await?;
+        let stats = QueryStats::new(Some(files), observations.len(), started)
+            .with_generated_at(generated_at);
+        log_query_stats("observation_data", &stats);
+        Ok((observations, stats))
+    }
+
+    async fn daily_observations_with_stats(
+        &self,
+        req: &ObservationRequest,
+        station_ids: Vec<String>,
+    ) -> Result<(Vec<DailyObservation>, QueryStats), Error> {
+        let started = Instant::now();
+        let (observations, files, generated_at) =
+            self.daily_observations_query(req, station_ids).await?;
+        let stats = QueryStats::new(Some(files), observations.len(), started)
+            .with_generated_at(generated_at);
+        log_query_stats("daily_observations", &stats);
+        Ok((observations, stats))
     }
 
     async fn stations(&self) -> Result<Vec<Station>, Error> {
@@ -748,6 +2080,190 @@ impl WeatherData for WeatherAccess {
 
         Ok(stations.values)
     }
+
+    async fn stations_with_data(
+        &self,
+        date: Date,
+        file_type: FileType,
+    ) -> Result<Vec<String>, Error> {
+        let day_start = date.midnight().assume_utc();
+        let parquet_files = self
+            .file_access
+            .grab_file_names(FileParams {
+                start: Some(day_start),
+                end: Some(day_start + Duration::DAY - Duration::NANOSECOND),
+                observations: Some(file_type == FileType::Observations),
+                forecasts: Some(file_type == FileType::Forecasts),
+            })
+            .await?;
+        let file_paths = self.file_access.build_file_paths(parquet_files);
+        if file_paths.is_empty() {
+            return Ok(vec![]);
+        }
+        // Only the station_id column is read from each file
+        let query_sql = format!(
+            "SELECT DISTINCT station_id FROM read_parquet(['{}'], union_by_name = true)
+             WHERE station_id IS NOT NULL ORDER BY station_id",
+            file_paths.join("', '")
+        );
+        let conn = self.open_connection()?;
+        let mut stmt = conn.prepare(&query_sql)?;
+        let station_ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, duckdb::Error>>()?;
+        Ok(station_ids)
+    }
+
+    async fn custom_query(
+        &self,
+        req: &CustomQueryRequest,
+        limits: CustomQueryLimits,
+    ) -> Result<CustomQueryRows, Error> {
+        let sql = check_custom_query(&req.sql)?.to_string();
+        let parquet_files = self
+            .file_access
+            .grab_file_names(FileParams {
+                start: req.start,
+                end: req.end,
+                observations: req.observations,
+                forecasts: req.forecasts,
+            })
+            .await?;
+        let (observation_files, forecast_files): (Vec<String>, Vec<String>) = parquet_files
+            .into_iter()
+            .partition(|file_name| file_name.starts_with("observations"));
+        let conn = self.open_connection()?;
+        Ok(run_custom_query(
+            conn,
+            sql,
+            self.file_access.build_file_paths(observation_files),
+            self.file_access.build_file_paths(forecast_files),
+            limits,
+        ))
+    }
+
+    async fn forecasts_parquet(
+        &self,
+        req: &ForecastRequest,
+        station_ids: Vec<String>,
+    ) -> Result<(Body, QueryStats), Error> {
+        let started = Instant::now();
+        let (query_sql, files) = self.forecasts_sql(req, &station_ids).await?;
+        let query_sql = forecast_temperatures_sql(&query_sql, &req.temperature_unit);
+        let path = std::env::temp_dir().join(format!("forecasts_{}.parquet", Uuid::now_v7()));
+        let path = path.to_string_lossy().replace('\'', "''");
+
+        let conn = self.open_connection()?;
+        conn.execute_batch(&format!(
+            "COPY ({} ORDER BY station_id, date) TO '{}' (FORMAT PARQUET);",
+            query_sql, path
+        ))?;
+        let (rows, generated_at): (i64, Option<String>) = conn.query_row(
+            &format!(
+                "SELECT COUNT(*), MAX(generated_at) FROM read_parquet('{}')",
+                path
+            ),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        // Unlinked once it's open, the file is gone as soon as the response has streamed it
+        let file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| Error::Export(format!("opening {}: {}", path, e)))?;
+        if let Err(e) = tokio::fs::remove_file(&path).await {
+            warn!("failed to remove forecast export {}: {}", path, e);
+        }
+
+        let generated_at = generated_at.and_then(|at| OffsetDateTime::parse(&at, &Rfc3339).ok());
+        let stats =
+            QueryStats::new(Some(files), rows as usize, started).with_generated_at(generated_at);
+        log_query_stats("forecasts_parquet", &stats);
+        Ok((Body::from_stream(ReaderStream::new(file)), stats))
+    }
+
+    async fn observation_coverage(
+        &self,
+        req: &CoverageRequest,
+    ) -> Result<Vec<ObservationCoverage>, Error> {
+        // Look back a day like the observation queries, a station that went quiet before the
+        // range still shows up with no coverage. A reading lands in the file written after it,
+        // so the range's last hour is only complete with the hour after it.
+        let parquet_files = self
+            .file_access
+            .grab_file_names(FileParams {
+                start: Some(req.start.saturating_sub(Duration::days(1))),
+                end: Some(req.end.saturating_add(Duration::HOUR)),
+                observations: Some(true),
+                forecasts: Some(false),
+            })
+            .await?;
+        let file_paths = self.file_access.build_file_paths(parquet_files);
+        if file_paths.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // The same observation is repeated across files until the station reports again, so
+        // observations and hourly slots are counted by distinct time
+        let query_sql = format!(
+            r#"
+            WITH parquet_data AS (
+                SELECT station_id, generated_at::TIMESTAMPTZ AS observed_at
+                FROM read_parquet(['{}'], union_by_name = true)
+                WHERE station_id IS NOT NULL
+            ),
+            in_range AS (
+                SELECT * FROM parquet_data
+                WHERE observed_at >= '{}'::TIMESTAMPTZ AND observed_at < '{}'::TIMESTAMPTZ
+            )
+            SELECT
+                stations.station_id,
+                COUNT(DISTINCT in_range.observed_at) AS observations,
+                CAST(epoch(MIN(in_range.observed_at)) * 1000 AS BIGINT) AS first_observed_ms,
+                CAST(epoch(MAX(in_range.observed_at)) * 1000 AS BIGINT) AS last_observed_ms,
+                COUNT(DISTINCT floor(epoch(in_range.observed_at) / 3600)) / {}::DOUBLE AS coverage
+            FROM (SELECT DISTINCT station_id FROM parquet_data) AS stations
+            LEFT JOIN in_range ON in_range.station_id = stations.station_id
+            GROUP BY stations.station_id
+            ORDER BY coverage, stations.station_id
+            "#,
+            file_paths.join("', '"),
+            req.start.format(&Rfc3339)?,
+            req.end.format(&Rfc3339)?,
+            req.hourly_slots(),
+        );
+        let conn = self.open_connection()?;
+        let mut stmt = conn.prepare(&query_sql)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, f64>(4)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, duckdb::Error>>()?;
+
+        let from_ms = |ms: Option<i64>| {
+            ms.and_then(|ms| {
+                OffsetDateTime::from_unix_timestamp_nanos(i128::from(ms) * 1_000_000).ok()
+            })
+        };
+        Ok(rows
+            .into_iter()
+            .map(
+                |(station_id, observations, first_ms, last_ms, coverage)| ObservationCoverage {
+                    station_id,
+                    observations,
+                    first_observed: from_ms(first_ms),
+                    last_observed: from_ms(last_ms),
+                    coverage,
+                },
+            )
+            .collect())
+    }
 }
 
 struct Forecasts {
@@ -764,8 +2280,16 @@ impl Forecasts {
         self
     }
 
-    fn from_with_temp_unit(record_batch: &RecordBatch, target_unit: &TemperatureUnit) -> Self {
+    fn from_with_units(
+        record_batch: &RecordBatch,
+        target_unit: &TemperatureUnit,
+        wind_unit: Option<WindSpeedUnit>,
+        precip_unit: PrecipUnit,
+        bounds: &SanityBounds,
+    ) -> Self {
         let mut forecasts = Vec::new();
+        let wind_speed_unit_codes = wind_speed_unit_codes(record_batch);
+        let buckets = bucket_starts(record_batch);
         let station_id_arr = record_batch
             .column(0)
             .as_any()
@@ -863,8 +2387,8 @@ impl Forecasts {
                 None
             } else {
                 let wind_speed_val = wind_speed_arr.value(row_index);
-                // Filter out unreasonable values (negative or > 500 mph)
-                if (0..=500).contains(&wind_speed_val) {
+                // Filter out unreasonable values
+                if bounds.wind_speed_in_bounds(wind_speed_val) {
                     Some(wind_speed_val)
                 } else {
                     None
@@ -970,11 +2494,21 @@ impl Forecasts {
                 humidity_min,
                 temp_unit_code,
                 precip_chance,
+                dominant_precip: Some(dominant_precip(rain_amt, snow_amt, ice_amt).to_string()),
                 rain_amt,
                 snow_amt,
                 ice_amt,
+                precip_unit_code: PrecipUnit::Inches.to_string(),
+                bucket: bucket_start(buckets, row_index),
             };
             forecast.convert_temperature(target_unit);
+            if let (Some(to), Some(from)) = (
+                wind_unit,
+                stored_wind_unit(wind_speed_unit_codes, row_index),
+            ) {
+                forecast.convert_wind_speed(from, to);
+            }
+            forecast.convert_precip(precip_unit);
             forecasts.push(forecast);
         }
 
@@ -999,15 +2533,57 @@ pub struct Forecast {
     pub humidity_min: Option<i64>,
     pub temp_unit_code: String,
     pub precip_chance: Option<i64>,
-    /// Liquid precipitation (rain) amount in inches
+    /// Liquid precipitation (rain) amount, in inches unless `precip_unit_code` says otherwise
     pub rain_amt: Option<f64>,
-    /// Snow amount in inches
+    /// Snow amount, in `precip_unit_code`
     pub snow_amt: Option<f64>,
-    /// Ice accumulation in inches
+    /// Ice accumulation, in `precip_unit_code`
     pub ice_amt: Option<f64>,
+    /// Unit of the precipitation amounts, `in` unless another `precip_unit` was requested
+    #[serde(default)]
+    pub precip_unit_code: String,
+    /// Which of rain/snow/ice has the largest amount, "none" when all are zero or missing
+    #[serde(default)]
+    pub dominant_precip: Option<String>,
+    /// Start of the `granularity` bucket the row covers, RFC3339 UTC. Only set when a
+    /// granularity was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
+}
+
+/// Largest of the three forecast amounts wins, ties go to the more hazardous type
+/// (ice, then snow, then rain)
+pub fn dominant_precip(
+    rain_amt: Option<f64>,
+    snow_amt: Option<f64>,
+    ice_amt: Option<f64>,
+) -> &'static str {
+    [("ice", ice_amt), ("snow", snow_amt), ("rain", rain_amt)]
+        .into_iter()
+        .filter_map(|(kind, amount)| amount.filter(|amount| *amount > 0.0).map(|a| (kind, a)))
+        .fold(
+            None,
+            |dominant: Option<(&str, f64)>, (kind, amount)| match dominant {
+                Some((_, largest)) if largest >= amount => dominant,
+                _ => Some((kind, amount)),
+            },
+        )
+        .map_or("none", |(kind, _)| kind)
 }
 
 impl Forecast {
+    pub fn convert_precip(&mut self, target_unit: PrecipUnit) {
+        convert_precip_amounts(
+            [&mut self.rain_amt, &mut self.snow_amt, &mut self.ice_amt],
+            &mut self.precip_unit_code,
+            target_unit,
+        );
+    }
+
+    pub fn convert_wind_speed(&mut self, from: WindSpeedUnit, to: WindSpeedUnit) {
+        self.wind_speed = self.wind_speed.map(|speed| to.convert(speed, from));
+    }
+
     pub fn convert_temperature(&mut self, target_unit: &TemperatureUnit) {
         // Normalize the current unit code to handle the "celcius" spelling in data
         // The spelling error comes from NOAA data directly
@@ -1051,8 +2627,15 @@ impl Observations {
         self
     }
 
-    pub fn from_with_temp_unit(record_batch: &RecordBatch, target_unit: &TemperatureUnit) -> Self {
+    pub fn from_with_units(
+        record_batch: &RecordBatch,
+        target_unit: &TemperatureUnit,
+        wind_unit: Option<WindSpeedUnit>,
+        precip_unit: PrecipUnit,
+    ) -> Self {
         let mut observations = Vec::new();
+        let wind_speed_unit_codes = wind_speed_unit_codes(record_batch);
+        let buckets = bucket_starts(record_batch);
         // Column order matches the SELECT in observation_data():
         // 0: station_id, 1: start_time, 2: end_time, 3: temp_low, 4: temp_high,
         // 5: wind_speed, 6: temperature_unit_code, 7: wind_direction, 8: humidity,
@@ -1199,8 +2782,17 @@ impl Observations {
                 rain_amt,
                 snow_amt,
                 ice_amt,
+                precip_unit_code: PrecipUnit::Inches.to_string(),
+                bucket: bucket_start(buckets, row_index),
             };
             observation.convert_temperature(target_unit);
+            if let (Some(to), Some(from)) = (
+                wind_unit,
+                stored_wind_unit(wind_speed_unit_codes, row_index),
+            ) {
+                observation.wind_speed = to.convert(observation.wind_speed, from);
+            }
+            observation.convert_precip(precip_unit);
             observations.push(observation);
         }
 
@@ -1223,15 +2815,30 @@ pub struct Observation {
     pub wind_direction: Option<i64>,
     /// Relative humidity (percent)
     pub humidity: Option<i64>,
-    /// Liquid precipitation (rain) amount in inches
+    /// Liquid precipitation (rain) amount, in inches unless `precip_unit_code` says otherwise
     pub rain_amt: Option<f64>,
-    /// Snow amount in inches
+    /// Snow amount, in `precip_unit_code`
     pub snow_amt: Option<f64>,
-    /// Ice accumulation in inches
+    /// Ice accumulation, in `precip_unit_code`
     pub ice_amt: Option<f64>,
+    /// Unit of the precipitation amounts, `in` unless another `precip_unit` was requested
+    #[serde(default)]
+    pub precip_unit_code: String,
+    /// Start of the `granularity` bucket the row covers, RFC3339 UTC. Only set when a
+    /// granularity was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
 }
 
 impl Observation {
+    pub fn convert_precip(&mut self, target_unit: PrecipUnit) {
+        convert_precip_amounts(
+            [&mut self.rain_amt, &mut self.snow_amt, &mut self.ice_amt],
+            &mut self.precip_unit_code,
+            target_unit,
+        );
+    }
+
     pub fn convert_temperature(&mut self, target_unit: &TemperatureUnit) {
         // Normalize the current unit code to handle the "celcius" spelling in data
         // The spelling error comes from NOAA data directly
@@ -1274,15 +2881,26 @@ pub struct DailyObservation {
     pub wind_direction: Option<i64>,
     /// Relative humidity (percent)
     pub humidity: Option<i64>,
-    /// Liquid precipitation (rain) amount in inches
+    /// Liquid precipitation (rain) amount, in inches unless `precip_unit_code` says otherwise
     pub rain_amt: Option<f64>,
-    /// Snow amount in inches
+    /// Snow amount, in `precip_unit_code`
     pub snow_amt: Option<f64>,
-    /// Ice accumulation in inches
+    /// Ice accumulation, in `precip_unit_code`
     pub ice_amt: Option<f64>,
+    /// Unit of the precipitation amounts, `in` unless another `precip_unit` was requested
+    #[serde(default)]
+    pub precip_unit_code: String,
 }
 
 impl DailyObservation {
+    pub fn convert_precip(&mut self, target_unit: PrecipUnit) {
+        convert_precip_amounts(
+            [&mut self.rain_amt, &mut self.snow_amt, &mut self.ice_amt],
+            &mut self.precip_unit_code,
+            target_unit,
+        );
+    }
+
     pub fn convert_temperature(&mut self, target_unit: &TemperatureUnit) {
         let current_unit = match self.temp_unit_code.to_lowercase().as_str() {
             "celcius" => "celsius".to_string(),
@@ -1323,8 +2941,14 @@ impl DailyObservations {
         self
     }
 
-    pub fn from_with_temp_unit(record_batch: &RecordBatch, target_unit: &TemperatureUnit) -> Self {
+    pub fn from_with_units(
+        record_batch: &RecordBatch,
+        target_unit: &TemperatureUnit,
+        wind_unit: Option<WindSpeedUnit>,
+        precip_unit: PrecipUnit,
+    ) -> Self {
         let mut observations = Vec::new();
+        let wind_speed_unit_codes = wind_speed_unit_codes(record_batch);
         // Column order matches the SELECT in daily_observations():
         // 0: station_id, 1: date, 2: temp_low, 3: temp_high, 4: wind_speed,
         // 5: temperature_unit_code, 6: wind_direction, 7: humidity,
@@ -1464,8 +3088,16 @@ impl DailyObservations {
                 rain_amt,
                 snow_amt,
                 ice_amt,
+                precip_unit_code: PrecipUnit::Inches.to_string(),
             };
             observation.convert_temperature(target_unit);
+            if let (Some(to), Some(from)) = (
+                wind_unit,
+                stored_wind_unit(wind_speed_unit_codes, row_index),
+            ) {
+                observation.wind_speed = to.convert(observation.wind_speed, from);
+            }
+            observation.convert_precip(precip_unit);
             observations.push(observation);
         }
 
@@ -1475,6 +3107,114 @@ impl DailyObservations {
     }
 }
 
+/// One station/date with the forecast, what was observed and the signed error
+/// (observed - forecast), in whichever unit the comparison was requested in
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct DailyComparison {
+    pub station_id: String,
+    pub date: String,
+    pub temp_unit_code: String,
+    pub forecast_temp_low: i64,
+    pub observed_temp_low: f64,
+    pub temp_low_error: f64,
+    pub forecast_temp_high: i64,
+    pub observed_temp_high: f64,
+    pub temp_high_error: f64,
+    pub forecast_wind_speed: Option<i64>,
+    pub observed_wind_speed: Option<i64>,
+    pub wind_speed_error: Option<i64>,
+    /// Liquid precipitation (rain) amounts in inches
+    pub forecast_rain_amt: Option<f64>,
+    pub observed_rain_amt: Option<f64>,
+    pub rain_amt_error: Option<f64>,
+    /// Snow amounts in inches
+    pub forecast_snow_amt: Option<f64>,
+    pub observed_snow_amt: Option<f64>,
+    pub snow_amt_error: Option<f64>,
+}
+
+/// Inner joins daily forecasts and observations on station/date in an in-memory DuckDB
+pub fn compare_daily(
+    forecasts: &[Forecast],
+    observations: &[DailyObservation],
+) -> Result<Vec<DailyComparison>, duckdb::Error> {
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(
+        "CREATE TABLE forecasts (
+            station_id TEXT, date TEXT, temp_low BIGINT, temp_high BIGINT,
+            wind_speed BIGINT, rain_amt DOUBLE, snow_amt DOUBLE);
+         CREATE TABLE observations (
+            station_id TEXT, date TEXT, temp_unit_code TEXT, temp_low DOUBLE,
+            temp_high DOUBLE, wind_speed BIGINT, rain_amt DOUBLE, snow_amt DOUBLE);",
+    )?;
+    {
+        let mut appender = conn.appender("forecasts")?;
+        for forecast in forecasts {
+            appender.append_row(params![
+                forecast.station_id,
+                forecast.date,
+                forecast.temp_low,
+                forecast.temp_high,
+                forecast.wind_speed,
+                forecast.rain_amt,
+                forecast.snow_amt,
+            ])?;
+        }
+        let mut appender = conn.appender("observations")?;
+        for observation in observations {
+            appender.append_row(params![
+                observation.station_id,
+                observation.date,
+                observation.temp_unit_code,
+                observation.temp_low,
+                observation.temp_high,
+                // Daily observations use a negative speed for "no reading"
+                (observation.wind_speed >= 0).then_some(observation.wind_speed),
+                observation.rain_amt,
+                observation.snow_amt,
+            ])?;
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT
+            f.station_id, f.date, o.temp_unit_code,
+            f.temp_low, o.temp_low, o.temp_low - f.temp_low,
+            f.temp_high, o.temp_high, o.temp_high - f.temp_high,
+            f.wind_speed, o.wind_speed, o.wind_speed - f.wind_speed,
+            f.rain_amt, o.rain_amt, o.rain_amt - f.rain_amt,
+            f.snow_amt, o.snow_amt, o.snow_amt - f.snow_amt
+        FROM forecasts f
+        JOIN observations o ON o.station_id = f.station_id AND o.date = f.date
+        ORDER BY f.station_id, f.date",
+    )?;
+    let comparisons = stmt
+        .query_map([], |row| {
+            Ok(DailyComparison {
+                station_id: row.get(0)?,
+                date: row.get(1)?,
+                temp_unit_code: row.get(2)?,
+                forecast_temp_low: row.get(3)?,
+                observed_temp_low: row.get(4)?,
+                temp_low_error: row.get(5)?,
+                forecast_temp_high: row.get(6)?,
+                observed_temp_high: row.get(7)?,
+                temp_high_error: row.get(8)?,
+                forecast_wind_speed: row.get(9)?,
+                observed_wind_speed: row.get(10)?,
+                wind_speed_error: row.get(11)?,
+                forecast_rain_amt: row.get(12)?,
+                observed_rain_amt: row.get(13)?,
+                rain_amt_error: row.get(14)?,
+                forecast_snow_amt: row.get(15)?,
+                observed_snow_amt: row.get(16)?,
+                snow_amt_error: row.get(17)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(comparisons)
+}
+
 struct Stations {
     values: Vec<Station>,
 }
@@ -1557,7 +3297,25 @@ impl From<&RecordBatch> for Stations {
     }
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
+/// How completely a station reported over a range
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ObservationCoverage {
+    pub station_id: String,
+    /// Distinct observation times in the range
+    pub observations: i64,
+    /// Earliest observation in the range (RFC3339)
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>)]
+    pub first_observed: Option<OffsetDateTime>,
+    /// Latest observation in the range (RFC3339)
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>)]
+    pub last_observed: Option<OffsetDateTime>,
+    /// Share of the range's UTC hours with at least one observation, 0 to 1
+    pub coverage: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct Station {
     pub station_id: String,
     pub station_name: String,
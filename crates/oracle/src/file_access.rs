@@ -1,11 +1,16 @@
 use async_trait::async_trait;
 use axum::body::Body;
 use log::trace;
+use noaa_oracle_core::DataLayout;
 use serde::{Deserialize, Serialize};
+use std::io::SeekFrom;
 use time::{
     format_description::well_known::Rfc3339, macros::format_description, Date, OffsetDateTime,
 };
-use tokio::fs;
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncSeekExt},
+};
 use tokio_util::io::ReaderStream;
 use utoipa::IntoParams;
 
@@ -23,6 +28,7 @@ pub struct FileParams {
 
 pub struct FileAccess {
     data_dir: String,
+    layout: DataLayout,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -35,6 +41,8 @@ pub enum Error {
     NotFound(String),
     #[error("IO error: {0}")]
     Io(String),
+    #[error("S3 config error: {0}")]
+    S3Config(String),
 }
 
 #[async_trait]
@@ -43,17 +51,89 @@ pub trait FileData: Send + Sync {
     fn current_folder(&self) -> String;
     fn build_file_paths(&self, file_names: Vec<String>) -> Vec<String>;
     fn build_file_path(&self, filename: &str, file_generated_at: OffsetDateTime) -> String;
+    /// How files are arranged under the data dir
+    fn layout(&self) -> DataLayout {
+        DataLayout::default()
+    }
     /// Download a file and return its contents as an axum Body stream
     async fn download_file(
         &self,
         filename: &str,
         file_generated_at: OffsetDateTime,
     ) -> Result<Body, Error>;
+    /// Size of a stored file in bytes
+    async fn file_size(
+        &self,
+        filename: &str,
+        file_generated_at: OffsetDateTime,
+    ) -> Result<u64, Error>;
+    /// Download `length` bytes starting at `offset`, used to answer HTTP range requests
+    async fn download_file_range(
+        &self,
+        filename: &str,
+        file_generated_at: OffsetDateTime,
+        offset: u64,
+        length: u64,
+    ) -> Result<Body, Error>;
 }
 
 impl FileAccess {
     pub fn new(data_dir: String) -> Self {
-        Self { data_dir }
+        Self {
+            data_dir,
+            layout: DataLayout::default(),
+        }
+    }
+
+    pub fn with_layout(mut self, layout: DataLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn data_dir(&self) -> &str {
+        &self.data_dir
+    }
+
+    /// Collects matching files from the date folders directly under `folder`
+    async fn grab_date_folders(
+        &self,
+        folder: std::path::PathBuf,
+        params: &FileParams,
+        files_names: &mut Vec<String>,
+    ) -> Result<(), Error> {
+        if let Ok(mut entries) = fs::read_dir(folder).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                let folder_name = entry.file_name();
+                let Some(date) = folder_name
+                    .to_str()
+                    .and_then(|name| self.layout.date_folder(name))
+                else {
+                    continue;
+                };
+                let format = format_description!("[year]-[month]-[day]");
+                // Folders from another layout sharing the data dir are skipped
+                let Ok(directory_date) = Date::parse(date, &format) else {
+                    trace!("skipping non-date folder: {}", date);
+                    continue;
+                };
+                if !is_date_in_range(directory_date, params) {
+                    continue;
+                }
+
+                if let Ok(mut subentries) = fs::read_dir(path).await {
+                    while let Ok(Some(subentries)) = subentries.next_entry().await {
+                        if let Some(filename) = self.add_filename(subentries, params)? {
+                            files_names.push(filename);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 
     fn add_filename(
@@ -100,12 +180,8 @@ impl FileData for FileAccess {
                 let file_pieces: Vec<String> = file_name.split('_').map(|f| f.to_owned()).collect();
                 let created_time = drop_suffix(file_pieces.last().unwrap(), ".parquet");
                 let file_generated_at = OffsetDateTime::parse(&created_time, &Rfc3339).unwrap();
-                format!(
-                    "{}/{}/{}",
-                    self.data_dir,
-                    file_generated_at.date(),
-                    file_name
-                )
+                self.layout
+                    .file_path(&self.data_dir, file_name, file_generated_at.date())
             })
             .collect()
     }
@@ -120,12 +196,12 @@ impl FileData for FileAccess {
     }
 
     fn build_file_path(&self, filename: &str, file_generated_at: OffsetDateTime) -> String {
-        format!(
-            "{}/{}/{}",
-            self.data_dir,
-            file_generated_at.date(),
-            filename
-        )
+        self.layout
+            .file_path(&self.data_dir, filename, file_generated_at.date())
+    }
+
+    fn layout(&self) -> DataLayout {
+        self.layout
     }
 
     async fn download_file(
@@ -141,26 +217,54 @@ impl FileData for FileAccess {
         Ok(Body::from_stream(stream))
     }
 
+    async fn file_size(
+        &self,
+        filename: &str,
+        file_generated_at: OffsetDateTime,
+    ) -> Result<u64, Error> {
+        let file_path = self.build_file_path(filename, file_generated_at);
+        let metadata = fs::metadata(&file_path)
+            .await
+            .map_err(|e| Error::NotFound(format!("{}: {}", file_path, e)))?;
+        Ok(metadata.len())
+    }
+
+    async fn download_file_range(
+        &self,
+        filename: &str,
+        file_generated_at: OffsetDateTime,
+        offset: u64,
+        length: u64,
+    ) -> Result<Body, Error> {
+        let file_path = self.build_file_path(filename, file_generated_at);
+        let mut file = tokio::fs::File::open(&file_path)
+            .await
+            .map_err(|e| Error::NotFound(format!("{}: {}", file_path, e)))?;
+        file.seek(SeekFrom::Start(offset))
+            .await
+            .map_err(|e| Error::Io(format!("{}: {}", file_path, e)))?;
+        let stream = ReaderStream::new(file.take(length));
+        Ok(Body::from_stream(stream))
+    }
+
     async fn grab_file_names(&self, params: FileParams) -> Result<Vec<String>, Error> {
         let mut files_names = vec![];
-        if let Ok(mut entries) = fs::read_dir(self.data_dir.clone()).await {
-            while let Ok(Some(entry)) = entries.next_entry().await {
-                let path = entry.path();
-                if !path.is_dir() {
-                    continue;
-                }
-                if let Some(date) = entry.file_name().to_str() {
-                    let format = format_description!("[year]-[month]-[day]");
-                    let directory_date = Date::parse(date, &format)?;
-                    if !is_date_in_range(directory_date, &params) {
-                        continue;
-                    }
-
-                    if let Ok(mut subentries) = fs::read_dir(path).await {
-                        while let Ok(Some(subentries)) = subentries.next_entry().await {
-                            if let Some(filename) = self.add_filename(subentries, &params)? {
-                                files_names.push(filename);
-                            }
+        match self.layout {
+            DataLayout::DateSub => {
+                self.grab_date_folders(self.data_dir.clone().into(), &params, &mut files_names)
+                    .await?;
+            }
+            DataLayout::Hive => {
+                if let Ok(mut entries) = fs::read_dir(self.data_dir.clone()).await {
+                    while let Ok(Some(entry)) = entries.next_entry().await {
+                        let is_type_folder = entry
+                            .file_name()
+                            .to_str()
+                            .and_then(|name| self.layout.type_folder(name))
+                            .is_some();
+                        if is_type_folder && entry.path().is_dir() {
+                            self.grab_date_folders(entry.path(), &params, &mut files_names)
+                                .await?;
                         }
                     }
                 }
@@ -232,6 +336,9 @@ fn matches_file_params(filename: &str, params: &FileParams) -> Result<bool, Erro
     Ok(false)
 }
 
+/// How long `S3FileAccess::check_access` waits for credentials to load and the bucket to answer
+pub const S3_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 /// S3-backed file access for listing and downloading parquet files.
 /// S3 key format: weather_data/{YYYY-MM-DD}/{filename}
 pub struct S3FileAccess {
@@ -240,16 +347,44 @@ pub struct S3FileAccess {
 }
 
 impl S3FileAccess {
-    pub async fn new(bucket: String, endpoint: Option<String>) -> Self {
+    /// Errors when the bucket is blank or no AWS region is configured, every request would
+    /// fail without one
+    pub async fn new(bucket: String, endpoint: Option<String>) -> Result<Self, Error> {
+        if bucket.trim().is_empty() {
+            return Err(Error::S3Config(String::from("bucket name is empty")));
+        }
         let mut config_loader = aws_config::from_env();
         if let Some(endpoint_url) = endpoint {
             log::info!("Using custom S3 endpoint: {}", endpoint_url);
             config_loader = config_loader.endpoint_url(endpoint_url);
         }
         let config = config_loader.load().await;
+        if config.region().is_none() {
+            return Err(Error::S3Config(String::from(
+                "no AWS region set, configure AWS_REGION or a profile region",
+            )));
+        }
         let client = aws_sdk_s3::Client::new(&config);
         log::info!("S3 file access initialized for bucket: {}", bucket);
-        Self { client, bucket }
+        Ok(Self { client, bucket })
+    }
+
+    /// Confirms the configured credentials can reach the bucket
+    pub async fn check_access(&self) -> Result<(), Error> {
+        let head_bucket = self.client.head_bucket().bucket(&self.bucket).send();
+        match tokio::time::timeout(S3_CHECK_TIMEOUT, head_bucket).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(Error::Io(format!(
+                "can't access bucket {}: {}",
+                self.bucket,
+                aws_sdk_s3::error::DisplayErrorContext(e)
+            ))),
+            Err(_) => Err(Error::Io(format!(
+                "bucket {} didn't answer within {} seconds",
+                self.bucket,
+                S3_CHECK_TIMEOUT.as_secs()
+            ))),
+        }
     }
 
     /// Build the S3 key for a file
@@ -373,4 +508,52 @@ impl FileData for S3FileAccess {
 
         Ok(Body::from(bytes))
     }
+
+    async fn file_size(
+        &self,
+        filename: &str,
+        file_generated_at: OffsetDateTime,
+    ) -> Result<u64, Error> {
+        let key = Self::s3_key(filename, file_generated_at);
+        let resp = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| Error::NotFound(format!("S3 head_object '{}': {}", key, e)))?;
+
+        resp.content_length()
+            .map(|length| length as u64)
+            .ok_or_else(|| Error::Io(format!("S3 head_object '{}': missing content length", key)))
+    }
+
+    async fn download_file_range(
+        &self,
+        filename: &str,
+        file_generated_at: OffsetDateTime,
+        offset: u64,
+        length: u64,
+    ) -> Result<Body, Error> {
+        let key = Self::s3_key(filename, file_generated_at);
+        let resp = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .range(format!("bytes={}-{}", offset, offset + length - 1))
+            .send()
+            .await
+            .map_err(|e| Error::NotFound(format!("S3 get_object '{}': {}", key, e)))?;
+
+        let bytes = resp
+            .body
+            .collect()
+            .await
+            .map_err(|e| Error::Io(format!("S3 read body '{}': {}", key, e)))?
+            .into_bytes();
+
+        Ok(Body::from(bytes))
+    }
 }
@@ -1,17 +1,25 @@
 mod app_error;
+mod clock;
 mod db;
 mod file_access;
+mod listeners;
 mod nostr_extractor;
 pub mod oracle;
 pub mod routes;
 mod startup;
 pub mod templates;
+mod tls;
 mod utils;
+mod validate;
 
 pub use app_error::AppError;
+pub use clock::*;
 pub use db::*;
 pub use file_access::{drop_suffix, Error, FileAccess, FileData, FileParams, S3FileAccess};
+pub use listeners::{bind_listeners, serve_listeners, ListenerConfig, RouteScope};
 pub use nostr_extractor::{AuthError, NostrAuth};
 pub use routes::*;
 pub use startup::*;
+pub use tls::{load_tls_config, serve_h2c, serve_tls};
 pub use utils::*;
+pub use validate::validate_config;
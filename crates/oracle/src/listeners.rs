@@ -0,0 +1,121 @@
+use crate::{app_for, serve_h2c, serve_tls, AppState};
+use anyhow::anyhow;
+use axum::serve;
+use futures::{future::try_join_all, FutureExt};
+use log::info;
+use rustls::ServerConfig;
+use std::{fmt, future::Future, net::SocketAddr, str::FromStr, sync::Arc};
+use tokio::net::TcpListener;
+
+/// Which routes a listener exposes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RouteScope {
+    /// Every route except the `/admin` endpoints, those are unauthenticated and only served on
+    /// a listener explicitly scoped to `admin`
+    #[default]
+    Public,
+    /// The `/admin` endpoints plus the health, liveness, readiness and version probes
+    Admin,
+}
+
+impl FromStr for RouteScope {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "public" => Ok(RouteScope::Public),
+            "admin" => Ok(RouteScope::Admin),
+            _ => Err(anyhow!(
+                "unknown route scope '{}', expected public or admin",
+                value
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RouteScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteScope::Public => write!(f, "public"),
+            RouteScope::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+/// An address to listen on and the routes served there, written as `addr[=scope]`,
+/// e.g. `127.0.0.1:9801=admin`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListenerConfig {
+    pub addr: SocketAddr,
+    pub scope: RouteScope,
+}
+
+impl FromStr for ListenerConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (addr, scope) = match value.trim().split_once('=') {
+            Some((addr, scope)) => (addr, scope.parse()?),
+            None => (value.trim(), RouteScope::default()),
+        };
+        let addr = SocketAddr::from_str(addr)
+            .map_err(|e| anyhow!("invalid listen address '{}': {}", addr, e))?;
+        Ok(ListenerConfig { addr, scope })
+    }
+}
+
+impl fmt::Display for ListenerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.addr, self.scope)
+    }
+}
+
+/// Binds every listener concurrently, failing if any address can't be bound
+pub async fn bind_listeners(
+    configs: &[ListenerConfig],
+) -> Result<Vec<(ListenerConfig, TcpListener)>, anyhow::Error> {
+    try_join_all(configs.iter().map(|config| async move {
+        let listener = TcpListener::bind(config.addr)
+            .await
+            .map_err(|e| anyhow!("error binding to {}: {}", config.addr, e))?;
+        Ok((config.clone(), listener))
+    }))
+    .await
+}
+
+/// Serves each listener's routes over the shared app state until `signal` resolves, then waits
+/// for open connections to finish. Stops every listener as soon as one of them fails. Plain
+/// listeners accept h2c alongside HTTP/1 when `http2` is set, TLS ones follow the config's ALPN.
+pub async fn serve_listeners<F>(
+    listeners: Vec<(ListenerConfig, TcpListener)>,
+    app_state: AppState,
+    tls_config: Option<Arc<ServerConfig>>,
+    http2: bool,
+    signal: F,
+) -> Result<(), anyhow::Error>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let signal = signal.boxed().shared();
+    try_join_all(listeners.into_iter().map(|(config, listener)| {
+        let app = app_for(app_state.clone(), config.scope);
+        let tls_config = tls_config.clone();
+        let signal = signal.clone();
+        async move {
+            info!("  Serving {} routes on {}", config.scope, config.addr);
+            match tls_config {
+                Some(tls_config) => serve_tls(listener, tls_config, app, signal).await,
+                None if http2 => serve_h2c(listener, app, signal).await,
+                None => serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(signal)
+                .await
+                .map_err(|e| anyhow!("error serving {}: {}", config.addr, e)),
+            }
+        }
+    }))
+    .await?;
+    Ok(())
+}
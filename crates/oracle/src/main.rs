@@ -1,20 +1,24 @@
-use anyhow::anyhow;
-use axum::serve;
-use futures::TryFutureExt;
 use log::{error, info};
 use oracle::{
-    app, build_app_state, create_folder, get_config_info, get_log_level, setup_logger,
-    warm_forecast_cache,
+    bind_listeners, build_app_state, build_weather_access, check_weather_data_fresh,
+    connect_event_store, create_folder, get_config_info, get_log_level, load_tls_config,
+    oracle::Oracle, run_warm_cycle, serve_listeners, setup_logger, validate_config, Cli, Command,
+    RouteScope,
 };
-use std::{net::SocketAddr, str::FromStr, sync::Arc};
-use tokio::{net::TcpListener, signal};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    sync::Arc,
+};
+use time::OffsetDateTime;
+use tokio::signal;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = get_config_info();
     let log_level = get_log_level(&cli);
 
-    setup_logger()
+    setup_logger(cli.json_logs())
         .level(log_level)
         .level_for("duckdb", log_level)
         .level_for("oracle", log_level)
@@ -22,41 +26,81 @@ async fn main() -> anyhow::Result<()> {
         .level_for("http_request", log_level)
         .apply()?;
 
+    if cli.validate_config {
+        validate_config(&cli).await?;
+        info!("Config is valid");
+        return Ok(());
+    }
+
     // Get paths using the new helper methods
     let weather_data = cli.weather_dir();
     let event_data = cli.event_db();
     let static_dir = cli.static_dir();
     let private_key = cli.private_key();
     let remote_url = cli.remote_url();
-    let host = cli.host();
-    let port = cli.port();
 
     // Create required directories
     create_folder(&weather_data);
     create_folder(&event_data);
 
-    let socket_addr = SocketAddr::from_str(&format!("{}:{}", host, port))
-        .map_err(|e| anyhow!("invalid address: {}", e))?;
+    if let Some(command) = cli.command.clone() {
+        return run_command(command, &cli).await;
+    }
+
+    // Misconfigurations stop startup here, all reported at once, before anything is opened
+    validate_config(&cli).await?;
+
+    // Bad TLS files should stop startup before anything binds
+    let tls_config = cli
+        .tls_files()?
+        .map(|(cert, key)| load_tls_config(&cert, &key, cli.http2()))
+        .transpose()?;
+    let scheme = if tls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
 
-    let listener = TcpListener::bind(socket_addr)
-        .map_err(|e| anyhow!("error binding to socket: {}", e))
-        .await?;
+    let listeners = bind_listeners(&cli.listeners()?).await?;
 
     info!("NOAA Oracle starting...");
-    info!("  Listen: http://{}", socket_addr);
-    info!("  Docs:   http://{}/docs", socket_addr);
+    for (config, _) in &listeners {
+        info!(
+            "  Listen: {}://{} ({} routes)",
+            scheme, config.addr, config.scope
+        );
+        if config.scope != RouteScope::Admin {
+            info!("  Docs:   {}://{}/docs", scheme, config.addr);
+        }
+    }
     info!("  Weather data: {}", weather_data);
-    info!("  Event DB: {}", event_data);
+    info!("  Event DB: {}", cli.database_url());
     info!("  Static: {}", static_dir);
 
     let app_state = build_app_state(
         remote_url,
         static_dir,
         weather_data,
-        event_data,
+        cli.database_url(),
         private_key,
-        cli.s3_bucket,
-        cli.s3_endpoint,
+        cli.nonce_derivation()?,
+        cli.outcome_message_version()?,
+        cli.settlement_delay(),
+        cli.max_event_locations(),
+        cli.s3_bucket.clone(),
+        cli.s3_endpoint.clone(),
+        cli.layout()?,
+        cli.duckdb_extension_dir.clone(),
+        cli.query_settings(),
+        cli.http_retry_settings(),
+        cli.humidity_formula()?,
+        cli.sanity_bounds()?,
+        cli.precip_codes()?,
+        cli.max_batch_events(),
+        cli.max_body_size(),
+        cli.custom_query_limits(),
+        cli.max_data_age(),
+        cli.database_settings(),
     )
     .await
     .map_err(|e| {
@@ -70,7 +114,7 @@ async fn main() -> anyhow::Result<()> {
     let cache_state = Arc::new(app_state.clone());
     tokio::spawn(async move {
         // Initial warm-up
-        warm_forecast_cache(&cache_state).await;
+        run_warm_cycle(&cache_state).await;
 
         // Refresh every 30 minutes (source data arrives hourly, so at most 30 min stale)
         let mut interval = tokio::time::interval(std::time::Duration::from_secs(1800));
@@ -78,21 +122,47 @@ async fn main() -> anyhow::Result<()> {
         loop {
             interval.tick().await;
             // Clear old entries before re-warming
-            {
-                let mut cache = cache_state.forecast_cache.lock().unwrap();
-                cache.clear();
-            }
-            warm_forecast_cache(&cache_state).await;
+            cache_state.lock_forecast_cache().clear();
+            run_warm_cycle(&cache_state).await;
         }
     });
 
-    let app = app(app_state);
+    // Purge events past the retention window once a day, entries and weather cascade with them
+    if let Some(retention) = cli.event_retention() {
+        info!("  Event retention: {} days", retention.whole_days());
+        let purge_oracle = app_state.oracle.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(86400));
+            loop {
+                interval.tick().await;
+                if let Err(e) = purge_oracle.purge_expired_events(retention).await {
+                    error!("failed to purge expired events: {}", e);
+                }
+            }
+        });
+    }
 
-    serve(
-        listener,
-        app.into_make_service_with_connect_info::<SocketAddr>(),
+    // Warn about stale weather data every 10 minutes, a stuck daemon otherwise goes unnoticed
+    // until something probes /readyz
+    if let Some(max_age) = cli.max_data_age() {
+        info!("  Max data age: {} hours", max_age.whole_hours());
+        let staleness_state = app_state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+            loop {
+                interval.tick().await;
+                check_weather_data_fresh(&staleness_state).await;
+            }
+        });
+    }
+
+    serve_listeners(
+        listeners,
+        app_state,
+        tls_config,
+        cli.http2(),
+        shutdown_signal(),
     )
-    .with_graceful_shutdown(shutdown_signal())
     .await?;
 
     // Checkpoint WAL before exit so Litestream replicates a complete database.
@@ -104,6 +174,56 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn run_command(command: Command, cli: &Cli) -> anyhow::Result<()> {
+    let db = connect_event_store(&cli.database_url(), cli.database_settings()).await?;
+    match command {
+        Command::Maintenance { vacuum } => {
+            let report = db.maintenance(vacuum).await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        Command::ExportEvents { path } => {
+            let mut file = BufWriter::new(File::create(&path)?);
+            let exported = db
+                .export_events(&mut file, OffsetDateTime::now_utc())
+                .await?;
+            info!("exported {} events to {}", exported, path);
+        }
+        Command::ImportEvents { path } => {
+            let mut file = BufReader::new(File::open(&path)?);
+            let report = db
+                .import_events(&mut file, OffsetDateTime::now_utc())
+                .await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        Command::RotateKey => {
+            let key = Oracle::rotate_key(db.clone(), &cli.private_key(), OffsetDateTime::now_utc())
+                .await?;
+            info!(
+                "oracle key {} is active from {}",
+                key.pubkey, key.active_from
+            );
+        }
+        Command::MaterializeDaily { start, end } => {
+            let weather_db = build_weather_access(
+                cli.weather_dir(),
+                cli.layout()?,
+                cli.duckdb_extension_dir.clone(),
+                cli.query_settings(),
+                cli.http_retry_settings(),
+                cli.humidity_formula()?,
+                cli.sanity_bounds()?,
+                cli.precip_codes()?,
+            )?;
+            let report = weather_db.materialize_daily(start, end).await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        // Printed by get_config_info, which exits before anything is opened
+        Command::PrintConfig => {}
+    }
+    db.checkpoint().await;
+    Ok(())
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
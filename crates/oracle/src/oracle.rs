@@ -1,18 +1,33 @@
 use crate::{
-    weather_data, ActiveEvent, AddEventEntry, CreateEvent, CreateEventData, Database, Event,
-    EventFilter, EventStatus, EventSummary, Forecast, ForecastRequest, Observation,
-    ObservationRequest, ScoringField, SignEvent, TemperatureUnit, ValueOptions, Weather,
-    WeatherData, WeatherEntry,
+    generate_ranking_permutations, new_uuid_v7, settlement_delay, weather_data, ActiveEvent,
+    AddEventEntry, AttestationVerification, BatchEvent, Clock, CloneEvent, CreateEvent,
+    CreateEventData, DayTimeZone, EntryAudit, EntryLimitExceeded, Event, EventAggregates,
+    EventAnnouncement, EventAttestation, EventAudit, EventChanges, EventFilter, EventIncludes,
+    EventStatus, EventStore, EventSummary, Forecast, ForecastAggregation, ForecastFields,
+    ForecastRequest, MaintenanceReport, MigrationStatus, Observation, ObservationRequest,
+    ObservationTempAggregation, ObservationWindAggregation, OracleKey, OutcomeMessageVersion,
+    ParTolerances, PrecipUnit, RngSource, ScoringField, ScoringMode, SignEvent, StationAliases,
+    SystemClock, TemperatureUnit, ThreadRng, ValueOptions, VerifyAttestation, Weather, WeatherData,
+    WeatherEntry,
 };
 use anyhow::anyhow;
 use base64::{engine::general_purpose, Engine};
 use dlctix::{
     attestation_locking_point, attestation_secret,
     musig2::secp256k1::{rand, PublicKey, Secp256k1, SecretKey},
-    secp::{MaybePoint, Point},
+    secp::{MaybePoint, Point, Scalar},
 };
 use log::{debug, error, info, warn};
-use nostr_sdk::{key::Keys, nips::nip19::ToBech32, PublicKey as NostrPublicKey};
+use noaa_oracle_core::check_writable_dir;
+use nostr_sdk::{
+    hashes::{
+        hmac::{Hmac, HmacEngine},
+        sha256, Hash, HashEngine,
+    },
+    key::Keys,
+    nips::nip19::ToBech32,
+    PublicKey as NostrPublicKey,
+};
 use pem_rfc7468::{decode_vec, encode_string};
 use serde::Serialize;
 use std::{
@@ -20,13 +35,20 @@ use std::{
     fs::{metadata, File},
     io::{Read, Write},
     path::Path,
+    str::FromStr,
     sync::Arc,
 };
 use thiserror::Error;
-use time::OffsetDateTime;
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+/// Stations the example event falls back to before the oracle has any weather data
+pub const EXAMPLE_LOCATIONS: [&str; 3] = ["KORD", "KJFK", "KLAX"];
+
+/// How many changed events are loaded per query when building a changes response
+const CHANGES_PAGE_SIZE: usize = 500;
+
 #[derive(Error, Debug, Serialize, ToSchema)]
 pub enum Error {
     #[error("{0}")]
@@ -65,8 +87,14 @@ pub enum Error {
     ),
     #[error("Pubkeys in DB doesn't match with .pem")]
     MismatchPubkey(String),
+    #[error("Key rotation rejected: {0}")]
+    KeyRotation(String),
     #[error("Invalid entry: {0}")]
     BadEntry(String),
+    #[error("Not the event's coordinator: {0}")]
+    NotCoordinator(String),
+    #[error("Invalid attestation: {0}")]
+    BadAttestation(String),
     #[error("Invalid event: {0}")]
     #[schema(value_type = String)]
     BadEvent(#[serde(skip)] anyhow::Error),
@@ -88,38 +116,212 @@ pub enum Error {
     ),
 }
 
+impl Error {
+    /// Stable machine readable code sent as `code` in API error bodies, see `ErrorBody` for the
+    /// full list. Clients branch on this instead of the message, so codes are never renamed.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::NotFound(_) => "not_found",
+            Error::ValidateKey(_) => "key_invalid",
+            Error::MinOutcome(_) => "min_outcome",
+            Error::EventMaturity(_) => "event_maturity",
+            Error::ConvertKey(_) => "key_conversion_failed",
+            Error::Base32Key(_) => "key_encoding_failed",
+            Error::DataQuery(_) => "data_query_failed",
+            Error::MismatchPubkey(_) => "pubkey_mismatch",
+            Error::KeyRotation(_) => "key_rotation_rejected",
+            Error::BadEntry(_) => "bad_entry",
+            Error::NotCoordinator(_) => "not_coordinator",
+            Error::BadAttestation(_) => "bad_attestation",
+            Error::BadEvent(_) => "bad_event",
+            Error::WeatherData(_) => "weather_data_failed",
+            Error::OutcomeNotFound(_) => "outcome_not_found",
+            Error::Validation(_) => "message_validation_failed",
+        }
+    }
+
+    /// What the caller got wrong, without the message prefix. None for internal errors, their
+    /// causes aren't exposed to clients.
+    pub fn details(&self) -> Option<String> {
+        match self {
+            Error::NotFound(details)
+            | Error::MinOutcome(details)
+            | Error::EventMaturity(details)
+            | Error::KeyRotation(details)
+            | Error::BadEntry(details)
+            | Error::NotCoordinator(details)
+            | Error::BadAttestation(details) => Some(details.clone()),
+            Error::BadEvent(e) => Some(e.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// How the oracle picks the nonce it commits to when an event is announced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonceDerivation {
+    /// A fresh random nonce per event
+    #[default]
+    Random,
+    /// HMAC-SHA256 keyed by the oracle's private key over the event id, RFC6979-style. The same key and
+    /// event id always give the same nonce, so announcements can be reproduced for test vectors or
+    /// rebuilt after losing the event database.
+    ///
+    /// Security: the nonce is only as secret as the private key, and it must never sign two different
+    /// outcomes. The oracle signs each event once and event ids are unique. Ids are picked by the
+    /// client though, so creating or cloning an event under the id of a deleted or purged one is
+    /// refused in this mode. Importing an event under a reused id, or running the same key against
+    /// two databases that both sign an event with the same id, would still attest twice with one
+    /// nonce and leak the private key.
+    Deterministic,
+}
+
+impl NonceDerivation {
+    const TAG: &'static [u8] = b"noaa-oracle/event-nonce";
+
+    /// `rng` is only drawn from by `Random`
+    pub fn nonce(&self, private_key: &SecretKey, event_id: &Uuid, rng: &dyn RngSource) -> Scalar {
+        match self {
+            NonceDerivation::Random => {
+                let mut bytes = [0u8; 32];
+                rng.fill_bytes(&mut bytes);
+                Scalar::reduce_from(&bytes)
+            }
+            NonceDerivation::Deterministic => {
+                let mut engine = HmacEngine::<sha256::Hash>::new(&private_key.secret_bytes());
+                engine.input(Self::TAG);
+                engine.input(event_id.as_bytes());
+                let digest = Hmac::<sha256::Hash>::from_engine(engine).to_byte_array();
+                Scalar::reduce_from(&digest)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for NonceDerivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NonceDerivation::Random => write!(f, "random"),
+            NonceDerivation::Deterministic => write!(f, "deterministic"),
+        }
+    }
+}
+
+impl FromStr for NonceDerivation {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "random" => Ok(NonceDerivation::Random),
+            "deterministic" => Ok(NonceDerivation::Deterministic),
+            other => Err(anyhow!(
+                "unknown nonce derivation '{}', expected random or deterministic",
+                other
+            )),
+        }
+    }
+}
+
+/// Locations an event may list unless configured otherwise, each one multiplies the values
+/// entries pick from and the observations scored at signing
+pub const DEFAULT_MAX_EVENT_LOCATIONS: usize = 50;
+
 pub struct Oracle {
-    db: Arc<Database>,
+    db: Arc<dyn EventStore>,
     weather_data: Arc<dyn WeatherData>,
     private_key: SecretKey,
     public_key: PublicKey,
+    nonce_derivation: NonceDerivation,
+    outcome_message_version: OutcomeMessageVersion,
+    settlement_delay: Duration,
+    max_event_locations: usize,
+    clock: Arc<dyn Clock>,
+    rng: Arc<dyn RngSource>,
+    name: String,
 }
 
 impl Oracle {
     pub async fn new(
-        db: Arc<Database>,
+        db: Arc<dyn EventStore>,
         weather_data: Arc<dyn WeatherData>,
         private_key_file_path: &String,
     ) -> Result<Self, Error> {
         let secret_key = get_key(private_key_file_path)?;
         let secp = Secp256k1::new();
         let public_key = secret_key.public_key(&secp);
-        let oracle = Self {
+        let mut oracle = Self {
             db,
             weather_data,
             private_key: secret_key,
             public_key,
+            nonce_derivation: NonceDerivation::default(),
+            outcome_message_version: OutcomeMessageVersion::CURRENT,
+            settlement_delay: Duration::ZERO,
+            max_event_locations: DEFAULT_MAX_EVENT_LOCATIONS,
+            clock: Arc::new(SystemClock),
+            rng: Arc::new(ThreadRng),
+            name: String::new(),
         };
         oracle.validate_oracle_metadata().await?;
+        oracle.name = oracle
+            .db
+            .get_oracle_name()
+            .await
+            .map_err(Error::ValidateKey)?;
         Ok(oracle)
     }
 
+    pub fn with_nonce_derivation(mut self, nonce_derivation: NonceDerivation) -> Self {
+        self.nonce_derivation = nonce_derivation;
+        self
+    }
+
+    /// Outcome message layout new events are announced with, existing events keep the one they
+    /// were created with
+    pub fn with_outcome_message_version(
+        mut self,
+        outcome_message_version: OutcomeMessageVersion,
+    ) -> Self {
+        self.outcome_message_version = outcome_message_version;
+        self
+    }
+
+    /// How long after an event's end observation date to wait for late observations before signing,
+    /// events can override it with their own `settlement_delay_hours`
+    pub fn with_settlement_delay(mut self, settlement_delay: Duration) -> Self {
+        self.settlement_delay = settlement_delay;
+        self
+    }
+
+    pub fn with_max_event_locations(mut self, max_event_locations: usize) -> Self {
+        self.max_event_locations = max_event_locations;
+        self
+    }
+
+    /// Time source for event status, entry cutoffs, signing and the other "is it time yet"
+    /// checks, `SystemClock` unless replaced
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Randomness for random nonces and generated event ids, `ThreadRng` unless replaced
+    pub fn with_rng(mut self, rng: Arc<dyn RngSource>) -> Self {
+        self.rng = rng;
+        self
+    }
+
+    /// The current time according to the oracle's clock
+    pub fn now(&self) -> OffsetDateTime {
+        self.clock.now()
+    }
+
     pub async fn validate_oracle_metadata(&self) -> Result<(), Error> {
         match self.db.get_stored_public_key().await {
             Ok(stored_public_key) => {
                 if stored_public_key != self.public_key.x_only_public_key().0 {
                     return Err(Error::MismatchPubkey(format!(
-                        "stored_pubkey: {:?} pem_pubkey: {:?}",
+                        "stored_pubkey: {:?} pem_pubkey: {:?}, run `rotate-key` to make the .pem the active key",
                         stored_public_key,
                         self.public_key()
                     )));
@@ -134,6 +336,58 @@ impl Oracle {
         }
     }
 
+    /// Makes the key in `private_key_file_path` the active signing key. Retired keys are kept so
+    /// attestations they signed can still be verified. Rejected while any event is unsigned, only
+    /// the key that announced an event can attest to it. The new key is active from `now`.
+    pub async fn rotate_key(
+        db: Arc<dyn EventStore>,
+        private_key_file_path: &String,
+        now: OffsetDateTime,
+    ) -> Result<OracleKey, Error> {
+        let secret_key = get_key(private_key_file_path)?;
+        let pubkey = secret_key
+            .public_key(&Secp256k1::new())
+            .x_only_public_key()
+            .0;
+        let keys = db.get_oracle_keys().await.map_err(Error::ValidateKey)?;
+        if keys.is_empty() {
+            return Err(Error::KeyRotation(String::from(
+                "no stored key to rotate from, start the oracle once to store its key",
+            )));
+        }
+        if let Some(key) = keys.iter().find(|key| key.pubkey == pubkey) {
+            return Err(Error::KeyRotation(match key.active_until {
+                None => String::from("key is already the active key"),
+                Some(retired) => format!("key was retired at {}", retired),
+            }));
+        }
+
+        // keys are stored to the second, return the key as it will be read back
+        let now = now
+            .replace_nanosecond(0)
+            .map_err(|e| Error::KeyRotation(e.to_string()))?;
+        let aggregates = db
+            .event_aggregates(now, now)
+            .await
+            .map_err(Error::ValidateKey)?;
+        let unsigned = aggregates.total_events - aggregates.signed;
+        if unsigned > 0 {
+            return Err(Error::KeyRotation(format!(
+                "{} unsigned events were announced with the active key and could never be attested",
+                unsigned
+            )));
+        }
+
+        db.rotate_oracle_key(pubkey, now)
+            .await
+            .map_err(Error::ValidateKey)?;
+        Ok(OracleKey {
+            pubkey,
+            active_from: now,
+            active_until: None,
+        })
+    }
+
     async fn add_meta_data(&self) -> Result<(), Error> {
         self.db
             .add_oracle_metadata(self.public_key.x_only_public_key().0)
@@ -154,6 +408,16 @@ impl Oracle {
         general_purpose::STANDARD.encode(key)
     }
 
+    /// Hex encoded x-only public key, the form stored in the oracle metadata
+    pub fn xonly_public_key(&self) -> String {
+        hex::encode(self.public_key.x_only_public_key().0.serialize())
+    }
+
+    /// Name stored alongside the public key in the oracle metadata
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn npub(&self) -> Result<String, Error> {
         let secret_key = self.private_key.display_secret().to_string();
         let keys = Keys::parse(&secret_key)?;
@@ -163,13 +427,72 @@ impl Oracle {
 
     pub async fn list_events(&self, filter: EventFilter) -> Result<Vec<EventSummary>, Error> {
         self.db
-            .filtered_list_events(filter)
+            .filtered_list_events(filter, self.now())
             .await
             .map_err(Error::ValidateKey)
     }
 
+    /// Events created, signed, moved to another status or deleted since `since`. The window
+    /// ends now, truncated to the millisecond changes are stamped at
+    pub async fn event_changes(&self, since: OffsetDateTime) -> Result<EventChanges, Error> {
+        let now = self.now();
+        let until = now
+            .replace_nanosecond(now.millisecond() as u32 * 1_000_000)
+            .map_err(|e| Error::ValidateKey(anyhow!(e)))?;
+        let changes = self
+            .db
+            .changed_events(since, until)
+            .await
+            .map_err(Error::ValidateKey)?;
+
+        let mut events = Vec::with_capacity(changes.changed.len());
+        // Chunked to stay well under the database's bind parameter limit
+        for ids in changes.changed.chunks(CHANGES_PAGE_SIZE) {
+            let page = self
+                .db
+                .filtered_list_events(
+                    EventFilter {
+                        limit: None,
+                        event_ids: Some(ids.to_vec()),
+                        ..Default::default()
+                    },
+                    now,
+                )
+                .await
+                .map_err(Error::ValidateKey)?;
+            events.extend(page);
+        }
+
+        Ok(EventChanges {
+            events,
+            deleted: changes.deleted,
+            until,
+        })
+    }
+
+    /// Event counts by status, plus how many were signed within the last `window_days`
+    pub async fn event_aggregates(&self, window_days: u32) -> Result<EventAggregates, Error> {
+        let now = self.now();
+        let signed_since = now - Duration::days(window_days.into());
+        let mut aggregates = self
+            .db
+            .event_aggregates(now, signed_since)
+            .await
+            .map_err(Error::ValidateKey)?;
+        aggregates.window_days = window_days;
+        Ok(aggregates)
+    }
+
     pub async fn get_event(&self, id: &Uuid) -> Result<Event, Error> {
-        match self.db.get_event(id).await {
+        self.get_event_with(id, &EventIncludes::all()).await
+    }
+
+    pub async fn get_event_with(
+        &self,
+        id: &Uuid,
+        includes: &EventIncludes,
+    ) -> Result<Event, Error> {
+        match self.db.get_event_with(id, includes, self.now()).await {
             Ok(event_data) => Ok(event_data),
             Err(e) if e.to_string().contains("no rows") => {
                 Err(Error::NotFound(format!("event with id {} not found", id)))
@@ -178,10 +501,242 @@ impl Oracle {
         }
     }
 
+    pub async fn get_event_announcement(&self, id: &Uuid) -> Result<EventAnnouncement, Error> {
+        let event = self.get_event_with(id, &EventIncludes::default()).await?;
+        let oracle_pubkey = self.event_public_key(id).await?;
+        Ok(EventAnnouncement::new(oracle_pubkey, &event))
+    }
+
+    /// The key that was active when the event was created, which may have been rotated out since
+    async fn event_public_key(&self, id: &Uuid) -> Result<Point, Error> {
+        let key = self
+            .db
+            .get_event_oracle_key(id)
+            .await
+            .map_err(Error::ValidateKey)?;
+        if key.pubkey == self.public_key.x_only_public_key().0 {
+            return Ok(Point::from(self.public_key));
+        }
+        Point::lift_x(&key.pubkey.serialize())
+            .map_err(|e| Error::ValidateKey(anyhow!("invalid stored pubkey: {}", e)))
+    }
+
+    /// Checks the event's attestation unlocks one of its outcomes under the key that was active
+    /// when the event was created, so events signed before a key rotation still verify
+    pub async fn verify_attestation(&self, id: &Uuid) -> Result<AttestationVerification, Error> {
+        let event = self.get_event_with(id, &EventIncludes::default()).await?;
+        let attestation = EventAttestation::new(&event).ok_or_else(|| {
+            Error::NotFound(format!("event with id {} has not been signed yet", id))
+        })?;
+        let announcement = EventAnnouncement::new(self.event_public_key(id).await?, &event);
+
+        let outcomes = generate_ranking_permutations(
+            event.total_allowed_entries as usize,
+            event.number_of_places_win as usize,
+        );
+        let Some(winners) = attestation
+            .outcome_index
+            .and_then(|index| outcomes.get(index))
+        else {
+            return Ok(AttestationVerification {
+                valid: false,
+                outcome_index: attestation.outcome_index,
+            });
+        };
+        attestation
+            .verify(&announcement, winners)
+            .map_err(|e| Error::BadAttestation(e.to_string()))
+    }
+
+    pub async fn verify(
+        &self,
+        request: VerifyAttestation,
+    ) -> Result<AttestationVerification, Error> {
+        match request {
+            VerifyAttestation::Event { event_id } => self.verify_attestation(&event_id).await,
+            VerifyAttestation::Artifacts {
+                announcement,
+                attestation,
+                winners,
+            } => attestation
+                .verify(&announcement, &winners)
+                .map_err(|e| Error::BadAttestation(e.to_string())),
+        }
+    }
+
+    /// Re-scores a signed event's entries from its persisted weather and compares the result with
+    /// the stored scores and the attestation. The event store only keeps whole degree
+    /// temperatures and wind speed, the other scoring fields are read from the weather files the
+    /// ETL scored from. Nothing is written back.
+    pub async fn audit_event(&self, id: &Uuid) -> Result<EventAudit, Error> {
+        let event = self.get_event(id).await?;
+        let attestation = EventAttestation::new(&event)
+            .filter(|_| event.status == EventStatus::Signed)
+            .ok_or_else(|| {
+                Error::NotFound(format!("event with id {} has not been signed yet", id))
+            })?;
+
+        let mut forecast_data = self
+            .event_forecast_data(
+                &event.locations,
+                event.start_observation_date,
+                event.end_observation_date,
+            )
+            .await?;
+        let mut observation_data = self
+            .event_observation_data(
+                &event.locations,
+                event.start_observation_date,
+                event.end_observation_date,
+            )
+            .await?;
+        let persisted = self
+            .db
+            .get_event_weather_in_unit(*id, &TemperatureUnit::Fahrenheit)
+            .await
+            .map_err(Error::ValidateKey)?;
+        overlay_persisted_weather(&persisted, &mut forecast_data, &mut observation_data);
+
+        let rescored: Vec<WeatherEntry> = event
+            .entries
+            .iter()
+            .map(|entry| {
+                let (base_score, score) = score_entry(
+                    entry,
+                    &event.locations,
+                    &event.scoring_fields,
+                    &event.par_tolerances,
+                    event.scoring_mode,
+                    &forecast_data,
+                    &observation_data,
+                );
+                WeatherEntry {
+                    score: Some(score),
+                    base_score: Some(base_score),
+                    ..entry.clone()
+                }
+            })
+            .collect();
+        let winners = rank_winners(&rescored, event.number_of_places_win as usize);
+        let announcement = EventAnnouncement::new(self.event_public_key(id).await?, &event);
+        let attestation_matches = attestation
+            .verify(&announcement, &winners)
+            .map_err(|e| Error::BadAttestation(e.to_string()))?
+            .valid;
+
+        let mut entries: Vec<EntryAudit> = event
+            .entries
+            .iter()
+            .zip(&rescored)
+            .map(|(stored, rescored)| {
+                let recomputed_score = rescored.score.unwrap_or_default();
+                let recomputed_base_score = rescored.base_score.unwrap_or_default();
+                EntryAudit {
+                    entry_id: stored.id,
+                    stored_score: stored.score,
+                    stored_base_score: stored.base_score,
+                    recomputed_score,
+                    recomputed_base_score,
+                    // the stores read a zero score back as None
+                    matches: stored.score.unwrap_or_default() == recomputed_score
+                        && stored.base_score.unwrap_or_default() == recomputed_base_score,
+                }
+            })
+            .collect();
+        entries.sort_by_key(|entry| cmp::Reverse(entry.recomputed_score));
+        let discrepancy = !attestation_matches || entries.iter().any(|entry| !entry.matches);
+        if discrepancy {
+            warn!("audit of event {} found a scoring discrepancy", id);
+        }
+
+        Ok(EventAudit {
+            event_id: event.id,
+            entries,
+            winners,
+            attestation_matches,
+            discrepancy,
+        })
+    }
+
+    pub async fn get_event_attestation(&self, id: &Uuid) -> Result<EventAttestation, Error> {
+        let event = self.get_event_with(id, &EventIncludes::default()).await?;
+        EventAttestation::new(&event)
+            .ok_or_else(|| Error::NotFound(format!("event with id {} has not been signed yet", id)))
+    }
+
+    pub async fn get_events_batch(&self, ids: &[Uuid]) -> Result<Vec<BatchEvent>, Error> {
+        let mut events = Vec::with_capacity(ids.len());
+        for id in ids {
+            match self.get_event(id).await {
+                Ok(event) => events.push(BatchEvent::Found {
+                    event: Box::new(event),
+                }),
+                Err(Error::NotFound(_)) => events.push(BatchEvent::NotFound { id: *id }),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(events)
+    }
+
+    pub async fn get_event_weather(
+        &self,
+        id: &Uuid,
+        unit: &TemperatureUnit,
+    ) -> Result<Vec<Weather>, Error> {
+        if !self.db.event_exists(id).await.map_err(Error::ValidateKey)? {
+            return Err(Error::NotFound(format!("event with id {} not found", id)));
+        }
+        self.db
+            .get_event_weather_in_unit(*id, unit)
+            .await
+            .map_err(Error::ValidateKey)
+    }
+
+    /// A `CreateEvent` this oracle would accept right now: a fresh id, observations over tomorrow
+    /// (UTC) and signing once the settlement delay has passed. Uses the first few stations the
+    /// weather data knows about, or `EXAMPLE_LOCATIONS` (needing `skip_station_check`) before
+    /// any data has arrived.
+    pub async fn example_event(&self, now: OffsetDateTime) -> Result<CreateEvent, Error> {
+        let mut locations: Vec<String> = self
+            .weather_data
+            .stations()
+            .await?
+            .into_iter()
+            .map(|station| station.station_id)
+            .collect();
+        locations.sort();
+        locations.truncate(EXAMPLE_LOCATIONS.len());
+        if locations.is_empty() {
+            locations = EXAMPLE_LOCATIONS.iter().map(|id| id.to_string()).collect();
+        }
+
+        let scoring_fields = ScoringField::defaults();
+        let start_observation_date = (now.date() + Duration::DAY).midnight().assume_utc();
+        let end_observation_date = start_observation_date + Duration::DAY;
+        Ok(CreateEvent {
+            id: new_uuid_v7(self.clock.as_ref(), self.rng.as_ref()),
+            signing_date: end_observation_date + settlement_delay(None, self.settlement_delay),
+            start_observation_date,
+            end_observation_date,
+            number_of_values_per_entry: Some(locations.len() * scoring_fields.len()),
+            locations,
+            total_allowed_entries: 10,
+            number_of_places_win: 1,
+            scoring_fields,
+            par_tolerances: Default::default(),
+            scoring_mode: ScoringMode::default(),
+            settlement_delay_hours: None,
+            entry_cutoff: None,
+        })
+    }
+
+    /// `skip_station_check` allows locations the weather data has never reported, for stations that
+    /// are expected to come online before the event starts
     pub async fn create_event(
         &self,
         coordinator_pubkey: NostrPublicKey,
         event: CreateEvent,
+        skip_station_check: bool,
     ) -> Result<Event, Error> {
         if event.id.get_version_num() != 7 {
             return Err(Error::BadEvent(anyhow!(
@@ -200,19 +755,113 @@ impl Oracle {
                 event.number_of_places_win
             )));
         }
+        if event.locations.len() > self.max_event_locations {
+            return Err(Error::BadEvent(anyhow!(
+                "Max number of locations in an event is {}, requested: {}",
+                self.max_event_locations,
+                event.locations.len()
+            )));
+        }
+
+        // the attestation has to land before the DLC expires, 1 day after the signing date
+        let settles_at = event.end_observation_date
+            + settlement_delay(event.settlement_delay_hours, self.settlement_delay);
+        if settles_at >= event.signing_date + Duration::DAY {
+            return Err(Error::BadEvent(anyhow!(
+                "settlement delay ends at {}, after the event expires",
+                settles_at
+            )));
+        }
 
+        // a deleted event may already be signed, its deterministic nonce must not sign again
+        if self.nonce_derivation == NonceDerivation::Deterministic
+            && self
+                .db
+                .event_was_deleted(&event.id)
+                .await
+                .map_err(Error::ValidateKey)?
+        {
+            return Err(Error::BadEvent(anyhow!(
+                "event id {} belonged to a deleted event, its nonce can't be reused",
+                event.id
+            )));
+        }
+
+        let event = if skip_station_check {
+            event
+        } else {
+            let locations = self.resolve_locations(&event.locations).await?;
+            CreateEvent { locations, ..event }
+        };
+
+        let nonce = self
+            .nonce_derivation
+            .nonce(&self.private_key, &event.id, self.rng.as_ref());
         let oracle_event = CreateEventData::new(
             Point::from(self.raw_public_key()),
             coordinator_pubkey,
             event,
+            nonce,
+            self.outcome_message_version,
         )
         .map_err(Error::BadEvent)?;
         self.db
-            .add_event(oracle_event)
+            .add_event(oracle_event, self.now())
             .await
             .map_err(Error::ValidateKey)
     }
 
+    /// A new event over `clone`'s dates with the locations, scoring and settings of `source_id`,
+    /// created like any other event. Entries close at the start observation date, so it needs to
+    /// be in the future. Only the source event's coordinator can clone it.
+    pub async fn clone_event(
+        &self,
+        coordinator_pubkey: NostrPublicKey,
+        source_id: &Uuid,
+        clone: CloneEvent,
+        skip_station_check: bool,
+    ) -> Result<Event, Error> {
+        let source = self
+            .get_event_with(source_id, &EventIncludes::default())
+            .await?;
+        if source.coordinator_pubkey != coordinator_pubkey.to_bech32()? {
+            return Err(Error::NotCoordinator(format!(
+                "event {} can only be cloned by its coordinator",
+                source_id
+            )));
+        }
+        if clone.start_observation_date <= self.now() {
+            return Err(Error::BadEvent(anyhow!(
+                "start observation date {} of the cloned event has already passed",
+                clone.start_observation_date
+            )));
+        }
+        let event =
+            clone.create_event(&source, new_uuid_v7(self.clock.as_ref(), self.rng.as_ref()));
+        self.create_event(coordinator_pubkey, event, skip_station_check)
+            .await
+    }
+
+    /// Events on stations without data can never be scored, so locations are resolved to the
+    /// station ids the weather data knows, through IATA/ICAO aliases, and unknown ones rejected up front
+    async fn resolve_locations(&self, locations: &[String]) -> Result<Vec<String>, Error> {
+        let stations = self.weather_data.stations().await?;
+        let resolved = StationAliases::new(&stations)
+            .resolve_all(locations)
+            .map_err(Error::BadEvent)?;
+        for (index, station_id) in resolved.iter().enumerate() {
+            if let Some(earlier) = resolved[..index].iter().position(|id| id == station_id) {
+                return Err(Error::BadEvent(anyhow!(
+                    "locations {} and {} are both station {}",
+                    locations[earlier],
+                    locations[index],
+                    station_id
+                )));
+            }
+        }
+        Ok(resolved)
+    }
+
     pub async fn add_event_entries(
         &self,
         nostr_pubkey: NostrPublicKey,
@@ -226,7 +875,7 @@ impl Oracle {
                 event_id
             )));
         }
-        let event = match self.db.get_event(&event_id).await {
+        let event = match self.db.get_event(&event_id, self.now()).await {
             Ok(event_data) => Ok(event_data),
             Err(e) if e.to_string().contains("no rows") => Err(Error::NotFound(format!(
                 "event with id {} not found",
@@ -264,10 +913,20 @@ impl Oracle {
                 .await?;
             weather_entry.push(entry.into());
         }
+        let entries_close_at = event.entries_close_at();
+        if self.now() >= entries_close_at {
+            return Err(Error::BadEntry(format!(
+                "entries for event {} closed at {}",
+                event_id, entries_close_at
+            )));
+        }
         self.db
             .add_event_entries(weather_entry.clone())
             .await
-            .map_err(Error::ValidateKey)?;
+            .map_err(|e| match e.downcast::<EntryLimitExceeded>() {
+                Ok(limit) => Error::BadEntry(limit.to_string()),
+                Err(e) => Error::ValidateKey(e),
+            })?;
 
         Ok(weather_entry)
     }
@@ -303,6 +962,7 @@ impl Oracle {
             if weather_choice.humidity.is_some() {
                 choice_count += 1;
             }
+            choice_count += weather_choice.predictions.len() as i64;
 
             if choice_count > event.number_of_values_per_entry {
                 return Err(Error::BadEntry(format!(
@@ -327,12 +987,66 @@ impl Oracle {
                 entry.id
             )));
         }
+
+        match event.scoring_mode {
+            ScoringMode::Ternary => {
+                if entry
+                    .expected_observations
+                    .iter()
+                    .any(|choice| !choice.predictions.is_empty())
+                {
+                    return Err(Error::BadEntry(format!(
+                        "entry_id {0} not valid, numeric predictions are only used by proximity scored events",
+                        entry.id
+                    )));
+                }
+            }
+            ScoringMode::Proximity => {
+                // every entry is measured over the same stations and fields so their distances compare fairly
+                let all_locations_predicted = event
+                    .locations
+                    .iter()
+                    .all(|location| locations_choose.contains(location));
+                let all_fields_predicted = entry.expected_observations.iter().all(|choice| {
+                    choice.predictions.len() == event.scoring_fields.len()
+                        && event
+                            .scoring_fields
+                            .iter()
+                            .all(|field| choice.prediction_for(field).is_some_and(f64::is_finite))
+                });
+                if !all_locations_predicted || !all_fields_predicted {
+                    return Err(Error::BadEntry(format!(
+                        "entry_id {0} not valid, proximity events need a numeric prediction for every scoring field at every location",
+                        entry.id
+                    )));
+                }
+                for choice in &entry.expected_observations {
+                    for field in &event.scoring_fields {
+                        let range = field.prediction_range();
+                        if let Some(predicted) = choice
+                            .prediction_for(field)
+                            .filter(|predicted| !range.contains(predicted))
+                        {
+                            return Err(Error::BadEntry(format!(
+                                "entry_id {0} not valid, {1} prediction {2} at {3} is outside {4} to {5}",
+                                entry.id,
+                                field,
+                                predicted,
+                                choice.stations,
+                                range.start(),
+                                range.end()
+                            )));
+                        }
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
     pub async fn get_running_events(&self) -> Result<Vec<ActiveEvent>, Error> {
         self.db
-            .get_active_events()
+            .get_active_events(self.now())
             .await
             .map_err(Error::ValidateKey)
     }
@@ -398,10 +1112,11 @@ impl Oracle {
             etl_process_id
         );
         debug!(" etl_process_id {}, getting events to sign", etl_process_id);
-        // 3) sign results for events that are completed and need it
+        // 3) sign results for events that are completed, settled and need it
+        let now = self.now();
         let events_to_sign: Vec<Uuid> = events_to_update
             .iter()
-            .filter(|event| event.status == EventStatus::Completed && event.attestation.is_none())
+            .filter(|event| event.ready_to_sign(self.settlement_delay, now))
             .map(|event| event.id)
             .collect();
         debug!(
@@ -439,16 +1154,28 @@ impl Oracle {
                 "updating event {} with status {} weather data in process {}",
                 event.id, event.status, etl_process_id
             );
-            let forecast_data = self.event_forecast_data(&event).await?;
-            let weather = if event.start_observation_date > OffsetDateTime::now_utc() {
+            let forecast_data = self
+                .event_forecast_data(
+                    &event.locations,
+                    event.start_observation_date,
+                    event.end_observation_date,
+                )
+                .await?;
+            let weather = if event.start_observation_date > self.now() {
                 add_only_forecast_data(&event, forecast_data).await?
             } else {
-                let observation_data = self.event_observation_data(&event).await?;
+                let observation_data = self
+                    .event_observation_data(
+                        &event.locations,
+                        event.start_observation_date,
+                        event.end_observation_date,
+                    )
+                    .await?;
                 add_forecast_data_and_observation_data(&event, forecast_data, observation_data)
                     .await?
             };
             self.db
-                .update_weather_station_data(event.id, weather)
+                .update_weather_station_data(event.id, weather, &TemperatureUnit::Fahrenheit)
                 .await?;
             info!(
                 "completed event {} weather data update {} in process {}",
@@ -488,284 +1215,44 @@ impl Oracle {
     ) -> Result<(), Error> {
         let entries: Vec<WeatherEntry> = self.db.get_event_weather_entries(&event.id).await?;
 
-        let observation_data = self.event_observation_data(&event).await?;
-        let forecast_data = self.event_forecast_data(&event).await?;
+        let observation_data = self
+            .event_observation_data(
+                &event.locations,
+                event.start_observation_date,
+                event.end_observation_date,
+            )
+            .await?;
+        let forecast_data = self
+            .event_forecast_data(
+                &event.locations,
+                event.start_observation_date,
+                event.end_observation_date,
+            )
+            .await?;
         let mut entry_scores: Vec<(Uuid, i64, i64)> = vec![];
 
-        // Get the scoring fields for this event (defaults to temp_high, temp_low, wind_speed)
-        let scoring_fields = &event.scoring_fields;
-
         for entry in entries {
             if entry.event_id != event.id {
                 warn!("entry {} not in this event {}", entry.id, event.id);
                 continue;
             }
 
-            // Score logic, match on Par 2pts, on Over 1pt, on Under 1pt, created_at used as tie breaker (older > newer)
-            let mut base_score = 0;
-            const OVER_OR_UNDER_POINTS: u64 = 10;
-            const PAR_POINTS: u64 = 20;
-            let expected_observations = entry.expected_observations.clone();
-            let locations = event.locations.clone();
-            for location in locations {
-                let Some(choice) = expected_observations
-                    .iter()
-                    .find(|expected| expected.stations == location)
-                else {
-                    continue;
-                };
-
-                let Some(forecast) = forecast_data
-                    .iter()
-                    .find(|forecast| forecast.station_id == location)
-                else {
-                    warn!("no forecast found for: {}", location);
-                    continue;
-                };
-
-                let Some(observation) = observation_data
-                    .iter()
-                    .find(|observation| observation.station_id == location)
-                else {
-                    warn!("no observation found for: {}", location);
-                    continue;
-                };
-
-                // Score temp_high if enabled for this event
-                if scoring_fields.contains(&ScoringField::TempHigh) {
-                    if let Some(high_temp) = choice.temp_high.clone() {
-                        match high_temp {
-                            ValueOptions::Over => {
-                                if forecast.temp_high < observation.temp_high.round() as i64 {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                            ValueOptions::Par => {
-                                if forecast.temp_high == observation.temp_high.round() as i64 {
-                                    base_score += PAR_POINTS;
-                                }
-                            }
-                            ValueOptions::Under => {
-                                if forecast.temp_high > observation.temp_high.round() as i64 {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Score temp_low if enabled for this event
-                if scoring_fields.contains(&ScoringField::TempLow) {
-                    if let Some(temp_low) = choice.temp_low.clone() {
-                        match temp_low {
-                            ValueOptions::Over => {
-                                if forecast.temp_low < observation.temp_low.round() as i64 {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                            ValueOptions::Par => {
-                                if forecast.temp_low == observation.temp_low.round() as i64 {
-                                    base_score += PAR_POINTS;
-                                }
-                            }
-                            ValueOptions::Under => {
-                                if forecast.temp_low > observation.temp_low.round() as i64 {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Score wind_speed if enabled for this event
-                if scoring_fields.contains(&ScoringField::WindSpeed) {
-                    if let Some(wind_speed_choice) = choice.wind_speed.clone() {
-                        match forecast.wind_speed {
-                            // NOAA provided a wind forecast - normal scoring
-                            Some(forecast_wind) => match wind_speed_choice {
-                                ValueOptions::Over => {
-                                    if forecast_wind < observation.wind_speed {
-                                        base_score += OVER_OR_UNDER_POINTS;
-                                    }
-                                }
-                                ValueOptions::Par => {
-                                    if forecast_wind == observation.wind_speed {
-                                        base_score += PAR_POINTS;
-                                    }
-                                }
-                                ValueOptions::Under => {
-                                    if forecast_wind > observation.wind_speed {
-                                        base_score += OVER_OR_UNDER_POINTS;
-                                    }
-                                }
-                            },
-                            // NOAA didn't forecast wind (implying 0/calm) - compare against actual
-                            None => {
-                                let implicit_forecast = 0; // NOAA's implicit calm prediction
-                                match wind_speed_choice {
-                                    ValueOptions::Over => {
-                                        if implicit_forecast < observation.wind_speed {
-                                            base_score += OVER_OR_UNDER_POINTS;
-                                        }
-                                    }
-                                    ValueOptions::Par => {
-                                        if implicit_forecast == observation.wind_speed {
-                                            base_score += PAR_POINTS;
-                                        }
-                                    }
-                                    ValueOptions::Under => {
-                                        if implicit_forecast > observation.wind_speed {
-                                            base_score += OVER_OR_UNDER_POINTS;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Score wind_direction if enabled
-                // Default to 0 if data is not available
-                if scoring_fields.contains(&ScoringField::WindDirection) {
-                    if let Some(wind_dir_choice) = &choice.wind_direction {
-                        let forecast_dir = forecast.wind_direction.unwrap_or(0);
-                        let observed_dir = observation.wind_direction.unwrap_or(0);
-                        // Wind direction comparison: consider "par" if within 22.5 degrees
-                        let diff = ((forecast_dir - observed_dir).abs() % 360)
-                            .min(360 - ((forecast_dir - observed_dir).abs() % 360));
-                        match wind_dir_choice {
-                            ValueOptions::Over => {
-                                if observed_dir > forecast_dir {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                            ValueOptions::Par => {
-                                if diff <= 22 {
-                                    base_score += PAR_POINTS;
-                                }
-                            }
-                            ValueOptions::Under => {
-                                if observed_dir < forecast_dir {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Score rain_amt if enabled
-                // Default to 0.0 if data is not available
-                if scoring_fields.contains(&ScoringField::RainAmt) {
-                    if let Some(rain_choice) = &choice.rain_amt {
-                        let forecast_rain = forecast.rain_amt.unwrap_or(0.0);
-                        let observed_rain = observation.rain_amt.unwrap_or(0.0);
-                        match rain_choice {
-                            ValueOptions::Over => {
-                                if observed_rain > forecast_rain {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                            ValueOptions::Par => {
-                                // Par for rain: within 0.1 inches
-                                if (observed_rain - forecast_rain).abs() <= 0.1 {
-                                    base_score += PAR_POINTS;
-                                }
-                            }
-                            ValueOptions::Under => {
-                                if observed_rain < forecast_rain {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Score snow_amt if enabled
-                // Default to 0.0 if data is not available
-                if scoring_fields.contains(&ScoringField::SnowAmt) {
-                    if let Some(snow_choice) = &choice.snow_amt {
-                        let forecast_snow = forecast.snow_amt.unwrap_or(0.0);
-                        let observed_snow = observation.snow_amt.unwrap_or(0.0);
-                        match snow_choice {
-                            ValueOptions::Over => {
-                                if observed_snow > forecast_snow {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                            ValueOptions::Par => {
-                                // Par for snow: within 0.5 inches
-                                if (observed_snow - forecast_snow).abs() <= 0.5 {
-                                    base_score += PAR_POINTS;
-                                }
-                            }
-                            ValueOptions::Under => {
-                                if observed_snow < forecast_snow {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Score humidity if enabled
-                // Default to 0 if data is not available
-                if scoring_fields.contains(&ScoringField::Humidity) {
-                    if let Some(humidity_choice) = &choice.humidity {
-                        // Use forecast humidity_max for comparison
-                        let forecast_humidity = forecast.humidity_max.unwrap_or(0);
-                        let observed_humidity = observation.humidity.unwrap_or(0);
-                        match humidity_choice {
-                            ValueOptions::Over => {
-                                if observed_humidity > forecast_humidity {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                            ValueOptions::Par => {
-                                // Par for humidity: within 5%
-                                if (observed_humidity - forecast_humidity).abs() <= 5 {
-                                    base_score += PAR_POINTS;
-                                }
-                            }
-                            ValueOptions::Under => {
-                                if observed_humidity < forecast_humidity {
-                                    base_score += OVER_OR_UNDER_POINTS;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            let (created_at_secs, created_at_nano) = entry
-                .id
-                .get_timestamp()
-                .expect("UUIDv7 should have timestamp")
-                .to_unix();
-            let time_millis = (created_at_secs * 1000) + (created_at_nano as u64 / 1_000_000);
-
-            // Scoring logic: score * 10^4 - timestamp
-            // Using 4 digits for timestamp (keeping within the 10000 range as before)
-            // Limit timestamp to last 4 digits (mod 10000) to maintain consistency with old code
-            let timestamp_part = time_millis % 10000;
-            // Use this to ensure uniqueness:
-            let total_score = (std::cmp::max(10000, base_score * 10000) - timestamp_part) as i64;
-
-            /* With our formula score * 10^4 - timestamp:
-            - Higher base scores will still dominate (primary sorting criterion)
-            - For equal scores, earlier entries (smaller timestamps) will result in higher total scores
-              which means they'll rank higher when sorting in descending order
-
-            This maintains the original constraints:
-            - Up to 10,000 entries over 24h with negligible collision risk
-            - Scales well for concurrent entry creation
-            - Keeps the amount of possible outcomes for the DLC as low as possible
-            */
+            let (base_score, total_score) = score_entry(
+                &entry,
+                &event.locations,
+                &event.scoring_fields,
+                &event.par_tolerances,
+                event.scoring_mode,
+                &forecast_data,
+                &observation_data,
+            );
 
             info!(
                 "updating entry {} for event {} to score {} in etl process {}",
                 entry.id, event.id, total_score, etl_process_id
             );
 
-            entry_scores.push((entry.id, total_score, base_score as i64));
+            entry_scores.push((entry.id, total_score, base_score));
         }
 
         self.db.update_entry_scores(entry_scores).await?;
@@ -778,7 +1265,7 @@ impl Oracle {
         etl_process_id: usize,
         event_ids: Vec<Uuid>,
     ) -> Result<(), Error> {
-        let mut events: Vec<SignEvent> = self.db.get_events_to_sign(event_ids).await?;
+        let mut events: Vec<SignEvent> = self.db.get_events_to_sign(event_ids, self.now()).await?;
         info!("events: {:?}", events);
         for event in events.iter_mut() {
             let entries = self.db.get_event_weather_entries(&event.id).await?;
@@ -786,41 +1273,12 @@ impl Oracle {
             // very important, the sort index of the entry should always be the same when getting the outcome
             entry_indices.sort_by_key(|entry| entry.id);
 
-            if event.signing_date < OffsetDateTime::now_utc() {
-                let all_zero_scores = entries
-                    .iter()
-                    .all(|entry| entry.base_score.is_none() || entry.base_score == Some(0));
-
-                let winners = if all_zero_scores && !entries.is_empty() {
-                    let all_indices: Vec<usize> = (0..entry_indices.len()).collect();
-
-                    all_indices.clone()
-                } else {
-                    // Sort by score descending for winners
-                    let mut top_entries: Vec<_> = entries
-                        .iter()
-                        .filter(|entry| entry.score.is_some())
-                        .cloned()
-                        .collect();
-                    top_entries.sort_by_key(|entry| cmp::Reverse(entry.score));
-                    top_entries.truncate(event.number_of_places_win as usize);
-
-                    // Get indices of winners in original entry_indices order
-                    let winners: Vec<usize> = top_entries
-                        .iter()
-                        .map(|top_entry| {
-                            entry_indices
-                                .iter()
-                                .position(|entry| entry.id == top_entry.id)
-                                .expect("Entry should exist")
-                        })
-                        .collect();
-
-                    winners
-                };
+            if event.signing_date < self.now() {
+                let winners = rank_winners(&entries, event.number_of_places_win as usize);
 
                 let nonce_point = event.nonce.base_point_mul();
-                let winner_bytes = get_winning_bytes(winners.clone());
+                let winner_bytes =
+                    get_winning_bytes(winners.clone(), event.outcome_message_version);
 
                 let locking_point =
                     attestation_locking_point(self.public_key, nonce_point, &winner_bytes);
@@ -848,7 +1306,7 @@ impl Oracle {
 
                 let attestation = attestation_secret(self.private_key, event.nonce, &winner_bytes);
                 event.attestation = Some(attestation);
-                self.db.update_event_attestation(event).await?;
+                self.db.update_event_attestation(event, self.now()).await?;
             }
         }
         info!(
@@ -858,32 +1316,55 @@ impl Oracle {
         Ok(())
     }
 
-    async fn event_forecast_data(&self, event: &ActiveEvent) -> Result<Vec<Forecast>, Error> {
+    async fn event_forecast_data(
+        &self,
+        locations: &[String],
+        start: OffsetDateTime,
+        end: OffsetDateTime,
+    ) -> Result<Vec<Forecast>, Error> {
         // Assumes locations have been sanitized when the event was created
-        let station_ids = event.locations.join(",");
+        let station_ids = locations.join(",");
         let forecast_requests = ForecastRequest {
-            start: Some(event.start_observation_date),
-            end: Some(event.end_observation_date),
+            start: Some(start),
+            end: Some(end),
             generated_start: None,
             generated_end: None,
+            as_of: None,
             station_ids: station_ids.clone(),
             temperature_unit: TemperatureUnit::Fahrenheit,
+            wind_unit: None,
+            // Entries and par tolerances are scored in inches whatever the API displays
+            precip_unit: PrecipUnit::Inches,
+            agg: ForecastAggregation::default(),
+            fields: ForecastFields::default(),
+            granularity: None,
         };
         self.weather_data
-            .forecasts_data(&forecast_requests, event.locations.clone())
+            .forecasts_data(&forecast_requests, locations.to_vec())
             .await
             .map_err(Error::WeatherData)
     }
 
-    async fn event_observation_data(&self, event: &ActiveEvent) -> Result<Vec<Observation>, Error> {
+    async fn event_observation_data(
+        &self,
+        locations: &[String],
+        start: OffsetDateTime,
+        end: OffsetDateTime,
+    ) -> Result<Vec<Observation>, Error> {
         let observation_requests = ObservationRequest {
-            start: Some(event.start_observation_date),
-            end: Some(event.end_observation_date),
-            station_ids: event.locations.join(","),
+            start: Some(start),
+            end: Some(end),
+            station_ids: locations.join(","),
             temperature_unit: TemperatureUnit::Fahrenheit,
+            tz: DayTimeZone::default(),
+            temp_agg: ObservationTempAggregation::default(),
+            wind_agg: ObservationWindAggregation::default(),
+            wind_unit: None,
+            precip_unit: PrecipUnit::Inches,
+            granularity: None,
         };
         self.weather_data
-            .observation_data(&observation_requests, event.locations.clone())
+            .observation_data(&observation_requests, locations.to_vec())
             .await
             .map_err(Error::WeatherData)
     }
@@ -893,17 +1374,343 @@ impl Oracle {
         self.db.health_check().await
     }
 
+    pub async fn migration_status(&self) -> Result<MigrationStatus, Error> {
+        self.db.migration_status().await.map_err(Error::ValidateKey)
+    }
+
+    pub async fn db_maintenance(&self, vacuum: bool) -> Result<MaintenanceReport, Error> {
+        self.db
+            .maintenance(vacuum)
+            .await
+            .map_err(Error::ValidateKey)
+    }
+
+    /// Removes events whose signing date is older than `retention`, whether or not they were
+    /// signed, along with their entries and weather
+    pub async fn purge_expired_events(&self, retention: Duration) -> Result<u64, Error> {
+        let cutoff = self.now() - retention;
+        let purged = self
+            .db
+            .purge_events_before(cutoff, self.now())
+            .await
+            .map_err(Error::ValidateKey)?;
+        info!(
+            "purged {} events with a signing date before {}",
+            purged, cutoff
+        );
+        Ok(purged)
+    }
+
     /// Checkpoint WAL to main database file before shutdown.
     pub async fn checkpoint(&self) {
         self.db.checkpoint().await;
     }
 }
 
-pub fn get_winning_bytes(winners: Vec<usize>) -> Vec<u8> {
-    winners
+/// Puts an event's persisted readings over the ones read back from the weather files, so an
+/// audit scores what the oracle stored. Unrounded observed temperatures are kept when they round
+/// to the stored value, proximity scoring measures them in hundredths.
+fn overlay_persisted_weather(
+    weather: &[Weather],
+    forecast_data: &mut Vec<Forecast>,
+    observation_data: &mut Vec<Observation>,
+) {
+    for stored in weather {
+        let forecasted = &stored.forecasted;
+        match forecast_data
+            .iter_mut()
+            .find(|forecast| forecast.station_id == stored.station_id)
+        {
+            Some(forecast) => {
+                forecast.temp_low = forecasted.temp_low;
+                forecast.temp_high = forecasted.temp_high;
+                forecast.wind_speed = forecasted.wind_speed;
+            }
+            None => forecast_data.push(Forecast {
+                station_id: stored.station_id.clone(),
+                date: forecasted.date.date().to_string(),
+                start_time: forecasted.date.format(&Rfc3339).unwrap_or_default(),
+                end_time: (forecasted.date + Duration::DAY)
+                    .format(&Rfc3339)
+                    .unwrap_or_default(),
+                temp_low: forecasted.temp_low,
+                temp_high: forecasted.temp_high,
+                wind_speed: forecasted.wind_speed,
+                wind_direction: None,
+                humidity_max: None,
+                humidity_min: None,
+                temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+                precip_chance: None,
+                rain_amt: None,
+                snow_amt: None,
+                ice_amt: None,
+                precip_unit_code: PrecipUnit::Inches.to_string(),
+                dominant_precip: None,
+                bucket: None,
+            }),
+        }
+
+        let Some(observed) = &stored.observed else {
+            continue;
+        };
+        match observation_data
+            .iter_mut()
+            .find(|observation| observation.station_id == stored.station_id)
+        {
+            Some(observation) => {
+                if observation.temp_low.round() as i64 != observed.temp_low {
+                    observation.temp_low = observed.temp_low as f64;
+                }
+                if observation.temp_high.round() as i64 != observed.temp_high {
+                    observation.temp_high = observed.temp_high as f64;
+                }
+                observation.wind_speed = observed.wind_speed;
+            }
+            None => observation_data.push(Observation {
+                station_id: stored.station_id.clone(),
+                start_time: observed.date.format(&Rfc3339).unwrap_or_default(),
+                end_time: (observed.date + Duration::DAY)
+                    .format(&Rfc3339)
+                    .unwrap_or_default(),
+                temp_low: observed.temp_low as f64,
+                temp_high: observed.temp_high as f64,
+                wind_speed: observed.wind_speed,
+                temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+                wind_direction: None,
+                humidity: None,
+                rain_amt: None,
+                snow_amt: None,
+                ice_amt: None,
+                precip_unit_code: PrecipUnit::Inches.to_string(),
+                bucket: None,
+            }),
+        }
+    }
+}
+
+/// Winning entries as indices into the entries sorted by id, or every index (a refund) when no
+/// entry scored. The signature commits to these indices, so this ordering must never change.
+fn rank_winners(entries: &[WeatherEntry], number_of_places_win: usize) -> Vec<usize> {
+    let mut entry_indices = entries.to_vec();
+    entry_indices.sort_by_key(|entry| entry.id);
+
+    let all_zero_scores = entries
         .iter()
-        .flat_map(|&idx| idx.to_be_bytes())
-        .collect::<Vec<u8>>()
+        .all(|entry| entry.base_score.is_none() || entry.base_score == Some(0));
+    if all_zero_scores && !entries.is_empty() {
+        return (0..entry_indices.len()).collect();
+    }
+
+    // Sort by score descending for winners
+    let mut top_entries: Vec<_> = entries
+        .iter()
+        .filter(|entry| entry.score.is_some())
+        .collect();
+    top_entries.sort_by_key(|entry| cmp::Reverse(entry.score));
+    top_entries.truncate(number_of_places_win);
+
+    // Get indices of winners in original entry_indices order
+    top_entries
+        .iter()
+        .map(|top_entry| {
+            entry_indices
+                .iter()
+                .position(|entry| entry.id == top_entry.id)
+                .expect("Entry should exist")
+        })
+        .collect()
+}
+
+/// Base and total score of one entry against the event's weather, shared by the ETL and the
+/// read-only audit so both always score the same way
+fn score_entry(
+    entry: &WeatherEntry,
+    locations: &[String],
+    scoring_fields: &[ScoringField],
+    par_tolerances: &ParTolerances,
+    scoring_mode: ScoringMode,
+    forecast_data: &[Forecast],
+    observation_data: &[Observation],
+) -> (i64, i64) {
+    // Score logic, match on Par 2pts, on Over 1pt, on Under 1pt, created_at used as tie breaker (older > newer)
+    // Each field's observation is classified against the forecast using the event's par band
+    // Proximity events instead total how far each numeric prediction landed from the observation
+    let mut base_score = 0;
+    let mut distance = 0.0;
+    const OVER_OR_UNDER_POINTS: u64 = 10;
+    const PAR_POINTS: u64 = 20;
+    let expected_observations = &entry.expected_observations;
+    for location in locations {
+        let Some(choice) = expected_observations
+            .iter()
+            .find(|expected| &expected.stations == location)
+        else {
+            continue;
+        };
+
+        let Some(observation) = observation_data
+            .iter()
+            .find(|observation| &observation.station_id == location)
+        else {
+            warn!("no observation found for: {}", location);
+            continue;
+        };
+
+        if scoring_mode == ScoringMode::Proximity {
+            for field in ScoringField::ALL
+                .iter()
+                .filter(|field| scoring_fields.contains(field))
+            {
+                if let Some(predicted) = choice.prediction_for(field) {
+                    distance += prediction_distance(field, observation, predicted);
+                }
+            }
+            continue;
+        }
+
+        let Some(forecast) = forecast_data
+            .iter()
+            .find(|forecast| &forecast.station_id == location)
+        else {
+            warn!("no forecast found for: {}", location);
+            continue;
+        };
+
+        for field in ScoringField::ALL
+            .iter()
+            .filter(|field| scoring_fields.contains(field))
+        {
+            let Some(picked) = choice.choice_for(field) else {
+                continue;
+            };
+            let tolerance = field.par_tolerance(par_tolerances);
+            let landed = classify_field(field, forecast, observation, tolerance);
+            if *picked == landed {
+                base_score += match landed {
+                    ValueOptions::Par => PAR_POINTS,
+                    ValueOptions::Over | ValueOptions::Under => OVER_OR_UNDER_POINTS,
+                };
+            }
+        }
+    }
+    let (created_at_secs, created_at_nano) = entry
+        .id
+        .get_timestamp()
+        .expect("UUIDv7 should have timestamp")
+        .to_unix();
+    let time_millis = (created_at_secs * 1000) + (created_at_nano as u64 / 1_000_000);
+
+    // Scoring logic: score * 10^4 - timestamp
+    // Using 4 digits for timestamp (keeping within the 10000 range as before)
+    // Limit timestamp to last 4 digits (mod 10000) to maintain consistency with old code
+    let timestamp_part = time_millis % 10000;
+    // Use this to ensure uniqueness:
+    let (base_score, total_score) = match scoring_mode {
+        ScoringMode::Ternary => (
+            base_score as i64,
+            (std::cmp::max(10000, base_score * 10000) - timestamp_part) as i64,
+        ),
+        ScoringMode::Proximity => {
+            // Closer is better, so the distance (in hundredths) is negated to keep the highest score winning.
+            // The extra -1 keeps a perfect, tie breaking score off zero, which reads back as unscored
+            // Predictions are bounded when entries are added, saturating keeps a score that still
+            // got out of range at the bottom of the ranking instead of wrapping to the top
+            let base_score = -((distance * 100.0).round() as i64);
+            (
+                base_score,
+                base_score
+                    .saturating_mul(10000)
+                    .saturating_sub(timestamp_part as i64 + 1),
+            )
+        }
+    };
+
+    /* With our formula score * 10^4 - timestamp:
+    - Higher base scores will still dominate (primary sorting criterion)
+    - For equal scores, earlier entries (smaller timestamps) will result in higher total scores
+      which means they'll rank higher when sorting in descending order
+
+    This maintains the original constraints:
+    - Up to 10,000 entries over 24h with negligible collision risk
+    - Scales well for concurrent entry creation
+    - Keeps the amount of possible outcomes for the DLC as low as possible
+    */
+
+    (base_score, total_score)
+}
+
+/// Where a station's observation landed against its forecast for one scoring field, missing
+/// readings count as 0 (NOAA leaving out a wind forecast implies calm)
+fn classify_field(
+    field: &ScoringField,
+    forecast: &Forecast,
+    observation: &Observation,
+    tolerance: f64,
+) -> ValueOptions {
+    match field {
+        ScoringField::TempHigh => ValueOptions::classify(
+            forecast.temp_high as f64,
+            observation.temp_high.round(),
+            tolerance,
+        ),
+        ScoringField::TempLow => ValueOptions::classify(
+            forecast.temp_low as f64,
+            observation.temp_low.round(),
+            tolerance,
+        ),
+        ScoringField::WindSpeed => ValueOptions::classify(
+            forecast.wind_speed.unwrap_or(0) as f64,
+            observation.wind_speed as f64,
+            tolerance,
+        ),
+        ScoringField::WindDirection => {
+            let forecast_dir = forecast.wind_direction.unwrap_or(0);
+            let observed_dir = observation.wind_direction.unwrap_or(0);
+            // headings wrap, so par is measured the short way around the compass
+            let diff = (forecast_dir - observed_dir).abs() % 360;
+            if diff.min(360 - diff) as f64 <= tolerance {
+                ValueOptions::Par
+            } else {
+                ValueOptions::classify(forecast_dir as f64, observed_dir as f64, 0.0)
+            }
+        }
+        ScoringField::RainAmt => ValueOptions::classify(
+            forecast.rain_amt.unwrap_or(0.0),
+            observation.rain_amt.unwrap_or(0.0),
+            tolerance,
+        ),
+        ScoringField::SnowAmt => ValueOptions::classify(
+            forecast.snow_amt.unwrap_or(0.0),
+            observation.snow_amt.unwrap_or(0.0),
+            tolerance,
+        ),
+        ScoringField::Humidity => ValueOptions::classify(
+            forecast.humidity_max.unwrap_or(0) as f64,
+            observation.humidity.unwrap_or(0) as f64,
+            tolerance,
+        ),
+    }
+}
+
+/// How far a numeric prediction landed from the observation, in the field's own unit
+fn prediction_distance(field: &ScoringField, observation: &Observation, predicted: f64) -> f64 {
+    match field {
+        ScoringField::TempHigh => (observation.temp_high - predicted).abs(),
+        ScoringField::TempLow => (observation.temp_low - predicted).abs(),
+        ScoringField::WindSpeed => (observation.wind_speed as f64 - predicted).abs(),
+        ScoringField::WindDirection => {
+            // headings wrap, so the distance is measured the short way around the compass
+            let diff = (observation.wind_direction.unwrap_or(0) as f64 - predicted).abs() % 360.0;
+            diff.min(360.0 - diff)
+        }
+        ScoringField::RainAmt => (observation.rain_amt.unwrap_or(0.0) - predicted).abs(),
+        ScoringField::SnowAmt => (observation.snow_amt.unwrap_or(0.0) - predicted).abs(),
+        ScoringField::Humidity => (observation.humidity.unwrap_or(0) as f64 - predicted).abs(),
+    }
+}
+
+pub fn get_winning_bytes(winners: Vec<usize>, version: OutcomeMessageVersion) -> Vec<u8> {
+    version.message(&winners)
 }
 
 async fn add_only_forecast_data(
@@ -979,6 +1786,39 @@ fn get_key(file_path: &String) -> Result<SecretKey, anyhow::Error> {
     }
 }
 
+/// Checks the key at `file_path` is usable without generating it: an existing file has to
+/// parse, a missing one needs a writable directory to be generated into
+pub fn check_key_file(file_path: &String) -> Result<(), anyhow::Error> {
+    if !is_pem_file(file_path) {
+        return Err(anyhow!("not a '.pem' file extension"));
+    }
+    if metadata(file_path).is_ok() {
+        return read_key(file_path)
+            .map(|_| ())
+            .map_err(|e| anyhow!("can't read key {}: {}", file_path, e));
+    }
+
+    let dir = match Path::new(file_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if !dir.is_dir() {
+        return Err(anyhow!(
+            "{} doesn't exist and its directory {} is missing",
+            file_path,
+            dir.display()
+        ));
+    }
+    check_writable_dir(&dir.to_string_lossy()).map_err(|e| {
+        anyhow!(
+            "{} doesn't exist and can't be generated in {}: {}",
+            file_path,
+            dir.display(),
+            e
+        )
+    })
+}
+
 fn generate_new_key() -> SecretKey {
     SecretKey::new(&mut rand::thread_rng())
 }
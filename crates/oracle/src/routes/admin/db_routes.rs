@@ -0,0 +1,40 @@
+use crate::{AppState, MaintenanceReport};
+use axum::{
+    extract::{Query, State},
+    response::ErrorResponse,
+    Json,
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Clone, Default, Serialize, Deserialize, IntoParams)]
+pub struct MaintenanceParams {
+    /// Also run `VACUUM` to rebuild the database file and release free pages, defaults to false
+    #[serde(default)]
+    pub vacuum: bool,
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/db/maintenance",
+    params(MaintenanceParams),
+    responses(
+        (status = OK, description = "Successfully ran event database maintenance", body = MaintenanceReport),
+        (status = INTERNAL_SERVER_ERROR, description = "Failed to run event database maintenance"),
+    ))]
+pub async fn db_maintenance(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<MaintenanceParams>,
+) -> Result<Json<MaintenanceReport>, ErrorResponse> {
+    state
+        .oracle
+        .db_maintenance(params.vacuum)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error running db maintenance: {}", e);
+            e.into()
+        })
+}
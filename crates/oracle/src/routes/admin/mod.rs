@@ -0,0 +1,3 @@
+pub mod db_routes;
+
+pub use db_routes::*;
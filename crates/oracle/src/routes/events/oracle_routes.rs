@@ -1,22 +1,31 @@
 use crate::{
-    oracle, AddEventEntries, AppState, CreateEvent, Event, EventFilter, EventSummary, NostrAuth,
-    WeatherEntry,
+    oracle, AddEventEntries, AppState, AttestationVerification, BatchEvent, ByteEncoding,
+    CloneEvent, CreateEvent, Event, EventAggregates, EventAnnouncement, EventAttestation,
+    EventAudit, EventChanges, EventFilter, EventIncludes, EventSummary, GetEventsBatch, NostrAuth,
+    TemperatureUnit, VerifyAttestation, Weather, WeatherEntry,
 };
+use anyhow::anyhow;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{
+        header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
     response::{ErrorResponse, IntoResponse, Response},
     Json,
 };
 use log::{error, info};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::{borrow::Borrow, sync::Arc};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio::task;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, PartialSchema, ToSchema};
 use uuid::Uuid;
 
+const OCTET_STREAM: &str = "application/octet-stream";
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Base64Pubkey {
     /// base64 representation of the compressed DER encoding of the publickey. This consists of a parity
@@ -31,6 +40,40 @@ pub struct Pubkey {
     pub key: String,
 }
 
+/// Everything a client needs to identify the oracle when building a DLC
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OracleInfo {
+    /// Name stored in the oracle metadata
+    pub name: String,
+    /// base64 representation of the compressed public key, same as `/oracle/pubkey`
+    pub pubkey: String,
+    /// hex representation of the 32 byte x-only public key
+    pub xonly_pubkey: String,
+    /// nostr npub in string format
+    pub npub: String,
+}
+
+/// The key only changes across restarts with a different key file, so clients can cache for an hour
+const ORACLE_INFO_CACHE_CONTROL: &str = "public, max-age=3600";
+
+#[utoipa::path(
+    get,
+    path = "/oracle",
+    responses(
+        (status = OK, description = "Successfully retrieved oracle's public key and metadata", body = OracleInfo),
+    ))]
+pub async fn get_oracle_info(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ErrorResponse> {
+    let info = OracleInfo {
+        name: state.oracle.name().to_owned(),
+        pubkey: state.oracle.public_key(),
+        xonly_pubkey: state.oracle.xonly_public_key(),
+        npub: state.oracle.npub()?,
+    };
+    Ok(([(CACHE_CONTROL, ORACLE_INFO_CACHE_CONTROL)], Json(info)))
+}
+
 #[utoipa::path(
     get,
     path = "/oracle/pubkey",
@@ -57,45 +100,211 @@ pub async fn get_npub(State(state): State<Arc<AppState>>) -> Result<Json<Pubkey>
     }))
 }
 
+#[derive(Clone, Default, Serialize, Deserialize, IntoParams)]
+pub struct EncodingParams {
+    /// Re-encode `nonce`, `attestation` and the announcement's locking points as `hex` or `base64`,
+    /// see `ByteEncoding` for the byte layout (default: the dlctix hex serialization)
+    #[param(inline)]
+    pub encoding: Option<ByteEncoding>,
+}
+
+/// Serializes the response as is unless the client asked for a specific encoding of its byte fields
+fn encoded_json<T: Serialize>(body: T, encoding: Option<ByteEncoding>) -> Response {
+    let Some(encoding) = encoding else {
+        return Json(body).into_response();
+    };
+    let reencoded = serde_json::to_value(body)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut value| {
+            match &mut value {
+                Value::Array(events) => events
+                    .iter_mut()
+                    .try_for_each(|event| encoding.reencode_event(event))?,
+                event => encoding.reencode_event(event)?,
+            }
+            Ok(value)
+        });
+    match reencoded {
+        Ok(value) => Json(value).into_response(),
+        Err(e) => {
+            error!("error re-encoding event response: {}", e);
+            oracle::Error::ValidateKey(e).into_response()
+        }
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/oracle/events",
-    params(EventFilter),
+    params(EventFilter, EncodingParams),
     responses(
         (status = OK, description = "Successfully retrieved oracle events", body = Vec<Event>),
     ))]
 pub async fn list_events(
     State(state): State<Arc<AppState>>,
     Query(filter): Query<EventFilter>,
-) -> Result<Json<Vec<EventSummary>>, ErrorResponse> {
+    Query(params): Query<EncodingParams>,
+) -> Result<Response, ErrorResponse> {
     state
         .oracle
         .list_events(filter)
         .await
-        .map(Json)
+        .map(|events: Vec<EventSummary>| encoded_json(events, params.encoding))
         .map_err(|e| {
             error!("error retrieving event data: {}", e);
             e.into()
         })
 }
+/// Default look-back for `signed_recently` in the event stats
+pub const DEFAULT_STATS_WINDOW_DAYS: u32 = 7;
+/// Longest look-back the event stats accept, roughly ten years
+pub const MAX_STATS_WINDOW_DAYS: u32 = 3650;
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct EventStatsParams {
+    /// Days back from now that `signed_recently` counts, defaults to 7
+    pub window_days: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/oracle/events/stats",
+    params(EventStatsParams),
+    responses(
+        (status = OK, description = "Successfully computed event aggregates", body = EventAggregates),
+        (status = BAD_REQUEST, description = "Window longer than the maximum allowed"),
+    ))]
+pub async fn event_stats(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EventStatsParams>,
+) -> Result<Json<EventAggregates>, ErrorResponse> {
+    let window_days = params.window_days.unwrap_or(DEFAULT_STATS_WINDOW_DAYS);
+    if window_days > MAX_STATS_WINDOW_DAYS {
+        let err = oracle::Error::BadEvent(anyhow!(
+            "window_days {} is longer than the max of {}",
+            window_days,
+            MAX_STATS_WINDOW_DAYS
+        ));
+        error!("error event stats: {}", err);
+        return Err(err.into());
+    }
+    state
+        .oracle
+        .event_aggregates(window_days)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error computing event stats: {}", e);
+            e.into()
+        })
+}
+
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct EventChangesParams {
+    /// Start of the window (RFC 3339, inclusive), usually the `until` of the previous poll
+    #[serde(with = "time::serde::rfc3339")]
+    #[param(value_type = String, format = DateTime)]
+    pub since: OffsetDateTime,
+}
+
+#[utoipa::path(
+    get,
+    path = "/oracle/events/changes",
+    params(EventChangesParams),
+    responses(
+        (status = OK, description = "Events changed and ids deleted since the given time", body = EventChanges),
+        (status = BAD_REQUEST, description = "Missing or malformed since"),
+    ))]
+pub async fn event_changes(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EventChangesParams>,
+) -> Result<Json<EventChanges>, ErrorResponse> {
+    state
+        .oracle
+        .event_changes(params.since)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error listing event changes: {}", e);
+            e.into()
+        })
+}
+
+/// What `POST /oracle/events` expects, for integrators building their first request
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateEventSchema {
+    /// JSON schema of the `CreateEvent` body, the types it refers to are under `components.schemas`
+    #[schema(value_type = Object)]
+    pub schema: Value,
+    /// A body the oracle accepts as is, with a fresh uuidv7 and observations over tomorrow (UTC)
+    pub example: CreateEvent,
+}
+
+/// `CreateEvent`'s derived schema with every schema it references attached, so its
+/// `#/components/schemas/...` refs resolve within the returned document
+pub fn create_event_json_schema() -> Result<Value, serde_json::Error> {
+    let mut referenced = Vec::new();
+    <CreateEvent as ToSchema>::schemas(&mut referenced);
+    let mut components = Map::new();
+    for (name, schema) in referenced {
+        components.insert(name, serde_json::to_value(schema)?);
+    }
+    let mut schema = serde_json::to_value(CreateEvent::schema())?;
+    schema["components"] = json!({ "schemas": components });
+    Ok(schema)
+}
+
+#[utoipa::path(
+    get,
+    path = "/oracle/events/schema",
+    responses(
+        (status = OK, description = "JSON schema of the create event body and a ready to send example", body = CreateEventSchema),
+    ))]
+pub async fn create_event_schema(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<CreateEventSchema>, ErrorResponse> {
+    let example = state
+        .oracle
+        .example_event(state.oracle.now())
+        .await
+        .map_err(|e| {
+            error!("error building example event: {}", e);
+            ErrorResponse::from(e)
+        })?;
+    let schema = create_event_json_schema().map_err(|e| {
+        error!("error serializing create event schema: {}", e);
+        ErrorResponse::from(oracle::Error::Validation(e))
+    })?;
+    Ok(Json(CreateEventSchema { schema, example }))
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, IntoParams)]
+pub struct CreateEventParams {
+    /// Accept locations the oracle has no weather data for yet, for stations expected to start
+    /// reporting before the event (default: false, unknown station ids are rejected)
+    pub skip_station_check: Option<bool>,
+}
+
 #[utoipa::path(
     post,
     path = "/oracle/events",
     request_body = CreateEvent,
+    params(CreateEventParams),
     responses(
         (status = OK, description = "Successfully created oracle weather event", body = Event),
-        (status = BAD_REQUEST, description = "Invalid event to be created"),
+        (status = BAD_REQUEST, description = "Invalid event to be created, including unknown station ids"),
         (status = FORBIDDEN, description = "Invalid signature from coordinator in nostr authorization header"),
         (status = UNAUTHORIZED, description = "Invalid nostr authorization header nip-98 using coordinator keys"),
     ))]
 pub async fn create_event(
     NostrAuth { pubkey, .. }: NostrAuth,
     State(state): State<Arc<AppState>>,
+    Query(params): Query<CreateEventParams>,
     Json(body): Json<CreateEvent>,
 ) -> Result<Json<Event>, ErrorResponse> {
     state
         .oracle
-        .create_event(pubkey, body)
+        .create_event(pubkey, body, params.skip_station_check.unwrap_or(false))
         .await
         .map(Json)
         .map_err(|e| {
@@ -104,31 +313,297 @@ pub async fn create_event(
         })
 }
 
+#[utoipa::path(
+    post,
+    path = "/oracle/events/{event_id}/clone",
+    request_body = CloneEvent,
+    params(
+        ("event_id" = Uuid, Path, description = "ID of the weather event to copy the locations, scoring and settings from"),
+        CreateEventParams
+    ),
+    responses(
+        (status = OK, description = "Successfully created the cloned oracle weather event, without the source's entries or attestation", body = Event),
+        (status = BAD_REQUEST, description = "Invalid dates for the new event or a start observation date in the past"),
+        (status = NOT_FOUND, description = "Event to clone not found"),
+        (status = FORBIDDEN, description = "Invalid signature from coordinator in nostr authorization header, or the event belongs to another coordinator"),
+        (status = UNAUTHORIZED, description = "Invalid nostr authorization header nip-98 using coordinator keys"),
+    ))]
+pub async fn clone_event(
+    NostrAuth { pubkey, .. }: NostrAuth,
+    State(state): State<Arc<AppState>>,
+    Path(event_id): Path<Uuid>,
+    Query(params): Query<CreateEventParams>,
+    Json(body): Json<CloneEvent>,
+) -> Result<Json<Event>, ErrorResponse> {
+    state
+        .oracle
+        .clone_event(
+            pubkey,
+            &event_id,
+            body,
+            params.skip_station_check.unwrap_or(false),
+        )
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error cloning event {}: {}", event_id, e);
+            e.into()
+        })
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, IntoParams)]
+pub struct GetEventParams {
+    /// Comma separated sections to load with the event metadata: `entries`, `weather`, `entry_choices`
+    /// (`entry_choices` implies `entries`), only metadata is returned when omitted
+    pub include: Option<String>,
+    /// Re-encode `nonce`, `attestation` and the announcement's locking points as `hex` or `base64`,
+    /// see `ByteEncoding` for the byte layout (default: the dlctix hex serialization)
+    #[param(inline)]
+    pub encoding: Option<ByteEncoding>,
+}
+
 #[utoipa::path(
     get,
     path = "/oracle/events/{event_id}",
     params(
         ("event_id" = Uuid, Path, description = "ID of a weather event the oracle is tracking"),
+        GetEventParams
     ),
     responses(
         (status = OK, description = "Successfully retrieved event data", body = Event),
+        (status = BAD_REQUEST, description = "Unknown section requested in include"),
         (status = NOT_FOUND, description = "Event not found for the provided ID"),
     ))]
 pub async fn get_event(
     State(state): State<Arc<AppState>>,
     Path(event_id): Path<Uuid>,
-) -> Result<Json<Event>, ErrorResponse> {
+    Query(params): Query<GetEventParams>,
+) -> Result<Response, ErrorResponse> {
+    let includes = params
+        .include
+        .as_deref()
+        .unwrap_or_default()
+        .parse::<EventIncludes>()
+        .map_err(|e| {
+            error!("error event includes: {}", e);
+            oracle::Error::BadEvent(e)
+        })?;
     state
         .oracle
-        .get_event(&event_id)
+        .get_event_with(&event_id, &includes)
         .await
-        .map(Json)
+        .map(|event: Event| encoded_json(event, params.encoding))
         .map_err(|e| {
             error!("error event data: {}", e);
             e.into()
         })
 }
 
+#[utoipa::path(
+    post,
+    path = "/oracle/events/batch",
+    params(EncodingParams),
+    request_body = GetEventsBatch,
+    responses(
+        (status = OK, description = "Successfully retrieved events, ids without an event are marked not_found", body = Vec<BatchEvent>),
+        (status = BAD_REQUEST, description = "More ids requested than the oracle's max batch size"),
+    ))]
+pub async fn get_events_batch(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EncodingParams>,
+    Json(body): Json<GetEventsBatch>,
+) -> Result<Response, ErrorResponse> {
+    if body.ids.len() > state.max_batch_events {
+        let err = oracle::Error::BadEvent(anyhow!(
+            "requested {} events, max per batch is {}",
+            body.ids.len(),
+            state.max_batch_events
+        ));
+        error!("error batch event data: {}", err);
+        return Err(err.into());
+    }
+    state
+        .oracle
+        .get_events_batch(&body.ids)
+        .await
+        .map(|events: Vec<BatchEvent>| encoded_json(events, params.encoding))
+        .map_err(|e| {
+            error!("error batch event data: {}", e);
+            e.into()
+        })
+}
+
+/// Clients opt into raw bytes with `Accept: application/octet-stream`, everything else gets JSON
+fn wants_bytes(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains(OCTET_STREAM))
+}
+
+fn artifact_response<T: Serialize>(
+    headers: &HeaderMap,
+    artifact: T,
+    to_bytes: impl FnOnce(&T) -> Result<Vec<u8>, hex::FromHexError>,
+) -> Response {
+    if !wants_bytes(headers) {
+        return Json(artifact).into_response();
+    }
+    match to_bytes(&artifact) {
+        Ok(bytes) => ([(CONTENT_TYPE, OCTET_STREAM)], bytes).into_response(),
+        Err(e) => {
+            error!("error encoding event artifact: {}", e);
+            oracle::Error::ValidateKey(anyhow!(e)).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/oracle/events/{event_id}/announcement",
+    params(
+        ("event_id" = Uuid, Path, description = "ID of a weather event the oracle is tracking"),
+    ),
+    responses(
+        (status = OK, description = "Successfully retrieved the event announcement, raw bytes when requested with `Accept: application/octet-stream`", body = EventAnnouncement,
+            content(
+                (EventAnnouncement = "application/json"),
+                (Vec<u8> = "application/octet-stream"),
+            )),
+        (status = NOT_FOUND, description = "Event not found for the provided ID"),
+    ))]
+pub async fn get_event_announcement(
+    State(state): State<Arc<AppState>>,
+    Path(event_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, ErrorResponse> {
+    let announcement = state
+        .oracle
+        .get_event_announcement(&event_id)
+        .await
+        .map_err(|e| {
+            error!("error event announcement: {}", e);
+            ErrorResponse::from(e)
+        })?;
+    Ok(artifact_response(
+        &headers,
+        announcement,
+        EventAnnouncement::to_bytes,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/oracle/events/{event_id}/attestation",
+    params(
+        ("event_id" = Uuid, Path, description = "ID of a weather event the oracle is tracking"),
+    ),
+    responses(
+        (status = OK, description = "Successfully retrieved the event attestation, raw bytes when requested with `Accept: application/octet-stream`", body = EventAttestation,
+            content(
+                (EventAttestation = "application/json"),
+                (Vec<u8> = "application/octet-stream"),
+            )),
+        (status = NOT_FOUND, description = "Event not found for the provided ID or not signed yet"),
+    ))]
+pub async fn get_event_attestation(
+    State(state): State<Arc<AppState>>,
+    Path(event_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, ErrorResponse> {
+    let attestation = state
+        .oracle
+        .get_event_attestation(&event_id)
+        .await
+        .map_err(|e| {
+            error!("error event attestation: {}", e);
+            ErrorResponse::from(e)
+        })?;
+    Ok(artifact_response(
+        &headers,
+        attestation,
+        EventAttestation::to_bytes,
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/oracle/events/{event_id}/audit",
+    params(
+        ("event_id" = Uuid, Path, description = "ID of a signed weather event"),
+    ),
+    responses(
+        (status = OK, description = "Entry scores recomputed from the event's persisted weather alongside the stored ones, with any discrepancy flagged. Nothing is changed", body = EventAudit),
+        (status = NOT_FOUND, description = "Event not found for the provided ID or not signed yet"),
+    ))]
+pub async fn audit_event(
+    State(state): State<Arc<AppState>>,
+    Path(event_id): Path<Uuid>,
+) -> Result<Json<EventAudit>, ErrorResponse> {
+    state
+        .oracle
+        .audit_event(&event_id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error auditing event: {}", e);
+            e.into()
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/verify",
+    request_body = VerifyAttestation,
+    responses(
+        (status = OK, description = "Whether the attestation unlocks the claimed outcome under the oracle's pubkey", body = AttestationVerification),
+        (status = BAD_REQUEST, description = "Artifacts could not be decoded"),
+        (status = NOT_FOUND, description = "Event not found for the provided ID or not signed yet"),
+    ))]
+pub async fn verify_attestation(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<VerifyAttestation>,
+) -> Result<Json<AttestationVerification>, ErrorResponse> {
+    state.oracle.verify(body).await.map(Json).map_err(|e| {
+        error!("error verifying attestation: {}", e);
+        e.into()
+    })
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams)]
+pub struct EventWeatherParams {
+    /// Unit to return the station temperatures in, defaults to fahrenheit
+    #[serde(default)]
+    pub unit: TemperatureUnit,
+}
+
+#[utoipa::path(
+    get,
+    path = "/oracle/events/{event_id}/weather",
+    params(
+        ("event_id" = Uuid, Path, description = "ID of a weather event the oracle is tracking"),
+        EventWeatherParams
+    ),
+    responses(
+        (status = OK, description = "Successfully retrieved event weather", body = Vec<Weather>),
+        (status = NOT_FOUND, description = "Event not found for the provided ID"),
+    ))]
+pub async fn get_event_weather(
+    State(state): State<Arc<AppState>>,
+    Path(event_id): Path<Uuid>,
+    Query(params): Query<EventWeatherParams>,
+) -> Result<Json<Vec<Weather>>, ErrorResponse> {
+    state
+        .oracle
+        .get_event_weather(&event_id, &params.unit)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("error event weather data: {}", e);
+            e.into()
+        })
+}
+
 #[utoipa::path(
     post,
     path = "/oracle/events/{event_id}/entries",
@@ -204,22 +679,64 @@ pub async fn update_data(State(state): State<Arc<AppState>>) -> Result<StatusCod
     Ok(StatusCode::OK)
 }
 
+/// Body of every oracle error response. `code` is the contract, `message` is for people.
+///
+/// | code | status |
+/// |------|--------|
+/// | `not_found` | 404 |
+/// | `min_outcome` | 400 |
+/// | `event_maturity` | 400 |
+/// | `bad_entry` | 400 |
+/// | `bad_event` | 400 |
+/// | `bad_attestation` | 400 |
+/// | `not_coordinator` | 403 |
+/// | `key_rotation_rejected` | 409 |
+/// | `key_invalid` | 500 |
+/// | `key_conversion_failed` | 500 |
+/// | `key_encoding_failed` | 500 |
+/// | `pubkey_mismatch` | 500 |
+/// | `data_query_failed` | 500 |
+/// | `weather_data_failed` | 500 |
+/// | `outcome_not_found` | 500 |
+/// | `message_validation_failed` | 500 |
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+    /// The rejected input or reason, only set for client errors
+    pub details: Option<String>,
+}
+
+impl oracle::Error {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            oracle::Error::NotFound(_) => StatusCode::NOT_FOUND,
+            oracle::Error::MinOutcome(_)
+            | oracle::Error::EventMaturity(_)
+            | oracle::Error::BadEntry(_)
+            | oracle::Error::BadEvent(_)
+            | oracle::Error::BadAttestation(_) => StatusCode::BAD_REQUEST,
+            oracle::Error::NotCoordinator(_) => StatusCode::FORBIDDEN,
+            oracle::Error::KeyRotation(_) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
 impl IntoResponse for oracle::Error {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self.borrow() {
-            oracle::Error::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
-            oracle::Error::MinOutcome(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            oracle::Error::EventMaturity(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            oracle::Error::BadEntry(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            oracle::Error::BadEvent(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            _ => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                String::from("internal server error"),
-            ),
+        let status = self.status();
+        let message = if status.is_server_error() {
+            error!("error handling request: {}", self);
+            String::from("internal server error")
+        } else {
+            self.to_string()
         };
-        let body = Json(json!({
-            "error": error_message,
-        }));
+        let body = Json(ErrorBody {
+            code: self.code().to_string(),
+            message,
+            details: self.details(),
+        });
         (status, body).into_response()
     }
 }
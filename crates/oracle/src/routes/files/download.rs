@@ -4,7 +4,9 @@ use axum::{
     http::{HeaderValue, Request, StatusCode},
 };
 use hyper::{
-    header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    header::{
+        ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, RANGE,
+    },
     HeaderMap,
 };
 use log::error;
@@ -13,22 +15,77 @@ use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
 use crate::{drop_suffix, AppState};
 
+/// Byte range requested through a `Range` header, inclusive of `end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// No usable range, send the whole file
+    Full,
+    Partial {
+        start: u64,
+        end: u64,
+    },
+    Unsatisfiable,
+}
+
+impl ByteRange {
+    /// Parses a single `bytes=` range against the file size. Malformed, multi-range and
+    /// non-byte ranges are ignored and answered with the full file, as RFC 9110 allows.
+    pub fn parse(header: Option<&str>, size: u64) -> Self {
+        let Some(spec) = header.and_then(|header| header.trim().strip_prefix("bytes=")) else {
+            return ByteRange::Full;
+        };
+        if spec.contains(',') {
+            return ByteRange::Full;
+        }
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return ByteRange::Full;
+        };
+        let last = size.checked_sub(1);
+        match (start.parse::<u64>(), end.parse::<u64>()) {
+            // bytes=-N, the last N bytes
+            (Err(_), Ok(suffix)) if start.is_empty() => match last {
+                Some(last) if suffix > 0 => ByteRange::Partial {
+                    start: size.saturating_sub(suffix),
+                    end: last,
+                },
+                _ => ByteRange::Unsatisfiable,
+            },
+            // bytes=N-
+            (Ok(start), Err(_)) if end.is_empty() => match last {
+                Some(last) if start <= last => ByteRange::Partial { start, end: last },
+                _ => ByteRange::Unsatisfiable,
+            },
+            (Ok(start), Ok(end)) if start <= end => match last {
+                Some(last) if start <= last => ByteRange::Partial {
+                    start,
+                    end: end.min(last),
+                },
+                _ => ByteRange::Unsatisfiable,
+            },
+            _ => ByteRange::Full,
+        }
+    }
+}
+
 #[utoipa::path(
     get,
     path = "file/{filename}",
     params(
          ("filename" = String, Path, description = "Name of file to download"),
+         ("Range" = Option<String>, Header, description = "Single byte range to resume a download, e.g. `bytes=1024-`"),
     ),
     responses(
         (status = OK, description = "Successfully retrieved file", content_type = "application/parquet", body = Vec<u8>),
+        (status = PARTIAL_CONTENT, description = "Successfully retrieved the requested byte range", content_type = "application/parquet", body = Vec<u8>),
         (status = BAD_REQUEST, description = "Invalid file name"),
+        (status = RANGE_NOT_SATISFIABLE, description = "Range starts past the end of the file"),
         (status = INTERNAL_SERVER_ERROR, description = "Failed to retrieve file by name")
     ))]
 pub async fn download(
     State(state): State<Arc<AppState>>,
     Path(filename): Path<String>,
-    _request: Request<Body>,
-) -> Result<(HeaderMap, Body), (StatusCode, String)> {
+    request: Request<Body>,
+) -> Result<(StatusCode, HeaderMap, Body), (StatusCode, String)> {
     let file_pieces: Vec<String> = filename.split('_').map(|f| f.to_owned()).collect();
     let created_time = drop_suffix(file_pieces.last().unwrap(), ".parquet");
     let file_generated_at = OffsetDateTime::parse(&created_time, &Rfc3339).map_err(|e| {
@@ -45,16 +102,58 @@ pub async fn download(
         )
     })?;
 
-    let body = state
+    let size = state
         .file_access
-        .download_file(&filename, file_generated_at)
+        .file_size(&filename, file_generated_at)
         .await
         .map_err(|err| {
             error!("error downloading file: {}", err);
             (StatusCode::NOT_FOUND, format!("File not found: {}", err))
         })?;
+    let range = ByteRange::parse(
+        request
+            .headers()
+            .get(RANGE)
+            .and_then(|value| value.to_str().ok()),
+        size,
+    );
 
     let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    let (status, body) = match range {
+        ByteRange::Full => {
+            let body = state
+                .file_access
+                .download_file(&filename, file_generated_at)
+                .await;
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(size));
+            (StatusCode::OK, body)
+        }
+        ByteRange::Partial { start, end } => {
+            let body = state
+                .file_access
+                .download_file_range(&filename, file_generated_at, start, end - start + 1)
+                .await;
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(end - start + 1));
+            headers.insert(
+                CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes {}-{}/{}", start, end, size)).unwrap(),
+            );
+            (StatusCode::PARTIAL_CONTENT, body)
+        }
+        ByteRange::Unsatisfiable => {
+            headers.insert(
+                CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{}", size)).unwrap(),
+            );
+            return Ok((StatusCode::RANGE_NOT_SATISFIABLE, headers, Body::empty()));
+        }
+    };
+    let body = body.map_err(|err| {
+        error!("error downloading file: {}", err);
+        (StatusCode::NOT_FOUND, format!("File not found: {}", err))
+    })?;
+
     headers.insert(
         CONTENT_TYPE,
         HeaderValue::from_str("application/parquet").unwrap(),
@@ -64,5 +163,5 @@ pub async fn download(
         HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)).unwrap(),
     );
 
-    Ok((headers, body))
+    Ok((status, headers, body))
 }
@@ -1,41 +1,201 @@
-use crate::{AppError, AppState, FileParams};
+use crate::{drop_suffix, AppError, AppState, FileData, FileParams};
+use anyhow::anyhow;
 use axum::{
+    body::{to_bytes, Body},
     extract::{Query, State},
+    http::HeaderValue,
+    response::{IntoResponse, Response},
     Json,
 };
+use hyper::{
+    header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    HeaderMap,
+};
 use log::error;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use utoipa::ToSchema;
+use std::{io::Write, sync::Arc};
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+use tokio::runtime::Handle;
+use tokio_util::io::{ReaderStream, SyncIoBridge};
+use utoipa::{IntoParams, ToSchema};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// Widest start/end window accepted by `/files`, a zip bundle must set both
+pub const MAX_FILE_RANGE_DAYS: i64 = 31;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileType {
+    Forecasts,
+    Observations,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileBundle {
+    Zip,
+}
+
+#[derive(Clone, Deserialize, IntoParams)]
+pub struct FilesRequest {
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub start: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub end: Option<OffsetDateTime>,
+    pub observations: Option<bool>,
+    pub forecasts: Option<bool>,
+    /// Only list one kind of file, takes precedence over `observations`/`forecasts`
+    #[serde(rename = "type")]
+    #[param(rename = "type", inline)]
+    pub file_type: Option<FileType>,
+    /// Stream the matching files back as one archive instead of listing them
+    #[param(inline)]
+    pub bundle: Option<FileBundle>,
+}
+
+impl FilesRequest {
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) if end < start => Err(anyhow!("end must not be before start")),
+            (Some(start), Some(end)) if end - start > Duration::days(MAX_FILE_RANGE_DAYS) => Err(
+                anyhow!("range must be at most {} days", MAX_FILE_RANGE_DAYS),
+            ),
+            (Some(_), Some(_)) => Ok(()),
+            _ if self.bundle.is_some() => Err(anyhow!("start and end are required for a bundle")),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<&FilesRequest> for FileParams {
+    fn from(value: &FilesRequest) -> Self {
+        let (observations, forecasts) = match value.file_type {
+            Some(FileType::Forecasts) => (Some(false), Some(true)),
+            Some(FileType::Observations) => (Some(true), Some(false)),
+            None => (value.observations, value.forecasts),
+        };
+        FileParams {
+            start: value.start,
+            end: value.end,
+            observations,
+            forecasts,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct FileEntry {
+    pub name: String,
+    pub file_type: FileType,
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    /// Where to fetch the file on its own, `GET /file/{name}`
+    pub url: String,
+}
+
+impl FileEntry {
+    /// None for names that don't follow `{type}_{rfc3339}.parquet`
+    fn from_name(name: String, remote_url: &str) -> Option<Self> {
+        let (file_type, created_time) = name.split_once('_')?;
+        let file_type = match file_type {
+            "forecasts" => FileType::Forecasts,
+            "observations" => FileType::Observations,
+            _ => return None,
+        };
+        let generated_at =
+            OffsetDateTime::parse(&drop_suffix(created_time, ".parquet"), &Rfc3339).ok()?;
+        Some(FileEntry {
+            url: format!("{}/file/{}", remote_url.trim_end_matches('/'), name),
+            name,
+            file_type,
+            generated_at,
+        })
+    }
+}
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct Files {
     pub file_names: Vec<String>,
+    pub files: Vec<FileEntry>,
 }
 
 #[utoipa::path(
     get,
-    path = "file/{filename}",
+    path = "files",
     params(
-         FileParams
+         FilesRequest
     ),
     responses(
-        (status = OK, description = "Successfully retrieved file names", body = Files),
-        (status = BAD_REQUEST, description = "Invalid file params"),
+        (status = OK, description = "Successfully retrieved file names, or a zip of the files with `bundle=zip`", body = Files),
+        (status = BAD_REQUEST, description = "Invalid file params or range too large"),
         (status = INTERNAL_SERVER_ERROR, description = "Failed to retrieve file names")
     ))]
 pub async fn files(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<FileParams>,
-) -> Result<Json<Files>, AppError> {
+    Query(request): Query<FilesRequest>,
+) -> Result<Response, AppError> {
+    request.validate()?;
     let file_names = state
         .file_access
-        .grab_file_names(params)
+        .grab_file_names(FileParams::from(&request))
         .await
         .map_err(|e| {
             error!("error getting filenames: {}", e);
             e
         })?;
-    let files = Files { file_names };
-    Ok(Json(files))
+    let files: Vec<FileEntry> = file_names
+        .iter()
+        .filter_map(|name| FileEntry::from_name(name.clone(), &state.remote_url))
+        .collect();
+
+    match request.bundle {
+        Some(FileBundle::Zip) => {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+            headers.insert(
+                CONTENT_DISPOSITION,
+                HeaderValue::from_static("attachment; filename=\"weather_files.zip\""),
+            );
+            Ok((headers, zip_files(state.file_access.clone(), files)).into_response())
+        }
+        None => Ok(Json(Files { file_names, files }).into_response()),
+    }
+}
+
+/// Streams the files into a zip as they're read, so the archive is never held in memory.
+/// Parquet is already compressed so entries are stored as-is.
+fn zip_files(file_access: Arc<dyn FileData>, files: Vec<FileEntry>) -> Body {
+    let (writer, reader) = tokio::io::duplex(64 * 1024);
+    let handle = Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let mut zip = ZipWriter::new_stream(SyncIoBridge::new_with_handle(writer, handle.clone()));
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(true);
+        for file in files {
+            let contents = handle.block_on(async {
+                let body = file_access
+                    .download_file(&file.name, file.generated_at)
+                    .await
+                    .map_err(|e| anyhow!("{}", e))?;
+                to_bytes(body, usize::MAX)
+                    .await
+                    .map_err(|e| anyhow!("{}", e))
+            });
+            let written = contents.and_then(|contents| {
+                zip.start_file(file.name.as_str(), options)?;
+                zip.write_all(&contents)?;
+                Ok(())
+            });
+            // Dropping the writer ends the response early, clients see a truncated archive
+            if let Err(e) = written {
+                error!("error adding {} to zip bundle: {}", file.name, e);
+                return;
+            }
+        }
+        if let Err(e) = zip.finish() {
+            error!("error finishing zip bundle: {}", e);
+        }
+    });
+    Body::from_stream(ReaderStream::new(reader))
 }
@@ -1,7 +1,9 @@
 pub mod download;
 pub mod get_names;
+pub mod schema;
 pub mod upload;
 
 pub use download::*;
 pub use get_names::*;
+pub use schema::*;
 pub use upload::*;
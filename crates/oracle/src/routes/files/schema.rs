@@ -0,0 +1,145 @@
+use axum::body::Bytes;
+use parquet::{
+    basic::Type as PhysicalType,
+    file::reader::{FileReader, SerializedFileReader},
+};
+
+/// Columns every file of a type has had since the first daemon release
+const FORECAST_COLUMNS: &[(&str, PhysicalType)] = &[
+    ("station_id", PhysicalType::BYTE_ARRAY),
+    ("station_name", PhysicalType::BYTE_ARRAY),
+    ("latitude", PhysicalType::DOUBLE),
+    ("longitude", PhysicalType::DOUBLE),
+    ("generated_at", PhysicalType::BYTE_ARRAY),
+    ("begin_time", PhysicalType::BYTE_ARRAY),
+    ("end_time", PhysicalType::BYTE_ARRAY),
+    ("max_temp", PhysicalType::INT64),
+    ("min_temp", PhysicalType::INT64),
+    ("temperature_unit_code", PhysicalType::BYTE_ARRAY),
+    ("wind_speed", PhysicalType::INT64),
+    ("wind_speed_unit_code", PhysicalType::BYTE_ARRAY),
+    ("wind_direction", PhysicalType::INT64),
+    ("wind_direction_unit_code", PhysicalType::BYTE_ARRAY),
+    ("relative_humidity_max", PhysicalType::INT64),
+    ("relative_humidity_min", PhysicalType::INT64),
+    ("relative_humidity_unit_code", PhysicalType::BYTE_ARRAY),
+    ("liquid_precipitation_amt", PhysicalType::DOUBLE),
+    ("liquid_precipitation_unit_code", PhysicalType::BYTE_ARRAY),
+    (
+        "twelve_hour_probability_of_precipitation",
+        PhysicalType::INT64,
+    ),
+    (
+        "twelve_hour_probability_of_precipitation_unit_code",
+        PhysicalType::BYTE_ARRAY,
+    ),
+];
+
+/// Columns added later, older files may not have them but queries union them by name
+const FORECAST_ADDED_COLUMNS: &[(&str, PhysicalType)] = &[
+    ("state", PhysicalType::BYTE_ARRAY),
+    ("iata_id", PhysicalType::BYTE_ARRAY),
+    ("elevation_m", PhysicalType::DOUBLE),
+    ("snow_amt", PhysicalType::DOUBLE),
+    ("snow_amt_unit_code", PhysicalType::BYTE_ARRAY),
+    ("snow_ratio", PhysicalType::DOUBLE),
+    ("snow_ratio_unit_code", PhysicalType::BYTE_ARRAY),
+    ("ice_amt", PhysicalType::DOUBLE),
+    ("ice_amt_unit_code", PhysicalType::BYTE_ARRAY),
+];
+
+const OBSERVATION_COLUMNS: &[(&str, PhysicalType)] = &[
+    ("station_id", PhysicalType::BYTE_ARRAY),
+    ("station_name", PhysicalType::BYTE_ARRAY),
+    ("latitude", PhysicalType::DOUBLE),
+    ("longitude", PhysicalType::DOUBLE),
+    ("generated_at", PhysicalType::BYTE_ARRAY),
+    ("temperature_value", PhysicalType::DOUBLE),
+    ("temperature_unit_code", PhysicalType::BYTE_ARRAY),
+    ("wind_direction", PhysicalType::INT64),
+    ("wind_direction_unit_code", PhysicalType::BYTE_ARRAY),
+    ("wind_speed", PhysicalType::INT64),
+    ("wind_speed_unit_code", PhysicalType::BYTE_ARRAY),
+    ("dewpoint_value", PhysicalType::DOUBLE),
+    ("dewpoint_unit_code", PhysicalType::BYTE_ARRAY),
+];
+
+const OBSERVATION_ADDED_COLUMNS: &[(&str, PhysicalType)] = &[
+    ("state", PhysicalType::BYTE_ARRAY),
+    ("iata_id", PhysicalType::BYTE_ARRAY),
+    ("elevation_m", PhysicalType::DOUBLE),
+    ("precip_in", PhysicalType::DOUBLE),
+    ("precip_unit_code", PhysicalType::BYTE_ARRAY),
+    ("wx_string", PhysicalType::BYTE_ARRAY),
+];
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum SchemaError {
+    #[error("Unknown weather file type `{0}`, expected forecasts or observations")]
+    UnknownFileType(String),
+    #[error("File is not readable parquet: {0}")]
+    Unreadable(String),
+    #[error("Missing column `{0}`")]
+    MissingColumn(String),
+    #[error("Column `{column}` has type {found}, expected {expected}")]
+    ColumnType {
+        column: String,
+        expected: PhysicalType,
+        found: PhysicalType,
+    },
+}
+
+/// Checks an uploaded file's columns against the schema the daemon writes for its type,
+/// read from the `<type>_<timestamp>.parquet` file name. Columns beyond the expected ones
+/// are allowed, queries only select the columns they know about.
+pub fn validate_parquet_schema(file_name: &str, data: Bytes) -> Result<(), SchemaError> {
+    let file_type = file_name.split('_').next().unwrap_or_default();
+    let (required, added) = match file_type {
+        "forecasts" => (FORECAST_COLUMNS, FORECAST_ADDED_COLUMNS),
+        "observations" => (OBSERVATION_COLUMNS, OBSERVATION_ADDED_COLUMNS),
+        _ => return Err(SchemaError::UnknownFileType(file_type.to_owned())),
+    };
+
+    let reader =
+        SerializedFileReader::new(data).map_err(|e| SchemaError::Unreadable(e.to_string()))?;
+    let schema = reader.metadata().file_metadata().schema_descr();
+    let columns: Vec<(&str, PhysicalType)> = schema
+        .columns()
+        .iter()
+        .map(|column| (column.name(), column.physical_type()))
+        .collect();
+    let found = |name: &str| {
+        columns
+            .iter()
+            .find(|(column, _)| *column == name)
+            .map(|(_, physical_type)| *physical_type)
+    };
+
+    for (name, expected) in required {
+        let Some(physical_type) = found(name) else {
+            return Err(SchemaError::MissingColumn(name.to_string()));
+        };
+        check_type(name, *expected, physical_type)?;
+    }
+    for (name, expected) in added {
+        if let Some(physical_type) = found(name) {
+            check_type(name, *expected, physical_type)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_type(
+    column: &str,
+    expected: PhysicalType,
+    found: PhysicalType,
+) -> Result<(), SchemaError> {
+    if expected != found {
+        return Err(SchemaError::ColumnType {
+            column: column.to_owned(),
+            expected,
+            found,
+        });
+    }
+    Ok(())
+}
@@ -1,34 +1,58 @@
 use axum::{
     extract::{Multipart, Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
 };
 use log::{error, info};
 use std::sync::Arc;
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 use tokio::{fs::File, io::AsyncWriteExt};
 
-use crate::AppState;
-use noaa_oracle_core::fs::create_dir_all;
+use crate::{validate_parquet_schema, AppState};
+use noaa_oracle_core::{fs::create_dir_all, sha256_hex, CONTENT_SHA256_HEADER, DATA_LAYOUT_HEADER};
 
 #[utoipa::path(
     post,
     path = "file/{file_name}",
     params(
          ("file_name" = String, Path, description = "Name of file to upload"),
+        ("x-data-layout" = Option<String>, Header, description = "Uploader's data dir layout, rejected when it differs from the oracle's"),
+        ("x-content-sha256" = Option<String>, Header, description = "Hex SHA-256 of the file, the upload is rejected when the received bytes don't match"),
     ),
     responses(
         (status = OK, description = "Successfully uploaded weather data file"),
-        (status = BAD_REQUEST, description = "Invalid file"),
+        (status = BAD_REQUEST, description = "Invalid file, mismatched data layout or checksum, or parquet not matching the forecast/observation schema"),
         (status = INTERNAL_SERVER_ERROR, description = "Failed to save file")
     ))]
 pub async fn upload(
     State(state): State<Arc<AppState>>,
     Path(file_name): Path<String>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> Result<(), (StatusCode, String)> {
     if !path_is_valid(&file_name) {
         return Err((StatusCode::BAD_REQUEST, "Invalid file".to_owned()));
     }
+    // Older daemons don't send a layout, only an explicit mismatch is rejected
+    if let Some(layout) = headers.get(DATA_LAYOUT_HEADER) {
+        let expected = state.file_access.layout().to_string();
+        if layout.to_str().ok() != Some(expected.as_str()) {
+            error!(
+                "upload layout {:?} does not match oracle layout '{}'",
+                layout, expected
+            );
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Data layout {:?} does not match the oracle's '{}'",
+                    layout, expected
+                ),
+            ));
+        }
+    }
+    // Older daemons don't send a checksum either, those uploads are stored unverified
+    let expected_checksum = headers
+        .get(CONTENT_SHA256_HEADER)
+        .map(|checksum| checksum.to_str().unwrap_or_default().to_owned());
     while let Some(field) = multipart.next_field().await.unwrap() {
         let data = field.bytes().await.map_err(|err| {
             error!("error getting file's bytes: {}", err);
@@ -44,6 +68,32 @@ pub async fn upload(
             bytes_to_mb(data.len())
         );
 
+        if let Some(expected) = &expected_checksum {
+            let received = sha256_hex(&data);
+            if !received.eq_ignore_ascii_case(expected) {
+                error!(
+                    "checksum mismatch for `{}`: expected {}, received {}",
+                    file_name, expected, received
+                );
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Checksum mismatch for {}: expected {}, received {}",
+                        file_name, expected, received
+                    ),
+                ));
+            }
+        }
+
+        // A file that doesn't match would break every query that reads it alongside others
+        validate_parquet_schema(&file_name, data.clone()).map_err(|err| {
+            error!("rejected `{}`: {}", file_name, err);
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid parquet schema for {}: {}", file_name, err),
+            )
+        })?;
+
         // Parse the date from the filename to save in the correct date directory
         // Filename format: observations_2026-01-21T23:59:43.269662415Z.parquet
         let file_generated_at = parse_file_timestamp(&file_name).map_err(|err| {
@@ -54,12 +104,12 @@ pub async fn upload(
             )
         })?;
 
-        // Use build_file_path which uses file_generated_at.date() for the directory
+        // build_file_path puts the file in its date (and for hive, type) directory
         let path = state
             .file_access
             .build_file_path(&file_name, file_generated_at);
 
-        // Ensure the date directory exists
+        // Ensure the directory exists
         if let Some(parent) = std::path::Path::new(&path).parent() {
             create_dir_all(parent.to_str().unwrap_or_default()).map_err(|err| {
                 error!("error creating directory: {}", err);
@@ -1,8 +1,10 @@
+pub mod admin;
 pub mod events;
 pub mod files;
 pub mod stations;
 pub mod ui;
 
+pub use admin::*;
 pub use events::*;
 pub use files::*;
 pub use stations::*;
@@ -1,37 +1,99 @@
 use ::serde::Deserialize;
 use axum::{
+    body::Body,
     extract::{Query, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, HeaderValue,
+    },
+    response::{IntoResponse, Response},
     Json,
 };
 use core::fmt;
-use serde::Serialize;
-use std::sync::Arc;
-use time::OffsetDateTime;
+use futures::stream;
+use log::error;
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use serde_json::{json, Value};
+use std::{collections::HashMap, convert::Infallible, str::FromStr, sync::Arc};
+use time::{format_description::well_known::Rfc3339, Date, OffsetDateTime, UtcOffset};
 use utoipa::{IntoParams, ToSchema};
 
-use crate::{AppError, AppState, DailyObservation, FileParams, Forecast, Observation, Station};
+use crate::{
+    AppError, AppState, DailyComparison, DailyObservation, FileParams, FileType, Forecast,
+    Observation, ObservationCoverage, QueryStats, Station, StationAliases, WeatherData,
+};
 
 #[utoipa::path(
     get,
     path = "stations/forecasts",
     params(
-        ForecastRequest
+        ForecastRequest,
+        EnvelopeRequest
     ),
     responses(
-        (status = OK, description = "Successfully retrieved forecast data", body = Vec<Forecast>),
-        (status = BAD_REQUEST, description = "Times are not in RFC3339 format"),
+        (status = OK, description = "Successfully retrieved forecast data", body = Vec<Forecast>, headers(
+            ("x-rows" = usize, description = "Rows returned"),
+            ("x-files" = usize, description = "Parquet files scanned"),
+            ("x-query-ms" = u64, description = "Query time in milliseconds"),
+            ("x-data-generated-at" = String, description = "Newest generated_at behind the rows, RFC3339"),
+            ("x-data-source" = String, description = "Only on empty results: `none` when no files exist for the range, `empty` when files matched but no rows did")
+        )),
+        (status = BAD_REQUEST, description = "Times are not in RFC3339 format, a field or the granularity is unknown or a station alias doesn't resolve"),
         (status = INTERNAL_SERVER_ERROR, description = "Failed to retrieved weather data")
     ))]
 pub async fn forecasts(
     State(state): State<Arc<AppState>>,
     Query(req): Query<ForecastRequest>,
-) -> Result<Json<Vec<Forecast>>, AppError> {
-    let forecasts = state
+    Query(envelope): Query<EnvelopeRequest>,
+) -> Result<(HeaderMap, Json<WeatherResponse<SelectedForecasts>>), AppError> {
+    let station_ids = resolve_station_ids(state.weather_db.as_ref(), req.station_ids()).await?;
+    let (forecasts, stats) = state
         .weather_db
-        .forecasts_data(&req, req.station_ids())
+        .forecasts_data_with_stats(&req, station_ids)
         .await?;
 
-    Ok(Json(forecasts))
+    let forecasts = SelectedForecasts {
+        forecasts,
+        fields: req.fields,
+    };
+    Ok((
+        stats_headers(&stats),
+        Json(WeatherResponse::new(forecasts, &stats, &envelope)),
+    ))
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, IntoParams)]
+pub struct EnvelopeRequest {
+    /// Wrap the rows as `{"generated_at": ..., "data": [...]}` instead of a bare array
+    #[serde(default)]
+    pub envelope: bool,
+}
+
+/// Weather query response body, the bare rows unless an envelope was asked for
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum WeatherResponse<T> {
+    Rows(T),
+    Envelope {
+        /// Newest `generated_at` behind the rows, RFC3339
+        generated_at: Option<String>,
+        data: T,
+    },
+}
+
+impl<T> WeatherResponse<T> {
+    pub fn new(data: T, stats: &QueryStats, request: &EnvelopeRequest) -> Self {
+        if !request.envelope {
+            return WeatherResponse::Rows(data);
+        }
+        WeatherResponse::Envelope {
+            generated_at: generated_at_rfc3339(stats),
+            data,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, IntoParams)]
@@ -52,9 +114,310 @@ pub struct ForecastRequest {
     #[serde(with = "time::serde::rfc3339::option")]
     #[serde(default)]
     pub generated_end: Option<OffsetDateTime>,
+    /// Replay the forecasts as they stood at this time: only forecasts generated at or before
+    /// it are used, so each window gets the latest issuance known then instead of the newest.
+    /// Defaults to now.
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "2024-08-11T12:00:00Z")]
+    pub as_of: Option<OffsetDateTime>,
     pub station_ids: String,
     #[serde(default)]
     pub temperature_unit: TemperatureUnit,
+    /// Unit `wind_speed` is converted to: `knots`, `mph` or `m/s`. Left in the stored unit
+    /// (knots) when unset.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "mph")]
+    pub wind_unit: Option<WindSpeedUnit>,
+    /// Unit `rain_amt`, `snow_amt` and `ice_amt` are returned in: `in` (default) or `mm`
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "mm")]
+    pub precip_unit: PrecipUnit,
+    /// How each day's sub-window values are combined: `minmax` (default), `median` or a
+    /// percentile `p1`..`p99`. Applies to `temp_low`, `temp_high` and `wind_speed`, the
+    /// remaining fields always use their min/max/sum daily aggregation.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "p90")]
+    pub agg: ForecastAggregation,
+    /// Comma separated forecast fields to return, e.g. `temp_low,temp_high`. Unlisted fields
+    /// are left out of the response and the query, `station_id` and `date` are always included.
+    /// Defaults to every field.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "temp_low,temp_high")]
+    pub fields: ForecastFields,
+    /// Time buckets forecasts are grouped in: `daily`, `hourly` (UTC hours) or `raw` (one
+    /// row per forecast window start). Each row then carries the bucket's start as `bucket`.
+    /// Unset groups by UTC day without a `bucket`.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "hourly")]
+    pub granularity: Option<Granularity>,
+}
+
+/// Daily aggregation of forecast sub-windows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ForecastAggregation {
+    /// Lowest `temp_low`, highest `temp_high` and highest `wind_speed` of the day
+    #[default]
+    MinMax,
+    /// Continuous percentile of the day's values, 50 is the median
+    Percentile(u8),
+}
+
+impl ForecastAggregation {
+    /// Fraction passed to DuckDB's `quantile_cont`, None for min/max
+    pub fn quantile(&self) -> Option<f64> {
+        match self {
+            ForecastAggregation::MinMax => None,
+            ForecastAggregation::Percentile(percentile) => Some(f64::from(*percentile) / 100.0),
+        }
+    }
+}
+
+impl FromStr for ForecastAggregation {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.to_lowercase();
+        match value.as_str() {
+            "minmax" => return Ok(ForecastAggregation::MinMax),
+            "median" => return Ok(ForecastAggregation::Percentile(50)),
+            _ => {}
+        }
+        value
+            .strip_prefix('p')
+            .and_then(|percentile| percentile.parse::<u8>().ok())
+            .filter(|percentile| (1..=99).contains(percentile))
+            .map(ForecastAggregation::Percentile)
+            .ok_or_else(|| {
+                format!(
+                    "unknown aggregation '{}', expected minmax, median or p1-p99",
+                    value
+                )
+            })
+    }
+}
+
+impl TryFrom<String> for ForecastAggregation {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ForecastAggregation> for String {
+    fn from(value: ForecastAggregation) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for ForecastAggregation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ForecastAggregation::MinMax => write!(f, "minmax"),
+            ForecastAggregation::Percentile(50) => write!(f, "median"),
+            ForecastAggregation::Percentile(percentile) => write!(f, "p{}", percentile),
+        }
+    }
+}
+
+/// Forecast fields that can be picked with `fields`, named as they serialize
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForecastField {
+    StartTime,
+    EndTime,
+    TempLow,
+    TempHigh,
+    WindSpeed,
+    WindDirection,
+    HumidityMax,
+    HumidityMin,
+    TempUnitCode,
+    PrecipChance,
+    RainAmt,
+    SnowAmt,
+    IceAmt,
+    DominantPrecip,
+    PrecipUnitCode,
+}
+
+impl ForecastField {
+    pub const ALL: [ForecastField; 15] = [
+        ForecastField::StartTime,
+        ForecastField::EndTime,
+        ForecastField::TempLow,
+        ForecastField::TempHigh,
+        ForecastField::WindSpeed,
+        ForecastField::WindDirection,
+        ForecastField::HumidityMax,
+        ForecastField::HumidityMin,
+        ForecastField::TempUnitCode,
+        ForecastField::PrecipChance,
+        ForecastField::RainAmt,
+        ForecastField::SnowAmt,
+        ForecastField::IceAmt,
+        ForecastField::DominantPrecip,
+        ForecastField::PrecipUnitCode,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForecastField::StartTime => "start_time",
+            ForecastField::EndTime => "end_time",
+            ForecastField::TempLow => "temp_low",
+            ForecastField::TempHigh => "temp_high",
+            ForecastField::WindSpeed => "wind_speed",
+            ForecastField::WindDirection => "wind_direction",
+            ForecastField::HumidityMax => "humidity_max",
+            ForecastField::HumidityMin => "humidity_min",
+            ForecastField::TempUnitCode => "temp_unit_code",
+            ForecastField::PrecipChance => "precip_chance",
+            ForecastField::RainAmt => "rain_amt",
+            ForecastField::SnowAmt => "snow_amt",
+            ForecastField::IceAmt => "ice_amt",
+            ForecastField::DominantPrecip => "dominant_precip",
+            ForecastField::PrecipUnitCode => "precip_unit_code",
+        }
+    }
+}
+
+/// Forecast fields a request wants, in response order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ForecastFields(Vec<ForecastField>);
+
+impl Default for ForecastFields {
+    fn default() -> Self {
+        ForecastFields(ForecastField::ALL.to_vec())
+    }
+}
+
+impl ForecastFields {
+    pub fn includes(&self, field: ForecastField) -> bool {
+        self.0.contains(&field)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ForecastField> {
+        self.0.iter()
+    }
+}
+
+impl FromStr for ForecastFields {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut fields = Vec::new();
+        for name in value.split(',').map(str::trim) {
+            // Always returned, accepted so clients can list them anyway
+            if name == "station_id" || name == "date" {
+                continue;
+            }
+            let field = ForecastField::ALL
+                .into_iter()
+                .find(|field| field.name() == name)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown forecast field '{}', expected station_id, date, {}",
+                        name,
+                        ForecastFields::default()
+                    )
+                })?;
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+        Ok(ForecastFields(fields))
+    }
+}
+
+impl TryFrom<String> for ForecastFields {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ForecastFields> for String {
+    fn from(value: ForecastFields) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for ForecastFields {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<&str> = self.iter().map(|field| field.name()).collect();
+        write!(f, "{}", names.join(","))
+    }
+}
+
+/// Forecasts serialized with only the requested fields, unrequested ones are omitted
+/// rather than sent as null
+pub struct SelectedForecasts {
+    pub forecasts: Vec<Forecast>,
+    pub fields: ForecastFields,
+}
+
+impl Serialize for SelectedForecasts {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.forecasts.len()))?;
+        for forecast in &self.forecasts {
+            seq.serialize_element(&SelectedForecast {
+                forecast,
+                fields: &self.fields,
+            })?;
+        }
+        seq.end()
+    }
+}
+
+struct SelectedForecast<'a> {
+    forecast: &'a Forecast,
+    fields: &'a ForecastFields,
+}
+
+impl Serialize for SelectedForecast<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let forecast = self.forecast;
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("station_id", &forecast.station_id)?;
+        map.serialize_entry("date", &forecast.date)?;
+        if let Some(bucket) = &forecast.bucket {
+            map.serialize_entry("bucket", bucket)?;
+        }
+        for field in self.fields.iter() {
+            let name = field.name();
+            match field {
+                ForecastField::StartTime => map.serialize_entry(name, &forecast.start_time)?,
+                ForecastField::EndTime => map.serialize_entry(name, &forecast.end_time)?,
+                ForecastField::TempLow => map.serialize_entry(name, &forecast.temp_low)?,
+                ForecastField::TempHigh => map.serialize_entry(name, &forecast.temp_high)?,
+                ForecastField::WindSpeed => map.serialize_entry(name, &forecast.wind_speed)?,
+                ForecastField::WindDirection => {
+                    map.serialize_entry(name, &forecast.wind_direction)?
+                }
+                ForecastField::HumidityMax => map.serialize_entry(name, &forecast.humidity_max)?,
+                ForecastField::HumidityMin => map.serialize_entry(name, &forecast.humidity_min)?,
+                ForecastField::TempUnitCode => {
+                    map.serialize_entry(name, &forecast.temp_unit_code)?
+                }
+                ForecastField::PrecipChance => {
+                    map.serialize_entry(name, &forecast.precip_chance)?
+                }
+                ForecastField::RainAmt => map.serialize_entry(name, &forecast.rain_amt)?,
+                ForecastField::SnowAmt => map.serialize_entry(name, &forecast.snow_amt)?,
+                ForecastField::IceAmt => map.serialize_entry(name, &forecast.ice_amt)?,
+                ForecastField::DominantPrecip => {
+                    map.serialize_entry(name, &forecast.dominant_precip)?
+                }
+                ForecastField::PrecipUnitCode => {
+                    map.serialize_entry(name, &forecast.precip_unit_code)?
+                }
+            }
+        }
+        map.end()
+    }
 }
 
 impl ForecastRequest {
@@ -68,9 +431,14 @@ impl ForecastRequest {
 
 impl From<&ForecastRequest> for FileParams {
     fn from(value: &ForecastRequest) -> Self {
+        // Files generated after `as_of` only hold forecasts it leaves out
+        let end = match (value.end, value.as_of) {
+            (Some(end), Some(as_of)) => Some(end.min(as_of)),
+            (end, as_of) => end.or(as_of),
+        };
         FileParams {
             start: value.start,
-            end: value.end,
+            end,
             observations: Some(false),
             forecasts: Some(true),
         }
@@ -88,6 +456,296 @@ pub struct ObservationRequest {
     pub station_ids: String,
     #[serde(default)]
     pub temperature_unit: TemperatureUnit,
+    /// Time zone daily observations are bucketed in, an IANA name such as
+    /// `America/New_York` or a fixed offset such as `-05:00`. Defaults to UTC.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "America/New_York")]
+    pub tz: DayTimeZone,
+    /// How `temp_low`/`temp_high` are taken from the window's readings on
+    /// `stations/observations`: `minmax` (default) for the lowest and highest reading, or
+    /// `firstlast` for the earliest and latest reading. Daily observations are always min/max.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "firstlast")]
+    pub temp_agg: ObservationTempAggregation,
+    /// Which readings `wind_speed` is the max of: `sustained` (default) for the sustained wind,
+    /// the same measure forecasts give, or `gust` for the peak gust. Readings without a gust
+    /// count their sustained wind, as do files written before gusts were recorded.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "gust")]
+    pub wind_agg: ObservationWindAggregation,
+    /// Unit `wind_speed` is converted to: `knots`, `mph` or `m/s`. Left in the stored unit
+    /// (knots) when unset.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "mph")]
+    pub wind_unit: Option<WindSpeedUnit>,
+    /// Unit `rain_amt`, `snow_amt` and `ice_amt` are returned in: `in` (default) or `mm`
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "mm")]
+    pub precip_unit: PrecipUnit,
+    /// Splits `stations/observations` into time buckets instead of one row for the whole
+    /// window: `daily` (days start in `tz`), `hourly` (UTC hours) or `raw` (one row per
+    /// reading). Each row then carries the bucket's start as `bucket`.
+    #[serde(default)]
+    #[param(value_type = Option<String>, example = "hourly")]
+    pub granularity: Option<Granularity>,
+}
+
+/// Time zone a day starts and ends in when observations are grouped by day
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DayTimeZone {
+    #[default]
+    Utc,
+    /// Fixed offset from UTC in seconds
+    Offset(i32),
+    /// IANA zone name, follows daylight saving changes
+    Named(String),
+}
+
+impl DayTimeZone {
+    /// DuckDB expression truncating the RFC3339 text in `column` to the start of its local day
+    pub fn day_sql(&self, column: &str) -> String {
+        match self {
+            DayTimeZone::Utc => format!("DATE_TRUNC('day', {}::TIMESTAMP)::TEXT", column),
+            DayTimeZone::Offset(seconds) => format!(
+                "DATE_TRUNC('day', ({}::TIMESTAMPTZ AT TIME ZONE 'UTC') + INTERVAL ({}) SECOND)::TEXT",
+                column, seconds
+            ),
+            DayTimeZone::Named(name) => format!(
+                "DATE_TRUNC('day', {}::TIMESTAMPTZ AT TIME ZONE '{}')::TEXT",
+                column, name
+            ),
+        }
+    }
+
+    /// DuckDB expression for the UTC timestamp the local day of the RFC3339 text in `column`
+    /// starts at
+    pub fn day_start_sql(&self, column: &str) -> String {
+        match self {
+            DayTimeZone::Utc => format!(
+                "DATE_TRUNC('day', {}::TIMESTAMPTZ AT TIME ZONE 'UTC')",
+                column
+            ),
+            DayTimeZone::Offset(seconds) => format!(
+                "DATE_TRUNC('day', ({}::TIMESTAMPTZ AT TIME ZONE 'UTC') + INTERVAL ({}) SECOND) - INTERVAL ({}) SECOND",
+                column, seconds, seconds
+            ),
+            DayTimeZone::Named(name) => format!(
+                "(DATE_TRUNC('day', {}::TIMESTAMPTZ AT TIME ZONE '{}') AT TIME ZONE '{}') AT TIME ZONE 'UTC'",
+                column, name, name
+            ),
+        }
+    }
+}
+
+impl FromStr for DayTimeZone {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.eq_ignore_ascii_case("utc") || value.eq_ignore_ascii_case("z") {
+            return Ok(DayTimeZone::Utc);
+        }
+        if let Some(offset) = value.strip_prefix(['+', '-']) {
+            let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "00"));
+            let two_digits = |part: &str| {
+                let digits = part.len() == 2 && part.bytes().all(|b| b.is_ascii_digit());
+                part.parse::<i32>().ok().filter(|_| digits)
+            };
+            let (Some(hours), Some(minutes)) = (two_digits(hours), two_digits(minutes)) else {
+                return Err(format!(
+                    "invalid offset '{}', expected +HH:MM or -HH:MM",
+                    value
+                ));
+            };
+            if hours > 14 || minutes >= 60 {
+                return Err(format!("offset '{}' is out of range", value));
+            }
+            let seconds = hours * 3600 + minutes * 60;
+            return Ok(match (value.starts_with('-'), seconds) {
+                (_, 0) => DayTimeZone::Utc,
+                (true, seconds) => DayTimeZone::Offset(-seconds),
+                (false, seconds) => DayTimeZone::Offset(seconds),
+            });
+        }
+        // The name ends up in the query text, so only allow the characters IANA names use
+        let valid_name = value.len() <= 64
+            && value.starts_with(|c: char| c.is_ascii_alphabetic())
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+        if !valid_name {
+            return Err(format!(
+                "unknown time zone '{}', expected an IANA name or +HH:MM offset",
+                value
+            ));
+        }
+        Ok(DayTimeZone::Named(value.to_owned()))
+    }
+}
+
+impl TryFrom<String> for DayTimeZone {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<DayTimeZone> for String {
+    fn from(value: DayTimeZone) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for DayTimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DayTimeZone::Utc => write!(f, "UTC"),
+            DayTimeZone::Offset(seconds) => {
+                let sign = if *seconds < 0 { '-' } else { '+' };
+                let seconds = seconds.unsigned_abs();
+                write!(
+                    f,
+                    "{}{:02}:{:02}",
+                    sign,
+                    seconds / 3600,
+                    seconds % 3600 / 60
+                )
+            }
+            DayTimeZone::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// How an observation window's temperature readings become `temp_low` and `temp_high`
+///
+/// Min/max are the extremes of every reading in the window, which are only the day's high and
+/// low when the window covers the whole day. First/last are the instantaneous readings at the
+/// window's edges, e.g. the temperature at the start and end of an event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ObservationTempAggregation {
+    /// Lowest reading as `temp_low`, highest as `temp_high`
+    #[default]
+    MinMax,
+    /// Earliest reading as `temp_low`, latest as `temp_high`
+    FirstLast,
+}
+
+impl ObservationTempAggregation {
+    /// DuckDB aggregates for `temp_low` and `temp_high` over `column`, readings ordered by
+    /// `generated_at`
+    pub fn sql(&self, column: &str) -> (String, String) {
+        match self {
+            ObservationTempAggregation::MinMax => {
+                (format!("MIN({})", column), format!("MAX({})", column))
+            }
+            ObservationTempAggregation::FirstLast => (
+                format!("arg_min({}, generated_at::TIMESTAMPTZ)", column),
+                format!("arg_max({}, generated_at::TIMESTAMPTZ)", column),
+            ),
+        }
+    }
+}
+
+impl FromStr for ObservationTempAggregation {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "minmax" => Ok(ObservationTempAggregation::MinMax),
+            "firstlast" => Ok(ObservationTempAggregation::FirstLast),
+            _ => Err(format!(
+                "unknown temperature aggregation '{}', expected minmax or firstlast",
+                value
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for ObservationTempAggregation {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ObservationTempAggregation> for String {
+    fn from(value: ObservationTempAggregation) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for ObservationTempAggregation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObservationTempAggregation::MinMax => write!(f, "minmax"),
+            ObservationTempAggregation::FirstLast => write!(f, "firstlast"),
+        }
+    }
+}
+
+/// Which wind readings an observation's `wind_speed` is taken from
+///
+/// Forecasts only give sustained wind, so `Sustained` keeps observations comparable with them.
+/// `Gust` is for events about peak gusts, which can run well above the sustained wind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ObservationWindAggregation {
+    /// Highest sustained wind
+    #[default]
+    Sustained,
+    /// Highest gust, falling back to the sustained wind for readings without one
+    Gust,
+}
+
+impl ObservationWindAggregation {
+    /// DuckDB expression for the wind reading `wind_speed` is the max of
+    pub fn sql(&self) -> &'static str {
+        match self {
+            ObservationWindAggregation::Sustained => "wind_speed",
+            ObservationWindAggregation::Gust => "COALESCE(wind_gust, wind_speed)",
+        }
+    }
+}
+
+impl FromStr for ObservationWindAggregation {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "sustained" => Ok(ObservationWindAggregation::Sustained),
+            "gust" => Ok(ObservationWindAggregation::Gust),
+            _ => Err(format!(
+                "unknown wind aggregation '{}', expected sustained or gust",
+                value
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for ObservationWindAggregation {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ObservationWindAggregation> for String {
+    fn from(value: ObservationWindAggregation) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for ObservationWindAggregation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObservationWindAggregation::Sustained => write!(f, "sustained"),
+            ObservationWindAggregation::Gust => write!(f, "gust"),
+        }
+    }
 }
 
 impl ObservationRequest {
@@ -110,7 +768,64 @@ impl From<&ObservationRequest> for FileParams {
     }
 }
 
-#[derive(Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+#[derive(Clone, Serialize, Deserialize, IntoParams)]
+pub struct CompareRequest {
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[serde(default)]
+    pub start: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[serde(default)]
+    pub end: Option<OffsetDateTime>,
+    /// Comma separated station ids
+    pub station: String,
+    /// Unit both sides are converted to before comparing
+    #[serde(default)]
+    pub unit: TemperatureUnit,
+}
+
+impl CompareRequest {
+    pub fn station_ids(&self) -> Vec<String> {
+        self.station.split(',').map(|id| id.to_owned()).collect()
+    }
+}
+
+impl From<&CompareRequest> for ForecastRequest {
+    fn from(value: &CompareRequest) -> Self {
+        ForecastRequest {
+            start: value.start,
+            end: value.end,
+            generated_start: None,
+            generated_end: None,
+            as_of: None,
+            station_ids: value.station.clone(),
+            temperature_unit: value.unit.clone(),
+            wind_unit: None,
+            precip_unit: PrecipUnit::default(),
+            agg: ForecastAggregation::default(),
+            fields: ForecastFields::default(),
+            granularity: None,
+        }
+    }
+}
+
+impl From<&CompareRequest> for ObservationRequest {
+    fn from(value: &CompareRequest) -> Self {
+        ObservationRequest {
+            start: value.start,
+            end: value.end,
+            station_ids: value.station.clone(),
+            temperature_unit: value.unit.clone(),
+            tz: DayTimeZone::default(),
+            temp_agg: ObservationTempAggregation::default(),
+            wind_agg: ObservationWindAggregation::default(),
+            wind_unit: None,
+            precip_unit: PrecipUnit::default(),
+            granularity: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum TemperatureUnit {
     Celsius,
@@ -127,50 +842,488 @@ impl fmt::Display for TemperatureUnit {
     }
 }
 
+/// Unit wind speeds are returned in, each row's stored `wind_speed_unit_code` says which
+/// unit it is converted from
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum WindSpeedUnit {
+    Knots,
+    Mph,
+    MetersPerSecond,
+}
+
+impl WindSpeedUnit {
+    /// Meters per second in one of this unit
+    fn meters_per_second(&self) -> f64 {
+        match self {
+            WindSpeedUnit::Knots => 1852.0 / 3600.0,
+            WindSpeedUnit::Mph => 1609.344 / 3600.0,
+            WindSpeedUnit::MetersPerSecond => 1.0,
+        }
+    }
+
+    /// Converts a speed in `from` to this unit, rounded to a whole number like the stored speeds
+    pub fn convert(&self, value: i64, from: WindSpeedUnit) -> i64 {
+        if from == *self {
+            return value;
+        }
+        (value as f64 * from.meters_per_second() / self.meters_per_second()).round() as i64
+    }
+}
+
+impl FromStr for WindSpeedUnit {
+    type Err = String;
+
+    /// Accepts the request names as well as the unit codes the data is stored with
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "knots" | "knot" | "kt" | "kts" => Ok(WindSpeedUnit::Knots),
+            "mph" => Ok(WindSpeedUnit::Mph),
+            "m/s" | "m-s" | "mps" | "m_s" => Ok(WindSpeedUnit::MetersPerSecond),
+            _ => Err(format!(
+                "unknown wind speed unit '{}', expected knots, mph or m/s",
+                value
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for WindSpeedUnit {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<WindSpeedUnit> for String {
+    fn from(value: WindSpeedUnit) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for WindSpeedUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WindSpeedUnit::Knots => write!(f, "knots"),
+            WindSpeedUnit::Mph => write!(f, "mph"),
+            WindSpeedUnit::MetersPerSecond => write!(f, "m/s"),
+        }
+    }
+}
+
+/// Unit precipitation amounts are returned in, the data is stored in inches
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum PrecipUnit {
+    #[default]
+    Inches,
+    Millimeters,
+}
+
+impl PrecipUnit {
+    /// Converts an amount in `from` to this unit
+    pub fn convert(&self, value: f64, from: PrecipUnit) -> f64 {
+        match (from, self) {
+            (PrecipUnit::Inches, PrecipUnit::Millimeters) => value * 25.4,
+            (PrecipUnit::Millimeters, PrecipUnit::Inches) => value / 25.4,
+            _ => value,
+        }
+    }
+}
+
+impl FromStr for PrecipUnit {
+    type Err = String;
+
+    /// Accepts the request names as well as the unit codes the data is stored with
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "in" | "inch" | "inches" => Ok(PrecipUnit::Inches),
+            "mm" | "millimeters" | "millimetres" => Ok(PrecipUnit::Millimeters),
+            _ => Err(format!(
+                "unknown precipitation unit '{}', expected in or mm",
+                value
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for PrecipUnit {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<PrecipUnit> for String {
+    fn from(value: PrecipUnit) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for PrecipUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PrecipUnit::Inches => write!(f, "in"),
+            PrecipUnit::Millimeters => write!(f, "mm"),
+        }
+    }
+}
+
+/// Time buckets weather rows are grouped in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Granularity {
+    /// No grouping, a row per forecast window start or observation reading
+    Raw,
+    /// UTC hours
+    Hourly,
+    #[default]
+    Daily,
+}
+
+impl Granularity {
+    /// DuckDB expression for the UTC start of the bucket the RFC3339 text in `column` falls
+    /// in, days start in `tz`
+    pub fn start_sql(&self, column: &str, tz: &DayTimeZone) -> String {
+        match self {
+            Granularity::Raw => format!("{}::TIMESTAMPTZ AT TIME ZONE 'UTC'", column),
+            Granularity::Hourly => format!(
+                "DATE_TRUNC('hour', {}::TIMESTAMPTZ AT TIME ZONE 'UTC')",
+                column
+            ),
+            Granularity::Daily => tz.day_start_sql(column),
+        }
+    }
+
+    /// DuckDB expression for the RFC3339 `bucket` value of the UTC timestamp `start`
+    pub fn bucket_sql(start: &str) -> String {
+        format!("strftime({}, '%Y-%m-%dT%H:%M:%SZ')", start)
+    }
+}
+
+impl FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "raw" => Ok(Granularity::Raw),
+            "hourly" => Ok(Granularity::Hourly),
+            "daily" => Ok(Granularity::Daily),
+            _ => Err(format!(
+                "unknown granularity '{}', expected raw, hourly or daily",
+                value
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for Granularity {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Granularity> for String {
+    fn from(value: Granularity) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for Granularity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Granularity::Raw => write!(f, "raw"),
+            Granularity::Hourly => write!(f, "hourly"),
+            Granularity::Daily => write!(f, "daily"),
+        }
+    }
+}
+
 #[utoipa::path(
     get,
     path = "stations/observations",
     params(
-        ObservationRequest
+        ObservationRequest,
+        EnvelopeRequest
     ),
     responses(
-        (status = OK, description = "Successfully retrieved observation data", body = Vec<Observation>),
-        (status = BAD_REQUEST, description = "Times are not in RFC3339 format"),
+        (status = OK, description = "Successfully retrieved observation data", body = Vec<Observation>, headers(
+            ("x-rows" = usize, description = "Rows returned"),
+            ("x-files" = usize, description = "Parquet files scanned"),
+            ("x-query-ms" = u64, description = "Query time in milliseconds"),
+            ("x-data-generated-at" = String, description = "Newest generated_at behind the rows, RFC3339"),
+            ("x-data-source" = String, description = "Only on empty results: `none` when no files exist for the range, `empty` when files matched but no rows did")
+        )),
+        (status = BAD_REQUEST, description = "Times are not in RFC3339 format, the granularity is unknown or a station alias doesn't resolve"),
         (status = INTERNAL_SERVER_ERROR, description = "Failed to retrieved weather data")
     ))]
 pub async fn observations(
     State(state): State<Arc<AppState>>,
     Query(req): Query<ObservationRequest>,
-) -> Result<Json<Vec<Observation>>, AppError> {
-    let observations = state
+    Query(envelope): Query<EnvelopeRequest>,
+) -> Result<(HeaderMap, Json<WeatherResponse<Vec<Observation>>>), AppError> {
+    let station_ids = resolve_station_ids(state.weather_db.as_ref(), req.station_ids()).await?;
+    let (observations, stats) = state
         .weather_db
-        .observation_data(&req, req.station_ids())
+        .observation_data_with_stats(&req, station_ids)
         .await?;
 
-    Ok(Json(observations))
+    Ok((
+        stats_headers(&stats),
+        Json(WeatherResponse::new(observations, &stats, &envelope)),
+    ))
 }
 
 #[utoipa::path(
     get,
     path = "stations/daily-observations",
     params(
-        ObservationRequest
+        ObservationRequest,
+        EnvelopeRequest
     ),
     responses(
-        (status = OK, description = "Successfully retrieved daily observation data", body = Vec<DailyObservation>),
-        (status = BAD_REQUEST, description = "Times are not in RFC3339 format"),
+        (status = OK, description = "Successfully retrieved daily observation data", body = Vec<DailyObservation>, headers(
+            ("x-rows" = usize, description = "Rows returned"),
+            ("x-files" = usize, description = "Parquet files scanned"),
+            ("x-query-ms" = u64, description = "Query time in milliseconds"),
+            ("x-data-generated-at" = String, description = "Newest generated_at behind the rows, RFC3339"),
+            ("x-data-source" = String, description = "Only on empty results: `none` when no files exist for the range, `empty` when files matched but no rows did")
+        )),
+        (status = BAD_REQUEST, description = "Times are not in RFC3339 format, the time zone is invalid or a station alias doesn't resolve"),
         (status = INTERNAL_SERVER_ERROR, description = "Failed to retrieved weather data")
     ))]
 pub async fn daily_observations(
     State(state): State<Arc<AppState>>,
     Query(req): Query<ObservationRequest>,
-) -> Result<Json<Vec<DailyObservation>>, AppError> {
-    let observations = state
+    Query(envelope): Query<EnvelopeRequest>,
+) -> Result<(HeaderMap, Json<WeatherResponse<Vec<DailyObservation>>>), AppError> {
+    let station_ids = resolve_station_ids(state.weather_db.as_ref(), req.station_ids()).await?;
+    let (observations, stats) = state
+        .weather_db
+        .daily_observations_with_stats(&req, station_ids)
+        .await?;
+
+    Ok((
+        stats_headers(&stats),
+        Json(WeatherResponse::new(observations, &stats, &envelope)),
+    ))
+}
+
+#[derive(Clone, Debug, Deserialize, IntoParams)]
+pub struct CoverageRequest {
+    /// Start of the range (RFC3339)
+    #[serde(with = "time::serde::rfc3339")]
+    #[param(value_type = String, example = "2024-08-12T00:00:00Z")]
+    pub start: OffsetDateTime,
+    /// End of the range, exclusive (RFC3339)
+    #[serde(with = "time::serde::rfc3339")]
+    #[param(value_type = String, example = "2024-08-13T00:00:00Z")]
+    pub end: OffsetDateTime,
+}
+
+impl CoverageRequest {
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.end <= self.start {
+            return Err(anyhow::anyhow!("end must be after start"));
+        }
+        Ok(())
+    }
+
+    /// UTC hours the range touches, each one a slot a station is expected to report in
+    pub fn hourly_slots(&self) -> i64 {
+        let start = self.start.to_offset(UtcOffset::UTC);
+        let first_hour = start
+            .replace_minute(0)
+            .and_then(|at| at.replace_second(0))
+            .and_then(|at| at.replace_nanosecond(0))
+            .unwrap_or(start);
+        let seconds = (self.end - first_hour).whole_seconds();
+        (seconds + 3599) / 3600
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "observations/coverage",
+    params(
+        CoverageRequest
+    ),
+    responses(
+        (status = OK, description = "Per station observation counts and the share of hourly slots reported in, least covered first", body = Vec<ObservationCoverage>),
+        (status = BAD_REQUEST, description = "Times are missing, not in RFC3339 format or end isn't after start"),
+        (status = INTERNAL_SERVER_ERROR, description = "Failed to retrieved weather data")
+    ))]
+pub async fn observation_coverage(
+    State(state): State<Arc<AppState>>,
+    Query(req): Query<CoverageRequest>,
+) -> Result<Json<Vec<ObservationCoverage>>, AppError> {
+    req.validate()?;
+    let coverage = state.weather_db.observation_coverage(&req).await?;
+    Ok(Json(coverage))
+}
+
+#[utoipa::path(
+    get,
+    path = "compare",
+    params(
+        CompareRequest
+    ),
+    responses(
+        (status = OK, description = "Successfully compared daily forecasts with observations", body = Vec<DailyComparison>),
+        (status = BAD_REQUEST, description = "Times are not in RFC3339 format or a station alias doesn't resolve"),
+        (status = INTERNAL_SERVER_ERROR, description = "Failed to retrieved weather data")
+    ))]
+pub async fn compare(
+    State(state): State<Arc<AppState>>,
+    Query(req): Query<CompareRequest>,
+) -> Result<Json<Vec<DailyComparison>>, AppError> {
+    let station_ids = resolve_station_ids(state.weather_db.as_ref(), req.station_ids()).await?;
+    let comparisons = state
         .weather_db
-        .daily_observations(&req, req.station_ids())
+        .forecast_comparison(&req, station_ids)
         .await?;
 
-    Ok(Json(observations))
+    Ok(Json(comparisons))
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams)]
+pub struct ForecastExportRequest {
+    /// Start of the forecast period (RFC3339)
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[serde(default)]
+    pub start: Option<OffsetDateTime>,
+    /// End of the forecast period (RFC3339)
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[serde(default)]
+    pub end: Option<OffsetDateTime>,
+    /// Comma separated station ids, every station when left off
+    #[serde(default)]
+    pub station: Option<String>,
+    #[serde(default)]
+    pub temperature_unit: TemperatureUnit,
+}
+
+impl ForecastExportRequest {
+    pub fn station_ids(&self) -> Vec<String> {
+        self.station
+            .iter()
+            .flat_map(|station| station.split(','))
+            .map(|id| id.to_owned())
+            .collect()
+    }
+}
+
+impl From<&ForecastExportRequest> for ForecastRequest {
+    fn from(value: &ForecastExportRequest) -> Self {
+        ForecastRequest {
+            start: value.start,
+            end: value.end,
+            generated_start: None,
+            generated_end: None,
+            as_of: None,
+            station_ids: value.station.clone().unwrap_or_default(),
+            temperature_unit: value.temperature_unit.clone(),
+            wind_unit: None,
+            precip_unit: PrecipUnit::default(),
+            agg: ForecastAggregation::default(),
+            fields: ForecastFields::default(),
+            granularity: None,
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "forecasts.parquet",
+    params(
+        ForecastExportRequest
+    ),
+    responses(
+        (status = OK, description = "The daily forecasts `stations/forecasts` returns as a parquet file, one row per station and day", content_type = "application/vnd.apache.parquet", headers(
+            ("x-rows" = usize, description = "Rows in the file"),
+            ("x-files" = usize, description = "Parquet files scanned"),
+            ("x-query-ms" = u64, description = "Query time in milliseconds"),
+            ("x-data-generated-at" = String, description = "Newest generated_at behind the rows, RFC3339")
+        )),
+        (status = BAD_REQUEST, description = "Times are not in RFC3339 format or a station alias doesn't resolve"),
+        (status = INTERNAL_SERVER_ERROR, description = "Failed to export the forecasts")
+    ))]
+pub async fn forecasts_parquet(
+    State(state): State<Arc<AppState>>,
+    Query(req): Query<ForecastExportRequest>,
+) -> Result<Response, AppError> {
+    let station_ids = resolve_station_ids(state.weather_db.as_ref(), req.station_ids()).await?;
+    let (body, stats) = state
+        .weather_db
+        .forecasts_parquet(&ForecastRequest::from(&req), station_ids)
+        .await?;
+
+    Ok((
+        stats_headers(&stats),
+        [
+            (CONTENT_TYPE, "application/vnd.apache.parquet"),
+            (
+                CONTENT_DISPOSITION,
+                "attachment; filename=\"forecasts.parquet\"",
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Exposes query size and timing as `x-rows`, `x-files` and `x-query-ms` headers, how
+/// fresh the data is as `x-data-generated-at` and why an empty result is empty as
+/// `x-data-source`
+fn stats_headers(stats: &QueryStats) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-rows", HeaderValue::from(stats.rows));
+    if let Some(files) = stats.files {
+        headers.insert("x-files", HeaderValue::from(files));
+    }
+    headers.insert("x-query-ms", HeaderValue::from(stats.elapsed_ms));
+    if let Some(generated_at) =
+        generated_at_rfc3339(stats).and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        headers.insert("x-data-generated-at", generated_at);
+    }
+    if let Some(empty) = stats.empty_data() {
+        headers.insert("x-data-source", HeaderValue::from_static(empty.as_str()));
+    }
+    headers
+}
+
+fn generated_at_rfc3339(stats: &QueryStats) -> Option<String> {
+    stats.generated_at?.format(&Rfc3339).ok()
+}
+
+/// Maps IATA/ICAO aliases in a station filter to NOAA station ids. NOAA station ids are 4
+/// characters, so the station list is only read when some id isn't, keeping plain station id
+/// filters as cheap as before
+pub async fn resolve_station_ids(
+    weather_db: &dyn WeatherData,
+    station_ids: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    let station_ids: Vec<String> = station_ids
+        .iter()
+        .map(|id| id.trim().to_uppercase())
+        .collect();
+    if station_ids
+        .iter()
+        .all(|id| id.is_empty() || id.chars().count() == 4)
+    {
+        return Ok(station_ids);
+    }
+    let station_ids: Vec<String> = station_ids
+        .into_iter()
+        .filter(|id| !id.is_empty())
+        .collect();
+    let stations = weather_db.stations().await?;
+    Ok(StationAliases::new(&stations).resolve_all(&station_ids)?)
 }
 
 #[utoipa::path(
@@ -186,3 +1339,163 @@ pub async fn get_stations(
     let stations: Vec<Station> = state.weather_db.stations().await?;
     Ok(Json(stations))
 }
+
+/// Most ids a single `/stations/lookup` request accepts
+pub const MAX_STATION_LOOKUP_IDS: usize = 500;
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct StationLookup {
+    /// Station ids, IATA or ICAO codes to look up, at most 500
+    pub ids: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum StationLookupResult {
+    Found { station: Station },
+    NotFound { id: String },
+}
+
+#[utoipa::path(
+    post,
+    path = "stations/lookup",
+    request_body = StationLookup,
+    responses(
+        (status = OK, description = "The requested stations in request order, ids without a station are marked not_found", body = Vec<StationLookupResult>),
+        (status = BAD_REQUEST, description = "More than 500 ids requested"),
+        (status = INTERNAL_SERVER_ERROR, description = "Failed to retrieved weather stations from data")
+    ))]
+pub async fn lookup_stations(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<StationLookup>,
+) -> Result<Json<Vec<StationLookupResult>>, AppError> {
+    if body.ids.len() > MAX_STATION_LOOKUP_IDS {
+        return Err(AppError::Request(anyhow::anyhow!(
+            "requested {} stations, max per lookup is {}",
+            body.ids.len(),
+            MAX_STATION_LOOKUP_IDS
+        )));
+    }
+    let stations = state.cached_stations().await?;
+    let aliases = StationAliases::new(&stations);
+    let by_id: HashMap<&str, &Station> = stations
+        .iter()
+        .map(|station| (station.station_id.as_str(), station))
+        .collect();
+    let results = body
+        .ids
+        .into_iter()
+        .map(|id| {
+            match aliases
+                .resolve(&id)
+                .ok()
+                .and_then(|station_id| by_id.get(station_id.as_str()).copied())
+            {
+                Some(station) => StationLookupResult::Found {
+                    station: station.clone(),
+                },
+                None => StationLookupResult::NotFound { id },
+            }
+        })
+        .collect();
+    Ok(Json(results))
+}
+
+time::serde::format_description!(utc_date, Date, "[year]-[month]-[day]");
+
+#[derive(Clone, Debug, Deserialize, IntoParams)]
+pub struct AvailableStationsRequest {
+    /// UTC day to check, `YYYY-MM-DD`
+    #[serde(with = "utc_date")]
+    #[param(value_type = String, example = "2024-08-12")]
+    pub date: Date,
+    /// Which files to look in, observations when unset
+    #[serde(rename = "type")]
+    #[param(rename = "type", inline)]
+    pub file_type: Option<FileType>,
+}
+
+#[utoipa::path(
+    get,
+    path = "stations/available",
+    params(
+        AvailableStationsRequest
+    ),
+    responses(
+        (status = OK, description = "Ids of the stations with data in the files generated on the date, sorted", body = Vec<String>),
+        (status = BAD_REQUEST, description = "Date is not in YYYY-MM-DD format or the type is unknown"),
+        (status = INTERNAL_SERVER_ERROR, description = "Failed to read the date's files")
+    ))]
+pub async fn available_stations(
+    State(state): State<Arc<AppState>>,
+    Query(req): Query<AvailableStationsRequest>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let station_ids = state
+        .weather_db
+        .stations_with_data(req.date, req.file_type.unwrap_or(FileType::Observations))
+        .await?;
+    Ok(Json(station_ids))
+}
+
+/// SQL to run over the weather files, the same tables the raw data page queries in the browser
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct CustomQueryRequest {
+    /// A single SELECT over the `observations` and `forecasts` tables
+    #[schema(
+        example = "SELECT station_id, MAX(temperature_value) AS high FROM observations GROUP BY station_id"
+    )]
+    pub sql: String,
+    /// Only load files generated at or after this time (RFC3339)
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub start: Option<OffsetDateTime>,
+    /// Only load files generated at or before this time (RFC3339)
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub end: Option<OffsetDateTime>,
+    /// Load the observation files into `observations`
+    pub observations: Option<bool>,
+    /// Load the forecast files into `forecasts`, both load when neither is set
+    pub forecasts: Option<bool>,
+}
+
+#[utoipa::path(
+    post,
+    path = "query",
+    request_body = CustomQueryRequest,
+    responses(
+        (status = OK, description = "Newline delimited JSON, one object per row. A last line with only an `error` means the result was cut short, e.g. by the row or time limit", content_type = "application/x-ndjson"),
+        (status = BAD_REQUEST, description = "Not a single SELECT, the SQL failed or the query hit the time limit before any rows"),
+        (status = INTERNAL_SERVER_ERROR, description = "Failed to load the weather files")
+    ))]
+pub async fn custom_query(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CustomQueryRequest>,
+) -> Result<Response, AppError> {
+    let mut rows = state
+        .weather_db
+        .custom_query(&req, state.custom_query_limits)
+        .await?;
+    // A query that fails before its first row still gets an error status
+    let first = match rows.recv().await {
+        Some(Err(err)) => return Err(err.into()),
+        first => first,
+    };
+    let lines = stream::unfold((first, rows), |(next, mut rows)| async move {
+        let line = match next? {
+            Ok(row) => Value::Object(row),
+            Err(err) => {
+                error!("custom query cut short: {}", err);
+                json!({ "error": err.to_string() })
+            }
+        };
+        let following = rows.recv().await;
+        Some((
+            Ok::<_, Infallible>(format!("{}\n", line)),
+            (following, rows),
+        ))
+    });
+    Ok((
+        [(CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response())
+}
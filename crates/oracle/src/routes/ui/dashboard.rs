@@ -11,13 +11,14 @@ use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 use std::collections::HashMap;
 
 use crate::{
-    db::EventStatus,
     templates::{
         dashboard_page,
         pages::dashboard::{dashboard_content, DashboardData},
         EventStats, WeatherDisplay,
     },
-    AppState, ForecastRequest, ObservationRequest, TemperatureUnit,
+    AppState, DayTimeZone, ForecastAggregation, ForecastFields, ForecastRequest,
+    ObservationRequest, ObservationTempAggregation, ObservationWindAggregation, PrecipUnit,
+    TemperatureUnit, DEFAULT_STATS_WINDOW_DAYS,
 };
 
 #[derive(Debug, Deserialize, Default)]
@@ -68,22 +69,12 @@ async fn build_dashboard_data(
     let npub = state.oracle.npub().unwrap_or_else(|_| "Error".to_string());
 
     // Get event statistics
-    let events = state
+    let stats: EventStats = state
         .oracle
-        .list_events(crate::db::EventFilter::default())
+        .event_aggregates(DEFAULT_STATS_WINDOW_DAYS)
         .await
-        .unwrap_or_default();
-
-    let mut stats = EventStats::default();
-
-    for event in &events {
-        match event.status {
-            EventStatus::Live => stats.live_count += 1,
-            EventStatus::Running => stats.running_count += 1,
-            EventStatus::Completed => stats.completed_count += 1,
-            EventStatus::Signed => stats.signed_count += 1,
-        }
-    }
+        .unwrap_or_default()
+        .into();
 
     // Always show weather for major airports on the dashboard
     let weather = get_latest_weather(state, start, end).await;
@@ -236,6 +227,12 @@ async fn get_latest_weather(
         end: query_end,
         station_ids: String::new(), // Empty = no filter, get all stations
         temperature_unit: TemperatureUnit::Fahrenheit,
+        tz: DayTimeZone::default(),
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        granularity: None,
     };
 
     let observations = state
@@ -314,8 +311,14 @@ async fn get_latest_weather(
             end: Some(today_end),
             generated_start: Some(yesterday_start),
             generated_end: Some(today_start),
+            as_of: None,
             station_ids: station_ids.join(","),
             temperature_unit: TemperatureUnit::Fahrenheit,
+            wind_unit: None,
+            precip_unit: PrecipUnit::default(),
+            agg: ForecastAggregation::default(),
+            fields: ForecastFields::default(),
+            granularity: None,
         };
 
         if let Ok(forecasts) = state
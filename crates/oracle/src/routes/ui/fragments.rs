@@ -4,18 +4,20 @@ use axum::{
     extract::{Query, State},
     response::Html,
 };
+use futures::FutureExt;
 use serde::Deserialize;
 use time::OffsetDateTime;
 
 use std::collections::HashMap;
 
 use crate::{
-    db::EventStatus,
     templates::{
         fragments::{event_stats, forecast_detail, oracle_info, weather_table_body},
         EventStats, ForecastComparison, ForecastDisplay, WeatherDisplay,
     },
-    AppState, ForecastRequest, ObservationRequest, TemperatureUnit,
+    AppState, DayTimeZone, ForecastAggregation, ForecastFields, ForecastRequest,
+    ObservationRequest, ObservationTempAggregation, ObservationWindAggregation, PrecipUnit,
+    TemperatureUnit, DEFAULT_STATS_WINDOW_DAYS,
 };
 
 /// Top 100 major US airport station IDs to show by default
@@ -45,21 +47,12 @@ pub async fn oracle_info_handler(State(state): State<Arc<AppState>>) -> Html<Str
 
 /// Handler for event stats fragment (GET /fragments/event-stats)
 pub async fn event_stats_handler(State(state): State<Arc<AppState>>) -> Html<String> {
-    let events = state
+    let stats: EventStats = state
         .oracle
-        .list_events(crate::db::EventFilter::default())
+        .event_aggregates(DEFAULT_STATS_WINDOW_DAYS)
         .await
-        .unwrap_or_default();
-
-    let mut stats = EventStats::default();
-    for event in &events {
-        match event.status {
-            EventStatus::Live => stats.live_count += 1,
-            EventStatus::Running => stats.running_count += 1,
-            EventStatus::Completed => stats.completed_count += 1,
-            EventStatus::Signed => stats.signed_count += 1,
-        }
-    }
+        .unwrap_or_default()
+        .into();
 
     Html(event_stats(&stats).into_string())
 }
@@ -117,6 +110,12 @@ async fn get_weather_for_stations(
         end: Some(now),
         station_ids: station_ids.join(","),
         temperature_unit: TemperatureUnit::Fahrenheit,
+        tz: DayTimeZone::default(),
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        granularity: None,
     };
 
     let observations = state
@@ -179,8 +178,14 @@ async fn populate_forecast_accuracy(state: &Arc<AppState>, weather_data: &mut [W
         end: Some(today_end),
         generated_start: Some(yesterday_start),
         generated_end: Some(today_start),
+        as_of: None,
         station_ids: station_ids.join(","),
         temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        agg: ForecastAggregation::default(),
+        fields: ForecastFields::default(),
+        granularity: None,
     };
 
     if let Ok(forecasts) = state
@@ -209,27 +214,45 @@ pub async fn forecast_handler(
 ) -> Html<String> {
     // Check cache first (keyed by station_id, refreshed every 30 min by background task)
     {
-        let cache = state.forecast_cache.lock().unwrap();
+        let cache = state.lock_forecast_cache();
         if let Some(cached) = cache.get(&station_id) {
             return Html(cached.html.clone());
         }
     }
 
     // Cache miss (non-default station or first request before warming completes)
-    let html = build_forecast_html(&state, &station_id).await;
-
-    {
-        let mut cache = state.forecast_cache.lock().unwrap();
-        cache.insert(
-            station_id,
-            crate::CachedFragment {
-                html: html.clone(),
-                created_at: std::time::Instant::now(),
-            },
-        );
-    }
+    Html(forecast_flight(&state, &station_id).await)
+}
 
-    Html(html)
+/// Build and cache a station's forecast fragment, joining the build already in flight for the
+/// station if there is one so concurrent cache misses share a single set of queries
+pub async fn forecast_flight(state: &Arc<AppState>, station_id: &str) -> String {
+    let flight = {
+        let mut flights = state.lock_forecast_flights();
+        flights
+            .entry(station_id.to_string())
+            .or_insert_with(|| {
+                let state = state.clone();
+                let station_id = station_id.to_string();
+                async move {
+                    let html = build_forecast_html(&state, &station_id).await;
+                    // Cached before the flight is dropped so later misses find one or the other
+                    state.lock_forecast_cache().insert(
+                        station_id.clone(),
+                        crate::CachedFragment {
+                            html: html.clone(),
+                            created_at: std::time::Instant::now(),
+                        },
+                    );
+                    state.lock_forecast_flights().remove(&station_id);
+                    html
+                }
+                .boxed()
+                .shared()
+            })
+            .clone()
+    };
+    flight.await
 }
 
 /// Build the forecast detail HTML for a station (used by handler and cache warming)
@@ -243,8 +266,14 @@ pub async fn build_forecast_html(state: &Arc<AppState>, station_id: &str) -> Str
         end: Some(future_end),
         generated_start: None,
         generated_end: None,
+        as_of: None,
         station_ids: station_id.to_string(),
         temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        agg: ForecastAggregation::default(),
+        fields: ForecastFields::default(),
+        granularity: None,
     };
 
     let forecasts = state
@@ -279,8 +308,14 @@ pub async fn build_forecast_html(state: &Arc<AppState>, station_id: &str) -> Str
         end: Some(now),
         generated_start: Some(past_start - time::Duration::days(1)),
         generated_end: Some(now),
+        as_of: None,
         station_ids: station_id.to_string(),
         temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        agg: ForecastAggregation::default(),
+        fields: ForecastFields::default(),
+        granularity: None,
     };
 
     let obs_req = ObservationRequest {
@@ -288,6 +323,12 @@ pub async fn build_forecast_html(state: &Arc<AppState>, station_id: &str) -> Str
         end: Some(now),
         station_ids: station_id.to_string(),
         temperature_unit: TemperatureUnit::Fahrenheit,
+        tz: DayTimeZone::default(),
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        granularity: None,
     };
 
     let (past_forecasts, daily_obs) = tokio::join!(
@@ -339,6 +380,17 @@ pub async fn build_forecast_html(state: &Arc<AppState>, station_id: &str) -> Str
     forecast_detail(station_id, &comparisons, &forecast_displays).into_string()
 }
 
+/// Runs one warm cycle in its own task so a panic ends that cycle instead of the refresh loop.
+/// A panicked build leaves its flight behind and every later join would panic with it, so the
+/// flights are dropped and the next cycle starts fresh
+pub async fn run_warm_cycle(state: &Arc<AppState>) {
+    let cycle_state = state.clone();
+    if let Err(e) = tokio::spawn(async move { warm_forecast_cache(&cycle_state).await }).await {
+        log::error!("forecast cache warming failed: {}", e);
+        state.lock_forecast_flights().clear();
+    }
+}
+
 /// Pre-warm the forecast cache for all default stations.
 /// Called at startup and every 30 minutes by the background refresh task.
 pub async fn warm_forecast_cache(state: &Arc<AppState>) {
@@ -355,15 +407,7 @@ pub async fn warm_forecast_cache(state: &Arc<AppState>) {
             let state = state.clone();
             let station_id = station_id.to_string();
             async move {
-                let html = build_forecast_html(&state, &station_id).await;
-                let mut cache = state.forecast_cache.lock().unwrap();
-                cache.insert(
-                    station_id,
-                    crate::CachedFragment {
-                        html,
-                        created_at: std::time::Instant::now(),
-                    },
-                );
+                forecast_flight(&state, &station_id).await;
             }
         })
         .collect();
@@ -373,5 +417,8 @@ pub async fn warm_forecast_cache(state: &Arc<AppState>) {
         .collect::<Vec<()>>()
         .await;
 
+    state
+        .forecast_cache_warmed
+        .store(true, std::sync::atomic::Ordering::Release);
     log::info!("Forecast cache warming complete.");
 }
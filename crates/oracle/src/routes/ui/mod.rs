@@ -8,7 +8,7 @@ pub use dashboard::dashboard_handler;
 pub use event_detail::event_detail_handler;
 pub use events::{events_cards_handler, events_handler, events_rows_handler};
 pub use fragments::{
-    event_stats_handler, forecast_handler, oracle_info_handler, warm_forecast_cache,
-    weather_handler,
+    event_stats_handler, forecast_handler, oracle_info_handler, run_warm_cycle,
+    warm_forecast_cache, weather_handler,
 };
 pub use raw_data::raw_data_handler;
@@ -1,12 +1,18 @@
 use crate::{
-    add_event_entries, create_event, daily_observations, dashboard_handler, db, download,
-    event_detail_handler, event_stats_handler, events_cards_handler, events_handler,
-    events_rows_handler, files, forecast_handler, forecasts, get_event, get_event_entry, get_npub,
-    get_pubkey, get_stations, list_events, observations,
-    oracle::{self, Oracle},
-    oracle_info_handler, raw_data_handler, routes, update_data, upload,
-    weather_data::WeatherAccess,
-    weather_handler, Database, FileAccess, FileData, WeatherData,
+    add_event_entries, audit_event, available_stations, clone_event, compare, connect_event_store,
+    create_event, create_event_schema, custom_query, daily_observations, dashboard_handler, db,
+    db_maintenance, download, drop_suffix, event_changes, event_detail_handler, event_stats,
+    event_stats_handler, events_cards_handler, events_handler, events_rows_handler, files,
+    forecast_handler, forecasts, forecasts_parquet, get_event, get_event_announcement,
+    get_event_attestation, get_event_entry, get_event_weather, get_events_batch, get_npub,
+    get_oracle_info, get_pubkey, get_stations, list_events, lookup_stations, observation_coverage,
+    observations,
+    oracle::{self, NonceDerivation, Oracle},
+    oracle_info_handler, raw_data_handler, routes, update_data, upload, verify_attestation,
+    weather_data::{self, WeatherAccess},
+    weather_handler, CustomQueryLimits, DatabaseSettings, EventStore, FileAccess, FileData,
+    FileParams, HttpRetrySettings, HumidityFormula, MigrationStatus, OutcomeMessageVersion,
+    PrecipCodes, QuerySettings, RouteScope, SanityBounds, Station, WeatherData,
 };
 use anyhow::anyhow;
 use axum::{
@@ -14,29 +20,50 @@ use axum::{
     extract::{DefaultBodyLimit, Path, Request, State},
     http::{header, StatusCode},
     middleware::{self, Next},
-    response::{IntoResponse, Response},
+    response::{ErrorResponse, IntoResponse, Response},
     routing::{get, post},
-    Router,
+    Json, Router,
 };
+use futures::future::{BoxFuture, Shared};
 use hyper::{
     header::{ACCEPT, CONTENT_TYPE},
     Method,
 };
 use log::info;
+use noaa_oracle_core::DataLayout;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
-    time::Instant,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, Instant},
 };
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 use tower_http::cors::{Any, CorsLayer};
-use utoipa::OpenApi;
+use utoipa::{OpenApi, ToSchema};
 use utoipa_scalar::{Scalar, Servable};
 
+/// Largest parquet upload accepted from the daemon, kept apart from the API's body limit
+const MAX_UPLOAD_SIZE: usize = 30 * 1024 * 1024;
+
 pub struct CachedFragment {
     pub html: String,
     pub created_at: Instant,
 }
 
+/// Forecast fragment being built, awaited by every cache miss for the station while it runs
+pub type ForecastFlight = Shared<BoxFuture<'static, String>>;
+
+/// How long the station list is reused before it's read from the weather data again
+const STATION_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+pub struct CachedStations {
+    pub stations: Arc<Vec<Station>>,
+    pub created_at: Instant,
+}
+
 #[derive(Clone)]
 pub struct AppState {
     pub static_dir: String,
@@ -45,22 +72,100 @@ pub struct AppState {
     pub weather_db: Arc<dyn WeatherData>,
     pub oracle: Arc<Oracle>,
     pub forecast_cache: Arc<Mutex<HashMap<String, CachedFragment>>>,
+    /// Forecast fragments being built right now, keyed like `forecast_cache`
+    pub forecast_flights: Arc<Mutex<HashMap<String, ForecastFlight>>>,
+    /// Set once the first `warm_forecast_cache` run finishes, gates `/readyz`
+    pub forecast_cache_warmed: Arc<AtomicBool>,
+    /// Station list behind `/stations/lookup`, scanning every observation file on each lookup
+    /// would be too slow
+    pub station_cache: Arc<Mutex<Option<CachedStations>>>,
+    pub max_batch_events: usize,
+    /// Largest request body in bytes the POST routes buffer before answering 413
+    pub max_body_size: usize,
+    pub custom_query_limits: CustomQueryLimits,
+    /// Age past which the newest weather file fails `/readyz`, a stuck or dead daemon shows
+    /// up as the data aging past it. None skips the check
+    pub max_data_age: Option<time::Duration>,
+}
+
+impl AppState {
+    /// Locks the forecast cache, recovering it if a task panicked while holding the lock.
+    /// Fragments are inserted whole, so a poisoned cache is still consistent
+    pub fn lock_forecast_cache(&self) -> MutexGuard<'_, HashMap<String, CachedFragment>> {
+        self.forecast_cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the in-flight forecast builds, recovering them like `lock_forecast_cache`
+    pub fn lock_forecast_flights(&self) -> MutexGuard<'_, HashMap<String, ForecastFlight>> {
+        self.forecast_flights
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the cached station list, recovering it like `lock_forecast_cache`
+    fn lock_station_cache(&self) -> MutexGuard<'_, Option<CachedStations>> {
+        self.station_cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The weather data's stations, re-read once the cached list is older than
+    /// `STATION_CACHE_TTL`
+    pub async fn cached_stations(&self) -> Result<Arc<Vec<Station>>, weather_data::Error> {
+        let cached = self
+            .lock_station_cache()
+            .as_ref()
+            .filter(|cached| cached.created_at.elapsed() < STATION_CACHE_TTL)
+            .map(|cached| cached.stations.clone());
+        if let Some(stations) = cached {
+            return Ok(stations);
+        }
+        let stations = Arc::new(self.weather_db.stations().await?);
+        *self.lock_station_cache() = Some(CachedStations {
+            stations: stations.clone(),
+            created_at: Instant::now(),
+        });
+        Ok(stations)
+    }
 }
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
+        routes::events::oracle_routes::get_oracle_info,
         routes::events::oracle_routes::get_npub,
         routes::events::oracle_routes::get_pubkey,
         routes::events::oracle_routes::list_events,
+        routes::events::oracle_routes::event_stats,
+        routes::events::oracle_routes::event_changes,
         routes::events::oracle_routes::create_event,
+        routes::events::oracle_routes::clone_event,
+        routes::events::oracle_routes::create_event_schema,
         routes::events::oracle_routes::get_event,
+        routes::events::oracle_routes::get_events_batch,
         routes::events::oracle_routes::add_event_entries,
         routes::events::oracle_routes::get_event_entry,
+        routes::events::oracle_routes::get_event_weather,
+        routes::events::oracle_routes::get_event_announcement,
+        routes::events::oracle_routes::get_event_attestation,
+        routes::events::oracle_routes::audit_event,
         routes::events::oracle_routes::update_data,
+        routes::events::oracle_routes::verify_attestation,
+        routes::admin::db_routes::db_maintenance,
+        version,
+        livez,
+        readyz,
         routes::stations::weather_routes::forecasts,
         routes::stations::weather_routes::observations,
+        routes::stations::weather_routes::observation_coverage,
         routes::stations::weather_routes::get_stations,
+        routes::stations::weather_routes::lookup_stations,
+        routes::stations::weather_routes::available_stations,
+        routes::stations::weather_routes::compare,
+        routes::stations::weather_routes::forecasts_parquet,
+        routes::stations::weather_routes::custom_query,
         routes::files::download::download,
         routes::files::get_names::files,
         routes::files::upload::upload,
@@ -68,12 +173,41 @@ pub struct AppState {
     components(
         schemas(
                 routes::files::get_names::Files,
+                routes::files::get_names::FileEntry,
+                routes::files::get_names::FileType,
+                db::DailyComparison,
+                db::ObservationCoverage,
+                routes::stations::weather_routes::CustomQueryRequest,
+                routes::stations::weather_routes::StationLookup,
+                routes::stations::weather_routes::StationLookupResult,
                 oracle::Error,
+                routes::events::oracle_routes::ErrorBody,
                 db::Event,
+                db::GetEventsBatch,
+                db::BatchEvent,
                 db::WeatherEntry,
+                db::Weather,
+                db::Observed,
+                db::Forecasted,
                 db::AddEventEntry,
                 db::CreateEvent,
+                db::CloneEvent,
+                routes::events::oracle_routes::CreateEventSchema,
+                db::MigrationStatus,
+                db::MaintenanceReport,
+                db::EventAggregates,
+                db::EventChanges,
+                db::EventAnnouncement,
+                db::EventAttestation,
+                db::VerifyAttestation,
+                db::AttestationVerification,
+                db::EventAudit,
+                db::EntryAudit,
+                db::ByteEncoding,
+                VersionInfo,
+                Readiness,
                 routes::events::oracle_routes::Pubkey,
+                routes::events::oracle_routes::OracleInfo,
                 routes::events::oracle_routes::Base64Pubkey
             )
     ),
@@ -83,35 +217,145 @@ pub struct AppState {
 )]
 struct ApiDoc;
 
+/// Weather queries always read local files with DuckDB, even when downloads are served from S3.
+/// `http_retries` only matters once DuckDB reads weather files over HTTP/S3.
+#[allow(clippy::too_many_arguments)]
+pub fn build_weather_access(
+    data_dir: String,
+    data_layout: DataLayout,
+    duckdb_extension_dir: Option<String>,
+    query_settings: QuerySettings,
+    http_retries: Option<HttpRetrySettings>,
+    humidity_formula: HumidityFormula,
+    sanity_bounds: SanityBounds,
+    precip_codes: PrecipCodes,
+) -> Result<WeatherAccess, anyhow::Error> {
+    query_settings.validate()?;
+    if let Some(http_retries) = &http_retries {
+        http_retries.validate()?;
+    }
+    let local_file_access = Arc::new(FileAccess::new(data_dir).with_layout(data_layout));
+    Ok(WeatherAccess::new(local_file_access)
+        .map_err(|e| anyhow!("error setting up weather data: {}", e))?
+        .with_extension_dir(duckdb_extension_dir)
+        .with_query_settings(query_settings)
+        .with_http_retries(http_retries)
+        .with_humidity_formula(humidity_formula)
+        .with_sanity_bounds(sanity_bounds)
+        .with_precip_codes(precip_codes))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn build_app_state(
     remote_url: String,
     static_dir: String,
     data_dir: String,
-    event_dir: String,
+    database_url: String,
     private_key_file_path: String,
+    nonce_derivation: NonceDerivation,
+    outcome_message_version: OutcomeMessageVersion,
+    settlement_delay: time::Duration,
+    max_event_locations: usize,
     s3_bucket: Option<String>,
     s3_endpoint: Option<String>,
+    data_layout: DataLayout,
+    duckdb_extension_dir: Option<String>,
+    query_settings: QuerySettings,
+    http_retries: Option<HttpRetrySettings>,
+    humidity_formula: HumidityFormula,
+    sanity_bounds: SanityBounds,
+    precip_codes: PrecipCodes,
+    max_batch_events: usize,
+    max_body_size: usize,
+    custom_query_limits: CustomQueryLimits,
+    max_data_age: Option<time::Duration>,
+    db_settings: DatabaseSettings,
 ) -> Result<AppState, anyhow::Error> {
     let file_access: Arc<dyn FileData> = if let Some(bucket) = s3_bucket {
         info!("Using S3 bucket '{}' for file access", bucket);
-        Arc::new(crate::S3FileAccess::new(bucket, s3_endpoint).await)
+        Arc::new(
+            crate::S3FileAccess::new(bucket, s3_endpoint)
+                .await
+                .map_err(|e| anyhow!("error setting up S3 file access: {}", e))?,
+        )
     } else {
-        Arc::new(FileAccess::new(data_dir.clone()))
+        Arc::new(FileAccess::new(data_dir.clone()).with_layout(data_layout))
     };
 
-    // WeatherAccess always uses local files (for DuckDB parquet queries)
-    let local_file_access = Arc::new(FileAccess::new(data_dir));
-    let weather_db = Arc::new(
-        WeatherAccess::new(local_file_access)
-            .map_err(|e| anyhow!("error setting up weather data: {}", e))?,
-    );
+    let weather_db = Arc::new(build_weather_access(
+        data_dir,
+        data_layout,
+        duckdb_extension_dir,
+        query_settings,
+        http_retries,
+        humidity_formula,
+        sanity_bounds,
+        precip_codes,
+    )?);
 
-    let db = Arc::new(
-        Database::new(&event_dir)
-            .await
-            .map_err(|e| anyhow!("error setting up SQLite database: {}", e))?,
+    let event_store = connect_event_store(&database_url, db_settings)
+        .await
+        .map_err(|e| anyhow!("error setting up event database: {}", e))?;
+
+    build_app_state_with(
+        remote_url,
+        static_dir,
+        private_key_file_path,
+        nonce_derivation,
+        outcome_message_version,
+        settlement_delay,
+        max_event_locations,
+        max_batch_events,
+        max_body_size,
+        custom_query_limits,
+        max_data_age,
+        AppBackends {
+            file_access,
+            weather_db,
+            event_store,
+        },
+    )
+    .await
+}
+
+/// Data layers behind the app, built from config by `build_app_state` or supplied directly
+/// by tests and alternate backends through `build_app_state_with`
+pub struct AppBackends {
+    pub file_access: Arc<dyn FileData>,
+    pub weather_db: Arc<dyn WeatherData>,
+    pub event_store: Arc<dyn EventStore>,
+}
+
+/// App state over already built backends, e.g. a mock weather source or a Postgres store
+#[allow(clippy::too_many_arguments)]
+pub async fn build_app_state_with(
+    remote_url: String,
+    static_dir: String,
+    private_key_file_path: String,
+    nonce_derivation: NonceDerivation,
+    outcome_message_version: OutcomeMessageVersion,
+    settlement_delay: time::Duration,
+    max_event_locations: usize,
+    max_batch_events: usize,
+    max_body_size: usize,
+    custom_query_limits: CustomQueryLimits,
+    max_data_age: Option<time::Duration>,
+    backends: AppBackends,
+) -> Result<AppState, anyhow::Error> {
+    custom_query_limits.validate()?;
+    let AppBackends {
+        file_access,
+        weather_db,
+        event_store,
+    } = backends;
+    let oracle = Arc::new(
+        Oracle::new(event_store, weather_db.clone(), &private_key_file_path)
+            .await?
+            .with_nonce_derivation(nonce_derivation)
+            .with_outcome_message_version(outcome_message_version)
+            .with_settlement_delay(settlement_delay)
+            .with_max_event_locations(max_event_locations),
     );
-    let oracle = Arc::new(Oracle::new(db, weather_db.clone(), &private_key_file_path).await?);
 
     Ok(AppState {
         static_dir,
@@ -120,9 +364,191 @@ pub async fn build_app_state(
         file_access,
         oracle,
         forecast_cache: Arc::new(Mutex::new(HashMap::new())),
+        forecast_flights: Arc::new(Mutex::new(HashMap::new())),
+        forecast_cache_warmed: Arc::new(AtomicBool::new(false)),
+        station_cache: Arc::new(Mutex::new(None)),
+        max_batch_events,
+        max_body_size,
+        custom_query_limits,
+        max_data_age,
     })
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VersionInfo {
+    /// Version of the running oracle build
+    pub version: String,
+    pub migrations: MigrationStatus,
+}
+
+#[utoipa::path(
+    get,
+    path = "/version",
+    responses(
+        (status = OK, description = "Successfully retrieved oracle and schema version", body = VersionInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Failed to read applied migrations"),
+    ))]
+pub async fn version(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<VersionInfo>, ErrorResponse> {
+    let migrations = state.oracle.migration_status().await.map_err(|e| {
+        log::error!("error reading migration status: {}", e);
+        e
+    })?;
+    Ok(Json(VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        migrations,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/livez",
+    responses(
+        (status = OK, description = "The process is running"),
+    ))]
+pub async fn livez() -> StatusCode {
+    StatusCode::OK
+}
+
+/// Each check behind `/readyz`, the oracle is ready once all of them pass
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Readiness {
+    pub ready: bool,
+    /// The event database answers queries
+    pub database: bool,
+    /// At least one weather file is listed and readable
+    pub weather_files: bool,
+    /// The oracle's signing key is loaded
+    pub oracle_key: bool,
+    /// The first forecast cache warm-up has finished
+    pub forecast_cache: bool,
+    /// The newest weather file is within `max_data_age_hours`, always true when that's unset
+    pub weather_data_fresh: bool,
+    /// When the newest weather file was generated
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub newest_generated_at: Option<OffsetDateTime>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    responses(
+        (status = OK, description = "Ready to serve traffic", body = Readiness),
+        (status = SERVICE_UNAVAILABLE, description = "A readiness check is failing, including weather data older than max_data_age_hours", body = Readiness),
+    ))]
+pub async fn readyz(State(state): State<Arc<AppState>>) -> (StatusCode, Json<Readiness>) {
+    let database = match state.oracle.health_check().await {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("readiness: database check failed: {}", e);
+            false
+        }
+    };
+    let newest_file = newest_weather_file(&state).await;
+    let weather_files = match &newest_file {
+        Some((file_name, generated_at)) => is_readable(&state, file_name, *generated_at).await,
+        None => false,
+    };
+    let newest_generated_at = newest_file.map(|(_, generated_at)| generated_at);
+    let weather_data_fresh = is_fresh(state.max_data_age, newest_generated_at);
+    let oracle_key = state.oracle.npub().is_ok();
+    let forecast_cache = state.forecast_cache_warmed.load(Ordering::Acquire);
+    let ready = database && weather_files && oracle_key && forecast_cache && weather_data_fresh;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(Readiness {
+            ready,
+            database,
+            weather_files,
+            oracle_key,
+            forecast_cache,
+            weather_data_fresh,
+            newest_generated_at,
+        }),
+    )
+}
+
+/// The newest listed weather file and when it was generated, None when there are none or
+/// listing fails
+async fn newest_weather_file(state: &AppState) -> Option<(String, OffsetDateTime)> {
+    let file_names = match state
+        .file_access
+        .grab_file_names(FileParams {
+            start: None,
+            end: None,
+            observations: None,
+            forecasts: None,
+        })
+        .await
+    {
+        Ok(file_names) => file_names,
+        Err(e) => {
+            log::warn!("readiness: listing weather files failed: {}", e);
+            return None;
+        }
+    };
+    file_names
+        .into_iter()
+        .filter_map(|name| {
+            let (_, created_time) = name.split_once('_')?;
+            let generated_at =
+                OffsetDateTime::parse(&drop_suffix(created_time, ".parquet"), &Rfc3339).ok()?;
+            Some((name, generated_at))
+        })
+        .max_by_key(|(_, generated_at)| *generated_at)
+}
+
+async fn is_readable(state: &AppState, file_name: &str, generated_at: OffsetDateTime) -> bool {
+    match state.file_access.file_size(file_name, generated_at).await {
+        Ok(_) => true,
+        Err(e) => {
+            log::warn!("readiness: weather file {} unreadable: {}", file_name, e);
+            false
+        }
+    }
+}
+
+/// Whether weather data generated at `newest_generated_at` is within `max_data_age`, warning
+/// when it isn't
+fn is_fresh(
+    max_data_age: Option<time::Duration>,
+    newest_generated_at: Option<OffsetDateTime>,
+) -> bool {
+    let Some(max_data_age) = max_data_age else {
+        return true;
+    };
+    let Some(generated_at) = newest_generated_at else {
+        log::warn!("weather data is stale: no weather files found, is the daemon running?");
+        return false;
+    };
+    let age = OffsetDateTime::now_utc() - generated_at;
+    if age <= max_data_age {
+        return true;
+    }
+    log::warn!(
+        "weather data is stale: newest file was generated {} hours ago at {}, past the {} hour limit, is the daemon running?",
+        age.whole_hours(),
+        generated_at.format(&Rfc3339).unwrap_or_default(),
+        max_data_age.whole_hours()
+    );
+    false
+}
+
+/// Checks the newest weather file against `max_data_age`, logging a warning when it's stale.
+/// Run periodically so a stalled daemon is reported even without anything probing `/readyz`
+pub async fn check_weather_data_fresh(state: &AppState) -> bool {
+    let newest_generated_at = newest_weather_file(state)
+        .await
+        .map(|(_, generated_at)| generated_at);
+    is_fresh(state.max_data_age, newest_generated_at)
+}
+
 pub async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     match state.oracle.health_check().await {
         Ok(()) => StatusCode::OK.into_response(),
@@ -133,13 +559,25 @@ pub async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoRespon
     }
 }
 pub fn app(app_state: AppState) -> Router {
+    app_for(app_state, RouteScope::Public)
+}
+
+/// Router exposing only the routes in `scope`, listeners built from the same state share its
+/// caches, database and oracle
+pub fn app_for(app_state: AppState, scope: RouteScope) -> Router {
     let api_docs = ApiDoc::openapi();
     let cors = CorsLayer::new()
         .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
         .allow_headers([ACCEPT, CONTENT_TYPE])
         .allow_origin(Any);
 
-    Router::new()
+    let probe_routes = Router::new()
+        .route("/version", get(version))
+        .route("/health", get(health_check))
+        .route("/livez", get(livez))
+        .route("/readyz", get(readyz));
+    let admin_routes = Router::new().route("/admin/db/maintenance", post(db_maintenance));
+    let public_routes = Router::new()
         // UI routes
         .route("/", get(dashboard_handler))
         .route("/events", get(events_handler))
@@ -155,29 +593,65 @@ pub fn app(app_state: AppState) -> Router {
         // API routes
         .route("/files", get(files))
         .route("/file/{file_name}", get(download))
-        .route("/file/{file_name}", post(upload))
+        .route(
+            "/file/{file_name}",
+            post(upload).layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE)),
+        )
         .route("/stations", get(get_stations))
+        .route("/stations/lookup", post(lookup_stations))
+        .route("/stations/available", get(available_stations))
         .route("/stations/forecasts", get(forecasts))
         .route("/stations/observations", get(observations))
         .route("/stations/daily-observations", get(daily_observations))
+        .route("/observations/coverage", get(observation_coverage))
+        .route("/compare", get(compare))
+        .route("/forecasts.parquet", get(forecasts_parquet))
+        .route("/query", post(custom_query))
+        .route("/oracle", get(get_oracle_info))
         .route("/oracle/npub", get(get_npub))
         .route("/oracle/pubkey", get(get_pubkey))
         .route("/oracle/update", post(update_data))
         .route("/oracle/events", get(list_events))
         .route("/oracle/events", post(create_event))
+        .route("/oracle/events/schema", get(create_event_schema))
+        .route("/oracle/events/batch", post(get_events_batch))
+        .route("/oracle/events/stats", get(event_stats))
+        .route("/oracle/events/changes", get(event_changes))
         .route("/oracle/events/{event_id}", get(get_event))
         .route("/oracle/events/{event_id}/entries", post(add_event_entries))
+        .route("/oracle/events/{event_id}/clone", post(clone_event))
+        .route("/oracle/events/{event_id}/weather", get(get_event_weather))
+        .route(
+            "/oracle/events/{event_id}/announcement",
+            get(get_event_announcement),
+        )
+        .route(
+            "/oracle/events/{event_id}/attestation",
+            get(get_event_attestation),
+        )
+        .route("/oracle/events/{event_id}/audit", get(audit_event))
         .route(
             "/oracle/events/{event_id}/entries/{entry_id}",
             get(get_event_entry),
         )
+        .route("/verify", post(verify_attestation))
         // Static files with explicit MIME types
-        .route("/static/{*path}", get(serve_static_file))
+        .route("/static/{*path}", get(serve_static_file));
+
+    let routes = match scope {
+        RouteScope::Public => public_routes.merge(probe_routes),
+        RouteScope::Admin => admin_routes.merge(probe_routes),
+    };
+    let max_body_size = app_state.max_body_size;
+    let router = routes
         .with_state(Arc::new(app_state))
         .layer(middleware::from_fn(log_request))
-        .layer(DefaultBodyLimit::max(30 * 1024 * 1024))
-        .merge(Scalar::with_url("/docs", api_docs))
-        .layer(cors)
+        .layer(DefaultBodyLimit::max(max_body_size));
+    let router = match scope {
+        RouteScope::Admin => router,
+        RouteScope::Public => router.merge(Scalar::with_url("/docs", api_docs)),
+    };
+    router.layer(cors)
 }
 
 async fn log_request(request: Request<Body>, next: Next) -> impl IntoResponse {
@@ -186,12 +660,23 @@ async fn log_request(request: Request<Body>, next: Next) -> impl IntoResponse {
         .uri()
         .path_and_query()
         .map(|p| p.as_str())
-        .unwrap_or_default();
-    info!(target: "http_request","new request, {} {}", request.method().as_str(), path);
+        .unwrap_or_default()
+        .to_string();
+    let method = request.method().clone();
+    info!(
+        target: "http_request",
+        method = method.as_str(), path = path.as_str();
+        "new request, {} {}", method.as_str(), path
+    );
 
     let response = next.run(request).await;
     let response_time = time::OffsetDateTime::now_utc() - now;
-    info!(target: "http_response", "response, code: {}, time: {}", response.status().as_str(), response_time);
+    info!(
+        target: "http_response",
+        method = method.as_str(), path = path.as_str(), status = response.status().as_u16(),
+        elapsed_ms = response_time.whole_milliseconds() as u64;
+        "response, code: {}, time: {}", response.status().as_str(), response_time
+    );
 
     response
 }
@@ -1,5 +1,7 @@
 use maud::{html, Markup};
 
+use crate::db::EventAggregates;
+
 /// Event statistics data
 #[derive(Default)]
 pub struct EventStats {
@@ -9,6 +11,17 @@ pub struct EventStats {
     pub signed_count: usize,
 }
 
+impl From<EventAggregates> for EventStats {
+    fn from(aggregates: EventAggregates) -> Self {
+        Self {
+            live_count: aggregates.live as usize,
+            running_count: aggregates.running as usize,
+            completed_count: aggregates.completed as usize,
+            signed_count: aggregates.signed as usize,
+        }
+    }
+}
+
 /// Event statistics display fragment
 /// Shows counts of events by status in a responsive grid
 pub fn event_stats(stats: &EventStats) -> Markup {
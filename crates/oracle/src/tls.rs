@@ -0,0 +1,172 @@
+use anyhow::{anyhow, Context};
+use axum::{extract::ConnectInfo, Router};
+use hyper::{body::Incoming, Request};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+    server::{conn::auto, graceful::GracefulShutdown},
+    service::TowerToHyperService,
+};
+use log::{debug, error};
+use rustls::{
+    crypto::ring,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    ServerConfig,
+};
+use std::{future::Future, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+
+/// How long a client gets to finish the TLS handshake before its connection is dropped, so
+/// clients that connect and go quiet don't pile up
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept, errors like running out of file descriptors would otherwise
+/// spin the accept loop
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Load a PEM certificate chain and private key into a rustls server config, offering `h2`
+/// ahead of `http/1.1` over ALPN when `http2` is set
+pub fn load_tls_config(
+    cert_path: &str,
+    key_path: &str,
+    http2: bool,
+) -> Result<Arc<ServerConfig>, anyhow::Error> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| anyhow!("failed to read TLS certificate {}: {}", cert_path, e))?;
+    if certs.is_empty() {
+        return Err(anyhow!("no certificates found in {}", cert_path));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| anyhow!("failed to read TLS private key {}: {}", key_path, e))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .with_context(|| format!("{} does not match {}", key_path, cert_path))?;
+    config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+    Ok(Arc::new(config))
+}
+
+/// Serve `app` over TLS until `signal` resolves, then wait for open connections to finish.
+/// Each request carries the peer's `ConnectInfo<SocketAddr>` like the plain TCP server.
+/// HTTP/2 is served when the config advertises `h2` over ALPN.
+pub async fn serve_tls<F>(
+    listener: TcpListener,
+    config: Arc<ServerConfig>,
+    app: Router,
+    signal: F,
+) -> Result<(), anyhow::Error>
+where
+    F: Future<Output = ()> + Send,
+{
+    let http2 = config
+        .alpn_protocols
+        .iter()
+        .any(|protocol| protocol == b"h2");
+    serve_connections(
+        listener,
+        Some(TlsAcceptor::from(config)),
+        app,
+        http2,
+        signal,
+    )
+    .await
+}
+
+/// Serve `app` over plain TCP, accepting cleartext HTTP/2 (h2c with prior knowledge) alongside
+/// HTTP/1, until `signal` resolves. Requests carry `ConnectInfo<SocketAddr>` like `axum::serve`.
+pub async fn serve_h2c<F>(
+    listener: TcpListener,
+    app: Router,
+    signal: F,
+) -> Result<(), anyhow::Error>
+where
+    F: Future<Output = ()> + Send,
+{
+    serve_connections(listener, None, app, true, signal).await
+}
+
+async fn serve_connections<F>(
+    listener: TcpListener,
+    acceptor: Option<TlsAcceptor>,
+    app: Router,
+    http2: bool,
+    signal: F,
+) -> Result<(), anyhow::Error>
+where
+    F: Future<Output = ()> + Send,
+{
+    let mut builder = auto::Builder::new(TokioExecutor::new());
+    builder.http1().timer(TokioTimer::new());
+    builder.http2().timer(TokioTimer::new());
+    let builder = if http2 { builder } else { builder.http1_only() };
+    let graceful = GracefulShutdown::new();
+    let mut signal = std::pin::pin!(signal);
+
+    loop {
+        let (stream, remote_addr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("error accepting connection: {}", e);
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    continue;
+                }
+            },
+            _ = signal.as_mut() => break,
+        };
+
+        let acceptor = acceptor.clone();
+        let builder = builder.clone();
+        let service = app
+            .clone()
+            .map_request(move |mut request: Request<Incoming>| {
+                request
+                    .extensions_mut()
+                    .insert(ConnectInfo::<SocketAddr>(remote_addr));
+                request
+            });
+        let watcher = graceful.watcher();
+        tokio::spawn(async move {
+            let service = TowerToHyperService::new(service);
+            let result = match acceptor {
+                Some(acceptor) => {
+                    let handshake =
+                        tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream));
+                    match handshake.await {
+                        Ok(Ok(stream)) => {
+                            let connection =
+                                builder.serve_connection(TokioIo::new(stream), service);
+                            watcher.watch(connection).await
+                        }
+                        Ok(Err(e)) => {
+                            debug!("TLS handshake with {} failed: {}", remote_addr, e);
+                            return;
+                        }
+                        Err(_) => {
+                            debug!("TLS handshake with {} timed out", remote_addr);
+                            return;
+                        }
+                    }
+                }
+                None => {
+                    let connection = builder.serve_connection(TokioIo::new(stream), service);
+                    watcher.watch(connection).await
+                }
+            };
+            if let Err(e) = result {
+                debug!("connection from {} closed: {}", remote_addr, e);
+            }
+        });
+    }
+
+    graceful.shutdown().await;
+    Ok(())
+}
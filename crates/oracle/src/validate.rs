@@ -0,0 +1,38 @@
+use noaa_oracle_core::{check_writable_dir, ConfigProblems};
+
+use crate::{oracle::check_key_file, Cli, S3FileAccess};
+
+/// Checks the settings that otherwise fail deep into startup or on the first request: the
+/// weather and sqlite event dirs have to be writable, the private key has to parse (or be
+/// possible to generate) and, with S3 enabled, the credentials have to reach the bucket.
+/// Every problem found is returned, not just the first
+pub async fn validate_config(cli: &Cli) -> Result<(), ConfigProblems> {
+    let mut problems = ConfigProblems::default();
+
+    let weather_dir = cli.weather_dir();
+    problems.check(
+        "weather_dir",
+        check_writable_dir(&weather_dir)
+            .map_err(|e| format!("{} is not writable: {}", weather_dir, e)),
+    );
+    problems.check("layout", cli.layout().map(|_| ()));
+    // Postgres stores have no local directory, connecting is left to startup
+    if let Some(path) = cli.database_url().strip_prefix("sqlite:") {
+        let event_dir = path.strip_prefix("//").unwrap_or(path);
+        problems.check(
+            "event_db",
+            check_writable_dir(event_dir)
+                .map_err(|e| format!("{} is not writable: {}", event_dir, e)),
+        );
+    }
+    problems.check("oracle_private_key", check_key_file(&cli.private_key()));
+    if let Some(ref bucket) = cli.s3_bucket {
+        let access = match S3FileAccess::new(bucket.clone(), cli.s3_endpoint.clone()).await {
+            Ok(file_access) => file_access.check_access().await,
+            Err(e) => Err(e),
+        };
+        problems.check("s3_bucket", access);
+    }
+
+    problems.into_result()
+}
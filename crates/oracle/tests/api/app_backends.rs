@@ -0,0 +1,125 @@
+use crate::helpers::{random_test_number, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use noaa_oracle_core::DataLayout;
+use oracle::{
+    app, build_app_state, build_app_state_with, create_folder,
+    oracle::{NonceDerivation, DEFAULT_MAX_EVENT_LOCATIONS},
+    AppBackends, CustomQueryLimits, Database, DatabaseSettings, FileAccess, Forecast,
+    HumidityFormula, OutcomeMessageVersion, PrecipCodes, PrecipUnit, QuerySettings, SanityBounds,
+    TemperatureUnit, DEFAULT_MAX_BATCH_EVENTS, DEFAULT_MAX_BODY_SIZE_KIB,
+};
+use serde_json::{from_slice, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn mock_forecast() -> Forecast {
+    Forecast {
+        station_id: String::from("PFNO"),
+        date: String::from("2024-08-12"),
+        start_time: String::from("2024-08-12T00:00:00Z"),
+        end_time: String::from("2024-08-13T00:00:00Z"),
+        temp_low: 50,
+        temp_high: 72,
+        wind_speed: Some(10),
+        wind_direction: None,
+        humidity_max: None,
+        humidity_min: None,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        precip_chance: None,
+        rain_amt: None,
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        dominant_precip: None,
+        bucket: None,
+    }
+}
+
+#[tokio::test]
+async fn serves_routes_from_injected_weather_backend() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .times(1)
+        .returning(|_, _| Ok(vec![mock_forecast()]));
+
+    let test_folder = format!("./test_data/{}", random_test_number());
+    let event_data = format!("{}/event_data", test_folder);
+    create_folder(&event_data);
+    let event_store = Arc::new(Database::new(&event_data).await.unwrap());
+    let state = build_app_state_with(
+        String::from("http://127.0.0.1:9100"),
+        String::from("./static"),
+        String::from("./oracle_private_key.pem"),
+        NonceDerivation::default(),
+        OutcomeMessageVersion::CURRENT,
+        time::Duration::ZERO,
+        DEFAULT_MAX_EVENT_LOCATIONS,
+        DEFAULT_MAX_BATCH_EVENTS,
+        DEFAULT_MAX_BODY_SIZE_KIB * 1024,
+        CustomQueryLimits::default(),
+        None,
+        AppBackends {
+            file_access: Arc::new(FileAccess::new(test_folder)),
+            weather_db: Arc::new(weather_data),
+            event_store,
+        },
+    )
+    .await
+    .unwrap();
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/stations/forecasts?station_ids=PFNO")
+        .body(Body::empty())
+        .unwrap();
+    let response = app(state).oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let forecasts: Vec<Value> = from_slice(&body).unwrap();
+    assert_eq!(forecasts.len(), 1);
+    assert_eq!(forecasts[0]["station_id"], "PFNO");
+    assert_eq!(forecasts[0]["temp_high"], 72);
+}
+
+#[tokio::test]
+async fn s3_init_failure_is_returned_not_panicked() {
+    let test_folder = format!("./test_data/{}", random_test_number());
+    let err = build_app_state(
+        String::from("http://127.0.0.1:9100"),
+        String::from("./static"),
+        format!("{}/weather_data", test_folder),
+        format!("sqlite:{}/event_data", test_folder),
+        String::from("./oracle_private_key.pem"),
+        NonceDerivation::default(),
+        OutcomeMessageVersion::CURRENT,
+        time::Duration::ZERO,
+        DEFAULT_MAX_EVENT_LOCATIONS,
+        Some(String::from(" ")),
+        None,
+        DataLayout::default(),
+        None,
+        QuerySettings::default(),
+        None,
+        HumidityFormula::default(),
+        SanityBounds::default(),
+        PrecipCodes::default(),
+        DEFAULT_MAX_BATCH_EVENTS,
+        DEFAULT_MAX_BODY_SIZE_KIB * 1024,
+        CustomQueryLimits::default(),
+        None,
+        DatabaseSettings::default(),
+    )
+    .await
+    .err()
+    .unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "error setting up S3 file access: S3 config error: bucket name is empty"
+    );
+}
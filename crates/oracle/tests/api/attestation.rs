@@ -1,4 +1,4 @@
-use crate::helpers::{spawn_app, MockWeatherAccess};
+use crate::helpers::{spawn_app, spawn_app_with_clock, MockWeatherAccess};
 use axum::{
     body::{to_bytes, Body},
     http::Request,
@@ -8,11 +8,11 @@ use hyper::{header, Method};
 use nostr_sdk::Keys;
 use oracle::{
     oracle::get_winning_bytes, AddEventEntries, AddEventEntry, CreateEvent, Event, EventStatus,
-    Forecast, Observation, TemperatureUnit, ValueOptions, WeatherChoices,
+    FixedClock, Forecast, Observation, PrecipUnit, TemperatureUnit, ValueOptions, WeatherChoices,
 };
 use serde_json::from_slice;
 use std::{cmp, sync::Arc};
-use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use time::{format_description::well_known::Rfc3339, macros::datetime, OffsetDateTime};
 use tokio::time::sleep;
 use tower::ServiceExt;
 use uuid::{ClockSequence, Timestamp, Uuid};
@@ -50,7 +50,9 @@ async fn attestation_unlocks_correct_dlc_outcome() {
         .times(2)
         .returning(|_, _| Ok(mock_observation_data()));
 
-    let test_app = spawn_app(Arc::new(weather_data)).await;
+    // entries go in the day before the event starts
+    let clock = Arc::new(FixedClock::new(datetime!(2024-08-11 00:00:00 UTC)));
+    let test_app = spawn_app_with_clock(Arc::new(weather_data), clock.clone()).await;
 
     let start_observation_date =
         OffsetDateTime::parse("2024-08-12T00:00:00+00:00", &Rfc3339).unwrap();
@@ -65,14 +67,18 @@ async fn attestation_unlocks_correct_dlc_outcome() {
         signing_date,
         locations: vec![String::from("PFNO"), String::from("KSAW")],
         total_allowed_entries: 3,
-        number_of_values_per_entry: 4,
+        number_of_values_per_entry: Some(4),
         number_of_places_win: 2,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     let event = test_app
         .oracle
-        .create_event(keys.public_key, new_event)
+        .create_event(keys.public_key, new_event, true)
         .await
         .unwrap();
 
@@ -90,6 +96,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -100,6 +107,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -116,6 +124,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -126,6 +135,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -142,6 +152,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -152,6 +163,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -165,6 +177,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
         .add_event_entries(keys.public_key, event.id, event_entries.entries)
         .await
         .unwrap();
+    clock.set(signing_date + time::Duration::hours(1));
 
     // Run ETL to sign the event
     let request = Request::builder()
@@ -186,7 +199,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
     // Get the signed event
     let request = Request::builder()
         .method(Method::GET)
-        .uri(format!("/oracle/events/{}", event.id))
+        .uri(format!("/oracle/events/{}?include=entries", event.id))
         .header(header::CONTENT_TYPE, "application/json")
         .body(Body::empty())
         .unwrap();
@@ -222,7 +235,7 @@ async fn attestation_unlocks_correct_dlc_outcome() {
         })
         .collect();
 
-    let winning_bytes = get_winning_bytes(winners);
+    let winning_bytes = get_winning_bytes(winners, signed_event.outcome_message_version);
 
     // Verify the attestation was computed correctly
     let expected_attestation = attestation_secret(
@@ -260,13 +273,13 @@ async fn event_not_signed_before_signing_date() {
         .expect_observation_data()
         .returning(|_, _| Ok(mock_observation_data()));
 
-    let test_app = spawn_app(Arc::new(weather_data)).await;
-
-    // Set signing date in the future
-    let now = OffsetDateTime::now_utc();
-    let start_observation_date = now - time::Duration::days(2);
-    let end_observation_date = now - time::Duration::days(1);
-    let signing_date = now + time::Duration::days(1); // Future!
+    let start_observation_date = datetime!(2024-08-12 00:00:00 UTC);
+    let end_observation_date = datetime!(2024-08-13 00:00:00 UTC);
+    let signing_date = datetime!(2024-08-15 00:00:00 UTC);
+    let clock = Arc::new(FixedClock::new(
+        start_observation_date - time::Duration::days(1),
+    ));
+    let test_app = spawn_app_with_clock(Arc::new(weather_data), clock.clone()).await;
 
     let new_event = CreateEvent {
         id: Uuid::now_v7(),
@@ -275,14 +288,18 @@ async fn event_not_signed_before_signing_date() {
         signing_date,
         locations: vec![String::from("PFNO")],
         total_allowed_entries: 2,
-        number_of_values_per_entry: 2,
+        number_of_values_per_entry: Some(2),
         number_of_places_win: 1,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     let event = test_app
         .oracle
-        .create_event(keys.public_key, new_event)
+        .create_event(keys.public_key, new_event, true)
         .await
         .unwrap();
 
@@ -298,6 +315,7 @@ async fn event_not_signed_before_signing_date() {
             rain_amt: None,
             snow_amt: None,
             humidity: None,
+            predictions: Default::default(),
         }],
     };
 
@@ -313,6 +331,7 @@ async fn event_not_signed_before_signing_date() {
             rain_amt: None,
             snow_amt: None,
             humidity: None,
+            predictions: Default::default(),
         }],
     };
 
@@ -321,6 +340,8 @@ async fn event_not_signed_before_signing_date() {
         .add_event_entries(keys.public_key, event.id, vec![entry_1, entry_2])
         .await
         .unwrap();
+    // observations are done but the signing date is still a day away
+    clock.set(end_observation_date + time::Duration::hours(1));
 
     // Run ETL
     let request = Request::builder()
@@ -374,9 +395,13 @@ async fn each_event_has_unique_nonce() {
         signing_date: now,
         locations: vec![String::from("PFNO")],
         total_allowed_entries: 2,
-        number_of_values_per_entry: 2,
+        number_of_values_per_entry: Some(2),
         number_of_places_win: 1,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     let event2 = CreateEvent {
@@ -386,19 +411,23 @@ async fn each_event_has_unique_nonce() {
         signing_date: now,
         locations: vec![String::from("PFNO")],
         total_allowed_entries: 2,
-        number_of_values_per_entry: 2,
+        number_of_values_per_entry: Some(2),
         number_of_places_win: 1,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     let created1 = test_app
         .oracle
-        .create_event(keys.public_key, event1)
+        .create_event(keys.public_key, event1, true)
         .await
         .unwrap();
     let created2 = test_app
         .oracle
-        .create_event(keys.public_key, event2)
+        .create_event(keys.public_key, event2, true)
         .await
         .unwrap();
 
@@ -425,14 +454,18 @@ async fn event_announcement_has_correct_outcome_count() {
         signing_date: now,
         locations: vec![String::from("PFNO")],
         total_allowed_entries: 5,
-        number_of_values_per_entry: 2,
+        number_of_values_per_entry: Some(2),
         number_of_places_win: 3,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     let created = test_app
         .oracle
-        .create_event(keys.public_key, event)
+        .create_event(keys.public_key, event, true)
         .await
         .unwrap();
 
@@ -469,7 +502,9 @@ async fn attestation_is_deterministic() {
         .expect_observation_data()
         .returning(|_, _| Ok(mock_observation_data()));
 
-    let test_app = spawn_app(Arc::new(weather_data)).await;
+    // entries go in the day before the event starts
+    let clock = Arc::new(FixedClock::new(datetime!(2024-08-11 00:00:00 UTC)));
+    let test_app = spawn_app_with_clock(Arc::new(weather_data), clock.clone()).await;
 
     let start_observation_date =
         OffsetDateTime::parse("2024-08-12T00:00:00+00:00", &Rfc3339).unwrap();
@@ -484,14 +519,18 @@ async fn attestation_is_deterministic() {
         signing_date,
         locations: vec![String::from("PFNO")],
         total_allowed_entries: 2,
-        number_of_values_per_entry: 2,
+        number_of_values_per_entry: Some(2),
         number_of_places_win: 1,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     let created = test_app
         .oracle
-        .create_event(keys.public_key, event)
+        .create_event(keys.public_key, event, true)
         .await
         .unwrap();
 
@@ -507,6 +546,7 @@ async fn attestation_is_deterministic() {
             rain_amt: None,
             snow_amt: None,
             humidity: None,
+            predictions: Default::default(),
         }],
     };
 
@@ -522,6 +562,7 @@ async fn attestation_is_deterministic() {
             rain_amt: None,
             snow_amt: None,
             humidity: None,
+            predictions: Default::default(),
         }],
     };
 
@@ -534,6 +575,7 @@ async fn attestation_is_deterministic() {
         )
         .await
         .unwrap();
+    clock.set(signing_date + time::Duration::hours(1));
 
     // Run ETL first to get scores
     let request = Request::builder()
@@ -547,7 +589,7 @@ async fn attestation_is_deterministic() {
     // Get the signed event
     let request = Request::builder()
         .method(Method::GET)
-        .uri(format!("/oracle/events/{}", created.id))
+        .uri(format!("/oracle/events/{}?include=entries", created.id))
         .body(Body::empty())
         .unwrap();
     let response = test_app.app.clone().oneshot(request).await.unwrap();
@@ -577,7 +619,7 @@ async fn attestation_is_deterministic() {
         })
         .collect();
 
-    let winning_bytes = get_winning_bytes(winners);
+    let winning_bytes = get_winning_bytes(winners, after_etl.outcome_message_version);
     let expected_attestation = attestation_secret(
         test_app.oracle.raw_private_key(),
         after_etl.nonce,
@@ -609,6 +651,9 @@ fn mock_forecast_data() -> Vec<Forecast> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            dominant_precip: None,
+            bucket: None,
         },
         Forecast {
             station_id: String::from("KSAW"),
@@ -626,6 +671,9 @@ fn mock_forecast_data() -> Vec<Forecast> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            dominant_precip: None,
+            bucket: None,
         },
     ]
 }
@@ -645,6 +693,8 @@ fn mock_observation_data() -> Vec<Observation> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            bucket: None,
         },
         Observation {
             station_id: String::from("KSAW"),
@@ -659,6 +709,8 @@ fn mock_observation_data() -> Vec<Observation> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            bucket: None,
         },
     ]
 }
@@ -0,0 +1,115 @@
+use crate::helpers::{spawn_app, TestApp, WeatherFixture};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use serde_json::{from_slice, json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Writes a parquet file holding one row per station
+fn write_file(fixture: &WeatherFixture, path: &str, station_ids: &[&str]) {
+    let rows: Vec<String> = station_ids
+        .iter()
+        .map(|station_id| format!("('{}', 12.5)", station_id))
+        .collect();
+    fixture.write(&format!(
+        "COPY (SELECT * FROM (VALUES {}) AS t(station_id, temperature_value)) TO '{}' (FORMAT PARQUET);",
+        rows.join(", "),
+        path
+    ));
+}
+
+/// Of the candidate stations PFNO, KSAW and KWMC, only PFNO and KSAW reported observations on
+/// 2024-08-12 while all three were forecast.
+async fn spawn_with_fixture() -> TestApp {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    let next_day_dir = fixture.day_dir("2024-08-13");
+    write_file(
+        &fixture,
+        &format!("{}/observations_2024-08-12T01:00:00Z.parquet", day_dir),
+        &["PFNO"],
+    );
+    write_file(
+        &fixture,
+        &format!("{}/observations_2024-08-12T23:00:00Z.parquet", day_dir),
+        &["PFNO", "KSAW"],
+    );
+    write_file(
+        &fixture,
+        &format!("{}/forecasts_2024-08-12T06:00:00Z.parquet", day_dir),
+        &["PFNO", "KSAW", "KWMC"],
+    );
+    write_file(
+        &fixture,
+        &format!("{}/observations_2024-08-13T00:00:00Z.parquet", next_day_dir),
+        &["KWMC"],
+    );
+
+    spawn_app(Arc::new(fixture.weather_access())).await
+}
+
+async fn get(test_app: &TestApp, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn lists_only_stations_that_reported_on_the_date() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, body) = get(&test_app, "/stations/available?date=2024-08-12").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!(["KSAW", "PFNO"]));
+
+    let (status, body) = get(
+        &test_app,
+        "/stations/available?date=2024-08-12&type=observations",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!(["KSAW", "PFNO"]));
+
+    let (status, body) = get(
+        &test_app,
+        "/stations/available?date=2024-08-12&type=forecasts",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!(["KSAW", "KWMC", "PFNO"]));
+
+    // Files from the next day, even one generated right at midnight, belong to that day
+    let (status, body) = get(&test_app, "/stations/available?date=2024-08-13").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!(["KWMC"]));
+}
+
+#[tokio::test]
+async fn date_without_files_has_no_stations() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, body) = get(&test_app, "/stations/available?date=2024-08-14").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!([]));
+}
+
+#[tokio::test]
+async fn rejects_malformed_date_or_type() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, _) = get(&test_app, "/stations/available?date=2024-08-12T00:00:00Z").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get(&test_app, "/stations/available?date=2024-08-12&type=radar").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get(&test_app, "/stations/available").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
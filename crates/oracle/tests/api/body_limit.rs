@@ -0,0 +1,86 @@
+use crate::helpers::{create_auth_event, spawn_app, MockWeatherAccess, TestApp};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hyper::{header, Method};
+use nostr_sdk::{
+    hashes::{sha256::Hash as Sha256Hash, Hash},
+    Keys,
+};
+use oracle::DEFAULT_MAX_BODY_SIZE_KIB;
+use serde_json::json;
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Posts `body` to `path` signed by a fresh key, returning the status
+async fn post(test_app: &TestApp, path: &str, body: String) -> StatusCode {
+    let keys = Keys::generate();
+    let auth_event = create_auth_event(
+        "POST",
+        &format!("http://localhost:3000{}", path),
+        Some(Sha256Hash::hash(body.as_bytes())),
+        &keys,
+    )
+    .await;
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::CONTENT_LENGTH, body.len())
+        .header(
+            header::AUTHORIZATION,
+            format!(
+                "Nostr {}",
+                BASE64.encode(serde_json::to_string(&auth_event).unwrap())
+            ),
+        )
+        .header("host", "localhost:3000")
+        .body(Body::from(body))
+        .unwrap();
+    test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .unwrap()
+        .status()
+}
+
+/// A JSON body padded one KiB past the default limit
+fn oversized_body() -> String {
+    json!({
+        "id": Uuid::now_v7(),
+        "locations": ["A".repeat((DEFAULT_MAX_BODY_SIZE_KIB + 1) * 1024)],
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn oversized_event_is_rejected_with_413() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    let status = post(&test_app, "/oracle/events", oversized_body()).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn oversized_entries_are_rejected_with_413() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    let path = format!("/oracle/events/{}/entries", Uuid::now_v7());
+    let status = post(&test_app, &path, oversized_body()).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn body_under_the_limit_reaches_the_handler() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    // Read in full, then rejected for what it holds rather than its size
+    let body = json!({ "id": Uuid::now_v7(), "locations": ["A".repeat(1024)] }).to_string();
+    let status = post(&test_app, "/oracle/events", body).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,236 @@
+use crate::helpers::{random_test_number, MockWeatherAccess};
+use nostr_sdk::Keys;
+use oracle::{
+    create_folder,
+    oracle::{Error, Oracle},
+    AddEventEntry, CloneEvent, CreateEvent, Database, EventStatus, FixedClock, ScoringField,
+    ScoringMode, ValueOptions, WeatherChoices,
+};
+use std::{collections::HashMap, sync::Arc};
+use time::{macros::datetime, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+const START: OffsetDateTime = datetime!(2024-08-12 00:00:00 UTC);
+const END: OffsetDateTime = datetime!(2024-08-13 00:00:00 UTC);
+
+async fn oracle_at(now: OffsetDateTime) -> Oracle {
+    create_folder("./test_data");
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+    let db = Arc::new(Database::new(&event_data).await.unwrap());
+    Oracle::new(
+        db,
+        Arc::new(MockWeatherAccess::new()),
+        &String::from("./oracle_private_key.pem"),
+    )
+    .await
+    .unwrap()
+    .with_clock(Arc::new(FixedClock::new(now)))
+}
+
+fn source_event() -> CreateEvent {
+    CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: START,
+        end_observation_date: END,
+        signing_date: END + Duration::hours(6),
+        locations: vec![String::from("PFNO"), String::from("KSAW")],
+        total_allowed_entries: 1,
+        number_of_values_per_entry: Some(3),
+        number_of_places_win: 1,
+        scoring_fields: vec![ScoringField::TempHigh, ScoringField::RainAmt],
+        par_tolerances: HashMap::from([(ScoringField::RainAmt, 0.1)]),
+        scoring_mode: ScoringMode::Ternary,
+        settlement_delay_hours: Some(2),
+        entry_cutoff: Some(START - Duration::hours(1)),
+    }
+}
+
+fn new_entry(event_id: Uuid) -> AddEventEntry {
+    AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_low: None,
+            temp_high: Some(ValueOptions::Par),
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn cloned_event_copies_settings_onto_new_dates() {
+    let oracle = oracle_at(START - Duration::days(1)).await;
+    let keys = Keys::generate();
+    let source = oracle
+        .create_event(keys.public_key, source_event(), true)
+        .await
+        .unwrap();
+    oracle
+        .add_event_entries(keys.public_key, source.id, vec![new_entry(source.id)])
+        .await
+        .unwrap();
+
+    let clone_id = Uuid::now_v7();
+    let start = START + Duration::days(1);
+    let cloned = oracle
+        .clone_event(
+            keys.public_key,
+            &source.id,
+            CloneEvent {
+                id: Some(clone_id),
+                start_observation_date: start,
+                end_observation_date: Some(start + Duration::hours(12)),
+                signing_date: None,
+            },
+            true,
+        )
+        .await
+        .unwrap();
+
+    // overridden
+    assert_eq!(cloned.id, clone_id);
+    assert_eq!(cloned.start_observation_date, start);
+    assert_eq!(cloned.end_observation_date, start + Duration::hours(12));
+    // the source's gaps around the observation window are kept
+    assert_eq!(cloned.signing_date, start + Duration::hours(18));
+    assert_eq!(cloned.entry_cutoff, Some(start - Duration::hours(1)));
+    // copied
+    assert_eq!(cloned.locations, source.locations);
+    assert_eq!(cloned.scoring_fields, source.scoring_fields);
+    assert_eq!(cloned.par_tolerances, source.par_tolerances);
+    assert_eq!(cloned.scoring_mode, source.scoring_mode);
+    assert_eq!(cloned.settlement_delay_hours, Some(2));
+    assert_eq!(cloned.total_allowed_entries, source.total_allowed_entries);
+    assert_eq!(
+        cloned.number_of_values_per_entry,
+        source.number_of_values_per_entry
+    );
+    assert_eq!(cloned.number_of_places_win, source.number_of_places_win);
+    assert_eq!(cloned.status, EventStatus::Live);
+    // never copied
+    assert_ne!(cloned.nonce, source.nonce);
+    assert!(cloned.attestation.is_none());
+    let cloned = oracle.get_event(&clone_id).await.unwrap();
+    assert!(cloned.entries.is_empty());
+    assert_eq!(oracle.get_event(&source.id).await.unwrap().entries.len(), 1);
+}
+
+#[tokio::test]
+async fn clone_keeps_the_source_window_when_only_the_start_moves() {
+    let oracle = oracle_at(START - Duration::days(1)).await;
+    let keys = Keys::generate();
+    let source = oracle
+        .create_event(keys.public_key, source_event(), true)
+        .await
+        .unwrap();
+
+    let start = START + Duration::days(7);
+    let cloned = oracle
+        .clone_event(
+            keys.public_key,
+            &source.id,
+            CloneEvent {
+                id: None,
+                start_observation_date: start,
+                end_observation_date: None,
+                signing_date: None,
+            },
+            true,
+        )
+        .await
+        .unwrap();
+
+    assert_ne!(cloned.id, source.id);
+    assert_eq!(cloned.id.get_version_num(), 7);
+    assert_eq!(cloned.end_observation_date, start + Duration::days(1));
+    assert_eq!(cloned.signing_date, start + Duration::hours(30));
+}
+
+#[tokio::test]
+async fn clone_rejects_invalid_dates() {
+    let oracle = oracle_at(START + Duration::days(2)).await;
+    let keys = Keys::generate();
+    let mut event = source_event();
+    event.start_observation_date = START + Duration::days(3);
+    event.end_observation_date = START + Duration::days(4);
+    event.signing_date = START + Duration::days(4) + Duration::hours(6);
+    event.entry_cutoff = None;
+    let source = oracle
+        .create_event(keys.public_key, event, true)
+        .await
+        .unwrap();
+    let clone = |start: OffsetDateTime, end: Option<OffsetDateTime>| CloneEvent {
+        id: None,
+        start_observation_date: start,
+        end_observation_date: end,
+        signing_date: None,
+    };
+
+    // starts before the oracle's now
+    let result = oracle
+        .clone_event(keys.public_key, &source.id, clone(START, None), true)
+        .await;
+    assert!(
+        matches!(&result, Err(Error::BadEvent(e)) if e.to_string().contains("already passed")),
+        "{:?}",
+        result
+    );
+    // ends before it starts
+    let start = START + Duration::days(5);
+    let result = oracle
+        .clone_event(
+            keys.public_key,
+            &source.id,
+            clone(start, Some(start - Duration::hours(1))),
+            true,
+        )
+        .await;
+    assert!(matches!(result, Err(Error::BadEvent(_))), "{:?}", result);
+    // unknown source
+    let result = oracle
+        .clone_event(keys.public_key, &Uuid::now_v7(), clone(start, None), true)
+        .await;
+    assert!(matches!(result, Err(Error::NotFound(_))), "{:?}", result);
+}
+
+#[tokio::test]
+async fn only_the_source_coordinator_can_clone() {
+    let oracle = oracle_at(START - Duration::days(1)).await;
+    let keys = Keys::generate();
+    let source = oracle
+        .create_event(keys.public_key, source_event(), true)
+        .await
+        .unwrap();
+    let clone_id = Uuid::now_v7();
+
+    let result = oracle
+        .clone_event(
+            Keys::generate().public_key,
+            &source.id,
+            CloneEvent {
+                id: Some(clone_id),
+                start_observation_date: START + Duration::days(1),
+                end_observation_date: None,
+                signing_date: None,
+            },
+            true,
+        )
+        .await;
+
+    assert!(
+        matches!(result, Err(Error::NotCoordinator(_))),
+        "{:?}",
+        result
+    );
+    assert!(matches!(
+        oracle.get_event(&clone_id).await,
+        Err(Error::NotFound(_))
+    ));
+}
@@ -0,0 +1,128 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use oracle::{DailyComparison, DailyObservation, Forecast, PrecipUnit, TemperatureUnit};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn mock_forecast(date: &str, temp_low: i64, temp_high: i64) -> Forecast {
+    Forecast {
+        station_id: String::from("KSAW"),
+        date: date.to_string(),
+        start_time: format!("{}T00:00:00+00:00", date),
+        end_time: format!("{}T23:59:59+00:00", date),
+        temp_low,
+        temp_high,
+        wind_speed: Some(10),
+        wind_direction: None,
+        humidity_max: None,
+        humidity_min: None,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        precip_chance: None,
+        rain_amt: Some(0.5),
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        dominant_precip: None,
+        bucket: None,
+    }
+}
+
+fn mock_observation(date: &str, temp_low: f64, temp_high: f64) -> DailyObservation {
+    DailyObservation {
+        station_id: String::from("KSAW"),
+        date: date.to_string(),
+        temp_low,
+        temp_high,
+        wind_speed: 7,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        wind_direction: None,
+        humidity: None,
+        rain_amt: Some(0.25),
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn can_compare_forecasts_with_observations() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .times(1)
+        .returning(|req, _| {
+            assert_eq!(req.temperature_unit, TemperatureUnit::Fahrenheit);
+            Ok(vec![
+                mock_forecast("2024-08-10", 60, 80),
+                // forecast-only, no observation for this date yet
+                mock_forecast("2024-08-12", 62, 84),
+            ])
+        });
+    weather_data
+        .expect_daily_observations()
+        .times(1)
+        .returning(|req, _| {
+            assert_eq!(req.temperature_unit, TemperatureUnit::Fahrenheit);
+            Ok(vec![
+                mock_observation("2024-08-10", 58.5, 83.0),
+                // observation-only, no forecast covered this date
+                mock_observation("2024-08-11", 61.0, 79.0),
+            ])
+        });
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/compare?start=2024-08-10T00:00:00Z&end=2024-08-13T00:00:00Z&station=KSAW&unit=fahrenheit")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let comparisons: Vec<DailyComparison> = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(comparisons.len(), 1);
+    let matched = &comparisons[0];
+    assert_eq!(matched.station_id, "KSAW");
+    assert_eq!(matched.date, "2024-08-10");
+    assert_eq!(matched.forecast_temp_low, 60);
+    assert_eq!(matched.observed_temp_low, 58.5);
+    assert_eq!(matched.temp_low_error, -1.5);
+    assert_eq!(matched.forecast_temp_high, 80);
+    assert_eq!(matched.observed_temp_high, 83.0);
+    assert_eq!(matched.temp_high_error, 3.0);
+    assert_eq!(matched.wind_speed_error, Some(-3));
+    assert_eq!(matched.rain_amt_error, Some(-0.25));
+    assert_eq!(matched.snow_amt_error, None);
+}
+
+#[tokio::test]
+async fn compare_skips_dates_missing_either_side() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .times(1)
+        .returning(|_, _| Ok(vec![mock_forecast("2024-08-12", 62, 84)]));
+    weather_data
+        .expect_daily_observations()
+        .times(1)
+        .returning(|_, _| Ok(vec![mock_observation("2024-08-11", 61.0, 79.0)]));
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/compare?station=KSAW")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let comparisons: Vec<DailyComparison> = serde_json::from_slice(&body).unwrap();
+    assert!(comparisons.is_empty());
+}
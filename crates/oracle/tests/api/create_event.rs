@@ -1,7 +1,8 @@
 use crate::helpers::{create_auth_event, spawn_app, MockWeatherAccess};
 use axum::{
     body::{to_bytes, Body},
-    http::Request,
+    http::{Request, StatusCode},
+    response::Response,
 };
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use dlctix::Outcome;
@@ -10,8 +11,8 @@ use nostr_sdk::{
     hashes::{sha256::Hash as Sha256Hash, Hash},
     Keys,
 };
-use oracle::{CreateEvent, Event};
-use serde_json::{from_slice, to_string};
+use oracle::{CreateEvent, Event, Station};
+use serde_json::{from_slice, to_string, Value};
 use std::sync::Arc;
 use time::OffsetDateTime;
 use tower::ServiceExt;
@@ -21,7 +22,7 @@ use uuid::Uuid;
 async fn can_create_oracle_event() {
     let base_url = "http://localhost:3000";
     let path = "/oracle/events";
-    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let test_app = spawn_app(Arc::new(known_stations(&["PFNO", "KSAW", "PAPG", "KWMC"]))).await;
     let keys = Keys::generate();
     let new_event = CreateEvent {
         id: Uuid::now_v7(),
@@ -36,8 +37,12 @@ async fn can_create_oracle_event() {
         ],
         total_allowed_entries: 5,
         number_of_places_win: 3,
-        number_of_values_per_entry: 6,
+        number_of_values_per_entry: Some(6),
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     let body_json = to_string(&new_event).unwrap();
@@ -83,7 +88,7 @@ async fn can_create_oracle_event() {
     assert_eq!(res.entry_ids.len(), 0);
     assert_eq!(
         res.number_of_values_per_entry,
-        new_event.number_of_values_per_entry as i64
+        new_event.number_of_values_per_entry.unwrap() as i64
     );
     assert!(res.weather.is_empty());
     assert!(!res.nonce.serialize().is_empty());
@@ -97,7 +102,7 @@ async fn can_create_oracle_event() {
 async fn can_create_and_get_oracle_event() {
     let base_url = "http://localhost:3000";
     let path = "/oracle/events";
-    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let test_app = spawn_app(Arc::new(known_stations(&["PFNO", "KSAW", "PAPG", "KWMC"]))).await;
     let keys = Keys::generate();
 
     let new_event = CreateEvent {
@@ -112,9 +117,13 @@ async fn can_create_and_get_oracle_event() {
             String::from("KWMC"),
         ],
         total_allowed_entries: 5,
-        number_of_values_per_entry: 6,
+        number_of_values_per_entry: Some(6),
         number_of_places_win: 3,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
     let body_json = to_string(&new_event).unwrap();
     let payload_hash = Sha256Hash::hash(body_json.as_bytes());
@@ -191,7 +200,7 @@ async fn can_create_and_get_oracle_event() {
     assert_eq!(res.entry_ids.len(), 0);
     assert_eq!(
         res.number_of_values_per_entry,
-        new_event.number_of_values_per_entry as i64
+        new_event.number_of_values_per_entry.unwrap() as i64
     );
     assert!(res.weather.is_empty());
     assert!(!res.nonce.serialize().is_empty());
@@ -200,3 +209,209 @@ async fn can_create_and_get_oracle_event() {
         .event_announcement
         .is_valid_outcome(&Outcome::Attestation(1)));
 }
+
+#[tokio::test]
+async fn rejects_event_with_unknown_stations() {
+    let test_app = spawn_app(Arc::new(known_stations(&["PFNO", "KSAW"]))).await;
+    let keys = Keys::generate();
+    let new_event = event_at(&["PFNO", "KZZZ", "KSAW", "KYYY"]);
+
+    let response = post_event(&test_app.app, &keys, &new_event, "").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Value = from_slice(&body).unwrap();
+    assert_eq!(res["code"], "bad_event");
+    let message = res["message"].as_str().unwrap();
+    assert!(message.contains("KZZZ, KYYY"), "{}", message);
+    assert!(!message.contains("PFNO"), "{}", message);
+}
+
+#[tokio::test]
+async fn resolves_location_aliases_to_station_ids() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data.expect_stations().times(2).returning(|| {
+        Ok(vec![
+            airport("KJFK", "JFK"),
+            airport("NYC01", "LGA"),
+            airport("PFNO", ""),
+        ])
+    });
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+    let keys = Keys::generate();
+
+    let new_event = event_at(&["JFK", "KLGA", "pfno"]);
+    let response = post_event(&test_app.app, &keys, &new_event, "").await;
+    assert!(response.status().is_success());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Event = from_slice(&body).unwrap();
+    assert_eq!(res.locations, vec!["KJFK", "NYC01", "PFNO"]);
+
+    // two names for one station would double count it
+    let new_event = event_at(&["JFK", "KJFK"]);
+    let response = post_event(&test_app.app, &keys, &new_event, "").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Value = from_slice(&body).unwrap();
+    let message = res["message"].as_str().unwrap();
+    assert!(
+        message.contains("JFK and KJFK are both station KJFK"),
+        "{}",
+        message
+    );
+}
+
+#[tokio::test]
+async fn can_skip_station_check_for_new_stations() {
+    // no stations expected, the check must not query the weather data
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+    let new_event = event_at(&["KZZZ"]);
+
+    let response = post_event(&test_app.app, &keys, &new_event, "?skip_station_check=true").await;
+    assert!(response.status().is_success());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Event = from_slice(&body).unwrap();
+    assert_eq!(res.locations, vec![String::from("KZZZ")]);
+}
+
+#[tokio::test]
+async fn values_per_entry_defaults_to_locations_times_scoring_fields() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+    let new_event = CreateEvent {
+        scoring_fields: vec![
+            oracle::ScoringField::TempHigh,
+            oracle::ScoringField::RainAmt,
+        ],
+        ..event_at(&["PFNO", "KSAW"])
+    };
+    let mut body = serde_json::to_value(&new_event).unwrap();
+    body.as_object_mut()
+        .unwrap()
+        .remove("number_of_values_per_entry");
+    let omitted: CreateEvent = serde_json::from_value(body).unwrap();
+    assert_eq!(omitted.number_of_values_per_entry, None);
+
+    let response = post_event(&test_app.app, &keys, &omitted, "?skip_station_check=true").await;
+    assert!(response.status().is_success());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Event = from_slice(&body).unwrap();
+    assert_eq!(res.number_of_values_per_entry, 4);
+}
+
+#[tokio::test]
+async fn rejects_values_per_entry_inconsistent_with_locations() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+
+    // Two locations with the three default scoring fields allow at most six values
+    for values in [0, 7] {
+        let new_event = CreateEvent {
+            number_of_values_per_entry: Some(values),
+            ..event_at(&["PFNO", "KSAW"])
+        };
+        let response =
+            post_event(&test_app.app, &keys, &new_event, "?skip_station_check=true").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let res: Value = from_slice(&body).unwrap();
+        assert_eq!(res["code"], "bad_event");
+        let message = res["message"].as_str().unwrap();
+        assert!(message.contains("between 1 and 6"), "{}", message);
+    }
+
+    let at_most = CreateEvent {
+        number_of_values_per_entry: Some(6),
+        ..event_at(&["PFNO", "KSAW"])
+    };
+    let response = post_event(&test_app.app, &keys, &at_most, "?skip_station_check=true").await;
+    assert!(response.status().is_success());
+}
+
+fn known_stations(station_ids: &[&str]) -> MockWeatherAccess {
+    let station_ids: Vec<String> = station_ids.iter().map(|id| id.to_string()).collect();
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data.expect_stations().times(1).returning(move || {
+        Ok(station_ids
+            .iter()
+            .map(|station_id| Station {
+                station_id: station_id.clone(),
+                station_name: format!("{} station", station_id),
+                state: String::from("AK"),
+                iata_id: station_id.clone(),
+                elevation_m: None,
+                latitude: 0.0,
+                longitude: 0.0,
+            })
+            .collect())
+    });
+    weather_data
+}
+
+fn airport(station_id: &str, iata_id: &str) -> Station {
+    Station {
+        station_id: station_id.to_string(),
+        station_name: format!("{} station", station_id),
+        state: String::from("NY"),
+        iata_id: iata_id.to_string(),
+        elevation_m: None,
+        latitude: 0.0,
+        longitude: 0.0,
+    }
+}
+
+fn event_at(locations: &[&str]) -> CreateEvent {
+    CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: OffsetDateTime::now_utc(),
+        end_observation_date: OffsetDateTime::now_utc(),
+        signing_date: OffsetDateTime::now_utc(),
+        locations: locations
+            .iter()
+            .map(|location| location.to_string())
+            .collect(),
+        total_allowed_entries: 5,
+        number_of_values_per_entry: None,
+        number_of_places_win: 3,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    }
+}
+
+async fn post_event(
+    app: &axum::Router,
+    keys: &Keys,
+    new_event: &CreateEvent,
+    query: &str,
+) -> Response {
+    let path = format!("/oracle/events{}", query);
+    let body_json = to_string(new_event).unwrap();
+    let payload_hash = Sha256Hash::hash(body_json.as_bytes());
+    let event = create_auth_event(
+        "POST",
+        &format!("http://localhost:3000{}", path),
+        Some(payload_hash),
+        keys,
+    )
+    .await;
+    let auth_header = format!(
+        "Nostr {}",
+        BASE64.encode(serde_json::to_string(&event).unwrap())
+    );
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::AUTHORIZATION, auth_header)
+        .header("host", "localhost:3000")
+        .body(Body::from(body_json))
+        .unwrap();
+    app.clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.")
+}
@@ -13,7 +13,7 @@ use nostr_sdk::{
 use oracle::{AddEventEntries, AddEventEntry, CreateEvent, WeatherChoices, WeatherEntry};
 use serde_json::{from_slice, to_string};
 use std::sync::Arc;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 use tower::ServiceExt;
 use uuid::Uuid;
 
@@ -24,9 +24,9 @@ async fn can_create_entry_into_event() {
     let oracle_event_id = Uuid::now_v7();
     let new_event = CreateEvent {
         id: oracle_event_id,
-        start_observation_date: OffsetDateTime::now_utc(),
-        end_observation_date: OffsetDateTime::now_utc(),
-        signing_date: OffsetDateTime::now_utc(),
+        start_observation_date: OffsetDateTime::now_utc() + Duration::days(1),
+        end_observation_date: OffsetDateTime::now_utc() + Duration::days(1),
+        signing_date: OffsetDateTime::now_utc() + Duration::days(1),
         locations: vec![
             String::from("PFNO"),
             String::from("KSAW"),
@@ -34,9 +34,13 @@ async fn can_create_entry_into_event() {
             String::from("KWMC"),
         ],
         total_allowed_entries: 1,
-        number_of_values_per_entry: 6,
+        number_of_values_per_entry: Some(6),
         number_of_places_win: 1,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     let new_entry = AddEventEntry {
@@ -52,6 +56,7 @@ async fn can_create_entry_into_event() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -62,6 +67,7 @@ async fn can_create_entry_into_event() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KWMC"),
@@ -72,6 +78,7 @@ async fn can_create_entry_into_event() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -84,7 +91,7 @@ async fn can_create_entry_into_event() {
 
     let oracle_event = test_app
         .oracle
-        .create_event(keys.public_key, new_event)
+        .create_event(keys.public_key, new_event, true)
         .await
         .unwrap();
 
@@ -135,9 +142,9 @@ async fn can_create_and_get_event_entry() {
     let keys = Keys::generate();
     let new_event = CreateEvent {
         id: Uuid::now_v7(),
-        start_observation_date: OffsetDateTime::now_utc(),
-        end_observation_date: OffsetDateTime::now_utc(),
-        signing_date: OffsetDateTime::now_utc(),
+        start_observation_date: OffsetDateTime::now_utc() + Duration::days(1),
+        end_observation_date: OffsetDateTime::now_utc() + Duration::days(1),
+        signing_date: OffsetDateTime::now_utc() + Duration::days(1),
         locations: vec![
             String::from("PFNO"),
             String::from("KSAW"),
@@ -146,8 +153,12 @@ async fn can_create_and_get_event_entry() {
         ],
         total_allowed_entries: 1,
         number_of_places_win: 1,
-        number_of_values_per_entry: 6,
+        number_of_values_per_entry: Some(6),
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
     let new_entry = AddEventEntry {
         id: Uuid::now_v7(),
@@ -162,6 +173,7 @@ async fn can_create_and_get_event_entry() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -172,6 +184,7 @@ async fn can_create_and_get_event_entry() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KWMC"),
@@ -182,6 +195,7 @@ async fn can_create_and_get_event_entry() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -206,7 +220,7 @@ async fn can_create_and_get_event_entry() {
     );
     let oracle_event = test_app
         .oracle
-        .create_event(event.pubkey, new_event)
+        .create_event(event.pubkey, new_event, true)
         .await
         .unwrap();
 
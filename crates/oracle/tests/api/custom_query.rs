@@ -0,0 +1,161 @@
+use crate::helpers::{spawn_app, TestApp, WeatherFixture};
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    Router,
+};
+use hyper::Method;
+use oracle::{app, CustomQueryLimits};
+use serde_json::{from_slice, json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Three PFNO and KSAW observations on 2024-08-12 and one forecast.
+async fn spawn_with_fixture() -> TestApp {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T01:53:00Z', 15.0),
+                ('PFNO', '2024-08-12T13:53:00Z', 25.0),
+                ('KSAW', '2024-08-12T13:53:00Z', 21.0)
+            ) AS t(station_id, generated_at, temperature_value)
+        ) TO '{day_dir}/observations_2024-08-12T23:00:00Z.parquet' (FORMAT PARQUET);
+        COPY (
+            SELECT 'PFNO' AS station_id, 80 AS max_temp
+        ) TO '{day_dir}/forecasts_2024-08-12T06:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+    ));
+
+    spawn_app(Arc::new(fixture.weather_access())).await
+}
+
+fn app_with_limits(test_app: &TestApp, limits: CustomQueryLimits) -> Router {
+    let mut state = (*test_app.state).clone();
+    state.custom_query_limits = limits;
+    app(state)
+}
+
+/// Status and the response's lines parsed as JSON
+async fn post_query(app: &Router, body: Value) -> (StatusCode, Vec<Value>) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/query")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let lines = if status == StatusCode::OK {
+        body.split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| from_slice(line).unwrap())
+            .collect()
+    } else {
+        vec![from_slice(&body).unwrap()]
+    };
+    (status, lines)
+}
+
+#[tokio::test]
+async fn streams_rows_over_the_loaded_tables() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, rows) = post_query(
+        &test_app.app,
+        json!({
+            "sql": "SELECT station_id, MAX(temperature_value) AS high FROM observations GROUP BY station_id ORDER BY station_id;",
+            "start": "2024-08-12T00:00:00Z",
+            "end": "2024-08-13T00:00:00Z",
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        rows,
+        vec![
+            json!({ "station_id": "KSAW", "high": 21.0 }),
+            json!({ "station_id": "PFNO", "high": 25.0 }),
+        ]
+    );
+
+    let (status, rows) = post_query(
+        &test_app.app,
+        json!({ "sql": "-- forecasts only\nSELECT * FROM forecasts", "forecasts": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rows, vec![json!({ "station_id": "PFNO", "max_temp": 80 })]);
+}
+
+#[tokio::test]
+async fn rejects_anything_but_a_single_select() {
+    let test_app = spawn_with_fixture().await;
+
+    for sql in [
+        "DELETE FROM observations",
+        "  /* sneaky */ DROP TABLE observations",
+        "COPY observations TO 'out.csv'",
+        "SELECT 1; DROP TABLE observations",
+        "SET enable_external_access = true",
+        "",
+    ] {
+        let (status, body) = post_query(&test_app.app, json!({ "sql": sql })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", sql);
+        assert!(
+            body[0]["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid custom query"),
+            "{}",
+            sql
+        );
+    }
+
+    // Past the keyword check, the connection still can't reach the host's files
+    let (status, _) = post_query(
+        &test_app.app,
+        json!({ "sql": "SELECT * FROM read_csv('Cargo.toml')" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn over_limit_result_is_cut_short() {
+    let test_app = spawn_with_fixture().await;
+    let app = app_with_limits(
+        &test_app,
+        CustomQueryLimits {
+            max_rows: 2,
+            ..Default::default()
+        },
+    );
+
+    let (status, rows) = post_query(
+        &app,
+        json!({ "sql": "SELECT * FROM observations", "observations": true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rows.len(), 3);
+    assert!(rows[0]["station_id"].is_string());
+    assert!(rows[1]["station_id"].is_string());
+    assert!(rows[2]["error"]
+        .as_str()
+        .unwrap()
+        .contains("more than 2 rows"));
+
+    // Exactly at the limit isn't cut short
+    let (status, rows) = post_query(
+        &app,
+        json!({ "sql": "SELECT * FROM observations WHERE station_id = 'PFNO'" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|row| row.get("error").is_none()));
+}
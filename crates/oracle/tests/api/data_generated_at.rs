@@ -0,0 +1,125 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp, WeatherFixture};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::{HeaderMap, Method};
+use serde_json::{from_slice, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const OLDER_GENERATED_AT: &str = "2024-08-11T12:00:00Z";
+const NEWEST_GENERATED_AT: &str = "2024-08-12T06:00:00Z";
+
+/// Two forecast files and one observation file for PFNO on 2024-08-12
+async fn spawn_with_fixture() -> TestApp {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    for generated_at in [OLDER_GENERATED_AT, NEWEST_GENERATED_AT] {
+        fixture.write(&format!(
+            r#"
+            COPY (
+                SELECT * FROM (VALUES
+                    ('PFNO', '2024-08-12T00:00:00Z', '2024-08-12T12:00:00Z', 50, 60, 4, 0.1),
+                    ('PFNO', '2024-08-12T12:00:00Z', '2024-08-13T00:00:00Z', 58, 72, 10, 0.2)
+                ) AS t(station_id, begin_time, end_time, min_temp, max_temp, wind_speed, liquid_precipitation_amt)
+                CROSS JOIN (SELECT 'fahrenheit' AS temperature_unit_code, '{generated_at}' AS generated_at)
+            ) TO '{day_dir}/forecasts_{generated_at}.parquet' (FORMAT PARQUET);
+            "#
+        ));
+    }
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T01:53:00Z', 10.0, 5, 180, 4.0, 0.0),
+                ('PFNO', '2024-08-12T13:53:00Z', 21.5, 9, 200, 8.0, 0.12)
+            ) AS t(station_id, generated_at, temperature_value, wind_speed, wind_direction, dewpoint_value, precip_in)
+            CROSS JOIN (SELECT 'celsius' AS temperature_unit_code)
+        ) TO '{day_dir}/observations_2024-08-12T23:00:00Z.parquet' (FORMAT PARQUET);
+        "#
+    ));
+
+    spawn_app(Arc::new(fixture.weather_access())).await
+}
+
+async fn get(test_app: &TestApp, uri: &str) -> (HeaderMap, Value) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let headers = response.headers().clone();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (headers, from_slice(&body).unwrap())
+}
+
+fn generated_at_header(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-data-generated-at")
+        .map(|value| value.to_str().unwrap())
+}
+
+#[tokio::test]
+async fn header_matches_newest_forecast_file() {
+    let test_app = spawn_with_fixture().await;
+
+    let (headers, body) = get(
+        &test_app,
+        "/stations/forecasts?station_ids=PFNO&start=2024-08-12T00:00:00Z&end=2024-08-13T00:00:00Z",
+    )
+    .await;
+    assert_eq!(generated_at_header(&headers), Some(NEWEST_GENERATED_AT));
+    assert!(body.is_array());
+}
+
+#[tokio::test]
+async fn header_matches_newest_observation() {
+    let test_app = spawn_with_fixture().await;
+    let range = "station_ids=PFNO&start=2024-08-12T00:00:00Z&end=2024-08-13T00:00:00Z";
+
+    let (headers, _) = get(&test_app, &format!("/stations/observations?{}", range)).await;
+    assert_eq!(generated_at_header(&headers), Some("2024-08-12T13:53:00Z"));
+    let (headers, _) = get(
+        &test_app,
+        &format!("/stations/daily-observations?{}", range),
+    )
+    .await;
+    assert_eq!(generated_at_header(&headers), Some("2024-08-12T13:53:00Z"));
+}
+
+#[tokio::test]
+async fn envelope_wraps_rows_with_generated_at() {
+    let test_app = spawn_with_fixture().await;
+
+    let (_, body) = get(
+        &test_app,
+        "/stations/forecasts?station_ids=PFNO&start=2024-08-12T00:00:00Z&end=2024-08-13T00:00:00Z&envelope=true",
+    )
+    .await;
+    assert_eq!(body["generated_at"], NEWEST_GENERATED_AT);
+    let data = body["data"].as_array().unwrap();
+    assert!(!data.is_empty());
+    assert!(data.iter().all(|row| row["station_id"] == "PFNO"));
+}
+
+#[tokio::test]
+async fn no_header_without_rows() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_observation_data()
+        .times(1)
+        .returning(|_, _| Ok(vec![]));
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    let (headers, body) = get(
+        &test_app,
+        "/stations/observations?station_ids=PFNO&envelope=true",
+    )
+    .await;
+    assert_eq!(generated_at_header(&headers), None);
+    assert_eq!(body["generated_at"], Value::Null);
+    assert_eq!(body["data"], Value::Array(vec![]));
+}
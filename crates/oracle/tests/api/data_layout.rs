@@ -0,0 +1,156 @@
+use crate::helpers::{
+    parquet_bytes, random_test_number, spawn_app_with_file_access, MockWeatherAccess,
+    FORECAST_MESSAGE_TYPE,
+};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use hyper::{header::CONTENT_TYPE, Method};
+use noaa_oracle_core::{DataLayout, DATA_LAYOUT_HEADER};
+use oracle::{create_folder, FileAccess, FileData, FileParams};
+use std::{fs, path::Path, sync::Arc};
+use time::macros::datetime;
+use tower::ServiceExt;
+
+const FORECAST: &str = "forecasts_2024-08-12T01:00:00Z.parquet";
+const OBSERVATION: &str = "observations_2024-08-12T01:00:00Z.parquet";
+const OLD_FORECAST: &str = "forecasts_2024-08-01T01:00:00Z.parquet";
+
+fn write_files(data_dir: &str, folders: &[(&str, &str)]) {
+    for (folder, name) in folders {
+        let folder = format!("{}/{}", data_dir, folder);
+        create_folder(&folder);
+        fs::write(format!("{}/{}", folder, name), name.as_bytes()).unwrap();
+    }
+}
+
+fn params() -> FileParams {
+    FileParams {
+        start: Some(datetime!(2024-08-10 00:00 UTC)),
+        end: Some(datetime!(2024-08-13 00:00 UTC)),
+        observations: None,
+        forecasts: None,
+    }
+}
+
+async fn assert_discovers(file_access: FileAccess, data_dir: &str) {
+    let mut file_names = file_access.grab_file_names(params()).await.unwrap();
+    file_names.sort();
+    assert_eq!(file_names, vec![FORECAST, OBSERVATION]);
+    for path in file_access.build_file_paths(file_names) {
+        assert!(Path::new(&path).exists(), "{} missing", path);
+        assert!(path.starts_with(data_dir));
+    }
+}
+
+#[tokio::test]
+async fn can_discover_files_in_date_sub_layout() {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    write_files(
+        &data_dir,
+        &[
+            ("2024-08-12", FORECAST),
+            ("2024-08-12", OBSERVATION),
+            ("2024-08-01", OLD_FORECAST),
+        ],
+    );
+
+    let file_access = FileAccess::new(data_dir.clone()).with_layout(DataLayout::DateSub);
+    assert_eq!(
+        file_access.build_file_path(FORECAST, datetime!(2024-08-12 01:00 UTC)),
+        format!("{}/2024-08-12/{}", data_dir, FORECAST)
+    );
+    assert_discovers(file_access, &data_dir).await;
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[tokio::test]
+async fn can_discover_files_in_hive_layout() {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    write_files(
+        &data_dir,
+        &[
+            ("type=forecasts/date=2024-08-12", FORECAST),
+            ("type=observations/date=2024-08-12", OBSERVATION),
+            ("type=forecasts/date=2024-08-01", OLD_FORECAST),
+        ],
+    );
+
+    let file_access = FileAccess::new(data_dir.clone()).with_layout(DataLayout::Hive);
+    assert_eq!(
+        file_access.build_file_path(OBSERVATION, datetime!(2024-08-12 01:00 UTC)),
+        format!(
+            "{}/type=observations/date=2024-08-12/{}",
+            data_dir, OBSERVATION
+        )
+    );
+    assert_discovers(file_access, &data_dir).await;
+
+    // The same tree read with the wrong layout finds nothing rather than misreading it
+    let date_sub = FileAccess::new(data_dir.clone());
+    assert!(date_sub.grab_file_names(params()).await.unwrap().is_empty());
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+fn upload_request(layout: Option<DataLayout>) -> Request<Body> {
+    let boundary = "layout-boundary";
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{FORECAST}\"\r\n\
+         Content-Type: application/parquet\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend(parquet_bytes(FORECAST_MESSAGE_TYPE));
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    let mut request = Request::builder()
+        .method(Method::POST)
+        .uri(format!("/file/{}", FORECAST))
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", boundary),
+        );
+    if let Some(layout) = layout {
+        request = request.header(DATA_LAYOUT_HEADER, layout.to_string());
+    }
+    request.body(Body::from(body)).unwrap()
+}
+
+#[tokio::test]
+async fn upload_is_stored_with_oracle_layout_and_rejects_mismatch() {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    let test_app = spawn_app_with_file_access(
+        Arc::new(MockWeatherAccess::new()),
+        Arc::new(FileAccess::new(data_dir.clone()).with_layout(DataLayout::Hive)),
+    )
+    .await;
+
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(upload_request(Some(DataLayout::DateSub)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(!Path::new(&data_dir).exists());
+
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(upload_request(Some(DataLayout::Hive)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let stored = format!("{}/type=forecasts/date=2024-08-12/{}", data_dir, FORECAST);
+    assert_eq!(
+        fs::read(&stored).unwrap(),
+        parquet_bytes(FORECAST_MESSAGE_TYPE)
+    );
+
+    // Uploaders that don't send a layout are still accepted
+    let response = test_app.app.oneshot(upload_request(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
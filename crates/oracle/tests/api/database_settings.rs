@@ -0,0 +1,69 @@
+use crate::helpers::random_test_number;
+use oracle::{create_folder, Database, DatabaseSettings};
+
+#[tokio::test]
+async fn opens_database_with_overridden_settings() {
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+    let settings = DatabaseSettings {
+        max_connections: 8,
+        busy_timeout_ms: 12000,
+        cache_size_kib: 2048,
+    };
+
+    let db = Database::new_with_settings(&event_data, settings)
+        .await
+        .unwrap();
+
+    let busy_timeout: i64 = sqlx::query_scalar("PRAGMA busy_timeout;")
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+    assert_eq!(busy_timeout, 12000);
+    let cache_size: i64 = sqlx::query_scalar("PRAGMA cache_size;")
+        .fetch_one(db.pool())
+        .await
+        .unwrap();
+    assert_eq!(cache_size, -2048);
+    assert_eq!(db.pool().options().get_max_connections(), 8);
+}
+
+#[tokio::test]
+async fn rejects_out_of_range_settings() {
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+
+    let no_connections = DatabaseSettings {
+        max_connections: 0,
+        ..DatabaseSettings::default()
+    };
+    assert!(Database::new_with_settings(&event_data, no_connections)
+        .await
+        .is_err());
+
+    let tiny_cache = DatabaseSettings {
+        cache_size_kib: 1,
+        ..DatabaseSettings::default()
+    };
+    assert!(Database::new_with_settings(&event_data, tiny_cache)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn read_pool_rejects_writes() {
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+    let db = Database::new(&event_data).await.unwrap();
+
+    let write = sqlx::query("INSERT INTO oracle_metadata (pubkey, name) VALUES (x'00', 'read')")
+        .execute(db.read_pool())
+        .await;
+    assert!(write.is_err());
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM oracle_metadata")
+        .fetch_one(db.read_pool())
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,105 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use duckdb::Connection;
+use hyper::Method;
+use oracle::DayTimeZone;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// Readings either side of UTC midnight on 2024-01-11, when US/Eastern is UTC-5
+const READINGS: &str =
+    "('2024-01-10T23:30:00Z'), ('2024-01-11T02:00:00Z'), ('2024-01-11T06:00:00Z')";
+
+/// Number of readings bucketed into each day
+fn readings_per_day(tz: &DayTimeZone) -> Vec<(String, i64)> {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("INSTALL icu; LOAD icu;")
+        .expect("duckdb icu extension");
+    let day = tz.day_sql("generated_at");
+    let sql = format!(
+        "SELECT {day} AS date, COUNT(*) FROM (VALUES {}) t(generated_at) GROUP BY {day} ORDER BY date",
+        READINGS
+    );
+    let mut stmt = conn.prepare(&sql).unwrap();
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect()
+}
+
+fn days(counts: &[(&str, i64)]) -> Vec<(String, i64)> {
+    counts
+        .iter()
+        .map(|(date, count)| (format!("{} 00:00:00", date), *count))
+        .collect()
+}
+
+#[test]
+fn utc_days_split_at_utc_midnight() {
+    let buckets = readings_per_day(&DayTimeZone::Utc);
+    assert_eq!(buckets, days(&[("2024-01-10", 1), ("2024-01-11", 2)]));
+}
+
+#[test]
+fn eastern_days_split_at_local_midnight() {
+    // 23:30Z and 02:00Z are still the evening of the 10th in New York
+    let expected = days(&[("2024-01-10", 2), ("2024-01-11", 1)]);
+    for tz in ["US/Eastern", "America/New_York", "-05:00"] {
+        let buckets = readings_per_day(&tz.parse().unwrap());
+        assert_eq!(buckets, expected, "bucketing in {}", tz);
+    }
+}
+
+#[test]
+fn parses_names_and_offsets() {
+    assert_eq!("UTC".parse::<DayTimeZone>().unwrap(), DayTimeZone::Utc);
+    assert_eq!("+00:00".parse::<DayTimeZone>().unwrap(), DayTimeZone::Utc);
+    assert_eq!(
+        "-05:00".parse::<DayTimeZone>().unwrap(),
+        DayTimeZone::Offset(-5 * 3600)
+    );
+    assert_eq!(
+        "+05:30".parse::<DayTimeZone>().unwrap(),
+        DayTimeZone::Offset(5 * 3600 + 30 * 60)
+    );
+    assert_eq!(
+        "America/Argentina/Buenos_Aires"
+            .parse::<DayTimeZone>()
+            .unwrap(),
+        DayTimeZone::Named(String::from("America/Argentina/Buenos_Aires"))
+    );
+    assert_eq!(DayTimeZone::Offset(-5 * 3600).to_string(), "-05:00");
+}
+
+#[test]
+fn rejects_invalid_zones() {
+    for tz in [
+        "+5",
+        "-05:75",
+        "+15:00",
+        "US/Eastern'; DROP TABLE x; --",
+        "",
+        "1abc",
+    ] {
+        assert!(
+            tz.parse::<DayTimeZone>().is_err(),
+            "{} should be rejected",
+            tz
+        );
+    }
+}
+
+#[tokio::test]
+async fn daily_observations_rejects_unknown_tz() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/stations/daily-observations?station_ids=PFNO&tz=Not%20A%20Zone")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
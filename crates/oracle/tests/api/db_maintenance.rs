@@ -0,0 +1,81 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use nostr_sdk::Keys;
+use oracle::{app_for, CreateEvent, EventStore, MaintenanceReport, RouteScope};
+use serde_json::from_slice;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn can_run_maintenance_on_populated_db() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+    for _ in 0..5 {
+        let new_event = CreateEvent {
+            id: Uuid::now_v7(),
+            start_observation_date: OffsetDateTime::now_utc(),
+            end_observation_date: OffsetDateTime::now_utc(),
+            signing_date: OffsetDateTime::now_utc(),
+            locations: vec![String::from("PFNO"), String::from("KSAW")],
+            total_allowed_entries: 5,
+            number_of_values_per_entry: Some(4),
+            number_of_places_win: 1,
+            scoring_fields: oracle::ScoringField::defaults(),
+            par_tolerances: Default::default(),
+            scoring_mode: Default::default(),
+            settlement_delay_hours: Default::default(),
+            entry_cutoff: Default::default(),
+        };
+        test_app
+            .oracle
+            .create_event(keys.public_key, new_event, true)
+            .await
+            .unwrap();
+    }
+
+    let report = test_app.db.maintenance(false).await.unwrap();
+    assert!(!report.vacuumed);
+    assert!(report.page_count_before > 0);
+    assert!(report.page_count_after > 0);
+
+    let request = || {
+        Request::builder()
+            .method(Method::POST)
+            .uri(String::from("/admin/db/maintenance?vacuum=true"))
+            .body(Body::empty())
+            .unwrap()
+    };
+    // unauthenticated, so only routed on a listener scoped to admin
+    let response = test_app
+        .app
+        .oneshot(request())
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let admin_app = app_for((*test_app.state).clone(), RouteScope::Admin);
+    let response = admin_app
+        .oneshot(request())
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let report: MaintenanceReport = from_slice(&body).unwrap();
+    assert!(report.vacuumed);
+    assert!(report.page_count_after > 0);
+    assert!(report.page_count_after <= report.page_count_before);
+    assert_eq!(report.freelist_count_after, 0);
+
+    let events = test_app
+        .oracle
+        .list_events(oracle::EventFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 5);
+}
@@ -0,0 +1,22 @@
+use oracle::weather_data::dominant_precip;
+
+#[test]
+fn largest_amount_wins() {
+    assert_eq!(dominant_precip(Some(0.4), Some(1.2), None), "snow");
+    assert_eq!(dominant_precip(Some(0.4), Some(0.1), Some(0.05)), "rain");
+    assert_eq!(dominant_precip(None, None, Some(0.1)), "ice");
+}
+
+#[test]
+fn ties_go_to_the_more_hazardous_type() {
+    assert_eq!(dominant_precip(Some(0.5), Some(0.5), None), "snow");
+    assert_eq!(dominant_precip(Some(0.5), Some(0.5), Some(0.5)), "ice");
+    assert_eq!(dominant_precip(Some(0.5), None, Some(0.5)), "ice");
+}
+
+#[test]
+fn none_when_all_zero_or_null() {
+    assert_eq!(dominant_precip(None, None, None), "none");
+    assert_eq!(dominant_precip(Some(0.0), Some(0.0), Some(0.0)), "none");
+    assert_eq!(dominant_precip(Some(0.0), None, None), "none");
+}
@@ -0,0 +1,72 @@
+use crate::helpers::random_test_number;
+use duckdb::Connection;
+use oracle::{
+    create_folder,
+    weather_data::{parquet_setup, WeatherAccess},
+    FileAccess,
+};
+use std::{fs, sync::Arc};
+
+#[test]
+fn can_skip_install_when_parquet_already_installed() {
+    assert_eq!(parquet_setup(true, true), None);
+    assert_eq!(parquet_setup(false, true), None);
+    assert_eq!(parquet_setup(true, false), Some("LOAD parquet;"));
+    assert_eq!(
+        parquet_setup(false, false),
+        Some("INSTALL parquet; LOAD parquet;")
+    );
+}
+
+#[test]
+#[ignore = "needs duckdb to download the parquet extension"]
+fn can_query_with_preconfigured_extension_dir() {
+    let extension_dir = format!("./test_data/duckdb_extensions_{}", random_test_number());
+    create_folder(&extension_dir);
+
+    // Stand in for an operator copying the extension onto an air-gapped host
+    let seed = Connection::open_in_memory().unwrap();
+    seed.execute_batch(&format!(
+        "SET extension_directory = '{}'; INSTALL parquet;",
+        extension_dir
+    ))
+    .expect("duckdb parquet extension");
+    drop(seed);
+
+    let weather_access = WeatherAccess::new(Arc::new(FileAccess::new(String::from(
+        "./test_data/unused",
+    ))))
+    .unwrap()
+    .with_extension_dir(Some(extension_dir.clone()));
+    let conn = weather_access.open_connection().unwrap();
+
+    let (directory, installed, loaded): (String, bool, bool) = conn
+        .query_row(
+            "SELECT current_setting('extension_directory'), installed, loaded
+             FROM duckdb_extensions() WHERE extension_name = 'parquet'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(directory, extension_dir);
+    // Already installed in the configured dir, so open_connection only had to LOAD it
+    assert_eq!(parquet_setup(installed, false), Some("LOAD parquet;"));
+    assert!(loaded);
+
+    let parquet_file = format!("{}/rows.parquet", extension_dir);
+    conn.execute_batch(&format!(
+        "COPY (SELECT * FROM range(3)) TO '{}' (FORMAT PARQUET);",
+        parquet_file
+    ))
+    .unwrap();
+    let rows: i64 = conn
+        .query_row(
+            &format!("SELECT count(*) FROM read_parquet('{}')", parquet_file),
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(rows, 3);
+
+    fs::remove_dir_all(&extension_dir).unwrap();
+}
@@ -0,0 +1,99 @@
+use crate::helpers::{spawn_app, TestApp, WeatherFixture};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use oracle::{EmptyData, QueryStats};
+use std::{sync::Arc, time::Instant};
+use tower::ServiceExt;
+
+const ENDPOINTS: [&str; 3] = ["forecasts", "observations", "daily-observations"];
+
+/// Forecasts and observations for PFNO on 2024-08-12
+async fn spawn_with_fixture() -> TestApp {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T00:00:00Z', '2024-08-12T12:00:00Z', 50, 60, 4, 0.1),
+                ('PFNO', '2024-08-12T12:00:00Z', '2024-08-13T00:00:00Z', 58, 72, 10, 0.2)
+            ) AS t(station_id, begin_time, end_time, min_temp, max_temp, wind_speed, liquid_precipitation_amt)
+            CROSS JOIN (SELECT 'fahrenheit' AS temperature_unit_code, '2024-08-11T12:00:00Z' AS generated_at)
+        ) TO '{day_dir}/forecasts_2024-08-11T12:00:00Z.parquet' (FORMAT PARQUET);
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T01:53:00Z', 10.0, 5),
+                ('PFNO', '2024-08-12T13:53:00Z', 21.5, 9)
+            ) AS t(station_id, generated_at, temperature_value, wind_speed)
+            CROSS JOIN (SELECT 'celsius' AS temperature_unit_code)
+        ) TO '{day_dir}/observations_2024-08-12T23:00:00Z.parquet' (FORMAT PARQUET);
+        "#
+    ));
+
+    spawn_app(Arc::new(fixture.weather_access())).await
+}
+
+async fn data_source(test_app: &TestApp, endpoint: &str, station_id: &str) -> Option<String> {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!(
+            "/stations/{}?station_ids={}&start=2024-08-12T00:00:00Z&end=2024-08-13T00:00:00Z",
+            endpoint, station_id
+        ))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    response
+        .headers()
+        .get("x-data-source")
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn missing_files_report_none() {
+    let fixture = WeatherFixture::new();
+    let test_app = spawn_app(Arc::new(fixture.weather_access())).await;
+
+    for endpoint in ENDPOINTS {
+        assert_eq!(
+            data_source(&test_app, endpoint, "PFNO").await.as_deref(),
+            Some("none"),
+            "{}",
+            endpoint
+        );
+    }
+}
+
+#[tokio::test]
+async fn unmatched_rows_report_empty() {
+    let test_app = spawn_with_fixture().await;
+
+    for endpoint in ENDPOINTS {
+        assert_eq!(
+            data_source(&test_app, endpoint, "KSAW").await.as_deref(),
+            Some("empty"),
+            "{}",
+            endpoint
+        );
+        assert_eq!(data_source(&test_app, endpoint, "PFNO").await, None);
+    }
+}
+
+#[test]
+fn empty_data_needs_a_file_count() {
+    let started = Instant::now();
+    assert_eq!(
+        QueryStats::new(Some(0), 0, started).empty_data(),
+        Some(EmptyData::NoFiles)
+    );
+    assert_eq!(
+        QueryStats::new(Some(3), 0, started).empty_data(),
+        Some(EmptyData::NoRows)
+    );
+    assert_eq!(QueryStats::new(Some(3), 2, started).empty_data(), None);
+    assert_eq!(QueryStats::new(None, 0, started).empty_data(), None);
+}
@@ -0,0 +1,136 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp};
+use nostr_sdk::Keys;
+use oracle::{
+    oracle::Error, AddEventEntry, CreateEvent, ScoringField, ValueOptions, WeatherChoices,
+    WeatherEntry,
+};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+fn new_event(
+    start_observation_date: OffsetDateTime,
+    entry_cutoff: Option<OffsetDateTime>,
+) -> CreateEvent {
+    CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date,
+        end_observation_date: start_observation_date + Duration::days(1),
+        signing_date: start_observation_date + Duration::days(2),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 1,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff,
+    }
+}
+
+async fn enter_event(
+    test_app: &TestApp,
+    start_observation_date: OffsetDateTime,
+    entry_cutoff: Option<OffsetDateTime>,
+) -> Result<Vec<WeatherEntry>, Error> {
+    let keys = Keys::generate();
+    let event = test_app
+        .oracle
+        .create_event(
+            keys.public_key,
+            new_event(start_observation_date, entry_cutoff),
+            true,
+        )
+        .await
+        .unwrap();
+    let entry = AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id: event.id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_low: Some(ValueOptions::Par),
+            temp_high: None,
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    };
+    test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, vec![entry])
+        .await
+}
+
+#[tokio::test]
+async fn entries_close_at_start_observation_date_by_default() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let now = OffsetDateTime::now_utc();
+
+    let result = enter_event(&test_app, now + Duration::hours(1), None).await;
+    assert!(result.is_ok());
+
+    let result = enter_event(&test_app, now - Duration::hours(1), None).await;
+    assert!(matches!(result, Err(Error::BadEntry(_))));
+}
+
+#[tokio::test]
+async fn entries_close_at_explicit_cutoff() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let now = OffsetDateTime::now_utc();
+    let start = now + Duration::days(1);
+
+    let result = enter_event(&test_app, start, Some(now + Duration::hours(1))).await;
+    assert!(result.is_ok());
+
+    let result = enter_event(&test_app, start, Some(now - Duration::hours(1))).await;
+    assert!(matches!(result, Err(Error::BadEntry(_))));
+}
+
+#[tokio::test]
+async fn entry_cutoff_is_stored_with_event() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let start = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap() + Duration::days(1);
+    let entry_cutoff = start - Duration::hours(6);
+
+    let event = test_app
+        .oracle
+        .create_event(
+            Keys::generate().public_key,
+            new_event(start, Some(entry_cutoff)),
+            true,
+        )
+        .await
+        .unwrap();
+    let stored = test_app.oracle.get_event(&event.id).await.unwrap();
+    assert_eq!(stored.entry_cutoff, Some(entry_cutoff));
+    assert_eq!(stored.entries_close_at(), entry_cutoff);
+
+    let event = test_app
+        .oracle
+        .create_event(Keys::generate().public_key, new_event(start, None), true)
+        .await
+        .unwrap();
+    let stored = test_app.oracle.get_event(&event.id).await.unwrap();
+    assert_eq!(stored.entry_cutoff, None);
+    assert_eq!(stored.entries_close_at(), start);
+}
+
+#[tokio::test]
+async fn rejects_entry_cutoff_after_observation_start() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let start = OffsetDateTime::now_utc() + Duration::days(1);
+
+    let result = test_app
+        .oracle
+        .create_event(
+            Keys::generate().public_key,
+            new_event(start, Some(start + Duration::hours(1))),
+            true,
+        )
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,151 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp};
+use nostr_sdk::Keys;
+use oracle::{
+    oracle::Error, AddEventEntry, CreateEvent, EntryLimitExceeded, EventStore, ScoringField,
+    ValueOptions, WeatherChoices, WeatherEntry,
+};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+const ALLOWED_ENTRIES: usize = 2;
+
+async fn create_event(test_app: &TestApp, keys: &Keys) -> Uuid {
+    let start_observation_date = OffsetDateTime::now_utc() + Duration::days(1);
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date,
+        end_observation_date: start_observation_date + Duration::days(1),
+        signing_date: start_observation_date + Duration::days(2),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: ALLOWED_ENTRIES,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    test_app
+        .oracle
+        .create_event(keys.public_key, new_event, true)
+        .await
+        .unwrap()
+        .id
+}
+
+fn entries(event_id: Uuid, count: usize) -> Vec<AddEventEntry> {
+    (0..count)
+        .map(|_| AddEventEntry {
+            id: Uuid::now_v7(),
+            event_id,
+            expected_observations: vec![WeatherChoices {
+                stations: String::from("PFNO"),
+                temp_low: Some(ValueOptions::Par),
+                temp_high: None,
+                wind_speed: None,
+                wind_direction: None,
+                rain_amt: None,
+                snow_amt: None,
+                humidity: None,
+                predictions: Default::default(),
+            }],
+        })
+        .collect()
+}
+
+fn stored(entries: Vec<AddEventEntry>) -> Vec<WeatherEntry> {
+    entries.into_iter().map(WeatherEntry::from).collect()
+}
+
+async fn entry_count(test_app: &TestApp, event_id: &Uuid) -> usize {
+    test_app
+        .oracle
+        .get_event(event_id)
+        .await
+        .unwrap()
+        .entries
+        .len()
+}
+
+#[tokio::test]
+async fn rejects_entries_past_the_cap() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+    let event_id = create_event(&test_app, &keys).await;
+
+    test_app
+        .oracle
+        .add_event_entries(
+            keys.public_key,
+            event_id,
+            entries(event_id, ALLOWED_ENTRIES),
+        )
+        .await
+        .unwrap();
+    assert_eq!(entry_count(&test_app, &event_id).await, ALLOWED_ENTRIES);
+
+    let err = test_app
+        .db
+        .add_event_entries(stored(entries(event_id, 1)))
+        .await
+        .unwrap_err();
+    let limit = err.downcast_ref::<EntryLimitExceeded>().unwrap();
+    assert_eq!(limit.allowed, ALLOWED_ENTRIES as i64);
+    assert_eq!(limit.existing, ALLOWED_ENTRIES as i64);
+    assert_eq!(limit.new, 1);
+    assert_eq!(entry_count(&test_app, &event_id).await, ALLOWED_ENTRIES);
+}
+
+#[tokio::test]
+async fn overflowing_batch_stores_nothing() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event_id = create_event(&test_app, &Keys::generate()).await;
+
+    test_app
+        .db
+        .add_event_entries(stored(entries(event_id, 1)))
+        .await
+        .unwrap();
+    let result = test_app
+        .db
+        .add_event_entries(stored(entries(event_id, ALLOWED_ENTRIES)))
+        .await;
+    assert!(result.is_err());
+    assert_eq!(entry_count(&test_app, &event_id).await, 1);
+
+    test_app
+        .db
+        .add_event_entries(stored(entries(event_id, 1)))
+        .await
+        .unwrap();
+    assert_eq!(entry_count(&test_app, &event_id).await, ALLOWED_ENTRIES);
+}
+
+#[tokio::test]
+async fn concurrent_submissions_cannot_exceed_the_cap() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+    let event_id = create_event(&test_app, &keys).await;
+
+    // both submissions can pass the oracle's "no entries yet" check before either is stored
+    let (first, second) = tokio::join!(
+        test_app.oracle.add_event_entries(
+            keys.public_key,
+            event_id,
+            entries(event_id, ALLOWED_ENTRIES)
+        ),
+        test_app.oracle.add_event_entries(
+            keys.public_key,
+            event_id,
+            entries(event_id, ALLOWED_ENTRIES)
+        ),
+    );
+    let results = [first, second];
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert!(results
+        .iter()
+        .any(|result| matches!(result, Err(Error::BadEntry(_)))));
+    assert_eq!(entry_count(&test_app, &event_id).await, ALLOWED_ENTRIES);
+}
@@ -0,0 +1,112 @@
+use anyhow::anyhow;
+use axum::{body::to_bytes, http::StatusCode, response::IntoResponse};
+use nostr_sdk::{nips::nip19::FromBech32, PublicKey, SecretKey};
+use oracle::{oracle::Error, weather_data, ErrorBody};
+use serde_json::from_slice;
+
+async fn render(err: Error) -> (StatusCode, ErrorBody) {
+    let response = err.into_response();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn client_errors_carry_code_message_and_details() {
+    let cases = [
+        (
+            Error::NotFound(String::from("event not found")),
+            StatusCode::NOT_FOUND,
+            "not_found",
+        ),
+        (
+            Error::MinOutcome(String::from("0")),
+            StatusCode::BAD_REQUEST,
+            "min_outcome",
+        ),
+        (
+            Error::EventMaturity(String::from("2024-08-12")),
+            StatusCode::BAD_REQUEST,
+            "event_maturity",
+        ),
+        (
+            Error::BadEntry(String::from("too many values")),
+            StatusCode::BAD_REQUEST,
+            "bad_entry",
+        ),
+        (
+            Error::BadEvent(anyhow!("unknown stations")),
+            StatusCode::BAD_REQUEST,
+            "bad_event",
+        ),
+        (
+            Error::BadAttestation(String::from("wrong nonce")),
+            StatusCode::BAD_REQUEST,
+            "bad_attestation",
+        ),
+        (
+            Error::NotCoordinator(String::from("another coordinator's event")),
+            StatusCode::FORBIDDEN,
+            "not_coordinator",
+        ),
+        (
+            Error::KeyRotation(String::from("key already active")),
+            StatusCode::CONFLICT,
+            "key_rotation_rejected",
+        ),
+    ];
+
+    for (err, status, code) in cases {
+        let message = err.to_string();
+        let details = err.details();
+        let (rendered_status, body) = render(err).await;
+        assert_eq!(rendered_status, status, "{}", code);
+        assert_eq!(body.code, code);
+        assert_eq!(body.message, message);
+        assert!(details.is_some(), "{}", code);
+        assert_eq!(body.details, details);
+    }
+}
+
+#[tokio::test]
+async fn internal_errors_hide_their_cause() {
+    let cases = [
+        (Error::ValidateKey(anyhow!("bad pem")), "key_invalid"),
+        (
+            Error::ConvertKey(SecretKey::parse("not a key").unwrap_err()),
+            "key_conversion_failed",
+        ),
+        (
+            Error::Base32Key(PublicKey::from_bech32("npub1invalid").unwrap_err()),
+            "key_encoding_failed",
+        ),
+        (
+            Error::DataQuery(sqlx::Error::RowNotFound),
+            "data_query_failed",
+        ),
+        (
+            Error::MismatchPubkey(String::from("npub1...")),
+            "pubkey_mismatch",
+        ),
+        (
+            Error::WeatherData(weather_data::Error::Materialize(String::from("disk full"))),
+            "weather_data_failed",
+        ),
+        (
+            Error::OutcomeNotFound(String::from("no outcome")),
+            "outcome_not_found",
+        ),
+        (
+            Error::Validation(serde_json::from_str::<u32>("nope").unwrap_err()),
+            "message_validation_failed",
+        ),
+    ];
+
+    for (err, code) in cases {
+        let (status, body) = render(err).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{}", code);
+        assert_eq!(body.code, code);
+        assert_eq!(body.message, "internal server error");
+        assert_eq!(body.details, None);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::helpers::{spawn_app, MockWeatherAccess};
+use crate::helpers::{spawn_app, spawn_app_with_clock, MockWeatherAccess};
 use axum::{
     body::{to_bytes, Body},
     http::Request,
@@ -9,11 +9,11 @@ use log::info;
 use nostr_sdk::Keys;
 use oracle::{
     oracle::get_winning_bytes, AddEventEntries, AddEventEntry, CreateEvent, Event, EventStatus,
-    Forecast, Observation, TemperatureUnit, WeatherChoices,
+    FixedClock, Forecast, Observation, PrecipUnit, TemperatureUnit, WeatherChoices,
 };
 use serde_json::from_slice;
 use std::{cmp, sync::Arc};
-use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use time::{format_description::well_known::Rfc3339, macros::datetime, OffsetDateTime};
 use tokio::time::sleep;
 use tower::ServiceExt;
 use uuid::{ClockSequence, Timestamp, Uuid};
@@ -72,7 +72,9 @@ async fn can_get_event_run_etl_and_see_it_signed() {
         .times(2)
         .returning(|_, _| Ok(mock_observation_data()));
 
-    let test_app = spawn_app(Arc::new(weather_data)).await;
+    // entries go in the day before the event starts
+    let clock = Arc::new(FixedClock::new(datetime!(2024-08-11 00:00:00 UTC)));
+    let test_app = spawn_app_with_clock(Arc::new(weather_data), clock.clone()).await;
 
     // This makes the event window 1 day (what is used by the oracle)
     let start_observation_date =
@@ -93,15 +95,19 @@ async fn can_get_event_run_etl_and_see_it_signed() {
             String::from("KWMC"),
         ],
         total_allowed_entries: 4,
-        number_of_values_per_entry: 6,
+        number_of_values_per_entry: Some(6),
         number_of_places_win: 3,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
 
     info!("above create event");
     let event = test_app
         .oracle
-        .create_event(keys.public_key, new_event_1)
+        .create_event(keys.public_key, new_event_1, true)
         .await
         .unwrap();
 
@@ -118,6 +124,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -128,6 +135,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KWMC"),
@@ -138,6 +146,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -154,6 +163,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -164,6 +174,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KWMC"),
@@ -174,6 +185,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -190,6 +202,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -200,6 +213,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KWMC"),
@@ -210,6 +224,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -226,6 +241,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KSAW"),
@@ -236,6 +252,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
             WeatherChoices {
                 stations: String::from("KWMC"),
@@ -246,6 +263,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
                 rain_amt: None,
                 snow_amt: None,
                 humidity: None,
+                predictions: Default::default(),
             },
         ],
     };
@@ -260,13 +278,10 @@ async fn can_get_event_run_etl_and_see_it_signed() {
     };
     test_app
         .oracle
-        .add_event_entries(
-            keys.public_key,
-            event_entries.event_id,
-            event_entries.entries,
-        )
+        .add_event_entries(keys.public_key, event.id, event_entries.entries)
         .await
         .unwrap();
+    clock.set(signing_date + time::Duration::hours(1));
 
     // 1) get event before etl
     let request = Request::builder()
@@ -310,7 +325,10 @@ async fn can_get_event_run_etl_and_see_it_signed() {
     // 3) get event after etl
     let request = Request::builder()
         .method(Method::GET)
-        .uri(format!("/oracle/events/{}", event.id))
+        .uri(format!(
+            "/oracle/events/{}?include=entries,weather",
+            event.id
+        ))
         .header(header::CONTENT_TYPE, "application/json")
         .body(Body::empty())
         .unwrap();
@@ -381,7 +399,7 @@ async fn can_get_event_run_etl_and_see_it_signed() {
 
     let winners = vec![first_place_index, second_place_index, third_place_index];
 
-    let winning_bytes = get_winning_bytes(winners);
+    let winning_bytes = get_winning_bytes(winners, res.outcome_message_version);
     println!("winning_bytes in test: {:?}", winning_bytes);
 
     let attested_outcome =
@@ -409,6 +427,9 @@ fn mock_forecast_data() -> Vec<Forecast> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            dominant_precip: None,
+            bucket: None,
         },
         Forecast {
             station_id: String::from("KSAW"),
@@ -426,6 +447,9 @@ fn mock_forecast_data() -> Vec<Forecast> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            dominant_precip: None,
+            bucket: None,
         },
         Forecast {
             station_id: String::from("PAPG"),
@@ -443,6 +467,9 @@ fn mock_forecast_data() -> Vec<Forecast> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            dominant_precip: None,
+            bucket: None,
         },
         Forecast {
             station_id: String::from("KWMC"),
@@ -460,6 +487,9 @@ fn mock_forecast_data() -> Vec<Forecast> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            dominant_precip: None,
+            bucket: None,
         },
     ]
 }
@@ -479,6 +509,8 @@ fn mock_observation_data() -> Vec<Observation> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            bucket: None,
         },
         Observation {
             station_id: String::from("KSAW"),
@@ -493,6 +525,8 @@ fn mock_observation_data() -> Vec<Observation> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            bucket: None,
         },
         Observation {
             station_id: String::from("PAPG"),
@@ -507,6 +541,8 @@ fn mock_observation_data() -> Vec<Observation> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            bucket: None,
         },
         Observation {
             station_id: String::from("KWMC"),
@@ -521,6 +557,8 @@ fn mock_observation_data() -> Vec<Observation> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            bucket: None,
         },
     ]
 }
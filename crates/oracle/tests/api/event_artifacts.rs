@@ -0,0 +1,170 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use dlctix::{attestation_secret, secp::Point};
+use hyper::{header, Method};
+use nostr_sdk::Keys;
+use oracle::{
+    oracle::get_winning_bytes, CreateEvent, EventAnnouncement, EventAttestation, EventStore,
+    ScoringField,
+};
+use serde_json::from_slice;
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+
+async fn create_event(test_app: &TestApp) -> Uuid {
+    let now = OffsetDateTime::now_utc();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(3),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(3),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    test_app
+        .oracle
+        .create_event(Keys::generate().public_key, new_event, true)
+        .await
+        .unwrap()
+        .id
+}
+
+async fn get_artifact(
+    test_app: &TestApp,
+    uri: String,
+    accept: Option<&str>,
+) -> (StatusCode, Option<String>, Vec<u8>) {
+    let mut request = Request::builder().method(Method::GET).uri(uri);
+    if let Some(accept) = accept {
+        request = request.header(header::ACCEPT, accept);
+    }
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .expect("Failed to execute request.");
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_owned());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, content_type, body.to_vec())
+}
+
+#[tokio::test]
+async fn can_download_event_announcement() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event_id = create_event(&test_app).await;
+    let event = test_app.oracle.get_event(&event_id).await.unwrap();
+    let uri = format!("/oracle/events/{}/announcement", event_id);
+
+    let (status, content_type, body) = get_artifact(&test_app, uri.clone(), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    let announcement: EventAnnouncement = from_slice(&body).unwrap();
+    assert_eq!(announcement.version, 1);
+    assert_eq!(announcement.event_id, event_id);
+    assert_eq!(
+        announcement.oracle_pubkey,
+        hex::encode(Point::from(test_app.oracle.raw_public_key()).serialize())
+    );
+    assert_eq!(
+        announcement.nonce_point,
+        hex::encode(event.nonce.base_point_mul().serialize())
+    );
+    assert_eq!(announcement.expiry, event.event_announcement.expiry);
+    let locking_points = &event.event_announcement.locking_points;
+    assert_eq!(announcement.locking_points.len(), locking_points.len());
+    assert_eq!(
+        announcement.locking_points[0],
+        hex::encode(locking_points[0].serialize())
+    );
+
+    let (status, content_type, body) = get_artifact(&test_app, uri, Some(OCTET_STREAM)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some(OCTET_STREAM));
+    assert_eq!(
+        body.len(),
+        1 + 16 + 33 + 33 + 4 + 4 + 33 * locking_points.len()
+    );
+    assert_eq!(body, announcement.to_bytes().unwrap());
+    assert_eq!(body[0], 1);
+    assert_eq!(&body[1..17], event_id.as_bytes());
+}
+
+#[tokio::test]
+async fn attestation_is_not_found_until_signed() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event_id = create_event(&test_app).await;
+    let uri = format!("/oracle/events/{}/attestation", event_id);
+
+    let (status, _, _) = get_artifact(&test_app, uri.clone(), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = get_artifact(&test_app, uri, Some(OCTET_STREAM)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _, _) = get_artifact(
+        &test_app,
+        format!("/oracle/events/{}/announcement", Uuid::now_v7()),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn can_download_event_attestation() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event_id = create_event(&test_app).await;
+
+    // attest that the second entry won
+    let mut event = test_app
+        .db
+        .get_events_to_sign(vec![event_id], test_app.oracle.now())
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let attestation = attestation_secret(
+        test_app.oracle.raw_private_key(),
+        event.nonce,
+        get_winning_bytes(vec![1], event.outcome_message_version),
+    );
+    event.attestation = Some(attestation);
+    test_app
+        .db
+        .update_event_attestation(&event, test_app.oracle.now())
+        .await
+        .unwrap();
+    let uri = format!("/oracle/events/{}/attestation", event_id);
+
+    let (status, content_type, body) = get_artifact(&test_app, uri.clone(), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    let artifact: EventAttestation = from_slice(&body).unwrap();
+    assert_eq!(artifact.version, 1);
+    assert_eq!(artifact.event_id, event_id);
+    assert_eq!(artifact.attestation, hex::encode(attestation.serialize()));
+    assert_eq!(artifact.outcome_index, Some(1));
+
+    let (status, content_type, body) = get_artifact(&test_app, uri, Some(OCTET_STREAM)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some(OCTET_STREAM));
+    assert_eq!(body, attestation.serialize().to_vec());
+}
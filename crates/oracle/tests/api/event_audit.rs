@@ -0,0 +1,229 @@
+use crate::helpers::{spawn_app, spawn_app_with_clock, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use nostr_sdk::Keys;
+use oracle::{
+    AddEventEntry, CreateEvent, EventAudit, EventStore, FixedClock, Forecast, Observation,
+    PrecipUnit, ScoringField, TemperatureUnit, ValueOptions, WeatherChoices,
+};
+use serde_json::from_slice;
+use std::sync::Arc;
+use time::{format_description::well_known::Rfc3339, macros::datetime, OffsetDateTime};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn new_event() -> CreateEvent {
+    CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: OffsetDateTime::parse("2024-08-12T00:00:00+00:00", &Rfc3339)
+            .unwrap(),
+        end_observation_date: OffsetDateTime::parse("2024-08-13T00:00:00+00:00", &Rfc3339).unwrap(),
+        signing_date: OffsetDateTime::parse("2024-08-13T03:00:00+00:00", &Rfc3339).unwrap(),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: vec![ScoringField::TempHigh, ScoringField::TempLow],
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    }
+}
+
+fn picks(event_id: Uuid, temp_high: ValueOptions, temp_low: ValueOptions) -> AddEventEntry {
+    AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_high: Some(temp_high),
+            temp_low: Some(temp_low),
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    }
+}
+
+/// An app holding a signed event whose entries finish over_and_par (30), both_par (20),
+/// both_under (0)
+async fn signed_event() -> (TestApp, Uuid, Vec<AddEventEntry>) {
+    // entries go in the day before the event starts
+    let clock = Arc::new(FixedClock::new(datetime!(2024-08-11 00:00:00 UTC)));
+    let test_app = spawn_app_with_clock(Arc::new(weather_data()), clock.clone()).await;
+    let keys = Keys::generate();
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event(), true)
+        .await
+        .unwrap();
+    let entries = vec![
+        picks(event.id, ValueOptions::Over, ValueOptions::Par),
+        picks(event.id, ValueOptions::Par, ValueOptions::Par),
+        picks(event.id, ValueOptions::Under, ValueOptions::Under),
+    ];
+    test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, entries.clone())
+        .await
+        .unwrap();
+    clock.set(event.signing_date + time::Duration::hours(1));
+    test_app.oracle.etl_data(1).await.unwrap();
+    (test_app, event.id, entries)
+}
+
+async fn get_audit(test_app: &TestApp, event_id: Uuid) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events/{}/audit", event_id))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
+
+fn weather_data() -> MockWeatherAccess {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .returning(|_, _| Ok(mock_forecast_data()));
+    weather_data
+        .expect_observation_data()
+        .returning(|_, _| Ok(mock_observation_data()));
+    weather_data
+}
+
+#[tokio::test]
+async fn recomputed_scores_match_what_was_signed() {
+    let (test_app, event_id, entries) = signed_event().await;
+
+    let (status, body) = get_audit(&test_app, event_id).await;
+    assert_eq!(status, StatusCode::OK);
+    let audit: EventAudit = from_slice(&body).unwrap();
+    assert_eq!(audit.event_id, event_id);
+    assert!(!audit.discrepancy);
+    assert!(audit.attestation_matches);
+    assert!(audit.entries.iter().all(|entry| entry.matches));
+    let ranking: Vec<(Uuid, i64)> = audit
+        .entries
+        .iter()
+        .map(|entry| (entry.entry_id, entry.recomputed_base_score))
+        .collect();
+    assert_eq!(
+        ranking,
+        vec![(entries[0].id, 30), (entries[1].id, 20), (entries[2].id, 0)]
+    );
+
+    // Auditing writes nothing back
+    let event = test_app.oracle.get_event(&event_id).await.unwrap();
+    let stored = event
+        .entries
+        .iter()
+        .find(|entry| entry.id == entries[0].id)
+        .unwrap();
+    assert_eq!(stored.base_score, Some(30));
+}
+
+#[tokio::test]
+async fn tampered_stored_score_is_flagged() {
+    let (test_app, event_id, entries) = signed_event().await;
+
+    // Last place rewritten to look like it won
+    let last = entries[2].id;
+    test_app
+        .db
+        .update_entry_scores(vec![(last, 999_999, 99)])
+        .await
+        .unwrap();
+
+    let (status, body) = get_audit(&test_app, event_id).await;
+    assert_eq!(status, StatusCode::OK);
+    let audit: EventAudit = from_slice(&body).unwrap();
+    assert!(audit.discrepancy);
+    // The signature was made over the real scores, so it still agrees with the recomputed ones
+    assert!(audit.attestation_matches);
+    let flagged: Vec<Uuid> = audit
+        .entries
+        .iter()
+        .filter(|entry| !entry.matches)
+        .map(|entry| entry.entry_id)
+        .collect();
+    assert_eq!(flagged, vec![last]);
+    let tampered = audit
+        .entries
+        .iter()
+        .find(|entry| entry.entry_id == last)
+        .unwrap();
+    assert_eq!(tampered.stored_base_score, Some(99));
+    assert_eq!(tampered.recomputed_base_score, 0);
+}
+
+#[tokio::test]
+async fn unsigned_event_cannot_be_audited() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event(), true)
+        .await
+        .unwrap();
+
+    let (status, _) = get_audit(&test_app, event.id).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+fn mock_forecast_data() -> Vec<Forecast> {
+    vec![Forecast {
+        station_id: String::from("PFNO"),
+        date: String::from("2024-08-12"),
+        start_time: String::from("2024-08-11T00:00:00+00:00"),
+        end_time: String::from("2024-08-12T00:00:00+00:00"),
+        temp_low: 50,
+        temp_high: 70,
+        wind_speed: Some(8),
+        wind_direction: None,
+        humidity_max: None,
+        humidity_min: None,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        precip_chance: None,
+        rain_amt: None,
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        dominant_precip: None,
+        bucket: None,
+    }]
+}
+
+fn mock_observation_data() -> Vec<Observation> {
+    vec![Observation {
+        station_id: String::from("PFNO"),
+        start_time: String::from("2024-08-12T00:00:00+00:00"),
+        end_time: String::from("2024-08-13T00:00:00+00:00"),
+        temp_low: 50.0,
+        temp_high: 75.0,
+        wind_speed: 11,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        wind_direction: None,
+        humidity: None,
+        rain_amt: None,
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        bucket: None,
+    }]
+}
@@ -0,0 +1,164 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use dlctix::secp::{MaybeScalar, Scalar};
+use hyper::Method;
+use nostr_sdk::Keys;
+use oracle::{CreateEvent, EventChanges, EventStore};
+use serde_json::from_slice;
+use std::sync::Arc;
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+async fn create_event(test_app: &TestApp) -> Uuid {
+    let now = OffsetDateTime::now_utc();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(3),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 2,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    test_app
+        .oracle
+        .create_event(Keys::generate().public_key, new_event, true)
+        .await
+        .unwrap()
+        .id
+}
+
+async fn sign_event(test_app: &TestApp, event_id: Uuid) {
+    let mut event = test_app
+        .db
+        .get_events_to_sign(vec![event_id], test_app.oracle.now())
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    event.attestation = Some(MaybeScalar::Valid(Scalar::one()));
+    test_app
+        .db
+        .update_event_attestation(&event, test_app.oracle.now())
+        .await
+        .unwrap();
+}
+
+/// Lets the clock move past the last write, changes are stamped to the millisecond
+async fn tick() {
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+}
+
+async fn get_changes(test_app: &TestApp, query: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events/changes{}", query))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
+
+async fn changes_since(test_app: &TestApp, since: OffsetDateTime) -> EventChanges {
+    let (status, body) = get_changes(
+        test_app,
+        &format!("?since={}", since.format(&Rfc3339).unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn returns_only_events_changed_since() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let unchanged = create_event(&test_app).await;
+    let signed = create_event(&test_app).await;
+    tick().await;
+
+    let since = OffsetDateTime::now_utc();
+    tick().await;
+    let created = create_event(&test_app).await;
+    sign_event(&test_app, signed).await;
+    let deleted = create_event(&test_app).await;
+    assert!(test_app
+        .db
+        .delete_event(&deleted, test_app.oracle.now())
+        .await
+        .unwrap());
+
+    let changes = changes_since(&test_app, since).await;
+    let mut changed: Vec<Uuid> = changes.events.iter().map(|event| event.id).collect();
+    changed.sort();
+    let mut expected = vec![created, signed];
+    expected.sort();
+    assert_eq!(changed, expected);
+    assert!(!changed.contains(&unchanged));
+    let signed_summary = changes.events.iter().find(|e| e.id == signed).unwrap();
+    assert!(signed_summary.attestation.is_some());
+    assert_eq!(changes.deleted, vec![deleted]);
+    assert!(changes.until >= since);
+
+    // Polling again from the returned `until` picks up nothing new
+    tick().await;
+    let changes = changes_since(&test_app, changes.until).await;
+    assert!(changes.events.is_empty());
+    assert!(changes.deleted.is_empty());
+}
+
+#[tokio::test]
+async fn deleted_events_that_came_back_are_not_reported_deleted() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let since = OffsetDateTime::now_utc();
+    tick().await;
+    let event_id = create_event(&test_app).await;
+    let event = test_app
+        .db
+        .get_event(&event_id, test_app.oracle.now())
+        .await
+        .unwrap();
+    assert!(test_app
+        .db
+        .delete_event(&event_id, test_app.oracle.now())
+        .await
+        .unwrap());
+
+    let changes = changes_since(&test_app, since).await;
+    assert!(changes.events.is_empty());
+    assert_eq!(changes.deleted, vec![event_id]);
+
+    test_app
+        .db
+        .import_event(event, test_app.oracle.now())
+        .await
+        .unwrap();
+    let changes = changes_since(&test_app, since).await;
+    assert_eq!(changes.events.len(), 1);
+    assert!(changes.deleted.is_empty());
+}
+
+#[tokio::test]
+async fn rejects_missing_or_malformed_since() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let (status, _) = get_changes(&test_app, "").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_changes(&test_app, "?since=yesterday").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,137 @@
+use crate::helpers::{spawn_app_with_clock, MockWeatherAccess, TestApp};
+use nostr_sdk::Keys;
+use oracle::{
+    AddEventEntry, CreateEvent, Event, EventStore, FixedClock, Forecasted, TemperatureUnit,
+    Weather, WeatherChoices,
+};
+use std::sync::Arc;
+use time::{macros::datetime, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+const NOW: OffsetDateTime = datetime!(2024-08-12 00:00:00 UTC);
+
+/// Winds the clock back to just before `signing_date` so the entry is added before the cutoff
+async fn create_event_with_children(
+    test_app: &TestApp,
+    clock: &FixedClock,
+    signing_date: OffsetDateTime,
+) -> Event {
+    clock.set(signing_date - Duration::hours(1));
+    let keys = Keys::generate();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: signing_date,
+        end_observation_date: signing_date,
+        signing_date,
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 1,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event, true)
+        .await
+        .unwrap();
+    let entry = AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id: event.id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_low: Some(oracle::ValueOptions::Par),
+            temp_high: None,
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    };
+    test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, vec![entry])
+        .await
+        .unwrap();
+    let weather = Weather {
+        station_id: String::from("PFNO"),
+        observed: None,
+        forecasted: Forecasted {
+            date: signing_date,
+            temp_low: 10,
+            temp_high: 20,
+            wind_speed: Some(5),
+        },
+    };
+    test_app
+        .db
+        .update_weather_station_data(event.id, vec![weather], &TemperatureUnit::Fahrenheit)
+        .await
+        .unwrap();
+    event
+}
+
+async fn count_rows(test_app: &TestApp, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+        .fetch_one(test_app.db.read_pool())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn deleting_event_cascades_to_children() {
+    let clock = Arc::new(FixedClock::new(NOW));
+    let test_app = spawn_app_with_clock(Arc::new(MockWeatherAccess::new()), clock.clone()).await;
+    let event = create_event_with_children(&test_app, &clock, NOW).await;
+    assert_eq!(count_rows(&test_app, "events_entries").await, 1);
+    assert_eq!(count_rows(&test_app, "expected_observations").await, 1);
+    assert_eq!(count_rows(&test_app, "events_weather").await, 1);
+
+    assert!(test_app
+        .db
+        .delete_event(&event.id, test_app.oracle.now())
+        .await
+        .unwrap());
+
+    assert_eq!(count_rows(&test_app, "events").await, 0);
+    assert_eq!(count_rows(&test_app, "events_entries").await, 0);
+    assert_eq!(count_rows(&test_app, "expected_observations").await, 0);
+    assert_eq!(count_rows(&test_app, "events_weather").await, 0);
+    assert_eq!(count_rows(&test_app, "weather").await, 0);
+    assert!(!test_app
+        .db
+        .delete_event(&event.id, test_app.oracle.now())
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn retention_purges_only_old_events() {
+    let clock = Arc::new(FixedClock::new(NOW));
+    let test_app = spawn_app_with_clock(Arc::new(MockWeatherAccess::new()), clock.clone()).await;
+    let old_event = create_event_with_children(&test_app, &clock, NOW - Duration::days(90)).await;
+    let recent_event = create_event_with_children(&test_app, &clock, NOW).await;
+    clock.set(NOW);
+
+    let purged = test_app
+        .oracle
+        .purge_expired_events(Duration::days(30))
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+
+    assert!(test_app.oracle.get_event(&old_event.id).await.is_err());
+    let remaining = test_app.oracle.get_event(&recent_event.id).await.unwrap();
+    assert_eq!(remaining.entries.len(), 1);
+    assert_eq!(remaining.entries[0].expected_observations.len(), 1);
+    assert_eq!(remaining.weather.len(), 1);
+    assert_eq!(count_rows(&test_app, "events_entries").await, 1);
+    assert_eq!(count_rows(&test_app, "expected_observations").await, 1);
+    assert_eq!(count_rows(&test_app, "events_weather").await, 1);
+    assert_eq!(count_rows(&test_app, "weather").await, 1);
+}
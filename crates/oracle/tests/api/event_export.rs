@@ -0,0 +1,231 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp};
+use nostr_sdk::Keys;
+use oracle::{
+    AddEventEntry, CreateEvent, Event, EventStore, Forecasted, ImportReport, Observed,
+    TemperatureUnit, Weather, WeatherChoices,
+};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+async fn create_complete_event(test_app: &TestApp) -> Event {
+    let keys = Keys::generate();
+    let start = OffsetDateTime::from_unix_timestamp(1_723_420_800).unwrap();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: start,
+        end_observation_date: start + Duration::days(1),
+        signing_date: start + Duration::days(1) + Duration::hours(3),
+        locations: vec![String::from("PFNO"), String::from("KSAW")],
+        total_allowed_entries: 2,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event, true)
+        .await
+        .unwrap();
+
+    let entry = AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id: event.id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_low: Some(oracle::ValueOptions::Par),
+            temp_high: Some(oracle::ValueOptions::Over),
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    };
+    let entry_id = entry.id;
+    test_app.db.add_entry(entry.into()).await.unwrap();
+    test_app
+        .db
+        .update_entry_scores(vec![(entry_id, 7, 3)])
+        .await
+        .unwrap();
+
+    let weather = Weather {
+        station_id: String::from("PFNO"),
+        observed: Some(Observed {
+            date: start,
+            temp_low: 12,
+            temp_high: 31,
+            wind_speed: 9,
+        }),
+        forecasted: Forecasted {
+            date: start,
+            temp_low: 10,
+            temp_high: 30,
+            wind_speed: Some(5),
+        },
+    };
+    test_app
+        .db
+        .update_weather_station_data(event.id, vec![weather], &TemperatureUnit::Fahrenheit)
+        .await
+        .unwrap();
+
+    let mut sign_event = test_app
+        .db
+        .get_events_to_sign(vec![event.id], test_app.oracle.now())
+        .await
+        .unwrap()
+        .remove(0);
+    sign_event.attestation = Some(sign_event.nonce.into());
+    test_app
+        .db
+        .update_event_attestation(&sign_event, test_app.oracle.now())
+        .await
+        .unwrap();
+
+    test_app
+        .db
+        .get_event(&event.id, test_app.oracle.now())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn export_then_import_round_trips_events() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let first = create_complete_event(&test_app).await;
+    let second = create_complete_event(&test_app).await;
+    assert!(first.attestation.is_some());
+    assert_eq!(first.entries[0].score, Some(7));
+
+    let mut dump = Vec::new();
+    let exported = test_app
+        .db
+        .export_events(&mut dump, test_app.oracle.now())
+        .await
+        .unwrap();
+    assert_eq!(exported, 2);
+    assert_eq!(String::from_utf8_lossy(&dump).lines().count(), 2);
+
+    assert!(test_app
+        .db
+        .delete_event(&first.id, test_app.oracle.now())
+        .await
+        .unwrap());
+    assert!(test_app
+        .db
+        .delete_event(&second.id, test_app.oracle.now())
+        .await
+        .unwrap());
+
+    let report = test_app
+        .db
+        .import_events(&mut dump.as_slice(), test_app.oracle.now())
+        .await
+        .unwrap();
+    assert_eq!(
+        report,
+        ImportReport {
+            imported: 2,
+            skipped: 0
+        }
+    );
+    assert_eq!(
+        test_app
+            .db
+            .get_event(&first.id, test_app.oracle.now())
+            .await
+            .unwrap(),
+        first
+    );
+    assert_eq!(
+        test_app
+            .db
+            .get_event(&second.id, test_app.oracle.now())
+            .await
+            .unwrap(),
+        second
+    );
+}
+
+#[tokio::test]
+async fn import_skips_existing_events() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_complete_event(&test_app).await;
+
+    let mut dump = Vec::new();
+    test_app
+        .db
+        .export_events(&mut dump, test_app.oracle.now())
+        .await
+        .unwrap();
+
+    let report = test_app
+        .db
+        .import_events(&mut dump.as_slice(), test_app.oracle.now())
+        .await
+        .unwrap();
+    assert_eq!(
+        report,
+        ImportReport {
+            imported: 0,
+            skipped: 1
+        }
+    );
+    let stored = test_app
+        .db
+        .get_event(&event.id, test_app.oracle.now())
+        .await
+        .unwrap();
+    assert_eq!(stored, event);
+    assert_eq!(stored.weather.len(), 1);
+}
+
+#[tokio::test]
+async fn import_keeps_the_exported_timestamps() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_complete_event(&test_app).await;
+    assert!(event.created_at.is_some());
+    assert!(event.last_modified.is_some());
+    let later = test_app.oracle.now() + Duration::days(1);
+
+    let mut dump = Vec::new();
+    test_app.db.export_events(&mut dump, later).await.unwrap();
+    assert!(test_app.db.delete_event(&event.id, later).await.unwrap());
+    test_app
+        .db
+        .import_events(&mut dump.as_slice(), later)
+        .await
+        .unwrap();
+
+    let stored = test_app.db.get_event(&event.id, later).await.unwrap();
+    assert_eq!(stored.created_at, event.created_at);
+    assert_eq!(stored.last_modified, event.last_modified);
+}
+
+#[tokio::test]
+async fn import_stamps_events_exported_without_timestamps() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let mut event = create_complete_event(&test_app).await;
+    assert!(test_app
+        .db
+        .delete_event(&event.id, test_app.oracle.now())
+        .await
+        .unwrap());
+
+    // as written before the export carried them
+    event.created_at = None;
+    event.last_modified = None;
+    let now = OffsetDateTime::from_unix_timestamp(1_900_000_000).unwrap();
+    test_app.db.import_event(event.clone(), now).await.unwrap();
+
+    let stored = test_app.db.get_event(&event.id, now).await.unwrap();
+    assert_eq!(stored.created_at, Some(now));
+    assert_eq!(stored.last_modified, Some(now));
+}
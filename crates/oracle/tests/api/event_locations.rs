@@ -0,0 +1,97 @@
+use crate::helpers::{random_test_number, spawn_app, MockWeatherAccess};
+use nostr_sdk::Keys;
+use oracle::{
+    create_folder,
+    oracle::{Error, Oracle, DEFAULT_MAX_EVENT_LOCATIONS},
+    CreateEvent, Database, ScoringField,
+};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+async fn limited_oracle(max_event_locations: usize) -> Oracle {
+    create_folder("./test_data");
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+    let db = Arc::new(Database::new(&event_data).await.unwrap());
+    Oracle::new(
+        db,
+        Arc::new(MockWeatherAccess::new()),
+        &String::from("./oracle_private_key.pem"),
+    )
+    .await
+    .unwrap()
+    .with_max_event_locations(max_event_locations)
+}
+
+fn event_with_locations(count: usize) -> CreateEvent {
+    let start_observation_date = OffsetDateTime::now_utc() + Duration::days(1);
+    CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date,
+        end_observation_date: start_observation_date + Duration::days(1),
+        signing_date: start_observation_date + Duration::days(2),
+        locations: (0..count).map(|i| format!("K{:03}", i)).collect(),
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(1),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn accepts_events_at_the_location_limit() {
+    let keys = Keys::generate();
+    let oracle = limited_oracle(3).await;
+
+    let event = oracle
+        .create_event(keys.public_key, event_with_locations(3), true)
+        .await
+        .unwrap();
+    assert_eq!(event.locations.len(), 3);
+}
+
+#[tokio::test]
+async fn rejects_events_over_the_location_limit() {
+    let keys = Keys::generate();
+    let oracle = limited_oracle(3).await;
+
+    let err = oracle
+        .create_event(keys.public_key, event_with_locations(4), true)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::BadEvent(_)), "{}", err);
+    assert!(err
+        .to_string()
+        .contains("Max number of locations in an event is 3, requested: 4"));
+}
+
+#[tokio::test]
+async fn default_location_limit_applies() {
+    let keys = Keys::generate();
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    test_app
+        .oracle
+        .create_event(
+            keys.public_key,
+            event_with_locations(DEFAULT_MAX_EVENT_LOCATIONS),
+            true,
+        )
+        .await
+        .unwrap();
+    let err = test_app
+        .oracle
+        .create_event(
+            keys.public_key,
+            event_with_locations(DEFAULT_MAX_EVENT_LOCATIONS + 1),
+            true,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::BadEvent(_)), "{}", err);
+}
@@ -0,0 +1,219 @@
+use crate::helpers::{create_auth_event, spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hyper::{header, Method};
+use nostr_sdk::{
+    hashes::{sha256::Hash as Sha256Hash, Hash},
+    Keys,
+};
+use oracle::{oracle::EXAMPLE_LOCATIONS, CreateEvent, Event, Station};
+use serde_json::{from_slice, Value};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tower::ServiceExt;
+
+fn known_stations(station_ids: &[&str], calls: usize) -> MockWeatherAccess {
+    let station_ids: Vec<String> = station_ids.iter().map(|id| id.to_string()).collect();
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_stations()
+        .times(calls)
+        .returning(move || {
+            Ok(station_ids
+                .iter()
+                .map(|station_id| Station {
+                    station_id: station_id.clone(),
+                    station_name: format!("{} station", station_id),
+                    state: String::from("AK"),
+                    iata_id: station_id.clone(),
+                    elevation_m: None,
+                    latitude: 0.0,
+                    longitude: 0.0,
+                })
+                .collect())
+        });
+    weather_data
+}
+
+/// Checks `value` against the parts of JSON schema the derived schemas use
+fn conforms(value: &Value, schema: &Value, root: &Value, path: &str) -> Result<(), String> {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference
+            .strip_prefix("#/components/schemas/")
+            .ok_or_else(|| format!("{}: unexpected ref {}", path, reference))?;
+        let target = &root["components"]["schemas"][name];
+        if target.is_null() {
+            return Err(format!("{}: unresolved ref {}", path, reference));
+        }
+        return conforms(value, target, root, path);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(options) = schema[key].as_array() {
+            return match options
+                .iter()
+                .any(|option| conforms(value, option, root, path).is_ok())
+            {
+                true => Ok(()),
+                false => Err(format!("{}: matches none of {}", path, key)),
+            };
+        }
+    }
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            return Err(format!("{}: {} not in {:?}", path, value, allowed));
+        }
+    }
+
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(kind) => vec![kind.as_str()],
+        Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    let type_matches = |kind: &str| match kind {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => false,
+    };
+    if !types.is_empty() && !types.iter().any(|kind| type_matches(kind)) {
+        return Err(format!("{}: {} is not {:?}", path, value, types));
+    }
+
+    if let Some(object) = value.as_object() {
+        for required in schema["required"].as_array().into_iter().flatten() {
+            let required = required.as_str().unwrap();
+            if !object.contains_key(required) {
+                return Err(format!("{}: missing required {}", path, required));
+            }
+        }
+        for (key, field) in object {
+            let field_path = format!("{}.{}", path, key);
+            match schema["properties"].get(key) {
+                Some(property) => conforms(field, property, root, &field_path)?,
+                None if schema["additionalProperties"].is_object() => {
+                    conforms(field, &schema["additionalProperties"], root, &field_path)?
+                }
+                None if schema["properties"].is_object() => {
+                    return Err(format!("{}: unknown property", field_path))
+                }
+                None => {}
+            }
+        }
+    }
+    if let Some(items) = value.as_array() {
+        for (index, item) in items.iter().enumerate() {
+            conforms(
+                item,
+                &schema["items"],
+                root,
+                &format!("{}[{}]", path, index),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+async fn get_schema(test_app: &crate::helpers::TestApp) -> Value {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/oracle/events/schema")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn example_matches_schema_and_is_accepted() {
+    let test_app = spawn_app(Arc::new(known_stations(
+        &["PFNO", "KSAW", "PAPG", "KWMC"],
+        2,
+    )))
+    .await;
+    let res = get_schema(&test_app).await;
+    let schema = &res["schema"];
+    let example = &res["example"];
+
+    assert!(schema["components"]["schemas"]["ScoringField"].is_object());
+    conforms(example, schema, schema, "example").unwrap();
+
+    let event: CreateEvent = serde_json::from_value(example.clone()).unwrap();
+    assert_eq!(event.id.get_version_num(), 7);
+    assert_eq!(event.locations, vec!["KSAW", "KWMC", "PAPG"]);
+    assert_eq!(
+        event.number_of_values_per_entry,
+        Some(event.locations.len() * event.scoring_fields.len())
+    );
+    assert!(event.start_observation_date > OffsetDateTime::now_utc());
+    assert!(event.start_observation_date < event.end_observation_date);
+    assert!(event.end_observation_date <= event.signing_date);
+
+    // Posted exactly as served, the oracle takes it
+    let base_url = "http://localhost:3000";
+    let path = "/oracle/events";
+    let keys = Keys::generate();
+    let body_json = example.to_string();
+    let auth_event = create_auth_event(
+        "POST",
+        &format!("{}{}", base_url, path),
+        Some(Sha256Hash::hash(body_json.as_bytes())),
+        &keys,
+    )
+    .await;
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(
+            header::AUTHORIZATION,
+            format!(
+                "Nostr {}",
+                BASE64.encode(serde_json::to_string(&auth_event).unwrap())
+            ),
+        )
+        .header("host", "localhost:3000")
+        .body(Body::from(body_json))
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let created: Event = from_slice(&body).unwrap();
+    assert_eq!(created.id, event.id);
+}
+
+#[tokio::test]
+async fn each_request_gets_a_fresh_example() {
+    let test_app = spawn_app(Arc::new(known_stations(&[], 2))).await;
+    let first = get_schema(&test_app).await;
+    let second = get_schema(&test_app).await;
+
+    assert_ne!(first["example"]["id"], second["example"]["id"]);
+    assert_eq!(
+        first["example"]["locations"],
+        serde_json::json!(EXAMPLE_LOCATIONS)
+    );
+}
+
+#[tokio::test]
+async fn schema_rejects_a_malformed_body() {
+    let test_app = spawn_app(Arc::new(known_stations(&["PFNO"], 1))).await;
+    let res = get_schema(&test_app).await;
+    let schema = &res["schema"];
+
+    let mut missing_id = res["example"].clone();
+    missing_id.as_object_mut().unwrap().remove("id");
+    assert!(conforms(&missing_id, schema, schema, "example").is_err());
+
+    let mut wrong_type = res["example"].clone();
+    wrong_type["total_allowed_entries"] = Value::from("ten");
+    assert!(conforms(&wrong_type, schema, schema, "example").is_err());
+}
@@ -0,0 +1,223 @@
+use crate::helpers::{spawn_app, spawn_app_with_clock, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use dlctix::secp::{MaybeScalar, Scalar};
+use hyper::Method;
+use nostr_sdk::Keys;
+use oracle::{AddEventEntry, CreateEvent, EventAggregates, EventStore, FixedClock, WeatherChoices};
+use serde_json::from_slice;
+use std::sync::Arc;
+use time::{macros::datetime, Duration, OffsetDateTime};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+const NOW: OffsetDateTime = datetime!(2024-08-12 00:00:00 UTC);
+
+/// Entries are added with the clock just before the start, it is left there for the next event
+async fn create_event(
+    test_app: &TestApp,
+    clock: &FixedClock,
+    start_observation_date: OffsetDateTime,
+    end_observation_date: OffsetDateTime,
+    signing_date: OffsetDateTime,
+    total_entries: usize,
+) -> Uuid {
+    clock.set(start_observation_date - Duration::hours(1));
+    let keys = Keys::generate();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date,
+        end_observation_date,
+        signing_date,
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: total_entries.max(1),
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event, true)
+        .await
+        .unwrap();
+    if total_entries > 0 {
+        let entries = (0..total_entries)
+            .map(|_| AddEventEntry {
+                id: Uuid::now_v7(),
+                event_id: event.id,
+                expected_observations: vec![WeatherChoices {
+                    stations: String::from("PFNO"),
+                    temp_low: Some(oracle::ValueOptions::Par),
+                    temp_high: None,
+                    wind_speed: None,
+                    wind_direction: None,
+                    rain_amt: None,
+                    snow_amt: None,
+                    humidity: None,
+                    predictions: Default::default(),
+                }],
+            })
+            .collect();
+        test_app
+            .oracle
+            .add_event_entries(keys.public_key, event.id, entries)
+            .await
+            .unwrap();
+    }
+    event.id
+}
+
+async fn sign_event(test_app: &TestApp, event_id: Uuid) {
+    let mut events = test_app
+        .db
+        .get_events_to_sign(vec![event_id], test_app.oracle.now())
+        .await
+        .unwrap();
+    let mut event = events.pop().unwrap();
+    event.attestation = Some(MaybeScalar::Valid(Scalar::one()));
+    test_app
+        .db
+        .update_event_attestation(&event, test_app.oracle.now())
+        .await
+        .unwrap();
+}
+
+async fn get_stats(test_app: &TestApp, query: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events/stats{}", query))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn event_stats_count_each_status() {
+    let clock = Arc::new(FixedClock::new(NOW));
+    let test_app = spawn_app_with_clock(Arc::new(MockWeatherAccess::new()), clock.clone()).await;
+    let now = NOW;
+
+    // live, one with entries and one without
+    create_event(
+        &test_app,
+        &clock,
+        now + Duration::days(1),
+        now + Duration::days(2),
+        now + Duration::days(3),
+        2,
+    )
+    .await;
+    create_event(
+        &test_app,
+        &clock,
+        now + Duration::days(5),
+        now + Duration::days(6),
+        now + Duration::days(7),
+        0,
+    )
+    .await;
+    // running
+    create_event(
+        &test_app,
+        &clock,
+        now - Duration::days(1),
+        now + Duration::days(1),
+        now + Duration::days(2),
+        1,
+    )
+    .await;
+    // completed, waiting to be signed
+    create_event(
+        &test_app,
+        &clock,
+        now - Duration::days(3),
+        now - Duration::days(2),
+        now - Duration::days(1),
+        3,
+    )
+    .await;
+    // signed inside and outside the default 7 day window
+    let recent = create_event(
+        &test_app,
+        &clock,
+        now - Duration::days(4),
+        now - Duration::days(3),
+        now - Duration::days(2),
+        0,
+    )
+    .await;
+    sign_event(&test_app, recent).await;
+    let old = create_event(
+        &test_app,
+        &clock,
+        now - Duration::days(31),
+        now - Duration::days(30),
+        now - Duration::days(29),
+        1,
+    )
+    .await;
+    sign_event(&test_app, old).await;
+    clock.set(now);
+
+    let (status, body) = get_stats(&test_app, "").await;
+    assert_eq!(status, StatusCode::OK);
+    let stats: EventAggregates = from_slice(&body).unwrap();
+    assert_eq!(
+        stats,
+        EventAggregates {
+            total_events: 6,
+            live: 2,
+            running: 1,
+            completed: 1,
+            signed: 2,
+            total_entries: 7,
+            signed_recently: 1,
+            window_days: 7,
+        }
+    );
+
+    let (status, body) = get_stats(&test_app, "?window_days=60").await;
+    assert_eq!(status, StatusCode::OK);
+    let stats: EventAggregates = from_slice(&body).unwrap();
+    assert_eq!(stats.signed_recently, 2);
+    assert_eq!(stats.window_days, 60);
+    assert_eq!(stats.signed, 2);
+}
+
+#[tokio::test]
+async fn event_stats_are_zero_without_events() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    let (status, body) = get_stats(&test_app, "?window_days=1").await;
+    assert_eq!(status, StatusCode::OK);
+    let stats: EventAggregates = from_slice(&body).unwrap();
+    assert_eq!(
+        stats,
+        EventAggregates {
+            window_days: 1,
+            ..EventAggregates::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn event_stats_reject_window_past_max() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    let (status, _) = get_stats(&test_app, "?window_days=3651").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
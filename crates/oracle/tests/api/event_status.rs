@@ -0,0 +1,168 @@
+use crate::helpers::{random_test_number, MockWeatherAccess};
+use dlctix::secp::{MaybeScalar, Scalar};
+use nostr_sdk::Keys;
+use oracle::{
+    create_folder, get_status, new_uuid_v7,
+    oracle::{Error, NonceDerivation, Oracle},
+    AddEventEntry, Clock, CreateEvent, Database, EventStatus, FixedClock, ScoringField, SeededRng,
+    ValueOptions, WeatherChoices,
+};
+use std::sync::Arc;
+use time::{macros::datetime, Duration, OffsetDateTime};
+use uuid::Uuid;
+
+const START: OffsetDateTime = datetime!(2024-08-12 00:00:00 UTC);
+const END: OffsetDateTime = datetime!(2024-08-13 00:00:00 UTC);
+
+async fn oracle_at(clock: Arc<FixedClock>, seed: u64) -> Oracle {
+    create_folder("./test_data");
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+    let db = Arc::new(Database::new(&event_data).await.unwrap());
+    Oracle::new(
+        db,
+        Arc::new(MockWeatherAccess::new()),
+        &String::from("./oracle_private_key.pem"),
+    )
+    .await
+    .unwrap()
+    .with_clock(clock)
+    .with_rng(Arc::new(SeededRng::new(seed)))
+}
+
+fn new_event(id: Uuid) -> CreateEvent {
+    CreateEvent {
+        id,
+        start_observation_date: START,
+        end_observation_date: END,
+        signing_date: END + Duration::days(1),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 1,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    }
+}
+
+fn new_entry(event_id: Uuid) -> AddEventEntry {
+    AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_low: Some(ValueOptions::Par),
+            temp_high: None,
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    }
+}
+
+#[test]
+fn status_changes_at_observation_boundaries() {
+    let clock = FixedClock::new(START - Duration::seconds(1));
+    let status = |attestation| get_status(attestation, START, END, clock.now());
+
+    assert_eq!(status(None), EventStatus::Live);
+    clock.set(START);
+    assert_eq!(status(None), EventStatus::Running);
+    clock.set(END - Duration::nanoseconds(1));
+    assert_eq!(status(None), EventStatus::Running);
+    clock.advance(Duration::nanoseconds(1));
+    assert_eq!(status(None), EventStatus::Completed);
+    // attested events stay signed whatever the time
+    clock.set(START - Duration::days(1));
+    assert_eq!(
+        status(Some(MaybeScalar::Valid(Scalar::one()))),
+        EventStatus::Signed
+    );
+}
+
+#[tokio::test]
+async fn oracle_reports_status_from_its_clock() {
+    let clock = Arc::new(FixedClock::new(START - Duration::hours(1)));
+    let oracle = oracle_at(clock.clone(), 7).await;
+    let keys = Keys::generate();
+    let event = oracle
+        .create_event(keys.public_key, new_event(Uuid::now_v7()), true)
+        .await
+        .unwrap();
+    let late_event = oracle
+        .create_event(keys.public_key, new_event(Uuid::now_v7()), true)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        oracle.get_event(&event.id).await.unwrap().status,
+        EventStatus::Live
+    );
+    // entries close at the start of the observation window by the oracle's clock
+    oracle
+        .add_event_entries(keys.public_key, event.id, vec![new_entry(event.id)])
+        .await
+        .unwrap();
+
+    clock.set(START);
+    assert_eq!(
+        oracle.get_event(&event.id).await.unwrap().status,
+        EventStatus::Running
+    );
+    let result = oracle
+        .add_event_entries(
+            keys.public_key,
+            late_event.id,
+            vec![new_entry(late_event.id)],
+        )
+        .await;
+    assert!(
+        matches!(&result, Err(Error::BadEntry(message)) if message.contains("closed")),
+        "{:?}",
+        result
+    );
+
+    clock.set(END);
+    assert_eq!(
+        oracle.get_event(&event.id).await.unwrap().status,
+        EventStatus::Completed
+    );
+    let running = oracle.get_running_events().await.unwrap();
+    let active = running.iter().find(|active| active.id == event.id).unwrap();
+    assert_eq!(active.status, EventStatus::Completed);
+}
+
+#[tokio::test]
+async fn seeded_oracles_draw_the_same_nonces_and_ids() {
+    let keys = Keys::generate();
+    let event_id = Uuid::now_v7();
+    let first = oracle_at(Arc::new(FixedClock::new(START)), 42).await;
+    let second = oracle_at(Arc::new(FixedClock::new(START)), 42).await;
+    assert_eq!(NonceDerivation::default(), NonceDerivation::Random);
+
+    let announced = first
+        .create_event(keys.public_key, new_event(event_id), true)
+        .await
+        .unwrap();
+    let rebuilt = second
+        .create_event(keys.public_key, new_event(event_id), true)
+        .await
+        .unwrap();
+    assert_eq!(announced.nonce, rebuilt.nonce);
+
+    let clock = FixedClock::new(START);
+    let id = new_uuid_v7(&clock, &SeededRng::new(42));
+    assert_eq!(id, new_uuid_v7(&clock, &SeededRng::new(42)));
+    assert_ne!(id, new_uuid_v7(&clock, &SeededRng::new(43)));
+    assert_eq!(id.get_version_num(), 7);
+    assert_eq!(
+        id.get_timestamp().unwrap().to_unix().0,
+        START.unix_timestamp() as u64
+    );
+}
@@ -0,0 +1,95 @@
+use crate::helpers::{random_test_number, spawn_app_with_file_access, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::{
+    header::{ACCEPT_RANGES, CONTENT_RANGE, RANGE},
+    HeaderMap, Method,
+};
+use oracle::{create_folder, FileAccess};
+use std::{fs, sync::Arc};
+use tower::ServiceExt;
+
+const FILE_NAME: &str = "observations_2024-08-12T00:00:00Z.parquet";
+const CONTENTS: &[u8] = b"0123456789abcdefghij";
+
+async fn spawn_with_file() -> TestApp {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    let day_dir = format!("{}/2024-08-12", data_dir);
+    create_folder(&day_dir);
+    fs::write(format!("{}/{}", day_dir, FILE_NAME), CONTENTS).unwrap();
+    spawn_app_with_file_access(
+        Arc::new(MockWeatherAccess::new()),
+        Arc::new(FileAccess::new(data_dir)),
+    )
+    .await
+}
+
+async fn download(test_app: &TestApp, range: Option<&str>) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let mut request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/file/{}", FILE_NAME));
+    if let Some(range) = range {
+        request = request.header(RANGE, range);
+    }
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, headers, body.to_vec())
+}
+
+#[tokio::test]
+async fn can_download_full_file() {
+    let test_app = spawn_with_file().await;
+
+    let (status, headers, body) = download(&test_app, None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[ACCEPT_RANGES], "bytes");
+    assert!(headers.get(CONTENT_RANGE).is_none());
+    assert_eq!(body, CONTENTS);
+}
+
+#[tokio::test]
+async fn can_download_partial_ranges() {
+    let test_app = spawn_with_file().await;
+
+    let (status, headers, body) = download(&test_app, Some("bytes=5-9")).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(headers[CONTENT_RANGE], "bytes 5-9/20");
+    assert_eq!(body, b"56789");
+
+    // Resuming an interrupted transfer
+    let (status, headers, body) = download(&test_app, Some("bytes=15-")).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(headers[CONTENT_RANGE], "bytes 15-19/20");
+    assert_eq!(body, b"fghij");
+
+    let (status, headers, body) = download(&test_app, Some("bytes=-3")).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(headers[CONTENT_RANGE], "bytes 17-19/20");
+    assert_eq!(body, b"hij");
+
+    // An end past the file is clamped to the last byte
+    let (_, headers, body) = download(&test_app, Some("bytes=18-100")).await;
+    assert_eq!(headers[CONTENT_RANGE], "bytes 18-19/20");
+    assert_eq!(body, b"ij");
+}
+
+#[tokio::test]
+async fn rejects_range_past_end_of_file() {
+    let test_app = spawn_with_file().await;
+
+    let (status, headers, body) = download(&test_app, Some("bytes=20-30")).await;
+
+    assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(headers[CONTENT_RANGE], "bytes */20");
+    assert!(body.is_empty());
+}
@@ -0,0 +1,112 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp, WeatherFixture};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use oracle::Forecast;
+use serde_json::from_slice;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// One day of four 6 hour forecast windows for PFNO:
+///   min_temp   50, 54, 60, 70
+///   max_temp   60, 66, 72, 90
+///   wind_speed  4,  8, 10, 20
+async fn spawn_with_fixture() -> TestApp {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T00:00:00Z', '2024-08-12T06:00:00Z', 50, 60, 4),
+                ('PFNO', '2024-08-12T06:00:00Z', '2024-08-12T12:00:00Z', 54, 66, 8),
+                ('PFNO', '2024-08-12T12:00:00Z', '2024-08-12T18:00:00Z', 60, 72, 10),
+                ('PFNO', '2024-08-12T18:00:00Z', '2024-08-13T00:00:00Z', 70, 90, 20)
+            ) AS t(station_id, begin_time, end_time, min_temp, max_temp, wind_speed)
+            CROSS JOIN (SELECT 'fahrenheit' AS temperature_unit_code, '2024-08-11T12:00:00Z' AS generated_at)
+        ) TO '{}/forecasts_2024-08-12T00:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+        day_dir
+    ));
+
+    spawn_app(Arc::new(fixture.weather_access())).await
+}
+
+async fn get_forecasts(test_app: &TestApp, agg: Option<&str>) -> (StatusCode, Vec<Forecast>) {
+    let uri = match agg {
+        Some(agg) => format!("/stations/forecasts?station_ids=PFNO&agg={}", agg),
+        None => String::from("/stations/forecasts?station_ids=PFNO"),
+    };
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    if status != StatusCode::OK {
+        return (status, vec![]);
+    }
+    (status, from_slice(&body).unwrap())
+}
+
+fn daily_values(forecasts: &[Forecast]) -> (i64, i64, Option<i64>) {
+    assert_eq!(forecasts.len(), 1);
+    (
+        forecasts[0].temp_low,
+        forecasts[0].temp_high,
+        forecasts[0].wind_speed,
+    )
+}
+
+#[tokio::test]
+async fn forecasts_default_to_daily_min_max() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, forecasts) = get_forecasts(&test_app, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(daily_values(&forecasts), (50, 90, Some(20)));
+
+    let (_, forecasts) = get_forecasts(&test_app, Some("minmax")).await;
+    assert_eq!(daily_values(&forecasts), (50, 90, Some(20)));
+}
+
+#[tokio::test]
+async fn forecasts_median_interpolates_between_middle_windows() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, forecasts) = get_forecasts(&test_app, Some("median")).await;
+    assert_eq!(status, StatusCode::OK);
+    // (54 + 60) / 2, (66 + 72) / 2, (8 + 10) / 2
+    assert_eq!(daily_values(&forecasts), (57, 69, Some(9)));
+}
+
+#[tokio::test]
+async fn forecasts_percentile_matches_hand_computed_values() {
+    let test_app = spawn_with_fixture().await;
+
+    // p90 sits at position 0.9 * (4 - 1) = 2.7 between the 3rd and 4th values:
+    // 60 + 0.7 * 10 = 67, 72 + 0.7 * 18 = 84.6, 10 + 0.7 * 10 = 17
+    let (status, forecasts) = get_forecasts(&test_app, Some("p90")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(daily_values(&forecasts), (67, 85, Some(17)));
+
+    // p25 at position 0.75: 50 + 0.75 * 4 = 53, 60 + 0.75 * 6 = 64.5, 4 + 0.75 * 4 = 7
+    let (_, forecasts) = get_forecasts(&test_app, Some("p25")).await;
+    assert_eq!(daily_values(&forecasts), (53, 65, Some(7)));
+}
+
+#[tokio::test]
+async fn forecasts_reject_unknown_aggregation() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data.expect_forecasts_data().never();
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    for agg in ["p0", "p100", "mean"] {
+        let (status, _) = get_forecasts(&test_app, Some(agg)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "agg={}", agg);
+    }
+}
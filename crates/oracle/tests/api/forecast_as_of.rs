@@ -0,0 +1,97 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, ForecastAggregation, ForecastFields, ForecastRequest, PrecipUnit,
+    TemperatureUnit, WeatherData,
+};
+use serde_json::json;
+use time::{macros::datetime, OffsetDateTime};
+
+/// PFNO's forecast for the morning of 2024-08-13 as issued twice, 2024-08-11T12:00Z with a
+/// high of 70 and 2024-08-12T12:00Z with a high of 80.
+fn weather_access_with_fixture() -> WeatherAccess {
+    let fixture = WeatherFixture::new();
+    for (generated_at, min_temp, max_temp) in [
+        ("2024-08-11T12:00:00Z", 50, 70),
+        ("2024-08-12T12:00:00Z", 60, 80),
+    ] {
+        let day_dir = fixture.day_dir(&generated_at[..10]);
+        fixture.write(&format!(
+            r#"
+            COPY (
+                SELECT 'PFNO' AS station_id, '2024-08-13T00:00:00Z' AS begin_time,
+                       '2024-08-13T12:00:00Z' AS end_time, {min_temp} AS min_temp,
+                       {max_temp} AS max_temp, 5 AS wind_speed,
+                       'fahrenheit' AS temperature_unit_code, '{generated_at}' AS generated_at
+            ) TO '{day_dir}/forecasts_{generated_at}.parquet' (FORMAT PARQUET);
+            "#,
+        ));
+    }
+
+    fixture.weather_access()
+}
+
+fn forecast_request(as_of: Option<OffsetDateTime>) -> ForecastRequest {
+    ForecastRequest {
+        start: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-14 00:00:00 UTC)),
+        generated_start: None,
+        generated_end: None,
+        as_of,
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        agg: ForecastAggregation::default(),
+        fields: ForecastFields::default(),
+        granularity: None,
+    }
+}
+
+async fn high(weather_access: &WeatherAccess, as_of: Option<OffsetDateTime>) -> Option<i64> {
+    let req = forecast_request(as_of);
+    let forecasts = weather_access
+        .forecasts_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert!(forecasts.len() <= 1);
+    forecasts.first().map(|forecast| forecast.temp_high)
+}
+
+#[tokio::test]
+async fn latest_forecast_by_default() {
+    let weather_access = weather_access_with_fixture();
+    assert_eq!(high(&weather_access, None).await, Some(80));
+}
+
+#[tokio::test]
+async fn as_of_returns_the_forecast_known_then() {
+    let weather_access = weather_access_with_fixture();
+    // Only the first issuance existed yet
+    assert_eq!(
+        high(&weather_access, Some(datetime!(2024-08-12 00:00:00 UTC))).await,
+        Some(70)
+    );
+    // Both existed, the newer one wins as it does without as_of
+    assert_eq!(
+        high(&weather_access, Some(datetime!(2024-08-12 12:00:00 UTC))).await,
+        Some(80)
+    );
+    // Nothing had been issued yet
+    assert_eq!(
+        high(&weather_access, Some(datetime!(2024-08-11 00:00:00 UTC))).await,
+        None
+    );
+}
+
+#[test]
+fn as_of_is_optional() {
+    let req: ForecastRequest = serde_json::from_value(json!({ "station_ids": "PFNO" })).unwrap();
+    assert_eq!(req.as_of, None);
+
+    let req: ForecastRequest = serde_json::from_value(json!({
+        "station_ids": "PFNO",
+        "as_of": "2024-08-12T00:00:00Z"
+    }))
+    .unwrap();
+    assert_eq!(req.as_of, Some(datetime!(2024-08-12 00:00:00 UTC)));
+}
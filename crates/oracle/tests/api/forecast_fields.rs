@@ -0,0 +1,130 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp, WeatherFixture};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use oracle::{weather_data::daily_forecast_select, ForecastFields};
+use serde_json::{from_slice, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn select(fields: &str) -> String {
+    daily_forecast_select(
+        &fields.parse::<ForecastFields>().unwrap(),
+        "MIN(df.start_time)",
+        "MAX(df.end_time)",
+    )
+}
+
+/// One day of forecast windows for PFNO
+async fn spawn_with_fixture() -> TestApp {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T00:00:00Z', '2024-08-12T12:00:00Z', 50, 60, 4, 0.1),
+                ('PFNO', '2024-08-12T12:00:00Z', '2024-08-13T00:00:00Z', 58, 72, 10, 0.2)
+            ) AS t(station_id, begin_time, end_time, min_temp, max_temp, wind_speed, liquid_precipitation_amt)
+            CROSS JOIN (SELECT 'fahrenheit' AS temperature_unit_code, '2024-08-11T12:00:00Z' AS generated_at)
+        ) TO '{}/forecasts_2024-08-12T00:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+        day_dir
+    ));
+
+    spawn_app(Arc::new(fixture.weather_access())).await
+}
+
+async fn get_forecasts(test_app: &TestApp, fields: &str) -> (StatusCode, Vec<Value>) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!(
+            "/stations/forecasts?station_ids=PFNO&fields={}",
+            fields
+        ))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    if status != StatusCode::OK {
+        return (status, vec![]);
+    }
+    (status, from_slice(&body).unwrap())
+}
+
+#[test]
+fn unrequested_fields_are_not_computed() {
+    let sql = select("temp_low,temp_high");
+    assert!(sql.contains("MIN(df.temp_low) AS temp_low"));
+    assert!(sql.contains("MAX(df.temp_high) AS temp_high"));
+    // Needed to convert the temperatures
+    assert!(sql.contains("MAX(df.temperature_unit_code) AS temperature_unit_code"));
+
+    for skipped in [
+        "NULL::BIGINT AS wind_speed",
+        "NULL::BIGINT AS humidity_max",
+        "NULL::DOUBLE AS precip_chance",
+        "NULL::DOUBLE AS rain_amt",
+    ] {
+        assert!(sql.contains(skipped), "{} missing from {}", skipped, sql);
+    }
+    assert!(!sql.contains("df.wind_speed"));
+    assert!(!sql.contains("daily_precip"));
+}
+
+#[test]
+fn precipitation_is_only_joined_when_requested() {
+    assert!(select("snow_amt").contains("LEFT JOIN daily_precip"));
+    assert!(select("snow_amt").contains("NULL::DOUBLE AS rain_amt"));
+
+    // Dominant precip is picked from all three amounts
+    let sql = select("dominant_precip");
+    assert!(sql.contains("LEFT JOIN daily_precip"));
+    assert!(sql.contains("dp.snow_amt AS snow_amt"));
+    assert!(sql.contains("dp.ice_amt AS ice_amt"));
+    assert!(!sql.contains("NULL::DOUBLE AS rain_amt"));
+
+    assert_eq!(
+        daily_forecast_select(
+            &ForecastFields::default(),
+            "MIN(df.start_time)",
+            "MAX(df.end_time)"
+        )
+        .matches("NULL::")
+        .count(),
+        0
+    );
+}
+
+#[test]
+fn parses_field_lists() {
+    let fields: ForecastFields = "station_id, temp_low,temp_low,date".parse().unwrap();
+    assert_eq!(fields.to_string(), "temp_low");
+    assert!("temp_low,humidity".parse::<ForecastFields>().is_err());
+    assert!("".parse::<ForecastFields>().is_err());
+}
+
+#[tokio::test]
+async fn forecasts_only_return_requested_fields() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, forecasts) = get_forecasts(&test_app, "temp_low,temp_high").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(forecasts.len(), 1);
+    let forecast = forecasts[0].as_object().unwrap();
+    let mut keys: Vec<&str> = forecast.keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(keys, vec!["date", "station_id", "temp_high", "temp_low"]);
+    assert_eq!(forecast["temp_low"], 50);
+    assert_eq!(forecast["temp_high"], 72);
+}
+
+#[tokio::test]
+async fn forecasts_reject_unknown_fields() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let (status, _) = get_forecasts(&test_app, "temp_low,feels_like").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
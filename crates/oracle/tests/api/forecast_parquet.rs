@@ -0,0 +1,134 @@
+use crate::helpers::{spawn_app, WeatherFixture};
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+};
+use hyper::Method;
+use oracle::weather_data::WeatherAccess;
+use parquet::{
+    file::reader::{FileReader, SerializedFileReader},
+    record::Field,
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// A 2024-08-12 forecast day for PFNO and KLWV in fahrenheit
+fn weather_fixture() -> WeatherAccess {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', 50, 70, 0.5::DOUBLE),
+                ('KLWV', 41, 59, 0.1::DOUBLE)
+            ) AS t(station_id, min_temp, max_temp, liquid_precipitation_amt)
+            CROSS JOIN (
+                SELECT '2024-08-12T00:00:00Z' AS begin_time, '2024-08-13T00:00:00Z' AS end_time,
+                       'fahrenheit' AS temperature_unit_code, '2024-08-11T12:00:00Z' AS generated_at
+            )
+        ) TO '{day_dir}/forecasts_2024-08-11T12:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+    ));
+    fixture.weather_access()
+}
+
+#[tokio::test]
+async fn forecasts_export_as_parquet_in_the_requested_unit() {
+    let test_app = spawn_app(Arc::new(weather_fixture())).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/forecasts.parquet?start=2024-08-12T00:00:00Z&end=2024-08-13T00:00:00Z&temperature_unit=celsius")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[CONTENT_TYPE],
+        "application/vnd.apache.parquet"
+    );
+    assert_eq!(response.headers()["x-rows"], "2");
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let reader = SerializedFileReader::new(body).unwrap();
+
+    let metadata = reader.metadata().file_metadata();
+    assert_eq!(metadata.num_rows(), 2);
+    let columns: Vec<&str> = metadata
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| column.name())
+        .collect();
+    assert_eq!(
+        columns,
+        vec![
+            "station_id",
+            "date",
+            "start_time",
+            "end_time",
+            "temp_low",
+            "temp_high",
+            "wind_speed",
+            "wind_direction",
+            "humidity_max",
+            "humidity_min",
+            "temperature_unit_code",
+            "precip_chance",
+            "rain_amt",
+            "snow_amt",
+            "ice_amt",
+            "generated_at",
+            "wind_speed_unit_code",
+        ]
+    );
+
+    let rows: Vec<(String, i64, i64, String)> = reader
+        .get_row_iter(None)
+        .unwrap()
+        .map(|row| {
+            let row = row.unwrap();
+            let mut station_id = String::new();
+            let mut temp_low = 0;
+            let mut temp_high = 0;
+            let mut unit = String::new();
+            for (name, field) in row.get_column_iter() {
+                match (name.as_str(), field) {
+                    ("station_id", Field::Str(value)) => station_id = value.clone(),
+                    ("temp_low", Field::Long(value)) => temp_low = *value,
+                    ("temp_high", Field::Long(value)) => temp_high = *value,
+                    ("temperature_unit_code", Field::Str(value)) => unit = value.clone(),
+                    _ => {}
+                }
+            }
+            (station_id, temp_low, temp_high, unit)
+        })
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            (String::from("KLWV"), 5, 15, String::from("celsius")),
+            (String::from("PFNO"), 10, 21, String::from("celsius")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn empty_forecast_export_keeps_the_columns() {
+    let test_app = spawn_app(Arc::new(weather_fixture())).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/forecasts.parquet?start=2024-09-01T00:00:00Z&end=2024-09-02T00:00:00Z")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let reader = SerializedFileReader::new(body).unwrap();
+    let metadata = reader.metadata().file_metadata();
+    assert_eq!(metadata.num_rows(), 0);
+    assert_eq!(metadata.schema_descr().num_columns(), 17);
+}
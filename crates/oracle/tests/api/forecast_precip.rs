@@ -0,0 +1,80 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, ForecastAggregation, ForecastFields, ForecastRequest, PrecipUnit,
+    TemperatureUnit, WeatherData,
+};
+
+/// PFNO liquid precipitation windows:
+///   2024-08-12  two overlapping 12h windows (0.5 each) and one 24h window (0.6), nothing chains
+///   2024-08-13  four chained 6h windows (0.1, 0.2, 0.3, 0.4) and a 24h window (1.5)
+///   2024-08-14  a single 6h window (0.7)
+fn weather_access_with_fixture() -> WeatherAccess {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT station_id, begin_time, end_time, amt::DOUBLE AS liquid_precipitation_amt,
+                   50 AS min_temp, 70 AS max_temp, 'fahrenheit' AS temperature_unit_code,
+                   '2024-08-11T12:00:00Z' AS generated_at
+            FROM (VALUES
+                ('PFNO', '2024-08-12T00:00:00Z', '2024-08-12T12:00:00Z', 0.5),
+                ('PFNO', '2024-08-12T06:00:00Z', '2024-08-12T18:00:00Z', 0.5),
+                ('PFNO', '2024-08-12T00:00:00Z', '2024-08-13T00:00:00Z', 0.6),
+                ('PFNO', '2024-08-13T00:00:00Z', '2024-08-13T06:00:00Z', 0.1),
+                ('PFNO', '2024-08-13T06:00:00Z', '2024-08-13T12:00:00Z', 0.2),
+                ('PFNO', '2024-08-13T12:00:00Z', '2024-08-13T18:00:00Z', 0.3),
+                ('PFNO', '2024-08-13T18:00:00Z', '2024-08-14T00:00:00Z', 0.4),
+                ('PFNO', '2024-08-13T00:00:00Z', '2024-08-14T00:00:00Z', 1.5),
+                ('PFNO', '2024-08-14T18:00:00Z', '2024-08-15T00:00:00Z', 0.7)
+            ) AS t(station_id, begin_time, end_time, amt)
+        ) TO '{}/forecasts_2024-08-12T00:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+        day_dir
+    ));
+
+    fixture.weather_access()
+}
+
+#[tokio::test]
+async fn single_window_days_use_longest_window_instead_of_summing_overlaps() {
+    let weather_access = weather_access_with_fixture();
+    let request = ForecastRequest {
+        start: None,
+        end: None,
+        generated_start: None,
+        generated_end: None,
+        as_of: None,
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        agg: ForecastAggregation::default(),
+        fields: ForecastFields::default(),
+        granularity: None,
+    };
+
+    let mut forecasts = weather_access
+        .forecasts_data(&request, vec![String::from("PFNO")])
+        .await
+        .unwrap();
+    forecasts.sort_by(|a, b| a.date.cmp(&b.date));
+    let rain: Vec<(String, f64)> = forecasts
+        .iter()
+        .map(|forecast| {
+            let rain = (forecast.rain_amt.unwrap() * 100.0).round() / 100.0;
+            (forecast.date[..10].to_string(), rain)
+        })
+        .collect();
+
+    assert_eq!(
+        rain,
+        vec![
+            // Previously 1.0, both overlapping 12h windows were summed
+            (String::from("2024-08-12"), 0.6),
+            // Chained windows still win over the 24h total
+            (String::from("2024-08-13"), 1.0),
+            (String::from("2024-08-14"), 0.7),
+        ]
+    );
+}
@@ -0,0 +1,183 @@
+use crate::helpers::{spawn_app, TestApp};
+use async_trait::async_trait;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use futures::future::join_all;
+use hyper::Method;
+use oracle::{
+    run_warm_cycle, warm_forecast_cache,
+    weather_data::{Error, WeatherData},
+    CoverageRequest, CustomQueryLimits, CustomQueryRequest, CustomQueryRows, DailyObservation,
+    FileType, Forecast, ForecastRequest, Observation, ObservationCoverage, ObservationRequest,
+    QueryStats, Station,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tower::ServiceExt;
+
+const CONCURRENT_REQUESTS: usize = 20;
+
+/// Weather source slow enough for requests to overlap, counting the daily observation
+/// queries per station. Each forecast fragment build runs exactly one.
+#[derive(Default)]
+struct SlowWeather {
+    builds: Mutex<HashMap<String, usize>>,
+}
+
+impl SlowWeather {
+    fn builds(&self, station_id: &str) -> usize {
+        self.builds
+            .lock()
+            .unwrap()
+            .get(station_id)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl WeatherData for SlowWeather {
+    async fn forecasts_data(
+        &self,
+        _req: &ForecastRequest,
+        _station_ids: Vec<String>,
+    ) -> Result<Vec<Forecast>, Error> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(vec![])
+    }
+
+    async fn observation_data(
+        &self,
+        _req: &ObservationRequest,
+        _station_ids: Vec<String>,
+    ) -> Result<Vec<Observation>, Error> {
+        Ok(vec![])
+    }
+
+    async fn daily_observations(
+        &self,
+        _req: &ObservationRequest,
+        station_ids: Vec<String>,
+    ) -> Result<Vec<DailyObservation>, Error> {
+        for station_id in station_ids {
+            *self.builds.lock().unwrap().entry(station_id).or_default() += 1;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(vec![])
+    }
+
+    async fn stations(&self) -> Result<Vec<Station>, Error> {
+        Ok(vec![])
+    }
+
+    async fn stations_with_data(
+        &self,
+        _date: time::Date,
+        _file_type: FileType,
+    ) -> Result<Vec<String>, Error> {
+        Ok(vec![])
+    }
+
+    async fn custom_query(
+        &self,
+        _req: &CustomQueryRequest,
+        _limits: CustomQueryLimits,
+    ) -> Result<CustomQueryRows, Error> {
+        Err(Error::CustomQuery(String::from("not supported")))
+    }
+
+    async fn forecasts_parquet(
+        &self,
+        _req: &ForecastRequest,
+        _station_ids: Vec<String>,
+    ) -> Result<(Body, QueryStats), Error> {
+        Err(Error::Export(String::from("not supported")))
+    }
+
+    async fn observation_coverage(
+        &self,
+        _req: &CoverageRequest,
+    ) -> Result<Vec<ObservationCoverage>, Error> {
+        Ok(vec![])
+    }
+}
+
+async fn get_forecast_fragment(test_app: &TestApp, station_id: &str) -> StatusCode {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/fragments/forecast/{}", station_id))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    status
+}
+
+#[tokio::test]
+async fn concurrent_misses_share_one_build() {
+    let weather = Arc::new(SlowWeather::default());
+    let test_app = spawn_app(weather.clone()).await;
+
+    let statuses =
+        join_all((0..CONCURRENT_REQUESTS).map(|_| get_forecast_fragment(&test_app, "PFNO"))).await;
+    assert!(statuses.iter().all(|status| *status == StatusCode::OK));
+    assert_eq!(weather.builds("PFNO"), 1);
+    assert!(test_app.state.forecast_flights.lock().unwrap().is_empty());
+
+    // After the cache is cleared the next burst builds once more
+    test_app.state.forecast_cache.lock().unwrap().clear();
+    join_all((0..CONCURRENT_REQUESTS).map(|_| get_forecast_fragment(&test_app, "PFNO"))).await;
+    assert_eq!(weather.builds("PFNO"), 2);
+}
+
+#[tokio::test]
+async fn requests_during_warming_join_the_warm_build() {
+    let weather = Arc::new(SlowWeather::default());
+    let test_app = spawn_app(weather.clone()).await;
+
+    let requests =
+        join_all((0..CONCURRENT_REQUESTS).map(|_| get_forecast_fragment(&test_app, "KATL")));
+    let (_, statuses) = tokio::join!(warm_forecast_cache(&test_app.state), requests);
+
+    assert!(statuses.iter().all(|status| *status == StatusCode::OK));
+    assert_eq!(weather.builds("KATL"), 1);
+    assert_eq!(weather.builds("KLAX"), 1);
+}
+
+#[tokio::test]
+async fn poisoned_cache_lock_recovers() {
+    let weather = Arc::new(SlowWeather::default());
+    let test_app = spawn_app(weather.clone()).await;
+
+    // A task panicking while it holds the locks poisons them
+    let state = test_app.state.clone();
+    std::thread::spawn(move || {
+        let _cache = state.forecast_cache.lock().unwrap();
+        let _flights = state.forecast_flights.lock().unwrap();
+        panic!("fragment build failed");
+    })
+    .join()
+    .unwrap_err();
+    assert!(test_app.state.forecast_cache.is_poisoned());
+
+    assert_eq!(
+        get_forecast_fragment(&test_app, "PFNO").await,
+        StatusCode::OK
+    );
+    assert!(test_app.state.lock_forecast_cache().contains_key("PFNO"));
+
+    // Warm cycles keep filling the cache too
+    run_warm_cycle(&test_app.state).await;
+    assert!(test_app.state.lock_forecast_cache().contains_key("KATL"));
+    assert!(test_app
+        .state
+        .forecast_cache_warmed
+        .load(std::sync::atomic::Ordering::Acquire));
+    assert_eq!(weather.builds("PFNO"), 1);
+}
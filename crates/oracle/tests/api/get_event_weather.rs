@@ -0,0 +1,228 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::{header, Method};
+use nostr_sdk::Keys;
+use oracle::{
+    CreateEvent, Event, EventStore, Forecast, Forecasted, Observation, Observed, PrecipUnit,
+    TemperatureUnit, Weather,
+};
+use serde_json::from_slice;
+use std::sync::Arc;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn can_get_event_weather_in_requested_unit() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .returning(|_, _| Ok(mock_forecast_data()));
+    weather_data
+        .expect_observation_data()
+        .returning(|_, _| Ok(mock_observation_data()));
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    let event = create_past_event(&test_app.oracle).await;
+    test_app.oracle.etl_data(1).await.unwrap();
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events/{}/weather", event.id))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let weather: Vec<Weather> = from_slice(&body).unwrap();
+    assert_eq!(weather.len(), 1);
+    assert_eq!(weather[0].station_id, "PFNO");
+    assert_eq!(weather[0].forecasted.temp_low, 9);
+    assert_eq!(weather[0].forecasted.temp_high, 35);
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events/{}/weather?unit=celsius", event.id))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let weather: Vec<Weather> = from_slice(&body).unwrap();
+    assert_eq!(weather.len(), 1);
+    // 9F -> -12.8C, 35F -> 1.7C
+    assert_eq!(weather[0].forecasted.temp_low, -13);
+    assert_eq!(weather[0].forecasted.temp_high, 2);
+    let observed = weather[0].observed.as_ref().unwrap();
+    // observed 9.4F is stored as 9F, 36.6F is stored as 37F
+    assert_eq!(observed.temp_low, -13);
+    assert_eq!(observed.temp_high, 3);
+}
+
+#[tokio::test]
+async fn get_event_weather_returns_not_found_for_unknown_event() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events/{}/weather", Uuid::now_v7()))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn converts_stored_celsius_weather_to_fahrenheit_on_read() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_past_event(&test_app.oracle).await;
+    test_app
+        .db
+        .update_weather_station_data(event.id, vec![celsius_weather()], &TemperatureUnit::Celsius)
+        .await
+        .unwrap();
+
+    let weather = test_app
+        .db
+        .get_event_weather_in_unit(event.id, &TemperatureUnit::Fahrenheit)
+        .await
+        .unwrap();
+    assert_eq!(weather.len(), 1);
+    // -5C -> 23F, 20C -> 68F
+    assert_eq!(weather[0].forecasted.temp_low, 23);
+    assert_eq!(weather[0].forecasted.temp_high, 68);
+    let observed = weather[0].observed.as_ref().unwrap();
+    // 0C -> 32F, 21C -> 69.8F
+    assert_eq!(observed.temp_low, 32);
+    assert_eq!(observed.temp_high, 70);
+
+    let weather = test_app
+        .db
+        .get_event_weather_in_unit(event.id, &TemperatureUnit::Celsius)
+        .await
+        .unwrap();
+    assert_eq!(weather[0].forecasted.temp_low, -5);
+    assert_eq!(weather[0].forecasted.temp_high, 20);
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!(
+            "/oracle/events/{}/weather?unit=fahrenheit",
+            event.id
+        ))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let weather: Vec<Weather> = from_slice(&body).unwrap();
+    assert_eq!(weather[0].forecasted.temp_low, 23);
+    assert_eq!(weather[0].forecasted.temp_high, 68);
+}
+
+async fn create_past_event(oracle: &oracle::oracle::Oracle) -> Event {
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: OffsetDateTime::parse("2024-08-12T00:00:00+00:00", &Rfc3339)
+            .unwrap(),
+        end_observation_date: OffsetDateTime::parse("2024-08-13T00:00:00+00:00", &Rfc3339).unwrap(),
+        signing_date: OffsetDateTime::parse("2024-08-13T03:00:00+00:00", &Rfc3339).unwrap(),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 4,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    oracle
+        .create_event(Keys::generate().public_key, new_event, true)
+        .await
+        .unwrap()
+}
+
+fn celsius_weather() -> Weather {
+    Weather {
+        station_id: String::from("PFNO"),
+        observed: Some(Observed {
+            date: OffsetDateTime::parse("2024-08-12T00:00:00+00:00", &Rfc3339).unwrap(),
+            temp_low: 0,
+            temp_high: 21,
+            wind_speed: 11,
+        }),
+        forecasted: Forecasted {
+            date: OffsetDateTime::parse("2024-08-12T00:00:00+00:00", &Rfc3339).unwrap(),
+            temp_low: -5,
+            temp_high: 20,
+            wind_speed: Some(8),
+        },
+    }
+}
+
+fn mock_forecast_data() -> Vec<Forecast> {
+    vec![Forecast {
+        station_id: String::from("PFNO"),
+        date: String::from("2024-08-12"),
+        start_time: String::from("2024-08-11T00:00:00+00:00"),
+        end_time: String::from("2024-08-12T00:00:00+00:00"),
+        temp_low: 9,
+        temp_high: 35,
+        wind_speed: Some(8),
+        wind_direction: None,
+        humidity_max: None,
+        humidity_min: None,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        precip_chance: None,
+        rain_amt: None,
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        dominant_precip: None,
+        bucket: None,
+    }]
+}
+
+fn mock_observation_data() -> Vec<Observation> {
+    vec![Observation {
+        station_id: String::from("PFNO"),
+        start_time: String::from("2024-08-12T00:00:00+00:00"),
+        end_time: String::from("2024-08-13T00:00:00+00:00"),
+        temp_low: 9.4,
+        temp_high: 36.6,
+        wind_speed: 11,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        wind_direction: None,
+        humidity: None,
+        rain_amt: None,
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        bucket: None,
+    }]
+}
@@ -1,14 +1,17 @@
 use crate::helpers::{spawn_app, MockWeatherAccess};
 use axum::{
     body::{to_bytes, Body},
-    http::Request,
+    http::{Request, StatusCode},
 };
 use hyper::{header, Method};
 use nostr_sdk::Keys;
-use oracle::{CreateEvent, EventSummary};
+use oracle::{
+    AddEventEntry, CreateEvent, Event, EventFilter, EventStore, EventSummary, Forecasted,
+    TemperatureUnit, Weather, WeatherChoices,
+};
 use serde_json::from_slice;
 use std::sync::Arc;
-use time::OffsetDateTime;
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
 use tower::ServiceExt;
 use uuid::Uuid;
 
@@ -30,9 +33,13 @@ async fn can_get_all_events() {
             String::from("KWMC"),
         ],
         total_allowed_entries: 5,
-        number_of_values_per_entry: 6,
+        number_of_values_per_entry: Some(6),
         number_of_places_win: 1,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
     let new_event_2 = CreateEvent {
         id: Uuid::now_v7(),
@@ -46,9 +53,13 @@ async fn can_get_all_events() {
             String::from("KJAN"),
         ],
         total_allowed_entries: 5,
-        number_of_values_per_entry: 6,
+        number_of_values_per_entry: Some(6),
         number_of_places_win: 1,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
     let new_event_3 = CreateEvent {
         id: Uuid::now_v7(),
@@ -62,9 +73,13 @@ async fn can_get_all_events() {
             String::from("KDED"),
         ],
         total_allowed_entries: 5,
-        number_of_values_per_entry: 6,
+        number_of_values_per_entry: Some(6),
         number_of_places_win: 1,
         scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
     };
     let expected = [
         new_event_1.clone(),
@@ -73,17 +88,17 @@ async fn can_get_all_events() {
     ];
     test_app
         .oracle
-        .create_event(keys.public_key, new_event_1)
+        .create_event(keys.public_key, new_event_1, true)
         .await
         .unwrap();
     test_app
         .oracle
-        .create_event(keys.public_key, new_event_2)
+        .create_event(keys.public_key, new_event_2, true)
         .await
         .unwrap();
     test_app
         .oracle
-        .create_event(keys.public_key, new_event_3)
+        .create_event(keys.public_key, new_event_3, true)
         .await
         .unwrap();
 
@@ -102,8 +117,10 @@ async fn can_get_all_events() {
     assert!(response.status().is_success());
     let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
     let res: Vec<EventSummary> = from_slice(&body).unwrap();
-    for (index, event_summary) in res.iter().enumerate() {
-        let cur_expect = expected.get(index).unwrap();
+    assert_eq!(res.len(), expected.len());
+    // listed newest created first by default
+    for (event_summary, cur_expect) in res.iter().zip(expected.iter().rev()) {
+        assert_eq!(event_summary.id, cur_expect.id);
         // SQLite stores timestamps as seconds, so we truncate to second precision
         assert_eq!(
             event_summary.signing_date,
@@ -132,3 +149,448 @@ async fn can_get_all_events() {
         assert!(event_summary.attestation.is_none());
     }
 }
+
+/// Starts tomorrow so the entry goes in before the cutoff
+async fn create_event_with_entry_and_weather(test_app: &crate::helpers::TestApp) -> Event {
+    let keys = Keys::generate();
+    let tomorrow = OffsetDateTime::now_utc() + Duration::days(1);
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: tomorrow,
+        end_observation_date: tomorrow,
+        signing_date: tomorrow,
+        locations: vec![String::from("PFNO"), String::from("KSAW")],
+        total_allowed_entries: 1,
+        number_of_values_per_entry: Some(4),
+        number_of_places_win: 1,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event, true)
+        .await
+        .unwrap();
+    let entry = AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id: event.id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_low: Some(oracle::ValueOptions::Par),
+            temp_high: None,
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    };
+    test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, vec![entry])
+        .await
+        .unwrap();
+    let weather = Weather {
+        station_id: String::from("PFNO"),
+        observed: None,
+        forecasted: Forecasted {
+            date: OffsetDateTime::now_utc(),
+            temp_low: 10,
+            temp_high: 20,
+            wind_speed: Some(5),
+        },
+    };
+    test_app
+        .db
+        .update_weather_station_data(event.id, vec![weather], &TemperatureUnit::Fahrenheit)
+        .await
+        .unwrap();
+    event
+}
+
+async fn get_event_json(test_app: &crate::helpers::TestApp, uri: String) -> Event {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn get_event_defaults_to_metadata_only() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_event_with_entry_and_weather(&test_app).await;
+
+    let res = get_event_json(&test_app, format!("/oracle/events/{}", event.id)).await;
+    assert_eq!(res.id, event.id);
+    assert_eq!(res.locations, event.locations);
+    assert!(res.entries.is_empty());
+    assert!(res.entry_ids.is_empty());
+    assert!(res.weather.is_empty());
+}
+
+#[tokio::test]
+async fn get_event_only_loads_requested_sections() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_event_with_entry_and_weather(&test_app).await;
+
+    let res = get_event_json(
+        &test_app,
+        format!("/oracle/events/{}?include=weather", event.id),
+    )
+    .await;
+    assert!(res.entries.is_empty());
+    assert_eq!(res.weather.len(), 1);
+
+    let res = get_event_json(
+        &test_app,
+        format!("/oracle/events/{}?include=entries", event.id),
+    )
+    .await;
+    assert!(res.weather.is_empty());
+    assert_eq!(res.entries.len(), 1);
+    assert_eq!(res.entry_ids, vec![res.entries[0].id]);
+    assert!(res.entries[0].expected_observations.is_empty());
+
+    let res = get_event_json(
+        &test_app,
+        format!(
+            "/oracle/events/{}?include=entries,weather,entry_choices",
+            event.id
+        ),
+    )
+    .await;
+    assert_eq!(res.weather.len(), 1);
+    assert_eq!(res.entries.len(), 1);
+    assert_eq!(res.entries[0].expected_observations.len(), 1);
+}
+
+#[tokio::test]
+async fn get_event_rejects_unknown_include() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_event_with_entry_and_weather(&test_app).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events/{}?include=scores", event.id))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Three events observing a day each, a week apart, signed an hour after observing ends
+async fn create_weekly_events(test_app: &crate::helpers::TestApp) -> (OffsetDateTime, Vec<Uuid>) {
+    let keys = Keys::generate();
+    let base = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap() + Duration::days(10);
+    let mut ids = vec![];
+    for week in 0..3 {
+        let start_observation_date = base + Duration::weeks(week);
+        let new_event = CreateEvent {
+            id: Uuid::now_v7(),
+            start_observation_date,
+            end_observation_date: start_observation_date + Duration::days(1),
+            signing_date: start_observation_date + Duration::days(1) + Duration::hours(1),
+            locations: vec![String::from("PFNO"), String::from("KSAW")],
+            total_allowed_entries: 5,
+            number_of_values_per_entry: Some(4),
+            number_of_places_win: 1,
+            scoring_fields: oracle::ScoringField::defaults(),
+            par_tolerances: Default::default(),
+            scoring_mode: Default::default(),
+            settlement_delay_hours: Default::default(),
+            entry_cutoff: Default::default(),
+        };
+        ids.push(new_event.id);
+        test_app
+            .oracle
+            .create_event(keys.public_key, new_event, true)
+            .await
+            .unwrap();
+    }
+    (base, ids)
+}
+
+async fn list_event_ids(test_app: &crate::helpers::TestApp, query: String) -> Vec<Uuid> {
+    let mut ids = list_ordered_event_ids(test_app, query).await;
+    ids.sort();
+    ids
+}
+
+async fn list_ordered_event_ids(test_app: &crate::helpers::TestApp, query: String) -> Vec<Uuid> {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events?{}", query))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let events: Vec<EventSummary> = from_slice(&body).unwrap();
+    events.iter().map(|event| event.id).collect()
+}
+
+fn rfc3339(date: OffsetDateTime) -> String {
+    date.format(&Rfc3339).unwrap()
+}
+
+#[tokio::test]
+async fn can_filter_events_by_observation_range() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let (base, ids) = create_weekly_events(&test_app).await;
+
+    let res = list_event_ids(
+        &test_app,
+        format!(
+            "observation_after={}&observation_before={}",
+            rfc3339(base + Duration::days(6)),
+            rfc3339(base + Duration::days(9))
+        ),
+    )
+    .await;
+    assert_eq!(res, vec![ids[1]]);
+
+    // bounds are inclusive, touching a window's edge still matches it
+    let res = list_event_ids(
+        &test_app,
+        format!(
+            "observation_after={}&observation_before={}",
+            rfc3339(base + Duration::days(1)),
+            rfc3339(base + Duration::weeks(1))
+        ),
+    )
+    .await;
+    assert_eq!(res, vec![ids[0], ids[1]]);
+
+    let res = list_event_ids(
+        &test_app,
+        format!(
+            "signing_after={}&signing_before={}",
+            rfc3339(base + Duration::weeks(1)),
+            rfc3339(base + Duration::weeks(3))
+        ),
+    )
+    .await;
+    assert_eq!(res, vec![ids[1], ids[2]]);
+}
+
+#[tokio::test]
+async fn can_filter_events_with_open_ended_range() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let (base, ids) = create_weekly_events(&test_app).await;
+
+    let res = list_event_ids(
+        &test_app,
+        format!("observation_after={}", rfc3339(base + Duration::days(6))),
+    )
+    .await;
+    assert_eq!(res, vec![ids[1], ids[2]]);
+
+    let res = list_event_ids(
+        &test_app,
+        format!("observation_before={}", rfc3339(base + Duration::days(6))),
+    )
+    .await;
+    assert_eq!(res, vec![ids[0]]);
+
+    // date bounds narrow the id and limit filters rather than replacing them
+    let events = test_app
+        .oracle
+        .list_events(EventFilter {
+            event_ids: Some(vec![ids[0], ids[1]]),
+            signing_after: Some(base + Duration::days(2)),
+            ..EventFilter::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        events.iter().map(|event| event.id).collect::<Vec<_>>(),
+        vec![ids[1]]
+    );
+
+    let res = list_event_ids(
+        &test_app,
+        format!(
+            "limit=1&observation_after={}",
+            rfc3339(base + Duration::days(6))
+        ),
+    )
+    .await;
+    assert_eq!(res.len(), 1);
+    assert_ne!(res, vec![ids[0]]);
+}
+
+#[tokio::test]
+async fn event_range_filter_can_match_nothing() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let (base, _) = create_weekly_events(&test_app).await;
+
+    let res = list_event_ids(
+        &test_app,
+        format!("observation_after={}", rfc3339(base + Duration::weeks(4))),
+    )
+    .await;
+    assert!(res.is_empty());
+
+    // an inverted range is empty rather than an error
+    let res = list_event_ids(
+        &test_app,
+        format!(
+            "observation_after={}&observation_before={}",
+            rfc3339(base + Duration::weeks(3)),
+            rfc3339(base)
+        ),
+    )
+    .await;
+    assert!(res.is_empty());
+}
+
+/// Three events, returned in creation order, whose observation, signing and entry count
+/// orderings all differ from it and from each other
+async fn create_sortable_events(test_app: &crate::helpers::TestApp) -> Vec<Uuid> {
+    let keys = Keys::generate();
+    let base = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap() + Duration::days(10);
+    let events = [
+        (base + Duration::days(1), base + Duration::weeks(3), 2),
+        (base + Duration::weeks(2), base + Duration::days(15), 3),
+        (base, base + Duration::days(8), 0),
+    ];
+    let mut ids = vec![];
+    for (start_observation_date, signing_date, total_entries) in events {
+        let new_event = CreateEvent {
+            id: Uuid::now_v7(),
+            start_observation_date,
+            end_observation_date: start_observation_date + Duration::days(1),
+            signing_date,
+            locations: vec![String::from("PFNO"), String::from("KSAW")],
+            total_allowed_entries: if total_entries == 0 { 5 } else { total_entries },
+            number_of_values_per_entry: Some(4),
+            number_of_places_win: 1,
+            scoring_fields: oracle::ScoringField::defaults(),
+            par_tolerances: Default::default(),
+            scoring_mode: Default::default(),
+            settlement_delay_hours: Default::default(),
+            entry_cutoff: Default::default(),
+        };
+        let event = test_app
+            .oracle
+            .create_event(keys.public_key, new_event, true)
+            .await
+            .unwrap();
+        let entries: Vec<AddEventEntry> = (0..total_entries)
+            .map(|_| AddEventEntry {
+                id: Uuid::now_v7(),
+                event_id: event.id,
+                expected_observations: vec![WeatherChoices {
+                    stations: String::from("PFNO"),
+                    temp_low: Some(oracle::ValueOptions::Par),
+                    temp_high: None,
+                    wind_speed: None,
+                    wind_direction: None,
+                    rain_amt: None,
+                    snow_amt: None,
+                    humidity: None,
+                    predictions: Default::default(),
+                }],
+            })
+            .collect();
+        if !entries.is_empty() {
+            test_app
+                .oracle
+                .add_event_entries(keys.public_key, event.id, entries)
+                .await
+                .unwrap();
+        }
+        ids.push(event.id);
+    }
+    ids
+}
+
+#[tokio::test]
+async fn can_sort_events_by_each_key() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let ids = create_sortable_events(&test_app).await;
+
+    let ascending = [
+        ("created", vec![ids[0], ids[1], ids[2]]),
+        ("observation_date", vec![ids[2], ids[0], ids[1]]),
+        ("signing_date", vec![ids[2], ids[1], ids[0]]),
+        ("total_entries", vec![ids[2], ids[0], ids[1]]),
+    ];
+    for (sort, expected) in ascending {
+        let res = list_ordered_event_ids(&test_app, format!("sort={}&direction=asc", sort)).await;
+        assert_eq!(res, expected, "sort={} asc", sort);
+
+        let res = list_ordered_event_ids(&test_app, format!("sort={}&direction=desc", sort)).await;
+        let descending: Vec<Uuid> = expected.into_iter().rev().collect();
+        assert_eq!(res, descending, "sort={} desc", sort);
+    }
+}
+
+#[tokio::test]
+async fn events_default_to_newest_created_and_page_by_offset() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let ids = create_sortable_events(&test_app).await;
+
+    let res = list_ordered_event_ids(&test_app, String::new()).await;
+    assert_eq!(res, vec![ids[2], ids[1], ids[0]]);
+
+    // direction alone keeps the default created sort
+    let res = list_ordered_event_ids(&test_app, String::from("direction=asc")).await;
+    assert_eq!(res, vec![ids[0], ids[1], ids[2]]);
+
+    let mut paged = vec![];
+    for offset in 0..3 {
+        paged.extend(
+            list_ordered_event_ids(
+                &test_app,
+                format!("sort=signing_date&direction=asc&limit=1&offset={}", offset),
+            )
+            .await,
+        );
+    }
+    assert_eq!(paged, vec![ids[2], ids[1], ids[0]]);
+
+    let res = list_ordered_event_ids(&test_app, String::from("sort=created&offset=3")).await;
+    assert!(res.is_empty());
+}
+
+#[tokio::test]
+async fn list_events_rejects_unknown_sort() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/oracle/events?sort=popularity")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
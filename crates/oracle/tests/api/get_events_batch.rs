@@ -0,0 +1,95 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::{header, Method};
+use nostr_sdk::Keys;
+use oracle::{BatchEvent, CreateEvent, GetEventsBatch, DEFAULT_MAX_BATCH_EVENTS};
+use serde_json::{from_slice, to_string};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn can_get_batch_of_existing_and_missing_events() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: OffsetDateTime::now_utc(),
+        end_observation_date: OffsetDateTime::now_utc(),
+        signing_date: OffsetDateTime::now_utc(),
+        locations: vec![String::from("PFNO"), String::from("KSAW")],
+        total_allowed_entries: 5,
+        number_of_values_per_entry: Some(4),
+        number_of_places_win: 1,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event, true)
+        .await
+        .unwrap();
+    let missing_id = Uuid::now_v7();
+
+    let body = GetEventsBatch {
+        ids: vec![event.id, missing_id],
+    };
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(String::from("/oracle/events/batch"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(to_string(&body).unwrap()))
+        .unwrap();
+    let response = test_app
+        .app
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Vec<BatchEvent> = from_slice(&body).unwrap();
+
+    assert_eq!(res.len(), 2);
+    match &res[0] {
+        BatchEvent::Found { event: found } => {
+            assert_eq!(found.id, event.id);
+            assert_eq!(found.locations, event.locations);
+        }
+        BatchEvent::NotFound { .. } => panic!("expected event {} to be found", event.id),
+    }
+    match &res[1] {
+        BatchEvent::NotFound { id } => assert_eq!(*id, missing_id),
+        BatchEvent::Found { .. } => panic!("expected event {} to be missing", missing_id),
+    }
+}
+
+#[tokio::test]
+async fn rejects_batch_over_max_events() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    let body = GetEventsBatch {
+        ids: (0..=DEFAULT_MAX_BATCH_EVENTS)
+            .map(|_| Uuid::now_v7())
+            .collect(),
+    };
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(String::from("/oracle/events/batch"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(to_string(&body).unwrap()))
+        .unwrap();
+    let response = test_app
+        .app
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
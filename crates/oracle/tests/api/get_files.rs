@@ -0,0 +1,116 @@
+use crate::helpers::{random_test_number, spawn_app_with_file_access, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::{header::CONTENT_TYPE, Method};
+use oracle::{create_folder, FileAccess, FileType, Files};
+use serde_json::from_slice;
+use std::{fs, io::Cursor, io::Read, sync::Arc};
+use tower::ServiceExt;
+use zip::ZipArchive;
+
+const FILES: [&str; 3] = [
+    "forecasts_2024-08-12T00:00:00Z.parquet",
+    "observations_2024-08-12T00:00:00Z.parquet",
+    "forecasts_2024-08-13T00:00:00Z.parquet",
+];
+
+/// Files only need the right names here, the contents are never parsed as parquet
+async fn spawn_with_files() -> TestApp {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    for name in FILES {
+        let day_dir = format!("{}/{}", data_dir, &name[name.len() - 28..name.len() - 18]);
+        create_folder(&day_dir);
+        fs::write(format!("{}/{}", day_dir, name), name.as_bytes()).unwrap();
+    }
+    spawn_app_with_file_access(
+        Arc::new(MockWeatherAccess::new()),
+        Arc::new(FileAccess::new(data_dir)),
+    )
+    .await
+}
+
+async fn get(test_app: &TestApp, uri: &str) -> (StatusCode, Option<String>, Vec<u8>) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, content_type, body.to_vec())
+}
+
+#[tokio::test]
+async fn can_list_files_by_type_with_download_urls() {
+    let test_app = spawn_with_files().await;
+
+    let (status, _, body) = get(
+        &test_app,
+        "/files?start=2024-08-11T00:00:00Z&end=2024-08-14T00:00:00Z&type=forecasts",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let files: Files = from_slice(&body).unwrap();
+
+    let mut names: Vec<&str> = files.files.iter().map(|file| file.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec![FILES[0], FILES[2]]);
+    assert_eq!(files.file_names.len(), 2);
+    let first = files
+        .files
+        .iter()
+        .find(|file| file.name == FILES[0])
+        .unwrap();
+    assert_eq!(first.file_type, FileType::Forecasts);
+    assert_eq!(
+        first.url,
+        format!("http://127.0.0.1:9100/file/{}", FILES[0])
+    );
+}
+
+#[tokio::test]
+async fn can_bundle_files_as_zip() {
+    let test_app = spawn_with_files().await;
+
+    let (status, content_type, body) = get(
+        &test_app,
+        "/files?start=2024-08-11T00:00:00Z&end=2024-08-14T00:00:00Z&bundle=zip",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/zip"));
+
+    let mut archive = ZipArchive::new(Cursor::new(body)).unwrap();
+    assert_eq!(archive.len(), FILES.len());
+    for name in FILES {
+        let mut contents = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, name);
+    }
+}
+
+#[tokio::test]
+async fn rejects_ranges_over_the_cap_and_unbounded_bundles() {
+    let test_app = spawn_with_files().await;
+
+    let (status, _, _) = get(
+        &test_app,
+        "/files?start=2024-01-01T00:00:00Z&end=2024-08-14T00:00:00Z",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _, _) = get(&test_app, "/files?start=2024-08-11T00:00:00Z&bundle=zip").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
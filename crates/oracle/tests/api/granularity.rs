@@ -0,0 +1,233 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, DayTimeZone, ForecastAggregation, ForecastFields, ForecastRequest,
+    Granularity, ObservationRequest, ObservationTempAggregation, ObservationWindAggregation,
+    PrecipUnit, SelectedForecasts, TemperatureUnit, WeatherData,
+};
+use serde_json::json;
+use time::macros::datetime;
+
+/// Three chained 6 hour PFNO forecast windows on 2024-08-12 and four PFNO rain readings, two
+/// of them in the same hour and one just past midnight UTC on the 13th.
+fn weather_fixture() -> WeatherAccess {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T00:00:00Z', '2024-08-12T06:00:00Z', 50, 60, 0.1::DOUBLE),
+                ('PFNO', '2024-08-12T06:00:00Z', '2024-08-12T12:00:00Z', 52, 66, 0.2::DOUBLE),
+                ('PFNO', '2024-08-12T12:00:00Z', '2024-08-12T18:00:00Z', 58, 72, 0.3::DOUBLE)
+            ) AS t(station_id, begin_time, end_time, min_temp, max_temp, liquid_precipitation_amt)
+            CROSS JOIN (SELECT 'fahrenheit' AS temperature_unit_code, '2024-08-11T12:00:00Z' AS generated_at)
+        ) TO '{day_dir}/forecasts_2024-08-11T12:00:00Z.parquet' (FORMAT PARQUET);
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T07:53:00Z', 18.0, 0.1::DOUBLE),
+                ('PFNO', '2024-08-12T07:58:00Z', 19.0, 0.05::DOUBLE),
+                ('PFNO', '2024-08-12T13:53:00Z', 21.0, 0.2::DOUBLE),
+                ('PFNO', '2024-08-13T02:53:00Z', 15.0, NULL)
+            ) AS t(station_id, generated_at, temperature_value, precip_in)
+            CROSS JOIN (SELECT 'celsius' AS temperature_unit_code, 'RA' AS wx_string)
+        ) TO '{day_dir}/observations_2024-08-12T23:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+    ));
+
+    fixture.weather_access()
+}
+
+fn forecast_request(granularity: Option<Granularity>) -> ForecastRequest {
+    ForecastRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        generated_start: Some(datetime!(2024-08-11 00:00:00 UTC)),
+        generated_end: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        as_of: None,
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        agg: ForecastAggregation::default(),
+        fields: ForecastFields::default(),
+        granularity,
+    }
+}
+
+fn observation_request(granularity: Option<Granularity>, tz: DayTimeZone) -> ObservationRequest {
+    ObservationRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-13 06:00:00 UTC)),
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Celsius,
+        tz,
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        granularity,
+    }
+}
+
+fn hundredths(amount: Option<f64>) -> Option<f64> {
+    amount.map(|amount| (amount * 100.0).round() / 100.0)
+}
+
+#[tokio::test]
+async fn forecasts_group_by_each_granularity() {
+    let weather_access = weather_fixture();
+
+    // unset keeps the plain daily rows
+    let req = forecast_request(None);
+    let forecasts = weather_access
+        .forecasts_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(forecasts.len(), 1);
+    assert_eq!(forecasts[0].bucket, None);
+    assert_eq!(forecasts[0].date, "2024-08-12 00:00:00");
+
+    let req = forecast_request(Some(Granularity::Daily));
+    let forecasts = weather_access
+        .forecasts_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(forecasts.len(), 1);
+    assert_eq!(forecasts[0].bucket.as_deref(), Some("2024-08-12T00:00:00Z"));
+    assert_eq!(forecasts[0].date, "2024-08-12 00:00:00");
+    assert_eq!((forecasts[0].temp_low, forecasts[0].temp_high), (50, 72));
+    assert_eq!(hundredths(forecasts[0].rain_amt), Some(0.6));
+
+    // the windows start on the hour, so hourly and raw give the same rows
+    for granularity in [Granularity::Hourly, Granularity::Raw] {
+        let req = forecast_request(Some(granularity));
+        let forecasts = weather_access
+            .forecasts_data(&req, req.station_ids())
+            .await
+            .unwrap();
+        let rows: Vec<_> = forecasts
+            .iter()
+            .map(|forecast| {
+                (
+                    forecast.bucket.as_deref().unwrap(),
+                    forecast.date.as_str(),
+                    forecast.temp_high,
+                    hundredths(forecast.rain_amt),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("2024-08-12T00:00:00Z", "2024-08-12 00:00:00", 60, Some(0.1)),
+                ("2024-08-12T06:00:00Z", "2024-08-12 00:00:00", 66, Some(0.2)),
+                ("2024-08-12T12:00:00Z", "2024-08-12 00:00:00", 72, Some(0.3)),
+            ],
+            "{}",
+            granularity
+        );
+    }
+}
+
+#[tokio::test]
+async fn observations_group_by_each_granularity() {
+    let weather_access = weather_fixture();
+    let buckets = |granularity, tz| {
+        let weather_access = &weather_access;
+        async move {
+            let req = observation_request(granularity, tz);
+            weather_access
+                .observation_data(&req, req.station_ids())
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|observation| {
+                    (
+                        observation.bucket,
+                        observation.temp_low,
+                        observation.temp_high,
+                        hundredths(observation.rain_amt),
+                    )
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+    let bucket = |start: &str| Some(start.to_string());
+
+    // unset keeps one row for the whole window
+    assert_eq!(
+        buckets(None, DayTimeZone::Utc).await,
+        [(None, 15.0, 21.0, Some(0.35))]
+    );
+    assert_eq!(
+        buckets(Some(Granularity::Raw), DayTimeZone::Utc).await,
+        [
+            (bucket("2024-08-12T07:53:00Z"), 18.0, 18.0, Some(0.1)),
+            (bucket("2024-08-12T07:58:00Z"), 19.0, 19.0, Some(0.05)),
+            (bucket("2024-08-12T13:53:00Z"), 21.0, 21.0, Some(0.2)),
+            (bucket("2024-08-13T02:53:00Z"), 15.0, 15.0, None),
+        ]
+    );
+    assert_eq!(
+        buckets(Some(Granularity::Hourly), DayTimeZone::Utc).await,
+        [
+            (bucket("2024-08-12T07:00:00Z"), 18.0, 19.0, Some(0.15)),
+            (bucket("2024-08-12T13:00:00Z"), 21.0, 21.0, Some(0.2)),
+            (bucket("2024-08-13T02:00:00Z"), 15.0, 15.0, None),
+        ]
+    );
+    assert_eq!(
+        buckets(Some(Granularity::Daily), DayTimeZone::Utc).await,
+        [
+            (bucket("2024-08-12T00:00:00Z"), 18.0, 21.0, Some(0.35)),
+            (bucket("2024-08-13T00:00:00Z"), 15.0, 15.0, None),
+        ]
+    );
+    // a -05:00 day runs 05:00 to 05:00 UTC and takes in the reading after midnight
+    assert_eq!(
+        buckets(Some(Granularity::Daily), "-05:00".parse().unwrap()).await,
+        [(bucket("2024-08-12T05:00:00Z"), 15.0, 21.0, Some(0.35))]
+    );
+}
+
+#[tokio::test]
+async fn selected_forecasts_carry_the_bucket() {
+    let weather_access = weather_fixture();
+    let req = forecast_request(Some(Granularity::Hourly));
+    let forecasts = weather_access
+        .forecasts_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    let body = serde_json::to_value(SelectedForecasts {
+        forecasts,
+        fields: "temp_high".parse().unwrap(),
+    })
+    .unwrap();
+    assert_eq!(
+        body[0],
+        json!({
+            "station_id": "PFNO",
+            "date": "2024-08-12 00:00:00",
+            "bucket": "2024-08-12T00:00:00Z",
+            "temp_high": 60
+        })
+    );
+}
+
+#[test]
+fn parses_granularity_names() {
+    assert_eq!("raw".parse::<Granularity>(), Ok(Granularity::Raw));
+    assert_eq!("Hourly".parse::<Granularity>(), Ok(Granularity::Hourly));
+    assert_eq!("DAILY".parse::<Granularity>(), Ok(Granularity::Daily));
+    assert!("weekly".parse::<Granularity>().is_err());
+
+    let req: ForecastRequest = serde_json::from_value(json!({ "station_ids": "PFNO" })).unwrap();
+    assert_eq!(req.granularity, None);
+    let req: ObservationRequest =
+        serde_json::from_value(json!({ "station_ids": "PFNO", "granularity": "hourly" })).unwrap();
+    assert_eq!(req.granularity, Some(Granularity::Hourly));
+    assert!(serde_json::from_value::<ForecastRequest>(
+        json!({ "station_ids": "PFNO", "granularity": "weekly" })
+    )
+    .is_err());
+}
@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use axum::Router;
+use duckdb::Connection;
 use log::{info, LevelFilter};
 use mockall::mock;
 use nostr_sdk::{
@@ -8,23 +9,33 @@ use nostr_sdk::{
     Event, EventBuilder, Keys, Url,
 };
 use oracle::{
-    app, create_folder, oracle::Oracle, setup_logger, AppState, Database, FileData, WeatherData,
+    app, build_app_state_with, create_folder,
+    oracle::{NonceDerivation, Oracle, DEFAULT_MAX_EVENT_LOCATIONS},
+    setup_logger,
+    weather_data::WeatherAccess,
+    AppBackends, AppState, CustomQueryLimits, Database, FileAccess, FileData, FixedClock,
+    OutcomeMessageVersion, WeatherData, DEFAULT_MAX_BATCH_EVENTS, DEFAULT_MAX_BODY_SIZE_KIB,
 };
+use parquet::{file::writer::SerializedFileWriter, schema::parser::parse_message_type};
 use rand::Rng;
 use std::{
-    collections::HashMap,
     str::FromStr,
-    sync::{Arc, Mutex, Once},
+    sync::{Arc, Once},
 };
 
 pub struct TestApp {
     pub app: Router,
+    pub state: Arc<AppState>,
     pub oracle: Arc<Oracle>,
+    pub db: Arc<Database>,
 }
 static INIT_LOGGER: Once = Once::new();
 fn init_logger() {
     INIT_LOGGER.call_once(|| {
-        setup_logger().level(LevelFilter::Debug).apply().unwrap();
+        setup_logger(false)
+            .level(LevelFilter::Debug)
+            .apply()
+            .unwrap();
     });
 }
 
@@ -33,7 +44,116 @@ pub fn random_test_number() -> i32 {
     rng.gen_range(10000..99999)
 }
 
+/// Forecast columns as the daemon writes them, upload tests need a file that passes schema checks
+pub const FORECAST_MESSAGE_TYPE: &str = "message forecast {
+    required binary station_id (UTF8);
+    required binary station_name (UTF8);
+    required double latitude;
+    required double longitude;
+    required binary generated_at (UTF8);
+    required binary begin_time (UTF8);
+    required binary end_time (UTF8);
+    optional int64 max_temp;
+    optional int64 min_temp;
+    required binary temperature_unit_code (UTF8);
+    optional int64 wind_speed;
+    required binary wind_speed_unit_code (UTF8);
+    optional int64 wind_direction;
+    required binary wind_direction_unit_code (UTF8);
+    optional int64 relative_humidity_max;
+    optional int64 relative_humidity_min;
+    required binary relative_humidity_unit_code (UTF8);
+    optional double liquid_precipitation_amt;
+    required binary liquid_precipitation_unit_code (UTF8);
+    optional int64 twelve_hour_probability_of_precipitation;
+    required binary twelve_hour_probability_of_precipitation_unit_code (UTF8);
+    required binary state (UTF8);
+    required binary iata_id (UTF8);
+    optional double elevation_m;
+    optional double snow_amt;
+    required binary snow_amt_unit_code (UTF8);
+    optional double snow_ratio;
+    required binary snow_ratio_unit_code (UTF8);
+    optional double ice_amt;
+    required binary ice_amt_unit_code (UTF8);
+}";
+
+/// An empty parquet file with the given schema
+pub fn parquet_bytes(message_type: &str) -> Vec<u8> {
+    let schema = Arc::new(parse_message_type(message_type).unwrap());
+    let writer = SerializedFileWriter::new(Vec::new(), schema, Default::default()).unwrap();
+    writer.into_inner().unwrap()
+}
+
+/// A fresh weather data folder plus a DuckDB connection to write its parquet files with.
+/// Panics when DuckDB's parquet extension can't be loaded so a broken setup fails the test
+/// instead of passing it.
+pub struct WeatherFixture {
+    conn: Connection,
+    pub data_dir: String,
+}
+
+impl Default for WeatherFixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WeatherFixture {
+    pub fn new() -> Self {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("INSTALL parquet; LOAD parquet;")
+            .expect("duckdb parquet extension");
+        let data_dir = format!("./test_data/weather_{}", random_test_number());
+        create_folder(&data_dir);
+        Self { conn, data_dir }
+    }
+
+    /// Creates the folder for `date` (YYYY-MM-DD) and returns its path
+    pub fn day_dir(&self, date: &str) -> String {
+        let day_dir = format!("{}/{}", self.data_dir, date);
+        create_folder(&day_dir);
+        day_dir
+    }
+
+    pub fn write(&self, sql: &str) {
+        self.conn.execute_batch(sql).unwrap();
+    }
+
+    pub fn file_access(&self) -> Arc<FileAccess> {
+        Arc::new(FileAccess::new(self.data_dir.clone()))
+    }
+
+    pub fn weather_access(&self) -> WeatherAccess {
+        WeatherAccess::new(self.file_access()).unwrap()
+    }
+}
+
 pub async fn spawn_app(weather_db: Arc<dyn WeatherData>) -> TestApp {
+    spawn_app_with_file_access(weather_db, Arc::new(MockFileAccess::new())).await
+}
+
+/// An app whose oracle reads the time from `clock`, so a test can add entries before an
+/// event's cutoff and then advance the clock past its signing date before running the ETL
+pub async fn spawn_app_with_clock(
+    weather_db: Arc<dyn WeatherData>,
+    clock: Arc<FixedClock>,
+) -> TestApp {
+    spawn(weather_db, Arc::new(MockFileAccess::new()), Some(clock)).await
+}
+
+pub async fn spawn_app_with_file_access(
+    weather_db: Arc<dyn WeatherData>,
+    file_access: Arc<dyn FileData>,
+) -> TestApp {
+    spawn(weather_db, file_access, None).await
+}
+
+async fn spawn(
+    weather_db: Arc<dyn WeatherData>,
+    file_access: Arc<dyn FileData>,
+    clock: Option<Arc<FixedClock>>,
+) -> TestApp {
     init_logger();
     create_folder("./test_data");
     let random_test_number = random_test_number();
@@ -45,23 +165,46 @@ pub async fn spawn_app(weather_db: Arc<dyn WeatherData>) -> TestApp {
 
     let db = Arc::new(Database::new(&event_data).await.unwrap());
     let private_key_file_path = String::from("./oracle_private_key.pem");
-    let oracle = Arc::new(
-        Oracle::new(db, weather_db.clone(), &private_key_file_path)
-            .await
-            .unwrap(),
-    );
-
-    let app_state = AppState {
-        static_dir: String::from("./static"),
-        remote_url: String::from("http://127.0.0.1:9100"),
-        weather_db,
-        file_access: Arc::new(MockFileAccess::new()),
-        oracle: oracle.clone(),
-        forecast_cache: Arc::new(Mutex::new(HashMap::new())),
-    };
+    let mut app_state = build_app_state_with(
+        String::from("http://127.0.0.1:9100"),
+        String::from("./static"),
+        private_key_file_path.clone(),
+        NonceDerivation::default(),
+        OutcomeMessageVersion::CURRENT,
+        time::Duration::ZERO,
+        DEFAULT_MAX_EVENT_LOCATIONS,
+        DEFAULT_MAX_BATCH_EVENTS,
+        DEFAULT_MAX_BODY_SIZE_KIB * 1024,
+        CustomQueryLimits::default(),
+        None,
+        AppBackends {
+            file_access,
+            weather_db: weather_db.clone(),
+            event_store: db.clone(),
+        },
+    )
+    .await
+    .unwrap();
+    if let Some(clock) = clock {
+        // Same defaults as above, only the time source differs
+        app_state.oracle = Arc::new(
+            Oracle::new(db.clone(), weather_db, &private_key_file_path)
+                .await
+                .unwrap()
+                .with_clock(clock),
+        );
+    }
+    let oracle = app_state.oracle.clone();
+    // Shares the cache and readiness flag with the router's copy
+    let state = Arc::new(app_state.clone());
     let app = app(app_state);
 
-    TestApp { app, oracle }
+    TestApp {
+        app,
+        state,
+        oracle,
+        db,
+    }
 }
 
 mock! {
@@ -73,6 +216,8 @@ mock! {
         fn build_file_paths(&self, file_names: Vec<String>) -> Vec<String>;
         fn build_file_path(&self, filename: &str, file_generated_at: time::OffsetDateTime) -> String;
         async fn download_file(&self, filename: &str, file_generated_at: time::OffsetDateTime) -> Result<axum::body::Body, oracle::Error>;
+        async fn file_size(&self, filename: &str, file_generated_at: time::OffsetDateTime) -> Result<u64, oracle::Error>;
+        async fn download_file_range(&self, filename: &str, file_generated_at: time::OffsetDateTime, offset: u64, length: u64) -> Result<axum::body::Body, oracle::Error>;
     }
 }
 
@@ -96,6 +241,25 @@ mock! {
             station_ids: Vec<String>,
         ) -> Result<Vec<oracle::DailyObservation>, oracle::weather_data::Error>;
         async fn stations(&self) -> Result<Vec<oracle::Station>, oracle::weather_data::Error>;
+        async fn stations_with_data(
+            &self,
+            date: time::Date,
+            file_type: oracle::FileType,
+        ) -> Result<Vec<String>, oracle::weather_data::Error>;
+        async fn custom_query(
+            &self,
+            req: &oracle::CustomQueryRequest,
+            limits: oracle::CustomQueryLimits,
+        ) -> Result<oracle::CustomQueryRows, oracle::weather_data::Error>;
+        async fn forecasts_parquet(
+            &self,
+            req: &oracle::ForecastRequest,
+            station_ids: Vec<String>,
+        ) -> Result<(axum::body::Body, oracle::QueryStats), oracle::weather_data::Error>;
+        async fn observation_coverage(
+            &self,
+            req: &oracle::CoverageRequest,
+        ) -> Result<Vec<oracle::ObservationCoverage>, oracle::weather_data::Error>;
     }
 }
 
@@ -0,0 +1,117 @@
+use crate::tls::{write_tls_files, CERT, KEY};
+use axum::{
+    body::{to_bytes, Body},
+    extract::ConnectInfo,
+    routing::get,
+    Router,
+};
+use clap::Parser;
+use hyper::{client::conn::http2, Request, StatusCode, Version};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use oracle::{load_tls_config, serve_h2c, serve_tls, Cli};
+use rustls::{
+    crypto::ring,
+    pki_types::{pem::PemObject, CertificateDer, ServerName},
+    ClientConfig, RootCertStore,
+};
+use std::{fs, net::SocketAddr, sync::Arc};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+use tokio_rustls::TlsConnector;
+
+fn peer_app() -> Router {
+    Router::new().route(
+        "/peer",
+        get(|ConnectInfo(peer): ConnectInfo<SocketAddr>| async move { peer.ip().to_string() }),
+    )
+}
+
+/// Sends `GET /peer` over an HTTP/2 connection, returning the status, version and body
+async fn get_peer<T>(io: T, authority: &str) -> (StatusCode, Version, String)
+where
+    T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let (mut sender, connection) = http2::handshake(TokioExecutor::new(), TokioIo::new(io))
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let request = Request::get(format!("https://{}/peer", authority))
+        .body(Body::empty())
+        .unwrap();
+    let response = sender.send_request(request).await.unwrap();
+    let (status, version) = (response.status(), response.version());
+    let body = to_bytes(Body::new(response.into_body()), usize::MAX)
+        .await
+        .unwrap();
+    (status, version, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn can_complete_h2c_request() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown, stop) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_h2c(listener, peer_app(), async {
+        stop.await.ok();
+    }));
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (status, version, body) = get_peer(stream, &addr.to_string()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(version, Version::HTTP_2);
+    // the peer address still reaches handlers through ConnectInfo
+    assert_eq!(body, "127.0.0.1");
+
+    shutdown.send(()).unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn negotiates_h2_over_tls() {
+    let (dir, cert_path, key_path) = write_tls_files(CERT, KEY);
+    let tls_config = load_tls_config(&cert_path, &key_path, true).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown, stop) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_tls(listener, tls_config, peer_app(), async {
+        stop.await.ok();
+    }));
+
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from_pem_file(&cert_path).unwrap())
+        .unwrap();
+    let mut client_config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    client_config.alpn_protocols = vec![b"h2".to_vec()];
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let stream = TlsConnector::from(Arc::new(client_config))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+
+    let (status, version, body) = get_peer(stream, "localhost").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(version, Version::HTTP_2);
+    assert_eq!(body, "127.0.0.1");
+
+    shutdown.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn http2_is_off_by_default() {
+    assert!(!Cli::parse_from(["oracle"]).http2());
+    assert!(Cli::parse_from(["oracle", "--http2"]).http2());
+    assert!(!Cli::parse_from(["oracle", "--http2=false"]).http2());
+}
@@ -0,0 +1,157 @@
+use axum::{body::to_bytes, http::StatusCode, response::IntoResponse};
+use duckdb::Connection;
+use oracle::{
+    classify_remote_error,
+    weather_data::{self, httpfs_setup, WeatherAccess},
+    AppError, FileAccess, HttpRetrySettings, RemoteReadFailure,
+};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[test]
+fn can_skip_install_when_httpfs_already_installed() {
+    assert_eq!(httpfs_setup(true, true), None);
+    assert_eq!(httpfs_setup(true, false), Some("LOAD httpfs;"));
+    assert_eq!(
+        httpfs_setup(false, false),
+        Some("INSTALL httpfs; LOAD httpfs;")
+    );
+}
+
+#[test]
+#[ignore = "needs duckdb httpfs"]
+fn can_apply_http_retries_to_connection() {
+    let settings = HttpRetrySettings {
+        retries: 5,
+        timeout_secs: 10,
+        retry_wait_ms: 250,
+        backoff: 2.0,
+    };
+    let weather_access = WeatherAccess::new(Arc::new(FileAccess::new(String::from(
+        "./test_data/unused",
+    ))))
+    .unwrap()
+    .with_http_retries(Some(settings));
+    let conn = weather_access
+        .open_connection()
+        .expect("duckdb httpfs extension");
+
+    let (retries, wait_ms): (i64, i64) = conn
+        .query_row(
+            "SELECT current_setting('http_retries'), current_setting('http_retry_wait_ms')",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!((retries, wait_ms), (5, 250));
+}
+
+#[test]
+fn httpfs_is_left_unloaded_without_retries() {
+    let weather_access = WeatherAccess::new(Arc::new(FileAccess::new(String::from(
+        "./test_data/unused",
+    ))))
+    .unwrap();
+    let conn = weather_access
+        .open_connection()
+        .expect("duckdb parquet extension");
+    let loaded: bool = conn
+        .query_row(
+            "SELECT loaded FROM duckdb_extensions() WHERE extension_name = 'httpfs'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(!loaded);
+}
+
+#[test]
+fn rejects_invalid_http_retries() {
+    HttpRetrySettings::default().validate().unwrap();
+
+    let no_timeout = HttpRetrySettings {
+        timeout_secs: 0,
+        ..HttpRetrySettings::default()
+    };
+    assert!(no_timeout.validate().is_err());
+
+    let shrinking_wait = HttpRetrySettings {
+        backoff: 0.5,
+        ..HttpRetrySettings::default()
+    };
+    let err = shrinking_wait.validate().unwrap_err();
+    assert!(err.to_string().contains("backoff"));
+}
+
+#[test]
+fn tells_transient_remote_failures_from_missing_files() {
+    let cases = [
+        (
+            r#"IO Error: Unable to connect to URL "s3://weather/2024-08-12/forecasts.parquet": 503 (Service Unavailable)"#,
+            Some(RemoteReadFailure::Transient),
+        ),
+        (
+            "HTTP Error: HTTP GET error on 'http://localhost:4566/weather/forecasts.parquet' (HTTP 500)",
+            Some(RemoteReadFailure::Transient),
+        ),
+        (
+            "IO Error: Could not establish connection error for HTTP HEAD to 'https://weather.s3.amazonaws.com/forecasts.parquet'",
+            Some(RemoteReadFailure::Transient),
+        ),
+        (
+            "HTTP Error: HTTP GET error on 'https://weather.s3.amazonaws.com/observations_2024-01-14T04:44:22.404930703Z.parquet' (HTTP 404)",
+            Some(RemoteReadFailure::Missing),
+        ),
+        (
+            r#"IO Error: No files found that match the pattern "s3://weather/2024-08-12/*.parquet""#,
+            Some(RemoteReadFailure::Missing),
+        ),
+        // Retrying won't fix bad credentials
+        (
+            "HTTP Error: HTTP GET error on 'https://weather.s3.amazonaws.com/forecasts.parquet' (HTTP 403)",
+            None,
+        ),
+        // Local reads are never remote failures
+        (
+            r#"IO Error: No files found that match the pattern "./weather_data/2024-08-12/*.parquet""#,
+            None,
+        ),
+    ];
+    for (message, expected) in cases {
+        assert_eq!(classify_remote_error(message), expected, "{}", message);
+    }
+}
+
+async fn render(err: weather_data::Error) -> (StatusCode, Value) {
+    let response = AppError::WeatherData(err).into_response();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn remote_failures_map_to_retryable_and_not_found_statuses() {
+    let duckdb_error = || {
+        Connection::open_in_memory()
+            .unwrap()
+            .execute_batch("SELECT * FROM missing_table")
+            .unwrap_err()
+    };
+
+    let (status, body) = render(weather_data::Error::RemoteUnavailable(duckdb_error())).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        body["error"],
+        "weather files temporarily unavailable, retry later"
+    );
+
+    let (status, body) = render(weather_data::Error::RemoteMissing(duckdb_error())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "weather file not found");
+
+    // Anything else DuckDB reports stays an internal error
+    let err = weather_data::Error::from(duckdb_error());
+    assert!(matches!(err, weather_data::Error::Query(_)));
+    let (status, _) = render(err).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+}
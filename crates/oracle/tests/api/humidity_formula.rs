@@ -0,0 +1,48 @@
+use oracle::{HumidityFormula, MagnusCoefficients};
+
+/// (temperature °C, dewpoint °C, RH %) from Buck's (1981) over-ice vapour pressure equation
+const ICE_REFERENCE: [(f64, f64, f64); 2] = [(-10.0, -15.0, 63.60), (-20.0, -25.0, 61.29)];
+
+#[test]
+fn ice_coefficients_match_reference_below_freezing() {
+    for (temperature, dewpoint, reference) in ICE_REFERENCE {
+        let ice = MagnusCoefficients::ICE.relative_humidity(temperature, dewpoint);
+        let water = MagnusCoefficients::WATER.relative_humidity(temperature, dewpoint);
+
+        assert!(
+            (ice - reference).abs() < 0.05,
+            "ice {} vs {}",
+            ice,
+            reference
+        );
+        // The water set overestimates by around 3% RH at these temperatures
+        assert!(water - reference > 3.0, "water {} vs {}", water, reference);
+    }
+}
+
+#[test]
+fn ice_below_freezing_only_switches_under_zero() {
+    let formula = HumidityFormula::IceBelowFreezing;
+
+    assert_eq!(formula.coefficients(-0.5), MagnusCoefficients::ICE);
+    assert_eq!(formula.coefficients(0.0), MagnusCoefficients::WATER);
+    assert_eq!(
+        formula.relative_humidity(5.0, 0.0),
+        HumidityFormula::Water.relative_humidity(5.0, 0.0)
+    );
+    assert!((formula.relative_humidity(-10.0, -15.0) - 63.60).abs() < 0.05);
+    assert_eq!(
+        HumidityFormula::Water.coefficients(-10.0),
+        MagnusCoefficients::WATER
+    );
+}
+
+#[test]
+fn humidity_formula_parses_from_config_names() {
+    assert_eq!(
+        "ice-below-freezing".parse::<HumidityFormula>().unwrap(),
+        HumidityFormula::IceBelowFreezing
+    );
+    assert_eq!(HumidityFormula::default().to_string(), "water");
+    assert!("magnus".parse::<HumidityFormula>().is_err());
+}
@@ -0,0 +1,44 @@
+use log::{Level, Record};
+use oracle::json_log_line;
+use serde_json::Value;
+
+#[test]
+fn json_log_line_includes_record_fields_and_key_values() {
+    let fields: &[(&str, &str)] = &[("method", "GET"), ("path", "/oracle/events")];
+    let line = json_log_line(
+        &Record::builder()
+            .level(Level::Info)
+            .target("http_request")
+            .args(format_args!("new request, GET /oracle/events"))
+            .key_values(&fields)
+            .build(),
+    );
+
+    assert!(!line.contains('\n'));
+    let parsed: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(parsed["level"], "INFO");
+    assert_eq!(parsed["target"], "http_request");
+    assert_eq!(parsed["message"], "new request, GET /oracle/events");
+    assert_eq!(parsed["method"], "GET");
+    assert_eq!(parsed["path"], "/oracle/events");
+    assert!(parsed["timestamp"]
+        .as_str()
+        .is_some_and(|ts| !ts.is_empty()));
+}
+
+#[test]
+fn json_log_line_keeps_numeric_key_values_as_numbers() {
+    let fields: &[(&str, u64)] = &[("status", 200), ("elapsed_ms", 12)];
+    let line = json_log_line(
+        &Record::builder()
+            .level(Level::Info)
+            .target("http_response")
+            .args(format_args!("response"))
+            .key_values(&fields)
+            .build(),
+    );
+
+    let parsed: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(parsed["status"], 200);
+    assert_eq!(parsed["elapsed_ms"], 12);
+}
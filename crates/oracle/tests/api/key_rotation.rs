@@ -0,0 +1,183 @@
+use crate::helpers::{random_test_number, MockWeatherAccess};
+use dlctix::{attestation_secret, secp::Point};
+use nostr_sdk::Keys;
+use oracle::{
+    create_folder,
+    oracle::{get_winning_bytes, Error, Oracle},
+    Clock, CreateEvent, Database, EventStore, FixedClock, ScoringField,
+};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+struct RotationTest {
+    db: Arc<Database>,
+    clock: Arc<FixedClock>,
+    folder: String,
+}
+
+impl RotationTest {
+    async fn new() -> Self {
+        create_folder("./test_data");
+        let folder = format!("./test_data/{}", random_test_number());
+        let event_data = format!("{}/event_data", folder);
+        create_folder(&event_data);
+        let db = Arc::new(Database::new(&event_data).await.unwrap());
+        let clock = Arc::new(FixedClock::new(OffsetDateTime::now_utc()));
+        Self { db, clock, folder }
+    }
+
+    /// Missing key files are generated on first use
+    fn key_path(&self, name: &str) -> String {
+        format!("{}/{}.pem", self.folder, name)
+    }
+
+    async fn oracle(&self, key: &str) -> Result<Oracle, Error> {
+        Oracle::new(
+            self.db.clone(),
+            Arc::new(MockWeatherAccess::new()),
+            &self.key_path(key),
+        )
+        .await
+        .map(|oracle| oracle.with_clock(self.clock.clone()))
+    }
+
+    async fn rotate(&self, key: &str) -> Result<oracle::OracleKey, Error> {
+        Oracle::rotate_key(self.db.clone(), &self.key_path(key), self.clock.now()).await
+    }
+}
+
+async fn create_event(oracle: &Oracle) -> Uuid {
+    let now = OffsetDateTime::now_utc();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(3),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(3),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    oracle
+        .create_event(Keys::generate().public_key, new_event, true)
+        .await
+        .unwrap()
+        .id
+}
+
+/// Attests that the second entry won using `signer`'s private key
+async fn sign_event(db: &Database, signer: &Oracle, event_id: Uuid) {
+    let mut event = db
+        .get_events_to_sign(vec![event_id], signer.now())
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    event.attestation = Some(attestation_secret(
+        signer.raw_private_key(),
+        event.nonce,
+        get_winning_bytes(vec![1], event.outcome_message_version),
+    ));
+    db.update_event_attestation(&event, signer.now())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn rotation_is_rejected_while_events_are_unsigned() {
+    let test = RotationTest::new().await;
+    let old_oracle = test.oracle("old_key").await.unwrap();
+    let event_id = create_event(&old_oracle).await;
+
+    let result = test.rotate("new_key").await;
+    assert!(matches!(result, Err(Error::KeyRotation(_))));
+    assert_eq!(test.db.get_oracle_keys().await.unwrap().len(), 1);
+
+    sign_event(&test.db, &old_oracle, event_id).await;
+    assert!(test.rotate("new_key").await.is_ok());
+}
+
+#[tokio::test]
+async fn attestations_verify_under_the_key_active_at_creation() {
+    let test = RotationTest::new().await;
+    let old_oracle = test.oracle("old_key").await.unwrap();
+    let old_event = create_event(&old_oracle).await;
+    sign_event(&test.db, &old_oracle, old_event).await;
+    // keys are tracked to the second, keep the old event out of the new key's range
+    test.clock.advance(Duration::seconds(2));
+
+    let rotated = test.rotate("new_key").await.unwrap();
+    assert!(matches!(
+        test.oracle("old_key").await,
+        Err(Error::MismatchPubkey(_))
+    ));
+    let new_oracle = test.oracle("new_key").await.unwrap();
+    assert_eq!(
+        rotated.pubkey,
+        new_oracle.raw_public_key().x_only_public_key().0
+    );
+
+    let keys = test.db.get_oracle_keys().await.unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(
+        keys[0].pubkey,
+        old_oracle.raw_public_key().x_only_public_key().0
+    );
+    assert!(keys[0].active_until.is_some());
+    assert_eq!(keys[1], rotated);
+
+    assert!(
+        new_oracle
+            .verify_attestation(&old_event)
+            .await
+            .unwrap()
+            .valid
+    );
+    // retired keys are stored x-only, the announcement only keeps the old key up to parity
+    let announcement = new_oracle.get_event_announcement(&old_event).await.unwrap();
+    assert_eq!(
+        announcement.oracle_pubkey[2..],
+        hex::encode(Point::from(old_oracle.raw_public_key()).serialize_xonly())
+    );
+
+    let new_event = create_event(&new_oracle).await;
+    sign_event(&test.db, &new_oracle, new_event).await;
+    assert!(
+        new_oracle
+            .verify_attestation(&new_event)
+            .await
+            .unwrap()
+            .valid
+    );
+
+    // an attestation from the retired key no longer unlocks new events
+    let forged_event = create_event(&new_oracle).await;
+    sign_event(&test.db, &old_oracle, forged_event).await;
+    assert!(
+        !new_oracle
+            .verify_attestation(&forged_event)
+            .await
+            .unwrap()
+            .valid
+    );
+}
+
+#[tokio::test]
+async fn rotation_to_active_or_retired_key_is_rejected() {
+    let test = RotationTest::new().await;
+    test.oracle("old_key").await.unwrap();
+
+    let result = test.rotate("old_key").await;
+    assert!(matches!(result, Err(Error::KeyRotation(_))));
+
+    test.rotate("new_key").await.unwrap();
+    let result = test.rotate("old_key").await;
+    assert!(matches!(result, Err(Error::KeyRotation(_))));
+    assert_eq!(test.db.get_oracle_keys().await.unwrap().len(), 2);
+}
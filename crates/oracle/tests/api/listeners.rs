@@ -0,0 +1,100 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use oracle::{bind_listeners, serve_listeners, ListenerConfig, RouteScope};
+use std::{net::SocketAddr, sync::Arc};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    sync::oneshot,
+};
+
+/// Status code of a plain HTTP/1.1 GET
+async fn get_status(addr: SocketAddr, path: &str) -> u16 {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(
+            format!(
+                "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+                path
+            )
+            .as_bytes(),
+        )
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .unwrap()
+}
+
+#[test]
+fn parses_listener_configs() {
+    assert_eq!(
+        "127.0.0.1:9801=admin".parse::<ListenerConfig>().unwrap(),
+        ListenerConfig {
+            addr: "127.0.0.1:9801".parse().unwrap(),
+            scope: RouteScope::Admin,
+        }
+    );
+    assert_eq!(
+        "0.0.0.0:9800".parse::<ListenerConfig>().unwrap().scope,
+        RouteScope::Public
+    );
+    assert!("localhost=admin".parse::<ListenerConfig>().is_err());
+    assert!("127.0.0.1:9801=internal".parse::<ListenerConfig>().is_err());
+    assert!("127.0.0.1:9801=all".parse::<ListenerConfig>().is_err());
+}
+
+#[tokio::test]
+async fn serves_each_listener_its_own_routes() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let configs = [
+        "127.0.0.1:0=public".parse::<ListenerConfig>().unwrap(),
+        "127.0.0.1:0=admin".parse::<ListenerConfig>().unwrap(),
+    ];
+    let listeners = bind_listeners(&configs).await.unwrap();
+    let addrs: Vec<SocketAddr> = listeners
+        .iter()
+        .map(|(_, listener)| listener.local_addr().unwrap())
+        .collect();
+    let (shutdown, stop) = oneshot::channel::<()>();
+    let server = tokio::spawn(serve_listeners(
+        listeners,
+        (*test_app.state).clone(),
+        None,
+        false,
+        async {
+            stop.await.ok();
+        },
+    ));
+
+    let (public, admin) = (addrs[0], addrs[1]);
+    assert_eq!(get_status(public, "/livez").await, 200);
+    assert_eq!(get_status(public, "/oracle/pubkey").await, 200);
+    assert_eq!(get_status(admin, "/livez").await, 200);
+    assert_eq!(get_status(admin, "/oracle/pubkey").await, 404);
+
+    // GET on a POST-only route is 405 where it's routed and 404 where it isn't
+    assert_eq!(get_status(admin, "/admin/db/maintenance").await, 405);
+    assert_eq!(get_status(public, "/admin/db/maintenance").await, 404);
+
+    shutdown.send(()).unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn fails_when_any_bind_fails() {
+    let taken = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let configs = [
+        "127.0.0.1:0=public".parse::<ListenerConfig>().unwrap(),
+        ListenerConfig {
+            addr: taken.local_addr().unwrap(),
+            scope: RouteScope::Admin,
+        },
+    ];
+
+    let err = bind_listeners(&configs).await.unwrap_err();
+    assert!(err.to_string().contains("error binding"));
+}
@@ -1,7 +1,82 @@
+mod app_backends;
 mod attestation;
+mod available_stations;
+mod body_limit;
+mod clone_event;
+mod compare_forecasts;
 mod create_event;
 mod create_event_entry;
+mod custom_query;
+mod data_generated_at;
+mod data_layout;
+mod database_settings;
+mod day_time_zone;
+mod db_maintenance;
+mod dominant_precip;
+mod duckdb_extensions;
+mod empty_data_source;
+mod entry_cutoff;
+mod entry_limit;
+mod error_codes;
 mod etl_workflow;
+mod event_artifacts;
+mod event_audit;
+mod event_changes;
+mod event_cleanup;
+mod event_export;
+mod event_locations;
+mod event_schema;
+mod event_stats;
+mod event_status;
+mod file_download;
+mod forecast_aggregation;
+mod forecast_as_of;
+mod forecast_fields;
+mod forecast_parquet;
+mod forecast_precip;
+mod forecast_single_flight;
+mod get_event_weather;
 mod get_events;
+mod get_events_batch;
+mod get_files;
+mod granularity;
 mod helpers;
+mod http2;
+mod http_retries;
+mod humidity_formula;
+mod json_logging;
+mod key_rotation;
+mod listeners;
+mod materialize_daily;
+mod nonce_derivation;
+mod observation_coverage;
+mod observation_temp_agg;
+mod observation_wind_agg;
+mod oracle_info;
+mod outcome_message_version;
+mod par_tolerance;
+#[cfg(feature = "postgres")]
+mod postgres_store;
+mod precip_codes;
+mod precip_unit;
+mod print_config;
+mod query_settings;
+mod readiness;
+#[cfg(feature = "localstack")]
+mod s3_retries;
+mod sanity_bounds;
+mod scalar_encoding;
+mod scoring_mode;
+mod settlement_delay;
+mod sql_timestamp;
+mod station_aliases;
+mod station_lookup;
+mod tls;
 mod ui_fragments;
+mod upload_checksum;
+mod upload_schema;
+mod validate_config;
+mod verify_attestation;
+mod version;
+mod weather_query_stats;
+mod wind_unit;
@@ -0,0 +1,203 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, DayTimeZone, ForecastAggregation, ForecastFields, ForecastRequest,
+    ObservationRequest, ObservationTempAggregation, ObservationWindAggregation, PrecipUnit,
+    TemperatureUnit, WeatherData, DAILY_FOLDER,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use time::macros::{date, datetime};
+
+/// Two days of forecasts and observations for PFNO and PAEG, each day's forecast generated
+/// the day before.
+fn weather_fixture() -> (String, WeatherAccess) {
+    let fixture = WeatherFixture::new();
+    for (generated, day) in [("2024-08-11", "2024-08-12"), ("2024-08-12", "2024-08-13")] {
+        let generated_dir = fixture.day_dir(generated);
+        let day_dir = fixture.day_dir(day);
+        fixture.write(&format!(
+            r#"
+            COPY (
+                SELECT * FROM (VALUES
+                    ('PFNO', '{day}T00:00:00Z', '{day}T12:00:00Z', 50, 61, 4, 0.1),
+                    ('PFNO', '{day}T12:00:00Z', '{day}T23:00:00Z', 55, 72, 12, 0.25),
+                    ('PAEG', '{day}T00:00:00Z', '{day}T12:00:00Z', 30, 41, 20, NULL),
+                    ('PAEG', '{day}T12:00:00Z', '{day}T23:00:00Z', 28, 39, 25, 0.4)
+                ) AS t(station_id, begin_time, end_time, min_temp, max_temp, wind_speed, liquid_precipitation_amt)
+                CROSS JOIN (SELECT 'fahrenheit' AS temperature_unit_code, '{generated}T12:00:00Z' AS generated_at)
+            ) TO '{generated_dir}/forecasts_{generated}T12:00:00Z.parquet' (FORMAT PARQUET);
+            COPY (
+                SELECT * FROM (VALUES
+                    ('PFNO', '{day}T01:53:00Z', 10.0, 5, 180, 4.0, 0.0),
+                    ('PFNO', '{day}T13:53:00Z', 21.5, 9, 200, 8.0, 0.12),
+                    ('PAEG', '{day}T05:53:00Z', -1.0, 14, 20, -3.0, 0.05),
+                    ('PAEG', '{day}T17:53:00Z', 3.0, 22, 40, -2.0, NULL)
+                ) AS t(station_id, generated_at, temperature_value, wind_speed, wind_direction, dewpoint_value, precip_in)
+                CROSS JOIN (SELECT 'celsius' AS temperature_unit_code)
+            ) TO '{day_dir}/observations_{day}T23:00:00Z.parquet' (FORMAT PARQUET);
+            "#
+        ));
+    }
+
+    let weather_access = fixture.weather_access();
+    (fixture.data_dir, weather_access)
+}
+
+fn forecast_request() -> ForecastRequest {
+    ForecastRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-14 00:00:00 UTC)),
+        generated_start: None,
+        generated_end: None,
+        as_of: None,
+        station_ids: String::from("PFNO,PAEG"),
+        temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        agg: ForecastAggregation::MinMax,
+        fields: ForecastFields::default(),
+        granularity: None,
+    }
+}
+
+fn observation_request() -> ObservationRequest {
+    ObservationRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-14 00:00:00 UTC)),
+        station_ids: String::from("PFNO,PAEG"),
+        temperature_unit: TemperatureUnit::Fahrenheit,
+        tz: DayTimeZone::Utc,
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        granularity: None,
+    }
+}
+
+/// Rows as JSON sorted by station and date, query order isn't fixed
+fn sorted<T: Serialize>(rows: &[T]) -> Vec<Value> {
+    let mut rows: Vec<Value> = rows
+        .iter()
+        .map(|row| serde_json::to_value(row).unwrap())
+        .collect();
+    rows.sort_by_key(|row| (row["station_id"].to_string(), row["date"].to_string()));
+    rows
+}
+
+#[tokio::test]
+async fn materialized_days_match_on_the_fly_results() {
+    let (data_dir, weather_access) = weather_fixture();
+    let forecasts = forecast_request();
+    let observations = observation_request();
+    let station_ids = forecasts.station_ids();
+
+    let on_the_fly_forecasts = weather_access
+        .forecasts_data(&forecasts, station_ids.clone())
+        .await
+        .unwrap();
+    let on_the_fly_observations = weather_access
+        .daily_observations(&observations, station_ids.clone())
+        .await
+        .unwrap();
+    assert_eq!(on_the_fly_forecasts.len(), 4);
+    assert_eq!(on_the_fly_observations.len(), 4);
+
+    let report = weather_access
+        .materialize_daily(date!(2024 - 08 - 12), date!(2024 - 08 - 13))
+        .await
+        .unwrap();
+    assert_eq!(report.forecast_days, 2);
+    assert_eq!(report.observation_days, 2);
+
+    // With the raw files gone the results can only come from the materialized days
+    for day in ["2024-08-11", "2024-08-12", "2024-08-13"] {
+        std::fs::remove_dir_all(format!("{}/{}", data_dir, day)).unwrap();
+    }
+    let (materialized_forecasts, forecast_stats) = weather_access
+        .forecasts_data_with_stats(&forecasts, station_ids.clone())
+        .await
+        .unwrap();
+    let (materialized_observations, observation_stats) = weather_access
+        .daily_observations_with_stats(&observations, station_ids)
+        .await
+        .unwrap();
+
+    assert_eq!(forecast_stats.files, Some(2));
+    assert_eq!(observation_stats.files, Some(2));
+    assert_eq!(
+        sorted(&materialized_forecasts),
+        sorted(&on_the_fly_forecasts)
+    );
+    assert_eq!(
+        sorted(&materialized_observations),
+        sorted(&on_the_fly_observations)
+    );
+}
+
+#[tokio::test]
+async fn partial_days_and_missing_days_use_raw_files() {
+    let (data_dir, weather_access) = weather_fixture();
+    weather_access
+        .materialize_daily(date!(2024 - 08 - 12), date!(2024 - 08 - 12))
+        .await
+        .unwrap();
+    assert!(Path::new(&format!(
+        "{}/{}/daily_observations_2024-08-12.parquet",
+        data_dir, DAILY_FOLDER
+    ))
+    .exists());
+    std::fs::remove_file(format!(
+        "{}/2024-08-12/observations_2024-08-12T23:00:00Z.parquet",
+        data_dir
+    ))
+    .unwrap();
+    let daily_observations = |observations: ObservationRequest| {
+        let weather_access = &weather_access;
+        async move {
+            weather_access
+                .daily_observations(&observations, observations.station_ids())
+                .await
+                .unwrap()
+                .len()
+        }
+    };
+
+    let whole_day = ObservationRequest {
+        end: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        ..observation_request()
+    };
+    assert_eq!(daily_observations(whole_day.clone()).await, 2);
+
+    // 08-13 was never materialized, only its raw file is left
+    let observations = daily_observations(observation_request()).await;
+    assert_eq!(observations, 2);
+
+    let ends_mid_day = ObservationRequest {
+        end: Some(datetime!(2024-08-12 18:00:00 UTC)),
+        ..observation_request()
+    };
+    assert_eq!(daily_observations(ends_mid_day).await, 0);
+
+    // Materialized days are bucketed in UTC only
+    let eastern_day = ObservationRequest {
+        tz: DayTimeZone::Offset(-5 * 3600),
+        ..whole_day
+    };
+    assert_eq!(daily_observations(eastern_day).await, 0);
+}
+
+#[tokio::test]
+async fn rejects_unfinished_days() {
+    let (_, weather_access) = weather_fixture();
+    let today = time::OffsetDateTime::now_utc().date();
+    assert!(weather_access
+        .materialize_daily(today, today)
+        .await
+        .is_err());
+    assert!(weather_access
+        .materialize_daily(date!(2024 - 08 - 13), date!(2024 - 08 - 12))
+        .await
+        .is_err());
+}
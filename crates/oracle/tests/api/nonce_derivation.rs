@@ -0,0 +1,148 @@
+use crate::helpers::{random_test_number, MockWeatherAccess};
+use dlctix::musig2::secp256k1::SecretKey;
+use nostr_sdk::Keys;
+use oracle::{
+    create_folder,
+    oracle::{Error, NonceDerivation, Oracle},
+    CreateEvent, Database, FixedClock, ScoringField, ThreadRng,
+};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+async fn deterministic_oracle() -> Oracle {
+    deterministic_oracle_at(Arc::new(FixedClock::new(OffsetDateTime::now_utc()))).await
+}
+
+async fn deterministic_oracle_at(clock: Arc<FixedClock>) -> Oracle {
+    create_folder("./test_data");
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+    let db = Arc::new(Database::new(&event_data).await.unwrap());
+    Oracle::new(
+        db,
+        Arc::new(MockWeatherAccess::new()),
+        &String::from("./oracle_private_key.pem"),
+    )
+    .await
+    .unwrap()
+    .with_nonce_derivation(NonceDerivation::Deterministic)
+    .with_clock(clock)
+}
+
+fn new_event(id: Uuid) -> CreateEvent {
+    let now = OffsetDateTime::now_utc();
+    CreateEvent {
+        id,
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(3),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(3),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    }
+}
+
+#[test]
+fn deterministic_nonce_depends_only_on_key_and_event() {
+    let key = SecretKey::from_slice(&[7; 32]).unwrap();
+    let other_key = SecretKey::from_slice(&[8; 32]).unwrap();
+    let event_id = Uuid::now_v7();
+    let derivation = NonceDerivation::Deterministic;
+
+    assert_eq!(
+        derivation.nonce(&key, &event_id, &ThreadRng),
+        derivation.nonce(&key, &event_id, &ThreadRng)
+    );
+    assert_ne!(
+        derivation.nonce(&key, &event_id, &ThreadRng),
+        derivation.nonce(&key, &Uuid::now_v7(), &ThreadRng)
+    );
+    assert_ne!(
+        derivation.nonce(&key, &event_id, &ThreadRng),
+        derivation.nonce(&other_key, &event_id, &ThreadRng)
+    );
+    assert_ne!(
+        NonceDerivation::Random.nonce(&key, &event_id, &ThreadRng),
+        NonceDerivation::Random.nonce(&key, &event_id, &ThreadRng)
+    );
+}
+
+#[test]
+fn nonce_derivation_parses_from_config() {
+    assert_eq!(
+        "deterministic".parse::<NonceDerivation>().unwrap(),
+        NonceDerivation::Deterministic
+    );
+    assert_eq!(
+        "Random".parse::<NonceDerivation>().unwrap(),
+        NonceDerivation::Random
+    );
+    assert!("rfc6979".parse::<NonceDerivation>().is_err());
+    assert_eq!(NonceDerivation::default(), NonceDerivation::Random);
+}
+
+#[tokio::test]
+async fn deterministic_oracles_announce_the_same_event() {
+    let keys = Keys::generate();
+    let event_id = Uuid::now_v7();
+    let first = deterministic_oracle().await;
+    let second = deterministic_oracle().await;
+
+    let announced = first
+        .create_event(keys.public_key, new_event(event_id), true)
+        .await
+        .unwrap();
+    let rebuilt = second
+        .create_event(keys.public_key, new_event(event_id), true)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        announced.nonce,
+        NonceDerivation::Deterministic.nonce(&first.raw_private_key(), &event_id, &ThreadRng)
+    );
+    assert_eq!(announced.nonce, rebuilt.nonce);
+    assert_eq!(announced.event_announcement, rebuilt.event_announcement);
+}
+
+#[tokio::test]
+async fn deterministic_oracle_refuses_ids_of_purged_events() {
+    let keys = Keys::generate();
+    let event_id = Uuid::now_v7();
+    let clock = Arc::new(FixedClock::new(OffsetDateTime::now_utc()));
+    let oracle = deterministic_oracle_at(clock.clone()).await;
+    oracle
+        .create_event(keys.public_key, new_event(event_id), true)
+        .await
+        .unwrap();
+
+    clock.advance(Duration::days(10));
+    assert_eq!(
+        oracle
+            .purge_expired_events(Duration::days(1))
+            .await
+            .unwrap(),
+        1
+    );
+
+    // the purged event may have been signed with this id's nonce
+    let result = oracle
+        .create_event(keys.public_key, new_event(event_id), true)
+        .await;
+    assert!(
+        matches!(&result, Err(Error::BadEvent(e)) if e.to_string().contains("deleted event")),
+        "{:?}",
+        result
+    );
+    oracle
+        .create_event(keys.public_key, new_event(Uuid::now_v7()), true)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,180 @@
+use crate::helpers::{spawn_app, TestApp, WeatherFixture};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use oracle::CoverageRequest;
+use serde_json::{from_slice, json, Value};
+use std::sync::Arc;
+use time::{format_description::well_known::Rfc3339, macros::datetime, Duration};
+use tower::ServiceExt;
+
+/// Hourly files for 2024-08-12, each holding the latest readings like the daemon writes them.
+/// KWELL reported every hour while KSPARSE reported three times in two hours, its last reading
+/// repeated in every file after it. KQUIET last reported the evening before.
+async fn spawn_with_fixture() -> TestApp {
+    let fixture = WeatherFixture::new();
+    write_file(
+        &fixture,
+        "2024-08-11T22:00:00Z",
+        &[("KQUIET", "2024-08-11T21:53:00Z")],
+    );
+    for hour in 1..=24 {
+        let file_at = datetime!(2024-08-12 00:00:00 UTC) + Duration::hours(hour);
+        let kwell = format!("2024-08-12T{:02}:53:00Z", hour - 1);
+        let mut rows = vec![("KWELL", kwell.as_str())];
+        match hour {
+            1..=3 => {}
+            4 => rows.extend([
+                ("KSPARSE", "2024-08-12T03:10:00Z"),
+                ("KSPARSE", "2024-08-12T03:40:00Z"),
+            ]),
+            5..=15 => rows.push(("KSPARSE", "2024-08-12T03:40:00Z")),
+            _ => rows.push(("KSPARSE", "2024-08-12T15:10:00Z")),
+        }
+        write_file(&fixture, &file_at.format(&Rfc3339).unwrap(), &rows);
+    }
+
+    spawn_app(Arc::new(fixture.weather_access())).await
+}
+
+/// Writes the observations file generated at `file_at` into its date folder
+fn write_file(fixture: &WeatherFixture, file_at: &str, readings: &[(&str, &str)]) {
+    let day_dir = fixture.day_dir(&file_at[..10]);
+    let rows: Vec<String> = readings
+        .iter()
+        .map(|(station_id, generated_at)| format!("('{}', '{}', 20.0)", station_id, generated_at))
+        .collect();
+    fixture.write(&format!(
+        "COPY (SELECT * FROM (VALUES {}) AS t(station_id, generated_at, temperature_value)) TO '{}/observations_{}.parquet' (FORMAT PARQUET);",
+        rows.join(", "),
+        day_dir,
+        file_at
+    ));
+}
+
+async fn get(test_app: &TestApp, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn reports_coverage_per_station_least_covered_first() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, body) = get(
+        &test_app,
+        "/observations/coverage?start=2024-08-12T00:00:00Z&end=2024-08-13T00:00:00Z",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!([
+            {
+                "station_id": "KQUIET",
+                "observations": 0,
+                "first_observed": null,
+                "last_observed": null,
+                "coverage": 0.0
+            },
+            {
+                "station_id": "KSPARSE",
+                "observations": 3,
+                "first_observed": "2024-08-12T03:10:00Z",
+                "last_observed": "2024-08-12T15:10:00Z",
+                "coverage": 2.0 / 24.0
+            },
+            {
+                "station_id": "KWELL",
+                "observations": 24,
+                "first_observed": "2024-08-12T00:53:00Z",
+                "last_observed": "2024-08-12T23:53:00Z",
+                "coverage": 1.0
+            }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn narrower_range_counts_only_its_hours() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, body) = get(
+        &test_app,
+        "/observations/coverage?start=2024-08-12T03:00:00Z&end=2024-08-12T07:00:00Z",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let coverage: Vec<(&str, f64)> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|station| {
+            (
+                station["station_id"].as_str().unwrap(),
+                station["coverage"].as_f64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        coverage,
+        vec![("KQUIET", 0.0), ("KSPARSE", 0.25), ("KWELL", 1.0)]
+    );
+}
+
+#[tokio::test]
+async fn rejects_missing_or_inverted_range() {
+    let test_app = spawn_with_fixture().await;
+
+    let (status, _) = get(
+        &test_app,
+        "/observations/coverage?start=2024-08-12T00:00:00Z",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = get(
+        &test_app,
+        "/observations/coverage?start=2024-08-13T00:00:00Z&end=2024-08-12T00:00:00Z",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn hourly_slots_include_a_partial_first_and_last_hour() {
+    let req = |start, end| CoverageRequest { start, end };
+    assert_eq!(
+        req(
+            datetime!(2024-08-12 00:00:00 UTC),
+            datetime!(2024-08-13 00:00:00 UTC)
+        )
+        .hourly_slots(),
+        24
+    );
+    assert_eq!(
+        req(
+            datetime!(2024-08-12 00:30:00 UTC),
+            datetime!(2024-08-12 02:00:00 UTC)
+        )
+        .hourly_slots(),
+        2
+    );
+    assert_eq!(
+        req(
+            datetime!(2024-08-12 00:30:00 UTC),
+            datetime!(2024-08-12 02:15:00 UTC)
+        )
+        .hourly_slots(),
+        3
+    );
+}
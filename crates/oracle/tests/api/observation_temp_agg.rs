@@ -0,0 +1,93 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, DayTimeZone, ObservationRequest, ObservationTempAggregation,
+    ObservationWindAggregation, PrecipUnit, TemperatureUnit, WeatherData,
+};
+use serde_json::json;
+use time::macros::datetime;
+
+/// PFNO readings through 2024-08-12, the first and last aren't the day's extremes.
+fn weather_fixture() -> WeatherAccess {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T01:53:00Z', 15.0, 5),
+                ('PFNO', '2024-08-12T07:53:00Z', 5.0, 7),
+                ('PFNO', '2024-08-12T13:53:00Z', 25.0, 9),
+                ('PFNO', '2024-08-12T19:53:00Z', 12.0, 4)
+            ) AS t(station_id, generated_at, temperature_value, wind_speed)
+            CROSS JOIN (SELECT 'celsius' AS temperature_unit_code)
+        ) TO '{}/observations_2024-08-12T23:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+        day_dir
+    ));
+
+    fixture.weather_access()
+}
+
+fn observation_request(temp_agg: ObservationTempAggregation) -> ObservationRequest {
+    ObservationRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Celsius,
+        tz: DayTimeZone::Utc,
+        temp_agg,
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        granularity: None,
+    }
+}
+
+async fn temps(weather_access: &WeatherAccess, req: ObservationRequest) -> (f64, f64) {
+    let observations = weather_access
+        .observation_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(observations.len(), 1);
+    (observations[0].temp_low, observations[0].temp_high)
+}
+
+#[tokio::test]
+async fn minmax_uses_extremes_of_the_window() {
+    let weather_access = weather_fixture();
+
+    let req = observation_request(ObservationTempAggregation::MinMax);
+    assert_eq!(temps(&weather_access, req).await, (5.0, 25.0));
+}
+
+#[tokio::test]
+async fn firstlast_uses_readings_at_the_window_edges() {
+    let weather_access = weather_fixture();
+
+    let req = observation_request(ObservationTempAggregation::FirstLast);
+    assert_eq!(temps(&weather_access, req).await, (15.0, 12.0));
+
+    // A sub-daily window only sees its own readings
+    let morning = ObservationRequest {
+        start: Some(datetime!(2024-08-12 06:00:00 UTC)),
+        end: Some(datetime!(2024-08-12 14:00:00 UTC)),
+        ..observation_request(ObservationTempAggregation::FirstLast)
+    };
+    assert_eq!(temps(&weather_access, morning).await, (5.0, 25.0));
+}
+
+#[test]
+fn parses_aggregation_names() {
+    assert_eq!(
+        "minmax".parse::<ObservationTempAggregation>(),
+        Ok(ObservationTempAggregation::MinMax)
+    );
+    assert_eq!(
+        "FirstLast".parse::<ObservationTempAggregation>(),
+        Ok(ObservationTempAggregation::FirstLast)
+    );
+    assert!("median".parse::<ObservationTempAggregation>().is_err());
+
+    let req: ObservationRequest = serde_json::from_value(json!({ "station_ids": "PFNO" })).unwrap();
+    assert_eq!(req.temp_agg, ObservationTempAggregation::MinMax);
+}
@@ -0,0 +1,124 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, DayTimeZone, ObservationRequest, ObservationTempAggregation,
+    ObservationWindAggregation, PrecipUnit, TemperatureUnit, WeatherData,
+};
+use serde_json::json;
+use time::macros::datetime;
+
+/// PFNO readings through 2024-08-12 with both sustained wind and gusts, gusts only reported
+/// while gusting, plus an older file written before gusts were recorded.
+fn weather_fixture() -> WeatherAccess {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T01:53:00Z', 15.0, 12, NULL),
+                ('PFNO', '2024-08-12T07:53:00Z', 18.0, 14, 31),
+                ('PFNO', '2024-08-12T13:53:00Z', 21.0, 9, 22),
+                ('PFNO', '2024-08-12T19:53:00Z', 17.0, 18, NULL)
+            ) AS t(station_id, generated_at, temperature_value, wind_speed, wind_gust)
+            CROSS JOIN (SELECT 'celsius' AS temperature_unit_code)
+        ) TO '{day_dir}/observations_2024-08-12T23:00:00Z.parquet' (FORMAT PARQUET);
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T00:53:00Z', 14.0, 25)
+            ) AS t(station_id, generated_at, temperature_value, wind_speed)
+            CROSS JOIN (SELECT 'celsius' AS temperature_unit_code)
+        ) TO '{day_dir}/observations_2024-08-12T01:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+    ));
+
+    fixture.weather_access()
+}
+
+fn observation_request(
+    start: time::OffsetDateTime,
+    wind_agg: ObservationWindAggregation,
+) -> ObservationRequest {
+    ObservationRequest {
+        start: Some(start),
+        end: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Celsius,
+        tz: DayTimeZone::Utc,
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg,
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        granularity: None,
+    }
+}
+
+async fn wind(weather_access: &WeatherAccess, req: ObservationRequest) -> (i64, i64) {
+    let observations = weather_access
+        .observation_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(observations.len(), 1);
+    let daily = weather_access
+        .daily_observations(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(daily.len(), 1);
+    (observations[0].wind_speed, daily[0].wind_speed)
+}
+
+#[tokio::test]
+async fn sustained_ignores_gusts() {
+    let weather_access = weather_fixture();
+    let req = observation_request(
+        datetime!(2024-08-12 01:00:00 UTC),
+        ObservationWindAggregation::Sustained,
+    );
+    assert_eq!(wind(&weather_access, req).await, (18, 18));
+}
+
+#[tokio::test]
+async fn gust_takes_the_peak_gust() {
+    let weather_access = weather_fixture();
+    let req = observation_request(
+        datetime!(2024-08-12 01:00:00 UTC),
+        ObservationWindAggregation::Gust,
+    );
+    assert_eq!(wind(&weather_access, req).await, (31, 31));
+}
+
+#[tokio::test]
+async fn gust_falls_back_to_sustained_without_gusts() {
+    let weather_access = weather_fixture();
+    // Only the reading that wasn't gusting
+    let evening = observation_request(
+        datetime!(2024-08-12 19:00:00 UTC),
+        ObservationWindAggregation::Gust,
+    );
+    assert_eq!(wind(&weather_access, evening).await, (18, 18));
+
+    // Only the reading from the file without a gust column
+    let overnight = ObservationRequest {
+        end: Some(datetime!(2024-08-12 01:00:00 UTC)),
+        ..observation_request(
+            datetime!(2024-08-12 00:00:00 UTC),
+            ObservationWindAggregation::Gust,
+        )
+    };
+    assert_eq!(wind(&weather_access, overnight).await, (25, 25));
+}
+
+#[test]
+fn parses_aggregation_names() {
+    assert_eq!(
+        "sustained".parse::<ObservationWindAggregation>(),
+        Ok(ObservationWindAggregation::Sustained)
+    );
+    assert_eq!(
+        "Gust".parse::<ObservationWindAggregation>(),
+        Ok(ObservationWindAggregation::Gust)
+    );
+    assert!("mean".parse::<ObservationWindAggregation>().is_err());
+
+    let req: ObservationRequest = serde_json::from_value(json!({ "station_ids": "PFNO" })).unwrap();
+    assert_eq!(req.wind_agg, ObservationWindAggregation::Sustained);
+}
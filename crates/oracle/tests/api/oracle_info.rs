@@ -0,0 +1,48 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use base64::{engine::general_purpose, Engine};
+use dlctix::secp::Point;
+use hyper::{header, Method};
+use oracle::OracleInfo;
+use serde_json::from_slice;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn oracle_info_matches_configured_key() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/oracle")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get(header::CACHE_CONTROL).unwrap(),
+        "public, max-age=3600"
+    );
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let info: OracleInfo = from_slice(&body).unwrap();
+
+    let public_key = test_app.oracle.raw_public_key();
+    assert_eq!(
+        general_purpose::STANDARD.decode(&info.pubkey).unwrap(),
+        Point::from(public_key).serialize()
+    );
+    assert_eq!(
+        hex::decode(&info.xonly_pubkey).unwrap(),
+        public_key.x_only_public_key().0.serialize()
+    );
+    assert_eq!(info.npub, test_app.oracle.npub().unwrap());
+    assert_eq!(info.name, "4casttruth");
+}
@@ -0,0 +1,153 @@
+use crate::helpers::{random_test_number, MockWeatherAccess};
+use dlctix::{attestation_locking_point, attestation_secret, secp::Point};
+use nostr_sdk::Keys;
+use oracle::{
+    create_folder, oracle::get_winning_bytes, oracle::Oracle, CreateEvent, Database, EventStore,
+    OutcomeMessageVersion, ScoringField,
+};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+async fn test_db() -> Arc<Database> {
+    create_folder("./test_data");
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+    Arc::new(Database::new(&event_data).await.unwrap())
+}
+
+async fn oracle_with(db: Arc<Database>, version: OutcomeMessageVersion) -> Oracle {
+    Oracle::new(
+        db,
+        Arc::new(MockWeatherAccess::new()),
+        &String::from("./oracle_private_key.pem"),
+    )
+    .await
+    .unwrap()
+    .with_outcome_message_version(version)
+}
+
+async fn create_event(oracle: &Oracle) -> Uuid {
+    let now = OffsetDateTime::now_utc();
+    let event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(3),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(3),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    oracle
+        .create_event(Keys::generate().public_key, event, true)
+        .await
+        .unwrap()
+        .id
+}
+
+/// Attests that the second entry won, building the outcome message with `version`
+async fn sign_event(
+    db: &Database,
+    oracle: &Oracle,
+    event_id: Uuid,
+    version: OutcomeMessageVersion,
+) {
+    let mut event = db
+        .get_events_to_sign(vec![event_id], oracle.now())
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    event.attestation = Some(attestation_secret(
+        oracle.raw_private_key(),
+        event.nonce,
+        get_winning_bytes(vec![1], version),
+    ));
+    db.update_event_attestation(&event, oracle.now())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn events_commit_to_messages_of_their_version() {
+    let db = test_db().await;
+    let legacy_oracle = oracle_with(db.clone(), OutcomeMessageVersion::V0).await;
+    let oracle = oracle_with(db.clone(), OutcomeMessageVersion::CURRENT).await;
+
+    for (oracle, version) in [
+        (&legacy_oracle, OutcomeMessageVersion::V0),
+        (&oracle, OutcomeMessageVersion::V1),
+    ] {
+        let event_id = create_event(oracle).await;
+        let event = oracle.get_event(&event_id).await.unwrap();
+        assert_eq!(event.outcome_message_version, version);
+        let announcement = oracle.get_event_announcement(&event_id).await.unwrap();
+        assert_eq!(announcement.outcome_message_version, version);
+
+        let message = get_winning_bytes(vec![1], version);
+        assert_eq!(message.len(), version as usize + usize::BITS as usize / 8);
+        assert_eq!(
+            event.event_announcement.locking_points[1],
+            attestation_locking_point(
+                Point::from(oracle.raw_public_key()),
+                event.nonce.base_point_mul(),
+                &message
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn verification_uses_the_events_version() {
+    let db = test_db().await;
+    let legacy_oracle = oracle_with(db.clone(), OutcomeMessageVersion::V0).await;
+    let oracle = oracle_with(db.clone(), OutcomeMessageVersion::CURRENT).await;
+
+    let legacy_event = create_event(&legacy_oracle).await;
+    let event = create_event(&oracle).await;
+    sign_event(&db, &oracle, legacy_event, OutcomeMessageVersion::V0).await;
+    sign_event(&db, &oracle, event, OutcomeMessageVersion::V1).await;
+
+    // Both verify through the oracle that now announces with the newer layout
+    for event_id in [legacy_event, event] {
+        let verification = oracle.verify_attestation(&event_id).await.unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.outcome_index, Some(1));
+    }
+
+    // Checked as the other layout, the same attestation doesn't unlock the claimed outcome
+    let mut announcement = oracle.get_event_announcement(&legacy_event).await.unwrap();
+    let attestation = oracle.get_event_attestation(&legacy_event).await.unwrap();
+    assert!(attestation.verify(&announcement, &[1]).unwrap().valid);
+    announcement.outcome_message_version = OutcomeMessageVersion::V1;
+    assert!(!attestation.verify(&announcement, &[1]).unwrap().valid);
+
+    // Announcements served before the version was recorded read as the unprefixed layout
+    let mut served =
+        serde_json::to_value(oracle.get_event_announcement(&legacy_event).await.unwrap()).unwrap();
+    served
+        .as_object_mut()
+        .unwrap()
+        .remove("outcome_message_version");
+    let announcement: oracle::EventAnnouncement = serde_json::from_value(served).unwrap();
+    assert!(attestation.verify(&announcement, &[1]).unwrap().valid);
+}
+
+#[tokio::test]
+async fn signing_with_the_wrong_version_fails_verification() {
+    let db = test_db().await;
+    let legacy_oracle = oracle_with(db.clone(), OutcomeMessageVersion::V0).await;
+
+    let event_id = create_event(&legacy_oracle).await;
+    sign_event(&db, &legacy_oracle, event_id, OutcomeMessageVersion::V1).await;
+
+    let verification = legacy_oracle.verify_attestation(&event_id).await.unwrap();
+    assert!(!verification.valid);
+    assert_eq!(verification.outcome_index, None);
+}
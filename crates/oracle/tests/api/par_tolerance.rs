@@ -0,0 +1,98 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use nostr_sdk::Keys;
+use oracle::{CreateEvent, ParTolerances, ScoringField, ValueOptions};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+fn new_event(par_tolerances: ParTolerances) -> CreateEvent {
+    let now = OffsetDateTime::now_utc();
+    CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(3),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 5,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: vec![ScoringField::RainAmt, ScoringField::Humidity],
+        par_tolerances,
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    }
+}
+
+#[test]
+fn classify_uses_inclusive_band() {
+    let rain = ScoringField::RainAmt.par_tolerance(&ParTolerances::new());
+    assert_eq!(ValueOptions::classify(0.5, 0.6, rain), ValueOptions::Par);
+    assert_eq!(ValueOptions::classify(0.5, 0.45, rain), ValueOptions::Par);
+    assert_eq!(ValueOptions::classify(0.5, 0.7, rain), ValueOptions::Over);
+    assert_eq!(ValueOptions::classify(0.5, 0.3, rain), ValueOptions::Under);
+
+    let temp = ScoringField::TempHigh.par_tolerance(&ParTolerances::new());
+    assert_eq!(ValueOptions::classify(70.0, 70.0, temp), ValueOptions::Par);
+    assert_eq!(ValueOptions::classify(70.0, 71.0, temp), ValueOptions::Over);
+    assert_eq!(
+        ValueOptions::classify(70.0, 69.0, temp),
+        ValueOptions::Under
+    );
+}
+
+#[test]
+fn event_override_replaces_default_band() {
+    let overrides = ParTolerances::from([(ScoringField::RainAmt, 0.25)]);
+    assert_eq!(ScoringField::RainAmt.par_tolerance(&overrides), 0.25);
+    assert_eq!(
+        ScoringField::Humidity.par_tolerance(&overrides),
+        ScoringField::Humidity.default_par_tolerance()
+    );
+    assert_eq!(
+        ValueOptions::classify(0.5, 0.7, ScoringField::RainAmt.par_tolerance(&overrides)),
+        ValueOptions::Par
+    );
+}
+
+#[tokio::test]
+async fn par_tolerances_are_stored_with_event() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+    let overrides =
+        ParTolerances::from([(ScoringField::RainAmt, 0.25), (ScoringField::Humidity, 2.0)]);
+
+    let created = test_app
+        .oracle
+        .create_event(keys.public_key, new_event(overrides.clone()), true)
+        .await
+        .unwrap();
+    assert_eq!(created.par_tolerances, overrides);
+
+    let event = test_app.oracle.get_event(&created.id).await.unwrap();
+    assert_eq!(event.par_tolerances, overrides);
+
+    let default_event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event(ParTolerances::new()), true)
+        .await
+        .unwrap();
+    let event = test_app.oracle.get_event(&default_event.id).await.unwrap();
+    assert!(event.par_tolerances.is_empty());
+}
+
+#[tokio::test]
+async fn negative_par_tolerance_is_rejected() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let keys = Keys::generate();
+
+    let result = test_app
+        .oracle
+        .create_event(
+            keys.public_key,
+            new_event(ParTolerances::from([(ScoringField::RainAmt, -0.1)])),
+            true,
+        )
+        .await;
+    assert!(result.is_err());
+}
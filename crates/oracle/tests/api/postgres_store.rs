@@ -0,0 +1,252 @@
+//! Runs against a live server with `cargo test -- --ignored` and `NOAA_ORACLE_TEST_POSTGRES_URL`
+//! set, e.g. `postgres://postgres@127.0.0.1:5432/postgres`, each test creates its own database
+//! on it.
+use crate::helpers::{random_test_number, MockWeatherAccess};
+use nostr_sdk::{Keys, Url};
+use oracle::{
+    oracle::Oracle, AddEventEntry, CreateEvent, Event, EventIncludes, EventStore, Forecasted,
+    ImportReport, Observed, PostgresDatabase, TemperatureUnit, Weather, WeatherChoices,
+};
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+struct PostgresApp {
+    oracle: Arc<Oracle>,
+    db: Arc<PostgresDatabase>,
+}
+
+async fn spawn_postgres_app() -> PostgresApp {
+    let admin_url =
+        std::env::var("NOAA_ORACLE_TEST_POSTGRES_URL").expect("NOAA_ORACLE_TEST_POSTGRES_URL");
+    let database = format!(
+        "oracle_test_{}_{}",
+        random_test_number(),
+        Uuid::now_v7().simple()
+    );
+    let admin = PgPool::connect(&admin_url).await.unwrap();
+    sqlx::query(&format!("CREATE DATABASE {}", database))
+        .execute(&admin)
+        .await
+        .unwrap();
+
+    let mut url = Url::parse(&admin_url).unwrap();
+    url.set_path(&database);
+    let db = Arc::new(
+        PostgresDatabase::new(url.as_str(), oracle::DatabaseSettings::default())
+            .await
+            .unwrap(),
+    );
+    let oracle = Arc::new(
+        Oracle::new(
+            db.clone(),
+            Arc::new(MockWeatherAccess::new()),
+            &String::from("./oracle_private_key.pem"),
+        )
+        .await
+        .unwrap(),
+    );
+    PostgresApp { oracle, db }
+}
+
+async fn create_complete_event(app: &PostgresApp, signing_date: OffsetDateTime) -> Event {
+    let keys = Keys::generate();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: signing_date - Duration::days(1),
+        end_observation_date: signing_date - Duration::hours(3),
+        signing_date,
+        locations: vec![String::from("PFNO"), String::from("KSAW")],
+        total_allowed_entries: 2,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: oracle::ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    let event = app
+        .oracle
+        .create_event(keys.public_key, new_event, true)
+        .await
+        .unwrap();
+
+    let entry = AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id: event.id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_low: Some(oracle::ValueOptions::Par),
+            temp_high: Some(oracle::ValueOptions::Over),
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    };
+    let entry_id = entry.id;
+    app.db.add_entry(entry.into()).await.unwrap();
+    app.db
+        .update_entry_scores(vec![(entry_id, 7, 3)])
+        .await
+        .unwrap();
+
+    let weather = Weather {
+        station_id: String::from("PFNO"),
+        observed: Some(Observed {
+            date: signing_date,
+            temp_low: 0,
+            temp_high: 21,
+            wind_speed: 9,
+        }),
+        forecasted: Forecasted {
+            date: signing_date,
+            temp_low: -5,
+            temp_high: 20,
+            wind_speed: Some(5),
+        },
+    };
+    app.db
+        .update_weather_station_data(event.id, vec![weather], &TemperatureUnit::Celsius)
+        .await
+        .unwrap();
+
+    app.db.get_event(&event.id, app.oracle.now()).await.unwrap()
+}
+
+async fn count_rows(app: &PostgresApp, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+        .fetch_one(app.db.pool())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs NOAA_ORACLE_TEST_POSTGRES_URL"]
+async fn postgres_store_reads_back_events() {
+    let app = spawn_postgres_app().await;
+    let event = create_complete_event(&app, OffsetDateTime::now_utc()).await;
+
+    assert_eq!(event.locations, vec!["PFNO", "KSAW"]);
+    assert_eq!(event.entries.len(), 1);
+    assert_eq!(event.entries[0].score, Some(7));
+    assert_eq!(event.entries[0].expected_observations.len(), 1);
+    assert_eq!(event.entry_ids, vec![event.entries[0].id]);
+    // stored in celsius, read back in the default fahrenheit: -5C -> 23F, 20C -> 68F
+    assert_eq!(event.weather[0].forecasted.temp_low, 23);
+    assert_eq!(event.weather[0].forecasted.temp_high, 68);
+
+    let metadata_only = app
+        .db
+        .get_event_with(&event.id, &EventIncludes::default(), app.oracle.now())
+        .await
+        .unwrap();
+    assert!(metadata_only.entries.is_empty());
+    assert!(metadata_only.weather.is_empty());
+    assert_eq!(metadata_only.nonce, event.nonce);
+
+    let active = app.db.get_active_events(app.oracle.now()).await.unwrap();
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].total_entries, 1);
+
+    let status = app.db.migration_status().await.unwrap();
+    assert!(status.up_to_date);
+    app.db.health_check().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs NOAA_ORACLE_TEST_POSTGRES_URL"]
+async fn postgres_deleting_event_cascades_to_children() {
+    let app = spawn_postgres_app().await;
+    let event = create_complete_event(&app, OffsetDateTime::now_utc()).await;
+    assert_eq!(count_rows(&app, "expected_observations").await, 1);
+
+    assert!(app
+        .db
+        .delete_event(&event.id, app.oracle.now())
+        .await
+        .unwrap());
+    assert!(!app.db.event_exists(&event.id).await.unwrap());
+    assert_eq!(count_rows(&app, "events_entries").await, 0);
+    assert_eq!(count_rows(&app, "expected_observations").await, 0);
+    assert_eq!(count_rows(&app, "events_weather").await, 0);
+    assert_eq!(count_rows(&app, "weather").await, 0);
+}
+
+#[tokio::test]
+#[ignore = "needs NOAA_ORACLE_TEST_POSTGRES_URL"]
+async fn postgres_retention_purges_only_old_events() {
+    let app = spawn_postgres_app().await;
+    let old = create_complete_event(&app, OffsetDateTime::now_utc() - Duration::days(40)).await;
+    let recent = create_complete_event(&app, OffsetDateTime::now_utc()).await;
+
+    let purged = app
+        .oracle
+        .purge_expired_events(Duration::days(30))
+        .await
+        .unwrap();
+    assert_eq!(purged, 1);
+    assert!(!app.db.event_exists(&old.id).await.unwrap());
+    assert!(app.db.event_exists(&recent.id).await.unwrap());
+}
+
+#[tokio::test]
+#[ignore = "needs NOAA_ORACLE_TEST_POSTGRES_URL"]
+async fn postgres_export_then_import_round_trips_events() {
+    let app = spawn_postgres_app().await;
+    let mut event = create_complete_event(&app, OffsetDateTime::now_utc()).await;
+    let mut sign_event = app
+        .db
+        .get_events_to_sign(vec![event.id], app.oracle.now())
+        .await
+        .unwrap()
+        .remove(0);
+    sign_event.attestation = Some(sign_event.nonce.into());
+    app.db
+        .update_event_attestation(&sign_event, app.oracle.now())
+        .await
+        .unwrap();
+    event = app.db.get_event(&event.id, app.oracle.now()).await.unwrap();
+
+    let mut dump = Vec::new();
+    assert_eq!(
+        app.db
+            .export_events(&mut dump, app.oracle.now())
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(app
+        .db
+        .delete_event(&event.id, app.oracle.now())
+        .await
+        .unwrap());
+
+    let report = app
+        .db
+        .import_events(&mut dump.as_slice(), app.oracle.now())
+        .await
+        .unwrap();
+    assert_eq!(
+        report,
+        ImportReport {
+            imported: 1,
+            skipped: 0
+        }
+    );
+    assert_eq!(
+        app.db.get_event(&event.id, app.oracle.now()).await.unwrap(),
+        event
+    );
+
+    let report = app
+        .db
+        .import_events(&mut dump.as_slice(), app.oracle.now())
+        .await
+        .unwrap();
+    assert_eq!(report.skipped, 1);
+}
@@ -0,0 +1,121 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, DayTimeZone, FileAccess, ObservationRequest,
+    ObservationTempAggregation, ObservationWindAggregation, PrecipCodes, PrecipUnit,
+    TemperatureUnit, WeatherData,
+};
+use std::sync::Arc;
+use time::macros::datetime;
+
+/// A PFNO observation of 0.2in above freezing reporting the `wx_string` weather codes
+fn weather_fixture(wx_string: &str) -> Arc<FileAccess> {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T01:53:00Z', 3.0, 5, 0.2, '{wx_string}')
+            ) AS t(station_id, generated_at, temperature_value, wind_speed, precip_in, wx_string)
+            CROSS JOIN (SELECT 'celsius' AS temperature_unit_code)
+        ) TO '{day_dir}/observations_2024-08-12T23:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+    ));
+
+    fixture.file_access()
+}
+
+fn observation_request() -> ObservationRequest {
+    ObservationRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Celsius,
+        tz: DayTimeZone::Utc,
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit: None,
+        precip_unit: PrecipUnit::default(),
+        granularity: None,
+    }
+}
+
+/// (rain, snow, ice) from the window and the daily observations
+async fn precip(weather_access: &WeatherAccess) -> [(Option<f64>, Option<f64>, Option<f64>); 2] {
+    let req = observation_request();
+    let window = weather_access
+        .observation_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    let daily = weather_access
+        .daily_observations(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(window.len(), 1);
+    assert_eq!(daily.len(), 1);
+    [
+        (window[0].rain_amt, window[0].snow_amt, window[0].ice_amt),
+        (daily[0].rain_amt, daily[0].snow_amt, daily[0].ice_amt),
+    ]
+}
+
+#[tokio::test]
+async fn unknown_codes_default_to_rain() {
+    let weather_access = WeatherAccess::new(weather_fixture("UP BR")).unwrap();
+
+    for (rain, snow, ice) in precip(&weather_access).await {
+        assert_eq!(rain, Some(0.2));
+        assert_eq!(snow, None);
+        assert_eq!(ice, None);
+    }
+}
+
+#[tokio::test]
+async fn present_weather_snow_lands_in_snow_amt() {
+    // How the daemon's weather.gov source writes snow and mist. Above freezing the
+    // temperature fallback would have called it rain, so only the code can make it snow.
+    let weather_access = WeatherAccess::new(weather_fixture("SN BR")).unwrap();
+
+    for (rain, snow, ice) in precip(&weather_access).await {
+        assert_eq!(rain, None);
+        // 0.2in liquid at the default 10:1 snow ratio
+        assert_eq!(snow.map(|snow| (snow * 100.0).round() / 100.0), Some(2.0));
+        assert_eq!(ice, None);
+    }
+}
+
+#[tokio::test]
+async fn custom_ice_codes_reclassify_rain() {
+    let file_access = weather_fixture("UP BR");
+    let mut codes = PrecipCodes::default();
+    codes.ice.push(String::from("UP"));
+    codes.validate().unwrap();
+    let weather_access = WeatherAccess::new(file_access)
+        .unwrap()
+        .with_precip_codes(codes);
+
+    for (rain, _, ice) in precip(&weather_access).await {
+        assert_eq!(rain, None);
+        assert_eq!(ice, Some(0.2));
+    }
+}
+
+#[test]
+fn rejects_invalid_codes() {
+    assert!(PrecipCodes::default().validate().is_ok());
+
+    let invalid = |snow: &[&str], ice: &[&str]| {
+        let codes = |codes: &[&str]| codes.iter().map(|code| code.to_string()).collect();
+        PrecipCodes {
+            snow: codes(snow),
+            ice: codes(ice),
+        }
+        .validate()
+        .is_err()
+    };
+    assert!(invalid(&[], &["FZRA"]));
+    assert!(invalid(&["SN"], &["FZ|RA"]));
+    assert!(invalid(&["SN"], &["fzra"]));
+    assert!(invalid(&["SN'"], &["FZRA"]));
+    assert!(invalid(&["SN", "PL"], &["PL"]));
+}
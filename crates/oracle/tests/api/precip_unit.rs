@@ -0,0 +1,145 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, DayTimeZone, ForecastAggregation, ForecastFields, ForecastRequest,
+    ObservationRequest, ObservationTempAggregation, ObservationWindAggregation, PrecipUnit,
+    TemperatureUnit, WeatherData,
+};
+use serde_json::json;
+use time::macros::datetime;
+
+/// A PFNO forecast day with 0.5in of rain and two PFNO rain readings of 0.1in and 0.2in.
+fn weather_fixture() -> WeatherAccess {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT 'PFNO' AS station_id, '2024-08-12T00:00:00Z' AS begin_time,
+                   '2024-08-13T00:00:00Z' AS end_time, 0.5::DOUBLE AS liquid_precipitation_amt,
+                   50 AS min_temp, 70 AS max_temp, 'fahrenheit' AS temperature_unit_code,
+                   '2024-08-11T12:00:00Z' AS generated_at
+        ) TO '{day_dir}/forecasts_2024-08-11T12:00:00Z.parquet' (FORMAT PARQUET);
+        COPY (
+            SELECT * FROM (VALUES
+                ('PFNO', '2024-08-12T07:53:00Z', 18.0, 0.1::DOUBLE),
+                ('PFNO', '2024-08-12T13:53:00Z', 21.0, 0.2::DOUBLE)
+            ) AS t(station_id, generated_at, temperature_value, precip_in)
+            CROSS JOIN (SELECT 'celsius' AS temperature_unit_code, 'RA' AS wx_string)
+        ) TO '{day_dir}/observations_2024-08-12T23:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+    ));
+
+    fixture.weather_access()
+}
+
+fn forecast_request(precip_unit: PrecipUnit) -> ForecastRequest {
+    ForecastRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        generated_start: Some(datetime!(2024-08-11 00:00:00 UTC)),
+        generated_end: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        as_of: None,
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit: None,
+        precip_unit,
+        agg: ForecastAggregation::default(),
+        fields: ForecastFields::default(),
+        granularity: None,
+    }
+}
+
+fn observation_request(precip_unit: PrecipUnit) -> ObservationRequest {
+    ObservationRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Celsius,
+        tz: DayTimeZone::Utc,
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit: None,
+        precip_unit,
+        granularity: None,
+    }
+}
+
+fn hundredths(amount: Option<f64>) -> Option<f64> {
+    amount.map(|amount| (amount * 100.0).round() / 100.0)
+}
+
+#[tokio::test]
+async fn forecast_rain_converts_to_mm() {
+    let weather_access = weather_fixture();
+    for (precip_unit, rain_amt, code) in [
+        (PrecipUnit::Inches, 0.5, "in"),
+        (PrecipUnit::Millimeters, 12.7, "mm"),
+    ] {
+        let req = forecast_request(precip_unit);
+        let forecasts = weather_access
+            .forecasts_data(&req, req.station_ids())
+            .await
+            .unwrap();
+        assert_eq!(forecasts.len(), 1);
+        assert_eq!(hundredths(forecasts[0].rain_amt), Some(rain_amt));
+        assert_eq!(forecasts[0].precip_unit_code, code);
+        assert_eq!(forecasts[0].dominant_precip.as_deref(), Some("rain"));
+    }
+}
+
+#[tokio::test]
+async fn observed_rain_converts_to_mm() {
+    let weather_access = weather_fixture();
+    let req = observation_request(PrecipUnit::Millimeters);
+    let observations = weather_access
+        .observation_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(observations.len(), 1);
+    // 0.3in is 7.62mm
+    assert_eq!(hundredths(observations[0].rain_amt), Some(7.62));
+    assert_eq!(observations[0].precip_unit_code, "mm");
+
+    let daily = weather_access
+        .daily_observations(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(daily.len(), 1);
+    assert_eq!(hundredths(daily[0].rain_amt), Some(7.62));
+    assert_eq!(daily[0].precip_unit_code, "mm");
+}
+
+#[test]
+fn converts_inches_to_mm() {
+    assert_eq!(
+        PrecipUnit::Millimeters.convert(1.0, PrecipUnit::Inches),
+        25.4
+    );
+    assert_eq!(
+        PrecipUnit::Millimeters.convert(0.5, PrecipUnit::Inches),
+        12.7
+    );
+    assert_eq!(
+        PrecipUnit::Millimeters.convert(0.0, PrecipUnit::Inches),
+        0.0
+    );
+    assert_eq!(
+        PrecipUnit::Inches.convert(25.4, PrecipUnit::Millimeters),
+        1.0
+    );
+    assert_eq!(PrecipUnit::Inches.convert(0.25, PrecipUnit::Inches), 0.25);
+}
+
+#[test]
+fn parses_unit_names() {
+    assert_eq!("in".parse::<PrecipUnit>(), Ok(PrecipUnit::Inches));
+    assert_eq!("inches".parse::<PrecipUnit>(), Ok(PrecipUnit::Inches));
+    assert_eq!("MM".parse::<PrecipUnit>(), Ok(PrecipUnit::Millimeters));
+    assert!("cm".parse::<PrecipUnit>().is_err());
+
+    let req: ForecastRequest = serde_json::from_value(json!({ "station_ids": "PFNO" })).unwrap();
+    assert_eq!(req.precip_unit, PrecipUnit::Inches);
+    let req: ObservationRequest =
+        serde_json::from_value(json!({ "station_ids": "PFNO", "precip_unit": "mm" })).unwrap();
+    assert_eq!(req.precip_unit, PrecipUnit::Millimeters);
+}
@@ -0,0 +1,61 @@
+use crate::helpers::random_test_number;
+use clap::CommandFactory;
+use noaa_oracle_core::{ConfigSource, ValueSource};
+use oracle::{create_folder, resolve_config, Cli};
+use std::{fs, path::PathBuf};
+
+#[test]
+fn can_resolve_effective_config_with_sources() {
+    let config_dir = format!("./test_data/config_{}", random_test_number());
+    create_folder(&config_dir);
+    let config_path = format!("{}/oracle.toml", config_dir);
+    fs::write(
+        &config_path,
+        r#"
+data_dir = "/srv/weather"
+port = "9000"
+oracle_private_key = "/secrets/oracle.pem"
+database_url = "postgres://oracle:hunter2@db/events"
+"#,
+    )
+    .unwrap();
+    // Only this test reads the humidity formula from the environment
+    std::env::set_var("NOAA_ORACLE_HUMIDITY_FORMULA", "ice-below-freezing");
+
+    let matches = Cli::command()
+        .try_get_matches_from(["oracle", "--config", &config_path, "--port", "9100"])
+        .unwrap();
+    let resolved = resolve_config(&matches);
+    let config = resolved.effective(&matches);
+    std::env::remove_var("NOAA_ORACLE_HUMIDITY_FORMULA");
+
+    let file = ValueSource::File(ConfigSource::Explicit(PathBuf::from(&config_path)));
+    let value = |name: &str| config.get(name).unwrap().clone();
+
+    // command line beats the file
+    assert_eq!(value("port").value, "9100");
+    assert_eq!(value("port").source, ValueSource::CommandLine);
+    // the file's `data_dir` alias still lands on weather_dir
+    assert_eq!(value("weather_dir").value, "/srv/weather");
+    assert_eq!(value("weather_dir").source, file);
+    assert_eq!(value("humidity_formula").value, "ice-below-freezing");
+    assert_eq!(
+        value("humidity_formula").source,
+        ValueSource::Env(String::from("NOAA_ORACLE_HUMIDITY_FORMULA"))
+    );
+    assert_eq!(value("layout").value, "date-sub");
+    assert_eq!(value("layout").source, ValueSource::Default);
+    assert_eq!(value("remote_url").value, "http://127.0.0.1:9100");
+
+    assert_eq!(value("oracle_private_key").value, "<redacted>");
+    assert_eq!(value("oracle_private_key").source, file);
+    assert_eq!(
+        value("database_url").value,
+        "postgres://oracle:<redacted>@db/events"
+    );
+    let printed = config.to_string();
+    assert!(!printed.contains("hunter2"));
+    assert!(!printed.contains("oracle.pem"));
+
+    fs::remove_dir_all(&config_dir).unwrap();
+}
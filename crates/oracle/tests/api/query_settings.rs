@@ -0,0 +1,46 @@
+use duckdb::Connection;
+use oracle::QuerySettings;
+
+#[test]
+fn can_apply_query_settings_to_connection() {
+    let conn = Connection::open_in_memory().unwrap();
+    let settings = QuerySettings {
+        threads: 1,
+        memory_limit_mib: 256,
+    };
+    settings.apply(&conn).unwrap();
+
+    let (threads, memory_limit): (i64, String) = conn
+        .query_row(
+            "SELECT current_setting('threads'), current_setting('memory_limit')",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(threads, 1);
+    assert_eq!(memory_limit, "256.0 MiB");
+}
+
+#[test]
+fn default_query_settings_are_valid() {
+    let settings = QuerySettings::default();
+    settings.validate().unwrap();
+    let cores = std::thread::available_parallelism().unwrap().get() as u32;
+    assert!(settings.threads <= cores);
+}
+
+#[test]
+fn rejects_invalid_query_settings() {
+    let no_threads = QuerySettings {
+        threads: 0,
+        ..QuerySettings::default()
+    };
+    assert!(no_threads.validate().is_err());
+
+    let tiny_memory = QuerySettings {
+        memory_limit_mib: 16,
+        ..QuerySettings::default()
+    };
+    let err = tiny_memory.validate().unwrap_err();
+    assert!(err.to_string().contains("memory limit"));
+}
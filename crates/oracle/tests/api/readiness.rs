@@ -0,0 +1,161 @@
+use crate::helpers::{spawn_app_with_file_access, MockFileAccess, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    Router,
+};
+use hyper::Method;
+use oracle::{app, check_weather_data_fresh, warm_forecast_cache, AppState, Readiness};
+use std::sync::{atomic::Ordering, Arc};
+use time::{format_description::well_known::Rfc3339, macros::datetime, Duration, OffsetDateTime};
+use tower::ServiceExt;
+
+async fn spawn_with_weather_file() -> TestApp {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .returning(|_, _| Ok(vec![]));
+    weather_data
+        .expect_daily_observations()
+        .returning(|_, _| Ok(vec![]));
+    let mut file_access = MockFileAccess::new();
+    file_access
+        .expect_grab_file_names()
+        .returning(|_| Ok(vec![String::from("forecasts_2024-08-12T00:00:00Z.parquet")]));
+    file_access.expect_file_size().returning(|_, _| Ok(1024));
+    spawn_app_with_file_access(Arc::new(weather_data), Arc::new(file_access)).await
+}
+
+/// A warmed app whose weather files are `file_names`, checked against a 6 hour max data age
+async fn spawn_with_max_data_age(file_names: Vec<String>) -> (AppState, Router) {
+    let mut file_access = MockFileAccess::new();
+    file_access
+        .expect_grab_file_names()
+        .returning(move |_| Ok(file_names.clone()));
+    file_access.expect_file_size().returning(|_, _| Ok(1024));
+    let test_app =
+        spawn_app_with_file_access(Arc::new(MockWeatherAccess::new()), Arc::new(file_access)).await;
+    test_app
+        .state
+        .forecast_cache_warmed
+        .store(true, Ordering::Release);
+    let mut state = (*test_app.state).clone();
+    state.max_data_age = Some(Duration::hours(6));
+    (state.clone(), app(state))
+}
+
+async fn get(test_app: &TestApp, uri: &str) -> (StatusCode, Vec<u8>) {
+    get_from(&test_app.app, uri).await
+}
+
+async fn get_from(app: &Router, uri: &str) -> (StatusCode, Vec<u8>) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
+
+#[tokio::test]
+async fn livez_is_ok_before_the_cache_is_warm() {
+    let test_app = spawn_with_weather_file().await;
+
+    let (status, _) = get(&test_app, "/livez").await;
+
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn readyz_waits_for_the_first_cache_warm() {
+    let test_app = spawn_with_weather_file().await;
+
+    let (status, body) = get(&test_app, "/readyz").await;
+    let readiness: Readiness = serde_json::from_slice(&body).unwrap();
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!readiness.ready);
+    assert!(readiness.database);
+    assert!(readiness.weather_files);
+    assert!(readiness.oracle_key);
+    assert!(!readiness.forecast_cache);
+    // no max data age configured, old data doesn't count against readiness
+    assert!(readiness.weather_data_fresh);
+
+    warm_forecast_cache(&test_app.state).await;
+
+    let (status, body) = get(&test_app, "/readyz").await;
+    let readiness: Readiness = serde_json::from_slice(&body).unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert!(readiness.ready);
+    assert!(readiness.forecast_cache);
+}
+
+#[tokio::test]
+async fn readyz_needs_a_weather_file() {
+    let mut file_access = MockFileAccess::new();
+    file_access
+        .expect_grab_file_names()
+        .returning(|_| Ok(vec![]));
+    let test_app =
+        spawn_app_with_file_access(Arc::new(MockWeatherAccess::new()), Arc::new(file_access)).await;
+    test_app
+        .state
+        .forecast_cache_warmed
+        .store(true, std::sync::atomic::Ordering::Release);
+
+    let (status, body) = get(&test_app, "/readyz").await;
+    let readiness: Readiness = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!readiness.weather_files);
+    assert!(readiness.forecast_cache);
+}
+
+#[tokio::test]
+async fn readyz_fails_once_weather_data_is_stale() {
+    // the daemon last uploaded in 2024, long past the 6 hour limit
+    let (state, app) = spawn_with_max_data_age(vec![
+        String::from("forecasts_2024-08-12T00:00:00Z.parquet"),
+        String::from("observations_2024-08-12T23:00:00Z.parquet"),
+    ])
+    .await;
+
+    let (status, body) = get_from(&app, "/readyz").await;
+    let readiness: Readiness = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!readiness.ready);
+    assert!(readiness.weather_files);
+    assert!(!readiness.weather_data_fresh);
+    assert_eq!(
+        readiness.newest_generated_at,
+        Some(datetime!(2024-08-12 23:00:00 UTC))
+    );
+    assert!(!check_weather_data_fresh(&state).await);
+}
+
+#[tokio::test]
+async fn readyz_passes_with_recent_weather_data() {
+    let generated_at = (OffsetDateTime::now_utc() - Duration::hours(1))
+        .replace_nanosecond(0)
+        .unwrap();
+    let (state, app) = spawn_with_max_data_age(vec![
+        String::from("forecasts_2024-08-12T00:00:00Z.parquet"),
+        format!(
+            "observations_{}.parquet",
+            generated_at.format(&Rfc3339).unwrap()
+        ),
+    ])
+    .await;
+
+    let (status, body) = get_from(&app, "/readyz").await;
+    let readiness: Readiness = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(readiness.weather_data_fresh);
+    assert_eq!(readiness.newest_generated_at, Some(generated_at));
+    assert!(check_weather_data_fresh(&state).await);
+}
@@ -0,0 +1,139 @@
+//! Runs against a live localstack with `cargo test -- --ignored` and
+//! `NOAA_ORACLE_TEST_LOCALSTACK_URL` set, e.g. `http://127.0.0.1:4566`, faults are injected
+//! through localstack's chaos API.
+use crate::helpers::random_test_number;
+use aws_sdk_s3::{
+    config::{BehaviorVersion, Credentials, Region},
+    primitives::ByteStream,
+    Client,
+};
+use duckdb::Connection;
+use oracle::{
+    weather_data::{self, WeatherAccess},
+    FileAccess, HttpRetrySettings,
+};
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    sync::Arc,
+};
+
+struct Localstack {
+    url: String,
+    bucket: String,
+}
+
+async fn localstack_bucket() -> Localstack {
+    let url =
+        std::env::var("NOAA_ORACLE_TEST_LOCALSTACK_URL").expect("NOAA_ORACLE_TEST_LOCALSTACK_URL");
+    let config = aws_sdk_s3::Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .endpoint_url(&url)
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("test", "test", None, None, "localstack"))
+        .force_path_style(true)
+        .build();
+    let client = Client::from_conf(config);
+    let bucket = format!("oracle-test-{}", random_test_number());
+    client.create_bucket().bucket(&bucket).send().await.unwrap();
+
+    // One forecast row written locally, then uploaded the way the daemon would
+    let local = format!("./test_data/s3_retries_{}.parquet", random_test_number());
+    Connection::open_in_memory()
+        .unwrap()
+        .execute_batch(&format!(
+            "INSTALL parquet; LOAD parquet;
+             COPY (SELECT 'PFNO' AS station_id, 70 AS max_temp) TO '{}' (FORMAT PARQUET);",
+            local
+        ))
+        .unwrap();
+    client
+        .put_object()
+        .bucket(&bucket)
+        .key("weather_data/2024-08-12/forecasts.parquet")
+        .body(ByteStream::from_path(&local).await.unwrap())
+        .send()
+        .await
+        .unwrap();
+    std::fs::remove_file(&local).unwrap();
+
+    Localstack { url, bucket }
+}
+
+/// Replaces localstack's active faults, an empty list clears them
+fn set_faults(url: &str, faults: &str) {
+    let host = url.trim_start_matches("http://").trim_end_matches('/');
+    let mut stream = TcpStream::connect(host).unwrap();
+    write!(
+        stream,
+        "POST /_localstack/chaos/faults HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        host,
+        faults.len(),
+        faults
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+}
+
+fn count_rows(localstack: &Localstack, key: &str) -> Result<i64, weather_data::Error> {
+    let weather_access = WeatherAccess::new(Arc::new(FileAccess::new(String::from(
+        "./test_data/unused",
+    ))))
+    .unwrap()
+    .with_http_retries(Some(HttpRetrySettings {
+        retries: 2,
+        timeout_secs: 5,
+        retry_wait_ms: 10,
+        backoff: 1.0,
+    }));
+    let conn = weather_access.open_connection()?;
+    conn.execute_batch(&format!(
+        "CREATE SECRET (TYPE S3, KEY_ID 'test', SECRET 'test', REGION 'us-east-1',
+                        ENDPOINT '{}', URL_STYLE 'path', USE_SSL false);",
+        localstack.url.trim_start_matches("http://")
+    ))?;
+    Ok(conn.query_row(
+        &format!(
+            "SELECT count(*) FROM read_parquet('s3://{}/{}')",
+            localstack.bucket, key
+        ),
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+#[tokio::test]
+#[ignore = "needs NOAA_ORACLE_TEST_LOCALSTACK_URL"]
+async fn transient_s3_failure_is_retryable() {
+    let localstack = localstack_bucket().await;
+    let key = "weather_data/2024-08-12/forecasts.parquet";
+
+    set_faults(
+        &localstack.url,
+        r#"[{"service": "s3", "probability": 1.0, "error": {"statusCode": 503, "code": "ServiceUnavailable"}}]"#,
+    );
+    let err = count_rows(&localstack, key).unwrap_err();
+    set_faults(&localstack.url, "[]");
+    assert!(
+        matches!(err, weather_data::Error::RemoteUnavailable(_)),
+        "{}",
+        err
+    );
+
+    // The same read goes through once S3 recovers
+    assert_eq!(count_rows(&localstack, key).unwrap(), 1);
+}
+
+#[tokio::test]
+#[ignore = "needs NOAA_ORACLE_TEST_LOCALSTACK_URL"]
+async fn missing_s3_file_is_not_found() {
+    let localstack = localstack_bucket().await;
+    let err = count_rows(&localstack, "weather_data/2024-08-12/missing.parquet").unwrap_err();
+    assert!(
+        matches!(err, weather_data::Error::RemoteMissing(_)),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,109 @@
+use duckdb::Connection;
+use oracle::{SanityBounds, TemperatureUnit};
+
+/// Temperatures from `rows` that pass the bounds' SQL filter
+fn kept(
+    bounds: &SanityBounds,
+    default_unit: TemperatureUnit,
+    rows: &[(f64, Option<&str>)],
+) -> Vec<f64> {
+    let values: Vec<String> = rows
+        .iter()
+        .map(|(value, unit)| match unit {
+            Some(unit) => format!("({}, '{}')", value, unit),
+            None => format!("({}, NULL)", value),
+        })
+        .collect();
+    let sql = format!(
+        "SELECT temperature_value FROM (VALUES {}) t(temperature_value, temperature_unit_code)
+         WHERE {} ORDER BY temperature_value",
+        values.join(", "),
+        bounds.temperature_filter("temperature_value", &default_unit)
+    );
+    let conn = Connection::open_in_memory().unwrap();
+    let mut stmt = conn.prepare(&sql).unwrap();
+    stmt.query_map([], |row| row.get::<_, f64>(0))
+        .unwrap()
+        .map(|value| value.unwrap())
+        .collect()
+}
+
+#[test]
+fn cold_celsius_reading_is_kept_with_celsius_bounds() {
+    let bounds = SanityBounds {
+        temperature_min: -90.0,
+        temperature_max: 60.0,
+        ..SanityBounds::defaults_for(TemperatureUnit::Celsius)
+    };
+
+    // -62°C (Vostok-like cold) is valid, -95°C is not, whatever spelling the unit uses
+    let rows = [
+        (-95.0, Some("celcius")),
+        (-62.0, Some("celcius")),
+        (-62.0, Some("Celsius")),
+        (25.0, Some("celsius")),
+    ];
+    assert_eq!(
+        kept(&bounds, TemperatureUnit::Celsius, &rows),
+        vec![-62.0, -62.0, 25.0]
+    );
+}
+
+#[test]
+fn bounds_are_converted_to_each_rows_unit() {
+    // -90°C..60°C is -130°F..140°F
+    let bounds = SanityBounds {
+        temperature_min: -90.0,
+        temperature_max: 60.0,
+        ..SanityBounds::defaults_for(TemperatureUnit::Celsius)
+    };
+    let rows = [
+        (-131.0, Some("fahrenheit")),
+        (-80.0, Some("fahrenheit")),
+        (139.0, Some("fahrenheit")),
+        (141.0, Some("fahrenheit")),
+    ];
+    assert_eq!(
+        kept(&bounds, TemperatureUnit::Fahrenheit, &rows),
+        vec![-80.0, 139.0]
+    );
+
+    // Rows without a unit code are compared in the query's default unit
+    assert_eq!(
+        kept(&bounds, TemperatureUnit::Celsius, &[(100.0, None)]),
+        Vec::<f64>::new()
+    );
+    assert_eq!(
+        kept(&bounds, TemperatureUnit::Fahrenheit, &[(100.0, None)]),
+        vec![100.0]
+    );
+}
+
+#[test]
+fn default_bounds_match_previous_fahrenheit_filters() {
+    let bounds = SanityBounds::default();
+    assert_eq!(bounds.temperature_unit, TemperatureUnit::Fahrenheit);
+    assert_eq!(
+        (bounds.temperature_min, bounds.temperature_max),
+        (-200.0, 200.0)
+    );
+    assert!(bounds.wind_speed_in_bounds(500));
+    assert!(!bounds.wind_speed_in_bounds(501));
+    assert!(!bounds.wind_speed_in_bounds(-1));
+}
+
+#[test]
+fn rejects_inverted_bounds() {
+    let inverted = SanityBounds {
+        temperature_min: 10.0,
+        temperature_max: -10.0,
+        ..SanityBounds::default()
+    };
+    assert!(inverted.validate().is_err());
+
+    let no_wind = SanityBounds {
+        wind_speed_max: 0,
+        ..SanityBounds::default()
+    };
+    assert!(no_wind.validate().is_err());
+}
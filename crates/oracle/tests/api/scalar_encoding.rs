@@ -0,0 +1,152 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use dlctix::secp::{MaybePoint, MaybeScalar, Scalar};
+use hyper::{header, Method};
+use nostr_sdk::Keys;
+use oracle::{ByteEncoding, CreateEvent, Event, EventStore, ScoringField};
+use serde_json::{from_slice, json, Value};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+async fn create_signed_event(test_app: &TestApp) -> Event {
+    let now = OffsetDateTime::now_utc();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: now - Duration::days(3),
+        end_observation_date: now - Duration::days(2),
+        signing_date: now - Duration::days(1),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(3),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    let event = test_app
+        .oracle
+        .create_event(Keys::generate().public_key, new_event, true)
+        .await
+        .unwrap();
+    let mut to_sign = test_app
+        .db
+        .get_events_to_sign(vec![event.id], test_app.oracle.now())
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    to_sign.attestation = Some(MaybeScalar::Valid(Scalar::one()));
+    test_app
+        .db
+        .update_event_attestation(&to_sign, test_app.oracle.now())
+        .await
+        .unwrap();
+    test_app.oracle.get_event(&event.id).await.unwrap()
+}
+
+async fn send(test_app: &TestApp, request: Request<Body>) -> (StatusCode, Value) {
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn get(test_app: &TestApp, uri: String) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    send(test_app, request).await
+}
+
+fn decode(encoding: ByteEncoding, value: &Value) -> Vec<u8> {
+    encoding.decode(value.as_str().unwrap()).unwrap()
+}
+
+/// Decodes the event's byte fields and checks they are the same scalars and points the oracle holds
+fn assert_round_trips(encoding: ByteEncoding, encoded: &Value, event: &Event) {
+    let nonce = Scalar::from_slice(&decode(encoding, &encoded["nonce"])).unwrap();
+    assert_eq!(nonce, event.nonce);
+    let attestation = MaybeScalar::from_slice(&decode(encoding, &encoded["attestation"])).unwrap();
+    assert_eq!(Some(attestation), event.attestation);
+    if let Some(points) = encoded["event_announcement"]["locking_points"].as_array() {
+        let points: Vec<MaybePoint> = points
+            .iter()
+            .map(|point| MaybePoint::from_slice(&decode(encoding, point)).unwrap())
+            .collect();
+        assert_eq!(points, event.event_announcement.locking_points);
+    }
+}
+
+#[tokio::test]
+async fn event_scalars_round_trip_in_each_encoding() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_signed_event(&test_app).await;
+
+    let (status, default) = get(&test_app, format!("/oracle/events/{}", event.id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, hex) = get(
+        &test_app,
+        format!("/oracle/events/{}?encoding=hex", event.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(hex, default);
+    assert_round_trips(ByteEncoding::Hex, &hex, &event);
+
+    let (status, base64) = get(
+        &test_app,
+        format!("/oracle/events/{}?encoding=base64", event.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(base64["nonce"], hex["nonce"]);
+    assert_eq!(base64["id"], hex["id"]);
+    assert_round_trips(ByteEncoding::Base64, &base64, &event);
+}
+
+#[tokio::test]
+async fn listed_and_batched_events_use_requested_encoding() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_signed_event(&test_app).await;
+
+    let (status, listed) = get(&test_app, String::from("/oracle/events?encoding=base64")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_round_trips(ByteEncoding::Base64, &listed[0], &event);
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/oracle/events/batch?encoding=base64")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "ids": [event.id] }).to_string()))
+        .unwrap();
+    let (status, batch) = send(&test_app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_round_trips(ByteEncoding::Base64, &batch[0]["event"], &event);
+}
+
+#[tokio::test]
+async fn unknown_encoding_is_rejected() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = create_signed_event(&test_app).await;
+
+    let (status, _) = get(
+        &test_app,
+        format!("/oracle/events/{}?encoding=base58", event.id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
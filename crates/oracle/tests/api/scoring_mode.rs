@@ -0,0 +1,336 @@
+use crate::helpers::{spawn_app, spawn_app_with_clock, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::Request,
+};
+use hyper::{header, Method};
+use nostr_sdk::Keys;
+use oracle::{
+    oracle::Error, AddEventEntry, CreateEvent, Event, EventStatus, FixedClock, Forecast,
+    Observation, PrecipUnit, ScoringField, ScoringMode, TemperatureUnit, ValueOptions,
+    WeatherChoices, WeatherPredictions,
+};
+use serde_json::from_slice;
+use std::{cmp, sync::Arc};
+use time::{format_description::well_known::Rfc3339, macros::datetime, Duration, OffsetDateTime};
+use tokio::time::sleep;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn new_event(scoring_mode: ScoringMode) -> CreateEvent {
+    CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: OffsetDateTime::parse("2024-08-12T00:00:00+00:00", &Rfc3339)
+            .unwrap(),
+        end_observation_date: OffsetDateTime::parse("2024-08-13T00:00:00+00:00", &Rfc3339).unwrap(),
+        signing_date: OffsetDateTime::parse("2024-08-13T03:00:00+00:00", &Rfc3339).unwrap(),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 2,
+        scoring_fields: vec![ScoringField::TempHigh, ScoringField::TempLow],
+        par_tolerances: Default::default(),
+        scoring_mode,
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    }
+}
+
+fn picks(event_id: Uuid, temp_high: ValueOptions, temp_low: ValueOptions) -> AddEventEntry {
+    AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_high: Some(temp_high),
+            temp_low: Some(temp_low),
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: Default::default(),
+        }],
+    }
+}
+
+fn predictions(event_id: Uuid, temp_high: f64, temp_low: f64) -> AddEventEntry {
+    AddEventEntry {
+        id: Uuid::now_v7(),
+        event_id,
+        expected_observations: vec![WeatherChoices {
+            stations: String::from("PFNO"),
+            temp_high: None,
+            temp_low: None,
+            wind_speed: None,
+            wind_direction: None,
+            rain_amt: None,
+            snow_amt: None,
+            humidity: None,
+            predictions: WeatherPredictions::from([
+                (ScoringField::TempHigh, temp_high),
+                (ScoringField::TempLow, temp_low),
+            ]),
+        }],
+    }
+}
+
+async fn run_etl(test_app: &TestApp) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(String::from("/oracle/update"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert!(response.status().is_success());
+
+    // wait for etl to run in background
+    sleep(std::time::Duration::from_secs(1)).await;
+}
+
+/// Entry ids ordered from first to last place, along with their base scores
+async fn ranking(test_app: &TestApp, event_id: Uuid) -> Vec<(Uuid, i64)> {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(format!("/oracle/events/{}?include=entries", event_id))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert!(response.status().is_success());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let event: Event = from_slice(&body).unwrap();
+    assert_eq!(event.status, EventStatus::Signed);
+
+    let mut entries = event.entries;
+    entries.sort_by_key(|entry| cmp::Reverse(entry.score));
+    entries
+        .into_iter()
+        .map(|entry| (entry.id, entry.base_score.unwrap_or_default()))
+        .collect()
+}
+
+#[tokio::test]
+async fn ranks_entries_under_each_scoring_mode() {
+    let keys = Keys::generate();
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .returning(|_, _| Ok(mock_forecast_data()));
+    weather_data
+        .expect_observation_data()
+        .returning(|_, _| Ok(mock_observation_data()));
+    // entries go in the day before the events start
+    let clock = Arc::new(FixedClock::new(datetime!(2024-08-11 00:00:00 UTC)));
+    let test_app = spawn_app_with_clock(Arc::new(weather_data), clock.clone()).await;
+
+    // forecast high 70 low 50, observed high 75 low 50
+    let ternary = test_app
+        .oracle
+        .create_event(keys.public_key, new_event(ScoringMode::Ternary), true)
+        .await
+        .unwrap();
+    let both_par = picks(ternary.id, ValueOptions::Par, ValueOptions::Par);
+    let over_and_par = picks(ternary.id, ValueOptions::Over, ValueOptions::Par);
+    let both_under = picks(ternary.id, ValueOptions::Under, ValueOptions::Under);
+    test_app
+        .oracle
+        .add_event_entries(
+            keys.public_key,
+            ternary.id,
+            vec![both_par.clone(), over_and_par.clone(), both_under.clone()],
+        )
+        .await
+        .unwrap();
+
+    let proximity = test_app
+        .oracle
+        .create_event(keys.public_key, new_event(ScoringMode::Proximity), true)
+        .await
+        .unwrap();
+    // predicting the forecast is 5 away, the near miss only 1.5 away
+    let forecasted = predictions(proximity.id, 70.0, 50.0);
+    let near_miss = predictions(proximity.id, 76.0, 49.5);
+    let far_off = predictions(proximity.id, 60.0, 40.0);
+    test_app
+        .oracle
+        .add_event_entries(
+            keys.public_key,
+            proximity.id,
+            vec![forecasted.clone(), near_miss.clone(), far_off.clone()],
+        )
+        .await
+        .unwrap();
+
+    clock.set(proximity.signing_date + Duration::hours(1));
+    run_etl(&test_app).await;
+
+    assert_eq!(
+        ranking(&test_app, ternary.id).await,
+        vec![(over_and_par.id, 30), (both_par.id, 20), (both_under.id, 0)]
+    );
+    assert_eq!(
+        ranking(&test_app, proximity.id).await,
+        vec![
+            (near_miss.id, -150),
+            (forecasted.id, -500),
+            (far_off.id, -2500)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn proximity_entries_need_every_prediction() {
+    let keys = Keys::generate();
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event(ScoringMode::Proximity), true)
+        .await
+        .unwrap();
+
+    let mut missing_low = predictions(event.id, 70.0, 50.0);
+    missing_low.expected_observations[0]
+        .predictions
+        .remove(&ScoringField::TempLow);
+    let entries = vec![
+        predictions(event.id, 70.0, 50.0),
+        predictions(event.id, 71.0, 50.0),
+        missing_low,
+    ];
+    let result = test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, entries)
+        .await;
+    assert!(result.is_err());
+
+    let entries = vec![
+        predictions(event.id, 70.0, 50.0),
+        predictions(event.id, 71.0, 50.0),
+        picks(event.id, ValueOptions::Par, ValueOptions::Par),
+    ];
+    let result = test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, entries)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn proximity_entries_reject_out_of_range_predictions() {
+    let keys = Keys::generate();
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let now = OffsetDateTime::now_utc();
+    let event = CreateEvent {
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(2) + Duration::hours(3),
+        ..new_event(ScoringMode::Proximity)
+    };
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, event, true)
+        .await
+        .unwrap();
+
+    // far enough off to overflow the total score if it were ever scored
+    let entries = vec![
+        predictions(event.id, 70.0, 50.0),
+        predictions(event.id, 71.0, 50.0),
+        predictions(event.id, 1e17, 50.0),
+    ];
+    let result = test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, entries)
+        .await;
+    assert!(
+        matches!(&result, Err(Error::BadEntry(message)) if message.contains("outside")),
+        "{:?}",
+        result
+    );
+
+    let entries = vec![
+        predictions(event.id, 70.0, 50.0),
+        predictions(event.id, 71.0, 50.0),
+        predictions(event.id, 200.0, -200.0),
+    ];
+    test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, entries)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn ternary_entries_reject_predictions() {
+    let keys = Keys::generate();
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event = test_app
+        .oracle
+        .create_event(keys.public_key, new_event(ScoringMode::Ternary), true)
+        .await
+        .unwrap();
+
+    let entries = vec![
+        picks(event.id, ValueOptions::Par, ValueOptions::Par),
+        picks(event.id, ValueOptions::Over, ValueOptions::Par),
+        predictions(event.id, 70.0, 50.0),
+    ];
+    let result = test_app
+        .oracle
+        .add_event_entries(keys.public_key, event.id, entries)
+        .await;
+    assert!(result.is_err());
+}
+
+fn mock_forecast_data() -> Vec<Forecast> {
+    vec![Forecast {
+        station_id: String::from("PFNO"),
+        date: String::from("2024-08-12"),
+        start_time: String::from("2024-08-11T00:00:00+00:00"),
+        end_time: String::from("2024-08-12T00:00:00+00:00"),
+        temp_low: 50,
+        temp_high: 70,
+        wind_speed: Some(8),
+        wind_direction: None,
+        humidity_max: None,
+        humidity_min: None,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        precip_chance: None,
+        rain_amt: None,
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        dominant_precip: None,
+        bucket: None,
+    }]
+}
+
+fn mock_observation_data() -> Vec<Observation> {
+    vec![Observation {
+        station_id: String::from("PFNO"),
+        start_time: String::from("2024-08-12T00:00:00+00:00"),
+        end_time: String::from("2024-08-13T00:00:00+00:00"),
+        temp_low: 50.0,
+        temp_high: 75.0,
+        wind_speed: 11,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        wind_direction: None,
+        humidity: None,
+        rain_amt: None,
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        bucket: None,
+    }]
+}
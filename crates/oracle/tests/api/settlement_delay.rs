@@ -0,0 +1,136 @@
+use crate::helpers::{random_test_number, spawn_app, MockWeatherAccess};
+use nostr_sdk::Keys;
+use oracle::{create_folder, oracle::Oracle, CreateEvent, Database, EventStatus, ScoringField};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+fn weather_data() -> MockWeatherAccess {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .returning(|_, _| Ok(vec![]));
+    weather_data
+        .expect_observation_data()
+        .returning(|_, _| Ok(vec![]));
+    weather_data
+}
+
+async fn delayed_oracle(settlement_delay: Duration) -> Oracle {
+    create_folder("./test_data");
+    let event_data = format!("./test_data/{}/event_data", random_test_number());
+    create_folder(&event_data);
+    let db = Arc::new(Database::new(&event_data).await.unwrap());
+    Oracle::new(
+        db,
+        Arc::new(weather_data()),
+        &String::from("./oracle_private_key.pem"),
+    )
+    .await
+    .unwrap()
+    .with_settlement_delay(settlement_delay)
+}
+
+/// Observations ended 2 hours ago and the signing date passed an hour ago
+fn ended_event(settlement_delay_hours: Option<u32>) -> CreateEvent {
+    let now = OffsetDateTime::now_utc();
+    CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: now - Duration::days(1),
+        end_observation_date: now - Duration::hours(2),
+        signing_date: now - Duration::hours(1),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(2),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours,
+        entry_cutoff: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn event_is_not_signed_until_its_settlement_delay_passes() {
+    let keys = Keys::generate();
+    let test_app = spawn_app(Arc::new(weather_data())).await;
+
+    let settling = test_app
+        .oracle
+        .create_event(keys.public_key, ended_event(Some(3)), true)
+        .await
+        .unwrap();
+    assert_eq!(settling.settlement_delay_hours, Some(3));
+    let settled = test_app
+        .oracle
+        .create_event(keys.public_key, ended_event(Some(1)), true)
+        .await
+        .unwrap();
+
+    test_app.oracle.etl_data(1).await.unwrap();
+
+    let event = test_app.oracle.get_event(&settling.id).await.unwrap();
+    assert_eq!(event.status, EventStatus::Completed);
+    assert!(event.attestation.is_none());
+    assert_eq!(event.settlement_delay_hours, Some(3));
+
+    let event = test_app.oracle.get_event(&settled.id).await.unwrap();
+    assert_eq!(event.status, EventStatus::Signed);
+    assert!(event.attestation.is_some());
+}
+
+#[tokio::test]
+async fn oracle_settlement_delay_applies_unless_event_overrides_it() {
+    let keys = Keys::generate();
+    let oracle = delayed_oracle(Duration::hours(3)).await;
+
+    let settling = oracle
+        .create_event(keys.public_key, ended_event(None), true)
+        .await
+        .unwrap();
+    let settled = oracle
+        .create_event(keys.public_key, ended_event(Some(0)), true)
+        .await
+        .unwrap();
+
+    oracle.etl_data(1).await.unwrap();
+
+    let event = oracle.get_event(&settling.id).await.unwrap();
+    assert_eq!(event.status, EventStatus::Completed);
+    assert!(event.attestation.is_none());
+
+    let event = oracle.get_event(&settled.id).await.unwrap();
+    assert_eq!(event.status, EventStatus::Signed);
+}
+
+#[tokio::test]
+async fn rejects_settlement_delay_past_event_expiry() {
+    let keys = Keys::generate();
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let now = OffsetDateTime::now_utc();
+    let event = CreateEvent {
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(2),
+        ..ended_event(Some(24))
+    };
+
+    let result = test_app
+        .oracle
+        .create_event(keys.public_key, event, true)
+        .await;
+    assert!(result.is_err());
+
+    let delay_oracle = delayed_oracle(Duration::hours(30)).await;
+    let event = CreateEvent {
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(2),
+        ..ended_event(None)
+    };
+    let result = delay_oracle
+        .create_event(keys.public_key, event, true)
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,27 @@
+use oracle::parse_sql_timestamp;
+use time::{macros::datetime, UtcOffset};
+
+#[test]
+fn non_utc_offsets_are_normalized_to_utc() {
+    let parsed = parse_sql_timestamp("2024-08-11 00:27:39.013046-04").unwrap();
+    assert_eq!(parsed, datetime!(2024-08-11 04:27:39.013046 UTC));
+    assert_eq!(parsed.offset(), UtcOffset::UTC);
+
+    let parsed = parse_sql_timestamp("2024-08-11 10:27:39+05:30").unwrap();
+    assert_eq!(parsed, datetime!(2024-08-11 04:57:39 UTC));
+    assert_eq!(parsed.offset(), UtcOffset::UTC);
+
+    // Crossing midnight moves the date too
+    let parsed = parse_sql_timestamp("2024-08-11 22:00:00-04").unwrap();
+    assert_eq!(parsed.date(), time::macros::date!(2024 - 08 - 12));
+    assert_eq!(parsed.hour(), 2);
+}
+
+#[test]
+fn utc_timestamps_are_unchanged() {
+    assert_eq!(
+        parse_sql_timestamp("2024-08-11 04:27:39+00").unwrap(),
+        datetime!(2024-08-11 04:27:39 UTC)
+    );
+    assert!(parse_sql_timestamp("2024-08-11T04:27:39Z").is_err());
+}
@@ -0,0 +1,142 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use oracle::{Station, StationAliases};
+use serde_json::{from_slice, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn station(station_id: &str, iata_id: &str) -> Station {
+    Station {
+        station_id: station_id.to_string(),
+        station_name: format!("{} station", station_id),
+        state: String::from("NY"),
+        iata_id: iata_id.to_string(),
+        elevation_m: None,
+        latitude: 0.0,
+        longitude: 0.0,
+    }
+}
+
+fn airports() -> Vec<Station> {
+    vec![
+        station("KJFK", "JFK"),
+        // NOAA ids that aren't the ICAO code still answer to it
+        station("NYC01", "LGA"),
+        station("PANC", "ANC"),
+    ]
+}
+
+fn aliases() -> StationAliases {
+    StationAliases::new(&airports())
+}
+
+#[test]
+fn resolves_station_ids() {
+    let aliases = aliases();
+    assert_eq!(aliases.resolve("KJFK").unwrap(), "KJFK");
+    assert_eq!(aliases.resolve("nyc01").unwrap(), "NYC01");
+    assert_eq!(aliases.resolve(" PANC ").unwrap(), "PANC");
+}
+
+#[test]
+fn resolves_iata_codes() {
+    let aliases = aliases();
+    assert_eq!(aliases.resolve("JFK").unwrap(), "KJFK");
+    assert_eq!(aliases.resolve("lga").unwrap(), "NYC01");
+    assert_eq!(aliases.resolve("ANC").unwrap(), "PANC");
+}
+
+#[test]
+fn resolves_icao_codes() {
+    let aliases = aliases();
+    assert_eq!(aliases.resolve("KLGA").unwrap(), "NYC01");
+    assert_eq!(aliases.resolve("kjfk").unwrap(), "KJFK");
+}
+
+#[test]
+fn rejects_unknown_aliases() {
+    let aliases = aliases();
+    let err = aliases.resolve("XYZ").unwrap_err();
+    assert!(
+        err.to_string().contains("unknown station ids: XYZ"),
+        "{}",
+        err
+    );
+
+    let ids = vec![
+        String::from("JFK"),
+        String::from("XYZ"),
+        String::from("KLGA"),
+        String::from("QQQQ"),
+    ];
+    let err = aliases.resolve_all(&ids).unwrap_err();
+    assert!(err.to_string().contains("XYZ, QQQQ"), "{}", err);
+}
+
+#[test]
+fn rejects_aliases_shared_by_stations() {
+    let aliases = StationAliases::new(&[station("KJFK", "JFK"), station("NYC02", "JFK")]);
+    let err = aliases.resolve("JFK").unwrap_err();
+    assert!(err.to_string().contains("more than one station"), "{}", err);
+    // the station ids themselves stay unambiguous
+    assert_eq!(aliases.resolve("NYC02").unwrap(), "NYC02");
+}
+
+#[tokio::test]
+async fn query_filters_resolve_aliases() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_stations()
+        .times(2)
+        .returning(|| Ok(airports()));
+    weather_data
+        .expect_forecasts_data()
+        .withf(|_, station_ids| *station_ids == ["KJFK", "NYC01", "PANC"])
+        .times(1)
+        .returning(|_, _| Ok(vec![]));
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/stations/forecasts?station_ids=JFK,KLGA,panc")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/stations/forecasts?station_ids=JFK,XYZ")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Value = from_slice(&body).unwrap();
+    let message = res["error"].as_str().unwrap();
+    assert!(message.contains("unknown station ids: XYZ"), "{}", message);
+}
+
+#[tokio::test]
+async fn station_id_filters_skip_the_station_list() {
+    // no stations expected, plain station ids must not read the station list
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .withf(|_, station_ids| *station_ids == ["PFNO"])
+        .times(1)
+        .returning(|_, _| Ok(vec![]));
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/stations/forecasts?station_ids=pfno")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
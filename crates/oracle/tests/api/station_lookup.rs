@@ -0,0 +1,101 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request, StatusCode},
+    response::Response,
+};
+use hyper::Method;
+use oracle::{Station, MAX_STATION_LOOKUP_IDS};
+use serde_json::{from_slice, json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn station(station_id: &str, iata_id: &str) -> Station {
+    Station {
+        station_id: station_id.to_string(),
+        station_name: format!("{} station", station_id),
+        state: String::from("NY"),
+        iata_id: iata_id.to_string(),
+        elevation_m: Some(4.0),
+        latitude: 40.6,
+        longitude: -73.8,
+    }
+}
+
+async fn lookup(app: &axum::Router, body: Value) -> Response {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/stations/lookup")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn looks_up_known_and_unknown_stations() {
+    let mut weather_data = MockWeatherAccess::new();
+    // the second lookup is served from the cached station list
+    weather_data
+        .expect_stations()
+        .times(1)
+        .returning(|| Ok(vec![station("KJFK", "JFK"), station("KLGA", "LGA")]));
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    let response = lookup(&test_app.app, json!({ "ids": ["KLGA", "KZZZ", "JFK"] })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Value = from_slice(&body).unwrap();
+    assert_eq!(
+        res,
+        json!([
+            {
+                "status": "found",
+                "station": {
+                    "station_id": "KLGA",
+                    "station_name": "KLGA station",
+                    "state": "NY",
+                    "iata_id": "LGA",
+                    "elevation_m": 4.0,
+                    "latitude": 40.6,
+                    "longitude": -73.8
+                }
+            },
+            { "status": "not_found", "id": "KZZZ" },
+            {
+                "status": "found",
+                "station": {
+                    "station_id": "KJFK",
+                    "station_name": "KJFK station",
+                    "state": "NY",
+                    "iata_id": "JFK",
+                    "elevation_m": 4.0,
+                    "latitude": 40.6,
+                    "longitude": -73.8
+                }
+            }
+        ])
+    );
+
+    let response = lookup(&test_app.app, json!({ "ids": ["KJFK"] })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Value = from_slice(&body).unwrap();
+    assert_eq!(res[0]["station"]["station_id"], "KJFK");
+}
+
+#[tokio::test]
+async fn rejects_lookups_over_the_cap() {
+    // no stations expected, the cap is checked before the station list is read
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let ids: Vec<String> = (0..=MAX_STATION_LOOKUP_IDS)
+        .map(|i| format!("K{:03}", i))
+        .collect();
+
+    let response = lookup(&test_app.app, json!({ "ids": ids })).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: Value = from_slice(&body).unwrap();
+    let message = res["error"].as_str().unwrap();
+    assert!(message.contains("max per lookup is 500"), "{}", message);
+}
@@ -4,7 +4,7 @@ use axum::{
     http::Request,
 };
 use hyper::{header, Method};
-use oracle::{Forecast, Observation, Station, TemperatureUnit};
+use oracle::{Forecast, Observation, PrecipUnit, Station, TemperatureUnit};
 use std::sync::Arc;
 use tower::ServiceExt;
 
@@ -257,6 +257,8 @@ fn mock_observation_data() -> Vec<Observation> {
         rain_amt: None,
         snow_amt: None,
         ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        bucket: None,
     }]
 }
 
@@ -278,6 +280,9 @@ fn mock_forecast_data() -> Vec<Forecast> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            dominant_precip: None,
+            bucket: None,
         },
         Forecast {
             station_id: String::from("KORD"),
@@ -295,6 +300,9 @@ fn mock_forecast_data() -> Vec<Forecast> {
             rain_amt: None,
             snow_amt: None,
             ice_amt: None,
+            precip_unit_code: PrecipUnit::Inches.to_string(),
+            dominant_precip: None,
+            bucket: None,
         },
     ]
 }
@@ -0,0 +1,82 @@
+use crate::helpers::{
+    parquet_bytes, random_test_number, spawn_app_with_file_access, MockWeatherAccess,
+    FORECAST_MESSAGE_TYPE,
+};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::{header::CONTENT_TYPE, Method};
+use noaa_oracle_core::{sha256_hex, CONTENT_SHA256_HEADER};
+use oracle::FileAccess;
+use std::{fs, path::Path, sync::Arc};
+use tower::ServiceExt;
+
+const FORECAST: &str = "forecasts_2024-08-12T01:00:00Z.parquet";
+
+fn upload_request(body: &[u8], checksum: &str) -> Request<Body> {
+    let boundary = "checksum-boundary";
+    let mut multipart = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{FORECAST}\"\r\n\
+         Content-Type: application/parquet\r\n\r\n"
+    )
+    .into_bytes();
+    multipart.extend_from_slice(body);
+    multipart.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    Request::builder()
+        .method(Method::POST)
+        .uri(format!("/file/{}", FORECAST))
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .header(CONTENT_SHA256_HEADER, checksum)
+        .body(Body::from(multipart))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn upload_with_matching_checksum_is_stored() {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    let test_app = spawn_app_with_file_access(
+        Arc::new(MockWeatherAccess::new()),
+        Arc::new(FileAccess::new(data_dir.clone())),
+    )
+    .await;
+
+    let parquet = parquet_bytes(FORECAST_MESSAGE_TYPE);
+    let response = test_app
+        .app
+        .oneshot(upload_request(&parquet, &sha256_hex(&parquet)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let stored = format!("{}/2024-08-12/{}", data_dir, FORECAST);
+    assert_eq!(fs::read(&stored).unwrap(), parquet);
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[tokio::test]
+async fn corrupted_upload_is_rejected() {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    let test_app = spawn_app_with_file_access(
+        Arc::new(MockWeatherAccess::new()),
+        Arc::new(FileAccess::new(data_dir.clone())),
+    )
+    .await;
+
+    // The bytes were cut off in transit, the checksum still covers the whole file
+    let parquet = parquet_bytes(FORECAST_MESSAGE_TYPE);
+    let truncated = &parquet[..parquet.len() / 2];
+    let response = test_app
+        .app
+        .oneshot(upload_request(truncated, &sha256_hex(&parquet)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let message = String::from_utf8(body.to_vec()).unwrap();
+    assert!(message.contains("Checksum mismatch"), "{}", message);
+    assert!(!Path::new(&data_dir).exists());
+}
@@ -0,0 +1,127 @@
+use crate::helpers::{
+    parquet_bytes, random_test_number, spawn_app_with_file_access, MockWeatherAccess, TestApp,
+    FORECAST_MESSAGE_TYPE,
+};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::{header::CONTENT_TYPE, Method};
+use oracle::FileAccess;
+use std::{fs, path::Path, sync::Arc};
+use tower::ServiceExt;
+
+const FORECAST: &str = "forecasts_2024-08-12T01:00:00Z.parquet";
+const OBSERVATION: &str = "observations_2024-08-12T01:00:00Z.parquet";
+
+/// Observation columns from before state, iata_id and the precip fields were added
+const OLD_OBSERVATION_MESSAGE_TYPE: &str = "message observation {
+    required binary station_id (UTF8);
+    required binary station_name (UTF8);
+    required double latitude;
+    required double longitude;
+    required binary generated_at (UTF8);
+    optional double temperature_value;
+    required binary temperature_unit_code (UTF8);
+    optional int64 wind_direction;
+    required binary wind_direction_unit_code (UTF8);
+    optional int64 wind_speed;
+    required binary wind_speed_unit_code (UTF8);
+    optional double dewpoint_value;
+    required binary dewpoint_unit_code (UTF8);
+}";
+
+async fn spawn(data_dir: &str) -> TestApp {
+    spawn_app_with_file_access(
+        Arc::new(MockWeatherAccess::new()),
+        Arc::new(FileAccess::new(data_dir.to_owned())),
+    )
+    .await
+}
+
+async fn upload(test_app: &TestApp, file_name: &str, file: &[u8]) -> (StatusCode, String) {
+    let boundary = "schema-boundary";
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\n\
+         Content-Type: application/parquet\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(file);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(format!("/file/{}", file_name))
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .body(Body::from(body))
+        .unwrap();
+    let response = test_app.app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn uploads_matching_the_schema_are_stored() {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    let test_app = spawn(&data_dir).await;
+
+    let (status, _) = upload(&test_app, FORECAST, &parquet_bytes(FORECAST_MESSAGE_TYPE)).await;
+    assert_eq!(status, StatusCode::OK);
+    // Files written before the newer columns existed are still valid
+    let (status, _) = upload(
+        &test_app,
+        OBSERVATION,
+        &parquet_bytes(OLD_OBSERVATION_MESSAGE_TYPE),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(Path::new(&format!("{}/2024-08-12/{}", data_dir, FORECAST)).exists());
+    assert!(Path::new(&format!("{}/2024-08-12/{}", data_dir, OBSERVATION)).exists());
+
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[tokio::test]
+async fn uploads_not_matching_the_schema_are_rejected() {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    let test_app = spawn(&data_dir).await;
+
+    let wrong_type =
+        FORECAST_MESSAGE_TYPE.replace("optional int64 max_temp;", "optional double max_temp;");
+    let (status, message) = upload(&test_app, FORECAST, &parquet_bytes(&wrong_type)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        message.contains("`max_temp` has type DOUBLE, expected INT64"),
+        "{}",
+        message
+    );
+
+    let missing_column =
+        OLD_OBSERVATION_MESSAGE_TYPE.replace("optional double dewpoint_value;", "");
+    let (status, message) = upload(&test_app, OBSERVATION, &parquet_bytes(&missing_column)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        message.contains("Missing column `dewpoint_value`"),
+        "{}",
+        message
+    );
+
+    // A forecast uploaded under an observation name doesn't match either
+    let (status, _) = upload(
+        &test_app,
+        OBSERVATION,
+        &parquet_bytes(FORECAST_MESSAGE_TYPE),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, message) = upload(&test_app, FORECAST, b"not parquet").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(message.contains("not readable parquet"), "{}", message);
+
+    assert!(!Path::new(&data_dir).exists());
+}
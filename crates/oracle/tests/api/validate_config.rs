@@ -0,0 +1,140 @@
+use crate::helpers::random_test_number;
+use oracle::{create_folder, validate_config, Cli};
+use std::fs;
+
+/// A config whose dirs and key all live under a fresh test directory
+fn test_cli() -> (String, Cli) {
+    let root = format!("./test_data/validate_{}", random_test_number());
+    create_folder(&root);
+    let cli = Cli {
+        weather_dir: Some(format!("{}/weather_data", root)),
+        event_db: Some(format!("{}/event_data", root)),
+        oracle_private_key: Some(format!("{}/oracle_private_key.pem", root)),
+        ..Default::default()
+    };
+    (root, cli)
+}
+
+#[tokio::test]
+async fn valid_config_passes() {
+    let (root, cli) = test_cli();
+
+    validate_config(&cli).await.unwrap();
+    assert!(fs::metadata(format!("{}/weather_data", root))
+        .unwrap()
+        .is_dir());
+    // A missing key is fine, startup generates it, validation only checks it could
+    assert!(fs::metadata(format!("{}/oracle_private_key.pem", root)).is_err());
+}
+
+#[tokio::test]
+async fn unwritable_weather_dir_is_reported() {
+    let (root, mut cli) = test_cli();
+    let weather_dir = format!("{}/weather_file", root);
+    fs::write(&weather_dir, "not a dir").unwrap();
+    cli.weather_dir = Some(weather_dir.clone());
+
+    let err = validate_config(&cli).await.unwrap_err();
+    assert_eq!(
+        err.problems,
+        vec![format!(
+            "weather_dir: {} is not writable: exists but is not a directory",
+            weather_dir
+        )]
+    );
+}
+
+#[tokio::test]
+async fn unwritable_event_dir_is_reported() {
+    let (root, mut cli) = test_cli();
+    let event_dir = format!("{}/event_file", root);
+    fs::write(&event_dir, "not a dir").unwrap();
+    cli.database_url = Some(format!("sqlite://{}", event_dir));
+
+    let err = validate_config(&cli).await.unwrap_err();
+    assert_eq!(
+        err.problems,
+        vec![format!(
+            "event_db: {} is not writable: exists but is not a directory",
+            event_dir
+        )]
+    );
+}
+
+#[tokio::test]
+async fn unparseable_private_key_is_reported() {
+    let (root, mut cli) = test_cli();
+    let key_path = format!("{}/garbage.pem", root);
+    fs::write(&key_path, "not a pem file").unwrap();
+    cli.oracle_private_key = Some(key_path.clone());
+
+    let err = validate_config(&cli).await.unwrap_err();
+    assert_eq!(err.problems.len(), 1);
+    assert!(err.problems[0].starts_with(&format!(
+        "oracle_private_key: can't read key {}: ",
+        key_path
+    )));
+}
+
+#[tokio::test]
+async fn private_key_without_pem_extension_is_reported() {
+    let (root, mut cli) = test_cli();
+    cli.oracle_private_key = Some(format!("{}/oracle_private_key.txt", root));
+
+    let err = validate_config(&cli).await.unwrap_err();
+    assert_eq!(
+        err.problems,
+        vec!["oracle_private_key: not a '.pem' file extension"]
+    );
+}
+
+#[tokio::test]
+async fn private_key_in_missing_dir_is_reported() {
+    let (root, mut cli) = test_cli();
+    let key_path = format!("{}/secrets/oracle_private_key.pem", root);
+    cli.oracle_private_key = Some(key_path.clone());
+
+    let err = validate_config(&cli).await.unwrap_err();
+    assert_eq!(
+        err.problems,
+        vec![format!(
+            "oracle_private_key: {} doesn't exist and its directory {}/secrets is missing",
+            key_path, root
+        )]
+    );
+}
+
+#[tokio::test]
+async fn unreachable_s3_bucket_is_reported() {
+    let (_, mut cli) = test_cli();
+    cli.s3_bucket = Some(String::from("noaa-weather"));
+    // Nothing listens on port 1
+    cli.s3_endpoint = Some(String::from("http://127.0.0.1:1"));
+
+    let err = validate_config(&cli).await.unwrap_err();
+    assert_eq!(err.problems.len(), 1);
+    // Without a region the client can't be built, with one the bucket doesn't answer
+    assert!(err.problems[0].starts_with("s3_bucket: "));
+}
+
+#[tokio::test]
+async fn every_problem_is_reported_together() {
+    let (root, mut cli) = test_cli();
+    let weather_dir = format!("{}/weather_file", root);
+    fs::write(&weather_dir, "not a dir").unwrap();
+    cli.weather_dir = Some(weather_dir);
+    cli.layout = Some(String::from("flat"));
+    cli.oracle_private_key = Some(format!("{}/oracle_private_key.txt", root));
+
+    let err = validate_config(&cli).await.unwrap_err();
+    let settings: Vec<&str> = err
+        .problems
+        .iter()
+        .map(|problem| problem.split(':').next().unwrap())
+        .collect();
+    assert_eq!(
+        settings,
+        vec!["weather_dir", "layout", "oracle_private_key"]
+    );
+    assert!(err.to_string().starts_with("invalid config, 3 problem(s):"));
+}
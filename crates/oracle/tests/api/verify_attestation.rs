@@ -0,0 +1,179 @@
+use crate::helpers::{spawn_app, MockWeatherAccess, TestApp};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use dlctix::{
+    attestation_secret,
+    secp::{MaybeScalar, Scalar},
+};
+use hyper::{header, Method};
+use nostr_sdk::Keys;
+use oracle::{
+    oracle::get_winning_bytes, AttestationVerification, CreateEvent, EventStore, ScoringField,
+};
+use serde_json::{from_slice, json, Value};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Creates an event and attests `attestation`, the oracle's real signature when `None`
+async fn create_signed_event(test_app: &TestApp, attestation: Option<MaybeScalar>) -> Uuid {
+    let now = OffsetDateTime::now_utc();
+    let new_event = CreateEvent {
+        id: Uuid::now_v7(),
+        start_observation_date: now + Duration::days(1),
+        end_observation_date: now + Duration::days(2),
+        signing_date: now + Duration::days(3),
+        locations: vec![String::from("PFNO")],
+        total_allowed_entries: 3,
+        number_of_values_per_entry: Some(3),
+        number_of_places_win: 1,
+        scoring_fields: ScoringField::defaults(),
+        par_tolerances: Default::default(),
+        scoring_mode: Default::default(),
+        settlement_delay_hours: Default::default(),
+        entry_cutoff: Default::default(),
+    };
+    let event_id = test_app
+        .oracle
+        .create_event(Keys::generate().public_key, new_event, true)
+        .await
+        .unwrap()
+        .id;
+
+    // attest that the second entry won
+    let mut event = test_app
+        .db
+        .get_events_to_sign(vec![event_id], test_app.oracle.now())
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    event.attestation = Some(attestation.unwrap_or_else(|| {
+        attestation_secret(
+            test_app.oracle.raw_private_key(),
+            event.nonce,
+            get_winning_bytes(vec![1], event.outcome_message_version),
+        )
+    }));
+    test_app
+        .db
+        .update_event_attestation(&event, test_app.oracle.now())
+        .await
+        .unwrap();
+    event_id
+}
+
+async fn verify(test_app: &TestApp, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/verify")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = test_app
+        .app
+        .clone()
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn artifacts(test_app: &TestApp, event_id: &Uuid, winners: Vec<usize>) -> Value {
+    let announcement = test_app
+        .oracle
+        .get_event_announcement(event_id)
+        .await
+        .unwrap();
+    let attestation = test_app
+        .oracle
+        .get_event_attestation(event_id)
+        .await
+        .unwrap();
+    json!({
+        "announcement": announcement,
+        "attestation": attestation,
+        "winners": winners,
+    })
+}
+
+#[tokio::test]
+async fn can_verify_signed_event() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event_id = create_signed_event(&test_app, None).await;
+
+    let (status, body) = verify(&test_app, json!({ "event_id": event_id })).await;
+    assert_eq!(status, StatusCode::OK);
+    let verification: AttestationVerification = serde_json::from_value(body).unwrap();
+    assert_eq!(
+        verification,
+        AttestationVerification {
+            valid: true,
+            outcome_index: Some(1),
+        }
+    );
+
+    let (status, _) = verify(&test_app, json!({ "event_id": Uuid::now_v7() })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn tampered_event_attestation_is_invalid() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event_id = create_signed_event(&test_app, Some(MaybeScalar::Valid(Scalar::one()))).await;
+
+    let (status, body) = verify(&test_app, json!({ "event_id": event_id })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["valid"], false);
+    assert_eq!(body["outcome_index"], Value::Null);
+}
+
+#[tokio::test]
+async fn can_verify_announcement_and_attestation_artifacts() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event_id = create_signed_event(&test_app, None).await;
+
+    let (status, body) = verify(&test_app, artifacts(&test_app, &event_id, vec![1]).await).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["valid"], true);
+    assert_eq!(body["outcome_index"], 1);
+
+    // claiming a different winner than the one attested
+    let (status, body) = verify(&test_app, artifacts(&test_app, &event_id, vec![0]).await).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["valid"], false);
+
+    let mut tampered = artifacts(&test_app, &event_id, vec![1]).await;
+    tampered["attestation"]["attestation"] =
+        json!(hex::encode(MaybeScalar::Valid(Scalar::one()).serialize()));
+    let (status, body) = verify(&test_app, tampered).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["valid"], false);
+
+    // a locking point swapped in for the attested one no longer matches the oracle's key
+    let mut tampered = artifacts(&test_app, &event_id, vec![1]).await;
+    tampered["attestation"]["attestation"] =
+        json!(hex::encode(MaybeScalar::Valid(Scalar::one()).serialize()));
+    tampered["announcement"]["locking_points"][1] =
+        json!(hex::encode(Scalar::one().base_point_mul().serialize()));
+    let (status, body) = verify(&test_app, tampered).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["valid"], false);
+    assert_eq!(body["outcome_index"], 1);
+}
+
+#[tokio::test]
+async fn undecodable_artifacts_are_rejected() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+    let event_id = create_signed_event(&test_app, None).await;
+
+    let mut malformed = artifacts(&test_app, &event_id, vec![1]).await;
+    malformed["announcement"]["nonce_point"] = json!("not hex");
+    let (status, _) = verify(&test_app, malformed).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,38 @@
+use crate::helpers::{spawn_app, MockWeatherAccess};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use oracle::VersionInfo;
+use serde_json::from_slice;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn reports_migration_version_after_startup() {
+    let test_app = spawn_app(Arc::new(MockWeatherAccess::new())).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(String::from("/version"))
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app
+        .app
+        .oneshot(request)
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let res: VersionInfo = from_slice(&body).unwrap();
+
+    let applied: i64 = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+        .fetch_one(test_app.db.pool())
+        .await
+        .unwrap();
+    assert_eq!(res.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(res.migrations.current_version, Some(applied));
+    assert_eq!(res.migrations.latest_version, Some(applied));
+    assert!(res.migrations.up_to_date);
+}
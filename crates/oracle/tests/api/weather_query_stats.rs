@@ -0,0 +1,87 @@
+use crate::helpers::{random_test_number, spawn_app, MockWeatherAccess};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use hyper::Method;
+use oracle::{
+    create_folder, weather_data::WeatherAccess, FileAccess, Forecast, PrecipUnit, TemperatureUnit,
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn mock_forecast(station_id: &str) -> Forecast {
+    Forecast {
+        station_id: station_id.to_string(),
+        date: String::from("2024-08-12"),
+        start_time: String::from("2024-08-11T00:00:00+00:00"),
+        end_time: String::from("2024-08-12T00:00:00+00:00"),
+        temp_low: 9,
+        temp_high: 35,
+        wind_speed: Some(8),
+        wind_direction: None,
+        humidity_max: None,
+        humidity_min: None,
+        temp_unit_code: TemperatureUnit::Fahrenheit.to_string(),
+        precip_chance: None,
+        rain_amt: None,
+        snow_amt: None,
+        ice_amt: None,
+        precip_unit_code: PrecipUnit::Inches.to_string(),
+        dominant_precip: None,
+        bucket: None,
+    }
+}
+
+fn header_value(response: &axum::response::Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn forecast_query_returns_stats_headers() {
+    let mut weather_data = MockWeatherAccess::new();
+    weather_data
+        .expect_forecasts_data()
+        .times(1)
+        .returning(|_, _| Ok(vec![mock_forecast("PFNO"), mock_forecast("KSAW")]));
+    let test_app = spawn_app(Arc::new(weather_data)).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/stations/forecasts?station_ids=PFNO,KSAW")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header_value(&response, "x-rows").as_deref(), Some("2"));
+    assert!(header_value(&response, "x-query-ms")
+        .unwrap()
+        .parse::<u64>()
+        .is_ok());
+    // the mock doesn't read parquet files so there's no file count to report
+    assert_eq!(header_value(&response, "x-files"), None);
+}
+
+#[tokio::test]
+async fn parquet_backed_query_reports_files_scanned() {
+    let data_dir = format!("./test_data/weather_{}", random_test_number());
+    create_folder(&data_dir);
+    let weather_access = WeatherAccess::new(Arc::new(FileAccess::new(data_dir.clone()))).unwrap();
+    let test_app = spawn_app(Arc::new(weather_access)).await;
+
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri("/stations/observations?station_ids=PFNO")
+        .body(Body::empty())
+        .unwrap();
+    let response = test_app.app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header_value(&response, "x-files").as_deref(), Some("0"));
+    assert_eq!(header_value(&response, "x-rows").as_deref(), Some("0"));
+    assert!(header_value(&response, "x-query-ms").is_some());
+}
@@ -0,0 +1,173 @@
+use crate::helpers::WeatherFixture;
+use oracle::{
+    weather_data::WeatherAccess, DayTimeZone, ForecastAggregation, ForecastFields, ForecastRequest,
+    ObservationRequest, ObservationTempAggregation, ObservationWindAggregation, PrecipUnit,
+    TemperatureUnit, WeatherData, WindSpeedUnit,
+};
+use serde_json::json;
+use time::macros::datetime;
+
+/// A PFNO forecast window with 20 knots of wind and PFNO/KLWV observations with 10 m/s of
+/// wind, KLWV's from a file written without unit codes.
+fn weather_fixture() -> WeatherAccess {
+    let fixture = WeatherFixture::new();
+    let day_dir = fixture.day_dir("2024-08-12");
+    fixture.write(&format!(
+        r#"
+        COPY (
+            SELECT 'PFNO' AS station_id, '2024-08-12T00:00:00Z' AS begin_time,
+                   '2024-08-12T12:00:00Z' AS end_time, 50 AS min_temp, 70 AS max_temp,
+                   20 AS wind_speed, 'knots' AS wind_speed_unit_code,
+                   'fahrenheit' AS temperature_unit_code, '2024-08-11T12:00:00Z' AS generated_at
+        ) TO '{day_dir}/forecasts_2024-08-11T12:00:00Z.parquet' (FORMAT PARQUET);
+        COPY (
+            SELECT 'PFNO' AS station_id, '2024-08-12T07:53:00Z' AS generated_at,
+                   18.0 AS temperature_value, 10 AS wind_speed, 'm/s' AS wind_speed_unit_code,
+                   'celsius' AS temperature_unit_code
+        ) TO '{day_dir}/observations_2024-08-12T08:00:00Z.parquet' (FORMAT PARQUET);
+        COPY (
+            SELECT 'KLWV' AS station_id, '2024-08-12T07:53:00Z' AS generated_at,
+                   18.0 AS temperature_value, 10 AS wind_speed
+        ) TO '{day_dir}/observations_2024-08-12T09:00:00Z.parquet' (FORMAT PARQUET);
+        "#,
+    ));
+
+    fixture.weather_access()
+}
+
+fn forecast_request(wind_unit: Option<WindSpeedUnit>) -> ForecastRequest {
+    ForecastRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-12 12:00:00 UTC)),
+        generated_start: Some(datetime!(2024-08-11 00:00:00 UTC)),
+        generated_end: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        as_of: None,
+        station_ids: String::from("PFNO"),
+        temperature_unit: TemperatureUnit::Fahrenheit,
+        wind_unit,
+        precip_unit: PrecipUnit::default(),
+        agg: ForecastAggregation::default(),
+        fields: ForecastFields::default(),
+        granularity: None,
+    }
+}
+
+fn observation_request(station_id: &str, wind_unit: Option<WindSpeedUnit>) -> ObservationRequest {
+    ObservationRequest {
+        start: Some(datetime!(2024-08-12 00:00:00 UTC)),
+        end: Some(datetime!(2024-08-13 00:00:00 UTC)),
+        station_ids: String::from(station_id),
+        temperature_unit: TemperatureUnit::Celsius,
+        tz: DayTimeZone::Utc,
+        temp_agg: ObservationTempAggregation::default(),
+        wind_agg: ObservationWindAggregation::default(),
+        wind_unit,
+        precip_unit: PrecipUnit::default(),
+        granularity: None,
+    }
+}
+
+async fn forecast_wind(
+    weather_access: &WeatherAccess,
+    wind_unit: Option<WindSpeedUnit>,
+) -> Option<i64> {
+    let req = forecast_request(wind_unit);
+    let forecasts = weather_access
+        .forecasts_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(forecasts.len(), 1);
+    forecasts[0].wind_speed
+}
+
+async fn observed_wind(
+    weather_access: &WeatherAccess,
+    station_id: &str,
+    wind_unit: Option<WindSpeedUnit>,
+) -> (i64, i64) {
+    let req = observation_request(station_id, wind_unit);
+    let observations = weather_access
+        .observation_data(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(observations.len(), 1);
+    let daily = weather_access
+        .daily_observations(&req, req.station_ids())
+        .await
+        .unwrap();
+    assert_eq!(daily.len(), 1);
+    (observations[0].wind_speed, daily[0].wind_speed)
+}
+
+#[tokio::test]
+async fn forecast_knots_convert_to_mph() {
+    let weather_access = weather_fixture();
+    assert_eq!(forecast_wind(&weather_access, None).await, Some(20));
+    // 20 knots is 23.02 mph
+    assert_eq!(
+        forecast_wind(&weather_access, Some(WindSpeedUnit::Mph)).await,
+        Some(23)
+    );
+    assert_eq!(
+        forecast_wind(&weather_access, Some(WindSpeedUnit::Knots)).await,
+        Some(20)
+    );
+}
+
+#[tokio::test]
+async fn observation_meters_per_second_convert_to_mph() {
+    let weather_access = weather_fixture();
+    assert_eq!(observed_wind(&weather_access, "PFNO", None).await, (10, 10));
+    // 10 m/s is 22.37 mph
+    assert_eq!(
+        observed_wind(&weather_access, "PFNO", Some(WindSpeedUnit::Mph)).await,
+        (22, 22)
+    );
+}
+
+#[tokio::test]
+async fn rows_without_a_unit_code_are_knots() {
+    let weather_access = weather_fixture();
+    // 10 knots is 5.14 m/s
+    assert_eq!(
+        observed_wind(
+            &weather_access,
+            "KLWV",
+            Some(WindSpeedUnit::MetersPerSecond)
+        )
+        .await,
+        (5, 5)
+    );
+}
+
+#[test]
+fn converts_between_units() {
+    assert_eq!(WindSpeedUnit::Mph.convert(20, WindSpeedUnit::Knots), 23);
+    assert_eq!(
+        WindSpeedUnit::Mph.convert(10, WindSpeedUnit::MetersPerSecond),
+        22
+    );
+    assert_eq!(WindSpeedUnit::Knots.convert(23, WindSpeedUnit::Mph), 20);
+    assert_eq!(WindSpeedUnit::Knots.convert(-1, WindSpeedUnit::Knots), -1);
+}
+
+#[test]
+fn parses_unit_names() {
+    assert_eq!("knots".parse::<WindSpeedUnit>(), Ok(WindSpeedUnit::Knots));
+    assert_eq!("MPH".parse::<WindSpeedUnit>(), Ok(WindSpeedUnit::Mph));
+    assert_eq!(
+        "m/s".parse::<WindSpeedUnit>(),
+        Ok(WindSpeedUnit::MetersPerSecond)
+    );
+    assert_eq!(
+        "m-s".parse::<WindSpeedUnit>(),
+        Ok(WindSpeedUnit::MetersPerSecond)
+    );
+    assert!("beaufort".parse::<WindSpeedUnit>().is_err());
+
+    let req: ForecastRequest = serde_json::from_value(json!({ "station_ids": "PFNO" })).unwrap();
+    assert_eq!(req.wind_unit, None);
+    let req: ObservationRequest =
+        serde_json::from_value(json!({ "station_ids": "PFNO", "wind_unit": "mph" })).unwrap();
+    assert_eq!(req.wind_unit, Some(WindSpeedUnit::Mph));
+}